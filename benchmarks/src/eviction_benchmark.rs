@@ -9,6 +9,10 @@
 //! It can not scale up, therefore the provided query spec must be able to
 //! achieve a cache hit rate lower than the desired one (i.e have a wider
 //! gamut than needed for the desired hit rate).
+//!
+//! With `--compare-policies-capacity`, the benchmark instead compares the hit rates of every
+//! eviction policy, by replaying the same sequence of query parameters against an in-memory
+//! reader map under each of them.
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::str::FromStr;
@@ -17,11 +21,15 @@ use std::sync::atomic::{AtomicBool, AtomicU64};
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
-use clap::Parser;
-use database_utils::{DatabaseURL, QueryableConnection};
+use clap::{Parser, ValueEnum};
+use database_utils::{DatabaseConnection, DatabaseURL, QueryableConnection};
 use metrics::Unit;
 use prometheus_parse::Scrape;
+use reader_map::{EvictionQuantity, EvictionStrategy};
+use readyset_client::internal::IndexType;
 use readyset_client::metrics::recorded;
+use readyset_data::DfValue;
+use readyset_server::EvictionKind;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::UnboundedSender;
 use tracing::{debug, error, info};
@@ -72,6 +80,23 @@ pub struct EvictionBenchmark {
     /// a higher hit rate. Range 1 - 100 percent.
     #[arg(long, default_value = "100")]
     target_hit_rate: u8,
+
+    /// The eviction policy the ReadySet deployment under test was started with (its
+    /// `--eviction-policy`). This does not configure the deployment, but labels the results so
+    /// that the hit rates achieved by different policies can be compared.
+    #[arg(long)]
+    eviction_policy: Option<EvictionKind>,
+
+    /// Rather than benchmarking the deployment, compare the hit rates of every eviction policy, by
+    /// replaying the parameters the query would be executed with against an in-memory reader map
+    /// which evicts keys whenever it holds more than this many.
+    #[arg(long)]
+    compare_policies_capacity: Option<usize>,
+
+    /// The number of query executions to replay against each eviction policy with
+    /// `--compare-policies-capacity`.
+    #[arg(long, default_value = "1000000")]
+    compare_policies_requests: usize,
 }
 
 #[derive(Clone)]
@@ -102,6 +127,9 @@ impl BenchmarkControl for EvictionBenchmark {
         let mut conn = DatabaseURL::from_str(&deployment.target_conn_str)?
             .connect(None)
             .await?;
+        if let Some(capacity) = self.compare_policies_capacity {
+            return self.compare_policies(&mut conn, capacity).await;
+        }
         // For now drop the result of migrate as CREATE CACHE does not support
         // non-select queries.
         let _ = self.query.migrate(&mut conn).await;
@@ -135,6 +163,9 @@ impl BenchmarkControl for EvictionBenchmark {
         let mut labels = HashMap::new();
         labels.extend(self.query.labels());
         labels.extend(self.data_generator.labels());
+        if let Some(policy) = self.eviction_policy {
+            labels.insert("eviction_policy".to_string(), policy.to_string());
+        }
         labels
     }
 
@@ -154,6 +185,73 @@ impl BenchmarkControl for EvictionBenchmark {
     }
 }
 
+impl EvictionBenchmark {
+    /// Replay the same sequence of query parameters against a reader map under every eviction
+    /// policy, and report the hit rate each of them achieves
+    async fn compare_policies(
+        &self,
+        conn: &mut DatabaseConnection,
+        capacity: usize,
+    ) -> Result<BenchmarkResults> {
+        let prepared_statement = self.query.prepared_statement(conn).await?;
+        let (_, mut genset) = prepared_statement.query_generators();
+        let keys = (0..self.compare_policies_requests)
+            .map(|_| genset.generate_scaled(1.0))
+            .collect::<Vec<_>>();
+
+        let mut results = BenchmarkResults::new();
+        for policy in EvictionKind::value_variants() {
+            let hit_rate = simulate_hit_rate(*policy, &keys, capacity);
+            info!(%policy, "hit rate: {:.1}%", hit_rate * 100.);
+            results.push(
+                &format!("cache_hit_rate_{policy}"),
+                Unit::Percent,
+                MetricGoal::Increasing,
+                hit_rate * 100.,
+            );
+        }
+        Ok(results)
+    }
+}
+
+/// Returns the fraction of `keys` found in a reader map using the eviction policy `policy`, which
+/// is filled with every key that is missed, and evicts keys whenever it holds more than `capacity`
+/// of them
+fn simulate_hit_rate(policy: EvictionKind, keys: &[Vec<DfValue>], capacity: usize) -> f64 {
+    let strategy = match policy {
+        EvictionKind::Random => EvictionStrategy::new_random(),
+        EvictionKind::LRU => EvictionStrategy::new_lru(),
+        EvictionKind::Generational => EvictionStrategy::new_generational(),
+        EvictionKind::CostAware => EvictionStrategy::new_cost_aware(),
+    };
+    let (mut w, r) = reader_map::Options::default()
+        .with_index_type(IndexType::HashMap)
+        .with_eviction_strategy(strategy)
+        .construct::<Vec<DfValue>, ()>();
+
+    let mut len = 0;
+    let mut hits = 0;
+    for key in keys {
+        if matches!(r.get(key), Ok(Some(_))) {
+            hits += 1;
+            continue;
+        }
+        w.insert(key.clone(), ());
+        w.publish();
+        len += 1;
+        if len > capacity {
+            // Like readers over their memory limit, evict a batch of keys at a time
+            let nkeys = len - capacity * 99 / 100;
+            w.evict_keys(EvictionQuantity::Quantity(nkeys), |_, _| {
+                len -= 1;
+                0
+            });
+            w.publish();
+        }
+    }
+    hits as f64 / keys.len().max(1) as f64
+}
+
 #[derive(Debug, Clone, Default)]
 /// A batched set of results sent on an interval by the read benchmark thread.
 pub(crate) struct EvictionBenchmarkResultBatch {
@@ -372,6 +470,11 @@ impl MultithreadBenchmark for EvictionBenchmark {
         );
         let qps = hist.len() as f64 / interval.as_secs() as f64;
         let cache_hit_rate = if hit > 0. { hit / (hit + miss) } else { 0. };
+        if hit + miss > 0. {
+            benchmark_results
+                .entry("cache_hit_rate", Unit::Percent, MetricGoal::Increasing)
+                .push(cache_hit_rate * 100.);
+        }
         benchmark_histogram!(
             "query_benchmark.qps",
             Count,
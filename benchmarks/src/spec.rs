@@ -272,6 +272,8 @@ impl WorkloadSpec {
                     unparsed_create_cache_statement: None,
                     always: false,
                     concurrently: false,
+                    replicas: None,
+                    shards: None,
                 };
 
                 let _ = conn
@@ -214,6 +214,8 @@ impl ArbitraryQueryParameters {
             inner: Ok(nom_sql::CacheInner::Statement(Box::new(stmt))),
             always: false,
             concurrently: false,
+            replicas: None,
+            shards: None,
             unparsed_create_cache_statement: None,
         };
 
@@ -39,6 +39,27 @@ pub struct UpstreamConfig {
     #[serde(default)]
     pub upstream_db_url: Option<RedactedString>,
 
+    /// Replicate from a newline-delimited stream of Debezium change events instead of from an
+    /// upstream database. Accepts the path to a file or a named pipe, or a `tcp://<host>:<port>`
+    /// address to connect to.
+    ///
+    /// Files are followed as they grow. Named pipes and sockets cannot be rewound, so when the
+    /// replicator restarts, the producer is expected to replay the stream from its start; events
+    /// that were already applied are skipped.
+    #[arg(long, env = "CDC_SOURCE", conflicts_with = "upstream_db_url")]
+    #[serde(default)]
+    pub cdc_source: Option<String>,
+
+    /// The type of database the change events read from `--cdc-source` were captured from.
+    #[arg(
+        long,
+        env = "CDC_SOURCE_TYPE",
+        value_enum,
+        default_value = "postgresql"
+    )]
+    #[serde(default)]
+    pub cdc_source_type: DatabaseType,
+
     /// Disable verification of SSL certificates supplied by the upstream database (postgres
     /// only, ignored for mysql). Ignored if `--upstream-db-url` is not passed.
     ///
@@ -60,7 +81,10 @@ pub struct UpstreamConfig {
     /// Disable running DDL Streaming Replication Setup for PostgreSQL. If this flag is set
     /// the DDL Streaming Replication Setup SQL queries will need to be manually run on the
     /// primary server before streaming replication will start.
-    #[arg(long, env = "DISABLE_SETUP_DDL_REPLICATION", hide = true)]
+    ///
+    /// The setup is always skipped when the upstream database is a physical standby, since it can
+    /// only be run on the primary.
+    #[arg(long, env = "DISABLE_SETUP_DDL_REPLICATION")]
     #[serde(default)]
     pub disable_setup_ddl_replication: bool,
 
@@ -168,6 +192,12 @@ impl UpstreamConfig {
         }
     }
 
+    /// Returns true if ReadySet should replicate from somewhere, either an upstream database or a
+    /// stream of change events
+    pub fn has_replication_source(&self) -> bool {
+        self.upstream_db_url.is_some() || self.cdc_source.is_some()
+    }
+
     pub fn from_url<S: AsRef<str>>(url: S) -> Self {
         UpstreamConfig {
             upstream_db_url: Some(url.as_ref().to_string().into()),
@@ -175,6 +205,25 @@ impl UpstreamConfig {
         }
     }
 
+    /// Returns a copy of this config whose upstream database URL authenticates as the given user,
+    /// with the given password, instead of with the credentials in the configured URL
+    pub fn with_credentials(&self, user: &str, password: &str) -> ReadySetResult<Self> {
+        let mut url: url::Url = self
+            .upstream_db_url
+            .as_deref()
+            .ok_or(ReadySetError::InvalidUpstreamDatabase)?
+            .parse()
+            .map_err(|_| ReadySetError::InvalidUpstreamDatabase)?;
+        url.set_username(user)
+            .and_then(|_| url.set_password(Some(password)))
+            .map_err(|_| ReadySetError::InvalidUpstreamDatabase)?;
+
+        Ok(UpstreamConfig {
+            upstream_db_url: Some(url.to_string().into()),
+            ..self.clone()
+        })
+    }
+
     pub fn max_parallel_snapshot_tables(&self) -> usize {
         self.max_parallel_snapshot_tables
             .unwrap_or_else(|| default_max_parallel_snapshot_tables().expect("always Some"))
@@ -242,6 +291,8 @@ impl Default for UpstreamConfig {
     fn default() -> Self {
         Self {
             upstream_db_url: Default::default(),
+            cdc_source: Default::default(),
+            cdc_source_type: Default::default(),
             disable_upstream_ssl_verification: false,
             disable_setup_ddl_replication: false,
             replication_server_id: Default::default(),
@@ -318,6 +369,101 @@ impl Display for DatabaseType {
     }
 }
 
+/// The flavor of a MySQL-compatible upstream database.
+///
+/// MariaDB speaks the MySQL wire protocol, but its binlog format (in particular the format of its
+/// GTIDs), the way it stores `JSON` columns, and some of its system variables differ from MySQL's.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MySqlFlavor {
+    #[default]
+    MySQL,
+    MariaDB,
+}
+
+impl MySqlFlavor {
+    /// Determines the flavor of a server from the string returned by `SELECT VERSION()`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use database_utils::MySqlFlavor;
+    ///
+    /// assert_eq!(MySqlFlavor::from_version("8.0.36"), MySqlFlavor::MySQL);
+    /// assert_eq!(
+    ///     MySqlFlavor::from_version("10.11.6-MariaDB-1:10.11.6+maria~ubu2204-log"),
+    ///     MySqlFlavor::MariaDB
+    /// );
+    /// ```
+    pub fn from_version(version: &str) -> Self {
+        if version.to_ascii_lowercase().contains("mariadb") {
+            Self::MariaDB
+        } else {
+            Self::MySQL
+        }
+    }
+
+    /// Queries the server on the other end of `conn` for its version, and returns its flavor
+    pub async fn detect<Q>(conn: &mut Q) -> mysql::Result<Self>
+    where
+        Q: mysql::prelude::Queryable,
+    {
+        let version: Option<String> = conn.query_first("SELECT VERSION()").await?;
+        Ok(version
+            .map(|version| Self::from_version(&version))
+            .unwrap_or_default())
+    }
+
+    /// Parses the `major.minor.patch` version number out of the string returned by `SELECT
+    /// VERSION()`, encoded as `major * 10000 + minor * 100 + patch`. Returns `None` if the version
+    /// could not be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use database_utils::MySqlFlavor;
+    ///
+    /// assert_eq!(MySqlFlavor::parse_version_number("8.4.0"), Some(80400));
+    /// assert_eq!(
+    ///     MySqlFlavor::parse_version_number("8.0.36-0ubuntu0.22.04.1"),
+    ///     Some(80036)
+    /// );
+    /// // Old MariaDB releases report their version with a `5.5.5-` prefix
+    /// assert_eq!(
+    ///     MySqlFlavor::parse_version_number("5.5.5-10.6.16-MariaDB"),
+    ///     Some(100616)
+    /// );
+    /// assert_eq!(MySqlFlavor::parse_version_number("unknown"), None);
+    /// ```
+    pub fn parse_version_number(version: &str) -> Option<u32> {
+        let version = version.strip_prefix("5.5.5-").unwrap_or(version);
+        let mut parts = version.split(['.', '-']).map(|part| {
+            let digits = part
+                .find(|c: char| !c.is_ascii_digit())
+                .map_or(part, |end| &part[..end]);
+            digits.parse::<u32>().ok()
+        });
+        let major = parts.next()??;
+        let minor = parts.next().flatten().unwrap_or(0);
+        let patch = parts.next().flatten().unwrap_or(0);
+        Some(major * 10000 + minor * 100 + patch)
+    }
+
+    /// Returns `true` if this is [`MySqlFlavor::MariaDB`]
+    #[must_use]
+    pub fn is_mariadb(&self) -> bool {
+        matches!(self, Self::MariaDB)
+    }
+}
+
+impl Display for MySqlFlavor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MySqlFlavor::MySQL => f.write_str("mysql"),
+            MySqlFlavor::MariaDB => f.write_str("mariadb"),
+        }
+    }
+}
+
 /// URL for an upstream database.
 ///
 /// [`DatabaseURL`]s can be constructed directly via the [`From`] implementations, or parsed from a
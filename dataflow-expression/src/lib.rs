@@ -5,6 +5,7 @@ mod eval;
 pub mod like;
 mod lower;
 mod reader_processing;
+pub mod sketch;
 pub mod utils;
 
 use std::fmt::{self, Display, Formatter};
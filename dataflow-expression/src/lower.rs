@@ -786,6 +786,9 @@ impl Expr {
 
                 Ok(Self::Call { func, ty })
             }
+            AstExpr::Call(FunctionExpr::Grouping(_)) => invalid_query!(
+                "GROUPING can only be used in the SELECT list or HAVING clause of a grouped query"
+            ),
             AstExpr::Call(call) => internal!(
                 "Unexpected (aggregate?) call node in project expression: {:?}",
                 Sensitive(&call)
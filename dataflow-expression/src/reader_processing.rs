@@ -3,12 +3,14 @@ use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::sync::Arc;
 
-use nom_sql::OrderType;
+use nom_sql::{Double, OrderType};
 use partial_map::InsertionOrder;
-use readyset_data::DfValue;
+use readyset_data::{CompactRow, DfValue};
 use readyset_errors::{internal, ReadySetResult};
 use serde::{Deserialize, Serialize};
 
+use crate::sketch::{HyperLogLog, TDigest};
+
 /// Representation of an aggregate function
 // TODO(aspen): It would be really nice to deduplicate this somehow with the grouped operator itself
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
@@ -26,6 +28,12 @@ pub enum PostLookupAggregateFunction {
     Max,
     /// Take the minimum input value
     Min,
+    /// Merge together serialized [`HyperLogLog`] sketches, estimating the number of distinct
+    /// values once all rows have been merged
+    ApproxCountDistinct,
+    /// Merge together serialized [`TDigest`] sketches, estimating the value at the given
+    /// percentile once all rows have been merged
+    ApproxPercentile { percentile: Double },
 }
 
 impl PostLookupAggregateFunction {
@@ -45,6 +53,49 @@ impl PostLookupAggregateFunction {
             .into()),
             PostLookupAggregateFunction::Max => Ok(cmp::max(val1, val2).clone()),
             PostLookupAggregateFunction::Min => Ok(cmp::min(val1, val2).clone()),
+            PostLookupAggregateFunction::ApproxCountDistinct => {
+                let mut hll = HyperLogLog::from_df_value(val1)?;
+                hll.merge(&HyperLogLog::from_df_value(val2)?);
+                Ok((&hll).into())
+            }
+            PostLookupAggregateFunction::ApproxPercentile { .. } => {
+                let mut digest = TDigest::from_df_value(val1)?;
+                digest.merge(&TDigest::from_df_value(val2)?);
+                Ok((&digest).into())
+            }
+        }
+    }
+
+    /// Returns true if the result of this aggregate function must be passed through
+    /// [`finalize`][Self::finalize] before being returned to the client
+    pub fn requires_finalization(&self) -> bool {
+        matches!(
+            self,
+            PostLookupAggregateFunction::ApproxCountDistinct
+                | PostLookupAggregateFunction::ApproxPercentile { .. }
+        )
+    }
+
+    /// Convert the (possibly already aggregated) value in a result row into the value that should
+    /// be returned to the client.
+    ///
+    /// For sketch aggregates, this computes the estimate from the sketch; all other values are
+    /// returned unchanged.
+    pub fn finalize(&self, val: DfValue) -> ReadySetResult<DfValue> {
+        if !matches!(val, DfValue::ByteArray(_)) {
+            return Ok(val);
+        }
+        match self {
+            PostLookupAggregateFunction::ApproxCountDistinct => {
+                Ok((HyperLogLog::from_df_value(&val)?.estimate() as i64).into())
+            }
+            PostLookupAggregateFunction::ApproxPercentile { percentile } => {
+                match TDigest::from_df_value(&val)?.quantile(percentile.value) {
+                    Some(v) => DfValue::try_from(v),
+                    None => Ok(DfValue::None),
+                }
+            }
+            _ => Ok(val),
         }
     }
 }
@@ -83,6 +134,14 @@ pub struct PostLookupAggregates<Column = usize> {
 }
 
 impl<Column> PostLookupAggregates<Column> {
+    /// Returns true if any of the aggregates in self must be finalized before results are
+    /// returned to the client, even if only a single row was looked up
+    pub fn requires_finalization(&self) -> bool {
+        self.aggregates
+            .iter()
+            .any(|agg| agg.function.requires_finalization())
+    }
+
     /// Transform all column references in self by applying a function
     pub fn map_columns<F, C2, E>(self, mut f: F) -> Result<PostLookupAggregates<C2>, E>
     where
@@ -193,37 +252,69 @@ pub struct PreInsertion {
     group_by: Option<Vec<usize>>,
 }
 
+impl PreInsertion {
+    /// Compare two rows according to the order in which they should be stored in a reader.
+    fn cmp_rows(&self, cur_row: &[DfValue], elem: &[DfValue]) -> Ordering {
+        self.cmp_rows_by(|idx| cur_row[idx].cmp(&elem[idx]), || cur_row.cmp(elem))
+    }
+
+    /// Compare two rows according to the order in which they should be stored in a reader, given
+    /// a function comparing the values of the column at a given index in each row and a function
+    /// comparing the rows as a whole.
+    fn cmp_rows_by<C, R>(&self, cmp_column: C, cmp_row: R) -> Ordering
+    where
+        C: Fn(usize) -> Ordering,
+        R: FnOnce() -> Ordering,
+    {
+        if let Some(cols) = &self.group_by {
+            cols.iter()
+                .map(|&idx| cmp_column(idx))
+                .try_fold(Ordering::Equal, |acc, next| match acc {
+                    Ordering::Equal => Ok(next),
+                    ord => Err(ord),
+                })
+                .unwrap_or_else(|ord| ord)
+                .then_with(cmp_row)
+        } else if let Some(indices) = self.order_by.as_deref() {
+            indices
+                .iter()
+                .map(|&(idx, order_type)| order_type.apply(cmp_column(idx)))
+                .try_fold(Ordering::Equal, |acc, next| match acc {
+                    Ordering::Equal => Ok(next),
+                    ord => Err(ord),
+                })
+                .unwrap_or_else(|ord| ord)
+                .then_with(cmp_row)
+        } else {
+            cmp_row()
+        }
+    }
+}
+
 impl InsertionOrder<Box<[DfValue]>> for PreInsertion {
     fn get_insertion_order(
         &self,
         values: &[Box<[DfValue]>],
         elem: &Box<[DfValue]>,
     ) -> Result<usize, usize> {
-        if let Some(cols) = &self.group_by {
-            values.binary_search_by(|cur_row| {
-                cols.iter()
-                    .map(|&idx| cur_row[idx].cmp(&elem[idx]))
-                    .try_fold(Ordering::Equal, |acc, next| match acc {
-                        Ordering::Equal => Ok(next),
-                        ord => Err(ord),
-                    })
-                    .unwrap_or_else(|ord| ord)
-                    .then(cur_row.cmp(elem))
-            })
-        } else if let Some(indices) = self.order_by.as_deref() {
-            values.binary_search_by(|cur_row| {
-                indices
-                    .iter()
-                    .map(|&(idx, order_type)| order_type.apply(cur_row[idx].cmp(&elem[idx])))
-                    .try_fold(Ordering::Equal, |acc, next| match acc {
-                        Ordering::Equal => Ok(next),
-                        ord => Err(ord),
-                    })
-                    .unwrap_or_else(|ord| ord)
-                    .then(cur_row.cmp(elem))
-            })
-        } else {
+        if self.group_by.is_none() && self.order_by.is_none() {
             values.binary_search(elem)
+        } else {
+            values.binary_search_by(|cur_row| self.cmp_rows(cur_row, elem))
         }
     }
 }
+
+impl InsertionOrder<CompactRow> for PreInsertion {
+    fn get_insertion_order(
+        &self,
+        values: &[CompactRow],
+        elem: &CompactRow,
+    ) -> Result<usize, usize> {
+        // Compare the encoded rows directly, so that a lookup only ever decodes the few values
+        // which can't be compared in their encoded form
+        values.binary_search_by(|cur_row| {
+            self.cmp_rows_by(|idx| cur_row.cmp_column(elem, idx), || cur_row.cmp(elem))
+        })
+    }
+}
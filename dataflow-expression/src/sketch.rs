@@ -0,0 +1,522 @@
+//! Mergeable sketches used to compute approximate aggregates.
+//!
+//! Sketches are stored in dataflow as serialized [`DfValue::ByteArray`]s, so that they can be kept
+//! as the aggregated value of a group by grouped operators, and merged together after a lookup
+//! into a reader covering multiple keys (see [`PostLookupAggregateFunction`]). The serialized form
+//! of a sketch only depends on the values inserted into it, so sketches built by different domains
+//! (or different servers) can be merged together.
+//!
+//! Neither kind of sketch supports removing values, so the operators using them have to rebuild a
+//! group's sketch from scratch when a value in that group is deleted.
+//!
+//! [`PostLookupAggregateFunction`]: crate::PostLookupAggregateFunction
+
+use std::f64::consts::PI;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use readyset_data::DfValue;
+use readyset_errors::{internal, internal_err, ReadySetResult};
+
+/// Tag byte identifying a [`HyperLogLog`] serialized with only its non-zero registers
+const HLL_SPARSE_TAG: u8 = 1;
+/// Tag byte identifying a [`HyperLogLog`] serialized with all of its registers
+const HLL_DENSE_TAG: u8 = 2;
+/// Tag byte identifying a serialized [`TDigest`]
+const TDIGEST_TAG: u8 = 3;
+
+/// Number of bits of each hash used to pick a register in a [`HyperLogLog`]. With 2^14
+/// registers, the standard error of the estimate is about 0.8%.
+const HLL_PRECISION: u32 = 14;
+const HLL_REGISTERS: usize = 1 << HLL_PRECISION;
+
+/// Compression parameter for a [`TDigest`], which bounds the number of centroids kept to roughly
+/// this many
+const TDIGEST_COMPRESSION: f64 = 100.0;
+
+/// A 64-bit FNV-1a hasher with a final avalanche step.
+///
+/// We can't use [`std::collections::hash_map::DefaultHasher`], since its output isn't guaranteed
+/// to be stable across releases, and sketches built by different processes must agree on the hash
+/// of every value.
+struct SketchHasher(u64);
+
+impl Default for SketchHasher {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for SketchHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        // The finalizer from MurmurHash3, so that every bit of the input affects the high bits of
+        // the output (which we use to pick registers)
+        let mut h = self.0;
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xff51afd7ed558ccd);
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xc4ceb9fe1a85ec53);
+        h ^= h >> 33;
+        h
+    }
+}
+
+/// Read `N` bytes from the front of `bytes`, advancing it past them
+fn take<const N: usize>(bytes: &mut &[u8]) -> ReadySetResult<[u8; N]> {
+    if bytes.len() < N {
+        internal!("Truncated sketch");
+    }
+    let (head, tail) = bytes.split_at(N);
+    *bytes = tail;
+    head.try_into()
+        .map_err(|_| internal_err!("Truncated sketch"))
+}
+
+fn sketch_bytes(value: &DfValue) -> ReadySetResult<&[u8]> {
+    match value {
+        DfValue::ByteArray(bytes) => Ok(bytes),
+        _ => internal!(
+            "Expected a serialized sketch, got a {:?}",
+            value.infer_dataflow_type()
+        ),
+    }
+}
+
+/// A [HyperLogLog][] sketch, used to estimate the number of distinct values in a multiset.
+///
+/// [HyperLogLog]: https://en.wikipedia.org/wiki/HyperLogLog
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; HLL_REGISTERS],
+        }
+    }
+}
+
+impl HyperLogLog {
+    /// Construct a new, empty sketch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a value to the sketch
+    pub fn insert(&mut self, value: &DfValue) {
+        let mut hasher = SketchHasher::default();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let register = (hash >> (64 - HLL_PRECISION)) as usize;
+        // Set the lowest bit we'd shift in, so that the rank is at most 64 - HLL_PRECISION + 1
+        let rest = (hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+        if self.registers[register] < rank {
+            self.registers[register] = rank;
+        }
+    }
+
+    /// Merge all the values in `other` into this sketch
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        for (reg, other_reg) in self.registers.iter_mut().zip(&other.registers) {
+            if *reg < *other_reg {
+                *reg = *other_reg;
+            }
+        }
+    }
+
+    /// Estimate the number of distinct values that have been inserted into the sketch
+    pub fn estimate(&self) -> u64 {
+        let m = HLL_REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let estimate = alpha * m * m / sum;
+
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if estimate <= 2.5 * m && zeros > 0 {
+            // Linear counting is much more accurate for small cardinalities
+            (m * (m / zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
+    }
+
+    /// Serialize the sketch. Sketches with few non-zero registers are stored as a list of
+    /// (register, rank) pairs rather than as the full set of registers.
+    pub fn serialize(&self) -> Vec<u8> {
+        let non_zero = self.registers.iter().filter(|&&r| r != 0).count();
+        if non_zero * 3 < HLL_REGISTERS {
+            let mut out = Vec::with_capacity(1 + non_zero * 3);
+            out.push(HLL_SPARSE_TAG);
+            for (i, &r) in self.registers.iter().enumerate() {
+                if r != 0 {
+                    out.extend_from_slice(&(i as u16).to_le_bytes());
+                    out.push(r);
+                }
+            }
+            out
+        } else {
+            let mut out = Vec::with_capacity(1 + HLL_REGISTERS);
+            out.push(HLL_DENSE_TAG);
+            out.extend_from_slice(&self.registers);
+            out
+        }
+    }
+
+    /// Deserialize a sketch previously serialized with [`HyperLogLog::serialize`]
+    pub fn deserialize(mut bytes: &[u8]) -> ReadySetResult<Self> {
+        let [tag] = take::<1>(&mut bytes)?;
+        let mut res = Self::new();
+        match tag {
+            HLL_SPARSE_TAG => {
+                while !bytes.is_empty() {
+                    let register = u16::from_le_bytes(take::<2>(&mut bytes)?) as usize;
+                    let [rank] = take::<1>(&mut bytes)?;
+                    *res.registers
+                        .get_mut(register)
+                        .ok_or_else(|| internal_err!("Invalid HyperLogLog register"))? = rank;
+                }
+            }
+            HLL_DENSE_TAG => {
+                if bytes.len() != HLL_REGISTERS {
+                    internal!("Invalid HyperLogLog length");
+                }
+                res.registers.copy_from_slice(bytes);
+            }
+            _ => internal!("Invalid HyperLogLog tag {tag}"),
+        }
+        Ok(res)
+    }
+
+    /// Deserialize a sketch stored in a [`DfValue`]
+    pub fn from_df_value(value: &DfValue) -> ReadySetResult<Self> {
+        Self::deserialize(sketch_bytes(value)?)
+    }
+}
+
+impl From<&HyperLogLog> for DfValue {
+    fn from(hll: &HyperLogLog) -> Self {
+        DfValue::ByteArray(Arc::new(hll.serialize()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// A [t-digest][] sketch, used to estimate quantiles of a distribution of numbers.
+///
+/// This is a "merging" t-digest, which buffers inserted values as centroids of weight 1 and
+/// merges adjacent centroids together on [`TDigest::compress`], using the `k_1` scale function to
+/// keep centroids near the tails of the distribution small.
+///
+/// [t-digest]: https://arxiv.org/abs/1902.04023
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TDigest {
+    centroids: Vec<Centroid>,
+    min: f64,
+    max: f64,
+}
+
+impl TDigest {
+    /// Construct a new, empty sketch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if no values have been inserted into the sketch
+    pub fn is_empty(&self) -> bool {
+        self.centroids.is_empty()
+    }
+
+    fn total_weight(&self) -> f64 {
+        self.centroids.iter().map(|c| c.weight).sum()
+    }
+
+    fn update_bounds(&mut self, min: f64, max: f64) {
+        if self.is_empty() {
+            self.min = min;
+            self.max = max;
+        } else {
+            self.min = self.min.min(min);
+            self.max = self.max.max(max);
+        }
+    }
+
+    /// Add a value to the sketch. Values are buffered until the next call to
+    /// [`TDigest::compress`].
+    pub fn insert(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.update_bounds(value, value);
+        self.centroids.push(Centroid {
+            mean: value,
+            weight: 1.0,
+        });
+    }
+
+    /// Merge all the values in `other` into this sketch
+    pub fn merge(&mut self, other: &TDigest) {
+        if other.is_empty() {
+            return;
+        }
+        self.update_bounds(other.min, other.max);
+        self.centroids.extend_from_slice(&other.centroids);
+        self.compress();
+    }
+
+    /// Merge adjacent centroids together, bounding the size of the sketch
+    pub fn compress(&mut self) {
+        if self.centroids.len() <= 1 {
+            return;
+        }
+        self.centroids.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        // The k_1 scale function, and its inverse
+        let k = |q: f64| TDIGEST_COMPRESSION / (2.0 * PI) * (2.0 * q - 1.0).asin();
+        let k_inv = |k: f64| ((k * 2.0 * PI / TDIGEST_COMPRESSION).sin() + 1.0) / 2.0;
+
+        let total = self.total_weight();
+        let mut centroids = self.centroids.drain(..);
+        let Some(mut current) = centroids.next() else {
+            return;
+        };
+        let mut weight_before = 0.0;
+        let mut weight_limit = total * k_inv(k(0.0) + 1.0);
+        let mut merged = Vec::new();
+
+        for next in centroids {
+            if weight_before + current.weight + next.weight <= weight_limit {
+                current.weight += next.weight;
+                current.mean += (next.mean - current.mean) * next.weight / current.weight;
+            } else {
+                weight_before += current.weight;
+                merged.push(current);
+                weight_limit = total * k_inv(k((weight_before / total).min(1.0)) + 1.0);
+                current = next;
+            }
+        }
+        merged.push(current);
+
+        self.centroids = merged;
+    }
+
+    /// Estimate the value at the given quantile (between 0 and 1) of the values inserted into the
+    /// sketch, or [`None`] if the sketch is empty.
+    ///
+    /// The estimate is interpolated linearly between the centers of adjacent centroids, as with
+    /// SQL's `PERCENTILE_CONT`.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let mut digest = self.clone();
+        digest.compress();
+        let centroids = &digest.centroids;
+
+        let (first, last) = (centroids.first()?, centroids.last()?);
+        if centroids.len() == 1 && first.weight == 1.0 {
+            return Some(first.mean);
+        }
+
+        let total = digest.total_weight();
+        let rank = q.clamp(0.0, 1.0) * total;
+
+        if rank <= first.weight / 2.0 {
+            let t = rank / (first.weight / 2.0);
+            return Some(digest.min + t * (first.mean - digest.min));
+        }
+
+        let mut weight_before = 0.0;
+        for pair in centroids.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let a_center = weight_before + a.weight / 2.0;
+            let b_center = weight_before + a.weight + b.weight / 2.0;
+            if rank <= b_center {
+                let t = (rank - a_center) / (b_center - a_center);
+                return Some(a.mean + t * (b.mean - a.mean));
+            }
+            weight_before += a.weight;
+        }
+
+        let last_center = total - last.weight / 2.0;
+        let t = if last.weight > 0.0 {
+            ((rank - last_center) / (last.weight / 2.0)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        Some(last.mean + t * (digest.max - last.mean))
+    }
+
+    /// Serialize the sketch
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(1 + 16 + 4 + self.centroids.len() * 16);
+        out.push(TDIGEST_TAG);
+        out.extend_from_slice(&self.min.to_le_bytes());
+        out.extend_from_slice(&self.max.to_le_bytes());
+        out.extend_from_slice(&(self.centroids.len() as u32).to_le_bytes());
+        for c in &self.centroids {
+            out.extend_from_slice(&c.mean.to_le_bytes());
+            out.extend_from_slice(&c.weight.to_le_bytes());
+        }
+        out
+    }
+
+    /// Deserialize a sketch previously serialized with [`TDigest::serialize`]
+    pub fn deserialize(mut bytes: &[u8]) -> ReadySetResult<Self> {
+        let [tag] = take::<1>(&mut bytes)?;
+        if tag != TDIGEST_TAG {
+            internal!("Invalid t-digest tag {tag}");
+        }
+        let min = f64::from_le_bytes(take::<8>(&mut bytes)?);
+        let max = f64::from_le_bytes(take::<8>(&mut bytes)?);
+        let len = u32::from_le_bytes(take::<4>(&mut bytes)?) as usize;
+        let mut centroids = Vec::with_capacity(len.min(bytes.len() / 16));
+        for _ in 0..len {
+            let mean = f64::from_le_bytes(take::<8>(&mut bytes)?);
+            let weight = f64::from_le_bytes(take::<8>(&mut bytes)?);
+            centroids.push(Centroid { mean, weight });
+        }
+        Ok(Self {
+            centroids,
+            min,
+            max,
+        })
+    }
+
+    /// Deserialize a sketch stored in a [`DfValue`]
+    pub fn from_df_value(value: &DfValue) -> ReadySetResult<Self> {
+        Self::deserialize(sketch_bytes(value)?)
+    }
+}
+
+impl From<&TDigest> for DfValue {
+    fn from(digest: &TDigest) -> Self {
+        DfValue::ByteArray(Arc::new(digest.serialize()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hll_estimates_distinct_values() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..100_000 {
+            // Every value is inserted twice
+            hll.insert(&DfValue::from(i % 50_000));
+        }
+        let estimate = hll.estimate() as f64;
+        assert!(
+            (estimate - 50_000.0).abs() / 50_000.0 < 0.03,
+            "estimate was {estimate}"
+        );
+    }
+
+    #[test]
+    fn hll_small_cardinalities_are_exact() {
+        let mut hll = HyperLogLog::new();
+        for v in ["a", "b", "c", "a"] {
+            hll.insert(&DfValue::from(v));
+        }
+        assert_eq!(hll.estimate(), 3);
+    }
+
+    #[test]
+    fn hll_merge_matches_union() {
+        let mut a = HyperLogLog::new();
+        let mut b = HyperLogLog::new();
+        let mut union = HyperLogLog::new();
+        for i in 0..10_000 {
+            a.insert(&DfValue::from(i));
+            union.insert(&DfValue::from(i));
+        }
+        for i in 5_000..20_000 {
+            b.insert(&DfValue::from(i));
+            union.insert(&DfValue::from(i));
+        }
+        a.merge(&b);
+        assert_eq!(a, union);
+    }
+
+    #[test]
+    fn hll_serialize_round_trip() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..10 {
+            hll.insert(&DfValue::from(i));
+        }
+        let sparse = hll.serialize();
+        assert_eq!(sparse[0], HLL_SPARSE_TAG);
+        assert_eq!(HyperLogLog::deserialize(&sparse).unwrap(), hll);
+
+        for i in 0..100_000 {
+            hll.insert(&DfValue::from(i));
+        }
+        let dense = hll.serialize();
+        assert_eq!(dense[0], HLL_DENSE_TAG);
+        assert_eq!(HyperLogLog::deserialize(&dense).unwrap(), hll);
+    }
+
+    #[test]
+    fn tdigest_estimates_quantiles() {
+        let mut digest = TDigest::new();
+        for i in 1..=10_000 {
+            digest.insert(i as f64);
+            if i % 100 == 0 {
+                digest.compress();
+            }
+        }
+        digest.compress();
+        assert!(digest.centroids.len() < 2 * TDIGEST_COMPRESSION as usize);
+
+        for (q, expected) in [(0.01, 100.0), (0.5, 5_000.0), (0.99, 9_900.0)] {
+            let estimate = digest.quantile(q).unwrap();
+            assert!(
+                (estimate - expected).abs() / expected < 0.01,
+                "estimate for {q} was {estimate}"
+            );
+        }
+        assert_eq!(digest.quantile(0.0), Some(1.0));
+        assert_eq!(digest.quantile(1.0), Some(10_000.0));
+    }
+
+    #[test]
+    fn tdigest_small_inputs() {
+        let mut digest = TDigest::new();
+        assert_eq!(digest.quantile(0.5), None);
+        digest.insert(3.0);
+        assert_eq!(digest.quantile(0.5), Some(3.0));
+        digest.insert(1.0);
+        digest.insert(2.0);
+        assert_eq!(digest.quantile(0.5), Some(2.0));
+    }
+
+    #[test]
+    fn tdigest_merge_and_round_trip() {
+        let mut a = TDigest::new();
+        let mut b = TDigest::new();
+        for i in 0..1000 {
+            a.insert(i as f64);
+            b.insert((i + 1000) as f64);
+        }
+        a.compress();
+        b.compress();
+        a.merge(&b);
+
+        let round_tripped = TDigest::deserialize(&a.serialize()).unwrap();
+        assert_eq!(round_tripped, a);
+        let median = round_tripped.quantile(0.5).unwrap();
+        assert!((median - 1000.0).abs() < 20.0, "median was {median}");
+    }
+}
@@ -6,6 +6,7 @@ mod memory_state;
 mod mk_key;
 mod persistent_state;
 mod single_state;
+mod tiered_state;
 
 use std::borrow::Cow;
 use std::fmt::{self, Debug};
@@ -33,6 +34,7 @@ pub use crate::memory_state::MemoryState;
 pub use crate::persistent_state::{
     DurabilityMode, PersistenceParameters, PersistentState, PersistentStateHandle, SnapshotMode,
 };
+pub use crate::tiered_state::{TieredState, TieredStateParameters};
 
 /// Information about state evicted via a call to [`State::evict_bytes`]
 pub struct EvictBytesResult<'a> {
@@ -71,6 +73,8 @@ pub enum MaterializedNodeState {
     Persistent(PersistentState),
     /// A read handle to a [`PersistentState`] owned by another node.
     PersistentReadHandle(PersistentStateHandle),
+    /// Partial state that stores recently used rows in memory, and spills the rest to disk.
+    Tiered(TieredState),
 }
 
 /// Enum representing whether a base table node was already initialized (and has a replication
@@ -275,6 +279,7 @@ impl SizeOf for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.deep_size_of(),
             MaterializedNodeState::Persistent(ps) => ps.deep_size_of(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.deep_size_of(),
+            MaterializedNodeState::Tiered(ts) => ts.deep_size_of(),
         }
     }
 
@@ -283,6 +288,7 @@ impl SizeOf for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.size_of(),
             MaterializedNodeState::Persistent(ps) => ps.size_of(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.size_of(),
+            MaterializedNodeState::Tiered(ts) => ts.size_of(),
         }
     }
 
@@ -291,6 +297,7 @@ impl SizeOf for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.is_empty(),
             MaterializedNodeState::Persistent(ps) => ps.is_empty(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.is_empty(),
+            MaterializedNodeState::Tiered(ts) => ts.is_empty(),
         }
     }
 }
@@ -301,6 +308,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.add_index(index, tags),
             MaterializedNodeState::Persistent(ps) => ps.add_index(index, tags),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.add_index(index, tags),
+            MaterializedNodeState::Tiered(ts) => ts.add_index(index, tags),
         }
     }
 
@@ -309,6 +317,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.add_weak_index(index),
             MaterializedNodeState::Persistent(ps) => ps.add_weak_index(index),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.add_weak_index(index),
+            MaterializedNodeState::Tiered(ts) => ts.add_weak_index(index),
         }
     }
 
@@ -317,6 +326,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.is_useful(),
             MaterializedNodeState::Persistent(ps) => ps.is_useful(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.is_useful(),
+            MaterializedNodeState::Tiered(ts) => ts.is_useful(),
         }
     }
 
@@ -325,6 +335,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.is_partial(),
             MaterializedNodeState::Persistent(ps) => ps.is_partial(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.is_partial(),
+            MaterializedNodeState::Tiered(ts) => ts.is_partial(),
         }
     }
 
@@ -333,6 +344,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.replay_done(),
             MaterializedNodeState::Persistent(ps) => ps.replay_done(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.replay_done(),
+            MaterializedNodeState::Tiered(ts) => ts.replay_done(),
         }
     }
 
@@ -352,6 +364,9 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::PersistentReadHandle(rh) => {
                 rh.process_records(records, partial_tag, replication_offset)
             }
+            MaterializedNodeState::Tiered(ts) => {
+                ts.process_records(records, partial_tag, replication_offset)
+            }
         }
     }
 
@@ -360,6 +375,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.replication_offset(),
             MaterializedNodeState::Persistent(ps) => ps.replication_offset(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.replication_offset(),
+            MaterializedNodeState::Tiered(ts) => ts.replication_offset(),
         }
     }
 
@@ -368,6 +384,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.persisted_up_to(),
             MaterializedNodeState::Persistent(ps) => ps.persisted_up_to(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.persisted_up_to(),
+            MaterializedNodeState::Tiered(ts) => ts.persisted_up_to(),
         }
     }
 
@@ -376,6 +393,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.mark_filled(key, tag),
             MaterializedNodeState::Persistent(ps) => ps.mark_filled(key, tag),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.mark_filled(key, tag),
+            MaterializedNodeState::Tiered(ts) => ts.mark_filled(key, tag),
         }
     }
 
@@ -384,6 +402,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.mark_hole(key, tag),
             MaterializedNodeState::Persistent(ps) => ps.mark_hole(key, tag),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.mark_hole(key, tag),
+            MaterializedNodeState::Tiered(ts) => ts.mark_hole(key, tag),
         }
     }
 
@@ -392,6 +411,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.lookup(columns, key),
             MaterializedNodeState::Persistent(ps) => ps.lookup(columns, key),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.lookup(columns, key),
+            MaterializedNodeState::Tiered(ts) => ts.lookup(columns, key),
         }
     }
 
@@ -400,6 +420,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.lookup_range(columns, key),
             MaterializedNodeState::Persistent(ps) => ps.lookup_range(columns, key),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.lookup_range(columns, key),
+            MaterializedNodeState::Tiered(ts) => ts.lookup_range(columns, key),
         }
     }
 
@@ -408,6 +429,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.lookup_weak(columns, key),
             MaterializedNodeState::Persistent(ps) => ps.lookup_weak(columns, key),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.lookup_weak(columns, key),
+            MaterializedNodeState::Tiered(ts) => ts.lookup_weak(columns, key),
         }
     }
 
@@ -416,6 +438,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.as_persistent(),
             MaterializedNodeState::Persistent(ps) => ps.as_persistent(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.as_persistent(),
+            MaterializedNodeState::Tiered(ts) => ts.as_persistent(),
         }
     }
 
@@ -424,6 +447,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.as_persistent_mut(),
             MaterializedNodeState::Persistent(ps) => ps.as_persistent_mut(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.as_persistent_mut(),
+            MaterializedNodeState::Tiered(ts) => ts.as_persistent_mut(),
         }
     }
 
@@ -432,6 +456,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.key_count(),
             MaterializedNodeState::Persistent(ps) => ps.key_count(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.key_count(),
+            MaterializedNodeState::Tiered(ts) => ts.key_count(),
         }
     }
 
@@ -440,6 +465,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.row_count(),
             MaterializedNodeState::Persistent(ps) => ps.row_count(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.row_count(),
+            MaterializedNodeState::Tiered(ts) => ts.row_count(),
         }
     }
 
@@ -448,6 +474,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.all_records(),
             MaterializedNodeState::Persistent(ps) => ps.all_records(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.all_records(),
+            MaterializedNodeState::Tiered(ts) => ts.all_records(),
         }
     }
 
@@ -456,6 +483,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.evict_bytes(bytes),
             MaterializedNodeState::Persistent(ps) => ps.evict_bytes(bytes),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.evict_bytes(bytes),
+            MaterializedNodeState::Tiered(ts) => ts.evict_bytes(bytes),
         }
     }
 
@@ -464,6 +492,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.evict_keys(tag, keys),
             MaterializedNodeState::Persistent(ps) => ps.evict_keys(tag, keys),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.evict_keys(tag, keys),
+            MaterializedNodeState::Tiered(ts) => ts.evict_keys(tag, keys),
         }
     }
 
@@ -472,6 +501,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.evict_random(tag, rng),
             MaterializedNodeState::Persistent(ps) => ps.evict_random(tag, rng),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.evict_random(tag, rng),
+            MaterializedNodeState::Tiered(ts) => ts.evict_random(tag, rng),
         }
     }
 
@@ -480,6 +510,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.clear(),
             MaterializedNodeState::Persistent(ps) => ps.clear(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.clear(),
+            MaterializedNodeState::Tiered(ts) => ts.clear(),
         }
     }
 
@@ -488,6 +519,7 @@ impl State for MaterializedNodeState {
             MaterializedNodeState::Memory(ms) => ms.tear_down(),
             MaterializedNodeState::Persistent(ps) => ps.tear_down(),
             MaterializedNodeState::PersistentReadHandle(rh) => rh.tear_down(),
+            MaterializedNodeState::Tiered(ts) => ts.tear_down(),
         }
     }
 }
@@ -11,6 +11,7 @@ use readyset_data::DfValue;
 use readyset_errors::ReadySetResult;
 use replication_offset::ReplicationOffset;
 use tracing::trace;
+use vec1::Vec1;
 
 use crate::keyed_state::KeyedState;
 use crate::single_state::SingleState;
@@ -412,13 +413,13 @@ impl State for MemoryState {
 impl MemoryState {
     /// Returns the index in `self.state` of the index keyed on `cols` and with the given
     /// `index_type`, or None if no such index exists.
-    fn state_for(&self, cols: &[usize], index_type: IndexType) -> Option<usize> {
+    pub(crate) fn state_for(&self, cols: &[usize], index_type: IndexType) -> Option<usize> {
         self.state
             .iter()
             .position(|s| s.columns() == cols && s.index_type() == index_type)
     }
 
-    fn insert(&mut self, r: Vec<DfValue>, partial_tag: Option<Tag>) -> bool {
+    pub(crate) fn insert(&mut self, r: Vec<DfValue>, partial_tag: Option<Tag>) -> bool {
         let r = Row::from(r);
 
         let hit = if let Some(tag) = partial_tag {
@@ -456,7 +457,7 @@ impl MemoryState {
         hit
     }
 
-    fn remove(&mut self, r: &[DfValue]) -> bool {
+    pub(crate) fn remove(&mut self, r: &[DfValue]) -> bool {
         let mut hit = false;
         for s in &mut self.state {
             if let Some(row) = s.remove_row(r, &mut hit) {
@@ -475,6 +476,74 @@ impl MemoryState {
         hit
     }
 
+    /// Returns the number of strict indices in this state
+    pub(crate) fn num_indices(&self) -> usize {
+        self.state.len()
+    }
+
+    /// Returns the strict index at position `state_index` in `self.state`
+    pub(crate) fn index(&self, state_index: usize) -> &Index {
+        self.state[state_index].index()
+    }
+
+    /// Returns true if the strict index at position `state_index` is partial
+    pub(crate) fn is_partial_index(&self, state_index: usize) -> bool {
+        self.state[state_index].partial()
+    }
+
+    /// Returns the position in `self.state` of the index targeted by replays along `tag`
+    pub(crate) fn state_for_tag(&self, tag: Tag) -> Option<usize> {
+        self.by_tag.get(&tag).copied()
+    }
+
+    /// Returns true if this state has any weak indices
+    pub(crate) fn has_weak_indices(&self) -> bool {
+        !self.weak_indices.is_empty()
+    }
+
+    /// Evict a random key from the strict index at position `state_index`, returning the key, a
+    /// copy of the rows that were stored under it, and the number of bytes freed
+    pub(crate) fn evict_random_rows<R: rand::Rng>(
+        &mut self,
+        state_index: usize,
+        rng: &mut R,
+    ) -> Option<(Vec<DfValue>, Vec<Vec<DfValue>>, u64)> {
+        let (key, rows) = self.state[state_index].evict_random(rng)?;
+        let mut bytes_freed = base_row_bytes(&key);
+        let rows = rows
+            .iter()
+            .map(|row| {
+                bytes_freed += self.handle_evicted_row(row);
+                Vec::clone(&**row)
+            })
+            .collect();
+        self.mem_size = self.mem_size.saturating_sub(bytes_freed);
+        Some((key, rows, bytes_freed))
+    }
+
+    /// Mark `key` as filled in the strict index at position `state_index`, with the given `rows`.
+    ///
+    /// The rows are only inserted into that index (and any weak indices), not into any other
+    /// strict index.
+    pub(crate) fn fill_with_rows(
+        &mut self,
+        state_index: usize,
+        key: Vec1<DfValue>,
+        rows: Vec<Vec<DfValue>>,
+    ) {
+        self.mem_size += base_row_bytes(&key);
+        self.state[state_index].mark_filled(KeyComparison::Equal(key));
+        for row in rows {
+            let row = Row::from(row);
+            self.mem_size += row.deep_size_of();
+            for (key, weak_index) in self.weak_indices.iter_mut() {
+                // SAFETY: row remains inside the same state
+                weak_index.insert(key, unsafe { row.clone() }, false);
+            }
+            self.state[state_index].insert_row(row);
+        }
+    }
+
     /// Removes a `Row` that was evicted from `self::state` from `self::weak_indices`, and returns
     /// the number of bytes freed if the last reference to the `Row` was dropped.
     fn handle_evicted_row(&mut self, row: &Row) -> u64 {
@@ -65,7 +65,7 @@ mod handle;
 use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::io::{self, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::mpsc::RecvTimeoutError;
 use std::sync::{mpsc, Arc};
@@ -1823,6 +1823,31 @@ impl PersistentState {
         }
     }
 
+    /// Writes a consistent, point-in-time copy of this [`PersistentState`] - including all of its
+    /// indices and its replication offset - into a new directory inside `dir`, named after the
+    /// database, and returns the path to that directory.
+    ///
+    /// The copy is made with a [RocksDB checkpoint][checkpoint], which flushes the memtables first
+    /// so the copy includes every write made so far, and hard-links the (immutable) table files
+    /// rather than copying them when `dir` is on the same filesystem as the database. The copy can
+    /// be opened as a normal [`PersistentState`] by moving it into a deployment's storage
+    /// directory.
+    ///
+    /// [checkpoint]: https://github.com/facebook/rocksdb/wiki/Checkpoints
+    pub fn checkpoint(&self, dir: &Path) -> Result<PathBuf> {
+        // The name of the state can be a full path to the database, if it was created without a
+        // storage directory
+        let path = dir.join(
+            Path::new(self.name.as_str())
+                .file_name()
+                .unwrap_or_default(),
+        );
+        let db = self.db.db();
+        rocksdb::checkpoint::Checkpoint::new(&db)?.create_checkpoint(&path)?;
+        debug!(base = %self.name, path = %path.display(), "Wrote checkpoint");
+        Ok(path)
+    }
+
     fn enable_snapshot_mode(&mut self) {
         self.db.replication_offset = None; // Remove any replication offset first (although it should be None already)
         let meta = self.meta();
@@ -2762,6 +2787,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn persistent_state_checkpoint() {
+        let (_dir, name) = get_tmp_path();
+        let checkpoint_dir = tempdir().unwrap();
+        let params = PersistenceParameters {
+            mode: DurabilityMode::Permanent,
+            ..Default::default()
+        };
+        let first: Vec<DfValue> = vec![10.into(), "Cat".into()];
+        let second: Vec<DfValue> = vec![20.into(), "Bob".into()];
+        let replication_offset = ReplicationOffset::Postgres(PostgresPosition {
+            commit_lsn: 12.into(),
+            lsn: 0.into(),
+        });
+
+        let mut state = PersistentState::new(name, Some(&[0]), &params).unwrap();
+        state.add_index(Index::new(IndexType::HashMap, vec![0]), None);
+        state.add_index(Index::new(IndexType::HashMap, vec![1]), None);
+        state
+            .process_records(
+                &mut vec![first.clone()].into(),
+                None,
+                Some(replication_offset.clone()),
+            )
+            .unwrap();
+        let checkpoint = state.checkpoint(checkpoint_dir.path()).unwrap();
+
+        // Writes after the checkpoint aren't included in it
+        state
+            .process_records(&mut vec![second].into(), None, None)
+            .unwrap();
+        drop(state);
+
+        let restored =
+            PersistentState::new(checkpoint.to_str().unwrap().to_owned(), Some(&[0]), &params)
+                .unwrap();
+        assert_eq!(restored.replication_offset(), Some(&replication_offset));
+        match restored.lookup(&[1], &PointKey::Single("Cat".into())) {
+            LookupResult::Some(RecordResult::Owned(rows)) => assert_eq!(rows, vec![first]),
+            _ => unreachable!(),
+        }
+        match restored.lookup(&[0], &PointKey::Single(20.into())) {
+            LookupResult::Some(RecordResult::Owned(rows)) => assert!(rows.is_empty()),
+            _ => unreachable!(),
+        }
+    }
+
     #[test]
     fn persistent_state_recover_unique_key() {
         let (_dir, name) = get_tmp_path();
@@ -0,0 +1,870 @@
+//! Partial state for internal nodes which keeps its hot keys in memory and spills cold keys to
+//! disk.
+//!
+//! A [`TieredState`] wraps a [`MemoryState`], which holds the keys that have been used recently.
+//! When the state is asked to evict bytes, rather than dropping keys (which, for a partial node,
+//! forces upqueries when the keys are next needed) it moves them out of memory into a RocksDB
+//! database in a temporary directory. As far as the rest of the dataflow graph is concerned those
+//! keys are still filled: writes to them are applied to their rows on disk, and lookups of them
+//! are served from disk, after which they are brought back into memory. Only once the rows on disk
+//! exceed the state's disk limit are keys evicted for real; until then, writes to spilled keys
+//! which find the disk over its limit bring those keys back into memory rather than growing their
+//! rows on disk.
+//!
+//! States whose rows in memory are smaller than [`TieredStateParameters::min_memory_bytes`] evict
+//! keys as usual rather than spilling them, since there is little to be gained by keeping so
+//! little on disk.
+//!
+//! Keys are only spilled from partial hash indices, and only while the state has no weak indices,
+//! since weak indices must be able to see every row stored in a filled key of a strict index. Nor
+//! are they spilled while the state has any range indices, since range lookups are only ever
+//! answered from memory: the migration planner doesn't give tiered state to nodes with range
+//! indices, and if one is added later every spilled key is brought back into memory first.
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::{fs, mem, slice};
+
+use bincode::Options;
+use common::{IndexType, Record, Records, SizeOf, Tag};
+use indexmap::IndexMap;
+use rand::Rng;
+use readyset_client::debug::info::KeyCount;
+use readyset_client::internal::Index;
+use readyset_client::KeyComparison;
+use readyset_data::DfValue;
+use readyset_errors::{internal_err, ReadySetResult};
+use replication_offset::ReplicationOffset;
+use rocksdb::{WriteBatch, WriteOptions, DB};
+use serde::{Deserialize, Serialize};
+use tempfile::TempDir;
+use tracing::{error, trace};
+use vec1::Vec1;
+
+use crate::{
+    AllRecords, EvictBytesResult, EvictKeysResult, EvictRandomResult, LookupResult, MemoryState,
+    PersistenceParameters, PersistencePoint, PointKey, RangeKey, RangeLookupResult, RecordResult,
+    State,
+};
+
+/// Parameters for a [`TieredState`]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TieredStateParameters {
+    /// The maximum number of (serialized) bytes of rows to keep on disk, beyond which keys are
+    /// evicted from disk for real. `None` means there is no limit.
+    pub disk_limit: Option<u64>,
+    /// Keys are only spilled to disk while the rows in memory take up at least this many bytes
+    #[serde(default)]
+    pub min_memory_bytes: u64,
+}
+
+/// What we remember in memory about a key whose rows have been spilled to disk
+#[derive(Debug, Clone, Copy)]
+struct SpilledKey {
+    /// The number of rows stored under the key
+    rows: usize,
+    /// The number of bytes the rows take up on disk
+    bytes: u64,
+}
+
+/// The rows of spilled keys, stored in a RocksDB database which is deleted along with the state.
+///
+/// Each row is stored under its own RocksDB key, made up of the serialized index position and key
+/// followed by a unique row number, so that writes to a spilled key only touch the rows they
+/// change. Since the serialized index position and key can be decoded without knowing its length,
+/// no key's prefix is also a prefix of another key's rows.
+struct ColdStore {
+    db: DB,
+    /// The number to give the next row written, to keep the RocksDB keys of rows unique
+    next_row: u64,
+    /// The directory holding the database, which is removed when dropped
+    _dir: TempDir,
+}
+
+impl ColdStore {
+    fn open(name: &str, params: &PersistenceParameters) -> ReadySetResult<Self> {
+        let parent = params
+            .storage_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("."));
+        if !parent.is_dir() {
+            fs::create_dir_all(&parent)?;
+        }
+        let dir = tempfile::Builder::new()
+            .prefix(&format!("{}-{name}-tiered", params.db_filename_prefix))
+            .tempdir_in(parent)?;
+
+        let mut opts = rocksdb::Options::default();
+        opts.create_if_missing(true);
+        opts.set_compression_type(rocksdb::DBCompressionType::Lz4);
+        let db = DB::open(&opts, dir.path())
+            .map_err(|e| internal_err!("Could not open spilled state: {e}"))?;
+
+        Ok(Self {
+            db,
+            next_row: 0,
+            _dir: dir,
+        })
+    }
+
+    /// The prefix of the RocksDB keys of every row stored under `key`
+    fn prefix(state_index: usize, key: &[DfValue]) -> Vec<u8> {
+        let key = key
+            .iter()
+            .cloned()
+            .map(DfValue::normalize)
+            .collect::<Vec<_>>();
+        bincode::options()
+            .serialize(&(state_index as u32, key))
+            .expect("DfValue serialization can't fail")
+    }
+
+    /// There's nothing to recover the spilled rows for after a crash, so skip the WAL
+    fn write_options() -> WriteOptions {
+        let mut opts = WriteOptions::default();
+        opts.disable_wal(true);
+        opts
+    }
+
+    fn deserialize_row(value: &[u8]) -> ReadySetResult<Vec<DfValue>> {
+        bincode::options()
+            .deserialize(value)
+            .map_err(|e| internal_err!("Could not deserialize spilled row: {e}"))
+    }
+
+    /// Call `f` with the RocksDB key and serialized value of each row stored under `key`, until it
+    /// returns false
+    fn scan<F>(&self, state_index: usize, key: &[DfValue], mut f: F) -> ReadySetResult<()>
+    where
+        F: FnMut(&[u8], &[u8]) -> ReadySetResult<bool>,
+    {
+        let prefix = Self::prefix(state_index, key);
+        let mut iter = self.db.raw_iterator();
+        iter.seek(&prefix);
+        while let (Some(k), Some(v)) = (iter.key(), iter.value()) {
+            if !k.starts_with(&prefix) || !f(k, v)? {
+                break;
+            }
+            iter.next();
+        }
+        iter.status()
+            .map_err(|e| internal_err!("Could not read spilled rows: {e}"))
+    }
+
+    fn get(&self, state_index: usize, key: &[DfValue]) -> ReadySetResult<Vec<Vec<DfValue>>> {
+        let mut rows = vec![];
+        self.scan(state_index, key, |_, value| {
+            rows.push(Self::deserialize_row(value)?);
+            Ok(true)
+        })?;
+        Ok(rows)
+    }
+
+    /// Add `rows` to the rows stored under `key`, returning the number of bytes written
+    fn put(
+        &mut self,
+        state_index: usize,
+        key: &[DfValue],
+        rows: &[Vec<DfValue>],
+    ) -> ReadySetResult<u64> {
+        let prefix = Self::prefix(state_index, key);
+        let mut batch = WriteBatch::default();
+        let mut bytes = 0;
+        for row in rows {
+            let value = bincode::options()
+                .serialize(row)
+                .map_err(|e| internal_err!("Could not serialize spilled row: {e}"))?;
+            let mut row_key = prefix.clone();
+            row_key.extend_from_slice(&self.next_row.to_be_bytes());
+            self.next_row += 1;
+            bytes += value.len() as u64;
+            batch.put(row_key, value);
+        }
+        self.db
+            .write_opt(batch, &Self::write_options())
+            .map_err(|e| internal_err!("Could not write spilled rows: {e}"))?;
+        Ok(bytes)
+    }
+
+    /// Remove one copy of `row` from the rows stored under `key`, returning the number of bytes
+    /// it took up, or `None` if there was no such row
+    fn remove(
+        &self,
+        state_index: usize,
+        key: &[DfValue],
+        row: &[DfValue],
+    ) -> ReadySetResult<Option<u64>> {
+        let mut found = None;
+        self.scan(state_index, key, |k, value| {
+            if Self::deserialize_row(value)? == row {
+                found = Some((k.to_vec(), value.len() as u64));
+                return Ok(false);
+            }
+            Ok(true)
+        })?;
+        let Some((row_key, bytes)) = found else {
+            return Ok(None);
+        };
+        self.db
+            .delete_opt(row_key, &Self::write_options())
+            .map_err(|e| internal_err!("Could not delete spilled row: {e}"))?;
+        Ok(Some(bytes))
+    }
+
+    /// Remove every row stored under `key`
+    fn delete(&self, state_index: usize, key: &[DfValue]) {
+        let mut batch = WriteBatch::default();
+        let res = self
+            .scan(state_index, key, |k, _| {
+                batch.delete(k);
+                Ok(true)
+            })
+            .and_then(|()| {
+                self.db
+                    .write_opt(batch, &Self::write_options())
+                    .map_err(|e| internal_err!("Could not delete spilled rows: {e}"))
+            });
+        if let Err(error) = res {
+            // The key is forgotten in memory either way, so the rows will never be read again
+            error!(%error, "Could not delete spilled rows");
+        }
+    }
+}
+
+/// Partial state for an internal node, which spills keys that haven't been used recently to disk
+/// rather than evicting them. See [the module documentation](self) for more information.
+pub struct TieredState {
+    memory: MemoryState,
+    cold: ColdStore,
+    params: TieredStateParameters,
+    /// The keys spilled from each strict index of `memory`, by position of the index
+    spilled: Vec<IndexMap<Vec<DfValue>, SpilledKey>>,
+    /// The total number of bytes of rows on disk
+    disk_bytes: u64,
+    /// Spilled keys which were looked up, and should be brought back into memory the next time
+    /// the state is modified
+    promotions: RefCell<Vec<(usize, Vec<DfValue>)>>,
+    replication_offset: Option<ReplicationOffset>,
+}
+
+impl SizeOf for TieredState {
+    fn size_of(&self) -> u64 {
+        mem::size_of::<Self>() as u64
+    }
+
+    /// Only counts the rows in memory, since it's memory that eviction is trying to free
+    fn deep_size_of(&self) -> u64 {
+        self.memory.deep_size_of()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.memory.is_empty() && self.spilled.iter().all(IndexMap::is_empty)
+    }
+}
+
+impl TieredState {
+    /// Create a new, empty tiered state, spilling to a temporary directory named after `name`
+    /// within the storage directory in `persistence_params`
+    pub fn new(
+        name: &str,
+        persistence_params: &PersistenceParameters,
+        params: TieredStateParameters,
+    ) -> ReadySetResult<Self> {
+        Ok(Self {
+            memory: MemoryState::default(),
+            cold: ColdStore::open(name, persistence_params)?,
+            params,
+            spilled: vec![],
+            disk_bytes: 0,
+            promotions: Default::default(),
+            replication_offset: None,
+        })
+    }
+
+    fn has_spilled(&self) -> bool {
+        self.spilled.iter().any(|keys| !keys.is_empty())
+    }
+
+    fn has_range_indices(&self) -> bool {
+        (0..self.memory.num_indices())
+            .any(|i| self.memory.index(i).index_type == IndexType::BTreeMap)
+    }
+
+    /// Can keys be spilled from the strict index at `state_index`?
+    fn can_spill(&self, state_index: usize) -> bool {
+        !self.memory.has_weak_indices()
+            && !self.has_range_indices()
+            && self.memory.is_partial_index(state_index)
+            && self.memory.index(state_index).index_type == IndexType::HashMap
+    }
+
+    /// Move a random key from the strict index at `state_index` to disk, returning the number of
+    /// bytes freed in memory. If the key can't be written to disk, it's evicted for real and added
+    /// to `keys_evicted`.
+    fn spill_random<R: Rng>(
+        &mut self,
+        state_index: usize,
+        rng: &mut R,
+        keys_evicted: &mut Vec<Vec<DfValue>>,
+    ) -> Option<u64> {
+        let (key, rows, bytes_freed) = self.memory.evict_random_rows(state_index, rng)?;
+        match self.cold.put(state_index, &key, &rows) {
+            Ok(bytes) => {
+                trace!(?key, rows = rows.len(), "Spilled key to disk");
+                self.disk_bytes += bytes;
+                self.spilled[state_index].insert(
+                    key,
+                    SpilledKey {
+                        rows: rows.len(),
+                        bytes,
+                    },
+                );
+            }
+            // The key has already been evicted from memory, so the best we can do is to report it
+            // as evicted; it will be refilled by an upquery if it's needed again
+            Err(error) => {
+                error!(%error, "Could not spill key to disk");
+                keys_evicted.push(key);
+            }
+        }
+        Some(bytes_freed)
+    }
+
+    /// Forget a spilled key, deleting its rows from disk. Returns true if the key was spilled.
+    fn forget(&mut self, state_index: usize, key: &[DfValue]) -> bool {
+        match self.spilled[state_index].swap_remove(key) {
+            Some(spilled) => {
+                self.disk_bytes = self.disk_bytes.saturating_sub(spilled.bytes);
+                self.cold.delete(state_index, key);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Evict random spilled keys from the strict index at `state_index` until the rows on disk
+    /// fit within the disk limit, returning the evicted keys
+    fn enforce_disk_limit<R: Rng>(&mut self, state_index: usize, rng: &mut R) -> Vec<Vec<DfValue>> {
+        let Some(disk_limit) = self.params.disk_limit else {
+            return vec![];
+        };
+        let mut evicted = vec![];
+        while self.disk_bytes > disk_limit && !self.spilled[state_index].is_empty() {
+            let i = rng.gen_range(0..self.spilled[state_index].len());
+            if let Some((key, _)) = self.spilled[state_index].get_index(i) {
+                let key = key.clone();
+                self.forget(state_index, &key);
+                evicted.push(key);
+            }
+        }
+        evicted
+    }
+
+    /// Bring the rows of the spilled key `key` of the strict index at `state_index` back into
+    /// memory
+    fn promote(&mut self, state_index: usize, key: Vec<DfValue>) {
+        if !self.spilled[state_index].contains_key(&key) {
+            return;
+        }
+        let rows = self.cold.get(state_index, &key);
+        self.forget(state_index, &key);
+        match (rows, Vec1::try_from_vec(key)) {
+            (Ok(rows), Ok(key)) => self.memory.fill_with_rows(state_index, key, rows),
+            (Err(error), _) => error!(%error, "Could not read spilled key back into memory"),
+            (_, Err(_)) => error!("Spilled an empty key"),
+        }
+    }
+
+    /// Bring every key that was looked up since the state was last modified back into memory
+    fn promote_looked_up(&mut self) {
+        for (state_index, key) in self.promotions.take() {
+            self.promote(state_index, key);
+        }
+    }
+
+    /// Bring every spilled key back into memory
+    fn promote_all(&mut self) {
+        self.promotions.take();
+        for state_index in 0..self.spilled.len() {
+            let keys = self.spilled[state_index]
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>();
+            for key in keys {
+                self.promote(state_index, key);
+            }
+        }
+    }
+
+    /// Apply `record` to the rows of any spilled keys it falls into. Returns true if it did.
+    fn process_spilled_record(&mut self, record: &Record) -> ReadySetResult<bool> {
+        let mut hit = false;
+        for state_index in 0..self.spilled.len() {
+            if self.spilled[state_index].is_empty() {
+                continue;
+            }
+            let key = self
+                .memory
+                .index(state_index)
+                .columns
+                .iter()
+                .map(|&col| record.rec()[col].clone())
+                .collect::<Vec<_>>();
+            let Some(spilled) = self.spilled[state_index].get(&key).copied() else {
+                continue;
+            };
+            hit = true;
+
+            if self
+                .params
+                .disk_limit
+                .is_some_and(|disk_limit| self.disk_bytes > disk_limit)
+            {
+                // Writing to the key would only take the disk further over its limit, so bring it
+                // back into memory instead, where the record is applied along with all the others.
+                // It stays filled, so it can be spilled again (and the disk limit enforced,
+                // evicting keys downstream as well) the next time memory is freed.
+                let rows = self.cold.get(state_index, &key)?;
+                self.forget(state_index, &key);
+                match Vec1::try_from_vec(key) {
+                    Ok(key) => self.memory.fill_with_rows(state_index, key, rows),
+                    Err(_) => error!("Spilled an empty key"),
+                }
+                continue;
+            }
+            let spilled = match record {
+                Record::Positive(row) => {
+                    let bytes = self.cold.put(state_index, &key, slice::from_ref(row))?;
+                    self.disk_bytes += bytes;
+                    SpilledKey {
+                        rows: spilled.rows + 1,
+                        bytes: spilled.bytes + bytes,
+                    }
+                }
+                Record::Negative(row) => match self.cold.remove(state_index, &key, row)? {
+                    Some(bytes) => {
+                        self.disk_bytes = self.disk_bytes.saturating_sub(bytes);
+                        SpilledKey {
+                            rows: spilled.rows.saturating_sub(1),
+                            bytes: spilled.bytes.saturating_sub(bytes),
+                        }
+                    }
+                    None => continue,
+                },
+            };
+            self.spilled[state_index].insert(key, spilled);
+        }
+        Ok(hit)
+    }
+}
+
+impl State for TieredState {
+    fn add_index(&mut self, index: Index, tags: Option<Vec<Tag>>) {
+        if index.index_type == IndexType::BTreeMap {
+            // Range lookups are answered from memory alone
+            self.promote_all();
+        }
+        self.memory.add_index(index, tags);
+        self.spilled
+            .resize_with(self.memory.num_indices(), Default::default);
+    }
+
+    fn add_weak_index(&mut self, index: Index) {
+        // Weak indices have to see every row in a filled key
+        self.promote_all();
+        self.memory.add_weak_index(index)
+    }
+
+    fn is_useful(&self) -> bool {
+        self.memory.is_useful()
+    }
+
+    fn is_partial(&self) -> bool {
+        self.memory.is_partial()
+    }
+
+    fn replay_done(&self) -> bool {
+        self.memory.replay_done()
+    }
+
+    fn process_records(
+        &mut self,
+        records: &mut Records,
+        partial_tag: Option<Tag>,
+        replication_offset: Option<ReplicationOffset>,
+    ) -> ReadySetResult<()> {
+        self.promote_looked_up();
+        if let Some(replication_offset) = replication_offset {
+            self.replication_offset = Some(replication_offset);
+        }
+
+        if partial_tag.is_some() || !self.has_spilled() {
+            return self.memory.process_records(records, partial_tag, None);
+        }
+
+        // Records have to be kept if they hit a key in memory *or* on disk, since either way
+        // the key is filled as far as the nodes downstream are concerned
+        let mut res = Ok(());
+        let mut kept = Vec::with_capacity(records.len());
+        for record in mem::take(records) {
+            let cold_hit = match self.process_spilled_record(&record) {
+                Ok(hit) => hit,
+                Err(e) => {
+                    res = Err(e);
+                    false
+                }
+            };
+            let hot_hit = match record {
+                Record::Positive(ref r) => self.memory.insert(r.clone(), None),
+                Record::Negative(ref r) => self.memory.remove(r),
+            };
+            if hot_hit || cold_hit {
+                kept.push(record);
+            }
+        }
+        *records = kept.into();
+        res
+    }
+
+    fn replication_offset(&self) -> Option<&ReplicationOffset> {
+        self.replication_offset.as_ref()
+    }
+
+    fn persisted_up_to(&self) -> ReadySetResult<PersistencePoint> {
+        Ok(PersistencePoint::Persisted)
+    }
+
+    fn mark_filled(&mut self, key: KeyComparison, tag: Tag) {
+        self.promote_looked_up();
+        if let (KeyComparison::Equal(k), Some(state_index)) = (&key, self.memory.state_for_tag(tag))
+        {
+            // A replay is filling the key again, so the copy on disk is no longer needed
+            self.forget(state_index, k);
+        }
+        self.memory.mark_filled(key, tag)
+    }
+
+    fn mark_hole(&mut self, key: &KeyComparison, tag: Tag) {
+        self.promote_looked_up();
+        if let (KeyComparison::Equal(k), Some(state_index)) = (key, self.memory.state_for_tag(tag))
+        {
+            if self.forget(state_index, k) {
+                return;
+            }
+        }
+        self.memory.mark_hole(key, tag)
+    }
+
+    fn lookup<'a>(&'a self, columns: &[usize], key: &PointKey) -> LookupResult<'a> {
+        let res = self.memory.lookup(columns, key);
+        if res.is_some() || !self.has_spilled() {
+            return res;
+        }
+
+        let Some(state_index) = self.memory.state_for(columns, IndexType::HashMap) else {
+            return res;
+        };
+        let key = (0..key.len())
+            .filter_map(|i| key.get(i).cloned())
+            .collect::<Vec<_>>();
+        if !self.spilled[state_index].contains_key(&key) {
+            return res;
+        }
+        match self.cold.get(state_index, &key) {
+            Ok(rows) => {
+                trace!(?key, "Lookup hit spilled key");
+                self.promotions.borrow_mut().push((state_index, key));
+                LookupResult::Some(RecordResult::Owned(rows))
+            }
+            Err(error) => {
+                error!(%error, "Could not read spilled key");
+                LookupResult::Missing
+            }
+        }
+    }
+
+    fn lookup_range<'a>(&'a self, columns: &[usize], key: &RangeKey) -> RangeLookupResult<'a> {
+        // Keys are never spilled while there are range indices
+        self.memory.lookup_range(columns, key)
+    }
+
+    fn lookup_weak<'a>(&'a self, columns: &[usize], key: &PointKey) -> Option<RecordResult<'a>> {
+        // Keys are never spilled while there are weak indices
+        self.memory.lookup_weak(columns, key)
+    }
+
+    fn key_count(&self) -> KeyCount {
+        let spilled = self.spilled.iter().map(IndexMap::len).sum::<usize>();
+        match self.memory.key_count() {
+            KeyCount::ExactKeyCount(count) => KeyCount::ExactKeyCount(count + spilled),
+            count => count,
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        self.memory.row_count()
+            + self
+                .spilled
+                .iter()
+                .flat_map(IndexMap::values)
+                .map(|spilled| spilled.rows)
+                .sum::<usize>()
+    }
+
+    fn all_records(&self) -> AllRecords {
+        // Only fully materialized states can be read in full, and those never spill
+        self.memory.all_records()
+    }
+
+    /// Frees `bytes` of memory by spilling random keys of one of the state's indices to disk. If
+    /// that pushes the rows on disk over the disk limit, keys are evicted from disk, and returned
+    /// as evicted.
+    fn evict_bytes(&mut self, bytes: usize) -> Option<EvictBytesResult> {
+        self.promote_looked_up();
+        let mut rng = rand::thread_rng();
+        let spillable = (0..self.memory.num_indices())
+            .filter(|&i| self.can_spill(i))
+            .collect::<Vec<_>>();
+        if spillable.is_empty() || self.memory.deep_size_of() < self.params.min_memory_bytes {
+            return self.memory.evict_bytes(bytes);
+        }
+        let state_index = spillable[rng.gen_range(0..spillable.len())];
+
+        let mut bytes_freed = 0;
+        let mut keys_evicted = vec![];
+        while bytes_freed < bytes as u64 {
+            match self.spill_random(state_index, &mut rng, &mut keys_evicted) {
+                Some(freed) => bytes_freed += freed,
+                None => break,
+            }
+        }
+        keys_evicted.extend(self.enforce_disk_limit(state_index, &mut rng));
+
+        if bytes_freed == 0 && keys_evicted.is_empty() {
+            return None;
+        }
+
+        Some(EvictBytesResult {
+            index: self.memory.index(state_index),
+            keys_evicted,
+            bytes_freed,
+        })
+    }
+
+    fn evict_keys(&mut self, tag: Tag, keys: &[KeyComparison]) -> Option<EvictKeysResult> {
+        self.promote_looked_up();
+        if let Some(state_index) = self.memory.state_for_tag(tag) {
+            for key in keys {
+                if let KeyComparison::Equal(key) = key {
+                    self.forget(state_index, key);
+                }
+            }
+        }
+        self.memory.evict_keys(tag, keys)
+    }
+
+    fn evict_random<R: rand::Rng>(&mut self, tag: Tag, rng: &mut R) -> Option<EvictRandomResult> {
+        self.promote_looked_up();
+        let state_index = self.memory.state_for_tag(tag)?;
+        if self.memory.index(state_index).index_type == IndexType::HashMap
+            && !self.spilled[state_index].is_empty()
+        {
+            // Evict keys that have already been spilled before the ones that are still in memory
+            let i = rng.gen_range(0..self.spilled[state_index].len());
+            let key = self.spilled[state_index].get_index(i)?.0.clone();
+            self.forget(state_index, &key);
+            return Some(EvictRandomResult {
+                index: self.memory.index(state_index),
+                key_evicted: key,
+                bytes_freed: 0,
+            });
+        }
+        self.memory.evict_random(tag, rng)
+    }
+
+    fn clear(&mut self) {
+        self.promotions.take();
+        for state_index in 0..self.spilled.len() {
+            let keys = self.spilled[state_index]
+                .keys()
+                .cloned()
+                .collect::<Vec<_>>();
+            for key in keys {
+                self.forget(state_index, &key);
+            }
+        }
+        self.memory.clear()
+    }
+
+    fn tear_down(self) -> ReadySetResult<()> {
+        // Dropping the cold store deletes its directory
+        self.memory.tear_down()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vec1::vec1;
+
+    use super::*;
+
+    fn setup(disk_limit: Option<u64>) -> TieredState {
+        setup_with(TieredStateParameters {
+            disk_limit,
+            ..Default::default()
+        })
+    }
+
+    fn setup_with(params: TieredStateParameters) -> TieredState {
+        let mut state = TieredState::new(
+            "tiered_test",
+            &PersistenceParameters {
+                storage_dir: Some(std::env::temp_dir()),
+                ..Default::default()
+            },
+            params,
+        )
+        .unwrap();
+        state.add_index(Index::hash_map(vec![0]), Some(vec![Tag::new(0)]));
+        for i in 0..10 {
+            state.mark_filled(KeyComparison::Equal(vec1![i.into()]), Tag::new(0));
+            let mut records: Records = vec![(vec![i.into(), "a".into()], true)].into();
+            state.process_records(&mut records, None, None).unwrap();
+        }
+        state
+    }
+
+    fn lookup(state: &TieredState, key: i32) -> Option<Vec<Vec<DfValue>>> {
+        match state.lookup(&[0], &PointKey::Single(key.into())) {
+            LookupResult::Some(rows) => Some(rows.into_iter().map(|r| r.into_owned()).collect()),
+            LookupResult::Missing => None,
+        }
+    }
+
+    #[test]
+    fn spilled_keys_stay_filled() {
+        let mut state = setup(None);
+        let res = state.evict_bytes(usize::MAX).unwrap();
+        assert!(res.keys_evicted.is_empty());
+        assert!(res.bytes_freed > 0);
+        assert_eq!(state.memory.row_count(), 0);
+        assert_eq!(state.row_count(), 10);
+
+        for i in 0..10 {
+            assert_eq!(lookup(&state, i), Some(vec![vec![i.into(), "a".into()]]));
+        }
+    }
+
+    #[test]
+    fn writes_to_spilled_keys() {
+        let mut state = setup(None);
+        state.evict_bytes(usize::MAX).unwrap();
+
+        let mut records: Records = vec![
+            (vec![1.into(), "b".into()], true),
+            (vec![2.into(), "a".into()], false),
+            (vec![42.into(), "a".into()], true),
+        ]
+        .into();
+        state.process_records(&mut records, None, None).unwrap();
+        // The write to a hole is dropped, but the writes to spilled keys are kept
+        assert_eq!(records.len(), 2);
+
+        let mut rows = lookup(&state, 1).unwrap();
+        rows.sort();
+        assert_eq!(
+            rows,
+            vec![vec![1.into(), "a".into()], vec![1.into(), "b".into()]]
+        );
+        assert_eq!(lookup(&state, 2), Some(vec![]));
+        assert_eq!(lookup(&state, 42), None);
+    }
+
+    #[test]
+    fn duplicate_rows_in_spilled_keys() {
+        let mut state = setup(None);
+        state.evict_bytes(usize::MAX).unwrap();
+        let bytes = state.disk_bytes;
+
+        let row = vec![DfValue::from(1), "a".into()];
+        let mut records: Records = vec![(row.clone(), true)].into();
+        state.process_records(&mut records, None, None).unwrap();
+        assert!(state.disk_bytes > bytes);
+        assert_eq!(lookup(&state, 1), Some(vec![row.clone(), row.clone()]));
+
+        let mut records: Records = vec![(row.clone(), false)].into();
+        state.process_records(&mut records, None, None).unwrap();
+        assert_eq!(state.disk_bytes, bytes);
+        assert_eq!(lookup(&state, 1), Some(vec![row]));
+        assert_eq!(state.row_count(), 10);
+    }
+
+    #[test]
+    fn range_indices_prevent_spilling() {
+        let mut state = setup(None);
+        state.evict_bytes(usize::MAX).unwrap();
+        state.add_index(Index::btree_map(vec![1]), Some(vec![Tag::new(1)]));
+        assert!(state.spilled[0].is_empty());
+        assert_eq!(state.memory.row_count(), 10);
+
+        state.evict_bytes(usize::MAX);
+        assert!(state.spilled[0].is_empty());
+    }
+
+    #[test]
+    fn looked_up_keys_are_promoted() {
+        let mut state = setup(None);
+        state.evict_bytes(usize::MAX).unwrap();
+        assert!(lookup(&state, 3).is_some());
+
+        state
+            .process_records(&mut Records::default(), None, None)
+            .unwrap();
+        assert_eq!(state.memory.row_count(), 1);
+        assert!(!state.spilled[0].contains_key(&vec![DfValue::from(3)]));
+        assert_eq!(lookup(&state, 3), Some(vec![vec![3.into(), "a".into()]]));
+    }
+
+    #[test]
+    fn disk_limit_evicts() {
+        let mut state = setup(Some(0));
+        let res = state.evict_bytes(usize::MAX).unwrap();
+        assert_eq!(res.keys_evicted.len(), 10);
+        assert_eq!(state.row_count(), 0);
+        assert_eq!(lookup(&state, 1), None);
+    }
+
+    #[test]
+    fn writes_over_disk_limit_promote() {
+        let mut state = setup(None);
+        state.evict_bytes(usize::MAX).unwrap();
+        state.params.disk_limit = Some(0);
+
+        let mut records: Records = vec![(vec![1.into(), "b".into()], true)].into();
+        state.process_records(&mut records, None, None).unwrap();
+        assert_eq!(records.len(), 1);
+        assert!(!state.spilled[0].contains_key(&vec![DfValue::from(1)]));
+        assert_eq!(state.memory.row_count(), 2);
+        assert_eq!(state.row_count(), 11);
+    }
+
+    #[test]
+    fn small_states_evict() {
+        let mut state = setup_with(TieredStateParameters {
+            disk_limit: None,
+            min_memory_bytes: u64::MAX,
+        });
+        let res = state.evict_bytes(usize::MAX).unwrap();
+        assert!(!res.keys_evicted.is_empty());
+        assert!(state.spilled[0].is_empty());
+    }
+
+    #[test]
+    fn mark_hole_forgets_spilled_key() {
+        let mut state = setup(None);
+        state.evict_bytes(usize::MAX).unwrap();
+        state.mark_hole(&KeyComparison::Equal(vec1![1.into()]), Tag::new(0));
+        assert_eq!(lookup(&state, 1), None);
+        assert_eq!(state.row_count(), 9);
+    }
+}
@@ -15,9 +15,13 @@ use crate::error::MsqlSrvError;
 
 pub type AuthData = [u8; 20];
 
-/// The name of the (currently only) supported auth plugin
+/// The name of the auth plugin used to check client passwords ourselves
 pub const AUTH_PLUGIN_NAME: &str = "mysql_native_password";
 
+/// The name of the auth plugin used to receive client passwords in cleartext, so they can be
+/// checked against the upstream database
+pub const CLEAR_PASSWORD_PLUGIN_NAME: &str = "mysql_clear_password";
+
 /// Bytewise-XOR b1 with b2 in-place
 fn xor_slice_mut<const N: usize>(b1: &mut [u8; N], b2: &[u8; N]) {
     b1.iter_mut().zip(b2.iter()).for_each(|(x, y)| *x ^= y);
@@ -67,6 +71,12 @@ pub fn hash_password(password: &[u8], auth_data: &AuthData) -> [u8; 20] {
     res
 }
 
+/// Extract the password from the auth response sent by a client using the `mysql_clear_password`
+/// plugin, which sends the password followed by a NUL byte
+pub fn cleartext_password(auth_response: &[u8]) -> &[u8] {
+    auth_response.strip_suffix(&[0]).unwrap_or(auth_response)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
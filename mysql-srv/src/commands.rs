@@ -17,6 +17,7 @@ pub struct ClientHandshake<'a> {
     pub password: &'a [u8],
     pub database: Option<&'a str>,
     pub auth_plugin_name: Option<&'a str>,
+    pub zstd_compression_level: Option<u8>,
 }
 
 #[derive(Debug)]
@@ -138,6 +139,24 @@ pub fn client_handshake(i: &[u8]) -> IResult<&[u8], ClientHandshake<'_>> {
         (i, None)
     };
 
+    // We don't do anything with connection attributes, but they sit between the auth plugin name
+    // and the zstd compression level, so we still need to skip over them
+    let (i, _) = if capabilities.contains(CapabilityFlags::CLIENT_CONNECT_ATTRS) {
+        opt(|i| {
+            let (i, attrs_len) = lenenc_int(i)?;
+            take(attrs_len as usize)(i)
+        })(i)?
+    } else {
+        (i, None)
+    };
+
+    let (i, zstd_compression_level) =
+        if capabilities.contains(CapabilityFlags::CLIENT_ZSTD_COMPRESSION_ALGORITHM) {
+            opt(le_u8)(i)?
+        } else {
+            (i, None)
+        };
+
     Ok((
         i,
         ClientHandshake {
@@ -148,6 +167,7 @@ pub fn client_handshake(i: &[u8]) -> IResult<&[u8], ClientHandshake<'_>> {
             password,
             database,
             auth_plugin_name,
+            zstd_compression_level,
         },
     ))
 }
@@ -164,8 +184,13 @@ pub enum Command<'a> {
     ComSetOption(&'a [u8]),
     Execute {
         stmt: u32,
+        flags: u8,
         params: &'a [u8],
     },
+    Fetch {
+        stmt: u32,
+        rows: u32,
+    },
     SendLongData {
         stmt: u32,
         param: u16,
@@ -178,9 +203,22 @@ pub enum Command<'a> {
 
 pub fn execute(i: &[u8]) -> IResult<&[u8], Command<'_>> {
     let (i, stmt) = le_u32(i)?;
-    let (i, _flags) = take(1u8)(i)?;
+    let (i, flags) = le_u8(i)?;
     let (i, _iterations) = le_u32(i)?;
-    Ok((&[], Command::Execute { stmt, params: i }))
+    Ok((
+        &[],
+        Command::Execute {
+            stmt,
+            flags,
+            params: i,
+        },
+    ))
+}
+
+pub fn fetch(i: &[u8]) -> IResult<&[u8], Command<'_>> {
+    let (i, stmt) = le_u32(i)?;
+    let (i, rows) = le_u32(i)?;
+    Ok((i, Command::Fetch { stmt, rows }))
 }
 
 pub fn send_long_data(i: &[u8]) -> IResult<&[u8], Command<'_>> {
@@ -223,6 +261,7 @@ pub fn parse(i: &[u8]) -> IResult<&[u8], Command<'_>> {
             Command::ResetStmtData,
         ),
         preceded(tag(&[CommandByte::COM_STMT_EXECUTE as u8]), execute),
+        preceded(tag(&[CommandByte::COM_STMT_FETCH as u8]), fetch),
         preceded(
             tag(&[CommandByte::COM_STMT_SEND_LONG_DATA as u8]),
             send_long_data,
@@ -280,6 +319,29 @@ mod tests {
         assert_eq!(handshake.maxps, 16777216);
     }
 
+    #[test]
+    fn it_parses_handshake_with_zstd_compression_level() {
+        let capabilities = CapabilityFlags::CLIENT_PROTOCOL_41
+            | CapabilityFlags::CLIENT_SECURE_CONNECTION
+            | CapabilityFlags::CLIENT_PLUGIN_AUTH
+            | CapabilityFlags::CLIENT_CONNECT_ATTRS
+            | CapabilityFlags::CLIENT_ZSTD_COMPRESSION_ALGORITHM;
+        let mut data = capabilities.bits().to_le_bytes().to_vec();
+        data.extend_from_slice(&16777216u32.to_le_bytes());
+        data.push(UTF8_GENERAL_CI as u8);
+        data.extend_from_slice(&[0; 23]);
+        data.extend_from_slice(b"jon\0");
+        data.push(0); // password
+        data.extend_from_slice(b"mysql_native_password\0");
+        data.extend_from_slice(&[0x04, 0x01, b'a', 0x01, b'b']); // connection attributes
+        data.push(7);
+
+        let (_, handshake) = client_handshake(&data).unwrap();
+        assert_eq!(handshake.username, "jon");
+        assert_eq!(handshake.auth_plugin_name, Some("mysql_native_password"));
+        assert_eq!(handshake.zstd_compression_level, Some(7));
+    }
+
     #[tokio::test]
     async fn it_parses_request() {
         let data = &[
@@ -317,6 +379,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn it_parses_execute_with_cursor() {
+        let data = &[
+            0x17, 0x01, 0x00, 0x00, 0x00, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x01, 0x03, 0x00,
+            0x2a, 0x00, 0x00, 0x00,
+        ];
+        let (_, cmd) = parse(data).unwrap();
+        assert_eq!(
+            cmd,
+            Command::Execute {
+                stmt: 1,
+                flags: crate::constants::CURSOR_TYPE_READ_ONLY,
+                params: &[0x00, 0x01, 0x03, 0x00, 0x2a, 0x00, 0x00, 0x00],
+            }
+        );
+    }
+
+    #[test]
+    fn it_parses_fetch() {
+        let data = &[0x1c, 0x01, 0x00, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00];
+        let (_, cmd) = parse(data).unwrap();
+        assert_eq!(cmd, Command::Fetch { stmt: 1, rows: 64 });
+    }
+
     #[tokio::test]
     async fn it_parses_change_user() {
         let data = &[
@@ -0,0 +1,293 @@
+//! Compressed framing for the MySQL client/server protocol.
+//!
+//! Once a client negotiates compression in its handshake, every packet sent in either direction
+//! after authentication succeeds is carried inside a compressed packet. A compressed packet has a
+//! 7-byte header (the 3-byte length of its payload, a 1-byte sequence number, and the 3-byte
+//! length of the payload before compression), followed by a payload holding one or more ordinary
+//! packets, headers included. Payloads that are too small to be worth compressing are sent as-is,
+//! with a length before compression of 0.
+//!
+//! Compressed packets have their own sequence numbers, which (like those of ordinary packets)
+//! start over at 0 with each command sent by the client. Both directions share a single counter,
+//! so the first compressed packet of the response follows on from the last one of the command.
+//!
+//! See <https://dev.mysql.com/doc/dev/mysql-server/latest/page_protocol_basic_compression.html>
+
+use std::io::{self, IoSlice, Read, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use metrics::{register_counter, Counter};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Counter: The number of bytes of packets sent or received by clients using protocol
+/// compression, before compression. Labeled with the `direction` (`sent` or `received`).
+pub const COMPRESSION_UNCOMPRESSED_BYTES: &str = "readyset_mysql_compression.uncompressed_bytes";
+
+/// Counter: The number of bytes actually sent or received by clients using protocol compression,
+/// after compression and including the headers of compressed packets. Labeled with the `direction`
+/// (`sent` or `received`).
+pub const COMPRESSION_COMPRESSED_BYTES: &str = "readyset_mysql_compression.compressed_bytes";
+
+/// The length of the header of a compressed packet
+const HEADER_LEN: usize = 7;
+
+/// The largest payload a single compressed packet can carry
+const MAX_PAYLOAD_LEN: usize = 16_777_215;
+
+/// The zstd compression level to use if the client doesn't ask for one, which matches MySQL's
+/// default
+pub(crate) const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+/// A compression algorithm negotiated with a client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Algorithm {
+    /// zlib, negotiated with `CLIENT_COMPRESS`
+    Zlib,
+    /// zstd at the given compression level, negotiated with `CLIENT_ZSTD_COMPRESSION_ALGORITHM`
+    Zstd { level: i32 },
+}
+
+impl Algorithm {
+    fn compress(self, input: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Algorithm::Zlib => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(input)?;
+                encoder.finish()
+            }
+            Algorithm::Zstd { level } => zstd::bulk::compress(input, level),
+        }
+    }
+
+    fn decompress(self, input: &[u8], len: usize) -> io::Result<Vec<u8>> {
+        let output = match self {
+            Algorithm::Zlib => {
+                // Read at most one byte more than we expect, so that a packet that decompresses
+                // to more than it claims is caught below without decompressing all of it
+                let mut output = Vec::with_capacity(len);
+                ZlibDecoder::new(input)
+                    .take(len as u64 + 1)
+                    .read_to_end(&mut output)?;
+                output
+            }
+            Algorithm::Zstd { .. } => zstd::bulk::decompress(input, len)?,
+        };
+
+        if output.len() != len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "compressed packet decompressed to {} bytes, expected {len}",
+                    output.len()
+                ),
+            ));
+        }
+        Ok(output)
+    }
+}
+
+/// Create the two halves of a compressed connection with a client, using the given algorithm.
+///
+/// Payloads shorter than `threshold` bytes are sent uncompressed.
+pub(crate) fn new(algorithm: Algorithm, threshold: usize) -> (Compressor, Decompressor) {
+    let seq = Arc::new(AtomicU8::new(0));
+    let (uncompressed_sent, compressed_sent) = byte_counters("sent");
+    let (uncompressed_received, compressed_received) = byte_counters("received");
+    (
+        Compressor {
+            algorithm,
+            threshold,
+            seq: seq.clone(),
+            buf: Vec::new(),
+            uncompressed_bytes: uncompressed_sent,
+            compressed_bytes: compressed_sent,
+        },
+        Decompressor {
+            algorithm,
+            seq,
+            pending: Vec::new(),
+            pos: 0,
+            uncompressed_bytes: uncompressed_received,
+            compressed_bytes: compressed_received,
+        },
+    )
+}
+
+/// Returns the counters of bytes before and after compression for the given direction
+fn byte_counters(direction: &'static str) -> (Counter, Counter) {
+    (
+        register_counter!(COMPRESSION_UNCOMPRESSED_BYTES, "direction" => direction),
+        register_counter!(COMPRESSION_COMPRESSED_BYTES, "direction" => direction),
+    )
+}
+
+/// Wraps outgoing packets in compressed packets
+pub(crate) struct Compressor {
+    algorithm: Algorithm,
+    /// Payloads shorter than this are sent uncompressed
+    threshold: usize,
+    /// The sequence number of the next compressed packet, shared with the [`Decompressor`]
+    seq: Arc<AtomicU8>,
+    /// Reusable buffer for the packets being compressed
+    buf: Vec<u8>,
+    uncompressed_bytes: Counter,
+    compressed_bytes: Counter,
+}
+
+impl Compressor {
+    /// Compress the given (already framed) packets, and write them all to `w`
+    pub(crate) async fn write_all<W: AsyncWrite + Unpin>(
+        &mut self,
+        w: &mut W,
+        slices: &[IoSlice<'_>],
+    ) -> io::Result<()> {
+        self.buf.clear();
+        for slice in slices {
+            self.buf.extend_from_slice(slice);
+        }
+
+        let mut out = Vec::with_capacity(self.buf.len() + HEADER_LEN);
+        for chunk in self.buf.chunks(MAX_PAYLOAD_LEN) {
+            let compressed = if chunk.len() >= self.threshold {
+                // Don't bother sending the compressed payload if it didn't get any smaller
+                Some(self.algorithm.compress(chunk)?).filter(|c| c.len() < chunk.len())
+            } else {
+                None
+            };
+            let (payload, uncompressed_len) = match &compressed {
+                Some(compressed) => (&compressed[..], chunk.len()),
+                None => (chunk, 0),
+            };
+
+            out.extend_from_slice(&(payload.len() as u32).to_le_bytes()[..3]);
+            out.push(self.seq.fetch_add(1, Ordering::Relaxed));
+            out.extend_from_slice(&(uncompressed_len as u32).to_le_bytes()[..3]);
+            out.extend_from_slice(payload);
+        }
+
+        self.uncompressed_bytes.increment(self.buf.len() as u64);
+        self.compressed_bytes.increment(out.len() as u64);
+        w.write_all(&out).await
+    }
+}
+
+/// Unwraps the packets in incoming compressed packets
+pub(crate) struct Decompressor {
+    algorithm: Algorithm,
+    /// The sequence number of the next compressed packet, shared with the [`Compressor`]
+    seq: Arc<AtomicU8>,
+    /// The payload of the last compressed packet read
+    pending: Vec<u8>,
+    /// How much of `pending` has already been read
+    pos: usize,
+    uncompressed_bytes: Counter,
+    compressed_bytes: Counter,
+}
+
+impl Decompressor {
+    /// Read decompressed bytes into `buf`, reading the next compressed packet from `r` if all of
+    /// the last one has already been read. Returns 0 once `r` reaches EOF.
+    pub(crate) async fn read<R: AsyncRead + Unpin>(
+        &mut self,
+        r: &mut R,
+        buf: &mut [u8],
+    ) -> io::Result<usize> {
+        while self.pos == self.pending.len() {
+            let mut header = [0u8; HEADER_LEN];
+            match r.read_exact(&mut header).await {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(0),
+                Err(e) => return Err(e),
+            }
+            let [l0, l1, l2, seq, u0, u1, u2] = header;
+            let compressed_len = u32::from_le_bytes([l0, l1, l2, 0]) as usize;
+            let uncompressed_len = u32::from_le_bytes([u0, u1, u2, 0]) as usize;
+            self.seq.store(seq.wrapping_add(1), Ordering::Relaxed);
+
+            let mut payload = vec![0; compressed_len];
+            r.read_exact(&mut payload).await?;
+            self.pending = if uncompressed_len == 0 {
+                payload
+            } else {
+                self.algorithm.decompress(&payload, uncompressed_len)?
+            };
+            self.pos = 0;
+
+            self.compressed_bytes
+                .increment((HEADER_LEN + compressed_len) as u64);
+            self.uncompressed_bytes.increment(self.pending.len() as u64);
+        }
+
+        let n = buf.len().min(self.pending.len() - self.pos);
+        buf[..n].copy_from_slice(&self.pending[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::{PacketReader, PacketWriter};
+
+    async fn round_trip(algorithm: Algorithm) {
+        let packets = vec![vec![1u8; 10], vec![2u8; 1000], vec![3u8; 100_000]];
+
+        let (compressor, _) = new(algorithm, 50);
+        let mut written = Vec::new();
+        let mut writer = PacketWriter::new(&mut written);
+        writer.enable_compression(compressor);
+        for packet in &packets {
+            writer.write_packet(packet).await.unwrap();
+        }
+        writer.flush().await.unwrap();
+        drop(writer);
+
+        // The first packet is too small to be worth compressing
+        assert_eq!(&written[4..7], &[0, 0, 0]);
+        assert_eq!(&written[7..11], &[10, 0, 0, 0]);
+
+        let (_, decompressor) = new(algorithm, 50);
+        let mut reader = PacketReader::new(&written[..]);
+        reader.enable_compression(decompressor);
+        for (seq, packet) in packets.iter().enumerate() {
+            let (read_seq, read) = reader.next().await.unwrap().unwrap();
+            assert_eq!(read_seq as usize, seq);
+            assert_eq!(&read[..], &packet[..]);
+        }
+        // Larger packets compress well enough to be sent compressed
+        assert!(written.len() < packets.iter().map(|p| p.len()).sum());
+        assert!(reader.next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn zlib_round_trip() {
+        round_trip(Algorithm::Zlib).await
+    }
+
+    #[tokio::test]
+    async fn zstd_round_trip() {
+        round_trip(Algorithm::Zstd {
+            level: DEFAULT_ZSTD_LEVEL,
+        })
+        .await
+    }
+
+    #[test]
+    fn decompressed_length_mismatch() {
+        for algorithm in [
+            Algorithm::Zlib,
+            Algorithm::Zstd {
+                level: DEFAULT_ZSTD_LEVEL,
+            },
+        ] {
+            let compressed = algorithm.compress(&[0u8; 1000]).unwrap();
+            assert_eq!(algorithm.decompress(&compressed, 1000).unwrap().len(), 1000);
+            algorithm.decompress(&compressed, 10).unwrap_err();
+            algorithm.decompress(&compressed, 2000).unwrap_err();
+        }
+    }
+}
@@ -53,8 +53,13 @@ pub const CAN_HANDLE_EXPIRED_PASSWORDS: u32 = 0x00400000;
 pub const SESSION_TRACK: u32 = 0x00800000;
 /// Can send OK after a Text Resultset.
 pub const DEPRECATE_EOF: u32 = 0x01000000;
+/// Client supports compressing packets with zstd.
+pub const ZSTD_COMPRESSION_ALGORITHM: u32 = 0x04000000;
 /// Client supports plugin authentication
 pub const CLIENT_PLUGIN_AUTH: u32 = 0x00080000;
 
 pub const SSL_VERIFY_SERVER_CERT: u32 = 0x40000000;
 pub const REMEMBER_OPTIONS: u32 = 0x80000000;
+
+/// `COM_STMT_EXECUTE` flag asking for a read-only cursor to be opened on the statement's results
+pub const CURSOR_TYPE_READ_ONLY: u8 = 0x01;
@@ -169,9 +169,13 @@ use std::collections::HashMap;
 use std::io;
 use std::sync::Arc;
 
-use constants::{CLIENT_PLUGIN_AUTH, CONNECT_WITH_DB, PROTOCOL_41, RESERVED, SECURE_CONNECTION};
+use constants::{
+    CLIENT_PLUGIN_AUTH, COMPRESS, CONNECT_WITH_DB, CURSOR_TYPE_READ_ONLY, MULTI_RESULTS,
+    MULTI_STATEMENTS, PROTOCOL_41, RESERVED, SECURE_CONNECTION, ZSTD_COMPRESSION_ALGORITHM,
+};
 use error::{other_error, OtherErrorKind};
 use mysql_common::constants::CapabilityFlags;
+use nom_sql::Dialect;
 use readyset_adapter_types::{DeallocateId, ParsedCommand};
 use readyset_data::DfType;
 use tokio::io::{AsyncRead, AsyncWrite};
@@ -179,13 +183,18 @@ use tokio::net;
 use tracing::{debug, info, trace};
 use writers::write_err;
 
-use crate::authentication::{generate_auth_data, hash_password, AUTH_PLUGIN_NAME};
+use crate::authentication::{
+    cleartext_password, generate_auth_data, hash_password, AUTH_PLUGIN_NAME,
+    CLEAR_PASSWORD_PLUGIN_NAME,
+};
 use crate::commands::change_user;
 pub use crate::myc::constants::{ColumnFlags, ColumnType, StatusFlags};
+use crate::resultset::Cursor;
 pub use crate::writers::prepare_column_definitions;
 
 mod authentication;
 mod commands;
+mod compression;
 mod constants;
 pub mod error;
 mod errorcodes;
@@ -232,10 +241,14 @@ impl From<&mysql_async::Column> for Column {
     }
 }
 
+pub use crate::compression::{COMPRESSION_COMPRESSED_BYTES, COMPRESSION_UNCOMPRESSED_BYTES};
 pub use crate::error::MsqlSrvError;
 pub use crate::errorcodes::ErrorKind;
 pub use crate::params::{ParamParser, ParamValue, Params};
-pub use crate::resultset::{InitWriter, QueryResultWriter, RowWriter, StatementMetaWriter};
+pub use crate::resultset::{
+    ColumnWriter, InitWriter, QueryResultWriter, RowEncoder, RowSource, RowWriter,
+    StatementMetaWriter,
+};
 pub use crate::value::{ToMySqlValue, Value, ValueInner};
 
 /// A simple wrapper response to allow either an io::Result or a ParsedCommand to be returned
@@ -310,6 +323,42 @@ pub trait MySqlShim<W: AsyncWrite + Unpin + Send> {
     fn require_authentication(&self) -> bool {
         true
     }
+
+    /// Return true if clients should be authenticated with
+    /// [`authenticate_upstream`](MySqlShim::authenticate_upstream) rather than with
+    /// [`password_for_username`](MySqlShim::password_for_username).
+    ///
+    /// Since checking credentials against the upstream needs the client's password in cleartext,
+    /// clients are asked to switch to the `mysql_clear_password` authentication plugin.
+    fn uses_upstream_authentication(&self) -> bool {
+        false
+    }
+
+    /// Check the given client credentials against the upstream database, returning true if they
+    /// are valid.
+    async fn authenticate_upstream(&mut self, _username: &str, _password: &[u8]) -> bool {
+        false
+    }
+
+    /// Return true if clients may be authenticated with
+    /// [`authenticate_upstream`](MySqlShim::authenticate_upstream) even though their connections
+    /// are unencrypted, which sends their passwords over the network in cleartext. Otherwise,
+    /// clients are refused when
+    /// [`uses_upstream_authentication`](MySqlShim::uses_upstream_authentication) returns true,
+    /// since connections to this server are never encrypted.
+    fn allows_unencrypted_upstream_authentication(&self) -> bool {
+        false
+    }
+
+    /// Called once the response to each command has been sent to the client, after which the
+    /// client is idle until it sends its next command.
+    fn on_command_finished(&mut self) {}
+
+    /// Return the size, in bytes, below which packets are sent uncompressed to clients that
+    /// negotiate protocol compression, or [`None`] to not offer compression to clients at all
+    fn compression_threshold(&self) -> Option<usize> {
+        None
+    }
 }
 
 /// Stores a preencoded result schema for a prepared MySQL statement
@@ -317,7 +366,7 @@ pub struct CachedSchema {
     /// The MySQL schema
     pub mysql_schema: Vec<Column>,
     /// Associated ReadySet types
-    pub column_types: Vec<DfType>,
+    pub column_types: Arc<[DfType]>,
     /// Preencoded schema as a byte dump
     pub preencoded_schema: Arc<[u8]>,
 }
@@ -334,6 +383,9 @@ pub struct MySqlIntermediary<B, R: AsyncRead + Unpin, W: AsyncWrite + Unpin> {
     enable_statement_logging: bool,
     /// The capabilities of the client
     client_capabilities: CapabilityFlags,
+    /// Whether the client has multi-statement queries enabled, either in the handshake or with
+    /// `COM_SET_OPTION`
+    multi_statements: bool,
     /// Auth data sent to client
     auth_data: [u8; 20],
 }
@@ -384,10 +436,22 @@ struct StatementData {
     long_data: HashMap<u16, Vec<u8>>,
     bound_types: Vec<(myc::constants::ColumnType, bool)>,
     params: u16,
+    /// The read-only cursor opened by the last execution of the statement, if it hasn't been
+    /// fetched to the end or reset yet
+    cursor: Option<Cursor>,
 }
 
-const CAPABILITIES: u32 =
-    PROTOCOL_41 | SECURE_CONNECTION | RESERVED | CLIENT_PLUGIN_AUTH | CONNECT_WITH_DB;
+const CAPABILITIES: u32 = PROTOCOL_41
+    | SECURE_CONNECTION
+    | RESERVED
+    | CLIENT_PLUGIN_AUTH
+    | CONNECT_WITH_DB
+    | MULTI_STATEMENTS
+    | MULTI_RESULTS;
+
+/// The value of the option sent with `COM_SET_OPTION` to enable multi-statement queries. Any other
+/// value (`MYSQL_OPTION_MULTI_STATEMENTS_OFF`) disables them.
+const MYSQL_OPTION_MULTI_STATEMENTS_ON: u16 = 0;
 
 impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
     MySqlIntermediary<B, R, W>
@@ -409,6 +473,7 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
             schema_cache: HashMap::new(),
             enable_statement_logging,
             client_capabilities: CapabilityFlags::empty(),
+            multi_statements: false,
             auth_data: [0; 20],
         };
         if let (true, database) = mi.init().await? {
@@ -442,10 +507,15 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
         init_packet.extend_from_slice(&[0x08, 0x00, 0x00, 0x00]); // TODO: connection ID
         init_packet.extend_from_slice(&auth_data[..8]);
         init_packet.push(0);
-        init_packet.extend_from_slice(&CAPABILITIES.to_le_bytes()[..2]);
+        let capabilities = if self.shim.compression_threshold().is_some() {
+            CAPABILITIES | COMPRESS | ZSTD_COMPRESSION_ALGORITHM
+        } else {
+            CAPABILITIES
+        };
+        init_packet.extend_from_slice(&capabilities.to_le_bytes()[..2]);
         init_packet.extend_from_slice(&[0x21]); // UTF8_GENERAL_CI
         init_packet.extend_from_slice(&[0x00, 0x00]); // status flags
-        init_packet.extend_from_slice(&CAPABILITIES.to_le_bytes()[2..]);
+        init_packet.extend_from_slice(&capabilities.to_le_bytes()[2..]);
         // We will add a \0 byte below so we need to account for that when sending the length, since
         // rust strings don't add the null terminator
         init_packet.extend_from_slice(&[(auth_data.len() + 1) as u8]);
@@ -490,12 +560,54 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
         self.writer.set_seq(seq + 1);
 
         self.client_capabilities = handshake.capabilities;
+        self.multi_statements = handshake
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_MULTI_STATEMENTS);
         let username = handshake.username.to_owned();
         let password = handshake.password.to_vec();
         let database = handshake.database.map(String::from);
         let client_auth_plugin = handshake.auth_plugin_name.map(|s| s.to_owned());
+        let compression = if handshake
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_COMPRESS)
+        {
+            Some(compression::Algorithm::Zlib)
+        } else if handshake
+            .capabilities
+            .contains(CapabilityFlags::CLIENT_ZSTD_COMPRESSION_ALGORITHM)
+        {
+            Some(compression::Algorithm::Zstd {
+                level: handshake
+                    .zstd_compression_level
+                    .map(i32::from)
+                    .unwrap_or(compression::DEFAULT_ZSTD_LEVEL),
+            })
+        } else {
+            None
+        };
 
-        let handshake_password = if client_auth_plugin.iter().all(|apn| apn != AUTH_PLUGIN_NAME)
+        let upstream_auth = self.shim.uses_upstream_authentication();
+        if upstream_auth
+            && self.shim.require_authentication()
+            && !self.shim.allows_unencrypted_upstream_authentication()
+        {
+            debug!(%username, "Refusing upstream authentication over an unencrypted connection");
+            writers::write_err(
+                ErrorKind::ER_NOT_SUPPORTED_AUTH_MODE,
+                b"Authenticating against the upstream database requires an encrypted connection",
+                &mut self.writer,
+            )
+            .await?;
+            self.writer.flush().await?;
+            return Ok((false, database));
+        }
+        let auth_plugin = if upstream_auth {
+            CLEAR_PASSWORD_PLUGIN_NAME
+        } else {
+            AUTH_PLUGIN_NAME
+        };
+
+        let handshake_password = if client_auth_plugin.iter().all(|apn| apn != auth_plugin)
             // Some clients (at the very least certain versions of PHP's MySQL PDO library) send an
             // empty password response in the initial handshake, even if the auth plugin is set and
             // correct. We want to send a switch-authentication request in that case too
@@ -523,41 +635,27 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
                 "Client offered incorrect authentication plugin, sending switch request",
             );
 
-            let mut auth_switch_request_packet =
-                Vec::with_capacity(1 + AUTH_PLUGIN_NAME.len() + 1 + auth_data.len() + 1);
-            auth_switch_request_packet.push(0xfe);
-            auth_switch_request_packet.extend_from_slice(AUTH_PLUGIN_NAME.as_bytes());
-            auth_switch_request_packet.push(0);
-            auth_switch_request_packet.extend_from_slice(&auth_data);
-            auth_switch_request_packet.push(0);
-            self.writer
-                .write_packet(&auth_switch_request_packet)
-                .await?;
-            self.writer.flush().await?;
-
-            let (seq, auth_switch_response) = self.reader.next().await?.ok_or_else(|| {
-                io::Error::new(
-                    io::ErrorKind::ConnectionAborted,
-                    "peer terminated connection",
-                )
-            })?;
-            self.writer.set_seq(seq + 1);
-
-            auth_switch_response.to_vec()
+            self.switch_auth_plugin(auth_plugin).await?
         } else {
             password
         };
 
-        let auth_success = !self.shim.require_authentication()
-            || self
-                .shim
+        let auth_success = if !self.shim.require_authentication() {
+            true
+        } else if upstream_auth {
+            self.shim
+                .authenticate_upstream(&username, cleartext_password(&handshake_password))
+                .await
+        } else {
+            self.shim
                 .password_for_username(&username)
                 .map_or(false, |password| {
                     let expected = hash_password(&password, &auth_data);
                     let actual = handshake_password.as_slice();
                     trace!(?expected, ?actual);
                     expected == actual
-                });
+                })
+        };
 
         if auth_success {
             debug!(%username, "Successfully authenticated client");
@@ -573,9 +671,45 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
         }
         self.writer.flush().await?;
 
+        // Everything after the OK packet for a successful authentication is compressed, if the
+        // client asked for it (and we offered it)
+        if let (true, Some(algorithm), Some(threshold)) =
+            (auth_success, compression, self.shim.compression_threshold())
+        {
+            debug!(?algorithm, "Enabling protocol compression");
+            let (compressor, decompressor) = compression::new(algorithm, threshold);
+            self.writer.enable_compression(compressor);
+            self.reader.enable_compression(decompressor);
+        }
+
         Ok((auth_success, database))
     }
 
+    /// Ask the client to switch to the given authentication plugin, and return its response
+    async fn switch_auth_plugin(&mut self, plugin_name: &str) -> Result<Vec<u8>, io::Error> {
+        let mut auth_switch_request_packet =
+            Vec::with_capacity(1 + plugin_name.len() + 1 + self.auth_data.len() + 1);
+        auth_switch_request_packet.push(0xfe);
+        auth_switch_request_packet.extend_from_slice(plugin_name.as_bytes());
+        auth_switch_request_packet.push(0);
+        auth_switch_request_packet.extend_from_slice(&self.auth_data);
+        auth_switch_request_packet.push(0);
+        self.writer
+            .write_packet(&auth_switch_request_packet)
+            .await?;
+        self.writer.flush().await?;
+
+        let (seq, auth_switch_response) = self.reader.next().await?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "peer terminated connection",
+            )
+        })?;
+        self.writer.set_seq(seq + 1);
+
+        Ok(auth_switch_response.to_vec())
+    }
+
     async fn run(mut self) -> Result<(), io::Error> {
         use crate::commands::Command;
 
@@ -613,6 +747,45 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
                     let username = change_user.username.to_owned();
                     let authpassword = change_user.password.to_vec();
 
+                    if self.shim.uses_upstream_authentication() {
+                        let database = change_user.database.unwrap_or_default().to_owned();
+                        // We need the new user's password in cleartext to check it against the
+                        // upstream, so ask the client for it again if it sent anything else
+                        let auth_response =
+                            if change_user.auth_plugin_name == CLEAR_PASSWORD_PLUGIN_NAME {
+                                authpassword
+                            } else {
+                                self.switch_auth_plugin(CLEAR_PASSWORD_PLUGIN_NAME).await?
+                            };
+                        let password = cleartext_password(&auth_response);
+
+                        if self.shim.authenticate_upstream(&username, password).await
+                            && self
+                                .shim
+                                .on_change_user(
+                                    &username,
+                                    &String::from_utf8_lossy(password),
+                                    &database,
+                                )
+                                .await
+                                .is_ok()
+                        {
+                            debug!("Successfully authenticated client");
+                            writers::write_ok_packet(&mut self.writer, 0, 0, StatusFlags::empty())
+                                .await?;
+                        } else {
+                            debug!("Upstream rejected credentials");
+                            writers::write_err(
+                                ErrorKind::ER_ACCESS_DENIED_ERROR,
+                                format!("Access denied for user {}", username).as_bytes(),
+                                &mut self.writer,
+                            )
+                            .await?;
+                        }
+                        self.writer.flush().await?;
+                        continue;
+                    }
+
                     if change_user.auth_plugin_name != AUTH_PLUGIN_NAME {
                         // This should never happen, as we already accepted a connection using
                         // AUTH_PLUGIN_NAME
@@ -682,46 +855,38 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
                     self.writer.flush().await?;
                 }
                 Command::Query(q) => {
-                    let w = QueryResultWriter::new(&mut self.writer, false);
-                    let res = self
-                        .shim
-                        .on_query(
-                            ::std::str::from_utf8(q)
-                                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
-                            w,
-                        )
-                        .await;
-
-                    match res {
-                        QueryResultsResponse::Command(cmd) => {
-                            match cmd {
-                                ParsedCommand::Deallocate(dealloc_id) => {
-                                    if DeallocateId::All == dealloc_id {
-                                        // mysql doesn't allow 'deallocate all',
-                                        // should probably be a nom error.
-                                        writers::write_err(
-                                            ErrorKind::ER_PARSE_ERROR,
-                                            "Unsupported 'DEALLOCATE PREPARE ALL'".as_bytes(),
-                                            &mut self.writer,
-                                        )
-                                        .await?;
-                                    } else {
-                                        self.shim.on_close(dealloc_id.clone()).await;
-                                        if let DeallocateId::Numeric(id) = dealloc_id {
-                                            stmts.remove(&id);
-                                        }
-                                        writers::write_ok_packet(
-                                            &mut self.writer,
-                                            0,
-                                            0,
-                                            StatusFlags::empty(),
-                                        )
-                                        .await?;
-                                    }
-                                }
+                    let query = ::std::str::from_utf8(q)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    let statements = if self.multi_statements {
+                        nom_sql::split_statements(Dialect::MySQL, query)
+                    } else {
+                        vec![]
+                    };
+
+                    if statements.len() > 1 {
+                        // Run each statement separately, so that they can each be routed on their
+                        // own, and send their resultsets one after another. As in MySQL, an error
+                        // in one statement stops the rest from running.
+                        let last = statements.len() - 1;
+                        for (i, statement) in statements.into_iter().enumerate() {
+                            let mut errored = false;
+                            let w = QueryResultWriter::for_statement(
+                                &mut self.writer,
+                                i < last,
+                                &mut errored,
+                            );
+                            let res = self.shim.on_query(statement, w).await;
+                            let command_errored = self
+                                .handle_query_response(res, i < last, &mut stmts)
+                                .await?;
+                            if errored || command_errored {
+                                break;
                             }
                         }
-                        QueryResultsResponse::IoResult(result) => result?,
+                    } else {
+                        let w = QueryResultWriter::new(&mut self.writer, false);
+                        let res = self.shim.on_query(query, w).await;
+                        self.handle_query_response(res, false, &mut stmts).await?;
                     }
                 }
                 Command::Prepare(q) => {
@@ -739,33 +904,82 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
                         .await?;
                 }
                 Command::ResetStmtData(stmt) => {
-                    stmts
-                        .get_mut(&stmt)
-                        .ok_or_else(|| {
-                            io::Error::new(
-                                io::ErrorKind::InvalidData,
-                                format!("got reset data packet for unknown statement {}", stmt),
-                            )
-                        })?
-                        .long_data
-                        .clear();
+                    let state = stmts.get_mut(&stmt).ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("got reset data packet for unknown statement {}", stmt),
+                        )
+                    })?;
+                    state.long_data.clear();
+                    state.cursor = None;
                     writers::write_ok_packet(&mut self.writer, 0, 0, StatusFlags::empty()).await?;
                 }
-                Command::Execute { stmt, params } => {
+                Command::Execute {
+                    stmt,
+                    flags,
+                    params,
+                } => {
                     let state = stmts.get_mut(&stmt).ok_or_else(|| {
                         io::Error::new(
                             io::ErrorKind::InvalidData,
                             format!("asked to execute unknown statement {}", stmt),
                         )
                     })?;
+                    // Executing the statement again closes any cursor opened by the last execution
+                    let mut cursor = None;
                     {
                         let params = params::ParamParser::new(params, state);
-                        let w = QueryResultWriter::new(&mut self.writer, true);
+                        let w = if flags & CURSOR_TYPE_READ_ONLY != 0 {
+                            QueryResultWriter::for_cursor(&mut self.writer, &mut cursor)
+                        } else {
+                            QueryResultWriter::new(&mut self.writer, true)
+                        };
                         self.shim
                             .on_execute(stmt, params, w, &mut self.schema_cache)
                             .await?;
                     }
                     state.long_data.clear();
+                    state.cursor = cursor;
+                }
+                Command::Fetch { stmt, rows } => {
+                    let state = stmts.get_mut(&stmt).ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("asked to fetch from unknown statement {}", stmt),
+                        )
+                    })?;
+                    match state.cursor.as_mut() {
+                        Some(cursor) => match cursor.fetch(rows as usize) {
+                            Ok(fetched) => {
+                                for row in fetched {
+                                    self.writer.enqueue_packet(row);
+                                }
+                                let status = cursor.status_flags();
+                                if cursor.is_exhausted() {
+                                    state.cursor = None;
+                                }
+                                writers::write_eof_packet(&mut self.writer, status).await?;
+                            }
+                            // Producing the rows failed, which ends the resultset
+                            Err(e) => {
+                                state.cursor = None;
+                                writers::write_err(
+                                    ErrorKind::ER_UNKNOWN_ERROR,
+                                    e.to_string().as_bytes(),
+                                    &mut self.writer,
+                                )
+                                .await?;
+                            }
+                        },
+                        None => {
+                            writers::write_err(
+                                ErrorKind::ER_STMT_HAS_NO_OPEN_CURSOR,
+                                format!("The statement ({}) has no open cursor.", stmt).as_bytes(),
+                                &mut self.writer,
+                            )
+                            .await?;
+                        }
+                    }
                 }
                 Command::SendLongData { stmt, param, data } => {
                     stmts
@@ -814,12 +1028,12 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
                     writers::write_ok_packet(&mut self.writer, 0, 0, StatusFlags::empty()).await?;
                     self.writer.flush().await?;
                 }
-                Command::ComSetOption(_) => {
-                    // ReadySet already support multi-statement support for the MySQL protocol, so
-                    // we can simply respond with ok. We parse an incoming query as multiple single
-                    // statements, so failure with any one will be forwarded to the underlying
-                    // database as a single statement, meaning that the underlying database does
-                    // not need to have multi-statement support enabled for this connection.
+                Command::ComSetOption(option) => {
+                    // Multi-statement queries are split up and run one statement at a time, so
+                    // the upstream database never needs multi-statement support enabled for this
+                    // connection; we only need to keep track of whether to split queries.
+                    self.multi_statements =
+                        option.starts_with(&MYSQL_OPTION_MULTI_STATEMENTS_ON.to_le_bytes());
                     writers::write_ok_packet(&mut self.writer, 0, 0, StatusFlags::empty()).await?;
                     self.writer.flush().await?;
                 }
@@ -834,8 +1048,47 @@ impl<B: MySqlShim<W> + Send, R: AsyncRead + Unpin, W: AsyncWrite + Unpin + Send>
             }
 
             self.writer.flush().await?;
+            self.shim.on_command_finished();
         }
 
         Ok(())
     }
+
+    /// Finish responding to a `COM_QUERY` (or one statement of a multi-statement `COM_QUERY`)
+    /// after the shim has handled it, by running any command it returned.
+    ///
+    /// Returns true if an error was sent to the client by this method.
+    async fn handle_query_response(
+        &mut self,
+        response: QueryResultsResponse,
+        more_statements: bool,
+        stmts: &mut HashMap<u32, StatementData>,
+    ) -> io::Result<bool> {
+        match response {
+            QueryResultsResponse::Command(ParsedCommand::Deallocate(dealloc_id)) => {
+                if DeallocateId::All == dealloc_id {
+                    // mysql doesn't allow 'deallocate all',
+                    // should probably be a nom error.
+                    writers::write_err(
+                        ErrorKind::ER_PARSE_ERROR,
+                        "Unsupported 'DEALLOCATE PREPARE ALL'".as_bytes(),
+                        &mut self.writer,
+                    )
+                    .await?;
+                    return Ok(true);
+                }
+
+                self.shim.on_close(dealloc_id.clone()).await;
+                if let DeallocateId::Numeric(id) = dealloc_id {
+                    stmts.remove(&id);
+                }
+                let mut status = StatusFlags::empty();
+                status.set(StatusFlags::SERVER_MORE_RESULTS_EXISTS, more_statements);
+                writers::write_ok_packet(&mut self.writer, 0, 0, status).await?;
+            }
+            QueryResultsResponse::IoResult(result) => result?,
+        }
+
+        Ok(false)
+    }
 }
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
+use crate::compression::{Compressor, Decompressor};
 use crate::error::{other_error, OtherErrorKind};
 use crate::resultset::{MAX_POOL_ROWS, MAX_POOL_ROW_CAPACITY};
 
@@ -15,6 +16,9 @@ pub struct PacketWriter<W> {
 
     /// Reusable packets
     preallocated: Vec<QueuedPacket>,
+
+    /// Set once the client has negotiated protocol compression
+    compressor: Option<Compressor>,
 }
 
 /// Type for packets being enqueued in the packet writer.
@@ -55,6 +59,19 @@ async fn write_all_vectored<'a, W: AsyncWrite + Unpin>(
     }
 }
 
+/// Write the given packets to `w`, wrapping them in compressed packets first if compression is
+/// enabled
+async fn write_packets<'a, W: AsyncWrite + Unpin>(
+    w: &'a mut W,
+    compressor: &mut Option<Compressor>,
+    slices: &'a mut [IoSlice<'a>],
+) -> io::Result<()> {
+    match compressor {
+        Some(compressor) => compressor.write_all(w, slices).await,
+        None => write_all_vectored(w, slices).await,
+    }
+}
+
 // Gets an IoSlice to each of the packets currently enqueued in `queue`.
 fn queued_packet_slices(queue: &[QueuedPacket]) -> Vec<IoSlice<'_>> {
     if queue.is_empty() {
@@ -82,6 +99,7 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
             w,
             queue: Vec::new(),
             preallocated: Vec::new(),
+            compressor: None,
         }
     }
 
@@ -89,6 +107,11 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
         self.seq = seq;
     }
 
+    /// Wrap all packets written from now on in compressed packets
+    pub fn enable_compression(&mut self, compressor: Compressor) {
+        self.compressor = Some(compressor);
+    }
+
     /// Flushes the writer. This function *must* be called before dropping the internal writer
     /// or writes may be lossed.
     pub async fn flush(&mut self) -> Result<(), tokio::io::Error> {
@@ -131,7 +154,7 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
     pub async fn write_queued_packets(&mut self) -> Result<(), tokio::io::Error> {
         let mut slices = queued_packet_slices(&self.queue);
         if !slices.is_empty() {
-            write_all_vectored(&mut self.w, &mut slices).await?;
+            write_packets(&mut self.w, &mut self.compressor, &mut slices).await?;
             self.return_queued_to_pool();
         }
 
@@ -172,7 +195,7 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
             }
         }
 
-        write_all_vectored(&mut self.w, &mut slices).await?;
+        write_packets(&mut self.w, &mut self.compressor, &mut slices).await?;
         self.return_queued_to_pool();
 
         Ok(())
@@ -207,7 +230,7 @@ impl<W: AsyncWrite + Unpin> PacketWriter<W> {
             IoSlice::new(packet),
         ]);
 
-        write_all_vectored(&mut self.w, &mut slices).await?;
+        write_packets(&mut self.w, &mut self.compressor, &mut slices).await?;
 
         self.seq = self.seq.wrapping_add(1);
         Ok(())
@@ -232,6 +255,8 @@ pub struct PacketReader<R> {
     start: usize,
     remaining: usize,
     r: R,
+    /// Set once the client has negotiated protocol compression
+    decompressor: Option<Decompressor>,
 }
 
 impl<R> PacketReader<R> {
@@ -241,8 +266,14 @@ impl<R> PacketReader<R> {
             start: 0,
             remaining: 0,
             r,
+            decompressor: None,
         }
     }
+
+    /// Unwrap all packets read from now on from compressed packets
+    pub fn enable_compression(&mut self, decompressor: Decompressor) {
+        self.decompressor = Some(decompressor);
+    }
 }
 
 impl<R: AsyncRead + Unpin> PacketReader<R> {
@@ -294,7 +325,10 @@ impl<R: AsyncRead + Unpin> PacketReader<R> {
                         length: new_len,
                     })
                 })?;
-                self.r.read(buf).await?
+                match &mut self.decompressor {
+                    Some(decompressor) => decompressor.read(&mut self.r, buf).await?,
+                    None => self.r.read(buf).await?,
+                }
             };
             self.bytes.truncate(end + read);
             self.remaining = self.bytes.len();
@@ -1,5 +1,5 @@
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io;
 use std::sync::Arc;
 
@@ -13,6 +13,12 @@ use crate::{writers, Column, ErrorKind, StatementData};
 pub(crate) const DEFAULT_ROW_CAPACITY: usize = 4096;
 pub(crate) const MAX_POOL_ROW_CAPACITY: usize = DEFAULT_ROW_CAPACITY * 4;
 pub(crate) const MAX_POOL_ROWS: usize = 4096;
+/// The most encoded row data written up front that a read-only cursor holds back from the client.
+/// Resultsets whose rows go over this are sent to the client without a cursor, which the protocol
+/// allows for any statement: clients check for `SERVER_STATUS_CURSOR_EXISTS` to see if a cursor
+/// was opened. Rows produced lazily by a [`RowSource`] don't count towards this, since they're
+/// never held in memory.
+pub(crate) const MAX_CURSOR_BYTES: usize = 16 * 1024 * 1024;
 
 /// Convenience type for responding to a client `USE <db>` command.
 pub struct InitWriter<'a, W: AsyncWrite + Unpin> {
@@ -83,6 +89,204 @@ impl<'a, W: AsyncWrite + Unpin + 'a> StatementMetaWriter<'a, W> {
     }
 }
 
+/// Something the values of a row of a resultset can be written to, one column at a time
+pub trait ColumnWriter {
+    /// Write a value to the next column of the current row
+    fn write_col<T>(&mut self, v: T) -> io::Result<()>
+    where
+        T: ToMySqlValue;
+}
+
+/// Encode `v` as the value of column `col` of a row whose data is being written to `row_data`.
+///
+/// In the binary protocol, the row starts with a header and a bitmap of its NULL columns, which
+/// are written along with the first column; `bitmap_idx` is set to where the bitmap begins.
+fn encode_col<T>(
+    row_data: &mut Vec<u8>,
+    columns: &[Column],
+    col: usize,
+    is_bin: bool,
+    bitmap_idx: &mut usize,
+    v: T,
+) -> io::Result<()>
+where
+    T: ToMySqlValue,
+{
+    if !is_bin {
+        return v.to_mysql_text(row_data);
+    }
+
+    if col == 0 {
+        row_data.push(0x00);
+        // leave space for nullmap
+        *bitmap_idx = row_data.len();
+        row_data.resize(row_data.len() + (columns.len() + 7 + 2) / 8, 0);
+    }
+
+    let c = columns.get(col).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "row has more columns than specification",
+        )
+    })?;
+
+    if v.is_null() {
+        if c.colflags.contains(ColumnFlags::NOT_NULL_FLAG) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "given NULL value for NOT NULL column",
+            ));
+        }
+        // https://web.archive.org/web/20170404144156/https://dev.mysql.com/doc/internals/en/null-bitmap.html
+        // NULL-bitmap-byte = ((field-pos + offset) / 8)
+        // NULL-bitmap-bit  = ((field-pos + offset) % 8)
+        let idx = *bitmap_idx + (col + 2) / 8;
+        // Always safe to access `idx` because we allocate sufficient space in advance
+        row_data[idx] |= 1u8 << ((col + 2) % 8);
+        Ok(())
+    } else {
+        v.to_mysql_bin(row_data, c)
+    }
+}
+
+/// Encodes the values of a single row produced by a [`RowSource`]
+pub struct RowEncoder<'a> {
+    columns: &'a [Column],
+    is_bin: bool,
+    row_data: Vec<u8>,
+    /// The index where the null bitmap for the row begins
+    bitmap_idx: usize,
+    /// The next column to write
+    col: usize,
+}
+
+impl<'a> RowEncoder<'a> {
+    fn new(columns: &'a [Column], is_bin: bool, mut row_data: Vec<u8>) -> Self {
+        row_data.reserve(DEFAULT_ROW_CAPACITY);
+        RowEncoder {
+            columns,
+            is_bin,
+            row_data,
+            bitmap_idx: 0,
+            col: 0,
+        }
+    }
+
+    /// The columns of the resultset the row belongs to
+    pub fn columns(&self) -> &'a [Column] {
+        self.columns
+    }
+
+    /// Returns the encoded row, once every column has been written
+    fn finish(self) -> io::Result<Vec<u8>> {
+        if self.col != self.columns.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "row has fewer columns than specification",
+            ));
+        }
+        Ok(self.row_data)
+    }
+}
+
+impl<'a> ColumnWriter for RowEncoder<'a> {
+    fn write_col<T>(&mut self, v: T) -> io::Result<()>
+    where
+        T: ToMySqlValue,
+    {
+        if self.columns.is_empty() {
+            return Ok(());
+        }
+        encode_col(
+            &mut self.row_data,
+            self.columns,
+            self.col,
+            self.is_bin,
+            &mut self.bitmap_idx,
+            v,
+        )?;
+        self.col += 1;
+        Ok(())
+    }
+}
+
+/// The rows of a resultset, produced one at a time as they're sent to the client.
+///
+/// Passing a `RowSource` to [`RowWriter::finish_with_rows`] rather than writing every row up front
+/// means that, when the client opened a read-only cursor on the statement's results, each row is
+/// only produced once the client fetches it with `COM_STMT_FETCH`.
+pub trait RowSource: Send {
+    /// Write the values of the next row to `row`, or return `false` if there are no more rows.
+    ///
+    /// Errors are sent to the client in place of the row, and end the resultset.
+    fn next_row(&mut self, row: &mut RowEncoder<'_>) -> io::Result<bool>;
+}
+
+/// The rows of a resultset held back from the client by a read-only cursor, until the client asks
+/// for them with `COM_STMT_FETCH`
+pub(crate) struct Cursor {
+    /// Encoded rows that were written before the cursor was opened, and haven't been fetched yet
+    rows: VecDeque<Vec<u8>>,
+    /// The total size of `rows`, in bytes
+    bytes: usize,
+    /// Where the rest of the rows are produced from, along with the columns they're encoded for
+    source: Option<(Box<dyn RowSource>, Vec<Column>)>,
+    /// The status flags to send along with each batch of fetched rows
+    status_flags: StatusFlags,
+}
+
+impl Cursor {
+    fn new() -> Self {
+        Cursor {
+            rows: VecDeque::new(),
+            bytes: 0,
+            source: None,
+            status_flags: StatusFlags::empty(),
+        }
+    }
+
+    /// Remove up to `n` rows from the front of the cursor, producing them from the cursor's
+    /// [`RowSource`] once the rows written up front run out
+    pub(crate) fn fetch(&mut self, n: usize) -> io::Result<Vec<Vec<u8>>> {
+        let mut fetched = self
+            .rows
+            .drain(..n.min(self.rows.len()))
+            .collect::<Vec<_>>();
+        self.bytes -= fetched.iter().map(Vec::len).sum::<usize>();
+        while fetched.len() < n {
+            let Some((source, columns)) = self.source.as_mut() else {
+                break;
+            };
+            let mut row = RowEncoder::new(columns, true, Vec::new());
+            if source.next_row(&mut row)? {
+                fetched.push(row.finish()?);
+            } else {
+                self.source = None;
+            }
+        }
+        Ok(fetched)
+    }
+
+    /// Returns true if every row of the cursor has been fetched.
+    ///
+    /// Since rows are produced lazily, this isn't known until the client tries to fetch past the
+    /// last row, so the last batch of rows the client fetches may be empty.
+    pub(crate) fn is_exhausted(&self) -> bool {
+        self.rows.is_empty() && self.source.is_none()
+    }
+
+    /// The status flags to send to the client after a batch of fetched rows
+    pub(crate) fn status_flags(&self) -> StatusFlags {
+        let mut status = self.status_flags;
+        status.set(StatusFlags::SERVER_STATUS_CURSOR_EXISTS, true);
+        status.set(
+            StatusFlags::SERVER_STATUS_LAST_ROW_SENT,
+            self.is_exhausted(),
+        );
+        status
+    }
+}
+
 #[derive(Debug)]
 enum Finalizer {
     Ok {
@@ -118,6 +322,17 @@ pub struct QueryResultWriter<'a, W: AsyncWrite + Unpin> {
     pub(crate) is_bin: bool,
     pub(crate) writer: &'a mut PacketWriter<W>,
     last_end: Option<Finalizer>,
+    /// Set when this query is one statement of a multi-statement query, and isn't the last one.
+    /// The other statements' resultsets follow this one's, so the client has to be told that more
+    /// resultsets exist even after `no_more_results` is called.
+    more_statements: bool,
+    /// Set to true if an error is sent to the client, which ends a multi-statement query
+    errored: Option<&'a mut bool>,
+    /// Set when the client asked for a read-only cursor on this statement's results. If the
+    /// statement returns a resultset whose rows written up front add up to at most
+    /// [`MAX_CURSOR_BYTES`], its rows are held in a [`Cursor`] stored here rather than being sent
+    /// to the client.
+    cursor: Option<&'a mut Option<Cursor>>,
 }
 
 impl<'a, W: AsyncWrite + Unpin> QueryResultWriter<'a, W> {
@@ -126,6 +341,47 @@ impl<'a, W: AsyncWrite + Unpin> QueryResultWriter<'a, W> {
             is_bin,
             writer,
             last_end: None,
+            more_statements: false,
+            errored: None,
+            cursor: None,
+        }
+    }
+
+    /// Create a writer for the results of executing a prepared statement with a read-only cursor.
+    ///
+    /// If the statement returns a resultset, only its columns are sent to the client, and a
+    /// [`Cursor`] holding its rows is stored in `cursor`, unless the rows written up front add up
+    /// to more than [`MAX_CURSOR_BYTES`], in which case they're all sent to the client as usual.
+    pub(crate) fn for_cursor(
+        writer: &'a mut PacketWriter<W>,
+        cursor: &'a mut Option<Cursor>,
+    ) -> Self {
+        QueryResultWriter {
+            is_bin: true,
+            writer,
+            last_end: None,
+            more_statements: false,
+            errored: None,
+            cursor: Some(cursor),
+        }
+    }
+
+    /// Create a writer for the results of one statement of a multi-statement query.
+    ///
+    /// `more_statements` should be true unless this is the last statement in the query, and
+    /// `errored` is set to true if the statement's response is an error.
+    pub(crate) fn for_statement(
+        writer: &'a mut PacketWriter<W>,
+        more_statements: bool,
+        errored: &'a mut bool,
+    ) -> Self {
+        QueryResultWriter {
+            is_bin: false,
+            writer,
+            last_end: None,
+            more_statements,
+            errored: Some(errored),
+            cursor: None,
         }
     }
 
@@ -145,7 +401,7 @@ impl<'a, W: AsyncWrite + Unpin> QueryResultWriter<'a, W> {
             }
             _ => StatusFlags::empty(),
         };
-        if more_exists {
+        if more_exists || self.more_statements {
             status.set(StatusFlags::SERVER_MORE_RESULTS_EXISTS, true);
         }
         match self.last_end.take() {
@@ -228,6 +484,12 @@ impl<'a, W: AsyncWrite + Unpin> QueryResultWriter<'a, W> {
     {
         self.finalize(true).await?;
         writers::write_err(kind, msg.borrow(), self.writer).await?;
+        if let Some(errored) = self.errored.as_deref_mut() {
+            *errored = true;
+        }
+        if let Some(cursor) = self.cursor.as_deref_mut() {
+            *cursor = None;
+        }
         self.no_more_results().await
     }
 
@@ -264,7 +526,6 @@ impl<'a, W: AsyncWrite + Unpin> Drop for QueryResultWriter<'a, W> {
 #[must_use]
 pub struct RowWriter<'a, W: AsyncWrite + Unpin> {
     result: QueryResultWriter<'a, W>,
-    bitmap_len: usize,
     /// The index where the null bitmap for the current row begins
     bitmap_idx: usize,
     columns: &'a [Column],
@@ -292,12 +553,10 @@ where
         columns: &'a [Column],
         cached_column_def: Option<Arc<[u8]>>,
     ) -> io::Result<RowWriter<'a, W>> {
-        let bitmap_len = (columns.len() + 7 + 2) / 8;
         let mut rw = RowWriter {
             result,
             columns,
             cached: cached_column_def,
-            bitmap_len,
             bitmap_idx: 0,
 
             col: 0,
@@ -316,13 +575,47 @@ where
             return Ok(());
         }
 
+        // When the rows are held in a cursor, the columns are sent when the resultset is finished,
+        // along with the status flags telling the client about the cursor
+        if let Some(cursor) = self.result.cursor.as_deref_mut() {
+            *cursor = Some(Cursor::new());
+            return Ok(());
+        }
+
+        self.write_columns(StatusFlags::empty()).await
+    }
+
+    async fn write_columns(&mut self, status: StatusFlags) -> io::Result<()> {
         match &self.cached {
             Some(cached) => {
-                writers::column_definitions_cached(self.columns, cached.clone(), self.result.writer)
-                    .await
+                writers::column_definitions_cached(
+                    self.columns,
+                    cached.clone(),
+                    self.result.writer,
+                    status,
+                )
+                .await
             }
-            None => writers::column_definitions(self.columns, self.result.writer).await,
+            None => writers::column_definitions(self.columns, self.result.writer, status).await,
+        }
+    }
+
+    /// Returns the cursor this resultset's rows are being held in, if any
+    fn cursor(&mut self) -> Option<&mut Cursor> {
+        self.result.cursor.as_deref_mut().and_then(Option::as_mut)
+    }
+
+    /// Close the cursor this resultset's rows are being held in because it grew too large, and
+    /// send the columns and the rows held so far to the client without a cursor
+    async fn abandon_cursor(&mut self) -> io::Result<()> {
+        let Some(cursor) = self.result.cursor.take().and_then(|cursor| cursor.take()) else {
+            return Ok(());
+        };
+        self.write_columns(StatusFlags::empty()).await?;
+        for row in cursor.rows {
+            self.result.writer.enqueue_packet(row);
         }
+        Ok(())
     }
 
     /// Write a value to the next column of the current row as a part of this resultset.
@@ -354,41 +647,14 @@ where
             row_data
         });
 
-        if self.result.is_bin {
-            if self.col == 0 {
-                row_data.push(0x00);
-                // leave space for nullmap
-                self.bitmap_idx = row_data.len();
-                row_data.resize(row_data.len() + self.bitmap_len, 0);
-            }
-
-            let c = self.columns.get(self.col).ok_or_else(|| {
-                io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "row has more columns than specification",
-                )
-            })?;
-
-            if v.is_null() {
-                if c.colflags.contains(ColumnFlags::NOT_NULL_FLAG) {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidData,
-                        "given NULL value for NOT NULL column",
-                    ));
-                } else {
-                    // https://web.archive.org/web/20170404144156/https://dev.mysql.com/doc/internals/en/null-bitmap.html
-                    // NULL-bitmap-byte = ((field-pos + offset) / 8)
-                    // NULL-bitmap-bit  = ((field-pos + offset) % 8)
-                    let idx = self.bitmap_idx + (self.col + 2) / 8;
-                    // Always safe to access `idx` because we allocate sufficient space in advance
-                    row_data[idx] |= 1u8 << ((self.col + 2) % 8);
-                }
-            } else {
-                v.to_mysql_bin(row_data, c)?;
-            }
-        } else {
-            v.to_mysql_text(row_data)?;
-        }
+        encode_col(
+            row_data,
+            self.columns,
+            self.col,
+            self.result.is_bin,
+            &mut self.bitmap_idx,
+            v,
+        )?;
         self.col += 1;
         Ok(())
     }
@@ -408,10 +674,25 @@ where
         }
 
         if let Some(packet) = self.row_data.take() {
-            self.result.writer.enqueue_packet(packet);
+            self.send_row(packet).await?;
         }
 
         self.col = 0;
+        Ok(())
+    }
+
+    /// Send an encoded row to the client, or hold it in the cursor if there is one
+    async fn send_row(&mut self, packet: Vec<u8>) -> io::Result<()> {
+        match self.cursor() {
+            Some(cursor) => {
+                cursor.bytes += packet.len();
+                cursor.rows.push_back(packet);
+                if cursor.bytes > MAX_CURSOR_BYTES {
+                    self.abandon_cursor().await?;
+                }
+            }
+            None => self.result.writer.enqueue_packet(packet),
+        }
 
         if self.result.writer.queue_len() > MAX_POOL_ROWS {
             self.result.writer.flush().await?;
@@ -440,6 +721,18 @@ where
     }
 }
 
+impl<'a, W> ColumnWriter for RowWriter<'a, W>
+where
+    W: AsyncWrite + Unpin + 'a,
+{
+    fn write_col<T>(&mut self, v: T) -> io::Result<()>
+    where
+        T: ToMySqlValue,
+    {
+        RowWriter::write_col(self, v)
+    }
+}
+
 impl<'a, W: AsyncWrite + Unpin + 'a> RowWriter<'a, W> {
     fn finish_inner(&mut self) -> io::Result<()> {
         if self.finished {
@@ -457,10 +750,13 @@ impl<'a, W: AsyncWrite + Unpin + 'a> RowWriter<'a, W> {
             });
             Ok(())
         } else {
-            // we wrote out at least one row
-            self.result.last_end = Some(Finalizer::Eof {
-                status_flags: self.last_status_flags.take(),
-            });
+            let status_flags = self.last_status_flags.take();
+            match self.cursor() {
+                // The resultset is finished off when the client fetches the last of the rows
+                Some(cursor) => cursor.status_flags = status_flags.unwrap_or(StatusFlags::empty()),
+                // we wrote out at least one row
+                None => self.result.last_end = Some(Finalizer::Eof { status_flags }),
+            }
             Ok(())
         }
     }
@@ -481,6 +777,42 @@ impl<'a, W: AsyncWrite + Unpin + 'a> RowWriter<'a, W> {
         self.result.error(kind, msg).await
     }
 
+    /// Send the rest of the rows of this resultset from `rows`, then indicate to the client that no
+    /// more rows are coming.
+    ///
+    /// If the client opened a read-only cursor on the statement's results, rows are only taken
+    /// from `rows` as the client fetches them, rather than all being produced up front.
+    pub async fn finish_with_rows<R>(mut self, mut rows: R) -> io::Result<()>
+    where
+        R: RowSource + 'static,
+    {
+        if !self.columns.is_empty() && self.col != 0 {
+            self.end_row().await?;
+        }
+
+        let columns = self.columns;
+        if let Some(cursor) = self.cursor() {
+            cursor.source = Some((Box::new(rows), columns.to_vec()));
+            return self.finish().await;
+        }
+
+        loop {
+            let buffer = self.result.writer.get_buffer();
+            let mut row = RowEncoder::new(columns, self.result.is_bin, buffer);
+            match rows.next_row(&mut row) {
+                Ok(true) if columns.is_empty() => self.col += 1,
+                Ok(true) => self.send_row(row.finish()?).await?,
+                Ok(false) => break,
+                Err(e) => {
+                    return self
+                        .error(ErrorKind::ER_UNKNOWN_ERROR, e.to_string().as_bytes())
+                        .await
+                }
+            }
+        }
+        self.finish().await
+    }
+
     /// Indicate to the client that no more rows are coming.
     pub async fn finish(self) -> io::Result<()> {
         self.finish_one().await?.no_more_results().await
@@ -491,6 +823,12 @@ impl<'a, W: AsyncWrite + Unpin + 'a> RowWriter<'a, W> {
         if !self.columns.is_empty() && self.col != 0 {
             self.end_row().await?;
         }
+        // Every row fit in the cursor, so the client can be told about it
+        if self.cursor().is_some() {
+            let mut status = StatusFlags::empty();
+            status.set(StatusFlags::SERVER_STATUS_CURSOR_EXISTS, true);
+            self.write_columns(status).await?;
+        }
         self.finish_inner()?;
         Ok(self.result)
     }
@@ -81,8 +81,8 @@ where
     buf.write_u16::<LittleEndian>(0)?; // number of warnings
     w.enqueue_packet(buf);
 
-    write_column_definitions(pi, w, true).await?;
-    write_column_definitions(ci, w, true).await
+    write_column_definitions(pi, w, true, StatusFlags::empty()).await?;
+    write_column_definitions(ci, w, true, StatusFlags::empty()).await
 }
 
 /// Compute the size of the buffer required to encode this buffer
@@ -166,6 +166,7 @@ pub(crate) async fn write_column_definitions<'a, I, W>(
     i: I,
     w: &mut PacketWriter<W>,
     only_eof_on_nonempty: bool,
+    status: StatusFlags,
 ) -> io::Result<()>
 where
     I: IntoIterator<Item = &'a Column>,
@@ -183,11 +184,17 @@ where
     if empty && only_eof_on_nonempty {
         Ok(())
     } else {
-        write_eof_packet(w, StatusFlags::empty()).await
+        write_eof_packet(w, status).await
     }
 }
 
-pub(crate) async fn column_definitions<'a, I, W>(i: I, w: &mut PacketWriter<W>) -> io::Result<()>
+/// Write the column count and definitions that start a resultset, followed by an EOF packet with
+/// the given status flags
+pub(crate) async fn column_definitions<'a, I, W>(
+    i: I,
+    w: &mut PacketWriter<W>,
+    status: StatusFlags,
+) -> io::Result<()>
 where
     I: IntoIterator<Item = &'a Column>,
     <I as IntoIterator>::IntoIter: ExactSizeIterator,
@@ -197,13 +204,14 @@ where
     let mut buf = w.get_buffer();
     buf.write_lenenc_int(i.len() as u64)?;
     w.enqueue_packet(buf);
-    write_column_definitions(i, w, false).await
+    write_column_definitions(i, w, false, status).await
 }
 
 pub(crate) async fn column_definitions_cached<'a, I, W>(
     i: I,
     cached: Arc<[u8]>,
     w: &mut PacketWriter<W>,
+    status: StatusFlags,
 ) -> io::Result<()>
 where
     I: IntoIterator<Item = &'a Column>,
@@ -213,5 +221,5 @@ where
     let i = i.into_iter();
     w.enqueue_raw(cached).await?;
     w.seq = w.seq.wrapping_add((1 + i.len()) as u8);
-    write_eof_packet(w, StatusFlags::empty()).await
+    write_eof_packet(w, status).await
 }
@@ -1,4 +1,5 @@
-//! ALTER TABLE Statement AST and parsing (incomplete)
+//! ALTER TABLE Statement AST and parsing (incomplete), as well as the ReadySet-specific
+//! ALTER READYSET and ALTER CACHE statements
 //!
 //! See https://dev.mysql.com/doc/refman/8.0/en/alter-table.html
 
@@ -21,10 +22,10 @@ use crate::common::{
     debug_print, parse_fallible, statement_terminator, until_statement_terminator, ws_sep_comma,
     TableKey,
 };
-use crate::create::key_specification;
+use crate::create::{cache_replicas_option, key_specification};
 use crate::literal::literal;
-use crate::table::{relation, Relation};
-use crate::whitespace::whitespace1;
+use crate::table::{relation, table_list, Relation};
+use crate::whitespace::{whitespace0, whitespace1};
 use crate::{Dialect, DialectDisplay, Literal, NomSqlResult, SqlIdentifier};
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize, Arbitrary)]
@@ -463,6 +464,156 @@ pub fn alter_table_statement(
     }
 }
 
+/// ALTER READYSET statements, used to change which tables ReadySet replicates at runtime
+///
+/// This is a non-standard ReadySet-specific extension to SQL
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize, Arbitrary)]
+pub enum AlterReadySetStatement {
+    /// `ALTER READYSET ADD TABLES <table> [, <table>...]`
+    ///
+    /// Start replicating the given tables, snapshotting them without interrupting replication of
+    /// the other tables
+    AddTables(Vec<Relation>),
+    /// `ALTER READYSET DROP TABLE[S] <table> [, <table>...]`
+    ///
+    /// Stop replicating the given tables, and remove them (and any caches that depend on them)
+    /// from ReadySet
+    DropTables(Vec<Relation>),
+    /// `ALTER READYSET RESNAPSHOT TABLE <table>`
+    ///
+    /// Discard all data ReadySet has for the given table and snapshot it again from upstream
+    ResnapshotTable(Relation),
+}
+
+impl DialectDisplay for AlterReadySetStatement {
+    fn display(&self, dialect: Dialect) -> impl fmt::Display + '_ {
+        fmt_with(move |f| {
+            write!(f, "ALTER READYSET ")?;
+            match self {
+                AlterReadySetStatement::AddTables(tables) => write!(
+                    f,
+                    "ADD TABLES {}",
+                    tables.iter().map(|t| t.display(dialect)).join(", ")
+                ),
+                AlterReadySetStatement::DropTables(tables) => write!(
+                    f,
+                    "DROP TABLES {}",
+                    tables.iter().map(|t| t.display(dialect)).join(", ")
+                ),
+                AlterReadySetStatement::ResnapshotTable(table) => {
+                    write!(f, "RESNAPSHOT TABLE {}", table.display(dialect))
+                }
+            }
+        })
+    }
+}
+
+fn alter_readyset_add_tables(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], AlterReadySetStatement> {
+    move |i| {
+        let (i, _) = tag_no_case("add")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = alt((tag_no_case("tables"), tag_no_case("table")))(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, tables) = table_list(dialect)(i)?;
+
+        Ok((i, AlterReadySetStatement::AddTables(tables)))
+    }
+}
+
+fn alter_readyset_drop_tables(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], AlterReadySetStatement> {
+    move |i| {
+        let (i, _) = tag_no_case("drop")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = alt((tag_no_case("tables"), tag_no_case("table")))(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, tables) = table_list(dialect)(i)?;
+
+        Ok((i, AlterReadySetStatement::DropTables(tables)))
+    }
+}
+
+fn alter_readyset_resnapshot_table(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], AlterReadySetStatement> {
+    move |i| {
+        let (i, _) = tag_no_case("resnapshot")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("table")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, table) = relation(dialect)(i)?;
+
+        Ok((i, AlterReadySetStatement::ResnapshotTable(table)))
+    }
+}
+
+pub fn alter_readyset_statement(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], AlterReadySetStatement> {
+    move |i| {
+        let (i, _) = tag_no_case("alter")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("readyset")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, stmt) = alt((
+            alter_readyset_add_tables(dialect),
+            alter_readyset_drop_tables(dialect),
+            alter_readyset_resnapshot_table(dialect),
+        ))(i)?;
+        let (i, _) = statement_terminator(i)?;
+
+        Ok((i, stmt))
+    }
+}
+
+/// `ALTER CACHE <name> SET (replicas = <n>)`
+///
+/// Change how many times the reader for an existing cache is replicated, at runtime.
+///
+/// This is a non-standard ReadySet-specific extension to SQL
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize, Arbitrary)]
+pub struct AlterCacheStatement {
+    /// The name of the cache to alter
+    pub name: Relation,
+    /// The new number of reader replicas for the cache
+    pub replicas: usize,
+}
+
+impl DialectDisplay for AlterCacheStatement {
+    fn display(&self, dialect: Dialect) -> impl fmt::Display + '_ {
+        fmt_with(move |f| {
+            write!(
+                f,
+                "ALTER CACHE {} SET (replicas = {})",
+                self.name.display(dialect),
+                self.replicas
+            )
+        })
+    }
+}
+
+pub fn alter_cache_statement(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], AlterCacheStatement> {
+    move |i| {
+        let (i, _) = tag_no_case("alter")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("cache")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, name) = relation(dialect)(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("set")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, replicas) = cache_replicas_option(i)?;
+        let (i, _) = statement_terminator(i)?;
+
+        Ok((i, AlterCacheStatement { name, replicas }))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -502,6 +653,81 @@ mod tests {
         assert_eq!(result.unwrap().1, expected);
     }
 
+    #[test]
+    fn alter_readyset_add_tables() {
+        let res = test_parse!(
+            alter_readyset_statement(Dialect::MySQL),
+            b"ALTER READYSET ADD TABLES t1, db.t2"
+        );
+        assert_eq!(
+            res,
+            AlterReadySetStatement::AddTables(vec![
+                "t1".into(),
+                Relation {
+                    schema: Some("db".into()),
+                    name: "t2".into()
+                }
+            ])
+        );
+        assert_eq!(
+            res.display(Dialect::MySQL).to_string(),
+            "ALTER READYSET ADD TABLES `t1`, `db`.`t2`"
+        );
+    }
+
+    #[test]
+    fn alter_readyset_drop_table() {
+        let res = test_parse!(
+            alter_readyset_statement(Dialect::PostgreSQL),
+            b"alter readyset drop table public.t1;"
+        );
+        assert_eq!(
+            res,
+            AlterReadySetStatement::DropTables(vec![Relation {
+                schema: Some("public".into()),
+                name: "t1".into()
+            }])
+        );
+    }
+
+    #[test]
+    fn alter_readyset_resnapshot_table() {
+        let res = test_parse!(
+            alter_readyset_statement(Dialect::MySQL),
+            b"ALTER READYSET RESNAPSHOT TABLE t1"
+        );
+        assert_eq!(res, AlterReadySetStatement::ResnapshotTable("t1".into()));
+        assert_eq!(
+            res.display(Dialect::PostgreSQL).to_string(),
+            "ALTER READYSET RESNAPSHOT TABLE \"t1\""
+        );
+    }
+
+    #[test]
+    fn alter_cache_set_replicas() {
+        let res = test_parse!(
+            alter_cache_statement(Dialect::MySQL),
+            b"ALTER CACHE q1 SET (replicas = 4)"
+        );
+        assert_eq!(
+            res,
+            AlterCacheStatement {
+                name: "q1".into(),
+                replicas: 4
+            }
+        );
+        assert_eq!(
+            res.display(Dialect::PostgreSQL).to_string(),
+            "ALTER CACHE \"q1\" SET (replicas = 4)"
+        );
+
+        let res = test_parse!(
+            alter_cache_statement(Dialect::PostgreSQL),
+            b"alter cache public.q1 set(REPLICAS=1);"
+        );
+        assert_eq!(res.replicas, 1);
+    }
+
     mod mysql {
         use super::*;
         use crate::common::ReferentialAction;
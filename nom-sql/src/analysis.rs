@@ -99,12 +99,19 @@ impl<'a> ReferredColumnsIter<'a> {
             Max(arg) => self.visit_expr(arg),
             Min(arg) => self.visit_expr(arg),
             GroupConcat { expr, .. } => self.visit_expr(expr),
-            Call { arguments, .. } => arguments.first().and_then(|first_arg| {
-                if arguments.len() >= 2 {
-                    self.exprs_to_visit.extend(arguments.iter().skip(1));
-                }
-                self.visit_expr(first_arg)
-            }),
+            ApproxCountDistinct(arg) => self.visit_expr(arg),
+            ApproxPercentile { expr, percentile } => {
+                self.exprs_to_visit.push(percentile.as_ref());
+                self.visit_expr(expr)
+            }
+            Call { arguments, .. } | Grouping(arguments) => {
+                arguments.first().and_then(|first_arg| {
+                    if arguments.len() >= 2 {
+                        self.exprs_to_visit.extend(arguments.iter().skip(1));
+                    }
+                    self.visit_expr(first_arg)
+                })
+            }
             Substring { string, pos, len } => {
                 self.exprs_to_visit.extend(pos.iter().map(|e| e.as_ref()));
                 self.exprs_to_visit.extend(len.iter().map(|e| e.as_ref()));
@@ -210,10 +217,17 @@ impl<'a> ReferredColumnsMut<'a> {
             Max(arg) => self.visit_expr(arg),
             Min(arg) => self.visit_expr(arg),
             GroupConcat { expr, .. } => self.visit_expr(expr),
-            Call { arguments, .. } => arguments.split_first_mut().and_then(|(first_arg, args)| {
-                self.exprs_to_visit.extend(args);
-                self.visit_expr(first_arg)
-            }),
+            ApproxCountDistinct(arg) => self.visit_expr(arg),
+            ApproxPercentile { expr, percentile } => {
+                self.exprs_to_visit.push(percentile.as_mut());
+                self.visit_expr(expr)
+            }
+            Call { arguments, .. } | Grouping(arguments) => {
+                arguments.split_first_mut().and_then(|(first_arg, args)| {
+                    self.exprs_to_visit.extend(args);
+                    self.visit_expr(first_arg)
+                })
+            }
             Substring { string, pos, len } => {
                 self.exprs_to_visit
                     .extend(pos.iter_mut().map(|e| e.as_mut()));
@@ -313,7 +327,7 @@ impl SelectStatement {
             .chain(&self.where_clause)
             .chain(&self.having)
             .chain(self.group_by.iter().flat_map(|gb| {
-                gb.fields.iter().filter_map(|f| match f {
+                gb.all_fields().filter_map(|f| match f {
                     FieldReference::Expr(expr) => Some(expr),
                     _ => None,
                 })
@@ -344,8 +358,11 @@ pub fn is_aggregate(function: &FunctionExpr) -> bool {
         | FunctionExpr::Sum { .. }
         | FunctionExpr::Max(_)
         | FunctionExpr::Min(_)
-        | FunctionExpr::GroupConcat { .. } => true,
+        | FunctionExpr::GroupConcat { .. }
+        | FunctionExpr::ApproxCountDistinct(_)
+        | FunctionExpr::ApproxPercentile { .. } => true,
         FunctionExpr::Substring { .. }
+        | FunctionExpr::Grouping(_)
         // For now, assume all "generic" function calls are not aggregates
         | FunctionExpr::Call { .. } => false,
     }
@@ -17,16 +17,16 @@ use crate::set::Variable;
 use crate::transaction::{CommitStatement, RollbackStatement, StartTransactionStatement};
 use crate::truncate::TruncateStatement;
 use crate::{
-    AlterColumnOperation, AlterTableDefinition, AlterTableStatement, CacheInner, CaseWhenBranch,
-    Column, ColumnConstraint, ColumnSpecification, CommentStatement, CommonTableExpr,
-    CompoundSelectStatement, CreateCacheStatement, CreateTableStatement, CreateViewStatement,
-    DeallocateStatement, DeleteStatement, DropAllCachesStatement, DropAllProxiedQueriesStatement,
-    DropCacheStatement, DropTableStatement, DropViewStatement, ExplainStatement, Expr,
-    FieldDefinitionExpr, FieldReference, FunctionExpr, GroupByClause, InValue, InsertStatement,
-    JoinClause, JoinConstraint, JoinRightSide, Literal, OrderBy, OrderClause, Relation,
-    SelectSpecification, SelectStatement, SetNames, SetPostgresParameter, SetStatement,
-    SetVariables, ShowStatement, SqlIdentifier, SqlQuery, SqlType, TableExpr, TableExprInner,
-    TableKey, UpdateStatement, UseStatement,
+    AlterCacheStatement, AlterColumnOperation, AlterReadySetStatement, AlterTableDefinition,
+    AlterTableStatement, CacheInner, CaseWhenBranch, Column, ColumnConstraint, ColumnSpecification,
+    CommentStatement, CommonTableExpr, CompoundSelectStatement, CreateCacheStatement,
+    CreateTableStatement, CreateViewStatement, DeallocateStatement, DeleteStatement,
+    DropAllCachesStatement, DropAllProxiedQueriesStatement, DropCacheStatement, DropTableStatement,
+    DropViewStatement, ExplainStatement, Expr, FieldDefinitionExpr, FieldReference, FunctionExpr,
+    GroupByClause, InValue, InsertStatement, JoinClause, JoinConstraint, JoinRightSide, Literal,
+    OrderBy, OrderClause, Relation, SelectSpecification, SelectStatement, SetNames,
+    SetPostgresParameter, SetStatement, SetVariables, ShowStatement, SqlIdentifier, SqlQuery,
+    SqlType, TableExpr, TableExprInner, TableKey, UpdateStatement, UseStatement,
 };
 
 /// Each method of the `Visitor` trait is a hook to be potentially overridden when recursively
@@ -374,6 +374,20 @@ pub trait Visitor<'ast>: Sized {
         Ok(())
     }
 
+    fn visit_alter_readyset_statement(
+        &mut self,
+        alter_readyset_statement: &'ast AlterReadySetStatement,
+    ) -> Result<(), Self::Error> {
+        walk_alter_readyset_statement(self, alter_readyset_statement)
+    }
+
+    fn visit_alter_cache_statement(
+        &mut self,
+        alter_cache_statement: &'ast AlterCacheStatement,
+    ) -> Result<(), Self::Error> {
+        walk_relation(self, &alter_cache_statement.name)
+    }
+
     fn visit_drop_all_proxied_queries_statement(
         &mut self,
         _drop_all_proxied_queries_statement: &'ast DropAllProxiedQueriesStatement,
@@ -516,7 +530,12 @@ pub fn walk_function_expr<'ast, V: Visitor<'ast>>(
         FunctionExpr::Max(expr) => visitor.visit_expr(expr.as_ref()),
         FunctionExpr::Min(expr) => visitor.visit_expr(expr.as_ref()),
         FunctionExpr::GroupConcat { expr, .. } => visitor.visit_expr(expr.as_ref()),
-        FunctionExpr::Call { arguments, .. } => {
+        FunctionExpr::ApproxCountDistinct(expr) => visitor.visit_expr(expr.as_ref()),
+        FunctionExpr::ApproxPercentile { expr, percentile } => {
+            visitor.visit_expr(expr.as_ref())?;
+            visitor.visit_expr(percentile.as_ref())
+        }
+        FunctionExpr::Call { arguments, .. } | FunctionExpr::Grouping(arguments) => {
             for arg in arguments {
                 visitor.visit_expr(arg)?;
             }
@@ -621,7 +640,7 @@ pub fn walk_group_by_clause<'ast, V: Visitor<'ast>>(
     visitor: &mut V,
     group_by_clause: &'ast GroupByClause,
 ) -> Result<(), V::Error> {
-    for field in &group_by_clause.fields {
+    for field in group_by_clause.all_fields() {
         visitor.visit_field_reference(field)?;
     }
     Ok(())
@@ -898,7 +917,9 @@ pub fn walk_column_constraint<'a, V: Visitor<'a>>(
     column_constraint: &'a ColumnConstraint,
 ) -> Result<(), V::Error> {
     match column_constraint {
-        ColumnConstraint::DefaultValue(expr) => visitor.visit_expr(expr),
+        ColumnConstraint::DefaultValue(expr) | ColumnConstraint::Check(expr) => {
+            visitor.visit_expr(expr)
+        }
         ColumnConstraint::Null
         | ColumnConstraint::NotNull
         | ColumnConstraint::CharacterSet(_)
@@ -1131,6 +1152,22 @@ pub fn walk_drop_view_statement<'a, V: Visitor<'a>>(
     Ok(())
 }
 
+pub fn walk_alter_readyset_statement<'a, V: Visitor<'a>>(
+    visitor: &mut V,
+    alter_readyset_statement: &'a AlterReadySetStatement,
+) -> Result<(), V::Error> {
+    match alter_readyset_statement {
+        AlterReadySetStatement::AddTables(tables) | AlterReadySetStatement::DropTables(tables) => {
+            for table in tables {
+                visitor.visit_table(table)?;
+            }
+        }
+        AlterReadySetStatement::ResnapshotTable(table) => visitor.visit_table(table)?,
+    }
+
+    Ok(())
+}
+
 pub fn walk_truncate_statement<'a, V: Visitor<'a>>(
     visitor: &mut V,
     truncate_statement: &'a TruncateStatement,
@@ -1150,6 +1187,8 @@ pub fn walk_sql_query<'a, V: Visitor<'a>>(
         SqlQuery::CreateTable(statement) => visitor.visit_create_table_statement(statement),
         SqlQuery::CreateView(statement) => visitor.visit_create_view_statement(statement),
         SqlQuery::AlterTable(statement) => visitor.visit_alter_table_statement(statement),
+        SqlQuery::AlterReadySet(statement) => visitor.visit_alter_readyset_statement(statement),
+        SqlQuery::AlterCache(statement) => visitor.visit_alter_cache_statement(statement),
         SqlQuery::Insert(statement) => visitor.visit_insert_statement(statement),
         SqlQuery::CompoundSelect(statement) => visitor.visit_compound_select_statement(statement),
         SqlQuery::Select(statement) => visitor.visit_select_statement(statement),
@@ -100,6 +100,9 @@ pub enum ColumnConstraint {
     /// NOTE(aspen): Yes, this really is its own special thing, not just an expression - see
     /// <https://dev.mysql.com/doc/refman/8.0/en/timestamp-initialization.html>
     OnUpdateCurrentTimestamp(Option<Literal>),
+    /// A column-level `CHECK (expr)` constraint. MariaDB reports `JSON` columns as `LONGTEXT`
+    /// columns with a `CHECK (json_valid(col))` constraint.
+    Check(Expr),
 }
 
 impl DialectDisplay for ColumnConstraint {
@@ -120,6 +123,7 @@ impl DialectDisplay for ColumnConstraint {
                 }
                 Ok(())
             }
+            Self::Check(expr) => write!(f, "CHECK ({})", expr.display(dialect)),
         })
     }
 }
@@ -272,6 +276,17 @@ pub fn column_constraint(
                 ColumnConstraint::Collation(collation)
             },
         );
+        let check = map(
+            preceded(
+                delimited(whitespace0, tag_no_case("check"), whitespace0),
+                delimited(
+                    tuple((tag("("), whitespace0)),
+                    expression(dialect),
+                    tuple((whitespace0, tag(")"), whitespace0)),
+                ),
+            ),
+            ColumnConstraint::Check,
+        );
 
         alt((
             not_null,
@@ -283,6 +298,7 @@ pub fn column_constraint(
             character_set,
             collate,
             on_update_current_timestamp(dialect),
+            check,
         ))(i)
     }
 }
@@ -447,6 +463,26 @@ mod tests {
                 assert_eq!(res, canonical);
             }
         }
+
+        #[test]
+        fn mariadb_json_check_constraint() {
+            let input = b"`j` longtext CHARACTER SET utf8mb4 COLLATE utf8mb4_bin DEFAULT NULL CHECK (json_valid(`j`))";
+            let (_, res) = column_specification(Dialect::MySQL)(LocatedSpan::new(input)).unwrap();
+            assert_eq!(res.sql_type, SqlType::LongText);
+            assert_eq!(
+                res.constraints.last(),
+                Some(&ColumnConstraint::Check(Expr::Call(FunctionExpr::Call {
+                    name: "json_valid".into(),
+                    arguments: vec![Expr::Column("j".into())],
+                })))
+            );
+
+            let displayed = res.display(Dialect::MySQL).to_string();
+            let (_, round_tripped) =
+                column_specification(Dialect::MySQL)(LocatedSpan::new(displayed.as_bytes()))
+                    .unwrap();
+            assert_eq!(round_tripped, res);
+        }
     }
 
     mod postgres {
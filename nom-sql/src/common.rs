@@ -505,6 +505,97 @@ fn substring(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[
     }
 }
 
+fn grouping(dialect: Dialect) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, _) = tag_no_case("grouping")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, arguments) = separated_list1(ws_sep_comma, expression(dialect))(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+
+        Ok((i, FunctionExpr::Grouping(arguments)))
+    }
+}
+
+fn approx_count_distinct(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, _) = tag_no_case("approx_count_distinct")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, expr) = expression(dialect)(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+
+        Ok((i, FunctionExpr::ApproxCountDistinct(Box::new(expr))))
+    }
+}
+
+fn approx_percentile(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, _) = tag_no_case("approx_percentile")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, expr) = expression(dialect)(i)?;
+        let (i, _) = delimited(whitespace0, tag(","), whitespace0)(i)?;
+        let (i, percentile) = expression(dialect)(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+
+        Ok((
+            i,
+            FunctionExpr::ApproxPercentile {
+                expr: Box::new(expr),
+                percentile: Box::new(percentile),
+            },
+        ))
+    }
+}
+
+/// `PERCENTILE_CONT(percentile) WITHIN GROUP (ORDER BY expr)`
+fn percentile_cont(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
+    move |i| {
+        let (i, _) = tag_no_case("percentile_cont")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, percentile) = expression(dialect)(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("within")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("group")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag("(")(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag_no_case("order")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, _) = tag_no_case("by")(i)?;
+        let (i, _) = whitespace1(i)?;
+        let (i, expr) = expression(dialect)(i)?;
+        let (i, _) = whitespace0(i)?;
+        let (i, _) = tag(")")(i)?;
+
+        Ok((
+            i,
+            FunctionExpr::ApproxPercentile {
+                expr: Box::new(expr),
+                percentile: Box::new(percentile),
+            },
+        ))
+    }
+}
+
 fn function_call(
     dialect: Dialect,
 ) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], FunctionExpr> {
@@ -581,6 +672,10 @@ pub fn function_expr(
                     separator,
                 },
             ),
+            approx_count_distinct(dialect),
+            approx_percentile(dialect),
+            percentile_cont(dialect),
+            grouping(dialect),
             substring(dialect),
             function_call(dialect),
             function_call_without_parens,
@@ -849,7 +944,7 @@ pub fn field_reference_list(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{to_nom_result, SqlType};
+    use crate::{to_nom_result, Double, SqlType};
 
     fn test_opt_delimited_fn_call(i: &str) -> IResult<&[u8], &[u8]> {
         opt_delimited(tag("("), tag("abc"), tag(")"))(i.as_bytes())
@@ -919,6 +1014,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn approx_aggregates() {
+        assert_eq!(
+            test_parse!(function_expr(Dialect::MySQL), b"approx_count_distinct(a)"),
+            FunctionExpr::ApproxCountDistinct(Box::new(Expr::Column("a".into())))
+        );
+
+        let expected = FunctionExpr::ApproxPercentile {
+            expr: Box::new(Expr::Column("a".into())),
+            percentile: Box::new(Expr::Literal(Literal::Double(Double {
+                value: 0.5,
+                precision: 1,
+            }))),
+        };
+        assert_eq!(
+            test_parse!(function_expr(Dialect::MySQL), b"approx_percentile(a, 0.5)"),
+            expected
+        );
+        assert_eq!(
+            test_parse!(
+                function_expr(Dialect::PostgreSQL),
+                b"percentile_cont(0.5) WITHIN GROUP (ORDER BY a)"
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn grouping_function() {
+        assert_eq!(
+            test_parse!(function_expr(Dialect::PostgreSQL), b"GROUPING(a, t.b)"),
+            FunctionExpr::Grouping(vec![Expr::Column("a".into()), Expr::Column("t.b".into())])
+        );
+    }
+
     #[test]
     fn simple_generic_function() {
         let qlist = [
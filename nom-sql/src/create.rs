@@ -212,7 +212,7 @@ struct CreateCacheOptions {
     concurrently: bool,
 }
 
-/// `CREATE CACHE [CONCURRENTLY] [ALWAYS] [<name>] FROM ...`
+/// `CREATE CACHE [CONCURRENTLY] [ALWAYS] [<name>] [WITH (replicas = <n>, shards = <n>)] FROM ...`
 ///
 /// This is a non-standard ReadySet specific extension to SQL
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize, Arbitrary)]
@@ -234,6 +234,14 @@ pub struct CreateCacheStatement {
     pub always: bool,
     /// Whether the CREATE CACHE STATEMENT should block or run concurrently
     pub concurrently: bool,
+    /// The number of times to replicate the reader for the cache, if specified with `WITH
+    /// (replicas = <n>)`. If not provided, the reader is replicated according to the replication
+    /// strategy of the deployment
+    pub replicas: Option<usize>,
+    /// The number of shards to shard the dataflow nodes for the cache by, if specified with `WITH
+    /// (shards = <n>)`. If not provided, the nodes are sharded according to the sharding of the
+    /// deployment
+    pub shards: Option<usize>,
 }
 
 impl DialectDisplay for CreateCacheStatement {
@@ -249,6 +257,15 @@ impl DialectDisplay for CreateCacheStatement {
             if let Some(name) = &self.name {
                 write!(f, "{} ", name.display(dialect))?;
             }
+            let options = self
+                .replicas
+                .map(|replicas| format!("replicas = {replicas}"))
+                .into_iter()
+                .chain(self.shards.map(|shards| format!("shards = {shards}")))
+                .join(", ");
+            if !options.is_empty() {
+                write!(f, "WITH ({options}) ")?;
+            }
             write!(f, "FROM ")?;
             match &self.inner {
                 Ok(inner) => write!(f, "{}", inner.display(dialect)),
@@ -810,6 +827,68 @@ fn cached_query_options(mut i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], Create
     Ok((i, opts))
 }
 
+/// Parse the `(replicas = <n>)` option list given to `ALTER CACHE ... SET`, returning the number of
+/// reader replicas
+pub(crate) fn cache_replicas_option(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], usize> {
+    let (i, _) = tag("(")(i)?;
+    let (i, _) = whitespace0(i)?;
+    let (i, _) = tag_no_case("replicas")(i)?;
+    let (i, _) = whitespace0(i)?;
+    let (i, _) = tag("=")(i)?;
+    let (i, _) = whitespace0(i)?;
+    let (i, replicas) = map_res(
+        map_res(digit1, |i: LocatedSpan<&[u8]>| str::from_utf8(&i)),
+        usize::from_str,
+    )(i)?;
+    let (i, _) = whitespace0(i)?;
+    let (i, _) = tag(")")(i)?;
+    Ok((i, replicas))
+}
+
+/// Options given to `CREATE CACHE ... WITH`. This struct is only used for parsing.
+#[derive(Default)]
+struct CreateCacheWithOptions {
+    replicas: Option<usize>,
+    shards: Option<usize>,
+}
+
+/// Parse the `(replicas = <n>, shards = <n>)` option list given to `CREATE CACHE ... WITH`, in
+/// any order
+fn cache_with_options(i: LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], CreateCacheWithOptions> {
+    let (i, _) = tag("(")(i)?;
+    let (i, _) = whitespace0(i)?;
+    let (remaining, options) = separated_list1(
+        ws_sep_comma,
+        tuple((
+            alt((tag_no_case("replicas"), tag_no_case("shards"))),
+            delimited(whitespace0, tag("="), whitespace0),
+            map_res(
+                map_res(digit1, |i: LocatedSpan<&[u8]>| str::from_utf8(&i)),
+                usize::from_str,
+            ),
+        )),
+    )(i)?;
+    let (remaining, _) = whitespace0(remaining)?;
+    let (remaining, _) = tag(")")(remaining)?;
+
+    let mut opts = CreateCacheWithOptions::default();
+    for (option, _, value) in options {
+        let opt = if option.eq_ignore_ascii_case(b"replicas") {
+            &mut opts.replicas
+        } else {
+            &mut opts.shards
+        };
+        // Error if the same option appears twice.
+        if opt.replace(value).is_some() {
+            return Err(nom::Err::Failure(NomSqlError::from_error_kind(
+                i,
+                ErrorKind::Permutation,
+            )));
+        }
+    }
+    Ok((remaining, opts))
+}
+
 /// Extract the [`SelectStatement`] or Query ID from a CREATE CACHE statement. Query ID is
 /// parsed as a SqlIdentifier
 pub fn cached_query_inner(
@@ -835,6 +914,12 @@ pub fn create_cached_query(
         let (i, _) = whitespace1(i)?;
         let (i, opts) = cached_query_options(i)?;
         let (i, name) = opt(terminated(relation(dialect), whitespace1))(i)?;
+        let (i, with_options) = opt(delimited(
+            terminated(tag_no_case("with"), whitespace0),
+            cache_with_options,
+            whitespace1,
+        ))(i)?;
+        let with_options = with_options.unwrap_or_default();
         let (i, _) = tag_no_case("from")(i)?;
         let (i, _) = whitespace1(i)?;
         let (i, inner) =
@@ -847,6 +932,8 @@ pub fn create_cached_query(
                 unparsed_create_cache_statement,
                 always: opts.always,
                 concurrently: opts.concurrently,
+                replicas: with_options.replicas,
+                shards: with_options.shards,
             },
         ))
     }
@@ -1668,6 +1755,56 @@ mod tests {
             );
         }
 
+        #[test]
+        fn create_cached_query_with_replicas() {
+            let named = test_parse!(
+                create_cached_query(Dialect::MySQL),
+                b"CREATE CACHE foo WITH (replicas = 3) FROM SELECT id FROM users WHERE name = ?"
+            );
+            assert_eq!(named.name, Some("foo".into()));
+            assert_eq!(named.replicas, Some(3));
+            assert_eq!(
+                named.display(Dialect::MySQL).to_string(),
+                "CREATE CACHE `foo` WITH (replicas = 3) FROM SELECT `id` FROM `users` WHERE (`name` = ?)"
+            );
+
+            let unnamed = test_parse!(
+                create_cached_query(Dialect::MySQL),
+                b"CREATE CACHE ALWAYS with(REPLICAS=2) FROM q_0123456789ABCDEF"
+            );
+            assert!(unnamed.always);
+            assert!(unnamed.name.is_none());
+            assert_eq!(unnamed.replicas, Some(2));
+            assert_eq!(unnamed.shards, None);
+        }
+
+        #[test]
+        fn create_cached_query_with_shards() {
+            let res = test_parse!(
+                create_cached_query(Dialect::MySQL),
+                b"CREATE CACHE foo WITH (shards = 4, replicas = 2) FROM SELECT id FROM users"
+            );
+            assert_eq!(res.shards, Some(4));
+            assert_eq!(res.replicas, Some(2));
+            assert_eq!(
+                res.display(Dialect::MySQL).to_string(),
+                "CREATE CACHE `foo` WITH (replicas = 2, shards = 4) FROM SELECT `id` FROM `users`"
+            );
+
+            let res = test_parse!(
+                create_cached_query(Dialect::PostgreSQL),
+                b"CREATE CACHE WITH (SHARDS=3) FROM SELECT id FROM users"
+            );
+            assert!(res.name.is_none());
+            assert_eq!(res.shards, Some(3));
+            assert_eq!(res.replicas, None);
+
+            assert!(create_cached_query(Dialect::MySQL)(LocatedSpan::new(
+                b"CREATE CACHE foo WITH (shards = 4, shards = 2) FROM SELECT id FROM users"
+            ))
+            .is_err());
+        }
+
         #[test]
         fn lobsters_indexes() {
             let qstring = "CREATE TABLE `comments` (
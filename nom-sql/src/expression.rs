@@ -56,6 +56,31 @@ pub enum FunctionExpr {
         separator: Option<String>,
     },
 
+    /// `APPROX_COUNT_DISTINCT` aggregation, which estimates the number of distinct values of its
+    /// argument
+    ApproxCountDistinct(Box<Expr>),
+
+    /// Approximate percentile aggregation, which estimates the value at `percentile` (between 0
+    /// and 1) of the distribution of `expr`.
+    ///
+    /// The supported syntax is one of:
+    ///
+    /// `APPROX_PERCENTILE(expr, percentile)`
+    /// `PERCENTILE_CONT(percentile) WITHIN GROUP (ORDER BY expr)`
+    ///
+    /// Note that `PERCENTILE_CONT` is also computed approximately.
+    ApproxPercentile {
+        expr: Box<Expr>,
+        percentile: Box<Expr>,
+    },
+
+    /// `GROUPING(expr, ...)`, which returns a bitmask with a bit set for each of its arguments
+    /// that is *not* grouped by in the grouping set of the current row (see [`GroupingSets`]).
+    /// The first argument corresponds to the most significant bit.
+    ///
+    /// [`GroupingSets`]: crate::GroupingSets
+    Grouping(Vec<Expr>),
+
     /// The SQL `SUBSTRING`/`SUBSTR` function.
     ///
     /// The supported syntax is one of:
@@ -89,11 +114,15 @@ impl FunctionExpr {
             | FunctionExpr::Sum { expr: arg, .. }
             | FunctionExpr::Max(arg)
             | FunctionExpr::Min(arg)
-            | FunctionExpr::GroupConcat { expr: arg, .. } => {
-                concrete_iter!(iter::once(arg.as_ref()))
+            | FunctionExpr::GroupConcat { expr: arg, .. }
+            | FunctionExpr::ApproxCountDistinct(arg) => concrete_iter!(iter::once(arg.as_ref())),
+            FunctionExpr::ApproxPercentile { expr, percentile } => {
+                concrete_iter!(iter::once(expr.as_ref()).chain(iter::once(percentile.as_ref())))
             }
             FunctionExpr::CountStar => concrete_iter!(iter::empty()),
-            FunctionExpr::Call { arguments, .. } => concrete_iter!(arguments),
+            FunctionExpr::Call { arguments, .. } | FunctionExpr::Grouping(arguments) => {
+                concrete_iter!(arguments)
+            }
             FunctionExpr::Substring { string, pos, len } => {
                 concrete_iter!(iter::once(string.as_ref())
                     .chain(pos.iter().map(|p| p.as_ref()))
@@ -135,6 +164,20 @@ impl DialectDisplay for FunctionExpr {
                 }
                 write!(f, ")")
             }
+            FunctionExpr::ApproxCountDistinct(expr) => {
+                write!(f, "approx_count_distinct({})", expr.display(dialect))
+            }
+            FunctionExpr::ApproxPercentile { expr, percentile } => write!(
+                f,
+                "approx_percentile({}, {})",
+                expr.display(dialect),
+                percentile.display(dialect)
+            ),
+            FunctionExpr::Grouping(arguments) => write!(
+                f,
+                "grouping({})",
+                arguments.iter().map(|arg| arg.display(dialect)).join(", ")
+            ),
             FunctionExpr::Call { name, arguments } => {
                 write!(
                     f,
@@ -695,6 +738,12 @@ impl Arbitrary for Expr {
                     (box_expr.clone(), any::<Option<String>>()).prop_map(|(expr, separator)| {
                         FunctionExpr::GroupConcat { expr, separator }
                     }),
+                    box_expr.clone().prop_map(FunctionExpr::ApproxCountDistinct),
+                    (box_expr.clone(), box_expr.clone()).prop_map(|(expr, percentile)| {
+                        FunctionExpr::ApproxPercentile { expr, percentile }
+                    }),
+                    proptest::collection::vec(element.clone(), 1..4)
+                        .prop_map(FunctionExpr::Grouping),
                     (
                         box_expr.clone(),
                         option::of(box_expr.clone()),
@@ -14,7 +14,8 @@ use nom::{AsBytes, Err, HexDisplay, IResult};
 use nom_locate::LocatedSpan;
 
 pub use self::alter::{
-    AlterColumnOperation, AlterTableDefinition, AlterTableStatement, ReplicaIdentity,
+    AlterCacheStatement, AlterColumnOperation, AlterReadySetStatement, AlterTableDefinition,
+    AlterTableStatement, ReplicaIdentity,
 };
 pub use self::column::{Column, ColumnConstraint, ColumnSpecification};
 pub use self::comment::CommentStatement;
@@ -45,7 +46,8 @@ pub use self::literal::{
 pub use self::order::{OrderBy, OrderClause, OrderType};
 pub use self::parser::*;
 pub use self::select::{
-    CommonTableExpr, GroupByClause, JoinClause, LimitClause, LimitValue, SelectStatement,
+    CommonTableExpr, GroupByClause, GroupingSets, JoinClause, LimitClause, LimitValue,
+    SelectStatement,
 };
 pub use self::set::{
     PostgresParameterScope, PostgresParameterValue, PostgresParameterValueInner, SetNames,
@@ -59,6 +61,7 @@ pub use self::table::{
     replicator_table_list, NonReplicatedRelation, NotReplicatedReason, Relation, TableExpr,
     TableExprInner,
 };
+pub use self::tokens::{tokenize, Token, TokenKind, Tokens};
 pub use self::transaction::StartTransactionStatement;
 pub use self::truncate::TruncateStatement;
 pub use self::update::UpdateStatement;
@@ -96,6 +99,7 @@ mod show;
 mod sql_identifier;
 mod sql_type;
 mod table;
+pub mod tokens;
 mod transaction;
 mod truncate;
 mod update;
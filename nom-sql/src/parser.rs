@@ -8,7 +8,10 @@ use readyset_util::redacted::Sensitive;
 use serde::{Deserialize, Serialize};
 use test_strategy::Arbitrary;
 
-use crate::alter::{alter_table_statement, AlterTableStatement};
+use crate::alter::{
+    alter_cache_statement, alter_readyset_statement, alter_table_statement, AlterCacheStatement,
+    AlterReadySetStatement, AlterTableStatement,
+};
 use crate::comment::{comment, CommentStatement};
 use crate::common::statement_terminator;
 use crate::compound_select::{simple_or_compound_selection, CompoundSelectStatement};
@@ -30,6 +33,7 @@ use crate::select::{selection, SelectStatement};
 use crate::set::{set, SetStatement};
 use crate::show::{show, ShowStatement};
 use crate::sql_type::type_identifier;
+use crate::tokens::{tokenize, TokenKind};
 use crate::transaction::{
     commit, rollback, start_transaction, CommitStatement, RollbackStatement,
     StartTransactionStatement,
@@ -53,6 +57,8 @@ pub enum SqlQuery {
     DropAllCaches(DropAllCachesStatement),
     DropAllProxiedQueries(DropAllProxiedQueriesStatement),
     AlterTable(AlterTableStatement),
+    AlterReadySet(AlterReadySetStatement),
+    AlterCache(AlterCacheStatement),
     Insert(InsertStatement),
     CompoundSelect(CompoundSelectStatement),
     Select(SelectStatement),
@@ -89,6 +95,8 @@ impl DialectDisplay for SqlQuery {
             Self::Update(update) => write!(f, "{}", update.display(dialect)),
             Self::Set(set) => write!(f, "{}", set.display(dialect)),
             Self::AlterTable(alter) => write!(f, "{}", alter.display(dialect)),
+            Self::AlterReadySet(alter) => write!(f, "{}", alter.display(dialect)),
+            Self::AlterCache(alter) => write!(f, "{}", alter.display(dialect)),
             Self::CompoundSelect(compound) => write!(f, "{}", compound.display(dialect)),
             Self::StartTransaction(tx) => write!(f, "{}", tx),
             Self::Commit(commit) => write!(f, "{}", commit),
@@ -140,6 +148,8 @@ impl SqlQuery {
             Self::Update(_) => "UPDATE",
             Self::Set(_) => "SET",
             Self::AlterTable(_) => "ALTER TABLE",
+            Self::AlterReadySet(_) => "ALTER READYSET",
+            Self::AlterCache(_) => "ALTER CACHE",
             Self::CompoundSelect(_) => "SELECT",
             Self::StartTransaction(_) => "START TRANSACTION",
             Self::Commit(_) => "COMMIT",
@@ -166,7 +176,9 @@ impl SqlQuery {
             | SqlQuery::CreateCache(_)
             | SqlQuery::DropCache(_)
             | SqlQuery::DropAllCaches(_)
-            | SqlQuery::DropAllProxiedQueries(_) => true,
+            | SqlQuery::DropAllProxiedQueries(_)
+            | SqlQuery::AlterReadySet(_)
+            | SqlQuery::AlterCache(_) => true,
             SqlQuery::Show(show_stmt) => match show_stmt {
                 ShowStatement::Events | ShowStatement::Tables(_) => false,
                 ShowStatement::CachedQueries(_)
@@ -250,6 +262,8 @@ fn sql_query_part2(
     move |i| {
         alt((
             map(truncate(dialect), SqlQuery::Truncate),
+            map(alter_readyset_statement(dialect), SqlQuery::AlterReadySet),
+            map(alter_cache_statement(dialect), SqlQuery::AlterCache),
             // This does a more expensive clone of `i`, so process it last.
             map(create_cached_query(dialect), SqlQuery::CreateCache),
             map(comment(dialect), SqlQuery::Comment),
@@ -315,10 +329,237 @@ export_parser!(
     parse_sql_type
 );
 
+/// Keywords which, when they appear before the first `(` of a `CREATE` statement, mean the
+/// statement creates a stored routine whose body may contain `;`-terminated statements of its own
+const ROUTINE_KEYWORDS: &[&str] = &["PROCEDURE", "FUNCTION", "TRIGGER", "EVENT"];
+
+/// Keywords which, following `END`, close a compound statement in a routine body that we don't
+/// count towards the depth of nested blocks
+const UNCOUNTED_BLOCK_KEYWORDS: &[&str] = &["IF", "LOOP", "WHILE", "REPEAT"];
+
+/// Split the text of a multi-statement query into the statements it contains.
+///
+/// Statements are separated by `;`, unless it appears within a string literal, a quoted
+/// identifier, a comment, or the body of a stored routine: either a `BEGIN ... END` block in a
+/// `CREATE PROCEDURE`, `FUNCTION`, `TRIGGER` or `EVENT` statement, or (in PostgreSQL) a
+/// dollar-quoted string. The statements are returned without their terminators or any
+/// surrounding whitespace, and empty statements are skipped. The statements themselves aren't
+/// parsed, so this also splits queries containing statements that we can't parse.
+pub fn split_statements(dialect: Dialect, input: &str) -> Vec<&str> {
+    // Whitespace and comments don't separate keywords like `END` from the one they go with
+    let tokens = tokenize(dialect, input)
+        .filter(|token| !token.is_trivia())
+        .collect::<Vec<_>>();
+
+    let mut statements = vec![];
+    let mut start = 0;
+    // What we know about the statement starting at `start`
+    let mut first_word = true;
+    let mut is_create = false;
+    let mut seen_paren = false;
+    let mut is_routine = false;
+    // How many `BEGIN` (or `CASE`) blocks of a routine body we're in
+    let mut depth = 0usize;
+
+    let mut idx = 0;
+    while idx < tokens.len() {
+        let token = tokens[idx];
+        idx += 1;
+        match token.kind {
+            TokenKind::Punctuation if token.is_punctuation(';') && depth == 0 => {
+                statements.push(&input[start..token.offset]);
+                start = token.offset + 1;
+                first_word = true;
+                is_create = false;
+                seen_paren = false;
+                is_routine = false;
+            }
+            TokenKind::Punctuation if token.is_punctuation('(') => seen_paren = true,
+            TokenKind::Word => {
+                if first_word {
+                    first_word = false;
+                    is_create = token.is_keyword("CREATE");
+                    continue;
+                }
+                if is_create && !seen_paren && depth == 0 && !is_routine {
+                    is_routine = ROUTINE_KEYWORDS.iter().any(|kw| token.is_keyword(kw));
+                    continue;
+                }
+                if !is_routine {
+                    continue;
+                }
+
+                // Column names like `NEW.end` and labels like `end:` aren't keywords, but the `:`
+                // of a cast like `END::int` or an assignment like `x := 1` doesn't start a label
+                let qualified = idx >= 2 && tokens[idx - 2].is_punctuation('.');
+                let label = tokens.get(idx).is_some_and(|next| next.is_punctuation(':'))
+                    && !tokens
+                        .get(idx + 1)
+                        .is_some_and(|next| next.is_punctuation(':') || next.is_punctuation('='));
+                if qualified || label {
+                    continue;
+                }
+
+                if token.is_keyword("BEGIN") || token.is_keyword("CASE") {
+                    depth += 1;
+                } else if token.is_keyword("END") && depth > 0 {
+                    // `END IF`, `END LOOP` etc. close blocks we don't count, but `END CASE` closes
+                    // a `CASE` we did count
+                    match tokens.get(idx) {
+                        Some(next) if next.is_keyword("CASE") => {
+                            idx += 1;
+                            depth -= 1;
+                        }
+                        Some(next)
+                            if UNCOUNTED_BLOCK_KEYWORDS
+                                .iter()
+                                .any(|kw| next.is_keyword(kw)) =>
+                        {
+                            idx += 1;
+                        }
+                        _ => depth -= 1,
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    statements.push(&input[start..]);
+
+    statements
+        .into_iter()
+        .map(str::trim)
+        .filter(|statement| !statement.is_empty())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn split_statements_mysql() {
+        assert_eq!(
+            split_statements(
+                Dialect::MySQL,
+                "SELECT * FROM t WHERE x = 'a;b' OR y = \"c;\\\";d\"; \n\
+                 SELECT `weird;column` FROM t /* a; comment */;;\
+                 UPDATE t SET x = 1 -- trailing; comment"
+            ),
+            vec![
+                "SELECT * FROM t WHERE x = 'a;b' OR y = \"c;\\\";d\"",
+                "SELECT `weird;column` FROM t /* a; comment */",
+                "UPDATE t SET x = 1 -- trailing; comment",
+            ]
+        );
+    }
+
+    #[test]
+    fn split_statements_postgres() {
+        assert_eq!(
+            split_statements(
+                Dialect::PostgreSQL,
+                "SELECT \"weird;column\" FROM t WHERE x = 'a;''b'; SELECT 1;"
+            ),
+            vec![
+                "SELECT \"weird;column\" FROM t WHERE x = 'a;''b'",
+                "SELECT 1"
+            ]
+        );
+    }
+
+    #[test]
+    fn split_statements_comments_by_dialect() {
+        // `#` is a comment in MySQL, but an operator in PostgreSQL
+        assert_eq!(
+            split_statements(Dialect::MySQL, "SELECT 1 # a; comment\nFROM t; SELECT 2"),
+            vec!["SELECT 1 # a; comment\nFROM t", "SELECT 2"]
+        );
+        assert_eq!(
+            split_statements(Dialect::PostgreSQL, "SELECT 1 # 2; SELECT 3"),
+            vec!["SELECT 1 # 2", "SELECT 3"]
+        );
+
+        // In MySQL, `--` only starts a comment if it's followed by whitespace
+        assert_eq!(
+            split_statements(
+                Dialect::MySQL,
+                "SELECT 1--1; SELECT 2 -- a; comment\n; SELECT 3"
+            ),
+            vec!["SELECT 1--1", "SELECT 2 -- a; comment", "SELECT 3"]
+        );
+        assert_eq!(
+            split_statements(Dialect::PostgreSQL, "SELECT 1 --a; comment\n; SELECT 2"),
+            vec!["SELECT 1 --a; comment", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn split_statements_routine_bodies() {
+        let procedure = "CREATE DEFINER=`root`@`%` PROCEDURE p(IN x INT)\n\
+                         BEGIN\n\
+                         IF x > 0 THEN SELECT 1; END IF;\n\
+                         SELECT CASE WHEN x THEN 1 ELSE 2 END;\n\
+                         label: BEGIN SELECT 3; END label;\n\
+                         END";
+        assert_eq!(
+            split_statements(Dialect::MySQL, &format!("{procedure}; SELECT 4;")),
+            vec![procedure, "SELECT 4"]
+        );
+
+        let trigger = "CREATE TRIGGER trg BEFORE INSERT ON t FOR EACH ROW \
+                       BEGIN SET NEW.x = 1; SET NEW.y = 2; END";
+        assert_eq!(
+            split_statements(Dialect::MySQL, &format!("BEGIN; {trigger}; COMMIT")),
+            vec!["BEGIN", trigger, "COMMIT"]
+        );
+
+        let function = "CREATE FUNCTION f() RETURNS int AS $body$ BEGIN RETURN 1; END; \
+                        $body$ LANGUAGE plpgsql";
+        assert_eq!(
+            split_statements(
+                Dialect::PostgreSQL,
+                &format!("{function}; SELECT $$a;b$$, $1; SELECT 2")
+            ),
+            vec![function, "SELECT $$a;b$$, $1", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn split_statements_routine_blocks() {
+        let procedure = "CREATE PROCEDURE p(IN x INT)\n\
+                         BEGIN\n\
+                         CASE x\n\
+                         WHEN 1 THEN SELECT CASE WHEN x THEN 1 END; SELECT 2;\n\
+                         ELSE BEGIN SELECT 3; END;\n\
+                         END /* a comment */ CASE;\n\
+                         outer_loop: LOOP\n\
+                         IF x THEN LEAVE outer_loop; END\n  IF;\n\
+                         END LOOP outer_loop;\n\
+                         END";
+        assert_eq!(
+            split_statements(Dialect::MySQL, &format!("{procedure}; SELECT 4")),
+            vec![procedure, "SELECT 4"]
+        );
+
+        // Columns and labels named after keywords don't open or close blocks
+        let trigger = "CREATE TRIGGER trg BEFORE INSERT ON t FOR EACH ROW \
+                       BEGIN SET NEW.`end` = 1, NEW.begin = OLD.end; END";
+        assert_eq!(
+            split_statements(Dialect::MySQL, &format!("{trigger}; SELECT 1")),
+            vec![trigger, "SELECT 1"]
+        );
+    }
+
+    #[test]
+    fn split_single_statement() {
+        assert_eq!(
+            split_statements(Dialect::MySQL, "  SELECT 1;  "),
+            vec!["SELECT 1"]
+        );
+        assert!(split_statements(Dialect::MySQL, " ; ").is_empty());
+    }
+
     #[test]
     fn drop_all_caches() {
         let res = parse_query(Dialect::MySQL, "drOP ALL    caCHEs").unwrap();
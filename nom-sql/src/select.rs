@@ -1,7 +1,7 @@
 use std::fmt::Display;
-use std::{fmt, str};
+use std::{fmt, iter, mem, str};
 
-use itertools::Itertools;
+use itertools::{Either, Itertools};
 use nom::branch::alt;
 use nom::bytes::complete::{tag, tag_no_case};
 use nom::combinator::{map, opt};
@@ -17,8 +17,8 @@ use serde::{Deserialize, Serialize};
 use test_strategy::Arbitrary;
 
 use crate::common::{
-    field_definition_expr, field_list, field_reference_list, terminated_with_statement_terminator,
-    ws_sep_comma, FieldDefinitionExpr,
+    field_definition_expr, field_list, field_reference, field_reference_list,
+    terminated_with_statement_terminator, ws_sep_comma, FieldDefinitionExpr,
 };
 use crate::dialect::CommaSeparatedList;
 use crate::expression::expression;
@@ -36,19 +36,149 @@ use crate::{
 )]
 pub struct GroupByClause {
     pub fields: Vec<FieldReference>,
+    /// Grouping sets to group by in addition to `fields`, eg `ROLLUP(a, b)` in `GROUP BY x,
+    /// ROLLUP(a, b)`. The fields in `fields` are included in every grouping set.
+    pub grouping_sets: Option<GroupingSets>,
+}
+
+impl GroupByClause {
+    /// Returns an iterator over all the fields referenced by this clause, including the fields in
+    /// its grouping sets
+    pub fn all_fields(&self) -> impl Iterator<Item = &FieldReference> {
+        self.fields
+            .iter()
+            .chain(self.grouping_sets.iter().flat_map(|gs| gs.fields()))
+    }
+
+    /// Returns an iterator over mutable references to all the fields referenced by this clause,
+    /// including the fields in its grouping sets
+    pub fn all_fields_mut(&mut self) -> impl Iterator<Item = &mut FieldReference> {
+        self.fields
+            .iter_mut()
+            .chain(self.grouping_sets.iter_mut().flat_map(|gs| gs.fields_mut()))
+    }
+
+    /// If this clause has grouping sets, returns the full list of sets of fields that rows are
+    /// grouped by, each of which includes the fields in `fields`. Returns [`None`] if this clause
+    /// only groups by `fields`.
+    pub fn expanded_grouping_sets(&self) -> Option<Vec<Vec<&FieldReference>>> {
+        let grouping_sets = self.grouping_sets.as_ref()?;
+        Some(
+            grouping_sets
+                .expand()
+                .into_iter()
+                .map(|set| self.fields.iter().chain(set).collect())
+                .collect(),
+        )
+    }
 }
 
 impl DialectDisplay for GroupByClause {
     fn display(&self, dialect: Dialect) -> impl fmt::Display + '_ {
         fmt_with(move |f| {
-            write!(
-                f,
-                "GROUP BY {}",
-                self.fields
-                    .iter()
-                    .map(|field| field.display(dialect))
-                    .join(", ")
-            )
+            let fields = self.fields.iter().map(|field| field.display(dialect));
+            match &self.grouping_sets {
+                None => write!(f, "GROUP BY {}", fields.join(", ")),
+                // MySQL only supports rollups with the `WITH ROLLUP` modifier
+                Some(GroupingSets::Rollup(rollup))
+                    if dialect == Dialect::MySQL && self.fields.is_empty() =>
+                {
+                    write!(
+                        f,
+                        "GROUP BY {} WITH ROLLUP",
+                        rollup.iter().map(|field| field.display(dialect)).join(", ")
+                    )
+                }
+                Some(grouping_sets) => write!(
+                    f,
+                    "GROUP BY {}",
+                    fields
+                        .map(|field| field.to_string())
+                        .chain(iter::once(grouping_sets.display(dialect).to_string()))
+                        .join(", ")
+                ),
+            }
+        })
+    }
+}
+
+/// The grouping sets in a `GROUP BY` clause. Each grouping set groups the rows of the query
+/// separately, and the results of all of the groupings are returned together, with the columns
+/// that aren't in a grouping set set to NULL in the rows for that grouping set.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Arbitrary)]
+pub enum GroupingSets {
+    /// `ROLLUP(a, b, c)` (or MySQL's `GROUP BY a, b, c WITH ROLLUP`), which groups by every
+    /// prefix of the given fields: `(a, b, c)`, `(a, b)`, `(a)`, and `()`
+    Rollup(Vec<FieldReference>),
+    /// `CUBE(a, b)`, which groups by every subset of the given fields: `(a, b)`, `(a)`, `(b)`, and
+    /// `()`
+    Cube(Vec<FieldReference>),
+    /// `GROUPING SETS ((a, b), (c), ())`, which groups by each of the given sets of fields
+    Sets(Vec<Vec<FieldReference>>),
+}
+
+impl GroupingSets {
+    /// Returns an iterator over all the fields referenced by these grouping sets
+    pub fn fields(&self) -> impl Iterator<Item = &FieldReference> {
+        match self {
+            GroupingSets::Rollup(fields) | GroupingSets::Cube(fields) => {
+                Either::Left(fields.iter())
+            }
+            GroupingSets::Sets(sets) => Either::Right(sets.iter().flatten()),
+        }
+    }
+
+    /// Returns an iterator over mutable references to all the fields referenced by these grouping
+    /// sets
+    pub fn fields_mut(&mut self) -> impl Iterator<Item = &mut FieldReference> {
+        match self {
+            GroupingSets::Rollup(fields) | GroupingSets::Cube(fields) => {
+                Either::Left(fields.iter_mut())
+            }
+            GroupingSets::Sets(sets) => Either::Right(sets.iter_mut().flatten()),
+        }
+    }
+
+    /// Returns the explicit list of sets of fields that these grouping sets group by
+    pub fn expand(&self) -> Vec<Vec<&FieldReference>> {
+        match self {
+            GroupingSets::Rollup(fields) => (0..=fields.len())
+                .rev()
+                .map(|len| fields[..len].iter().collect())
+                .collect(),
+            GroupingSets::Cube(fields) => (0..(1usize << fields.len()))
+                .rev()
+                .map(|mask| {
+                    fields
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| mask & (1 << (fields.len() - 1 - i)) != 0)
+                        .map(|(_, field)| field)
+                        .collect()
+                })
+                .collect(),
+            GroupingSets::Sets(sets) => sets.iter().map(|set| set.iter().collect()).collect(),
+        }
+    }
+}
+
+impl DialectDisplay for GroupingSets {
+    fn display(&self, dialect: Dialect) -> impl fmt::Display + '_ {
+        fmt_with(move |f| {
+            let fmt_fields = |fields: &[FieldReference]| {
+                fields.iter().map(|field| field.display(dialect)).join(", ")
+            };
+            match self {
+                GroupingSets::Rollup(fields) => write!(f, "ROLLUP({})", fmt_fields(fields)),
+                GroupingSets::Cube(fields) => write!(f, "CUBE({})", fmt_fields(fields)),
+                GroupingSets::Sets(sets) => write!(
+                    f,
+                    "GROUPING SETS ({})",
+                    sets.iter()
+                        .map(|set| format!("({})", fmt_fields(set)))
+                        .join(", ")
+                ),
+            }
         })
     }
 }
@@ -302,6 +432,8 @@ impl SelectStatement {
                         | FunctionExpr::Max(_)
                         | FunctionExpr::Min(_)
                         | FunctionExpr::GroupConcat { .. }
+                        | FunctionExpr::ApproxCountDistinct(_)
+                        | FunctionExpr::ApproxPercentile { .. }
                 ),
                 Expr::NestedSelect(select) => select.contains_aggregate_select(),
                 _ => false,
@@ -388,8 +520,110 @@ pub fn group_by_clause(
         let (i, _) = whitespace1(i)?;
         let (i, _) = tag_no_case("by")(i)?;
         let (i, _) = whitespace1(i)?;
-        let (i, fields) = field_reference_list(dialect)(i)?;
-        Ok((i, GroupByClause { fields }))
+        let (i, elements) = separated_list1(
+            ws_sep_comma,
+            alt((
+                map(grouping_sets(dialect), GroupByElement::GroupingSets),
+                map(field_reference(dialect), GroupByElement::Field),
+            )),
+        )(i)?;
+        let (i, with_rollup) = opt(tuple((
+            whitespace1,
+            tag_no_case("with"),
+            whitespace1,
+            tag_no_case("rollup"),
+        )))(i)?;
+
+        let mut fields = vec![];
+        let mut grouping_sets = None;
+        for element in elements {
+            match element {
+                GroupByElement::Field(field) => fields.push(field),
+                // We only support a single set of grouping sets per GROUP BY clause
+                GroupByElement::GroupingSets(_) if grouping_sets.is_some() => {
+                    return Err(nom::Err::Error(NomSqlError {
+                        input: i,
+                        kind: ErrorKind::Tag,
+                    }))
+                }
+                GroupByElement::GroupingSets(gs) => grouping_sets = Some(gs),
+            }
+        }
+
+        if with_rollup.is_some() {
+            if grouping_sets.is_some() {
+                return Err(nom::Err::Error(NomSqlError {
+                    input: i,
+                    kind: ErrorKind::Tag,
+                }));
+            }
+            grouping_sets = Some(GroupingSets::Rollup(mem::take(&mut fields)));
+        }
+
+        Ok((
+            i,
+            GroupByClause {
+                fields,
+                grouping_sets,
+            },
+        ))
+    }
+}
+
+enum GroupByElement {
+    Field(FieldReference),
+    GroupingSets(GroupingSets),
+}
+
+// Parse ROLLUP(...), CUBE(...), or GROUPING SETS (...) in a GROUP BY clause
+fn grouping_sets(
+    dialect: Dialect,
+) -> impl Fn(LocatedSpan<&[u8]>) -> NomSqlResult<&[u8], GroupingSets> {
+    move |i| {
+        let parenthesized_fields = || {
+            delimited(
+                terminated(tag("("), whitespace0),
+                field_reference_list(dialect),
+                preceded(whitespace0, tag(")")),
+            )
+        };
+        let grouping_set = alt((
+            parenthesized_fields(),
+            map(field_reference(dialect), |field| vec![field]),
+        ));
+
+        alt((
+            map(
+                preceded(
+                    terminated(tag_no_case("rollup"), whitespace0),
+                    parenthesized_fields(),
+                ),
+                GroupingSets::Rollup,
+            ),
+            map(
+                preceded(
+                    terminated(tag_no_case("cube"), whitespace0),
+                    parenthesized_fields(),
+                ),
+                GroupingSets::Cube,
+            ),
+            map(
+                preceded(
+                    tuple((
+                        tag_no_case("grouping"),
+                        whitespace1,
+                        tag_no_case("sets"),
+                        whitespace0,
+                    )),
+                    delimited(
+                        terminated(tag("("), whitespace0),
+                        separated_list1(ws_sep_comma, grouping_set),
+                        preceded(whitespace0, tag(")")),
+                    ),
+                ),
+                GroupingSets::Sets,
+            ),
+        ))(i)
     }
 }
 
@@ -1215,6 +1449,7 @@ mod tests {
             fields: vec![FieldDefinitionExpr::from(Expr::Call(agg_expr))],
             group_by: Some(GroupByClause {
                 fields: vec![FieldReference::Expr(Expr::Column(Column::from("aid")))],
+                grouping_sets: None,
             }),
             ..Default::default()
         };
@@ -1235,6 +1470,7 @@ mod tests {
             fields: vec![FieldDefinitionExpr::from(Expr::Call(agg_expr))],
             group_by: Some(GroupByClause {
                 fields: vec![FieldReference::Expr(Expr::Column(Column::from("aid")))],
+                grouping_sets: None,
             }),
             ..Default::default()
         };
@@ -1267,6 +1503,7 @@ mod tests {
             fields: vec![FieldDefinitionExpr::from(Expr::Call(agg_expr))],
             group_by: Some(GroupByClause {
                 fields: vec![FieldReference::Expr(Expr::Column(Column::from("aid")))],
+                grouping_sets: None,
             }),
             ..Default::default()
         };
@@ -1299,6 +1536,7 @@ mod tests {
             fields: vec![FieldDefinitionExpr::from(Expr::Call(agg_expr))],
             group_by: Some(GroupByClause {
                 fields: vec![FieldReference::Expr(Expr::Column(Column::from("aid")))],
+                grouping_sets: None,
             }),
             ..Default::default()
         };
@@ -1332,6 +1570,7 @@ mod tests {
             fields: vec![FieldDefinitionExpr::from(Expr::Call(agg_expr))],
             group_by: Some(GroupByClause {
                 fields: vec![FieldReference::Expr(Expr::Column(Column::from("aid")))],
+                grouping_sets: None,
             }),
             ..Default::default()
         };
@@ -1380,6 +1619,7 @@ mod tests {
                 fields: vec![FieldReference::Expr(Expr::Column(Column::from(
                     "votes.comment_id",
                 )))],
+                grouping_sets: None,
             }),
             ..Default::default()
         };
@@ -1997,11 +2237,41 @@ mod tests {
             assert_eq!(
                 res.group_by,
                 Some(GroupByClause {
-                    fields: vec![FieldReference::Numeric(1)]
+                    fields: vec![FieldReference::Numeric(1)],
+                    grouping_sets: None,
                 })
             )
         }
 
+        #[test]
+        fn group_by_with_rollup() {
+            let res = test_parse!(
+                selection(Dialect::MySQL),
+                b"SELECT region, store, sum(sales) FROM t GROUP BY region, store WITH ROLLUP"
+            );
+            let group_by = res.group_by.clone().unwrap();
+            assert_eq!(
+                group_by,
+                GroupByClause {
+                    fields: vec![],
+                    grouping_sets: Some(GroupingSets::Rollup(vec![
+                        FieldReference::Expr(Expr::Column("region".into())),
+                        FieldReference::Expr(Expr::Column("store".into())),
+                    ])),
+                }
+            );
+            assert_eq!(
+                group_by.expanded_grouping_sets().unwrap().len(),
+                3,
+                "(region, store), (region), ()"
+            );
+            assert_eq!(
+                res.display(Dialect::MySQL).to_string(),
+                "SELECT `region`, `store`, sum(`sales`) FROM `t` GROUP BY `region`, `store` WITH \
+                 ROLLUP"
+            );
+        }
+
         #[test]
         fn order_by_column_number() {
             let res = test_parse!(selection(Dialect::MySQL), b"SELECT id FROM t ORDER BY 1");
@@ -2276,6 +2546,70 @@ mod tests {
             );
         }
 
+        #[test]
+        fn group_by_grouping_sets() {
+            let col = |c: &str| FieldReference::Expr(Expr::Column(c.into()));
+
+            let res = test_parse!(
+                selection(Dialect::PostgreSQL),
+                b"SELECT a, b, c, count(*) FROM t GROUP BY a, ROLLUP(b, c)"
+            );
+            let group_by = res.group_by.clone().unwrap();
+            assert_eq!(
+                group_by,
+                GroupByClause {
+                    fields: vec![col("a")],
+                    grouping_sets: Some(GroupingSets::Rollup(vec![col("b"), col("c")])),
+                }
+            );
+            assert_eq!(
+                group_by.expanded_grouping_sets().unwrap(),
+                vec![
+                    vec![&col("a"), &col("b"), &col("c")],
+                    vec![&col("a"), &col("b")],
+                    vec![&col("a")],
+                ]
+            );
+            assert_eq!(
+                res.display(Dialect::PostgreSQL).to_string(),
+                "SELECT \"a\", \"b\", \"c\", count(*) FROM \"t\" GROUP BY \"a\", ROLLUP(\"b\", \
+                 \"c\")"
+            );
+
+            let res = test_parse!(
+                selection(Dialect::PostgreSQL),
+                b"SELECT a, b, count(*) FROM t GROUP BY CUBE (a, b)"
+            );
+            assert_eq!(
+                res.group_by.unwrap().expanded_grouping_sets().unwrap(),
+                vec![
+                    vec![&col("a"), &col("b")],
+                    vec![&col("a")],
+                    vec![&col("b")],
+                    vec![]
+                ]
+            );
+
+            let res = test_parse!(
+                selection(Dialect::PostgreSQL),
+                b"SELECT a, b, count(*) FROM t GROUP BY GROUPING SETS ((a, b), a, ())"
+            );
+            let group_by = res.group_by.clone().unwrap();
+            assert_eq!(
+                group_by.grouping_sets,
+                Some(GroupingSets::Sets(vec![
+                    vec![col("a"), col("b")],
+                    vec![col("a")],
+                    vec![],
+                ]))
+            );
+            assert_eq!(
+                res.display(Dialect::PostgreSQL).to_string(),
+                "SELECT \"a\", \"b\", count(*) FROM \"t\" GROUP BY GROUPING SETS ((\"a\", \
+                 \"b\"), (\"a\"), ())"
+            );
+        }
+
         #[test]
         fn flarum_select_roundtrip_1() {
             let qstr = "select exists(select * from \"groups\" where \"id\" = ?) as \"exists\"";
@@ -266,8 +266,8 @@ pub enum NotReplicatedReason {
     /// TableDropped indicates that the table that was snapshotted has been dropped
     /// and because of this it is no longer being replicated.
     TableDropped,
-    /// Partitioned indicates that the table is a partitioned table which are not
-    /// supported by ReadySet.
+    /// Partitioned indicates that the table is a partition of a partitioned table, which is
+    /// replicated as part of its partition root rather than on its own.
     Partitioned,
     /// UnsupportedType indicates that a column type in the table is not supported.
     /// This will only reference the first unsupported type. If there are more than
@@ -285,7 +285,7 @@ impl NotReplicatedReason {
         match self {
                 NotReplicatedReason::Configuration => "The table was either excluded from replicated-tables or included in replication-tables-ignore option.".to_string(),
                 NotReplicatedReason::TableDropped => "Table has been dropped.".to_string(),
-                NotReplicatedReason::Partitioned => "Partitioned tables are not supported.".to_string(),
+                NotReplicatedReason::Partitioned => "Partitions are replicated through their partition root.".to_string(),
                 NotReplicatedReason::UnsupportedType(reason) => {
                     let prefix = "Unsupported type:";
                     if let Some(start) = reason.find(prefix) {
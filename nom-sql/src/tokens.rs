@@ -0,0 +1,281 @@
+//! A lexer which splits SQL text into [`Token`]s without parsing it.
+//!
+//! This is for code which needs to look at the text of queries we might not be able to parse, such
+//! as when splitting a multi-statement query or looking for the first keyword of a statement, but
+//! can't afford to be confused by `;`s, keywords or comments that appear within string literals,
+//! quoted identifiers or comments. Every byte of the input is part of exactly one token, so the
+//! text of the tokens always adds up to the input.
+
+use nom::branch::alt;
+use nom::combinator::map;
+use nom_locate::LocatedSpan;
+
+use crate::{Dialect, NomSqlResult};
+
+/// The kind of a [`Token`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A run of whitespace
+    Whitespace,
+    /// An unquoted identifier, keyword or number
+    Word,
+    /// A string literal or quoted identifier, including its quotes. Unterminated literals are a
+    /// single character long.
+    Quoted,
+    /// A PostgreSQL dollar-quoted string (`$tag$ ... $tag$`), which extends to the end of the
+    /// input if it's unterminated
+    DollarQuoted,
+    /// A comment which ends at the end of the line, including the line ending
+    LineComment,
+    /// A `/* ... */` comment, which extends to the end of the input if it's unterminated
+    BlockComment,
+    /// Any other single character, such as `;`, `(` or an operator
+    Punctuation,
+}
+
+/// A single token of SQL text, returned by [`tokenize`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    /// What kind of token this is
+    pub kind: TokenKind,
+    /// The text of the token
+    pub text: &'a str,
+    /// The byte offset of the token within the input
+    pub offset: usize,
+}
+
+impl<'a> Token<'a> {
+    /// Returns true if the token is whitespace or a comment, which don't affect the meaning of the
+    /// statement they're in
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.kind,
+            TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment
+        )
+    }
+
+    /// Returns true if the token is a word equal to `keyword`, ignoring case
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    /// Returns true if the token is the punctuation character `c`
+    pub fn is_punctuation(&self, c: char) -> bool {
+        self.kind == TokenKind::Punctuation && self.text.starts_with(c)
+    }
+}
+
+/// Returns the length of the word (an unquoted identifier, keyword or number) at the start of `i`
+fn word_len(i: &[u8]) -> usize {
+    i.iter()
+        .position(|c| !(c.is_ascii_alphanumeric() || *c == b'_' || *c == b'$'))
+        .unwrap_or(i.len())
+}
+
+/// Returns the length of the end-of-line comment at the start of `i` (including the line ending),
+/// or `None` if `i` doesn't start with a comment in the given dialect.
+///
+/// In MySQL, both `#` and `-- ` start a comment, but `--` must be followed by whitespace or a
+/// control character, so that `1--1` is a subtraction. PostgreSQL only has `--` comments.
+fn eol_comment_len(dialect: Dialect, i: &[u8]) -> Option<usize> {
+    let is_comment = match dialect {
+        Dialect::MySQL => {
+            i.starts_with(b"#")
+                || (i.starts_with(b"--")
+                    && i.get(2)
+                        .map_or(true, |c| c.is_ascii_whitespace() || c.is_ascii_control()))
+        }
+        Dialect::PostgreSQL => i.starts_with(b"--"),
+    };
+    is_comment.then(|| {
+        i.iter()
+            .position(|c| *c == b'\n')
+            .map_or(i.len(), |n| n + 1)
+    })
+}
+
+/// Returns the length of the PostgreSQL dollar-quoted string (`$tag$ ... $tag$`) at the start of
+/// `i`, or `None` if `i` doesn't start with one. Unterminated strings extend to the end of `i`.
+fn dollar_quoted_len(i: &[u8]) -> Option<usize> {
+    let tag_len = i[1..]
+        .iter()
+        .position(|c| !(c.is_ascii_alphanumeric() || *c == b'_'))
+        .unwrap_or(i.len() - 1);
+    if i[1..].first().is_some_and(u8::is_ascii_digit) || i.get(tag_len + 1) != Some(&b'$') {
+        return None;
+    }
+    let delimiter = &i[..tag_len + 2];
+    let body = &i[delimiter.len()..];
+    Some(
+        body.windows(delimiter.len())
+            .position(|w| w == delimiter)
+            .map_or(i.len(), |n| delimiter.len() * 2 + n),
+    )
+}
+
+/// Returns the length of the string literal or quoted identifier at the start of `i`, or `None` if
+/// it isn't terminated
+fn quoted_len(dialect: Dialect, i: &[u8]) -> Option<usize> {
+    let quoted = |i: LocatedSpan<&[u8]>| -> NomSqlResult<&[u8], ()> {
+        alt((
+            map(dialect.string_literal(), |_| ()),
+            map(dialect.identifier(), |_| ()),
+        ))(i)
+    };
+    match quoted(LocatedSpan::new(i)) {
+        Ok((remaining, ())) if remaining.location_offset() > 0 => Some(remaining.location_offset()),
+        _ => None,
+    }
+}
+
+/// An iterator over the [`Token`]s of SQL text, returned by [`tokenize`]
+#[derive(Debug, Clone)]
+pub struct Tokens<'a> {
+    dialect: Dialect,
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let i = &self.input.as_bytes()[self.pos..];
+        let first = *i.first()?;
+        let dialect = self.dialect;
+        let (kind, len) = if first.is_ascii_whitespace() {
+            let len = i
+                .iter()
+                .position(|c| !c.is_ascii_whitespace())
+                .unwrap_or(i.len());
+            (TokenKind::Whitespace, len)
+        } else if first.is_ascii_alphanumeric() || first == b'_' {
+            (TokenKind::Word, word_len(i))
+        } else if i.starts_with(b"/*") {
+            let len = i[2..]
+                .windows(2)
+                .position(|w| w == b"*/")
+                .map_or(i.len(), |n| n + 4);
+            (TokenKind::BlockComment, len)
+        } else if let Some(len) = eol_comment_len(dialect, i) {
+            (TokenKind::LineComment, len)
+        } else if let Some(len) = (first == b'$' && dialect == Dialect::PostgreSQL)
+            .then(|| dollar_quoted_len(i))
+            .flatten()
+        {
+            (TokenKind::DollarQuoted, len)
+        } else if let Some(len) = matches!(first, b'\'' | b'"' | b'`')
+            .then(|| quoted_len(dialect, i))
+            .flatten()
+        {
+            (TokenKind::Quoted, len)
+        } else {
+            let len = self.input[self.pos..]
+                .chars()
+                .next()
+                .map_or(1, char::len_utf8);
+            (TokenKind::Punctuation, len)
+        };
+
+        let token = Token {
+            kind,
+            text: &self.input[self.pos..self.pos + len],
+            offset: self.pos,
+        };
+        self.pos += len;
+        Some(token)
+    }
+}
+
+/// Split `input` into the [`Token`]s it consists of, according to the lexical rules of `dialect`
+pub fn tokenize(dialect: Dialect, input: &str) -> Tokens<'_> {
+    Tokens {
+        dialect,
+        input,
+        pos: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(dialect: Dialect, input: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(dialect, input)
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn tokens_cover_input() {
+        let input = "SELECT 'a;''b', `c`, \"d\" /* e */ FROM t -- f\n WHERE x >= 1é;";
+        let tokens = tokenize(Dialect::MySQL, input).collect::<Vec<_>>();
+        assert_eq!(tokens.iter().map(|t| t.text).collect::<String>(), input);
+        for token in &tokens {
+            assert_eq!(
+                &input[token.offset..token.offset + token.text.len()],
+                token.text
+            );
+        }
+    }
+
+    #[test]
+    fn mysql_tokens() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds(Dialect::MySQL, "SELECT 'a;b' # c\n,`d`/*e*/;"),
+            vec![
+                (Word, "SELECT"),
+                (Whitespace, " "),
+                (Quoted, "'a;b'"),
+                (Whitespace, " "),
+                (LineComment, "# c\n"),
+                (Punctuation, ","),
+                (Quoted, "`d`"),
+                (BlockComment, "/*e*/"),
+                (Punctuation, ";"),
+            ]
+        );
+        assert_eq!(
+            kinds(Dialect::MySQL, "1--1"),
+            vec![
+                (Word, "1"),
+                (Punctuation, "-"),
+                (Punctuation, "-"),
+                (Word, "1")
+            ]
+        );
+    }
+
+    #[test]
+    fn postgres_tokens() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds(Dialect::PostgreSQL, "$a$x;$a$ $1 # --c"),
+            vec![
+                (DollarQuoted, "$a$x;$a$"),
+                (Whitespace, " "),
+                (Punctuation, "$"),
+                (Word, "1"),
+                (Whitespace, " "),
+                (Punctuation, "#"),
+                (Whitespace, " "),
+                (LineComment, "--c"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_tokens() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds(Dialect::MySQL, "'a /* b"),
+            vec![
+                (Punctuation, "'"),
+                (Word, "a"),
+                (Whitespace, " "),
+                (BlockComment, "/* b")
+            ]
+        );
+    }
+}
@@ -25,6 +25,7 @@ const ID_ERROR_RESPONSE: u8 = b'E';
 const ID_PARAMETER_DESCRIPTION: u8 = b't';
 const ID_PARAMETER_STATUS: u8 = b'S';
 const ID_PARSE_COMPLETE: u8 = b'1';
+const ID_PORTAL_SUSPENDED: u8 = b's';
 const ID_READY_FOR_QUERY: u8 = b'Z';
 const ID_ROW_DESCRIPTION: u8 = b'T';
 const ID_NO_DATA: u8 = b'n';
@@ -232,6 +233,11 @@ fn encode(message: BackendMessage, dst: &mut BytesMut) -> Result<(), Error> {
             put_i32(LENGTH_PLACEHOLDER, dst);
         }
 
+        PortalSuspended => {
+            put_u8(ID_PORTAL_SUSPENDED, dst);
+            put_i32(LENGTH_PLACEHOLDER, dst);
+        }
+
         PassThroughSimpleRow(row) => {
             put_u8(ID_DATA_ROW, dst);
             // Put the length of this row in bytes. The length is equal to the length of the data,
@@ -951,6 +957,17 @@ mod tests {
         assert_eq!(buf, exp);
     }
 
+    #[test]
+    fn test_encode_portal_suspended() {
+        let mut codec = Codec::new();
+        let mut buf = BytesMut::new();
+        codec.encode(PortalSuspended, &mut buf).unwrap();
+        let mut exp = BytesMut::new();
+        exp.put_u8(b's'); // message id
+        exp.put_i32(4); // message length
+        assert_eq!(buf, exp);
+    }
+
     #[test]
     fn test_encode_passthrough_data_row() {
         let mut codec = Codec::new();
@@ -57,6 +57,10 @@ pub enum Credentials<'a> {
     /// Any credentials are accepted for this user
     Any,
     CleartextPassword(&'a str),
+    /// Credentials for this user are checked against the upstream database, with
+    /// [`PsqlBackend::authenticate_upstream`]. This requires the client to send its password in
+    /// cleartext.
+    Upstream,
 }
 
 /// A trait for implementing a SQL backend that produces responses to SQL query statements. This
@@ -86,6 +90,19 @@ pub trait PsqlBackend {
     /// Look up authentication credentials for the given user
     fn credentials_for_user(&self, user: &str) -> Option<Credentials>;
 
+    /// Check the given credentials against the upstream database, returning true if they are
+    /// valid. Only called for users whose credentials are [`Credentials::Upstream`].
+    async fn authenticate_upstream(&mut self, _user: &str, _password: &str) -> bool {
+        false
+    }
+
+    /// Return true if clients may send their password in cleartext to be checked against the
+    /// upstream database over connections that don't use TLS. Otherwise, users whose credentials
+    /// are [`Credentials::Upstream`] are refused unless they connect with TLS.
+    fn allows_unencrypted_upstream_authentication(&self) -> bool {
+        false
+    }
+
     /// Performs the specified SQL query.
     ///
     /// * `query` - The sql query to perform.
@@ -119,6 +136,44 @@ pub trait PsqlBackend {
         result_transfer_formats: &[TransferFormat],
     ) -> Result<QueryResponse<Self::Resultset>, Error>;
 
+    /// Executes a previously prepared SQL query like [`PsqlBackend::on_execute`], but only returns
+    /// the first `limit` rows of its results, leaving the rest in a cursor to be read with
+    /// [`PsqlBackend::on_fetch`]. This lets a portal be suspended without reading the rest of its
+    /// results into memory.
+    ///
+    /// * `cursor` - An identifier for the cursor, unique among the cursors open on this connection.
+    /// * `hold` - Whether the cursor has to stay open after the current transaction ends, until
+    ///   it's closed with [`PsqlBackend::on_close_cursor`].
+    /// * returns - `None`, without executing the statement, if its results can't be kept in a
+    ///   cursor.
+    async fn on_execute_cursor(
+        &mut self,
+        _statement_id: u32,
+        _params: &[PsqlValue],
+        _result_transfer_formats: &[TransferFormat],
+        _cursor: u32,
+        _hold: bool,
+        _limit: u32,
+    ) -> Result<Option<QueryResponse<Self::Resultset>>, Error> {
+        Ok(None)
+    }
+
+    /// Reads the next `limit` rows, or all of its remaining rows if `limit` is zero, from a cursor
+    /// opened by [`PsqlBackend::on_execute_cursor`].
+    async fn on_fetch(
+        &mut self,
+        _cursor: u32,
+        _limit: u32,
+        _result_transfer_formats: &[TransferFormat],
+    ) -> Result<Self::Resultset, Error> {
+        Err(Error::Unsupported("Cursors are not supported".to_string()))
+    }
+
+    /// Closes a cursor opened by [`PsqlBackend::on_execute_cursor`].
+    async fn on_close_cursor(&mut self, _cursor: u32) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Closes (deallocates) a prepared statement.
     ///
     /// * `statement_id` - The identifier of the prepared statement to close.
@@ -127,6 +182,10 @@ pub trait PsqlBackend {
     /// Determine if the connection is in an open transaction.
     fn in_transaction(&self) -> bool;
 
+    /// Called once the response to a `Query` or `Sync` message has been sent to the client, after
+    /// which the client is idle until it sends its next request.
+    fn on_ready_for_query(&mut self) {}
+
     /// Loads any extended types from the upstream postgres, returning a map of Oid to typelen
     async fn load_extended_types(&mut self) -> Result<HashMap<Oid, i16>, Error>;
 }
@@ -82,6 +82,9 @@ pub enum BackendMessage {
         parameter_value: String,
     },
     ParseComplete,
+    /// Sent in place of `CommandComplete` when an `Execute` reached its row limit before the end
+    /// of the portal's results
+    PortalSuspended,
     ReadyForQuery {
         status: u8,
     },
@@ -1,12 +1,15 @@
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
+use futures::{Stream, StreamExt};
 use postgres::SimpleQueryMessage;
 use postgres_protocol::Oid;
 use postgres_types::{Kind, Type};
 use readyset_adapter_types::DeallocateId;
-use smallvec::smallvec;
+use smallvec::{smallvec, SmallVec};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_postgres::CommandCompleteContents;
 use tracing::trace;
@@ -29,7 +32,7 @@ use crate::scram::{
 };
 use crate::value::PsqlValue;
 use crate::QueryResponse::*;
-use crate::{Column, Credentials, PrepareResponse, PsqlBackend};
+use crate::{Column, Credentials, PrepareResponse, PsqlBackend, PsqlSrvRow, QueryResponse};
 
 const ATTTYPMOD_NONE: i32 = -1;
 const TRANSFER_FORMAT_PLACEHOLDER: TransferFormat = TransferFormat::Text;
@@ -113,7 +116,10 @@ pub(crate) enum State {
 
 /// A struct to maintain state for an implementation of the backend side of the PostgreSQL
 /// frontend/backend protocol.
-pub struct Protocol {
+///
+/// `R` is the type of the resultsets returned by the backend, which are kept around for portals
+/// whose execution was suspended.
+pub struct Protocol<R> {
     /// The current state of the request-response flow
     state: State,
 
@@ -128,6 +134,26 @@ pub struct Protocol {
     /// values as well as metadata about the portal, and is keyed by the portal's name.
     portals: HashMap<String, PortalData>,
 
+    /// The remaining results of portals whose execution was suspended because an `Execute`
+    /// request reached its row limit, keyed by the portal's name. The next `Execute` of the portal
+    /// resumes reading from these results, rather than executing the statement again.
+    ///
+    /// Like in Postgres, suspended results are discarded when the transaction they were created in
+    /// ends.
+    suspended_results: HashMap<String, PortalResults<R>>,
+
+    /// The cursors on the backend holding the remaining results of suspended portals, keyed by the
+    /// portal's name. Portals whose results the backend can keep in a cursor are suspended this
+    /// way instead of with `suspended_results`.
+    suspended_cursors: HashMap<String, PortalCursor>,
+
+    /// Cursors of dropped portals that outlive their transaction, which still have to be closed on
+    /// the backend
+    cursors_to_close: Vec<u32>,
+
+    /// The identifier to give the next cursor opened on the backend
+    next_cursor: u32,
+
     /// Stores a mapping of Oid -> type lengths, used for when ReadySet encounters an
     /// unsupported/custom type. On the first instance of such a type, the hashmap will be
     /// populated with the data from pg_catalog.pg_type.
@@ -136,6 +162,9 @@ pub struct Protocol {
     /// Whether to allow TLS connections.
     allow_tls_connections: bool,
 
+    /// Whether the client has completed a TLS handshake
+    encrypted: bool,
+
     /// TLS server endpoint data for channel binding as specified by
     /// [RFC5929](https://www.rfc-editor.org/rfc/rfc5929)
     tls_server_end_point: Option<Vec<u8>>,
@@ -162,16 +191,59 @@ struct PortalData {
     result_transfer_formats: Arc<Vec<TransferFormat>>,
 }
 
+/// The results of a portal, which are kept around while the portal is suspended
+enum PortalResults<R> {
+    /// Results that are read lazily from the resultset returned by the backend
+    Lazy(R),
+    /// Results that were read into memory when the portal was suspended. This is done for rows
+    /// proxied from the upstream database that the backend couldn't keep in a cursor: reading
+    /// those lazily would leave a partially consumed result stream on the upstream connection,
+    /// which blocks every other query on that connection until the portal is resumed or dropped.
+    Buffered(VecDeque<PsqlSrvRow>),
+}
+
+/// A cursor opened on the backend with [`PsqlBackend::on_execute_cursor`], which holds the rest of
+/// the results of a suspended portal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PortalCursor {
+    id: u32,
+    /// Whether the cursor outlives the transaction it was opened in. Cursors that don't are closed
+    /// by the backend when their transaction ends, so we never need to close them ourselves.
+    hold: bool,
+}
+
+impl<R> Stream for PortalResults<R>
+where
+    R: Stream<Item = Result<PsqlSrvRow, Error>> + Unpin,
+{
+    type Item = Result<PsqlSrvRow, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.get_mut() {
+            Self::Lazy(resultset) => resultset.poll_next_unpin(cx),
+            Self::Buffered(rows) => Poll::Ready(rows.pop_front().map(Ok)),
+        }
+    }
+}
+
 /// An implementation of the backend side of the PostgreSQL frontend/backend protocol. See
 /// `on_request` for the primary entry point.
-impl Protocol {
-    pub fn new() -> Protocol {
+impl<R> Protocol<R>
+where
+    R: futures::Stream<Item = Result<PsqlSrvRow, Error>> + Unpin,
+{
+    pub fn new() -> Protocol<R> {
         Protocol {
             state: State::StartingUp,
             prepared_statements: HashMap::new(),
             portals: HashMap::new(),
+            suspended_results: HashMap::new(),
+            suspended_cursors: HashMap::new(),
+            cursors_to_close: Vec::new(),
+            next_cursor: 0,
             extended_types: HashMap::new(),
             allow_tls_connections: false,
+            encrypted: false,
             tls_server_end_point: None,
         }
     }
@@ -197,12 +269,16 @@ impl Protocol {
     ///   the frontend/backend protocol state in order to parse some types of frontend messages.)
     /// * returns - A `Response` representing a sequence of `BackendMessage`s to return to the
     ///   frontend, otherwise an `Error` if a failure occurs.
-    pub async fn on_request<B: PsqlBackend, C: AsyncRead + AsyncWrite + Unpin>(
+    pub async fn on_request<B, C>(
         &mut self,
         message: FrontendMessage,
         backend: &mut B,
         channel: &mut Channel<C>,
-    ) -> Result<Response<B::Resultset>, Error> {
+    ) -> Result<Response<R>, Error>
+    where
+        B: PsqlBackend<Resultset = R>,
+        C: AsyncRead + AsyncWrite + Unpin,
+    {
         trace!(?message, "protocol on_request");
         let get_ready_message = |version| {
             smallvec![
@@ -254,9 +330,19 @@ impl Protocol {
                             get_ready_message(backend.version())
                         }
                         crate::CredentialsNeeded::Cleartext => {
-                            self.state = State::AuthenticatingCleartext {
-                                user: user.ok_or(Error::NoUserSpecified)?,
-                            };
+                            let user = user.ok_or(Error::NoUserSpecified)?;
+                            // Don't ask for a password we'd send on to the upstream unless it's
+                            // encrypted on its way to us
+                            if !self.encrypted
+                                && !backend.allows_unencrypted_upstream_authentication()
+                                && backend.credentials_for_user(&user)
+                                    == Some(Credentials::Upstream)
+                            {
+                                return Err(Error::EncryptionRequired {
+                                    username: user.to_string(),
+                                });
+                            }
+                            self.state = State::AuthenticatingCleartext { user };
                             smallvec![AuthenticationCleartextPassword]
                         }
                         crate::CredentialsNeeded::ScramSha256 => {
@@ -283,17 +369,21 @@ impl Protocol {
             State::AuthenticatingCleartext { ref user } => match message {
                 Authenticate { mut body } => {
                     let password = decoder::decode_password_message_body(&mut body)?;
-                    backend
-                        .credentials_for_user(user)
-                        .filter(|c| match c {
-                            Credentials::Any => true,
-                            Credentials::CleartextPassword(expected_password) => {
-                                &password == *expected_password
-                            }
-                        })
-                        .ok_or_else(|| Error::AuthenticationFailure {
+                    let authenticated = match backend.credentials_for_user(user) {
+                        None => false,
+                        Some(Credentials::Any) => true,
+                        Some(Credentials::CleartextPassword(expected_password)) => {
+                            &*password == expected_password
+                        }
+                        Some(Credentials::Upstream) => {
+                            backend.authenticate_upstream(user, &password).await
+                        }
+                    };
+                    if !authenticated {
+                        return Err(Error::AuthenticationFailure {
                             username: user.to_string(),
-                        })?;
+                        });
+                    }
 
                     self.state = State::Ready;
 
@@ -319,6 +409,11 @@ impl Protocol {
                         return Ok(Response::Messages(get_ready_message(backend.version())));
                     }
                     Some(Credentials::CleartextPassword(pw)) => pw,
+                    Some(Credentials::Upstream) => {
+                        return Err(Error::Unsupported(
+                            "SCRAM authentication against the upstream database".into(),
+                        ))
+                    }
                 };
 
                 let SaslInitialResponse {
@@ -424,6 +519,8 @@ impl Protocol {
             State::Error => match message {
                 Sync => {
                     self.state = State::Ready;
+                    self.end_implicit_transaction(backend);
+                    self.close_cursors(backend).await?;
                     Ok(Response::Message(BackendMessage::ready_for_query(
                         self.transaction_state(backend),
                     )))
@@ -450,6 +547,7 @@ impl Protocol {
                             Error::MissingPreparedStatement(prepared_statement_name.to_string())
                         })?;
                     let n_cols = row_schema.len();
+                    let prepared_statement_id = *prepared_statement_id;
                     let result_transfer_formats = match result_transfer_formats[..] {
                         // If no format codes are provided, use the default format (`Text`).
                         [] => vec![Text; n_cols],
@@ -464,10 +562,11 @@ impl Protocol {
                             }
                         }
                     };
+                    self.drop_suspended_results(portal_name.borrow() as &str);
                     self.portals.insert(
                         portal_name.to_string(),
                         PortalData {
-                            prepared_statement_id: *prepared_statement_id,
+                            prepared_statement_id,
                             prepared_statement_name: prepared_statement_name.to_string(),
                             params,
                             result_transfer_formats: Arc::new(result_transfer_formats),
@@ -481,6 +580,7 @@ impl Protocol {
                     match name {
                         Portal(name) => {
                             self.portals.remove(name.borrow() as &str);
+                            self.drop_suspended_results(name.borrow() as &str);
                         }
 
                         PreparedStatement(name) => {
@@ -594,7 +694,7 @@ impl Protocol {
 
                 // A request to execute a portal (a combination of a prepared statement with
                 // parameter values).
-                Execute { portal_name, .. } => {
+                Execute { portal_name, limit } => {
                     self.state = State::Extended;
                     let PortalData {
                         prepared_statement_id,
@@ -605,60 +705,169 @@ impl Protocol {
                         .portals
                         .get(portal_name.borrow() as &str)
                         .ok_or_else(|| Error::MissingPreparedStatement(portal_name.to_string()))?;
-                    let response = backend
-                        .on_execute(*prepared_statement_id, params, result_transfer_formats)
-                        .await?;
-                    let res = if let Select { resultset, .. } = response {
-                        Ok(Response::Stream {
-                            header: None,
-                            resultset,
-                            result_transfer_formats: Some(result_transfer_formats.clone()),
-                            trailer: None,
-                        })
-                    } else {
-                        let command_complete = match response {
-                            Insert(n) => BackendMessage::CommandComplete {
-                                tag: CommandCompleteTag::Insert(n),
-                            },
-                            Update(n) => BackendMessage::CommandComplete {
-                                tag: CommandCompleteTag::Update(n),
-                            },
-                            Delete(n) => BackendMessage::CommandComplete {
-                                tag: CommandCompleteTag::Delete(n),
-                            },
-                            Command(tag) => BackendMessage::PassThroughCommandComplete(tag.into()),
-                            #[allow(clippy::unreachable)]
-                            Select { .. } => {
-                                return Err(Error::InternalError(
-                                    "Received Select response for Execute".to_string(),
-                                ));
+
+                    // If a previous execution of this portal was suspended, resume reading its
+                    // results rather than executing the statement again
+                    let was_in_transaction = backend.in_transaction();
+                    let mut cursor = self.suspended_cursors.remove(portal_name.borrow() as &str);
+                    let suspended = self.suspended_results.remove(portal_name.borrow() as &str);
+                    let results = match (cursor, suspended) {
+                        (Some(PortalCursor { id, .. }), _) => PortalResults::Lazy(
+                            backend
+                                .on_fetch(id, limit.max(0) as u32, result_transfer_formats)
+                                .await?,
+                        ),
+                        (None, Some(results)) => results,
+                        (None, None) => match Self::execute_portal(
+                            backend,
+                            &mut self.next_cursor,
+                            *prepared_statement_id,
+                            params,
+                            result_transfer_formats,
+                            limit,
+                        )
+                        .await?
+                        {
+                            (Select { resultset, .. }, new_cursor) => {
+                                cursor = new_cursor;
+                                PortalResults::Lazy(resultset)
                             }
-                            SimpleQuery(_) => {
-                                return Err(Error::InternalError(
-                                    "Received SimpleQuery response for Execute".to_string(),
-                                ));
+                            (response, _) => {
+                                let command_complete = match response {
+                                    Insert(n) => BackendMessage::CommandComplete {
+                                        tag: CommandCompleteTag::Insert(n),
+                                    },
+                                    Update(n) => BackendMessage::CommandComplete {
+                                        tag: CommandCompleteTag::Update(n),
+                                    },
+                                    Delete(n) => BackendMessage::CommandComplete {
+                                        tag: CommandCompleteTag::Delete(n),
+                                    },
+                                    Command(tag) => {
+                                        BackendMessage::PassThroughCommandComplete(tag.into())
+                                    }
+                                    Select { .. } => {
+                                        return Err(Error::InternalError(
+                                            "Received Select response for Execute".to_string(),
+                                        ));
+                                    }
+                                    SimpleQuery(_) => {
+                                        return Err(Error::InternalError(
+                                            "Received SimpleQuery response for Execute".to_string(),
+                                        ));
+                                    }
+                                    Deallocate(..) => {
+                                        return Err(Error::InternalError(
+                                            "Received Deallocate command for Execute".to_string(),
+                                        ));
+                                    }
+                                    Stream { .. } => {
+                                        return Err(Error::InternalError(
+                                            "Received Stream response for Execute".to_string(),
+                                        ));
+                                    }
+                                };
+                                // An executed COMMIT or ROLLBACK ends the explicit transaction,
+                                // and with it every portal created in that transaction
+                                if was_in_transaction && !backend.in_transaction() {
+                                    self.end_transaction();
+                                }
+                                self.state = State::Ready;
+                                return Ok(Response::Message(command_complete));
                             }
-                            Deallocate(..) => {
-                                return Err(Error::InternalError(
-                                    "Received Deallocate command for Execute".to_string(),
-                                ));
+                        },
+                    };
+
+                    // A limit of zero means there is no limit, so we can stream all the rows
+                    let (limit, mut results) = match (limit, results) {
+                        (..=0, PortalResults::Lazy(resultset)) => {
+                            // Every remaining row was fetched from the cursor, so it can be closed
+                            if let Some(PortalCursor { id, hold: true }) = cursor {
+                                self.cursors_to_close.push(id);
                             }
-                            Stream { .. } => {
+                            self.state = State::Ready;
+                            return Ok(Response::Stream {
+                                header: None,
+                                resultset,
+                                result_transfer_formats: Some(result_transfer_formats.clone()),
+                                trailer: None,
+                            });
+                        }
+                        // Rows buffered when the portal was suspended are sent all at once
+                        (..=0, results) => (u64::MAX, results),
+                        (limit, results) => (limit as u64, results),
+                    };
+
+                    // Otherwise, send at most `limit` rows, and suspend the portal if we reach the
+                    // limit. Like Postgres, we don't look ahead to check whether there are any
+                    // rows left, so a portal with exactly `limit` rows left is suspended, and the
+                    // next `Execute` completes it with zero rows.
+                    let mut messages = SmallVec::new();
+                    let mut n_rows = 0;
+                    let mut proxied = false;
+                    while n_rows < limit {
+                        let Some(row) = results.next().await else {
+                            break;
+                        };
+                        messages.push(match row? {
+                            PsqlSrvRow::ValueVec(values) => BackendMessage::DataRow {
+                                values,
+                                explicit_transfer_formats: Some(result_transfer_formats.clone()),
+                            },
+                            PsqlSrvRow::RawRow(row) => {
+                                proxied = true;
+                                BackendMessage::PassThroughDataRow(row)
+                            }
+                            PsqlSrvRow::SimpleQueryMessage(_) => {
                                 return Err(Error::InternalError(
-                                    "Received Stream response for Execute".to_string(),
+                                    "Received SimpleQuery message for Execute".to_string(),
                                 ));
                             }
-                        };
-                        Ok(Response::Message(command_complete))
-                    };
+                        });
+                        n_rows += 1;
+                    }
+
+                    if n_rows == limit {
+                        // The rows after the ones we fetched are still in the cursor
+                        if let Some(cursor) = cursor {
+                            self.suspended_cursors
+                                .insert(portal_name.to_string(), cursor);
+                        } else {
+                            let results = match results {
+                                PortalResults::Lazy(mut resultset) if proxied => {
+                                    let mut rows = VecDeque::new();
+                                    while let Some(row) = resultset.next().await {
+                                        rows.push_back(row?);
+                                    }
+                                    PortalResults::Buffered(rows)
+                                }
+                                results => results,
+                            };
+                            self.suspended_results
+                                .insert(portal_name.to_string(), results);
+                        }
+                        messages.push(PortalSuspended);
+                    } else {
+                        if let Some(PortalCursor { id, hold: true }) = cursor {
+                            self.cursors_to_close.push(id);
+                        }
+                        messages.push(BackendMessage::CommandComplete {
+                            tag: CommandCompleteTag::Select(n_rows),
+                        });
+                    }
+
                     self.state = State::Ready;
-                    res
+                    Ok(Response::Messages(messages))
                 }
 
                 // A request to directly execute a complete SQL statement, without creating a
                 // prepared statement.
                 Query { query } => {
+                    self.close_cursors(backend).await?;
                     let response = backend.on_query(query.borrow()).await?;
+                    // A simple query either runs in its own implicit transaction or ends the
+                    // current explicit transaction with a COMMIT or ROLLBACK
+                    self.end_implicit_transaction(backend);
                     if let Select { schema, resultset } = response {
                         let mut field_descriptions = Vec::with_capacity(schema.len());
                         for i in schema {
@@ -808,6 +1017,8 @@ impl Protocol {
                 // sequence, or after an error has occurred.
                 Sync => {
                     self.state = State::Ready;
+                    self.end_implicit_transaction(backend);
+                    self.close_cursors(backend).await?;
                     Ok(Response::Message(BackendMessage::ready_for_query(
                         self.transaction_state(backend),
                     )))
@@ -823,6 +1034,87 @@ impl Protocol {
         }
     }
 
+    /// Drop all portals if we're not in a transaction, since the transaction they were created in
+    /// has ended.
+    fn end_implicit_transaction<B: PsqlBackend>(&mut self, backend: &B) {
+        if !backend.in_transaction() {
+            self.end_transaction();
+        }
+    }
+
+    /// Drop all portals, along with the results of any suspended portals. Like Postgres, we don't
+    /// support portals that outlive the transaction they were created in.
+    fn end_transaction(&mut self) {
+        self.portals.clear();
+        self.suspended_results.clear();
+        self.cursors_to_close.extend(
+            self.suspended_cursors
+                .drain()
+                .filter(|(_, cursor)| cursor.hold)
+                .map(|(_, cursor)| cursor.id),
+        );
+    }
+
+    /// Execute the statement of a portal. If there's a limit on the number of rows to return, ask
+    /// the backend to keep the rest of the results in a new cursor, so that suspending the portal
+    /// doesn't have to read them, and return that cursor if the backend opened one.
+    async fn execute_portal<B: PsqlBackend<Resultset = R>>(
+        backend: &mut B,
+        next_cursor: &mut u32,
+        statement_id: u32,
+        params: &[PsqlValue],
+        result_transfer_formats: &[TransferFormat],
+        limit: i32,
+    ) -> Result<(QueryResponse<R>, Option<PortalCursor>), Error> {
+        if limit > 0 {
+            let cursor = PortalCursor {
+                id: *next_cursor,
+                hold: !backend.in_transaction(),
+            };
+            if let Some(response) = backend
+                .on_execute_cursor(
+                    statement_id,
+                    params,
+                    result_transfer_formats,
+                    cursor.id,
+                    cursor.hold,
+                    limit as u32,
+                )
+                .await?
+            {
+                *next_cursor = next_cursor.wrapping_add(1);
+                return Ok((response, Some(cursor)));
+            }
+        }
+        let response = backend
+            .on_execute(statement_id, params, result_transfer_formats)
+            .await?;
+        Ok((response, None))
+    }
+
+    /// Drop the results of the portal named `name`, if it was suspended
+    fn drop_suspended_results(&mut self, name: &str) {
+        self.suspended_results.remove(name);
+        if let Some(cursor) = self.suspended_cursors.remove(name) {
+            if cursor.hold {
+                self.cursors_to_close.push(cursor.id);
+            }
+        }
+    }
+
+    /// Close the cursors of dropped portals on the backend, once we're outside a transaction.
+    /// Inside one, they stay open until it ends, since the transaction might have failed, in which
+    /// case the backend can't run anything until it's rolled back.
+    async fn close_cursors<B: PsqlBackend>(&mut self, backend: &mut B) -> Result<(), Error> {
+        if backend.in_transaction() {
+            return Ok(());
+        }
+        for cursor in std::mem::take(&mut self.cursors_to_close) {
+            backend.on_close_cursor(cursor).await?;
+        }
+        Ok(())
+    }
+
     async fn on_deallocate<B: PsqlBackend, C: AsyncRead + AsyncWrite + Unpin>(
         &mut self,
         backend: &mut B,
@@ -863,11 +1155,11 @@ impl Protocol {
     ///   one of the frontend's requests.
     /// * `in_transaction` - if the operation is within an open transaction.
     /// * returns - A `Response` containing an `ErrorResponse` message to send to the frontend.
-    pub async fn on_error<B: PsqlBackend>(
+    pub async fn on_error<B: PsqlBackend<Resultset = R>>(
         &mut self,
         error: Error,
         in_transaction: bool,
-    ) -> Result<Response<B::Resultset>, Error> {
+    ) -> Result<Response<R>, Error> {
         match self.state {
             State::StartingUp | State::Extended => {
                 self.state = State::Error;
@@ -898,6 +1190,7 @@ impl Protocol {
     /// TLS server endpoint for optional use in channel binding later.
     pub fn completed_ssl_handshake(&mut self, server_end_point: Option<Vec<u8>>) {
         self.state = State::StartingUp;
+        self.encrypted = true;
         self.tls_server_end_point = server_end_point;
     }
 
@@ -1112,6 +1405,11 @@ mod tests {
         last_execute_params: Option<Vec<PsqlValue>>,
         last_transfer_formats: Option<Vec<TransferFormat>>,
         needed_credentials: Option<Credentials<'static>>,
+        /// Whether to keep the results of executes with a limit in a cursor
+        supports_cursors: bool,
+        /// The rows left in each open cursor
+        cursors: HashMap<u32, VecDeque<PsqlSrvRow>>,
+        closed_cursors: Vec<u32>,
     }
 
     impl Backend {
@@ -1129,6 +1427,9 @@ mod tests {
                 last_execute_params: None,
                 last_transfer_formats: None,
                 needed_credentials: None,
+                supports_cursors: false,
+                cursors: HashMap::new(),
+                closed_cursors: vec![],
             }
         }
     }
@@ -1152,6 +1453,10 @@ mod tests {
             self.needed_credentials
         }
 
+        async fn authenticate_upstream(&mut self, _user: &str, password: &str) -> bool {
+            password == "upstream password"
+        }
+
         async fn on_query(&mut self, query: &str) -> Result<QueryResponse<Self::Resultset>, Error> {
             self.last_query = Some(query.to_string());
             if self.is_query_err {
@@ -1249,6 +1554,62 @@ mod tests {
             }
         }
 
+        async fn on_execute_cursor(
+            &mut self,
+            statement_id: u32,
+            params: &[PsqlValue],
+            result_transfer_formats: &[TransferFormat],
+            cursor: u32,
+            _hold: bool,
+            limit: u32,
+        ) -> Result<Option<QueryResponse<Self::Resultset>>, Error> {
+            if !self.supports_cursors {
+                return Ok(None);
+            }
+            let QueryResponse::Select { schema, resultset } = self
+                .on_execute(statement_id, params, result_transfer_formats)
+                .await?
+            else {
+                return Ok(None);
+            };
+            let rows = resultset
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<Result<_, _>>()?;
+            self.cursors.insert(cursor, rows);
+            Ok(Some(QueryResponse::Select {
+                schema,
+                resultset: self.on_fetch(cursor, limit, &[]).await?,
+            }))
+        }
+
+        async fn on_fetch(
+            &mut self,
+            cursor: u32,
+            limit: u32,
+            _result_transfer_formats: &[TransferFormat],
+        ) -> Result<Self::Resultset, Error> {
+            let rows = self
+                .cursors
+                .get_mut(&cursor)
+                .ok_or_else(|| Error::InternalError("no such cursor".to_string()))?;
+            let n = if limit == 0 {
+                rows.len()
+            } else {
+                rows.len().min(limit as usize)
+            };
+            Ok(stream::iter(
+                rows.drain(..n).map(Ok).collect::<Vec<_>>().into_iter(),
+            ))
+        }
+
+        async fn on_close_cursor(&mut self, cursor: u32) -> Result<(), Error> {
+            self.cursors.remove(&cursor);
+            self.closed_cursors.push(cursor);
+            Ok(())
+        }
+
         async fn on_close(&mut self, statement_id: DeallocateId) -> Result<(), Error> {
             self.last_close = Some(statement_id);
             Ok(())
@@ -1436,6 +1797,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn authentication_flow_upstream() {
+        let mut protocol = Protocol::new();
+        protocol.completed_ssl_handshake(None);
+        let request = FrontendMessage::StartupMessage {
+            protocol_version: 12345,
+            user: Some(bytes_str("user_name")),
+            database: Some(bytes_str("database_name")),
+        };
+        let mut backend = Backend::new();
+        backend.needed_credentials = Some(Credentials::Upstream);
+        let mut channel = Channel::<NullBytestream>::new(NullBytestream);
+        block_on(protocol.on_request(request, &mut backend, &mut channel)).unwrap();
+
+        let auth_request = FrontendMessage::Authenticate {
+            body: "upstream password\x00".into(),
+        };
+        block_on(protocol.on_request(auth_request, &mut backend, &mut channel)).unwrap();
+        assert_eq!(protocol.state, State::Ready);
+
+        let mut protocol = Protocol::new();
+        protocol.completed_ssl_handshake(None);
+        let request = FrontendMessage::StartupMessage {
+            protocol_version: 12345,
+            user: Some(bytes_str("user_name")),
+            database: Some(bytes_str("database_name")),
+        };
+        block_on(protocol.on_request(request, &mut backend, &mut channel)).unwrap();
+        let auth_request = FrontendMessage::Authenticate {
+            body: "password\x00".into(),
+        };
+        let output =
+            block_on(protocol.on_request(auth_request, &mut backend, &mut channel)).unwrap_err();
+        assert!(
+            matches!(output, Error::AuthenticationFailure { .. }),
+            "output = {output:?}"
+        );
+    }
+
+    #[test]
+    fn authentication_upstream_requires_tls() {
+        let mut protocol = Protocol::new();
+        let request = FrontendMessage::StartupMessage {
+            protocol_version: 12345,
+            user: Some(bytes_str("user_name")),
+            database: Some(bytes_str("database_name")),
+        };
+        let mut backend = Backend::new();
+        backend.needed_credentials = Some(Credentials::Upstream);
+        let mut channel = Channel::<NullBytestream>::new(NullBytestream);
+        let output =
+            block_on(protocol.on_request(request, &mut backend, &mut channel)).unwrap_err();
+        assert!(
+            matches!(output, Error::EncryptionRequired { .. }),
+            "output = {output:?}"
+        );
+    }
+
     #[test]
     fn startup_message_without_database() {
         let mut protocol = Protocol::new();
@@ -2323,6 +2742,259 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn execute_read_with_limit() {
+        let mut protocol = Protocol::new();
+        let mut backend = Backend::new();
+        let mut channel = Channel::<NullBytestream>::new(NullBytestream);
+
+        let startup_request = FrontendMessage::StartupMessage {
+            protocol_version: 12345,
+            user: Some(bytes_str("user_name")),
+            database: Some(bytes_str("database_name")),
+        };
+        protocol
+            .on_request(startup_request, &mut backend, &mut channel)
+            .await
+            .unwrap();
+
+        let parse_request = FrontendMessage::Parse {
+            prepared_statement_name: bytes_str("prepared1"),
+            query: bytes_str("SELECT * FROM test WHERE x = $1 AND y = $2;"),
+            parameter_data_types: vec![],
+        };
+        protocol
+            .on_request(parse_request, &mut backend, &mut channel)
+            .await
+            .unwrap();
+
+        let bind_request = || FrontendMessage::Bind {
+            prepared_statement_name: bytes_str("prepared1"),
+            portal_name: bytes_str("portal1"),
+            params: vec![PsqlValue::Double(0.8887), PsqlValue::Int(45678)],
+            result_transfer_formats: vec![TransferFormat::Text, TransferFormat::Binary],
+        };
+        protocol
+            .on_request(bind_request(), &mut backend, &mut channel)
+            .await
+            .unwrap();
+
+        let execute_request = |limit| FrontendMessage::Execute {
+            portal_name: bytes_str("portal1"),
+            limit,
+        };
+
+        // Each execute with a limit returns at most that many rows, then suspends the portal
+        for expected in [
+            vec![PsqlValue::Int(88), PsqlValue::Double(0.123)],
+            vec![PsqlValue::Int(22), PsqlValue::Double(0.456)],
+        ] {
+            match protocol
+                .on_request(execute_request(1), &mut backend, &mut channel)
+                .await
+                .unwrap()
+            {
+                Response::Messages(messages) => match &messages[..] {
+                    [DataRow { values, .. }, PortalSuspended] => assert_eq!(*values, expected),
+                    _ => panic!("Unexpected messages {messages:?}"),
+                },
+                _ => panic!(),
+            }
+        }
+
+        // The portal had exactly as many rows as we asked for, so the next execute completes it
+        match protocol
+            .on_request(execute_request(1), &mut backend, &mut channel)
+            .await
+            .unwrap()
+        {
+            Response::Messages(messages) => assert!(matches!(
+                &messages[..],
+                [CommandComplete {
+                    tag: CommandCompleteTag::Select(0)
+                }]
+            )),
+            _ => panic!(),
+        }
+
+        // Portals are dropped at the end of the implicit transaction, along with their results
+        protocol
+            .on_request(execute_request(1), &mut backend, &mut channel)
+            .await
+            .unwrap();
+        assert!(protocol.suspended_results.contains_key("portal1"));
+        protocol
+            .on_request(FrontendMessage::Sync, &mut backend, &mut channel)
+            .await
+            .unwrap();
+        assert!(protocol.portals.is_empty());
+        assert!(protocol.suspended_results.is_empty());
+        assert!(matches!(
+            protocol
+                .on_request(execute_request(5), &mut backend, &mut channel)
+                .await,
+            Err(Error::MissingPreparedStatement(_))
+        ));
+
+        // Binding the portal again starts over from the first row
+        protocol
+            .on_request(bind_request(), &mut backend, &mut channel)
+            .await
+            .unwrap();
+        match protocol
+            .on_request(execute_request(5), &mut backend, &mut channel)
+            .await
+            .unwrap()
+        {
+            Response::Messages(messages) => assert!(matches!(
+                &messages[..],
+                [
+                    DataRow { .. },
+                    DataRow { .. },
+                    CommandComplete {
+                        tag: CommandCompleteTag::Select(2)
+                    }
+                ]
+            )),
+            _ => panic!(),
+        }
+
+        // Results buffered when a portal was suspended are all sent by an execute without a limit
+        protocol.suspended_results.insert(
+            "portal1".to_owned(),
+            PortalResults::Buffered(VecDeque::from([
+                PsqlSrvRow::ValueVec(vec![PsqlValue::Int(1)]),
+                PsqlSrvRow::ValueVec(vec![PsqlValue::Int(2)]),
+            ])),
+        );
+        match protocol
+            .on_request(execute_request(0), &mut backend, &mut channel)
+            .await
+            .unwrap()
+        {
+            Response::Messages(messages) => assert!(matches!(
+                &messages[..],
+                [
+                    DataRow { .. },
+                    DataRow { .. },
+                    CommandComplete {
+                        tag: CommandCompleteTag::Select(2)
+                    }
+                ]
+            )),
+            _ => panic!(),
+        }
+        assert!(protocol.suspended_results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn execute_read_with_limit_from_cursor() {
+        let mut protocol = Protocol::new();
+        let mut backend = Backend::new();
+        backend.supports_cursors = true;
+        let mut channel = Channel::<NullBytestream>::new(NullBytestream);
+
+        let startup_request = FrontendMessage::StartupMessage {
+            protocol_version: 12345,
+            user: Some(bytes_str("user_name")),
+            database: Some(bytes_str("database_name")),
+        };
+        protocol
+            .on_request(startup_request, &mut backend, &mut channel)
+            .await
+            .unwrap();
+
+        let parse_request = FrontendMessage::Parse {
+            prepared_statement_name: bytes_str("prepared1"),
+            query: bytes_str("SELECT * FROM test WHERE x = $1 AND y = $2;"),
+            parameter_data_types: vec![],
+        };
+        protocol
+            .on_request(parse_request, &mut backend, &mut channel)
+            .await
+            .unwrap();
+
+        let bind_request = || FrontendMessage::Bind {
+            prepared_statement_name: bytes_str("prepared1"),
+            portal_name: bytes_str("portal1"),
+            params: vec![PsqlValue::Double(0.8887), PsqlValue::Int(45678)],
+            result_transfer_formats: vec![TransferFormat::Text, TransferFormat::Binary],
+        };
+        protocol
+            .on_request(bind_request(), &mut backend, &mut channel)
+            .await
+            .unwrap();
+
+        let execute_request = |limit| FrontendMessage::Execute {
+            portal_name: bytes_str("portal1"),
+            limit,
+        };
+
+        // The rows after the limit are left in the backend's cursor rather than read by the
+        // protocol
+        match protocol
+            .on_request(execute_request(1), &mut backend, &mut channel)
+            .await
+            .unwrap()
+        {
+            Response::Messages(messages) => match &messages[..] {
+                [DataRow { values, .. }, PortalSuspended] => {
+                    assert_eq!(*values, vec![PsqlValue::Int(88), PsqlValue::Double(0.123)])
+                }
+                _ => panic!("Unexpected messages {messages:?}"),
+            },
+            _ => panic!(),
+        }
+        assert!(protocol.suspended_results.is_empty());
+        assert_eq!(
+            protocol.suspended_cursors.get("portal1"),
+            Some(&PortalCursor { id: 0, hold: true })
+        );
+        assert_eq!(backend.cursors[&0].len(), 1);
+
+        match protocol
+            .on_request(execute_request(0), &mut backend, &mut channel)
+            .await
+            .unwrap()
+        {
+            Response::Stream { resultset, .. } => {
+                let rows = resultset.collect::<Vec<_>>().await;
+                assert_eq!(rows.len(), 1);
+            }
+            _ => panic!(),
+        }
+        assert!(protocol.suspended_cursors.is_empty());
+
+        // The cursor outlives the implicit transaction, so it's closed once the transaction ends
+        assert!(backend.closed_cursors.is_empty());
+        protocol
+            .on_request(FrontendMessage::Sync, &mut backend, &mut channel)
+            .await
+            .unwrap();
+        assert_eq!(backend.closed_cursors, vec![0]);
+
+        // Cursors of portals that are dropped while suspended are closed too
+        protocol
+            .on_request(bind_request(), &mut backend, &mut channel)
+            .await
+            .unwrap();
+        protocol
+            .on_request(execute_request(1), &mut backend, &mut channel)
+            .await
+            .unwrap();
+        protocol
+            .on_request(bind_request(), &mut backend, &mut channel)
+            .await
+            .unwrap();
+        assert!(protocol.suspended_cursors.is_empty());
+        protocol
+            .on_request(FrontendMessage::Sync, &mut backend, &mut channel)
+            .await
+            .unwrap();
+        assert_eq!(backend.closed_cursors, vec![0, 1]);
+        assert!(backend.cursors.is_empty());
+    }
+
     #[test]
     fn execute_error() {
         let mut protocol = Protocol::new();
@@ -17,7 +17,7 @@ pub struct Runner<B: PsqlBackend, C> {
     /// Read and write stream. Handles io, TLS and protocol decoding/encoding
     channel: Channel<C>,
     /// Handles Postgres protocol messages and maintains protocol state
-    protocol: Protocol,
+    protocol: Protocol<B::Resultset>,
     /// Whether to log statements received from the client
     enable_statement_logging: bool,
 }
@@ -115,6 +115,10 @@ impl<B: PsqlBackend, C: AsyncRead + AsyncWrite + Unpin> Runner<B, C> {
         }
 
         let requires_flush = request.requires_flush();
+        let ends_with_ready_for_query = matches!(
+            request,
+            FrontendMessage::Query { .. } | FrontendMessage::Sync
+        );
         let response = self
             .protocol
             .on_request(request, &mut self.backend, &mut self.channel)
@@ -125,6 +129,10 @@ impl<B: PsqlBackend, C: AsyncRead + AsyncWrite + Unpin> Runner<B, C> {
             self.channel.flush().await?;
         }
 
+        if ends_with_ready_for_query {
+            self.backend.on_ready_for_query();
+        }
+
         Ok(())
     }
 
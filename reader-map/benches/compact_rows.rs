@@ -0,0 +1,128 @@
+//! This module contains [`criterion`] benchmarks comparing maps which store rows as
+//! `Box<[DfValue]>` against maps which store them as [`CompactRow`]s.
+//!
+//! Before running the benchmarks, the total heap footprint of the rows (as tracked by
+//! [`SizeOf`]) in each representation is printed, so that the memory saved can be weighed against
+//! the extra latency of encoding rows on write and decoding them on read.
+//!
+//! ```notrust
+//! $ cargo bench -p reader-map
+//! ```
+
+use common::SizeOf;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use reader_map::CompactRow;
+use readyset_data::DfValue;
+
+const KEYS: i64 = 10_000;
+const ROWS_PER_KEY: i64 = 4;
+
+/// Generate rows shaped like a typical cached query result: a couple of integer ids, a short and
+/// a long string, a nullable column and a float
+fn rows() -> Vec<Vec<DfValue>> {
+    (0..KEYS)
+        .flat_map(|key| {
+            (0..ROWS_PER_KEY).map(move |n| {
+                vec![
+                    DfValue::from(key),
+                    DfValue::from(key * ROWS_PER_KEY + n),
+                    DfValue::from(format!("user{n}")),
+                    DfValue::from(format!(
+                        "a somewhat longer description of row {n} for key {key}"
+                    )),
+                    if n % 2 == 0 {
+                        DfValue::None
+                    } else {
+                        DfValue::from(n)
+                    },
+                    DfValue::try_from(key as f64 / 3.0).unwrap(),
+                ]
+            })
+        })
+        .collect()
+}
+
+fn memory(_: &mut Criterion) {
+    let rows = rows();
+    let boxed = rows
+        .iter()
+        .map(|r| r.clone().into_boxed_slice().deep_size_of())
+        .sum::<u64>();
+    let compact = rows
+        .iter()
+        .map(|r| CompactRow::new(r).deep_size_of())
+        .sum::<u64>();
+    println!(
+        "{} rows: Box<[DfValue]> = {boxed} bytes, CompactRow = {compact} bytes ({:.1}%)",
+        rows.len(),
+        compact as f64 / boxed as f64 * 100.0
+    );
+}
+
+fn encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode row");
+    let rows = rows();
+    let mut i = 0;
+
+    group.bench_function("Box<[DfValue]>", |b| {
+        b.iter_batched(
+            || {
+                i = (i + 1) % rows.len();
+                rows[i].clone()
+            },
+            |row| black_box(row.into_boxed_slice()),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.bench_function("CompactRow", |b| {
+        b.iter_batched(
+            || {
+                i = (i + 1) % rows.len();
+                rows[i].clone()
+            },
+            |row| black_box(CompactRow::new(&row)),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+fn lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lookup key");
+    let rows = rows();
+
+    let (mut boxed_w, boxed_r) = reader_map::new::<DfValue, Box<[DfValue]>>();
+    let (mut compact_w, compact_r) = reader_map::new::<DfValue, CompactRow>();
+    for row in &rows {
+        boxed_w.insert(row[0].clone(), row.clone().into_boxed_slice());
+        compact_w.insert(row[0].clone(), CompactRow::new(row));
+    }
+    boxed_w.insert_full_range();
+    compact_w.insert_full_range();
+    boxed_w.publish();
+    compact_w.publish();
+
+    let mut key = 0;
+    group.bench_function("Box<[DfValue]>", |b| {
+        b.iter(|| {
+            key = (key + 1) % KEYS;
+            let values = boxed_r.get(&DfValue::from(key)).unwrap().unwrap();
+            black_box(values.iter().cloned().collect::<Vec<_>>())
+        })
+    });
+
+    group.bench_function("CompactRow", |b| {
+        b.iter(|| {
+            key = (key + 1) % KEYS;
+            let values = compact_r.get(&DfValue::from(key)).unwrap().unwrap();
+            black_box(values.iter().map(CompactRow::to_row).collect::<Vec<_>>())
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, memory, encode, lookup);
+criterion_main!(benches);
@@ -11,16 +11,19 @@
 //! reader exceeds its memory quota. Once called the strategy will return an
 //! iterator over the list of keys it proposes to evict.
 //!
-//! Currently three strategies are implemented:
+//! Currently four strategies are implemented:
 //!
 //! Random: simply sample an rng to evict the required number of keys
 //! LRU: evicts the least recently used keys
 //! Generational: like LRU but the count is inexact, and bucketed into
 //! generations, generation is counted as one eviction cycle.
+//! Cost-aware: a variant of W-TinyLFU, which evicts the keys with the lowest
+//! estimated access frequency, weighted by how expensive each key was to compute.
 
-use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering::Relaxed;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8};
 use std::sync::Arc;
+use std::time::Duration;
 
 use itertools::Either;
 use rand::seq::SliceRandom;
@@ -33,6 +36,30 @@ use crate::values::Values;
 /// The value of 100 ensures the granularity will be at least 1%.
 const NUM_GENERATIONS: usize = 100;
 
+/// Number of rows in the count-min sketch used by cost-aware eviction
+const SKETCH_DEPTH: usize = 4;
+/// Log2 of the number of counters in each row of the count-min sketch, and of the number of slots
+/// in the cost table
+const SKETCH_WIDTH_BITS: u32 = 13;
+const SKETCH_WIDTH: usize = 1 << SKETCH_WIDTH_BITS;
+/// Frequency counters saturate at this value, as in TinyLFU's 4-bit counters
+const MAX_FREQUENCY: u8 = 15;
+/// Once this many accesses have been recorded, all the frequency counters are halved, so that keys
+/// which used to be popular eventually make way for keys which are popular now
+const SKETCH_SAMPLE_SIZE: u64 = 10 * SKETCH_WIDTH as u64;
+/// Odd multipliers used to derive an independent counter index for every row of the sketch
+const SKETCH_SEEDS: [u64; SKETCH_DEPTH] = [
+    0x9e37_79b9_7f4a_7c15,
+    0xc2b2_ae3d_27d4_eb4f,
+    0x1656_67b1_9e37_79f9,
+    0xd6e8_feb8_6659_fd93,
+];
+/// Replay costs are stored in units of this many microseconds, so that keys which are cheap to
+/// compute all have a weight of one
+const COST_UNIT_MICROS: u128 = 10;
+/// Percentage of the keys in the map, most recently inserted, that are kept in the admission window
+const WINDOW_PERCENT: usize = 1;
+
 /// Handles the eviction of keys from the reader map
 #[derive(Clone, Debug)]
 pub enum EvictionStrategy {
@@ -44,6 +71,9 @@ pub enum EvictionStrategy {
     /// Keeps track of how recently an entry was read with a generation accuracy, evicts the ones
     /// that are oldest
     Generational(GenerationalEviction),
+    /// Keeps track of how frequently keys are requested, and how expensive they were to compute,
+    /// and evicts the keys that would be cheapest to lose
+    CostAware(CostAwareEviction),
 }
 
 impl Default for EvictionStrategy {
@@ -78,6 +108,38 @@ pub struct LRUEviction(Arc<AtomicU64>);
 #[derive(Clone, Default, Debug)]
 pub struct GenerationalEviction(Arc<AtomicU64>);
 
+/// Performs a cost-aware variant of W-TinyLFU eviction.
+///
+/// The access frequency of every key - including keys which are not in the map, and were requested
+/// and missed - is approximated by a count-min sketch of small saturating counters, which are
+/// periodically halved so that stale popularity fades away. Alongside it, a table indexed by key
+/// hash records how long the most recent replay for each key took. Keys are evicted in order of
+/// their estimated frequency multiplied by their cost, so that a key which is rarely read but very
+/// expensive to recompute can outlive a cheap key which is read somewhat more often.
+///
+/// The most recently inserted keys form an admission window, and are only evicted once nothing else
+/// is left. Unlike W-TinyLFU, there is no separate admission check comparing each key leaving the
+/// window against a victim from the main segment: once a key leaves the window it simply joins
+/// the rest of the map, where every eviction takes the keys with the lowest frequency and cost
+/// across all of it.
+///
+/// The metadata of every key holds the low 32 bits of its hash, which index the sketch and the
+/// cost table, and in its high 32 bits the sequence number at which the key was inserted.
+#[derive(Clone, Debug)]
+pub struct CostAwareEviction(Arc<CostAwareState>);
+
+#[derive(Debug)]
+struct CostAwareState {
+    /// `SKETCH_DEPTH` rows of `SKETCH_WIDTH` frequency counters each
+    frequencies: Box<[AtomicU8]>,
+    /// Number of accesses recorded since the counters were last halved
+    samples: AtomicU64,
+    /// The cost of the last replay for every key, in units of `COST_UNIT_MICROS`
+    costs: Box<[AtomicU32]>,
+    /// Number of keys inserted into the map so far
+    insertions: AtomicU64,
+}
+
 /// An iterator of sorts over [`EvictRangeGroup`] that groups together consecutive runs of evicted
 /// keys in a BTreeMap map. Does not actually implement iterator as that would require a lending
 /// iterator trait, which is not yet available (and the crate doesn't fit here well)
@@ -161,12 +223,24 @@ impl EvictionStrategy {
         EvictionStrategy::Generational(Default::default())
     }
 
-    /// Create new `EvictionMeta` for a newly added key
-    pub(crate) fn new_meta(&self) -> EvictionMeta {
+    /// Create a cost-aware eviction strategy
+    pub fn new_cost_aware() -> EvictionStrategy {
+        EvictionStrategy::CostAware(Default::default())
+    }
+
+    /// Returns true if the strategy needs the hash of a key to create its `EvictionMeta`
+    pub(crate) fn needs_key_hash(&self) -> bool {
+        matches!(self, EvictionStrategy::CostAware(_))
+    }
+
+    /// Create new `EvictionMeta` for a newly added key. `key_hash` is only meaningful if
+    /// [`needs_key_hash`](Self::needs_key_hash) returns true.
+    pub(crate) fn new_meta(&self, key_hash: u64) -> EvictionMeta {
         match self {
             EvictionStrategy::Random(_) => Default::default(),
             EvictionStrategy::LeastRecentlyUsed(lru) => lru.new_meta(),
             EvictionStrategy::Generational(gen) => gen.new_meta(),
+            EvictionStrategy::CostAware(cost) => cost.new_meta(key_hash),
         }
     }
 
@@ -176,6 +250,29 @@ impl EvictionStrategy {
             EvictionStrategy::Random(_) => {}
             EvictionStrategy::LeastRecentlyUsed(lru) => lru.on_read(meta),
             EvictionStrategy::Generational(gen) => gen.on_read(meta),
+            EvictionStrategy::CostAware(cost) => cost.on_read(meta),
+        }
+    }
+
+    /// Update the strategy following a read of a key that is not present in the map. The key's hash
+    /// is only computed if the strategy makes use of it.
+    pub(crate) fn on_miss<F>(&self, key_hash: F)
+    where
+        F: FnOnce() -> u64,
+    {
+        if let EvictionStrategy::CostAware(cost) = self {
+            cost.on_miss(key_hash())
+        }
+    }
+
+    /// Record how long it took to compute the values for a key. The key's hash is only computed if
+    /// the strategy makes use of it.
+    pub(crate) fn record_cost<F>(&self, key_hash: F, cost: Duration)
+    where
+        F: FnOnce() -> u64,
+    {
+        if let EvictionStrategy::CostAware(cost_aware) = self {
+            cost_aware.record_cost(key_hash(), cost)
         }
     }
 
@@ -195,9 +292,12 @@ impl EvictionStrategy {
             EvictionStrategy::LeastRecentlyUsed(lru) => {
                 Either::Right(Either::Left(lru.pick_keys_to_evict(data, nkeys)))
             }
-            EvictionStrategy::Generational(gen) => {
-                Either::Right(Either::Right(gen.pick_keys_to_evict(data, nkeys)))
-            }
+            EvictionStrategy::Generational(gen) => Either::Right(Either::Right(Either::Left(
+                gen.pick_keys_to_evict(data, nkeys),
+            ))),
+            EvictionStrategy::CostAware(cost) => Either::Right(Either::Right(Either::Right(
+                cost.pick_keys_to_evict(data, nkeys),
+            ))),
         }
     }
 
@@ -220,6 +320,7 @@ impl EvictionStrategy {
         let mut lru_f = None;
         let mut gen_f = None;
         let mut rand_f = None;
+        let mut cost_f = None;
         let iter = match self {
             EvictionStrategy::LeastRecentlyUsed(lru) => {
                 let (iter, group_by) = lru.pick_ranges_to_evict(data, nkeys);
@@ -234,7 +335,12 @@ impl EvictionStrategy {
             EvictionStrategy::Random(rand) => {
                 let (iter, group_by) = rand.pick_ranges_to_evict(data, nkeys);
                 rand_f = Some(group_by);
-                Either::Right(Either::Right(iter))
+                Either::Right(Either::Right(Either::Left(iter)))
+            }
+            EvictionStrategy::CostAware(cost) => {
+                let (iter, group_by) = cost.pick_ranges_to_evict(data, nkeys);
+                cost_f = Some(group_by);
+                Either::Right(Either::Right(Either::Right(iter)))
             }
         };
 
@@ -246,6 +352,8 @@ impl EvictionStrategy {
                     f(val)
                 } else if let Some(f) = gen_f.as_mut() {
                     f(val)
+                } else if let Some(f) = cost_f.as_mut() {
+                    f(val)
                 } else {
                     (rand_f.as_mut().unwrap())(val)
                 }
@@ -521,3 +629,216 @@ impl GenerationalEviction {
         })
     }
 }
+
+impl Default for CostAwareEviction {
+    fn default() -> Self {
+        CostAwareEviction(Arc::new(CostAwareState {
+            frequencies: (0..SKETCH_DEPTH * SKETCH_WIDTH)
+                .map(|_| AtomicU8::new(0))
+                .collect(),
+            samples: AtomicU64::new(0),
+            costs: (0..SKETCH_WIDTH).map(|_| AtomicU32::new(0)).collect(),
+            insertions: AtomicU64::new(0),
+        }))
+    }
+}
+
+impl CostAwareEviction {
+    fn new_meta(&self, key_hash: u64) -> EvictionMeta {
+        let seq = self.0.insertions.fetch_add(1, Relaxed) as u32;
+        EvictionMeta(AtomicU64::new(((seq as u64) << 32) | (key_hash as u32 as u64)).into())
+    }
+
+    fn on_read(&self, meta: &EvictionMeta) {
+        self.increment(meta.value() as u32);
+    }
+
+    fn on_miss(&self, key_hash: u64) {
+        self.increment(key_hash as u32);
+    }
+
+    fn record_cost(&self, key_hash: u64, cost: Duration) {
+        let units = (cost.as_micros() / COST_UNIT_MICROS).min(u32::MAX as u128) as u32;
+        self.0.costs[Self::slot(key_hash as u32, SKETCH_SEEDS[0])].store(units, Relaxed);
+    }
+
+    /// Returns the index of the counter for `hash` in a row of the sketch seeded with `seed`
+    fn slot(hash: u32, seed: u64) -> usize {
+        ((hash as u64 | 1 << 32).wrapping_mul(seed) >> (64 - SKETCH_WIDTH_BITS)) as usize
+    }
+
+    fn counters(&self, hash: u32) -> impl Iterator<Item = &AtomicU8> {
+        SKETCH_SEEDS.iter().enumerate().map(move |(row, seed)| {
+            &self.0.frequencies[row * SKETCH_WIDTH + Self::slot(hash, *seed)]
+        })
+    }
+
+    /// Record an access to the key with the given hash in the sketch
+    fn increment(&self, hash: u32) {
+        // Conservative update: only the smallest counters are incremented, as the others already
+        // overestimate the frequency of this key due to collisions
+        let min = self.frequency(hash);
+        if min < MAX_FREQUENCY {
+            for ctr in self.counters(hash) {
+                // As with the other strategies, races between readers here can lose an increment,
+                // which is an acceptable inaccuracy for an estimate
+                if ctr.load(Relaxed) == min {
+                    ctr.store(min + 1, Relaxed);
+                }
+            }
+        }
+
+        if self.0.samples.fetch_add(1, Relaxed) + 1 == SKETCH_SAMPLE_SIZE {
+            self.0.samples.store(0, Relaxed);
+            for ctr in self.0.frequencies.iter() {
+                ctr.store(ctr.load(Relaxed) / 2, Relaxed);
+            }
+        }
+    }
+
+    /// Returns the estimated number of accesses to the key with the given hash
+    fn frequency(&self, hash: u32) -> u8 {
+        self.counters(hash)
+            .map(|ctr| ctr.load(Relaxed))
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Computes a value for every key in the map, such that keys with lower values should be
+    /// evicted first.
+    fn eviction_order<K, V, S>(&self, data: &Data<K, V, S>) -> Vec<u64>
+    where
+        K: Ord + Clone,
+        S: std::hash::BuildHasher,
+    {
+        let newest = self.0.insertions.load(Relaxed) as u32;
+        let window = (data.len() * WINDOW_PERCENT / 100).max(1) as u32;
+
+        data.iter()
+            .map(|(_, v)| {
+                let meta = v.eviction_meta().value();
+                let hash = meta as u32;
+                let age = newest.wrapping_sub((meta >> 32) as u32);
+                if age <= window {
+                    // Keys in the window are ordered after every other key, oldest first
+                    u64::MAX - age as u64
+                } else {
+                    let cost = self.0.costs[Self::slot(hash, SKETCH_SEEDS[0])]
+                        .load(Relaxed)
+                        .max(1);
+                    (self.frequency(hash) as u64 + 1) * cost as u64
+                }
+            })
+            .collect()
+    }
+
+    /// Returns a predicate that will select exactly `nkeys` of the given eviction order values,
+    /// lowest first, when called once for each value in order.
+    fn cutoff(order: &[u64], nkeys: usize) -> impl FnMut(u64) -> bool {
+        let (cutoff, mut ties) = if nkeys == 0 {
+            (0, 0)
+        } else if nkeys >= order.len() {
+            (u64::MAX, usize::MAX)
+        } else {
+            let mut sorted = order.to_vec();
+            let (_, cutoff, _) = sorted.select_nth_unstable(nkeys - 1);
+            let cutoff = *cutoff;
+            // Many keys can share a value, so only as many of the keys at the cutoff as are needed
+            // to make up `nkeys` are evicted
+            let below = order.iter().filter(|v| **v < cutoff).count();
+            (cutoff, nkeys - below)
+        };
+
+        move |v| {
+            if v < cutoff {
+                true
+            } else if v == cutoff && ties > 0 {
+                ties -= 1;
+                true
+            } else {
+                false
+            }
+        }
+    }
+
+    fn pick_keys_to_evict<'a, K, V, S>(
+        &self,
+        data: &'a Data<K, V, S>,
+        nkeys: usize,
+    ) -> impl Iterator<Item = (&'a K, &'a Values<V>)>
+    where
+        K: Ord + Clone,
+        S: std::hash::BuildHasher,
+    {
+        let order = self.eviction_order(data);
+        let mut evict = Self::cutoff(&order, nkeys);
+
+        order
+            .into_iter()
+            .zip(data.iter())
+            .filter_map(move |(v, kv)| evict(v).then_some(kv))
+    }
+
+    fn pick_ranges_to_evict<'a, K, V, S>(
+        &self,
+        data: &'a Data<K, V, S>,
+        nkeys: usize,
+    ) -> (
+        impl Iterator<Item = (u64, (&'a K, &'a Values<V>))>,
+        impl FnMut(u64) -> bool,
+    )
+    where
+        K: Ord + Clone,
+        S: std::hash::BuildHasher,
+    {
+        let order = self.eviction_order(data);
+        let evict = Self::cutoff(&order, nkeys);
+
+        (order.into_iter().zip(data.iter()), evict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frequency_estimate() {
+        let cost = CostAwareEviction::default();
+        for _ in 0..5 {
+            cost.on_miss(1234);
+        }
+        cost.on_miss(5678);
+
+        assert_eq!(cost.frequency(1234), 5);
+        assert_eq!(cost.frequency(5678), 1);
+        assert_eq!(cost.frequency(91011), 0);
+    }
+
+    #[test]
+    fn frequency_saturates_and_ages() {
+        let cost = CostAwareEviction::default();
+        for _ in 0..100 {
+            cost.on_miss(42);
+        }
+        assert_eq!(cost.frequency(42), MAX_FREQUENCY);
+
+        for _ in 0..SKETCH_SAMPLE_SIZE {
+            cost.on_miss(7);
+        }
+        assert!(cost.frequency(42) <= MAX_FREQUENCY / 2);
+    }
+
+    #[test]
+    fn cutoff_selects_exactly_nkeys() {
+        let order = [3, 1, 1, 1, 2, 1];
+        for nkeys in 0..=order.len() {
+            let mut evict = CostAwareEviction::cutoff(&order, nkeys);
+            assert_eq!(order.iter().filter(|v| evict(**v)).count(), nkeys);
+        }
+
+        let mut evict = CostAwareEviction::cutoff(&order, 5);
+        let evicted = order.iter().filter(|v| evict(**v)).collect::<Vec<_>>();
+        assert!(!evicted.contains(&&3));
+    }
+}
@@ -392,11 +392,16 @@ where
         key: K,
         eviction_meta: &mut Option<EvictionMeta>,
     ) -> &mut Values<V> {
+        let key_hash = if self.eviction_strategy.needs_key_hash() {
+            self.hasher.hash_one(&key)
+        } else {
+            0
+        };
         self.data.entry(key).or_insert_with(|| {
             if let Some(meta) = eviction_meta.take() {
                 Values::new(meta)
             } else {
-                let meta = self.eviction_strategy.new_meta();
+                let meta = self.eviction_strategy.new_meta(key_hash);
                 eviction_meta.replace(meta.clone());
                 Values::new(meta)
             }
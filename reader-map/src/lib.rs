@@ -216,6 +216,10 @@
 //!
 //! The values for each key in the map are stored in [`refs::Values`], which is internally is a
 //! sorted vector of values.
+//!
+//! Maps that store rows of `DfValue`s can use [`CompactRow`] as their value type to store each row
+//! in a single contiguous buffer rather than as a boxed slice of values, at the cost of decoding
+//! the row's values when they're read.
 #![warn(
     missing_docs,
     rust_2018_idioms,
@@ -232,6 +236,7 @@ use std::hash::{BuildHasher, Hash};
 pub use eviction::EvictionStrategy;
 use partial_map::InsertionOrder;
 use readyset_client::internal::IndexType;
+pub use readyset_data::{CompactRow, CompactRowIter};
 
 use crate::inner::Inner;
 use crate::read::ReadHandle;
@@ -3,6 +3,7 @@ use std::collections::hash_map::RandomState;
 use std::fmt::{self};
 use std::hash::{BuildHasher, Hash};
 use std::iter::FromIterator;
+use std::time::Duration;
 
 use left_right::ReadGuard;
 use readyset_client::internal::IndexType;
@@ -121,8 +122,11 @@ where
         let MapReadRef { guard } = self.enter()?;
         Ok(ReadGuard::try_map(guard, |inner| {
             let v = inner.data.get(key);
-            if let Some(v) = v {
-                inner.eviction_strategy.on_read(v.eviction_meta());
+            match v {
+                Some(v) => inner.eviction_strategy.on_read(v.eviction_meta()),
+                None => inner
+                    .eviction_strategy
+                    .on_miss(|| inner.hasher.hash_one(key)),
             }
             v
         }))
@@ -200,6 +204,20 @@ where
         Ok((res, meta))
     }
 
+    /// Record how long it took to compute the values for `key`, for eviction strategies that take
+    /// the cost of keys into account. Does nothing if the map has not been published yet.
+    pub(crate) fn record_cost<Q>(&self, key: &Q, cost: Duration)
+    where
+        K: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        if let Ok(inner) = self.enter_inner() {
+            inner
+                .eviction_strategy
+                .record_cost(|| inner.hasher.hash_one(key), cost);
+        }
+    }
+
     /// Returns true if the [`WriteHandle`](crate::WriteHandle) has been dropped.
     pub fn was_dropped(&self) -> bool {
         self.handle.was_dropped()
@@ -133,10 +133,15 @@ where
         K: Borrow<Q> + Ord + Clone,
         Q: ?Sized + Hash + Ord + ToOwned<Owned = K>,
     {
-        self.guard.data.get(key).map(|v| {
-            self.guard.eviction_strategy.on_read(v.eviction_meta());
-            v
-        })
+        let v = self.guard.data.get(key);
+        match v {
+            Some(v) => self.guard.eviction_strategy.on_read(v.eviction_meta()),
+            None => self
+                .guard
+                .eviction_strategy
+                .on_miss(|| self.guard.hasher.hash_one(key)),
+        }
+        v
     }
 
     /// Returns a guarded reference to the smallest value corresponding to the key.
@@ -1,6 +1,8 @@
+use std::borrow::Borrow;
 use std::collections::hash_map::RandomState;
 use std::fmt;
 use std::hash::{BuildHasher, Hash};
+use std::time::Duration;
 
 use left_right::Absorb;
 use partial_map::InsertionOrder;
@@ -248,6 +250,23 @@ where
         self.add_op(Operation::Retain(k, Predicate(Box::new(f))))
     }
 
+    /// Record how long it took to compute the values for the given key.
+    ///
+    /// This is used by
+    /// [`EvictionStrategy::new_cost_aware`](crate::EvictionStrategy::new_cost_aware), which
+    /// prefers to keep keys which are expensive to recompute, and is ignored by all other eviction
+    /// strategies.
+    ///
+    /// The key may be any borrowed form of the map's key type, but `Hash` on the borrowed form
+    /// *must* match that for the key type.
+    pub fn record_cost<Q>(&self, key: &Q, cost: Duration)
+    where
+        K: Borrow<Q>,
+        Q: Hash + ?Sized,
+    {
+        self.r_handle.record_cost(key, cost)
+    }
+
     /// Remove the value-bag for randomly chosen keys given an `EvictionQuantity` to evict.
     ///
     /// This method immediately calls [`publish`](Self::publish) to ensure that the keys and values
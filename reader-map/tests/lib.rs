@@ -1,5 +1,6 @@
 use std::collections::hash_map::RandomState;
 use std::hash::Hash;
+use std::time::Duration;
 
 use partial_map::InsertionOrder;
 use reader_map::handles::{ReadHandle, WriteHandle};
@@ -1126,6 +1127,47 @@ fn eviction_generational() {
     assert!(to_evict.contains(&'c'));
 }
 
+#[test]
+fn eviction_cost_aware() {
+    let (mut w, r) = reader_map::Options::default()
+        .with_eviction_strategy(reader_map::EvictionStrategy::new_cost_aware())
+        .construct();
+
+    for k in ['a', 'b', 'c', 'd'] {
+        w.insert(k, k as u32);
+    }
+    w.publish();
+
+    for _ in 0..3 {
+        assert!(r.get(&'a').unwrap().is_some());
+    }
+    assert!(r.get(&'b').unwrap().is_some());
+    assert!(r.get(&'c').unwrap().is_some());
+    // 'c' is read as often as 'b', but is much more expensive to recompute
+    w.record_cost(&'c', Duration::from_millis(1));
+
+    // 'd' was inserted last, so it is still in the admission window, and 'b' is the cheapest of
+    // the remaining keys to lose
+    let to_evict = evict(&mut w, 0.25);
+    assert_eq!(to_evict, vec!['b']);
+    w.publish();
+
+    // Misses count towards the frequency of keys that are not in the map yet
+    for _ in 0..4 {
+        assert!(r.get(&'e').unwrap().is_none());
+    }
+    w.insert('e', 'e' as u32);
+    w.insert('f', 'f' as u32);
+    w.publish();
+
+    // 'e' has left the admission window, but was requested more often than 'd'
+    let to_evict = evict(&mut w, 0.25);
+    assert_eq!(to_evict, vec!['d']);
+    w.publish();
+    assert!(r.get(&'d').unwrap().is_none());
+    assert!(r.get(&'e').unwrap().is_some());
+}
+
 #[test]
 fn eviction_random() {
     let (mut w, r) = reader_map::new();
@@ -73,6 +73,7 @@ use std::collections::HashMap;
 use std::fmt::{self, Debug};
 use std::marker::PhantomData;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
@@ -110,10 +111,14 @@ use tracing::{debug, error, instrument, trace, warn};
 use vec1::Vec1;
 
 use crate::backend::noria_connector::ExecuteSelectContext;
+use crate::cache_staleness::CacheStaleness;
 use crate::metrics_handle::{MetricsHandle, MetricsSummary};
+use crate::privileges::TablePrivileges;
 use crate::query_handler::SetBehavior;
+use crate::query_hints::{HintedRoute, QueryHints};
 use crate::query_status_cache::QueryStatusCache;
 use crate::status_reporter::ReadySetStatusReporter;
+use crate::upstream_auth::UpstreamAuthCache;
 pub use crate::upstream_database::UpstreamPrepare;
 use crate::utils::{create_dummy_column, time_or_null};
 use crate::{create_dummy_schema, QueryHandler, UpstreamDatabase, UpstreamDestination};
@@ -277,6 +282,10 @@ pub struct BackendBuilder {
     dialect: Dialect,
     users: HashMap<String, String>,
     require_authentication: bool,
+    upstream_auth: Option<UpstreamAuthCache>,
+    allow_unencrypted_upstream_auth: bool,
+    table_privileges: Option<TablePrivileges>,
+    cache_staleness: Option<CacheStaleness>,
     ticket: Option<Timestamp>,
     timestamp_client: Option<TimestampClient>,
     query_log_sender: Option<UnboundedSender<QueryExecutionEvent>>,
@@ -300,6 +309,10 @@ impl Default for BackendBuilder {
             dialect: Dialect::MySQL,
             users: Default::default(),
             require_authentication: true,
+            upstream_auth: None,
+            allow_unencrypted_upstream_auth: false,
+            table_privileges: None,
+            cache_staleness: None,
             ticket: None,
             timestamp_client: None,
             query_log_sender: None,
@@ -349,6 +362,10 @@ impl BackendBuilder {
             noria,
             upstream,
             users: self.users,
+            upstream_auth: self.upstream_auth,
+            table_privileges: self.table_privileges,
+            cache_staleness: self.cache_staleness,
+            user: None,
             query_log_sender: self.query_log_sender,
             query_log_mode: self.query_log_mode,
             last_query: None,
@@ -364,6 +381,7 @@ impl BackendBuilder {
                 slowlog: self.slowlog,
                 dialect: self.dialect,
                 require_authentication: self.require_authentication,
+                allow_unencrypted_upstream_auth: self.allow_unencrypted_upstream_auth,
                 unsupported_set_mode: self.unsupported_set_mode,
                 migration_mode: self.migration_mode,
                 query_max_failure_duration: Duration::new(self.query_max_failure_seconds, 0),
@@ -420,6 +438,39 @@ impl BackendBuilder {
         self
     }
 
+    /// Authenticate clients by checking their credentials against the upstream database (instead
+    /// of against [`users`](Self::users)), remembering verified credentials in the given cache.
+    pub fn upstream_auth(mut self, upstream_auth: Option<UpstreamAuthCache>) -> Self {
+        self.upstream_auth = upstream_auth;
+        self
+    }
+
+    /// Check clients' credentials against the upstream database even over connections that
+    /// aren't encrypted, which sends their passwords over the network in cleartext
+    pub fn allow_unencrypted_upstream_auth(
+        mut self,
+        allow_unencrypted_upstream_auth: bool,
+    ) -> Self {
+        self.allow_unencrypted_upstream_auth = allow_unencrypted_upstream_auth;
+        self
+    }
+
+    /// Only serve reads from caches to clients authenticated against the upstream database whose
+    /// user may `SELECT` from every table the query reads from, according to the given
+    /// privileges. Other reads are proxied to the upstream.
+    pub fn table_privileges(mut self, table_privileges: Option<TablePrivileges>) -> Self {
+        self.table_privileges = table_privileges;
+        self
+    }
+
+    /// Honor `readyset:max_staleness` hints using the given bound on how stale caches are.
+    /// Without one, the staleness of caches is unknown, and reads with such hints are always
+    /// proxied to the upstream.
+    pub fn cache_staleness(mut self, cache_staleness: Option<CacheStaleness>) -> Self {
+        self.cache_staleness = cache_staleness;
+        self
+    }
+
     /// Whether or not to allow cache ddl statements to be executed. If false, cache ddl statements
     /// received will instead return an error prompting the user to use ReadySet cloud to manage
     /// their caches.
@@ -508,6 +559,8 @@ where
     /// If statement was successfully rewritten, will store all information necessary to install
     /// the view in readyset
     view_request: Option<ViewCreateRequest>,
+    /// The hints given in the statement's comments, which apply to every execution of it
+    hints: QueryHints,
 }
 
 impl<DB> PreparedStatement<DB>
@@ -559,6 +612,15 @@ where
     upstream: Option<DB>,
     /// Map from username to password for all users allowed to connect to the db
     pub users: HashMap<String, String>,
+    /// If set, clients are authenticated against the upstream database instead of with `users`
+    upstream_auth: Option<UpstreamAuthCache>,
+    /// If set, cached reads are only served to users with the upstream privileges to run them
+    table_privileges: Option<TablePrivileges>,
+    /// If set, bounds how stale caches are, for reads with a `readyset:max_staleness` hint
+    cache_staleness: Option<CacheStaleness>,
+    /// The upstream user this connection was authenticated as, if it was authenticated against
+    /// the upstream database
+    user: Option<String>,
 
     query_log_sender: Option<UnboundedSender<QueryExecutionEvent>>,
     query_log_mode: Option<QueryLogMode>,
@@ -627,6 +689,9 @@ struct BackendSettings {
     dialect: Dialect,
     slowlog: bool,
     require_authentication: bool,
+    /// Whether clients may be authenticated against the upstream database over unencrypted
+    /// connections
+    allow_unencrypted_upstream_auth: bool,
     /// How to behave when receiving unsupported `SET` statements
     unsupported_set_mode: UnsupportedSetMode,
     /// How this backend handles migrations, See MigrationMode.
@@ -648,6 +713,8 @@ struct BackendSettings {
 pub struct QueryInfo {
     pub destination: QueryDestination,
     pub noria_error: String,
+    /// The [`QueryHints`] given in the statement's comments, empty if there were none
+    pub hints: String,
 }
 
 impl FromRow for QueryInfo {
@@ -666,6 +733,8 @@ impl FromRow for QueryInfo {
                     res.noria_error = std::str::from_utf8(d)
                         .map_err(|_| FromRowError(row.clone()))?
                         .to_string();
+                } else if c.name_str() == "Query_hints" {
+                    res.hints = dest.to_string();
                 } else {
                     return Err(FromRowError(row.clone()));
                 }
@@ -867,6 +936,14 @@ where
         }
     }
 
+    /// Tell the upstream connection that the results of the last command have been sent to the
+    /// client, so that it can be given back to the upstream connection pool if there is one
+    pub fn release_upstream(&mut self) {
+        if let Some(upstream) = &mut self.upstream {
+            upstream.release();
+        }
+    }
+
     /// Switch the active database for this backend to the given named database.
     ///
     /// Internally, this will set the schema search path to a single-element vector with the
@@ -985,6 +1062,7 @@ where
         self.last_query = destination.map(|d| QueryInfo {
             destination: d,
             noria_error: String::new(),
+            hints: String::new(),
         });
 
         // Update noria migration state for query
@@ -1073,6 +1151,7 @@ where
             self.last_query = Some(QueryInfo {
                 destination: QueryDestination::Upstream,
                 noria_error: String::new(),
+                hints: String::new(),
             });
             res
         } else {
@@ -1087,6 +1166,7 @@ where
             self.last_query = Some(QueryInfo {
                 destination: QueryDestination::Readyset,
                 noria_error: String::new(),
+                hints: String::new(),
             });
 
             event.readyset_event = Some(ReadysetExecutionEvent::Other {
@@ -1241,6 +1321,7 @@ where
                 self.last_query = Some(QueryInfo {
                     destination: QueryDestination::Upstream,
                     noria_error: String::new(),
+                    hints: String::new(),
                 });
 
                 res
@@ -1335,6 +1416,7 @@ where
             parsed_query,
             view_request,
             always,
+            hints: QueryHints::parse(DB::SQL_DIALECT, query),
         });
 
         let query_log_sender = self.query_log_sender.clone();
@@ -1640,8 +1722,33 @@ where
             }
         }
 
+        let denied = cached_statement
+            .view_request
+            .as_ref()
+            .is_some_and(|view_request| {
+                !Self::may_read_cached(
+                    self.table_privileges.as_ref(),
+                    self.user.as_deref(),
+                    view_request,
+                )
+            });
+
+        // Hints only apply to reads, which are the only statements with a view request
+        let route = if cached_statement.view_request.is_some() {
+            Self::hinted_route(
+                &cached_statement.hints,
+                self.cache_staleness.as_ref(),
+                upstream.is_some(),
+            )
+        } else {
+            HintedRoute::Default
+        };
+        let cache_only = route == HintedRoute::CacheOnly;
+
         let should_fallback = {
-            if cached_statement.always {
+            if denied || route == HintedRoute::Upstream {
+                true
+            } else if cached_statement.always || cache_only {
                 false
             } else {
                 let is_recovering = cached_statement.in_fallback_recovery(
@@ -1666,11 +1773,32 @@ where
         };
 
         let result = match &cached_statement.prep.inner {
+            _ if let HintedRoute::Fail(e) = &route => {
+                Err(Self::hint_not_honored(&mut event, e.clone()).into())
+            }
+            _ if cache_only && denied => Err(Self::hint_not_honored(
+                &mut event,
+                ReadySetError::CacheOnlyQueryNotCached(
+                    "the current user may not read from every table referenced by this statement"
+                        .to_owned(),
+                ),
+            )
+            .into()),
+            // Without an upstream prepare there's nowhere to proxy the statement to
+            PrepareResultInner::Noria(_) if denied => Err(unsupported_err!(
+                "Current user may not read from every table referenced by this statement"
+            )
+            .into()),
             PrepareResultInner::Noria(prep) => {
                 Self::execute_noria(noria, prep, params, ticket, &mut event)
                     .await
                     .map_err(Into::into)
             }
+            PrepareResultInner::Upstream(_) if cache_only => Err(Self::hint_not_honored(
+                &mut event,
+                ReadySetError::CacheOnlyQueryNotCached("the statement is not cached".to_owned()),
+            )
+            .into()),
             PrepareResultInner::Upstream(prep) => {
                 // No inlined caches for this query exist if we are only prepared on upstream.
                 if cached_statement.migration_state.is_inlined() {
@@ -1680,6 +1808,11 @@ where
                 }
                 Self::execute_upstream(upstream, prep, params, exec_meta, &mut event, false).await
             }
+            PrepareResultInner::Both(nprep, _) if cache_only => {
+                Self::execute_noria(noria, nprep, params, ticket, &mut event)
+                    .await
+                    .map_err(Into::into)
+            }
             PrepareResultInner::Both(.., uprep) if should_fallback => {
                 Self::execute_upstream(upstream, uprep, params, exec_meta, &mut event, false).await
             }
@@ -1727,6 +1860,7 @@ where
             }
         };
 
+        Self::record_hints(&cached_statement.hints);
         self.last_query = event.destination.map(|d| QueryInfo {
             destination: d,
             noria_error: event
@@ -1734,12 +1868,114 @@ where
                 .as_ref()
                 .map(|e| e.to_string())
                 .unwrap_or_default(),
+            hints: cached_statement.hints.to_string(),
         });
         log_query(self.query_log_sender.as_ref(), event, self.settings.slowlog);
 
         result
     }
 
+    /// Executes the prepared statement identified by `id` like [`Backend::execute`], but only
+    /// returns the first `limit` rows of its results, leaving the rest in an upstream cursor named
+    /// `cursor` to be read with [`Backend::fetch_cursor`]. If `hold` is true, the cursor stays open
+    /// after the current transaction ends, until it's closed with [`Backend::close_cursor`].
+    ///
+    /// Returns `None` without executing anything unless the statement is a query that would be
+    /// proxied to the upstream database, since only those results can be kept in a cursor there.
+    pub async fn execute_cursor(
+        &mut self,
+        id: u32,
+        params: &[DfValue],
+        exec_meta: DB::ExecMeta<'_>,
+        cursor: &str,
+        hold: bool,
+        limit: u32,
+    ) -> Result<Option<QueryResult<'_, DB>>, DB::Error> {
+        let cached_statement = self
+            .state
+            .prepared_statements
+            .get(id as _)
+            .ok_or(PreparedStatementMissing { statement_id: id })?;
+        let PrepareResultInner::Upstream(prep) = &cached_statement.prep.inner else {
+            return Ok(None);
+        };
+        // Statements with a pending or inlined migration might be executed against ReadySet
+        // instead, which `execute` checks for
+        if !matches!(
+            cached_statement.parsed_query.as_deref(),
+            Some(SqlQuery::Select(_))
+        ) || cached_statement.migration_state.is_pending()
+            || cached_statement.migration_state.is_inlined()
+        {
+            return Ok(None);
+        }
+        if cached_statement.view_request.is_some()
+            && !matches!(
+                Self::hinted_route(
+                    &cached_statement.hints,
+                    self.cache_staleness.as_ref(),
+                    self.upstream.is_some(),
+                ),
+                HintedRoute::Default | HintedRoute::Upstream
+            )
+        {
+            return Ok(None);
+        }
+        let upstream = self.upstream.as_mut().ok_or_else(|| {
+            ReadySetError::Internal("This condition requires an upstream connector".to_string())
+        })?;
+
+        let mut event = QueryExecutionEvent::new(EventType::Execute);
+        event.query = cached_statement.parsed_query.clone();
+        event.query_id = cached_statement.query_id;
+        event.destination = Some(QueryDestination::Upstream);
+        let result = {
+            let _t = event.start_upstream_timer();
+            match upstream
+                .declare_cursor(prep.statement_id, params, cursor, hold)
+                .await
+            {
+                Ok(()) => upstream.fetch_cursor(cursor, limit, exec_meta).await,
+                Err(e) => Err(e),
+            }
+        };
+
+        Self::record_hints(&cached_statement.hints);
+        self.last_query = Some(QueryInfo {
+            destination: QueryDestination::Upstream,
+            noria_error: String::new(),
+            hints: cached_statement.hints.to_string(),
+        });
+        log_query(self.query_log_sender.as_ref(), event, self.settings.slowlog);
+
+        result.map(|r| Some(QueryResult::Upstream(r)))
+    }
+
+    /// Reads the next `limit` rows, or all of its remaining rows if `limit` is zero, from an
+    /// upstream cursor opened by [`Backend::execute_cursor`]
+    pub async fn fetch_cursor(
+        &mut self,
+        cursor: &str,
+        limit: u32,
+        exec_meta: DB::ExecMeta<'_>,
+    ) -> Result<QueryResult<'_, DB>, DB::Error> {
+        let upstream = self.upstream.as_mut().ok_or_else(|| {
+            ReadySetError::Internal("This condition requires an upstream connector".to_string())
+        })?;
+        upstream
+            .fetch_cursor(cursor, limit, exec_meta)
+            .await
+            .map(|r| QueryResult::Upstream(r))
+    }
+
+    /// Closes an upstream cursor opened by [`Backend::execute_cursor`]
+    pub async fn close_cursor(&mut self, cursor: &str) -> Result<(), DB::Error> {
+        if let Some(upstream) = &mut self.upstream {
+            upstream.close_cursor(cursor).await?;
+        }
+        Ok(())
+    }
+
     pub async fn remove_statement(&mut self, deallocate_id: DeallocateId) -> Result<(), DB::Error> {
         // in all cases, we need to call upstream.remove_statement(), but in the case
         // of a Numeric id and it's in self.state.prepared_statements, we need to use
@@ -1825,7 +2061,7 @@ where
     /// Generates response to the `EXPLAIN LAST STATEMENT` query
     #[instrument(skip_all)]
     fn explain_last_statement(&self) -> ReadySetResult<noria_connector::QueryResult<'static>> {
-        let (destination, error) = self
+        let (destination, error, hints) = self
             .last_query
             .as_ref()
             .map(|info| {
@@ -1835,17 +2071,23 @@ where
                         s if s.is_empty() => "ok".to_string(),
                         s => s.clone(),
                     },
+                    match &info.hints {
+                        s if s.is_empty() => "none".to_string(),
+                        s => s.clone(),
+                    },
                 )
             })
-            .unwrap_or_else(|| ("unknown".to_string(), "ok".to_string()));
+            .unwrap_or_else(|| ("unknown".to_string(), "ok".to_string(), "none".to_string()));
 
         Ok(noria_connector::QueryResult::Meta(vec![
             ("Query_destination", destination).into(),
             ("ReadySet_error", error).into(),
+            ("Query_hints", hints).into(),
         ]))
     }
 
     /// Forwards a `CREATE CACHE` request to ReadySet
+    #[allow(clippy::too_many_arguments)]
     #[instrument(skip(self))]
     async fn create_cached_query(
         &mut self,
@@ -1854,6 +2096,8 @@ where
         override_schema_search_path: Option<Vec<SqlIdentifier>>,
         always: bool,
         concurrently: bool,
+        replicas: Option<usize>,
+        shards: Option<usize>,
     ) -> ReadySetResult<noria_connector::QueryResult<'static>> {
         // If we have another query with the same name, drop that query first
         if let Some(name) = name {
@@ -1877,6 +2121,8 @@ where
                 override_schema_search_path,
                 always,
                 concurrently,
+                replicas,
+                shards,
             )
             .await
         {
@@ -2156,7 +2402,7 @@ where
             views.retain(|view| view.query_id == query_id);
         }
 
-        let select_schema = if let Some(handle) = self.metrics_handle.as_mut() {
+        let mut select_schema = if let Some(handle) = self.metrics_handle.as_mut() {
             // Must snapshot histograms to get the latest metrics
             handle.snapshot_counters(readyset_client_metrics::DatabaseType::ReadySet);
             create_dummy_schema!(
@@ -2169,6 +2415,13 @@ where
         } else {
             create_dummy_schema!("query id", "cache name", "query text", "fallback behavior")
         };
+        if self.table_privileges.is_some() {
+            select_schema
+                .schema
+                .to_mut()
+                .push(create_dummy_column("allowed roles"));
+            select_schema.columns.to_mut().push("allowed roles".into());
+        }
 
         // Get the cache name for each query from the view cache
         let mut results: Vec<Vec<DfValue>> = vec![];
@@ -2192,6 +2445,13 @@ where
                 row.push(DfValue::from(format!("{sample_count}")));
             }
 
+            // List the users who may read from the cache, if we're enforcing table privileges
+            if let Some(table_privileges) = self.table_privileges.as_ref() {
+                let readers =
+                    table_privileges.readers(&view.statement, self.noria.schema_search_path());
+                row.push(DfValue::from(readers.join(", ")));
+            }
+
             results.push(row);
         }
 
@@ -2311,6 +2571,8 @@ where
                 inner,
                 always,
                 concurrently,
+                replicas,
+                shards,
                 unparsed_create_cache_statement,
             }) => {
                 if !self.allow_cache_ddl {
@@ -2364,7 +2626,15 @@ where
                 };
 
                 let res = self
-                    .create_cached_query(name.as_ref(), stmt, search_path, *always, *concurrently)
+                    .create_cached_query(
+                        name.as_ref(),
+                        stmt,
+                        search_path,
+                        *always,
+                        *concurrently,
+                        *replicas,
+                        *shards,
+                    )
                     .await;
                 // The extend_recipe may have failed, in which case we should remove our intention
                 // to create this cache. Extend recipe waits a bit and then returns an
@@ -2442,6 +2712,18 @@ where
                 }
                 self.drop_all_proxied_queries().await
             }
+            SqlQuery::AlterReadySet(stmt) => {
+                if !self.allow_cache_ddl {
+                    unsupported!("{}", UNSUPPORTED_CACHE_DDL_MSG);
+                }
+                self.noria.alter_readyset(stmt).await
+            }
+            SqlQuery::AlterCache(stmt) => {
+                if !self.allow_cache_ddl {
+                    unsupported!("{}", UNSUPPORTED_CACHE_DDL_MSG);
+                }
+                self.noria.alter_cache(stmt).await
+            }
             SqlQuery::Show(ShowStatement::CachedQueries(query_id)) => {
                 // Log a telemetry event
                 if let Some(ref telemetry_sender) = self.telemetry_sender {
@@ -2839,6 +3121,8 @@ where
                     | SqlQuery::DropCache(_)
                     | SqlQuery::DropAllCaches(_)
                     | SqlQuery::DropAllProxiedQueries(_)
+                    | SqlQuery::AlterReadySet(_)
+                    | SqlQuery::AlterCache(_)
                     | SqlQuery::Explain(_) => {
                         unreachable!("path returns prior")
                     }
@@ -2907,6 +3191,11 @@ where
         let query_log_sender = self.query_log_sender.clone();
         let slowlog = self.settings.slowlog;
 
+        let hints = QueryHints::parse(DB::SQL_DIALECT, query);
+        let route = Self::hinted_route(&hints, self.cache_staleness.as_ref(), self.has_fallback());
+        // Whether the hints forbid proxying the statement, if it is a read
+        let hints_forbid_upstream = matches!(route, HintedRoute::CacheOnly | HintedRoute::Fail(_));
+
         let parse_result = {
             let _t = event.start_parse_timer();
             self.parse_query(query)
@@ -2919,6 +3208,14 @@ where
                 event.set_noria_error(&e);
                 Err(e.into())
             }
+            // Parse error, but hints forbid sending the query to fallback
+            Err(_) if hints_forbid_upstream => Err(Self::hint_not_honored(
+                &mut event,
+                ReadySetError::CacheOnlyQueryNotCached(
+                    "the statement could not be parsed".to_owned(),
+                ),
+            )
+            .into()),
             // Parse error, send to fallback
             Err(e) => {
                 if !matches!(
@@ -2933,7 +3230,11 @@ where
                 let fallback_res =
                     Self::query_fallback(self.upstream.as_mut(), query, &mut event).await;
                 if fallback_res.is_ok() {
-                    let (id, _) = self.state.query_status_cache.insert(query);
+                    // Hints aren't part of the query, so shouldn't make it a different one
+                    let (id, _) = self
+                        .state
+                        .query_status_cache
+                        .insert(QueryHints::strip(DB::SQL_DIALECT, query).as_ref());
                     if let Some(ref telemetry_sender) = self.telemetry_sender {
                         if let Err(e) = telemetry_sender.send_event_with_payload(
                             TelemetryEvent::QueryParseFailed,
@@ -2987,6 +3288,17 @@ where
                 )
                 .await
             }
+            Ok(ref parsed_query @ SqlQuery::Select(_))
+                if hints_forbid_upstream && Handler::requires_fallback(parsed_query) =>
+            {
+                Err(Self::hint_not_honored(
+                    &mut event,
+                    ReadySetError::CacheOnlyQueryNotCached(
+                        "the statement can only be run against the upstream database".to_owned(),
+                    ),
+                )
+                .into())
+            }
             Ok(ref parsed_query) if Handler::requires_fallback(parsed_query) => {
                 if self.has_fallback() {
                     if let SqlQuery::Select(stmt) = parsed_query {
@@ -3017,25 +3329,56 @@ where
 
                 event.query_id = Some(QueryId::from(&view_request));
 
-                let (noria_should_try, status, processed_query_params) =
-                    self.noria_should_try_select(&mut view_request);
-                let processed_query_params = processed_query_params?;
-
-                if noria_should_try {
-                    Self::query_adhoc_select(
-                        &mut self.noria,
-                        self.upstream.as_mut(),
-                        &self.settings,
-                        &mut self.state,
-                        query,
-                        &view_request,
-                        status,
-                        &mut event,
-                        processed_query_params,
-                    )
-                    .await
-                } else {
-                    Self::query_fallback(self.upstream.as_mut(), query, &mut event).await
+                match route {
+                    HintedRoute::Upstream => {
+                        Self::query_fallback(self.upstream.as_mut(), query, &mut event).await
+                    }
+                    HintedRoute::Fail(e) => Err(Self::hint_not_honored(&mut event, e).into()),
+                    HintedRoute::CacheOnly | HintedRoute::Default => {
+                        let cache_only = route == HintedRoute::CacheOnly;
+                        let (noria_should_try, status, processed_query_params) =
+                            self.noria_should_try_select(&mut view_request);
+                        let processed_query_params = processed_query_params?;
+                        let may_read_cached = Self::may_read_cached(
+                            self.table_privileges.as_ref(),
+                            self.user.as_deref(),
+                            &view_request,
+                        );
+
+                        if cache_only && !may_read_cached {
+                            Err(Self::hint_not_honored(
+                                &mut event,
+                                ReadySetError::CacheOnlyQueryNotCached(
+                                    "the current user may not read from every table referenced \
+                                     by this statement"
+                                        .to_owned(),
+                                ),
+                            )
+                            .into())
+                        } else if cache_only || (noria_should_try && may_read_cached) {
+                            // Without an upstream to fall back to, failures to read from the
+                            // cache are returned to the client
+                            let upstream = if cache_only {
+                                None
+                            } else {
+                                self.upstream.as_mut()
+                            };
+                            Self::query_adhoc_select(
+                                &mut self.noria,
+                                upstream,
+                                &self.settings,
+                                &mut self.state,
+                                query,
+                                &view_request,
+                                status,
+                                &mut event,
+                                processed_query_params,
+                            )
+                            .await
+                        } else {
+                            Self::query_fallback(self.upstream.as_mut(), query, &mut event).await
+                        }
+                    }
                 }
             }
             Ok(SqlQuery::Deallocate(stmt)) => Ok(Self::handle_deallocate_statement(stmt)),
@@ -3056,6 +3399,7 @@ where
             }
         };
 
+        Self::record_hints(&hints);
         self.last_query = event.destination.map(|d| QueryInfo {
             destination: d,
             noria_error: event
@@ -3063,6 +3407,7 @@ where
                 .as_ref()
                 .map(|e| e.to_string())
                 .unwrap_or_default(),
+            hints: hints.to_string(),
         });
 
         log_query(query_log_sender.as_ref(), event, slowlog);
@@ -3104,6 +3449,108 @@ where
         self.settings.require_authentication
     }
 
+    /// Returns true if clients should be authenticated with [`Self::authenticate_upstream`]
+    /// rather than by looking up their password in [`Self::users`]
+    pub fn uses_upstream_authentication(&self) -> bool {
+        self.upstream_auth.is_some() && self.upstream.is_some()
+    }
+
+    /// Returns true if clients may be authenticated with [`Self::authenticate_upstream`] over
+    /// connections that aren't encrypted
+    pub fn allows_unencrypted_upstream_authentication(&self) -> bool {
+        self.settings.allow_unencrypted_upstream_auth
+    }
+
+    /// Returns true if results for `view_request` may be served from a cache to `user`. This is
+    /// only ever false if `table_privileges` are being enforced and `user` lacks the upstream
+    /// privileges to run the query itself, in which case it should be proxied to the upstream.
+    fn may_read_cached(
+        table_privileges: Option<&TablePrivileges>,
+        user: Option<&str>,
+        view_request: &ViewCreateRequest,
+    ) -> bool {
+        match (table_privileges, user) {
+            (Some(table_privileges), Some(user)) => table_privileges.can_read(
+                user,
+                &view_request.statement,
+                &view_request.schema_search_path,
+            ),
+            _ => true,
+        }
+    }
+
+    /// How to route a read with the given hints. Without an upstream, hints which would send the
+    /// read upstream are ignored.
+    fn hinted_route(
+        hints: &QueryHints,
+        cache_staleness: Option<&CacheStaleness>,
+        has_upstream: bool,
+    ) -> HintedRoute {
+        /// Set once we've warned that `max_staleness` hints can't be honored
+        static WARNED_UNTRACKED_STALENESS: AtomicBool = AtomicBool::new(false);
+        if hints.max_staleness.is_some()
+            && cache_staleness.is_none()
+            && !WARNED_UNTRACKED_STALENESS.swap(true, Ordering::Relaxed)
+        {
+            warn!(
+                "Reads with a max_staleness hint are always proxied, since cache staleness isn't \
+                 being tracked. Enable tracking with --cache-staleness-tracking"
+            );
+        }
+
+        match hints.route(cache_staleness) {
+            HintedRoute::Upstream if !has_upstream => HintedRoute::Default,
+            route => route,
+        }
+    }
+
+    /// Records `error` as the reason a read which its hints keep from being proxied could not be
+    /// served from a cache, and returns it
+    fn hint_not_honored(event: &mut QueryExecutionEvent, error: ReadySetError) -> ReadySetError {
+        event.destination = Some(QueryDestination::Readyset);
+        event.set_noria_error(&error);
+        error
+    }
+
+    fn record_hints(hints: &QueryHints) {
+        for hint in hints.names() {
+            metrics::increment_counter!(recorded::QUERY_HINTS, "hint" => hint);
+        }
+    }
+
+    /// Authenticate a client by checking its credentials against the upstream database, and
+    /// switch this backend's upstream connection to run as the client's user.
+    ///
+    /// Credentials that were already verified recently are accepted without connecting to the
+    /// upstream; otherwise a separate connection is opened right away to check them. A rejected
+    /// attempt doesn't forget credentials verified earlier, so a client guessing passwords can't
+    /// force every other client of that user to be checked against the upstream again. The
+    /// upstream connection and the backend's user are only switched once the credentials are
+    /// accepted. Returns false if the credentials were rejected, or if the upstream couldn't be
+    /// reached.
+    pub async fn authenticate_upstream(&mut self, user: &str, password: &str) -> bool {
+        let (Some(cache), Some(upstream)) = (&self.upstream_auth, &mut self.upstream) else {
+            return false;
+        };
+
+        if cache.is_verified(user, password) {
+            trace!(%user, "Using cached upstream credentials");
+        } else {
+            if let Err(error) = upstream.check_credentials(user, password).await {
+                debug!(%user, %error, "Upstream rejected credentials");
+                return false;
+            }
+            cache.insert(user, password);
+        }
+
+        if let Err(error) = upstream.set_credentials(user, password) {
+            warn!(%error, "Could not set upstream credentials");
+            return false;
+        }
+        self.user = Some(user.to_owned());
+        true
+    }
+
     /// Gets a list of all `CREATE CACHE ...` statements
     async fn explain_caches(&mut self) -> ReadySetResult<noria_connector::QueryResult<'static>> {
         let results: Vec<Vec<DfValue>> = self
@@ -7,13 +7,14 @@ use std::time::Instant;
 
 use itertools::Itertools;
 use nom_sql::{
-    self, ColumnConstraint, DeleteStatement, DialectDisplay, Expr, InsertStatement, Relation,
-    SqlIdentifier, SqlQuery, TruncateStatement, UnaryOperator, UpdateStatement,
+    self, AlterCacheStatement, AlterReadySetStatement, ColumnConstraint, DeleteStatement,
+    DialectDisplay, Expr, InsertStatement, Relation, SqlIdentifier, SqlQuery, TruncateStatement,
+    UnaryOperator, UpdateStatement,
 };
 use readyset_client::consistency::Timestamp;
 use readyset_client::internal::LocalNodeIndex;
 use readyset_client::query::QueryId;
-use readyset_client::recipe::changelist::{Change, ChangeList, IntoChanges};
+use readyset_client::recipe::changelist::{Change, ChangeList, CreateCache, IntoChanges};
 use readyset_client::recipe::CacheExpr;
 use readyset_client::results::{ResultIterator, Results};
 use readyset_client::{
@@ -22,8 +23,8 @@ use readyset_client::{
 };
 use readyset_data::{DfType, DfValue, Dialect};
 use readyset_errors::{
-    internal_err, invariant_eq, table_err, unsupported, unsupported_err, ReadySetError,
-    ReadySetResult,
+    internal_err, invalid_query_err, invariant_eq, table_err, unsupported, unsupported_err,
+    ReadySetError, ReadySetResult,
 };
 use readyset_server::worker::readers::{CallResult, ReadRequestHandler};
 use readyset_sql_passes::adapter_rewrites::{self, AdapterRewriteParams, ProcessedQueryParams};
@@ -966,6 +967,7 @@ impl NoriaConnector {
     /// Returns Ok(Some(id)) if CREATE CACHE CONCURRENTLY is issued, where id is a unique identifier
     /// that can be used to query the status of the migration. Otherwise, returns Ok(None) on
     /// success and Err(_) on failure.
+    #[allow(clippy::too_many_arguments)]
     pub async fn handle_create_cached_query(
         &mut self,
         name: Option<&Relation>,
@@ -973,6 +975,8 @@ impl NoriaConnector {
         override_schema_search_path: Option<Vec<SqlIdentifier>>,
         always: bool,
         concurrently: bool,
+        replicas: Option<usize>,
+        shards: Option<usize>,
     ) -> ReadySetResult<Option<u64>> {
         let name = name
             .cloned()
@@ -980,7 +984,13 @@ impl NoriaConnector {
         let schema_search_path =
             override_schema_search_path.unwrap_or_else(|| self.schema_search_path.clone());
         let changelist = ChangeList::from_change(
-            Change::create_cache(name.clone(), statement.clone(), always),
+            Change::CreateCache(CreateCache {
+                name: Some(name.clone()),
+                statement: Box::new(statement.clone()),
+                always,
+                replicas,
+                shards,
+            }),
             self.dialect,
         )
         .with_schema_search_path(schema_search_path.clone());
@@ -1152,6 +1162,84 @@ impl NoriaConnector {
         Ok(())
     }
 
+    /// Handle an `ALTER READYSET` statement by asking the controller to change the set of tables
+    /// that are replicated from the upstream database.
+    pub(crate) async fn alter_readyset(
+        &mut self,
+        stmt: &AlterReadySetStatement,
+    ) -> ReadySetResult<QueryResult<'static>> {
+        match stmt {
+            AlterReadySetStatement::AddTables(tables) => {
+                let tables = tables
+                    .iter()
+                    .map(|t| self.qualify_table_name(t))
+                    .collect::<ReadySetResult<Vec<_>>>()?;
+                noria_await!(
+                    self.inner.get_mut()?,
+                    self.inner.get_mut()?.noria.add_replicated_tables(tables)
+                )?;
+            }
+            AlterReadySetStatement::DropTables(tables) => {
+                let tables = tables
+                    .iter()
+                    .map(|t| self.qualify_table_name(t))
+                    .collect::<ReadySetResult<Vec<_>>>()?;
+                noria_await!(
+                    self.inner.get_mut()?,
+                    self.inner.get_mut()?.noria.drop_replicated_tables(tables)
+                )?;
+                // Any caches that depended on the dropped tables are gone now
+                self.view_name_cache.clear().await;
+            }
+            AlterReadySetStatement::ResnapshotTable(table) => {
+                let table = self.qualify_table_name(table)?;
+                noria_await!(
+                    self.inner.get_mut()?,
+                    self.inner.get_mut()?.noria.resnapshot_table(table)
+                )?;
+                self.view_name_cache.clear().await;
+            }
+        }
+
+        Ok(QueryResult::Empty)
+    }
+
+    /// Handle an `ALTER CACHE` statement by asking the controller to change the number of times
+    /// the reader for the cache is replicated.
+    pub(crate) async fn alter_cache(
+        &mut self,
+        stmt: &AlterCacheStatement,
+    ) -> ReadySetResult<QueryResult<'static>> {
+        noria_await!(
+            self.inner.get_mut()?,
+            self.inner
+                .get_mut()?
+                .noria
+                .set_reader_replicas(&stmt.name, stmt.replicas)
+        )?;
+        // Any view we have for the cache only knows about its old reader replicas
+        self.inner.get_mut()?.views.remove(&stmt.name).await;
+
+        Ok(QueryResult::Empty)
+    }
+
+    /// Resolve the schema of a table referenced without one to the first schema in the schema
+    /// search path, since the replicator can only identify tables by their fully-qualified name.
+    fn qualify_table_name(&self, table: &Relation) -> ReadySetResult<Relation> {
+        if table.schema.is_some() {
+            return Ok(table.clone());
+        }
+
+        let schema = self.schema_search_path.first().ok_or_else(|| {
+            invalid_query_err!("No schema selected to resolve table {}", table.name)
+        })?;
+
+        Ok(Relation {
+            schema: Some(schema.clone()),
+            name: table.name.clone(),
+        })
+    }
+
     pub async fn view_create_request_from_name(
         &self,
         name: &Relation,
@@ -1533,11 +1621,22 @@ impl NoriaConnector {
         };
 
         let view_failed = self.failed_views.take(qname.as_ref()).is_some();
-        let getter = self
+        let getter = match self
             .inner
             .get_mut()?
             .get_noria_view(&qname, view_failed)
-            .await?;
+            .await
+        {
+            Ok(getter) => getter,
+            Err(e) => {
+                // The cache's reader replicas may be changing, so try again for a fresh view next
+                // time rather than waiting for a read to fail
+                if e.caused_by_reader_replica_not_running() {
+                    self.failed_views.insert(qname.into_owned());
+                }
+                return Err(e);
+            }
+        };
 
         let res = do_read(
             getter,
@@ -1551,7 +1650,14 @@ impl NoriaConnector {
         .await;
 
         if let Err(e) = res.as_ref() {
-            if e.is_networking_related() || e.caused_by_view_destroyed() {
+            // Views hold connections to the reader replicas that were running when they were
+            // built, so if the cache's replicas have changed since then (possibly by another
+            // connection or adapter altering the cache) reads will fail this way until the view is
+            // fetched again
+            if e.is_networking_related()
+                || e.caused_by_view_destroyed()
+                || e.caused_by_reader_replica_not_running()
+            {
                 self.failed_views.insert(qname.clone().into_owned());
             }
         }
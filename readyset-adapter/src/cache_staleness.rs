@@ -0,0 +1,309 @@
+//! Tracking how far behind the upstream database the data in caches may be.
+//!
+//! Caches are kept up to date by replicating changes from the upstream database, so reads served
+//! from them may miss writes which the upstream has committed but ReadySet hasn't replicated yet.
+//! [`CacheStaleness`] keeps an upper bound on how old those missing writes can be, for clients
+//! that ask for reads no older than some maximum staleness (see [`crate::query_hints`]).
+//!
+//! [`CacheStaleness::run`] periodically records the upstream's current replication log position,
+//! along with the time at which it was read, and then checks how far ReadySet has replicated. Once
+//! ReadySet has replicated up to a recorded position, every write committed before the time that
+//! position was recorded is in the caches, so the caches are at most as stale as the time since.
+//!
+//! ReadySet's replication position doesn't only move with changes to replicated tables: once the
+//! replicator has applied everything the upstream has sent it, it records the position it has
+//! caught up to when it receives a heartbeat (MySQL) or keepalive message (PostgreSQL) from the
+//! upstream. So while the upstream is only writing to other tables, or not writing at all, the
+//! bound stays within a few heartbeats of the polling interval rather than growing.
+//!
+//! Tracking only starts the first time [`CacheStaleness::staleness`] is called, so the upstream
+//! isn't polled unless clients actually ask for reads with a maximum staleness.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use database_utils::{DatabaseConnection, DatabaseError, DatabaseURL, QueryableConnection};
+use metrics::gauge;
+use nom_sql::Dialect;
+use parking_lot::RwLock;
+use readyset_client::ReadySetHandle;
+use readyset_client_metrics::recorded;
+use readyset_data::DfValue;
+use readyset_util::shutdown::ShutdownReceiver;
+use replication_offset::mysql::MySqlPosition;
+use replication_offset::postgres::{CommitLsn, PostgresPosition};
+use replication_offset::ReplicationOffset;
+use tokio::select;
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+/// The most upstream positions to remember while waiting for ReadySet to replicate up to them.
+/// When more are recorded, the oldest are forgotten, which only makes the bound less tight.
+const MAX_WATERMARKS: usize = 1024;
+
+/// Reads the current write-ahead log position of a PostgreSQL database. `pg_current_wal_lsn` can't
+/// be called on a standby, which ReadySet may be replicating from, so on a standby this reads the
+/// position it has replayed up to instead.
+const POSTGRES_POSITION: &str = "SELECT (CASE WHEN pg_is_in_recovery() \
+     THEN pg_last_wal_replay_lsn() ELSE pg_current_wal_lsn() END)::text";
+
+/// Reads the current binary log position of a MySQL database, in MySQL 8.4 and above
+const MYSQL_POSITION: &str = "SHOW BINARY LOG STATUS";
+
+/// Reads the current binary log position of a MySQL database, in MySQL 8.3 and below and MariaDB
+const MYSQL_LEGACY_POSITION: &str = "SHOW MASTER STATUS";
+
+#[derive(Debug, Default)]
+struct Watermarks {
+    /// Upstream replication log positions, each with the time it was read, which ReadySet has not
+    /// replicated up to yet, oldest first
+    pending: VecDeque<(Instant, ReplicationOffset)>,
+    /// The time at which the newest upstream position ReadySet has replicated up to was read
+    caught_up_to: Option<Instant>,
+}
+
+/// A handle to the bound on how stale cached data may be, shared by all connections to the
+/// adapter.
+///
+/// Until ReadySet has replicated up to the first position recorded from the upstream, the
+/// staleness of caches is unknown.
+#[derive(Debug, Default, Clone)]
+pub struct CacheStaleness {
+    watermarks: Arc<RwLock<Watermarks>>,
+    /// Set once anything has asked how stale caches are
+    requested: Arc<AtomicBool>,
+    /// Notified the first time anything asks how stale caches are, to start tracking it
+    start: Arc<Notify>,
+}
+
+impl CacheStaleness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns an upper bound on how long ago writes which may be missing from caches were
+    /// committed upstream, or `None` if no such bound is known yet
+    pub fn staleness(&self) -> Option<Duration> {
+        if !self.requested.swap(true, Ordering::Relaxed) {
+            self.start.notify_one();
+        }
+        self.watermarks
+            .read()
+            .caught_up_to
+            .map(|caught_up_to| caught_up_to.elapsed())
+    }
+
+    /// Remember that the upstream database's replication log was at `offset` at time `at`
+    pub fn record_upstream(&self, at: Instant, offset: ReplicationOffset) {
+        let mut watermarks = self.watermarks.write();
+        if watermarks.pending.len() >= MAX_WATERMARKS {
+            watermarks.pending.pop_front();
+        }
+        watermarks.pending.push_back((at, offset));
+    }
+
+    /// Remember that ReadySet has replicated every table up to `offset`
+    pub fn record_readyset(&self, offset: &ReplicationOffset) {
+        let mut watermarks = self.watermarks.write();
+        // Positions which can't be compared with ReadySet's (such as those from before the
+        // upstream switched to a new binlog) are never caught up to, and are forgotten once a
+        // later position is
+        let Some(caught_up) = watermarks
+            .pending
+            .iter()
+            .rposition(|(_, upstream)| offset.try_partial_cmp(upstream).is_ok_and(|o| o.is_ge()))
+        else {
+            return;
+        };
+        let caught_up_to = watermarks.pending[caught_up].0;
+        watermarks.caught_up_to = Some(caught_up_to);
+        watermarks.pending.drain(..=caught_up);
+    }
+
+    /// Once [`staleness`](Self::staleness) has first been called, every `interval`, record the
+    /// current replication log position of the upstream database at `upstream_url`, and check how
+    /// far ReadySet has replicated, until a shutdown signal is received.
+    pub async fn run(
+        self,
+        upstream_url: DatabaseURL,
+        mut controller: ReadySetHandle,
+        interval: Duration,
+        mut shutdown_recv: ShutdownReceiver,
+    ) {
+        let mut interval = tokio::time::interval(interval);
+        let fut = async {
+            self.start.notified().await;
+            info!("Starting to track cache staleness");
+            let mut conn = None;
+            let mut mysql_position_query = MYSQL_POSITION;
+            loop {
+                interval.tick().await;
+                if conn.is_none() {
+                    match upstream_url.connect(None).await {
+                        Ok(c) => conn = Some(c),
+                        Err(error) => {
+                            warn!(%error, "Could not connect to upstream to read its position");
+                            continue;
+                        }
+                    }
+                }
+                let Some(c) = conn.as_mut() else {
+                    continue;
+                };
+                let at = Instant::now();
+                let position = match upstream_position(c, mysql_position_query).await {
+                    Err(_)
+                        if c.dialect() == Dialect::MySQL
+                            && mysql_position_query == MYSQL_POSITION =>
+                    {
+                        mysql_position_query = MYSQL_LEGACY_POSITION;
+                        upstream_position(c, mysql_position_query).await
+                    }
+                    res => res,
+                };
+                match position {
+                    Ok(Some(position)) => self.record_upstream(at, position),
+                    Ok(None) => {
+                        debug!("Upstream database is not writing a replication log");
+                        continue;
+                    }
+                    Err(error) => {
+                        warn!(%error, "Could not read upstream replication log position");
+                        conn = None;
+                        continue;
+                    }
+                }
+
+                match controller.replication_offsets().await {
+                    Ok(offsets) => match offsets.min_present_offset() {
+                        Ok(Some(offset)) => self.record_readyset(offset),
+                        Ok(None) => {}
+                        Err(error) => warn!(%error, "Could not compare replication offsets"),
+                    },
+                    Err(error) => warn!(%error, "Could not load ReadySet replication offsets"),
+                }
+                if let Some(staleness) = self.staleness() {
+                    gauge!(recorded::CACHE_STALENESS, staleness.as_secs_f64());
+                }
+            }
+        };
+        select! {
+            biased;
+            _ = shutdown_recv.recv() => {
+                info!("Cache staleness tracker shutting down after shut down signal received");
+            }
+            _ = fut => unreachable!(),
+        }
+    }
+}
+
+/// Read the current replication log position of the upstream database, using `mysql_query` for
+/// MySQL. Returns `None` if the upstream isn't writing a replication log.
+async fn upstream_position(
+    conn: &mut DatabaseConnection,
+    mysql_query: &str,
+) -> Result<Option<ReplicationOffset>, DatabaseError> {
+    let query = match conn.dialect() {
+        Dialect::PostgreSQL => POSTGRES_POSITION,
+        Dialect::MySQL => mysql_query,
+    };
+    let rows = Vec::<Vec<DfValue>>::try_from(conn.query(query).await?)?;
+    let Some(row) = rows.into_iter().next() else {
+        return Ok(None);
+    };
+    let position = match (conn.dialect(), &row[..]) {
+        (Dialect::PostgreSQL, [lsn, ..]) => {
+            let lsn = <&str>::try_from(lsn)
+                .and_then(|lsn| lsn.parse::<CommitLsn>())
+                .map_err(|e| DatabaseError::ValueConversion(e.into()))?;
+            // ReadySet has applied every transaction that committed before `lsn` once it has
+            // reached any position within a transaction that commits at or after it
+            PostgresPosition::commit_start(lsn).into()
+        }
+        (Dialect::MySQL, [file, position, ..]) => {
+            // Depending on the protocol the position may come back as either a number or text
+            let position = match u64::try_from(position) {
+                Ok(position) => position,
+                Err(_) => <&str>::try_from(position)
+                    .map_err(|e| DatabaseError::ValueConversion(e.into()))?
+                    .parse()
+                    .map_err(|e: std::num::ParseIntError| {
+                        DatabaseError::ValueConversion(e.into())
+                    })?,
+            };
+            String::try_from(file)
+                .and_then(|file| MySqlPosition::from_file_name_and_position(file, position))
+                .map_err(|e| DatabaseError::ValueConversion(e.into()))?
+                .into()
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(position))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(position: u64) -> ReplicationOffset {
+        MySqlPosition::from_file_name_and_position("binlog.000001".into(), position)
+            .unwrap()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn starts_when_requested() {
+        let staleness = CacheStaleness::new();
+        let started = staleness.start.notified();
+        tokio::pin!(started);
+        assert!(futures::poll!(started.as_mut()).is_pending());
+        staleness.staleness();
+        assert!(futures::poll!(started.as_mut()).is_ready());
+    }
+
+    #[test]
+    fn unknown_until_caught_up() {
+        let staleness = CacheStaleness::new();
+        assert_eq!(staleness.staleness(), None);
+        staleness.record_upstream(Instant::now(), position(10));
+        staleness.record_readyset(&position(5));
+        assert_eq!(staleness.staleness(), None);
+        staleness.record_readyset(&position(10));
+        assert!(staleness.staleness().is_some());
+    }
+
+    #[test]
+    fn bounded_by_newest_position_caught_up_to() {
+        let staleness = CacheStaleness::new();
+        let start = Instant::now();
+        staleness.record_upstream(start - Duration::from_secs(30), position(10));
+        staleness.record_upstream(start - Duration::from_secs(20), position(20));
+        staleness.record_upstream(start - Duration::from_secs(10), position(30));
+
+        staleness.record_readyset(&position(25));
+        let bound = staleness.staleness().unwrap();
+        assert!(bound >= Duration::from_secs(20) && bound < Duration::from_secs(30));
+
+        staleness.record_readyset(&position(30));
+        let bound = staleness.staleness().unwrap();
+        assert!(bound >= Duration::from_secs(10) && bound < Duration::from_secs(20));
+    }
+
+    #[test]
+    fn incomparable_positions_are_skipped() {
+        let staleness = CacheStaleness::new();
+        let start = Instant::now();
+        staleness.record_upstream(
+            start - Duration::from_secs(20),
+            MySqlPosition::from_file_name_and_position("other.000001".into(), 1)
+                .unwrap()
+                .into(),
+        );
+        staleness.record_upstream(start - Duration::from_secs(10), position(10));
+        staleness.record_readyset(&position(10));
+        let bound = staleness.staleness().unwrap();
+        assert!(bound >= Duration::from_secs(10) && bound < Duration::from_secs(20));
+    }
+}
@@ -0,0 +1,279 @@
+//! An [`UpstreamDatabase`] that doesn't connect anywhere, for testing code that manages upstream
+//! connections, such as the [`UpstreamPool`](crate::upstream_pool::UpstreamPool).
+//!
+//! Every statement run on a [`FakeUpstream`] is recorded in a log shared by all connections opened
+//! on the same thread, along with the ID of the connection it ran on and the URL that connection
+//! was opened with.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use nom_sql::{SqlIdentifier, StartTransactionStatement};
+use readyset_adapter_types::DeallocateId;
+use readyset_data::DfValue;
+use readyset_errors::ReadySetError;
+
+use crate::upstream_database::{
+    IsFatalError, UpstreamConfig, UpstreamDatabase, UpstreamDestination, UpstreamPrepare,
+};
+
+thread_local! {
+    static LOG: RefCell<Vec<LogEntry>> = RefCell::new(vec![]);
+    static NEXT_CONNECTION_ID: Cell<usize> = Cell::new(0);
+    static REPLICATION_LAG: RefCell<HashMap<String, Option<Duration>>> =
+        RefCell::new(HashMap::new());
+}
+
+/// A statement run on a [`FakeUpstream`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LogEntry {
+    /// The ID of the connection the statement ran on, in the order connections were opened
+    pub(crate) connection: usize,
+    /// The URL the connection was opened with
+    pub(crate) url: String,
+    pub(crate) statement: String,
+}
+
+/// Take all the statements run on the current thread so far out of the log
+pub(crate) fn take_log() -> Vec<LogEntry> {
+    LOG.with(|log| log.take())
+}
+
+/// Make connections to `url` report the given replication lag from now on. Connections to URLs
+/// without a lag set report that they're caught up.
+pub(crate) fn set_replication_lag(url: &str, lag: Option<Duration>) {
+    REPLICATION_LAG.with(|lags| lags.borrow_mut().insert(url.to_owned(), lag));
+}
+
+/// Returns an [`UpstreamConfig`] for connecting to a [`FakeUpstream`] with the given URL
+pub(crate) fn config(url: &str) -> UpstreamConfig {
+    UpstreamConfig {
+        upstream_db_url: Some(url.to_owned().into()),
+        ..Default::default()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error(transparent)]
+pub(crate) struct FakeError(#[from] ReadySetError);
+
+impl IsFatalError for FakeError {
+    fn is_fatal(&self) -> bool {
+        matches!(self.0, ReadySetError::UpstreamConnectionLost(_))
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct FakeResult;
+
+impl UpstreamDestination for FakeResult {}
+
+pub(crate) struct FakeUpstream {
+    id: usize,
+    url: String,
+    statements: HashMap<u32, String>,
+    next_statement_id: u32,
+}
+
+impl FakeUpstream {
+    fn log(&self, statement: impl Into<String>) {
+        let entry = LogEntry {
+            connection: self.id,
+            url: self.url.clone(),
+            statement: statement.into(),
+        };
+        LOG.with(|log| log.borrow_mut().push(entry));
+    }
+}
+
+#[async_trait]
+impl UpstreamDatabase for FakeUpstream {
+    type QueryResult<'a> = FakeResult;
+    type StatementMeta = ();
+    type PrepareData<'a> = ();
+    type OwnedPrepareData = ();
+    type ExecMeta<'a> = ();
+    type Error = FakeError;
+
+    const DEFAULT_DB_VERSION: &'static str = "fake";
+    const SQL_DIALECT: nom_sql::Dialect = nom_sql::Dialect::PostgreSQL;
+
+    fn own_prepare_data(_: &Self::PrepareData<'_>) -> Self::OwnedPrepareData {}
+
+    fn borrow_prepare_data(_: &Self::OwnedPrepareData) -> Self::PrepareData<'_> {}
+
+    async fn connect(upstream_config: UpstreamConfig) -> Result<Self, Self::Error> {
+        let url = upstream_config
+            .upstream_db_url
+            .as_deref()
+            .cloned()
+            .unwrap_or_default();
+        let id = NEXT_CONNECTION_ID.with(|next| next.replace(next.get() + 1));
+        Ok(Self {
+            id,
+            url,
+            statements: HashMap::new(),
+            next_statement_id: 0,
+        })
+    }
+
+    async fn is_connected(&mut self) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+
+    async fn change_user(
+        &mut self,
+        user: &str,
+        _password: &str,
+        _database: &str,
+    ) -> Result<(), Self::Error> {
+        self.statements.clear();
+        self.log(format!("change user {user}"));
+        Ok(())
+    }
+
+    async fn reset(&mut self) -> Result<(), Self::Error> {
+        self.statements.clear();
+        self.log("reset");
+        Ok(())
+    }
+
+    fn version(&self) -> String {
+        Self::DEFAULT_DB_VERSION.to_owned()
+    }
+
+    async fn prepare<'a, 'b, S>(
+        &'a mut self,
+        query: S,
+        _data: Self::PrepareData<'b>,
+    ) -> Result<UpstreamPrepare<Self>, Self::Error>
+    where
+        S: AsRef<str> + Send + Sync + 'a,
+    {
+        let statement_id = self.next_statement_id;
+        self.next_statement_id += 1;
+        self.statements
+            .insert(statement_id, query.as_ref().to_owned());
+        self.log(format!("prepare {}", query.as_ref()));
+        Ok(UpstreamPrepare {
+            statement_id,
+            meta: (),
+        })
+    }
+
+    async fn execute<'a>(
+        &'a mut self,
+        statement_id: u32,
+        _params: &[DfValue],
+        _exec_meta: Self::ExecMeta<'_>,
+    ) -> Result<Self::QueryResult<'a>, Self::Error> {
+        let query = self
+            .statements
+            .get(&statement_id)
+            .ok_or(ReadySetError::PreparedStatementMissing { statement_id })?;
+        self.log(format!("execute {query}"));
+        Ok(FakeResult)
+    }
+
+    async fn declare_cursor(
+        &mut self,
+        statement_id: u32,
+        _params: &[DfValue],
+        cursor: &str,
+        _hold: bool,
+    ) -> Result<(), Self::Error> {
+        let query = self
+            .statements
+            .get(&statement_id)
+            .ok_or(ReadySetError::PreparedStatementMissing { statement_id })?;
+        self.log(format!("declare {cursor} for {query}"));
+        Ok(())
+    }
+
+    async fn fetch_cursor<'a>(
+        &'a mut self,
+        cursor: &str,
+        limit: u32,
+        _exec_meta: Self::ExecMeta<'_>,
+    ) -> Result<Self::QueryResult<'a>, Self::Error> {
+        self.log(format!("fetch {limit} from {cursor}"));
+        Ok(FakeResult)
+    }
+
+    async fn close_cursor(&mut self, cursor: &str) -> Result<(), Self::Error> {
+        self.log(format!("close {cursor}"));
+        Ok(())
+    }
+
+    async fn remove_statement(&mut self, statement_id: DeallocateId) -> Result<(), Self::Error> {
+        match statement_id {
+            DeallocateId::Numeric(statement_id) => {
+                let query = self
+                    .statements
+                    .remove(&statement_id)
+                    .ok_or(ReadySetError::PreparedStatementMissing { statement_id })?;
+                self.log(format!("deallocate {query}"));
+            }
+            DeallocateId::All => {
+                self.statements.clear();
+                self.log("deallocate all");
+            }
+            DeallocateId::Named(name) => self.log(format!("deallocate {name}")),
+        }
+        Ok(())
+    }
+
+    async fn query<'a>(&'a mut self, query: &'a str) -> Result<Self::QueryResult<'a>, Self::Error> {
+        self.log(query);
+        Ok(FakeResult)
+    }
+
+    async fn simple_query<'a>(
+        &'a mut self,
+        query: &'a str,
+    ) -> Result<Self::QueryResult<'a>, Self::Error> {
+        self.log(query);
+        Ok(FakeResult)
+    }
+
+    async fn handle_ryw_write<'a, S>(
+        &'a mut self,
+        query: S,
+    ) -> Result<(Self::QueryResult<'a>, String), Self::Error>
+    where
+        S: AsRef<str> + Send + Sync + 'a,
+    {
+        self.log(query.as_ref());
+        Ok((FakeResult, String::new()))
+    }
+
+    async fn start_tx<'a>(
+        &'a mut self,
+        _stmt: &StartTransactionStatement,
+    ) -> Result<Self::QueryResult<'a>, Self::Error> {
+        self.log("BEGIN");
+        Ok(FakeResult)
+    }
+
+    async fn commit<'a>(&'a mut self) -> Result<Self::QueryResult<'a>, Self::Error> {
+        self.log("COMMIT");
+        Ok(FakeResult)
+    }
+
+    async fn rollback<'a>(&'a mut self) -> Result<Self::QueryResult<'a>, Self::Error> {
+        self.log("ROLLBACK");
+        Ok(FakeResult)
+    }
+
+    async fn schema_search_path(&mut self) -> Result<Vec<SqlIdentifier>, Self::Error> {
+        Ok(vec![])
+    }
+
+    async fn replication_lag(&mut self) -> Result<Option<Duration>, Self::Error> {
+        Ok(REPLICATION_LAG
+            .with(|lags| lags.borrow().get(&self.url).copied())
+            .unwrap_or(Some(Duration::ZERO)))
+    }
+}
@@ -0,0 +1,690 @@
+//! Reading from caches over HTTP.
+//!
+//! Clients that can't (or would rather not) speak the upstream database's wire protocol can read
+//! from a named cache with a plain HTTP request to the adapter's HTTP server, passing the values
+//! of the cache's query parameters and getting back the matching rows as JSON.
+//!
+//! Reads go through the same [`NoriaConnector`] path as executing a prepared statement for the
+//! cache's query, so they see exactly what SQL clients would, and use the adapter's configured
+//! read behavior: with non-blocking reads, a key that isn't in the cache yet is reported as a
+//! retryable miss rather than waited for.
+//!
+//! Requests are authenticated with HTTP basic authentication against the same credentials SQL
+//! clients use, and, if upstream table privileges are enforced, users may only read from caches
+//! whose queries they could have run upstream. The HTTP server doesn't support TLS, so basic
+//! authentication, which sends passwords in cleartext, has to be explicitly allowed. Since there is
+//! no upstream to fall back to, a read that would be proxied for a SQL client is refused instead.
+//!
+//! Reads are only served once the upstream database's schema search path is known, since it's
+//! needed to resolve the tables referenced by caches' queries.
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use database_utils::{DatabaseURL, UpstreamConfig};
+use hyper::body::HttpBody;
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER, WWW_AUTHENTICATE};
+use hyper::http::response;
+use hyper::{Body, Method, Request, Response};
+use nom_sql::{Relation, SelectStatement, SqlIdentifier};
+use parking_lot::{Mutex, RwLock};
+use percent_encoding::percent_decode_str;
+use readyset_client::{ColumnSchema, ReadySetHandle, View, ViewCreateRequest};
+use readyset_client_metrics::{EventType, QueryExecutionEvent};
+use readyset_data::{DfType, DfValue, Dialect};
+use readyset_errors::ReadySetError;
+use readyset_sql_passes::adapter_rewrites::AdapterRewriteParams;
+use readyset_util::shared_cache::SharedCache;
+use serde_json::{json, Value as JsonValue};
+use thiserror::Error;
+use tracing::{debug, trace};
+
+use crate::backend::noria_connector::{
+    ExecuteSelectContext, PrepareResult, PreparedSelectTypes, QueryResult, ReadBehavior,
+};
+use crate::backend::NoriaConnector;
+use crate::privileges::TablePrivileges;
+use crate::upstream_auth::UpstreamAuthCache;
+
+/// The prefix of the path of every cache read request; the rest of the path is the cache's name
+pub const CACHES_PATH: &str = "/caches/";
+
+/// The query string parameter holding the value of each of a cache's query parameters, in order
+const KEY_PARAM: &str = "key";
+
+/// The largest request body, in bytes, accepted for a read. This is far more than any list of a
+/// cache's query parameters needs.
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// How long to wait after loading the list of caches before loading it again to look for a cache
+/// that wasn't in it, so that requests for caches that don't exist can't keep the controller busy
+const CACHES_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Reasons a read from a cache over HTTP can fail
+#[derive(Debug, Error)]
+enum CacheReadError {
+    #[error("Authentication required")]
+    Unauthorized,
+    #[error("Basic authentication over unencrypted HTTP is not allowed")]
+    UnencryptedCredentials,
+    #[error("User {0} may not read from this cache")]
+    Forbidden(String),
+    #[error(
+        "Upstream table privileges are enforced, so reads must be made by an authenticated user"
+    )]
+    AnonymousRead,
+    #[error("Not ready to serve reads yet; retry the request later")]
+    NotReady,
+    #[error("Cache {0} not found")]
+    CacheNotFound(String),
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("Request body is larger than {MAX_BODY_BYTES} bytes")]
+    PayloadTooLarge,
+    #[error("Key is not in the cache yet; retry the request later")]
+    CacheMiss,
+    #[error(transparent)]
+    ReadySet(#[from] ReadySetError),
+}
+
+impl CacheReadError {
+    fn status(&self) -> u16 {
+        match self {
+            CacheReadError::Unauthorized => 401,
+            CacheReadError::Forbidden(_)
+            | CacheReadError::AnonymousRead
+            | CacheReadError::UnencryptedCredentials => 403,
+            CacheReadError::CacheNotFound(_) => 404,
+            CacheReadError::BadRequest(_) => 400,
+            CacheReadError::PayloadTooLarge => 413,
+            CacheReadError::CacheMiss | CacheReadError::NotReady => 503,
+            CacheReadError::ReadySet(e) if e.is_view_not_found() => 404,
+            CacheReadError::ReadySet(_) => 500,
+        }
+    }
+}
+
+/// How clients of the HTTP cache read API are authenticated
+pub enum HttpAuthentication {
+    /// Accept any request, without knowing who made it. Credentials given with basic
+    /// authentication are ignored, so if table privileges are enforced every read is refused.
+    None,
+    /// Only accept the given usernames and passwords
+    Users(HashMap<String, String>),
+    /// Check credentials by connecting to the upstream database with them
+    Upstream {
+        cache: UpstreamAuthCache,
+        upstream_config: UpstreamConfig,
+    },
+}
+
+/// Serves reads from caches over HTTP, shared by all requests to the adapter's HTTP server.
+pub struct HttpCacheReads {
+    rh: ReadySetHandle,
+    auto_increments: Arc<tokio::sync::RwLock<HashMap<Relation, AtomicUsize>>>,
+    view_name_cache: SharedCache<ViewCreateRequest, Relation>,
+    view_cache: SharedCache<Relation, View>,
+    read_behavior: ReadBehavior,
+    dialect: Dialect,
+    parse_dialect: nom_sql::Dialect,
+    rewrite_params: AdapterRewriteParams,
+    authentication: HttpAuthentication,
+    /// Whether to accept credentials sent with basic authentication, which aren't encrypted
+    allow_basic_auth: bool,
+    table_privileges: Option<TablePrivileges>,
+    /// The queries of every cache, by cache name
+    statements: RwLock<HashMap<Relation, SelectStatement>>,
+    /// When `statements` was last loaded from the controller. Held while loading them, so that
+    /// concurrent requests for unknown caches only load them once.
+    statements_loaded_at: tokio::sync::Mutex<Option<Instant>>,
+    /// Used to resolve unqualified table names in caches' queries, once it's been loaded from the
+    /// upstream database
+    schema_search_path: RwLock<Option<Vec<SqlIdentifier>>>,
+    /// Connectors not currently used by any request, kept to be reused by later requests
+    connectors: Mutex<Vec<NoriaConnector>>,
+}
+
+impl HttpCacheReads {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        rh: ReadySetHandle,
+        auto_increments: Arc<tokio::sync::RwLock<HashMap<Relation, AtomicUsize>>>,
+        view_name_cache: SharedCache<ViewCreateRequest, Relation>,
+        view_cache: SharedCache<Relation, View>,
+        read_behavior: ReadBehavior,
+        dialect: Dialect,
+        parse_dialect: nom_sql::Dialect,
+        rewrite_params: AdapterRewriteParams,
+        authentication: HttpAuthentication,
+        allow_basic_auth: bool,
+        table_privileges: Option<TablePrivileges>,
+    ) -> Self {
+        Self {
+            rh,
+            auto_increments,
+            view_name_cache,
+            view_cache,
+            read_behavior,
+            dialect,
+            parse_dialect,
+            rewrite_params,
+            authentication,
+            allow_basic_auth,
+            table_privileges,
+            statements: Default::default(),
+            statements_loaded_at: Default::default(),
+            schema_search_path: Default::default(),
+            connectors: Default::default(),
+        }
+    }
+
+    /// Start serving reads, resolving unqualified table names using `schema_search_path`
+    pub fn set_schema_search_path(&self, schema_search_path: Vec<SqlIdentifier>) {
+        *self.schema_search_path.write() = Some(schema_search_path);
+        // Connectors were built with the old search path
+        self.connectors.lock().clear();
+    }
+
+    /// Take a connector not in use by any other request, or build a new one if there are none
+    async fn connector(&self, schema_search_path: Vec<SqlIdentifier>) -> NoriaConnector {
+        let idle = self.connectors.lock().pop();
+        match idle {
+            Some(noria) => noria,
+            None => {
+                NoriaConnector::new(
+                    self.rh.clone(),
+                    self.auto_increments.clone(),
+                    self.view_name_cache.new_local(),
+                    self.view_cache.new_local(),
+                    self.read_behavior,
+                    self.dialect,
+                    self.parse_dialect,
+                    schema_search_path,
+                    self.rewrite_params,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Serve a read from the cache named by the path of `req`, finishing the response in `res`
+    pub async fn handle(&self, req: Request<Body>, res: response::Builder) -> Response<Body> {
+        let result = self.read(req).await;
+        let res = match result {
+            Ok(body) => res
+                .status(200)
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            Err(error) => {
+                debug!(%error, "HTTP cache read failed");
+                let res = match &error {
+                    CacheReadError::Unauthorized => {
+                        res.header(WWW_AUTHENTICATE, "Basic realm=\"ReadySet\"")
+                    }
+                    CacheReadError::CacheMiss | CacheReadError::NotReady => {
+                        res.header(RETRY_AFTER, "1")
+                    }
+                    _ => res,
+                };
+                res.status(error.status())
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Body::from(
+                        json!({ "error": error.to_string() }).to_string(),
+                    ))
+            }
+        };
+        res.unwrap()
+    }
+
+    async fn read(&self, req: Request<Body>) -> Result<JsonValue, CacheReadError> {
+        let name = req
+            .uri()
+            .path()
+            .strip_prefix(CACHES_PATH)
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| CacheReadError::BadRequest("Missing cache name".into()))?;
+        let name = percent_decode_str(name)
+            .decode_utf8()
+            .map_err(|_| CacheReadError::BadRequest("Cache name is not valid UTF-8".into()))?
+            .into_owned();
+        let credentials = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|header| header.to_str().ok())
+            .and_then(parse_basic_auth);
+        let user = self.authenticate(credentials).await?;
+
+        let params = match *req.method() {
+            Method::GET => query_string_params(req.uri().query().unwrap_or_default()),
+            _ => json_params(&read_body(req.into_body()).await?)?,
+        };
+
+        let schema_search_path = self
+            .schema_search_path
+            .read()
+            .clone()
+            .ok_or(CacheReadError::NotReady)?;
+        let statement = self.statement(&name).await?;
+        if let Some(table_privileges) = &self.table_privileges {
+            let user = user.ok_or(CacheReadError::AnonymousRead)?;
+            if !table_privileges.can_read(&user, &statement, &schema_search_path) {
+                return Err(CacheReadError::Forbidden(user));
+            }
+        }
+
+        let mut noria = self.connector(schema_search_path).await;
+        let result = self.read_cache(&mut noria, name, statement, params).await;
+        self.connectors.lock().push(noria);
+        result
+    }
+
+    /// Read the rows matching `params` from the cache called `name`, whose query is `statement`
+    async fn read_cache(
+        &self,
+        noria: &mut NoriaConnector,
+        name: String,
+        statement: SelectStatement,
+        params: Vec<DfValue>,
+    ) -> Result<JsonValue, CacheReadError> {
+        let (types, ps) = match noria.prepare_select(statement, false, None).await {
+            Ok(PrepareResult::Select { types, statement }) => (types, statement),
+            Ok(_) => return Err(CacheReadError::CacheNotFound(name)),
+            Err(e) if e.is_view_not_found() => {
+                // The cache may have been dropped since we last looked it up
+                self.forget_statement(&name);
+                return Err(CacheReadError::CacheNotFound(name));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let params = match types {
+            PreparedSelectTypes::Schema(inner) => coerce_params(params, &inner.params)?,
+            PreparedSelectTypes::NoSchema => params,
+        };
+
+        let mut event = QueryExecutionEvent::new(EventType::Execute);
+        let result = noria
+            .execute_select(
+                ExecuteSelectContext::Prepared {
+                    ps: &ps,
+                    params: &params,
+                },
+                None,
+                &mut event,
+            )
+            .await;
+        match result {
+            Ok(QueryResult::Select { rows, schema }) => {
+                Ok(rows_to_json(rows.into_iter(), &schema.schema))
+            }
+            Ok(_) => Err(CacheReadError::CacheNotFound(name)),
+            Err(ReadySetError::ReaderMissingKey) => Err(CacheReadError::CacheMiss),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Check the credentials of a request, returning the name of the user making it, if known
+    async fn authenticate(
+        &self,
+        credentials: Option<(String, String)>,
+    ) -> Result<Option<String>, CacheReadError> {
+        match (&self.authentication, credentials) {
+            (HttpAuthentication::None, _) => Ok(None),
+            (_, Some(_)) if !self.allow_basic_auth => Err(CacheReadError::UnencryptedCredentials),
+            (HttpAuthentication::Users(users), Some((user, password)))
+                if users.get(&user) == Some(&password) =>
+            {
+                Ok(Some(user))
+            }
+            (
+                HttpAuthentication::Upstream {
+                    cache,
+                    upstream_config,
+                },
+                Some((user, password)),
+            ) => {
+                if cache.is_verified(&user, &password) {
+                    trace!(%user, "Using cached upstream credentials");
+                    return Ok(Some(user));
+                }
+                let url = upstream_config
+                    .with_credentials(&user, &password)?
+                    .upstream_db_url
+                    .ok_or(ReadySetError::InvalidUpstreamDatabase)?
+                    .parse::<DatabaseURL>()
+                    .map_err(|_| ReadySetError::InvalidUpstreamDatabase)?;
+                match url.connect(None).await {
+                    Ok(_) => {
+                        cache.insert(&user, &password);
+                        Ok(Some(user))
+                    }
+                    Err(error) => {
+                        debug!(%user, %error, "Upstream rejected credentials");
+                        Err(CacheReadError::Unauthorized)
+                    }
+                }
+            }
+            _ => Err(CacheReadError::Unauthorized),
+        }
+    }
+
+    /// Look up the query of the cache called `name`, which may be qualified with a schema.
+    ///
+    /// Caches that aren't known yet are looked up by reloading every cache from the controller, at
+    /// most once every [`CACHES_REFRESH_INTERVAL`].
+    async fn statement(&self, name: &str) -> Result<SelectStatement, CacheReadError> {
+        if let Some(statement) = find_cache(&self.statements.read(), name) {
+            return Ok(statement.clone());
+        }
+
+        let mut loaded_at = self.statements_loaded_at.lock().await;
+        // Another request may have loaded the caches while we were waiting
+        if let Some(statement) = find_cache(&self.statements.read(), name) {
+            return Ok(statement.clone());
+        }
+        if loaded_at.is_some_and(|at| at.elapsed() < CACHES_REFRESH_INTERVAL) {
+            return Err(CacheReadError::CacheNotFound(name.to_owned()));
+        }
+
+        let mut rh = self.rh.clone();
+        let caches = rh.verbose_views().await?;
+        *loaded_at = Some(Instant::now());
+        let mut statements = self.statements.write();
+        *statements = caches
+            .into_iter()
+            .map(|cache| (cache.name, cache.statement))
+            .collect();
+        find_cache(&statements, name)
+            .cloned()
+            .ok_or_else(|| CacheReadError::CacheNotFound(name.to_owned()))
+    }
+
+    fn forget_statement(&self, name: &str) {
+        self.statements
+            .write()
+            .retain(|cache, _| !cache_name_matches(cache, name));
+    }
+}
+
+fn cache_name_matches(cache: &Relation, name: &str) -> bool {
+    match name.split_once('.') {
+        Some((schema, name)) => {
+            cache.schema.as_ref().is_some_and(|s| s.as_str() == schema) && cache.name == name
+        }
+        None => cache.name == name,
+    }
+}
+
+fn find_cache<'a>(
+    statements: &'a HashMap<Relation, SelectStatement>,
+    name: &str,
+) -> Option<&'a SelectStatement> {
+    statements
+        .iter()
+        .find(|(cache, _)| cache_name_matches(cache, name))
+        .map(|(_, statement)| statement)
+}
+
+/// Read a request body, refusing bodies larger than [`MAX_BODY_BYTES`]
+async fn read_body(mut body: Body) -> Result<Vec<u8>, CacheReadError> {
+    if body
+        .size_hint()
+        .upper()
+        .is_some_and(|len| len > MAX_BODY_BYTES as u64)
+    {
+        return Err(CacheReadError::PayloadTooLarge);
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| CacheReadError::BadRequest(e.to_string()))?;
+        if bytes.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err(CacheReadError::PayloadTooLarge);
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    Ok(bytes)
+}
+
+/// Parse the username and password out of the value of an HTTP basic `Authorization` header
+fn parse_basic_auth(header: &str) -> Option<(String, String)> {
+    let (scheme, credentials) = header.split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let credentials = String::from_utf8(BASE64.decode(credentials.trim()).ok()?).ok()?;
+    let (user, password) = credentials.split_once(':')?;
+    Some((user.to_owned(), password.to_owned()))
+}
+
+/// Read the values of a cache's query parameters from the repeated `key` parameter of a query
+/// string, as text
+fn query_string_params(query: &str) -> Vec<DfValue> {
+    url::form_urlencoded::parse(query.as_bytes())
+        .filter(|(param, _)| param == KEY_PARAM)
+        .map(|(_, value)| DfValue::from(value.as_ref()))
+        .collect()
+}
+
+/// Read the values of a cache's query parameters from a JSON array
+fn json_params(body: &[u8]) -> Result<Vec<DfValue>, CacheReadError> {
+    let values: Vec<JsonValue> = serde_json::from_slice(body).map_err(|e| {
+        CacheReadError::BadRequest(format!("Expected a JSON array of parameters: {e}"))
+    })?;
+    values
+        .into_iter()
+        .map(|value| match value {
+            JsonValue::Null => Ok(DfValue::None),
+            JsonValue::Bool(b) => Ok(DfValue::from(b)),
+            JsonValue::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    Ok(DfValue::from(i))
+                } else if let Some(u) = n.as_u64() {
+                    Ok(DfValue::from(u))
+                } else {
+                    DfValue::try_from(n.as_f64().unwrap_or(f64::NAN))
+                        .map_err(|e| CacheReadError::BadRequest(e.to_string()))
+                }
+            }
+            JsonValue::String(s) => Ok(DfValue::from(s)),
+            value => Ok(DfValue::from(&value)),
+        })
+        .collect()
+}
+
+/// Coerce the parameters given by the client to the types of the cache's query parameters
+fn coerce_params(
+    params: Vec<DfValue>,
+    param_types: &[ColumnSchema],
+) -> Result<Vec<DfValue>, CacheReadError> {
+    if params.len() != param_types.len() {
+        return Err(CacheReadError::BadRequest(format!(
+            "Expected {} parameters, got {}",
+            param_types.len(),
+            params.len()
+        )));
+    }
+    params
+        .into_iter()
+        .zip(param_types)
+        .map(|(value, param)| {
+            value
+                .coerce_to(&param.column_type, &value.infer_dataflow_type())
+                .map_err(|e| CacheReadError::BadRequest(e.to_string()))
+        })
+        .collect()
+}
+
+/// Convert the rows read from a cache to JSON, as an object with the names of the columns and an
+/// array of rows
+fn rows_to_json<I>(rows: I, schema: &[ColumnSchema]) -> JsonValue
+where
+    I: Iterator<Item = Vec<DfValue>>,
+{
+    let columns = schema
+        .iter()
+        .map(|col| JsonValue::from(col.column.name.as_str()))
+        .collect::<Vec<_>>();
+    let rows = rows
+        .map(|row| {
+            row.iter()
+                .zip(schema)
+                .map(|(value, col)| value_to_json(value, &col.column_type))
+                .collect::<JsonValue>()
+        })
+        .collect::<Vec<_>>();
+    json!({ "columns": columns, "rows": rows })
+}
+
+/// Convert a value read from a column of type `ty` to JSON. Values JSON can't represent exactly,
+/// such as decimals, timestamps, and non-finite floats, become strings, and byte arrays become
+/// hex strings.
+fn value_to_json(value: &DfValue, ty: &DfType) -> JsonValue {
+    match value {
+        DfValue::None => JsonValue::Null,
+        _ if ty.is_bool() => JsonValue::Bool(value.is_truthy()),
+        DfValue::Int(i) => JsonValue::from(*i),
+        DfValue::UnsignedInt(u) => JsonValue::from(*u),
+        DfValue::Float(f) if f.is_finite() => JsonValue::from(*f as f64),
+        DfValue::Double(f) if f.is_finite() => JsonValue::from(*f),
+        DfValue::Text(_) | DfValue::TinyText(_) if ty.is_any_json() => value
+            .to_json()
+            .unwrap_or_else(|_| JsonValue::from(value.to_string())),
+        DfValue::ByteArray(bytes) => JsonValue::from(
+            bytes
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>(),
+        ),
+        DfValue::Array(array) if array.num_dimensions() == 1 => {
+            let element_ty = match ty {
+                DfType::Array(element_ty) => &**element_ty,
+                _ => &DfType::Unknown,
+            };
+            array
+                .values()
+                .map(|value| value_to_json(value, element_ty))
+                .collect()
+        }
+        _ => JsonValue::from(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::Column;
+
+    use super::*;
+
+    fn column(name: &str, column_type: DfType) -> ColumnSchema {
+        ColumnSchema {
+            column: Column {
+                name: name.into(),
+                table: None,
+            },
+            column_type,
+            base: None,
+        }
+    }
+
+    #[test]
+    fn basic_auth() {
+        assert_eq!(
+            parse_basic_auth("Basic YWxpY2U6aHVudGVyMjpiYW5hbmE="),
+            Some(("alice".to_owned(), "hunter2:banana".to_owned()))
+        );
+        assert_eq!(parse_basic_auth("Bearer YWxpY2U6aHVudGVyMg=="), None);
+        assert_eq!(parse_basic_auth("Basic not-base64"), None);
+    }
+
+    #[test]
+    fn cache_names() {
+        let cache = Relation {
+            schema: Some("public".into()),
+            name: "q1".into(),
+        };
+        assert!(cache_name_matches(&cache, "q1"));
+        assert!(cache_name_matches(&cache, "public.q1"));
+        assert!(!cache_name_matches(&cache, "other.q1"));
+        assert!(!cache_name_matches(&cache, "q2"));
+    }
+
+    #[tokio::test]
+    async fn body_size_limited() {
+        assert_eq!(read_body(Body::from("[1]")).await.unwrap(), b"[1]".to_vec());
+        assert!(matches!(
+            read_body(Body::from(vec![b' '; MAX_BODY_BYTES + 1])).await,
+            Err(CacheReadError::PayloadTooLarge)
+        ));
+
+        let (mut sender, body) = Body::channel();
+        tokio::spawn(async move {
+            for _ in 0..=MAX_BODY_BYTES / 1024 {
+                if sender.send_data(vec![b' '; 1024].into()).await.is_err() {
+                    break;
+                }
+            }
+        });
+        assert!(matches!(
+            read_body(body).await,
+            Err(CacheReadError::PayloadTooLarge)
+        ));
+    }
+
+    #[test]
+    fn query_string_keys() {
+        assert_eq!(
+            query_string_params("key=1&other=x&key=hello%20world"),
+            vec![DfValue::from("1"), DfValue::from("hello world")]
+        );
+    }
+
+    #[test]
+    fn json_keys() {
+        assert_eq!(
+            json_params(br#"[1, "a", null, true]"#).unwrap(),
+            vec![
+                DfValue::from(1i64),
+                DfValue::from("a"),
+                DfValue::None,
+                DfValue::from(true)
+            ]
+        );
+        assert!(json_params(br#"{"key": 1}"#).is_err());
+    }
+
+    #[test]
+    fn params_coerced_to_parameter_types() {
+        let params =
+            coerce_params(vec![DfValue::from("42")], &[column("id", DfType::BigInt)]).unwrap();
+        assert_eq!(params, vec![DfValue::from(42i64)]);
+        assert!(coerce_params(vec![], &[column("id", DfType::BigInt)]).is_err());
+    }
+
+    #[test]
+    fn rows_typed_by_schema() {
+        let schema = [
+            column("id", DfType::BigInt),
+            column("name", DfType::DEFAULT_TEXT),
+            column("active", DfType::Bool),
+            column("doc", DfType::Jsonb),
+            column("tags", DfType::Array(Box::new(DfType::DEFAULT_TEXT))),
+        ];
+        let rows = vec![vec![
+            DfValue::from(1i64),
+            DfValue::from("alice"),
+            DfValue::from(true),
+            DfValue::from(r#"{"a": [1, 2]}"#),
+            DfValue::from(vec![DfValue::from("x"), DfValue::None]),
+        ]];
+        assert_eq!(
+            rows_to_json(rows.into_iter(), &schema),
+            json!({
+                "columns": ["id", "name", "active", "doc", "tags"],
+                "rows": [[1, "alice", true, {"a": [1, 2]}, ["x", null]]],
+            })
+        );
+    }
+}
@@ -21,6 +21,7 @@ use tokio::sync::mpsc::Sender;
 use tokio_stream::wrappers::TcpListenerStream;
 use tower::Service;
 
+use crate::http_cache_reads::{HttpCacheReads, CACHES_PATH};
 use crate::status_reporter::ReadySetStatusReporter;
 use crate::UpstreamDatabase;
 
@@ -49,6 +50,9 @@ where
     pub metrics: HttpRouterMetrics,
 
     pub status_reporter: ReadySetStatusReporter<U>,
+
+    /// Used to serve reads from caches on /caches/<name>, if enabled.
+    pub cache_reads: Option<Arc<HttpCacheReads>>,
 }
 
 // For some reason, the default implementation, which should match this, isn't compiling.
@@ -64,6 +68,7 @@ where
             prometheus_handle: self.prometheus_handle.clone(),
             metrics: self.metrics.clone(),
             status_reporter: self.status_reporter.clone(),
+            cache_reads: self.cache_reads.clone(),
         }
     }
 }
@@ -177,6 +182,43 @@ where
     ///
     ///   This endpoint is intended to be scraped by Prometheus. For almost all cases you want to
     /// query Prometheus directly to get metrics data.
+    ///
+    /// ## Cache Reads
+    ///
+    /// Read the rows of a cache for the given values of its query's parameters, as JSON. Requests
+    /// are authenticated with HTTP basic authentication, using the same credentials as SQL
+    /// clients.
+    ///
+    /// * **URL**
+    ///
+    ///   `/caches/<name>`, where `<name>` may be qualified with a schema
+    ///
+    /// * **Method:**
+    ///
+    ///   `GET`, with the parameter values as repeated `key` query string parameters, in order
+    ///
+    ///   OR
+    ///
+    ///   `POST`, with the parameter values as a JSON array in the body
+    ///
+    /// * **Success Response:**
+    ///
+    ///     * **Code:** 200 <br /> **Content:** `{ "columns": [...], "rows": [[...], ...] }`
+    ///
+    /// * **Error Response:**
+    ///
+    ///     * **Code:** 400 Bad Request, if the parameters don't match the cache's query <br />
+    ///     * **Code:** 401 Unauthorized, if the credentials are missing or wrong <br />
+    ///     * **Code:** 403 Forbidden, if upstream privileges are enforced and the user may not read
+    ///       the cache's tables <br />
+    ///     * **Code:** 404 Not Found, if there is no such cache, or the adapter is run without
+    ///       `--http-cache-reads` <br />
+    ///     * **Code:** 503 Service Unavailable, if non-blocking reads are enabled and the key isn't
+    ///       in the cache yet
+    ///
+    /// * **Sample Call:**
+    ///
+    ///   `curl -u <user>:<password> -X GET '<adapter>:<adapter-port>/caches/q1?key=1'`
     fn call(&mut self, req: Request<Body>) -> Self::Future {
         let res = Response::builder()
             // disable CORS to allow use as API server
@@ -292,6 +334,22 @@ where
 
                 Box::pin(async move { Ok(res.unwrap()) })
             }
+            (&Method::GET | &Method::POST, path) if path.starts_with(CACHES_PATH) => {
+                match self.cache_reads.clone() {
+                    Some(cache_reads) => {
+                        Box::pin(async move { Ok(cache_reads.handle(req, res).await) })
+                    }
+                    None => {
+                        let res = res.status(404).header(CONTENT_TYPE, "text/plain").body(
+                            hyper::Body::from(
+                                "Cache reads over HTTP were not enabled. To fix this, run the \
+                                 adapter with --http-cache-reads",
+                            ),
+                        );
+                        Box::pin(async move { Ok(res.unwrap()) })
+                    }
+                }
+            }
             _ => Box::pin(async move {
                 let res = res
                     .status(404)
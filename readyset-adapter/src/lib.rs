@@ -6,14 +6,23 @@
 #![feature(if_let_guard)]
 #![deny(unreachable_pub)]
 pub mod backend;
+pub mod cache_staleness;
+#[cfg(test)]
+mod fake_upstream;
+pub mod http_cache_reads;
 pub mod http_router;
 pub mod metrics_handle;
 pub mod migration_handler;
+pub mod privileges;
 pub mod proxied_queries_reporter;
+pub mod query_hints;
 mod query_handler;
 pub mod query_status_cache;
 mod status_reporter;
+pub mod upstream_auth;
 pub mod upstream_database;
+pub mod upstream_pool;
+pub mod upstream_replicas;
 mod utils;
 pub mod views_synchronizer;
 
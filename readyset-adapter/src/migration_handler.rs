@@ -180,6 +180,8 @@ impl MigrationHandler {
                         Some(query.query().schema_search_path.clone()),
                         /* always */ false,
                         /* concurrently */ false,
+                        /* replicas */ None,
+                        /* shards */ None,
                     )
                     .await;
                 // Inform the query status cache of completed migrations
@@ -302,6 +304,8 @@ impl MigrationHandler {
                 Some(view_request.schema_search_path.clone()),
                 false,
                 false,
+                None,
+                None,
             )
             .await?;
         Ok(())
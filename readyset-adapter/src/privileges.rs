@@ -0,0 +1,667 @@
+//! Enforcement of upstream table privileges on reads served from caches.
+//!
+//! Caches hold the results of queries run against the upstream database's tables, with no notion
+//! of who is allowed to read those tables. When clients are authenticated against the upstream
+//! database (see [`crate::upstream_auth`]), each connection knows which upstream user it belongs
+//! to, and the adapter can refuse to serve cached results to users who couldn't have run the same
+//! query upstream.
+//!
+//! [`TablePrivileges`] holds a copy of the upstream's `SELECT` grants, which is kept up to date by
+//! [`TablePrivileges::run`] periodically reading them from the upstream's catalog. Reads from
+//! users who may not `SELECT` from every table a query refers to are proxied to the upstream,
+//! which then returns whatever permission error it would have returned without ReadySet.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use database_utils::{DatabaseConnection, DatabaseError, DatabaseURL, QueryableConnection};
+use nom_sql::analysis::visit::{self, Visitor};
+use nom_sql::{
+    CommonTableExpr, Dialect, Relation, SelectStatement, SqlIdentifier, TableExpr, TableExprInner,
+};
+use parking_lot::RwLock;
+use readyset_data::DfValue;
+use readyset_util::shutdown::ShutdownReceiver;
+use tokio::select;
+use tracing::{debug, info, warn};
+
+/// Lists every (non-system) table in a PostgreSQL database
+const POSTGRES_TABLES: &str = "SELECT n.nspname::text, c.relname::text \
+     FROM pg_catalog.pg_class c \
+     JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+     WHERE c.relkind IN ('r', 'v', 'm', 'p', 'f') \
+     AND n.nspname NOT IN ('pg_catalog', 'information_schema')";
+
+/// Lists every table each login role may `SELECT` from in a PostgreSQL database.
+///
+/// `has_table_privilege` takes care of grants to `PUBLIC`, privileges inherited through role
+/// membership, and superusers. Roles that may only select some of a table's columns don't have
+/// the privilege on the table as a whole, so their reads are always proxied.
+const POSTGRES_GRANTS: &str = "SELECT r.rolname::text, n.nspname::text, c.relname::text \
+     FROM pg_catalog.pg_class c \
+     JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace \
+     CROSS JOIN pg_catalog.pg_roles r \
+     WHERE r.rolcanlogin \
+     AND c.relkind IN ('r', 'v', 'm', 'p', 'f') \
+     AND n.nspname NOT IN ('pg_catalog', 'information_schema') \
+     AND has_schema_privilege(r.oid, n.oid, 'USAGE') \
+     AND has_table_privilege(r.oid, c.oid, 'SELECT')";
+
+/// Lists every (non-system) table in a MySQL database
+const MYSQL_TABLES: &str = "SELECT TABLE_SCHEMA, TABLE_NAME FROM information_schema.tables \
+     WHERE TABLE_SCHEMA NOT IN ('mysql', 'information_schema', 'performance_schema', 'sys')";
+
+/// Lists the users with a global `SELECT` privilege in a MySQL database
+const MYSQL_GLOBAL_GRANTS: &str = "SELECT GRANTEE FROM information_schema.user_privileges \
+     WHERE PRIVILEGE_TYPE = 'SELECT'";
+
+/// Lists the schemas each user may `SELECT` from in a MySQL database
+const MYSQL_SCHEMA_GRANTS: &str = "SELECT GRANTEE, TABLE_SCHEMA \
+     FROM information_schema.schema_privileges WHERE PRIVILEGE_TYPE = 'SELECT'";
+
+/// Lists the tables each user may `SELECT` from in a MySQL database
+const MYSQL_TABLE_GRANTS: &str = "SELECT GRANTEE, TABLE_SCHEMA, TABLE_NAME \
+     FROM information_schema.table_privileges WHERE PRIVILEGE_TYPE = 'SELECT'";
+
+/// Returns a row if the MySQL database supports roles, which were added in MySQL 8.0
+const MYSQL_ROLES_SUPPORTED: &str = "SELECT TABLE_NAME FROM information_schema.tables \
+     WHERE TABLE_SCHEMA = 'mysql' AND TABLE_NAME = 'role_edges'";
+
+/// Lists the roles granted to each user or role in a MySQL database
+const MYSQL_ROLE_EDGES: &str = "SELECT FROM_USER, TO_USER FROM mysql.role_edges";
+
+/// Lists the default roles of each user in a MySQL database, which are activated when it logs in
+const MYSQL_DEFAULT_ROLES: &str = "SELECT DEFAULT_ROLE_USER, USER FROM mysql.default_roles";
+
+/// Returns whether a MySQL database activates all of a user's granted roles when it logs in
+/// (rather than just its default roles), and the roles that are granted to every user
+const MYSQL_ROLE_SETTINGS: &str = "SELECT \
+     CAST(@@GLOBAL.activate_all_roles_on_login AS CHAR), \
+     CAST(@@GLOBAL.mandatory_roles AS CHAR)";
+
+/// Lists every user in a MySQL database
+const MYSQL_USERS: &str = "SELECT DISTINCT User FROM mysql.user";
+
+/// The tables a single upstream user may `SELECT` from
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct UserGrants {
+    /// The user may select from every table
+    all_tables: bool,
+    /// The user may select from every table in these schemas
+    schemas: HashSet<SqlIdentifier>,
+    /// The user may select from these tables
+    tables: HashSet<Relation>,
+}
+
+impl UserGrants {
+    fn extend(&mut self, other: &UserGrants) {
+        self.all_tables |= other.all_tables;
+        self.schemas.extend(other.schemas.iter().cloned());
+        self.tables.extend(other.tables.iter().cloned());
+    }
+
+    fn can_select(&self, table: &Relation) -> bool {
+        self.all_tables
+            || table
+                .schema
+                .as_ref()
+                .is_some_and(|schema| self.schemas.contains(schema))
+            || self.tables.contains(table)
+    }
+}
+
+/// A snapshot of the `SELECT` privileges granted in the upstream database
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Grants {
+    /// Every table in the upstream database, used to resolve unqualified table names
+    tables: HashSet<Relation>,
+    /// The privileges granted to each user, by username
+    users: HashMap<String, UserGrants>,
+}
+
+impl Grants {
+    /// Read the `SELECT` privileges granted to every user from the upstream database's catalog.
+    ///
+    /// In MySQL, the `information_schema` privilege tables only list privileges visible to the
+    /// connected user, so `conn` should be connected as a user with `SELECT` on the `mysql`
+    /// schema. Users are identified by username alone; privileges granted to the same username
+    /// for different hosts are combined.
+    ///
+    /// In MySQL 8.0 and later, users also have the privileges of the roles active when they log
+    /// in: their default roles, or every role granted to them (including `mandatory_roles`) if
+    /// `activate_all_roles_on_login` is enabled, along with the roles granted to those roles in
+    /// turn. Roles activated or deactivated later with `SET ROLE` aren't taken into account.
+    pub async fn load(conn: &mut DatabaseConnection) -> Result<Self, DatabaseError> {
+        let mut grants = Self::default();
+        match conn.dialect() {
+            Dialect::PostgreSQL => {
+                for row in query(conn, POSTGRES_TABLES).await? {
+                    if let [schema, name] = &row[..] {
+                        grants.tables.insert(Relation {
+                            schema: Some(schema.into()),
+                            name: name.into(),
+                        });
+                    }
+                }
+                for row in query(conn, POSTGRES_GRANTS).await? {
+                    if let [user, schema, name] = &row[..] {
+                        grants.user(user).tables.insert(Relation {
+                            schema: Some(schema.into()),
+                            name: name.into(),
+                        });
+                    }
+                }
+            }
+            Dialect::MySQL => {
+                for row in query(conn, MYSQL_TABLES).await? {
+                    if let [schema, name] = &row[..] {
+                        grants.tables.insert(Relation {
+                            schema: Some(schema.into()),
+                            name: name.into(),
+                        });
+                    }
+                }
+                for row in query(conn, MYSQL_GLOBAL_GRANTS).await? {
+                    if let [grantee] = &row[..] {
+                        grants.user(mysql_grantee_user(grantee)).all_tables = true;
+                    }
+                }
+                for row in query(conn, MYSQL_SCHEMA_GRANTS).await? {
+                    if let [grantee, schema] = &row[..] {
+                        // Schema-level grants may name schemas with a `LIKE` pattern; we only
+                        // understand escaped literals, so grants on a pattern match nothing
+                        grants
+                            .user(mysql_grantee_user(grantee))
+                            .schemas
+                            .insert(schema.replace("\\_", "_").replace("\\%", "%").into());
+                    }
+                }
+                for row in query(conn, MYSQL_TABLE_GRANTS).await? {
+                    if let [grantee, schema, name] = &row[..] {
+                        grants
+                            .user(mysql_grantee_user(grantee))
+                            .tables
+                            .insert(Relation {
+                                schema: Some(schema.into()),
+                                name: name.into(),
+                            });
+                    }
+                }
+                if !query(conn, MYSQL_ROLES_SUPPORTED).await?.is_empty() {
+                    grants.load_mysql_roles(conn).await?;
+                }
+            }
+        }
+        Ok(grants)
+    }
+
+    /// Give each user the privileges of the roles active when it logs in to a MySQL database
+    async fn load_mysql_roles(
+        &mut self,
+        conn: &mut DatabaseConnection,
+    ) -> Result<(), DatabaseError> {
+        let mut granted = HashMap::<String, HashSet<String>>::new();
+        for row in query(conn, MYSQL_ROLE_EDGES).await? {
+            if let [role, grantee] = &row[..] {
+                granted
+                    .entry(grantee.clone())
+                    .or_default()
+                    .insert(role.clone());
+            }
+        }
+
+        let settings = query(conn, MYSQL_ROLE_SETTINGS).await?;
+        let (activate_all, mandatory_roles) = match settings.first().map(|row| &row[..]) {
+            Some([activate_all, mandatory_roles]) => {
+                (activate_all == "1", mysql_role_list(mandatory_roles))
+            }
+            _ => (false, vec![]),
+        };
+
+        let mut active = HashMap::<String, HashSet<String>>::new();
+        if activate_all {
+            if !mandatory_roles.is_empty() {
+                for row in query(conn, MYSQL_USERS).await? {
+                    if let [user] = &row[..] {
+                        active
+                            .entry(user.clone())
+                            .or_default()
+                            .extend(mandatory_roles.iter().map(|role| (*role).to_owned()));
+                    }
+                }
+            }
+            for (user, roles) in &granted {
+                active
+                    .entry(user.clone())
+                    .or_default()
+                    .extend(roles.iter().cloned());
+            }
+        } else {
+            for row in query(conn, MYSQL_DEFAULT_ROLES).await? {
+                if let [role, user] = &row[..] {
+                    active.entry(user.clone()).or_default().insert(role.clone());
+                }
+            }
+        }
+
+        self.apply_roles(&granted, &active);
+        Ok(())
+    }
+
+    /// Give each user in `active` the privileges of the roles it maps to, and of the roles granted
+    /// to those roles in turn according to `granted`, which maps each user or role to the roles
+    /// granted to it
+    fn apply_roles(
+        &mut self,
+        granted: &HashMap<String, HashSet<String>>,
+        active: &HashMap<String, HashSet<String>>,
+    ) {
+        // Resolve every user's roles before giving any user its roles' privileges, so that a role
+        // which can also log in as a user doesn't pass on the privileges of its own active roles
+        let inherited = active
+            .iter()
+            .map(|(user, roles)| {
+                let mut inherited = UserGrants::default();
+                let mut seen = HashSet::new();
+                let mut to_visit = roles.iter().collect::<Vec<_>>();
+                while let Some(role) = to_visit.pop() {
+                    if !seen.insert(role) {
+                        continue;
+                    }
+                    if let Some(role_grants) = self.users.get(role) {
+                        inherited.extend(role_grants);
+                    }
+                    to_visit.extend(granted.get(role).into_iter().flatten());
+                }
+                (user, inherited)
+            })
+            .collect::<Vec<_>>();
+
+        for (user, inherited) in inherited {
+            self.user(user).extend(&inherited);
+        }
+    }
+
+    fn user(&mut self, user: &str) -> &mut UserGrants {
+        self.users.entry(user.to_owned()).or_default()
+    }
+
+    /// Resolve the given table name the same way the upstream would, by looking for an
+    /// unqualified table in each schema of the search path in turn. Returns `None` if no such
+    /// table exists upstream.
+    fn resolve(&self, table: &Relation, schema_search_path: &[SqlIdentifier]) -> Option<Relation> {
+        if table.schema.is_some() {
+            return Some(table.clone());
+        }
+        schema_search_path
+            .iter()
+            .map(|schema| Relation {
+                schema: Some(schema.clone()),
+                name: table.name.clone(),
+            })
+            .find(|table| self.tables.contains(table))
+    }
+
+    /// Returns true if `user` may select from every one of `tables`
+    fn can_select_all(
+        &self,
+        user: &str,
+        tables: &HashSet<Relation>,
+        schema_search_path: &[SqlIdentifier],
+    ) -> bool {
+        let Some(grants) = self.users.get(user) else {
+            return tables.is_empty();
+        };
+        tables.iter().all(|table| {
+            self.resolve(table, schema_search_path)
+                .is_some_and(|table| grants.can_select(&table))
+        })
+    }
+}
+
+/// Run `q`, and convert every value in the result to a string
+async fn query(conn: &mut DatabaseConnection, q: &str) -> Result<Vec<Vec<String>>, DatabaseError> {
+    Vec::<Vec<DfValue>>::try_from(conn.query(q).await?)?
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|v| String::try_from(v).map_err(|e| DatabaseError::ValueConversion(e.into())))
+                .collect()
+        })
+        .collect()
+}
+
+/// Extract the username from a MySQL grantee, which is formatted as `'user'@'host'`
+fn mysql_grantee_user(grantee: &str) -> &str {
+    let user = grantee.rsplit_once('@').map_or(grantee, |(user, _)| user);
+    user.strip_prefix('\'')
+        .and_then(|u| u.strip_suffix('\''))
+        .unwrap_or(user)
+}
+
+/// Extract the role names from the value of MySQL's `mandatory_roles` variable, which is a
+/// comma-separated list of roles formatted as `role`, `role@host`, `'role'@'host'` or
+/// `` `role`@`host` ``
+fn mysql_role_list(roles: &str) -> Vec<&str> {
+    roles
+        .split(',')
+        .map(|role| {
+            let role = role.trim();
+            let user = role.rsplit_once('@').map_or(role, |(user, _)| user);
+            user.trim_matches(|c| c == '\'' || c == '`')
+        })
+        .filter(|role| !role.is_empty())
+        .collect()
+}
+
+/// Collects the tables a query reads from, skipping references to its own common table
+/// expressions
+#[derive(Default)]
+struct ReferencedTables<'ast> {
+    tables: HashSet<Relation>,
+    ctes: HashSet<&'ast SqlIdentifier>,
+}
+
+impl<'ast> Visitor<'ast> for ReferencedTables<'ast> {
+    type Error = !;
+
+    fn visit_common_table_expr(&mut self, cte: &'ast CommonTableExpr) -> Result<(), Self::Error> {
+        self.ctes.insert(&cte.name);
+        visit::walk_common_table_expr(self, cte)
+    }
+
+    fn visit_table_expr(&mut self, table_expr: &'ast TableExpr) -> Result<(), Self::Error> {
+        if let TableExprInner::Table(table) = &table_expr.inner {
+            if table.schema.is_some() || !self.ctes.contains(&table.name) {
+                self.tables.insert(table.clone());
+            }
+        }
+        visit::walk_table_expr(self, table_expr)
+    }
+}
+
+fn referenced_tables(statement: &SelectStatement) -> HashSet<Relation> {
+    let mut visitor = ReferencedTables::default();
+    let Ok(()) = visitor.visit_select_statement(statement);
+    visitor.tables
+}
+
+/// A handle to the upstream's `SELECT` privileges, shared by all connections to the adapter.
+///
+/// Until the privileges have been loaded from the upstream for the first time, no user is
+/// allowed to read from caches.
+#[derive(Debug, Default, Clone)]
+pub struct TablePrivileges {
+    grants: Arc<RwLock<Option<Grants>>>,
+}
+
+impl TablePrivileges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the privileges with a newly loaded snapshot
+    pub fn set(&self, grants: Grants) {
+        *self.grants.write() = Some(grants);
+    }
+
+    /// Returns true if `user` may `SELECT` from every table referenced by `statement`, with
+    /// unqualified table names resolved using `schema_search_path`
+    pub fn can_read(
+        &self,
+        user: &str,
+        statement: &SelectStatement,
+        schema_search_path: &[SqlIdentifier],
+    ) -> bool {
+        self.grants.read().as_ref().is_some_and(|grants| {
+            grants.can_select_all(user, &referenced_tables(statement), schema_search_path)
+        })
+    }
+
+    /// Returns the (sorted) names of the users who may `SELECT` from every table referenced by
+    /// `statement`
+    pub fn readers(
+        &self,
+        statement: &SelectStatement,
+        schema_search_path: &[SqlIdentifier],
+    ) -> Vec<String> {
+        let grants = self.grants.read();
+        let Some(grants) = grants.as_ref() else {
+            return vec![];
+        };
+        let tables = referenced_tables(statement);
+        let mut readers = grants
+            .users
+            .keys()
+            .filter(|user| grants.can_select_all(user, &tables, schema_search_path))
+            .cloned()
+            .collect::<Vec<_>>();
+        readers.sort();
+        readers
+    }
+
+    /// Reload the privileges from the upstream database at `upstream_url` every `interval`, until
+    /// a shutdown signal is received. If loading fails, the last successfully loaded privileges
+    /// stay in place until the next attempt.
+    pub async fn run(
+        self,
+        upstream_url: DatabaseURL,
+        interval: Duration,
+        mut shutdown_recv: ShutdownReceiver,
+    ) {
+        let mut interval = tokio::time::interval(interval);
+        let fut = async {
+            let mut conn = None;
+            loop {
+                interval.tick().await;
+                if conn.is_none() {
+                    match upstream_url.connect(None).await {
+                        Ok(c) => conn = Some(c),
+                        Err(error) => {
+                            warn!(%error, "Could not connect to upstream to load table privileges");
+                            continue;
+                        }
+                    }
+                }
+                let Some(c) = conn.as_mut() else {
+                    continue;
+                };
+                match Grants::load(c).await {
+                    Ok(grants) => {
+                        debug!(
+                            users = grants.users.len(),
+                            "Loaded upstream table privileges"
+                        );
+                        self.set(grants);
+                    }
+                    Err(error) => {
+                        warn!(%error, "Could not load upstream table privileges");
+                        conn = None;
+                    }
+                }
+            }
+        };
+        select! {
+            biased;
+            _ = shutdown_recv.recv() => {
+                info!("Table privileges loader shutting down after shut down signal received");
+            }
+            _ = fut => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::parse_select_statement;
+
+    use super::*;
+
+    fn relation(schema: &str, name: &str) -> Relation {
+        Relation {
+            schema: Some(schema.into()),
+            name: name.into(),
+        }
+    }
+
+    fn privileges() -> TablePrivileges {
+        let privileges = TablePrivileges::new();
+        privileges.set(Grants {
+            tables: HashSet::from([
+                relation("public", "t1"),
+                relation("public", "t2"),
+                relation("other", "t2"),
+            ]),
+            users: HashMap::from([
+                (
+                    "alice".to_owned(),
+                    UserGrants {
+                        tables: HashSet::from([relation("public", "t1")]),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "bob".to_owned(),
+                    UserGrants {
+                        schemas: HashSet::from(["public".into()]),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "carol".to_owned(),
+                    UserGrants {
+                        all_tables: true,
+                        ..Default::default()
+                    },
+                ),
+            ]),
+        });
+        privileges
+    }
+
+    fn can_read(privileges: &TablePrivileges, user: &str, query: &str) -> bool {
+        let statement = parse_select_statement(Dialect::PostgreSQL, query).unwrap();
+        privileges.can_read(user, &statement, &["public".into()])
+    }
+
+    #[test]
+    fn nothing_readable_before_load() {
+        let privileges = TablePrivileges::new();
+        let statement = parse_select_statement(Dialect::PostgreSQL, "SELECT * FROM t1").unwrap();
+        assert!(!privileges.can_read("alice", &statement, &["public".into()]));
+    }
+
+    #[test]
+    fn table_grants() {
+        let privileges = privileges();
+        assert!(can_read(&privileges, "alice", "SELECT * FROM t1"));
+        assert!(can_read(&privileges, "alice", "SELECT * FROM public.t1"));
+        assert!(!can_read(&privileges, "alice", "SELECT * FROM t2"));
+        assert!(!can_read(
+            &privileges,
+            "alice",
+            "SELECT * FROM t1 JOIN t2 ON t1.x = t2.x"
+        ));
+        assert!(!can_read(
+            &privileges,
+            "alice",
+            "SELECT * FROM t1 WHERE x IN (SELECT x FROM t2)"
+        ));
+        assert!(!can_read(&privileges, "dave", "SELECT * FROM t1"));
+    }
+
+    #[test]
+    fn schema_and_global_grants() {
+        let privileges = privileges();
+        assert!(can_read(&privileges, "bob", "SELECT * FROM t2"));
+        assert!(!can_read(&privileges, "bob", "SELECT * FROM other.t2"));
+        assert!(can_read(&privileges, "carol", "SELECT * FROM other.t2"));
+        assert!(!can_read(&privileges, "carol", "SELECT * FROM missing"));
+    }
+
+    #[test]
+    fn ctes_are_not_tables() {
+        let privileges = privileges();
+        assert!(can_read(
+            &privileges,
+            "alice",
+            "WITH t2 AS (SELECT * FROM t1) SELECT * FROM t2"
+        ));
+    }
+
+    #[test]
+    fn readers() {
+        let privileges = privileges();
+        let statement = parse_select_statement(
+            Dialect::PostgreSQL,
+            "SELECT * FROM t1 JOIN t2 ON t1.x = t2.x",
+        )
+        .unwrap();
+        assert_eq!(
+            privileges.readers(&statement, &["public".into()]),
+            vec!["bob".to_owned(), "carol".to_owned()]
+        );
+    }
+
+    #[test]
+    fn roles() {
+        let mut grants = Grants {
+            tables: HashSet::from([relation("db", "t1"), relation("db", "t2")]),
+            users: HashMap::from([
+                (
+                    "reader".to_owned(),
+                    UserGrants {
+                        tables: HashSet::from([relation("db", "t1")]),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "nested".to_owned(),
+                    UserGrants {
+                        tables: HashSet::from([relation("db", "t2")]),
+                        ..Default::default()
+                    },
+                ),
+                (
+                    "inactive".to_owned(),
+                    UserGrants {
+                        all_tables: true,
+                        ..Default::default()
+                    },
+                ),
+            ]),
+        };
+        let granted = HashMap::from([
+            (
+                "alice".to_owned(),
+                HashSet::from(["reader".to_owned(), "inactive".to_owned()]),
+            ),
+            ("reader".to_owned(), HashSet::from(["nested".to_owned()])),
+            ("bob".to_owned(), HashSet::from(["inactive".to_owned()])),
+        ]);
+        let active = HashMap::from([("alice".to_owned(), HashSet::from(["reader".to_owned()]))]);
+        grants.apply_roles(&granted, &active);
+
+        let alice = &grants.users["alice"];
+        assert!(alice.can_select(&relation("db", "t1")));
+        assert!(alice.can_select(&relation("db", "t2")));
+        assert!(!alice.all_tables);
+        assert!(!grants.users.contains_key("bob"));
+    }
+
+    #[test]
+    fn parse_mysql_role_list() {
+        assert_eq!(
+            mysql_role_list("r1, r2@localhost,'r3'@'%',`r4`@`%`"),
+            vec!["r1", "r2", "r3", "r4"]
+        );
+        assert!(mysql_role_list("").is_empty());
+    }
+
+    #[test]
+    fn parse_mysql_grantee() {
+        assert_eq!(mysql_grantee_user("'alice'@'%'"), "alice");
+        assert_eq!(mysql_grantee_user("'bob'@'localhost'"), "bob");
+    }
+}
@@ -0,0 +1,352 @@
+//! Per-statement routing hints, given in SQL comments.
+//!
+//! Clients can steer individual statements without changing how the adapter as a whole is
+//! configured, by including a comment starting with `readyset:` anywhere in the statement,
+//! followed by one or more hints separated by commas or whitespace:
+//!
+//! * `/* readyset:proxy */` always runs the statement against the upstream database
+//! * `/* readyset:cache-only */` only ever serves the statement from a cache, returning an error
+//!   instead of proxying it to the upstream database
+//! * `/* readyset:max_staleness=5s */` only serves the statement from a cache if the data in caches
+//!   is known to be at most that far behind the upstream database (see [`crate::cache_staleness`]),
+//!   and proxies it otherwise. Durations are given as a whole number with an optional unit of `ms`,
+//!   `s` (the default), `m`, or `h`. How stale caches are is only tracked if the adapter was
+//!   started with `--cache-staleness-tracking`; otherwise these statements are always proxied.
+//!
+//! Hints only change where reads are served from; every other kind of statement is run the same
+//! way with or without them. Hints which aren't recognized are ignored.
+//!
+//! The parser discards comments, so hints don't affect how statements are identified: the same
+//! query with different hints (or none) shares a single cache and entry in the
+//! [`QueryStatusCache`](crate::query_status_cache::QueryStatusCache).
+
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+use nom_sql::{tokenize, Dialect, TokenKind};
+use readyset_errors::ReadySetError;
+
+use crate::cache_staleness::CacheStaleness;
+
+/// The prefix that marks a comment as containing hints
+const HINT_PREFIX: &str = "readyset:";
+
+/// Where a hint asks for a statement to be run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintedDestination {
+    /// `readyset:proxy`
+    Upstream,
+    /// `readyset:cache-only`
+    Cache,
+}
+
+/// The hints given in the comments of a single statement
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct QueryHints {
+    /// Where the statement should be run, if a hint says. If several hints do, the last one wins.
+    pub destination: Option<HintedDestination>,
+    /// The maximum staleness of cached data the statement may be served from
+    pub max_staleness: Option<Duration>,
+}
+
+/// How a read with [`QueryHints`] should be routed, given the current staleness of caches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintedRoute {
+    /// Route the read the same way as if it had no hints
+    Default,
+    /// Run the read against the upstream database
+    Upstream,
+    /// Serve the read from a cache, or fail
+    CacheOnly,
+    /// Fail the read without running it, with the given error
+    Fail(ReadySetError),
+}
+
+/// The byte ranges of every block comment in `query`, including the `/*` and `*/`
+fn block_comments(dialect: Dialect, query: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    tokenize(dialect, query)
+        .filter(|token| token.kind == TokenKind::BlockComment)
+        .map(|token| (token.offset, token.offset + token.text.len()))
+}
+
+/// Returns the hints in the given comment (including its `/*` and `*/`), if it is a hint comment
+fn hint_text(comment: &str) -> Option<&str> {
+    let body = comment
+        .strip_prefix("/*")
+        .map(|c| c.strip_suffix("*/").unwrap_or(c))?
+        .trim();
+    body.get(..HINT_PREFIX.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(HINT_PREFIX))
+        .map(|_| &body[HINT_PREFIX.len()..])
+}
+
+/// Parse a duration such as `500ms`, `5s`, `5` (seconds), `2m` or `1h`
+fn parse_duration(s: &str) -> Option<Duration> {
+    let unit_start = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (amount, unit) = s.split_at(unit_start);
+    let amount = amount.parse::<u64>().ok()?;
+    match unit.to_ascii_lowercase().as_str() {
+        "ms" => Some(Duration::from_millis(amount)),
+        "" | "s" => Some(Duration::from_secs(amount)),
+        "m" => Some(Duration::from_secs(amount.checked_mul(60)?)),
+        "h" => Some(Duration::from_secs(amount.checked_mul(60 * 60)?)),
+        _ => None,
+    }
+}
+
+impl QueryHints {
+    /// Parse the hints out of every hint comment in `query`, which is in the given SQL dialect
+    pub fn parse(dialect: Dialect, query: &str) -> Self {
+        let mut hints = Self::default();
+        // Avoid scanning statements which can't contain any comments at all
+        if !query.contains("/*") {
+            return hints;
+        }
+        for (start, end) in block_comments(dialect, query) {
+            let Some(text) = hint_text(&query[start..end]) else {
+                continue;
+            };
+            for hint in text
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|h| !h.is_empty())
+            {
+                let (name, value) = hint.split_once('=').unwrap_or((hint, ""));
+                match name.to_ascii_lowercase().as_str() {
+                    "proxy" => hints.destination = Some(HintedDestination::Upstream),
+                    "cache-only" | "cache_only" => {
+                        hints.destination = Some(HintedDestination::Cache)
+                    }
+                    "max_staleness" | "max-staleness" => {
+                        if let Some(max_staleness) = parse_duration(value) {
+                            hints.max_staleness = Some(max_staleness);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        hints
+    }
+
+    /// Returns true if no hints were given
+    pub fn is_empty(&self) -> bool {
+        self.destination.is_none() && self.max_staleness.is_none()
+    }
+
+    /// Returns `query` with every hint comment removed, so that statements which only differ in
+    /// their hints are identical
+    pub fn strip(dialect: Dialect, query: &str) -> Cow<'_, str> {
+        if !query.contains("/*") {
+            return Cow::Borrowed(query);
+        }
+        let hint_comments = block_comments(dialect, query)
+            .filter(|(start, end)| hint_text(&query[*start..*end]).is_some())
+            .collect::<Vec<_>>();
+        if hint_comments.is_empty() {
+            return Cow::Borrowed(query);
+        }
+
+        let mut stripped = String::with_capacity(query.len());
+        let mut rest_start = 0;
+        for (start, end) in hint_comments {
+            stripped.push_str(&query[rest_start..start]);
+            rest_start = end;
+            // Collapse the whitespace around the comment, so that removing it leaves behind the
+            // same text as if it had never been there
+            let trimmed_len = stripped.trim_end().len();
+            stripped.truncate(trimmed_len);
+            let following = &query[rest_start..];
+            rest_start += following.len() - following.trim_start().len();
+            if !stripped.is_empty() && rest_start < query.len() {
+                stripped.push(' ');
+            }
+        }
+        stripped.push_str(&query[rest_start..]);
+        Cow::Owned(stripped)
+    }
+
+    /// Work out how a read with these hints should be routed, given how stale caches are known to
+    /// be (if they're being tracked at all)
+    pub fn route(&self, staleness: Option<&CacheStaleness>) -> HintedRoute {
+        if self.destination == Some(HintedDestination::Upstream) {
+            return HintedRoute::Upstream;
+        }
+        if let Some(max_staleness) = self.max_staleness {
+            let current = staleness.and_then(|s| s.staleness());
+            if !current.is_some_and(|current| current <= max_staleness) {
+                if self.destination == Some(HintedDestination::Cache) {
+                    let reason = match current {
+                        Some(current) => format!(
+                            "caches may be up to {current:?} behind the upstream database, more \
+                             than the max_staleness of {max_staleness:?}"
+                        ),
+                        None => {
+                            "how far caches are behind the upstream database is unknown".to_owned()
+                        }
+                    };
+                    return HintedRoute::Fail(ReadySetError::CacheOnlyQueryNotCached(reason));
+                }
+                return HintedRoute::Upstream;
+            }
+        }
+        if self.destination == Some(HintedDestination::Cache) {
+            HintedRoute::CacheOnly
+        } else {
+            HintedRoute::Default
+        }
+    }
+
+    /// The names of the hints that were given, for metrics
+    pub fn names(&self) -> impl Iterator<Item = &'static str> {
+        self.destination
+            .map(|destination| match destination {
+                HintedDestination::Upstream => "proxy",
+                HintedDestination::Cache => "cache-only",
+            })
+            .into_iter()
+            .chain(self.max_staleness.map(|_| "max_staleness"))
+    }
+}
+
+impl Display for QueryHints {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut hints = self.names().map(str::to_owned).collect::<Vec<_>>();
+        if let (Some(max_staleness), Some(hint)) = (self.max_staleness, hints.last_mut()) {
+            *hint = format!("max_staleness={}ms", max_staleness.as_millis());
+        }
+        write!(f, "{}", hints.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_hints() {
+        assert!(QueryHints::parse(Dialect::MySQL, "SELECT * FROM t").is_empty());
+        assert!(
+            QueryHints::parse(Dialect::MySQL, "SELECT /* just a comment */ * FROM t").is_empty()
+        );
+        assert!(QueryHints::parse(
+            Dialect::MySQL,
+            "SELECT /*+ MAX_EXECUTION_TIME(10) */ * FROM t"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn proxy() {
+        let hints = QueryHints::parse(Dialect::MySQL, "/* readyset:proxy */ SELECT * FROM t");
+        assert_eq!(hints.destination, Some(HintedDestination::Upstream));
+        assert_eq!(hints.max_staleness, None);
+        assert_eq!(hints.route(None), HintedRoute::Upstream);
+    }
+
+    #[test]
+    fn cache_only() {
+        let hints = QueryHints::parse(
+            Dialect::PostgreSQL,
+            "SELECT * FROM t WHERE x = $1 /*readyset:cache-only*/",
+        );
+        assert_eq!(hints.destination, Some(HintedDestination::Cache));
+        assert_eq!(hints.route(None), HintedRoute::CacheOnly);
+    }
+
+    #[test]
+    fn max_staleness() {
+        let parse = |q: &str| QueryHints::parse(Dialect::MySQL, q).max_staleness;
+        assert_eq!(
+            parse("SELECT /* readyset:max_staleness=5s */ 1"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            parse("SELECT /* readyset:max_staleness=250ms */ 1"),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(
+            parse("SELECT /* readyset:max_staleness=2m */ 1"),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse("SELECT /* readyset:max_staleness=10 */ 1"),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(parse("SELECT /* readyset:max_staleness=soon */ 1"), None);
+    }
+
+    #[test]
+    fn several_hints() {
+        let hints = QueryHints::parse(
+            Dialect::MySQL,
+            "SELECT /* READYSET: cache-only, max_staleness=1s */ 1",
+        );
+        assert_eq!(hints.destination, Some(HintedDestination::Cache));
+        assert_eq!(hints.max_staleness, Some(Duration::from_secs(1)));
+        assert_eq!(hints.to_string(), "cache-only, max_staleness=1000ms");
+
+        let hints = QueryHints::parse(
+            Dialect::MySQL,
+            "/* readyset:cache-only */ SELECT /* readyset:proxy */ 1",
+        );
+        assert_eq!(hints.destination, Some(HintedDestination::Upstream));
+    }
+
+    #[test]
+    fn hints_in_literals_are_ignored() {
+        assert!(QueryHints::parse(Dialect::MySQL, "SELECT '/* readyset:proxy */'").is_empty());
+        assert!(
+            QueryHints::parse(Dialect::MySQL, "SELECT 'it''s /* readyset:proxy */'").is_empty()
+        );
+        assert!(QueryHints::parse(Dialect::MySQL, "SELECT 1 -- /* readyset:proxy */").is_empty());
+        assert!(!QueryHints::parse(Dialect::MySQL, "SELECT 'a' /* readyset:proxy */").is_empty());
+        assert!(QueryHints::parse(Dialect::MySQL, "SELECT 1 # /* readyset:proxy */").is_empty());
+        assert!(
+            QueryHints::parse(Dialect::PostgreSQL, "SELECT $a$/* readyset:proxy */$a$").is_empty()
+        );
+    }
+
+    #[test]
+    fn strip() {
+        assert_eq!(
+            QueryHints::strip(Dialect::MySQL, "SELECT /* readyset:proxy */ * FROM t"),
+            "SELECT * FROM t"
+        );
+        assert_eq!(
+            QueryHints::strip(Dialect::MySQL, "/* readyset:proxy */ SELECT * FROM t"),
+            "SELECT * FROM t"
+        );
+        assert_eq!(
+            QueryHints::strip(Dialect::MySQL, "SELECT * FROM t /* readyset:proxy */"),
+            "SELECT * FROM t"
+        );
+        assert_eq!(
+            QueryHints::strip(Dialect::MySQL, "SELECT /* other */ * FROM t"),
+            "SELECT /* other */ * FROM t"
+        );
+        assert!(matches!(
+            QueryHints::strip(Dialect::MySQL, "SELECT * FROM t"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn unknown_staleness_is_too_stale() {
+        let hints = QueryHints::parse(Dialect::MySQL, "SELECT /* readyset:max_staleness=5s */ 1");
+        assert_eq!(hints.route(None), HintedRoute::Upstream);
+        assert_eq!(
+            hints.route(Some(&CacheStaleness::new())),
+            HintedRoute::Upstream
+        );
+
+        let hints = QueryHints::parse(
+            Dialect::MySQL,
+            "SELECT /* readyset:cache-only max_staleness=5s */ 1",
+        );
+        assert!(matches!(
+            hints.route(None),
+            HintedRoute::Fail(ReadySetError::CacheOnlyQueryNotCached(_))
+        ));
+    }
+}
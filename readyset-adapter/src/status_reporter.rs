@@ -129,7 +129,10 @@ where
 
 /// [`ReadySetStatusReporterInner`] is responsible for aggregating status-related information from
 /// various sources and generating a [`ReadySetStatus`].
-struct ReadySetStatusReporterInner<U> {
+struct ReadySetStatusReporterInner<U>
+where
+    U: UpstreamDatabase,
+{
     pub(crate) upstream: LazyUpstream<U>,
     /// A handle to the ReadySet controller, for making controller rpc calls to obtain
     /// a [`ReadySetControllerStatus`]
@@ -0,0 +1,106 @@
+//! Pass-through authentication of clients against the upstream database.
+//!
+//! Rather than accepting a single configured username and password, an adapter running with
+//! upstream authentication checks each client's credentials by connecting to the upstream
+//! database as that client's user. The connection then serves as that client's upstream
+//! connection, so proxied queries run with the client's own upstream permissions.
+//!
+//! Since opening a connection to the upstream for every client connection is comparatively slow,
+//! credentials that were successfully verified are remembered for a configurable interval, during
+//! which clients presenting the same credentials are accepted without connecting to the upstream
+//! first. Only a salted hash of each password is kept in memory. Credentials are only ever replaced
+//! by ones that were themselves verified, never forgotten because of a rejected attempt, so that
+//! clients guessing passwords can't force others to be re-verified against the upstream.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// A cache of the credentials that have recently been successfully verified against the upstream
+/// database, shared by all connections to the adapter.
+#[derive(Clone)]
+pub struct UpstreamAuthCache {
+    /// How long credentials stay verified for after connecting to the upstream with them
+    ttl: Duration,
+    /// Random salt mixed into the hash of each password
+    salt: [u8; 16],
+    /// Map from username to the hash of the password last verified for that user, and when it
+    /// was verified
+    verified: Arc<Mutex<HashMap<String, ([u8; 32], Instant)>>>,
+}
+
+impl UpstreamAuthCache {
+    /// Create a new, empty cache, which remembers verified credentials for `ttl`. A `ttl` of zero
+    /// verifies every client against the upstream.
+    pub fn new(ttl: Duration) -> Self {
+        let mut salt = [0; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Self {
+            ttl,
+            salt,
+            verified: Default::default(),
+        }
+    }
+
+    fn hash(&self, user: &str, password: &str) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt);
+        hasher.update(user.as_bytes());
+        hasher.update([0]);
+        hasher.update(password.as_bytes());
+        hasher.finalize().into()
+    }
+
+    /// Returns true if the given credentials were successfully verified against the upstream less
+    /// than `ttl` ago
+    pub fn is_verified(&self, user: &str, password: &str) -> bool {
+        let hash = self.hash(user, password);
+        let mut verified = self.verified.lock();
+        match verified.get(user) {
+            Some((verified_hash, at)) if at.elapsed() < self.ttl => *verified_hash == hash,
+            Some(_) => {
+                verified.remove(user);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Record that the given credentials were just successfully verified against the upstream
+    pub fn insert(&self, user: &str, password: &str) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let hash = self.hash(user, password);
+        self.verified
+            .lock()
+            .insert(user.to_owned(), (hash, Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verified_credentials() {
+        let cache = UpstreamAuthCache::new(Duration::from_secs(60));
+        assert!(!cache.is_verified("alice", "hunter2"));
+
+        cache.insert("alice", "hunter2");
+        assert!(cache.is_verified("alice", "hunter2"));
+        assert!(!cache.is_verified("alice", "hunter3"));
+        assert!(!cache.is_verified("bob", "hunter2"));
+    }
+
+    #[test]
+    fn verified_credentials_expire() {
+        let cache = UpstreamAuthCache::new(Duration::ZERO);
+        cache.insert("alice", "hunter2");
+        assert!(!cache.is_verified("alice", "hunter2"));
+    }
+}
@@ -1,5 +1,7 @@
 use std::error::Error;
 use std::fmt::Debug;
+use std::hash::Hash;
+use std::time::Duration;
 
 use async_trait::async_trait;
 pub use database_utils::UpstreamConfig;
@@ -10,6 +12,9 @@ use readyset_data::DfValue;
 use readyset_errors::ReadySetError;
 use tracing::debug;
 
+use crate::upstream_pool::{PoolableUpstream, PooledSession, UpstreamPool};
+use crate::upstream_replicas::{ReplicaRoutingUpstream, ReplicaSession, UpstreamReplicas};
+
 /// Information about a statement that has been prepared in an [`UpstreamDatabase`]
 pub struct UpstreamPrepare<DB: UpstreamDatabase> {
     pub statement_id: u32,
@@ -75,6 +80,11 @@ pub trait UpstreamDatabase: Sized + Send {
     /// [`prepare`](UpstreamDatabase::prepaare)
     type PrepareData<'a>: Default + Send;
 
+    /// An owned copy of [`PrepareData`](Self::PrepareData), which is kept so that a statement can
+    /// be prepared again on a different connection leased from an
+    /// [`UpstreamPool`](crate::upstream_pool::UpstreamPool)
+    type OwnedPrepareData: Debug + Clone + Eq + Hash + Send + Sync + 'static;
+
     /// Metadata passed to [`execute`] by the protocol shim
     ///
     /// [`execute`](UpstreamDatabase::execute)
@@ -94,6 +104,15 @@ pub trait UpstreamDatabase: Sized + Send {
     /// Returns the SQL dialect to use for formatting queries
     const SQL_DIALECT: nom_sql::Dialect;
 
+    /// Convert the data passed to [`prepare`](Self::prepare) to its owned form
+    fn own_prepare_data(data: &Self::PrepareData<'_>) -> Self::OwnedPrepareData;
+
+    /// Borrow data that was previously converted with [`own_prepare_data`] to pass it to
+    /// [`prepare`](Self::prepare) again
+    ///
+    /// [`own_prepare_data`]: Self::own_prepare_data
+    fn borrow_prepare_data(data: &Self::OwnedPrepareData) -> Self::PrepareData<'_>;
+
     /// Create a new connection to this upstream database
     ///
     /// Connect will return an error if the upstream database is running an unsupported version.
@@ -113,6 +132,33 @@ pub trait UpstreamDatabase: Sized + Send {
     /// Reset the connection to the upstream database
     async fn reset(&mut self) -> Result<(), Self::Error>;
 
+    /// Run as the given user from now on, authenticating with the given password instead of with
+    /// the credentials in the configured upstream database URL.
+    ///
+    /// Implementations may defer connecting as the new user until the connection is next used, so
+    /// this does not by itself check that the credentials are valid.
+    fn set_credentials(&mut self, _user: &str, _password: &str) -> Result<(), Self::Error> {
+        Err(ReadySetError::Unsupported(
+            "Authenticating as a different user is not supported by this upstream".to_owned(),
+        )
+        .into())
+    }
+
+    /// Check that the upstream database accepts the given credentials, by connecting with them
+    /// separately from this upstream. Unlike [`set_credentials`](Self::set_credentials), this
+    /// doesn't change the credentials this upstream runs with.
+    async fn check_credentials(&mut self, _user: &str, _password: &str) -> Result<(), Self::Error> {
+        Err(ReadySetError::Unsupported(
+            "Authenticating as a different user is not supported by this upstream".to_owned(),
+        )
+        .into())
+    }
+
+    /// Called once the results of the last command run against this upstream have been sent to
+    /// the client. Implementations that share connections between clients can use this to give
+    /// the connection back to other clients while this one is idle.
+    fn release(&mut self) {}
+
     /// Returns a database name if it was included in the original connection string, or None if no
     /// database name was included in the original connection string.
     fn database(&self) -> Option<&str> {
@@ -155,6 +201,39 @@ pub trait UpstreamDatabase: Sized + Send {
         exec_meta: Self::ExecMeta<'_>,
     ) -> Result<Self::QueryResult<'a>, Self::Error>;
 
+    /// Execute a statement that was prepared earlier with [`prepare`](Self::prepare), leaving its
+    /// results in a cursor named `cursor` on the upstream connection instead of returning them.
+    /// The rows are then read with [`fetch_cursor`](Self::fetch_cursor).
+    ///
+    /// A cursor declared with `hold` stays open after the current transaction ends, until it's
+    /// closed with [`close_cursor`](Self::close_cursor). Otherwise, it's closed along with the
+    /// transaction.
+    async fn declare_cursor(
+        &mut self,
+        _statement_id: u32,
+        _params: &[DfValue],
+        _cursor: &str,
+        _hold: bool,
+    ) -> Result<(), Self::Error> {
+        Err(ReadySetError::Unsupported("Cursors are not supported by this upstream".into()).into())
+    }
+
+    /// Read the next `limit` rows from a cursor opened with
+    /// [`declare_cursor`](Self::declare_cursor), or all of its remaining rows if `limit` is zero
+    async fn fetch_cursor<'a>(
+        &'a mut self,
+        _cursor: &str,
+        _limit: u32,
+        _exec_meta: Self::ExecMeta<'_>,
+    ) -> Result<Self::QueryResult<'a>, Self::Error> {
+        Err(ReadySetError::Unsupported("Cursors are not supported by this upstream".into()).into())
+    }
+
+    /// Close a cursor opened with [`declare_cursor`](Self::declare_cursor)
+    async fn close_cursor(&mut self, _cursor: &str) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
     /// Remove a prepared statement from the cache, and tell the upstream database to remove it and
     /// free any resources associated with it.
     ///
@@ -208,18 +287,29 @@ pub trait UpstreamDatabase: Sized + Send {
     /// supports a multi-element schema search path, the concept of "currently connected database"
     /// in MySQL can be thought of as a schema search path that only has one element
     async fn schema_search_path(&mut self) -> Result<Vec<SqlIdentifier>, Self::Error>;
+
+    /// If this database is a replica, returns how far behind the database it replicates from it
+    /// is. Returns `None` if the database isn't currently replicating from anywhere.
+    async fn replication_lag(&mut self) -> Result<Option<Duration>, Self::Error>;
 }
 
-pub struct LazyUpstream<U> {
+pub struct LazyUpstream<U: UpstreamDatabase> {
     upstream: Option<U>,
     upstream_config: UpstreamConfig,
+    /// Set if connections are leased from an [`UpstreamPool`] shared with other clients, rather
+    /// than opened just for this client
+    pooled: Option<PooledSession<U>>,
+    /// Set if read-only statements may be sent to read replicas of the upstream database
+    replicas: Option<ReplicaSession<U>>,
 }
 
-impl<U> From<UpstreamConfig> for LazyUpstream<U> {
+impl<U: UpstreamDatabase> From<UpstreamConfig> for LazyUpstream<U> {
     fn from(upstream_config: UpstreamConfig) -> Self {
         Self {
             upstream: None,
             upstream_config,
+            pooled: None,
+            replicas: None,
         }
     }
 }
@@ -235,11 +325,47 @@ where
     }
 
     async fn upstream(&mut self) -> Result<&mut U, U::Error> {
-        if self.upstream.is_none() {
-            self.connect().await?;
+        match &mut self.pooled {
+            Some(pooled) => pooled.with_conn(&self.upstream_config).await,
+            None => Self::dedicated(&mut self.upstream, &self.upstream_config).await,
         }
+    }
 
-        Ok(self.upstream.as_mut().unwrap())
+    /// Returns the connection opened just for this client, opening it first if needed.
+    ///
+    /// This takes the fields it needs separately, so that callers can match on `self.pooled` at
+    /// the same time.
+    async fn dedicated<'a>(
+        upstream: &'a mut Option<U>,
+        upstream_config: &UpstreamConfig,
+    ) -> Result<&'a mut U, U::Error> {
+        if upstream.is_none() {
+            debug!("LazyUpstream connecting to upstream");
+            *upstream = Some(U::connect(upstream_config.clone()).await?);
+        }
+
+        Ok(upstream.as_mut().unwrap())
+    }
+}
+
+impl<U> PoolableUpstream for LazyUpstream<U>
+where
+    U: UpstreamDatabase + 'static,
+{
+    type Connection = U;
+
+    fn use_pool(&mut self, pool: UpstreamPool<U>) {
+        self.upstream = None;
+        self.pooled = Some(PooledSession::new(pool));
+    }
+}
+
+impl<U> ReplicaRoutingUpstream for LazyUpstream<U>
+where
+    U: UpstreamDatabase + 'static,
+{
+    fn use_replicas(&mut self, replicas: UpstreamReplicas<U>) {
+        self.replicas = Some(ReplicaSession::new(replicas));
     }
 }
 
@@ -251,17 +377,23 @@ where
     type QueryResult<'a> = U::QueryResult<'a> where U: 'a;
     type StatementMeta = U::StatementMeta;
     type PrepareData<'a> = U::PrepareData<'a>;
+    type OwnedPrepareData = U::OwnedPrepareData;
     type ExecMeta<'a> = U::ExecMeta<'a>;
     type Error = U::Error;
 
     const DEFAULT_DB_VERSION: &'static str = U::DEFAULT_DB_VERSION;
     const SQL_DIALECT: nom_sql::Dialect = U::SQL_DIALECT;
 
+    fn own_prepare_data(data: &Self::PrepareData<'_>) -> Self::OwnedPrepareData {
+        U::own_prepare_data(data)
+    }
+
+    fn borrow_prepare_data(data: &Self::OwnedPrepareData) -> Self::PrepareData<'_> {
+        U::borrow_prepare_data(data)
+    }
+
     async fn connect(upstream_config: UpstreamConfig) -> Result<Self, Self::Error> {
-        Ok(Self {
-            upstream: None,
-            upstream_config,
-        })
+        Ok(Self::from(upstream_config))
     }
 
     async fn is_connected(&mut self) -> Result<bool, Self::Error> {
@@ -274,18 +406,79 @@ where
         password: &str,
         database: &str,
     ) -> Result<(), Self::Error> {
-        self.upstream()
-            .await?
-            .change_user(user, password, database)
-            .await
+        // The replicas would need to be switched to the new user and database too
+        if let Some(replicas) = &mut self.replicas {
+            replicas.pin();
+        }
+
+        match &mut self.pooled {
+            Some(pooled) => {
+                pooled
+                    .change_user(&self.upstream_config, user, password, database)
+                    .await
+            }
+            None => {
+                Self::dedicated(&mut self.upstream, &self.upstream_config)
+                    .await?
+                    .change_user(user, password, database)
+                    .await
+            }
+        }
     }
 
     async fn reset(&mut self) -> Result<(), Self::Error> {
-        self.upstream().await?.reset().await
+        if let Some(replicas) = &mut self.replicas {
+            replicas.reset();
+        }
+
+        match &mut self.pooled {
+            Some(pooled) => pooled.reset().await,
+            None => {
+                Self::dedicated(&mut self.upstream, &self.upstream_config)
+                    .await?
+                    .reset()
+                    .await
+            }
+        }
+    }
+
+    fn set_credentials(&mut self, user: &str, password: &str) -> Result<(), Self::Error> {
+        self.upstream_config = self.upstream_config.with_credentials(user, password)?;
+        // Drop any existing connection, so that the next use of the upstream reconnects as the new
+        // user
+        self.upstream = None;
+        if let Some(pooled) = &mut self.pooled {
+            pooled.set_credentials();
+        }
+        if let Some(replicas) = &mut self.replicas {
+            replicas.set_credentials(user, password);
+        }
+        Ok(())
+    }
+
+    async fn check_credentials(&mut self, user: &str, password: &str) -> Result<(), Self::Error> {
+        let upstream_config = self.upstream_config.with_credentials(user, password)?;
+        let mut upstream = U::connect(upstream_config).await?;
+        if upstream.is_connected().await? {
+            Ok(())
+        } else {
+            Err(
+                ReadySetError::Internal("Could not connect to the upstream database".to_owned())
+                    .into(),
+            )
+        }
+    }
+
+    fn release(&mut self) {
+        if let Some(pooled) = &mut self.pooled {
+            pooled.release();
+        }
     }
 
     fn database(&self) -> Option<&str> {
-        if let Some(u) = &self.upstream {
+        if let Some(pooled) = &self.pooled {
+            pooled.database()
+        } else if let Some(u) = &self.upstream {
             u.database()
         } else {
             None
@@ -293,6 +486,10 @@ where
     }
 
     fn version(&self) -> String {
+        if let Some(pooled) = &self.pooled {
+            return pooled.version();
+        }
+
         match &self.upstream {
             Some(u) => u.version(),
             None => U::DEFAULT_DB_VERSION.into(),
@@ -307,8 +504,26 @@ where
     where
         S: AsRef<str> + Send + Sync + 'a,
     {
-        let UpstreamPrepare { statement_id, meta } =
-            self.upstream().await?.prepare(query, data).await?;
+        let replica_statement = self
+            .replicas
+            .is_some()
+            .then(|| (query.as_ref().to_owned(), U::own_prepare_data(&data)));
+        let UpstreamPrepare { statement_id, meta } = match &mut self.pooled {
+            Some(pooled) => {
+                pooled
+                    .prepare(&self.upstream_config, query.as_ref(), data)
+                    .await?
+            }
+            None => {
+                Self::dedicated(&mut self.upstream, &self.upstream_config)
+                    .await?
+                    .prepare(query, data)
+                    .await?
+            }
+        };
+        if let (Some(replicas), Some((query, data))) = (&mut self.replicas, replica_statement) {
+            replicas.add_statement(statement_id, &query, data);
+        }
         Ok(UpstreamPrepare { statement_id, meta })
     }
 
@@ -318,25 +533,137 @@ where
         params: &[DfValue],
         exec_meta: Self::ExecMeta<'_>,
     ) -> Result<Self::QueryResult<'a>, Self::Error> {
-        self.upstream()
-            .await?
-            .execute(statement_id, params, exec_meta)
-            .await
+        if let Some(replicas) = &mut self.replicas {
+            replicas.record_execute(statement_id, params);
+            if let Some((idx, replica_statement_id)) = replicas.ready_statement(statement_id).await
+            {
+                return replicas
+                    .execute(idx, replica_statement_id, params, exec_meta)
+                    .await;
+            }
+        }
+
+        match &mut self.pooled {
+            Some(pooled) => {
+                pooled
+                    .execute(&self.upstream_config, statement_id, params, exec_meta)
+                    .await
+            }
+            None => {
+                Self::dedicated(&mut self.upstream, &self.upstream_config)
+                    .await?
+                    .execute(statement_id, params, exec_meta)
+                    .await
+            }
+        }
+    }
+
+    // Cursors are always declared on the primary, since their rows are fetched over several round
+    // trips on the same connection
+    async fn declare_cursor(
+        &mut self,
+        statement_id: u32,
+        params: &[DfValue],
+        cursor: &str,
+        hold: bool,
+    ) -> Result<(), Self::Error> {
+        match &mut self.pooled {
+            Some(pooled) => {
+                pooled
+                    .declare_cursor(&self.upstream_config, statement_id, params, cursor, hold)
+                    .await
+            }
+            None => {
+                Self::dedicated(&mut self.upstream, &self.upstream_config)
+                    .await?
+                    .declare_cursor(statement_id, params, cursor, hold)
+                    .await
+            }
+        }
+    }
+
+    async fn fetch_cursor<'a>(
+        &'a mut self,
+        cursor: &str,
+        limit: u32,
+        exec_meta: Self::ExecMeta<'_>,
+    ) -> Result<Self::QueryResult<'a>, Self::Error> {
+        match &mut self.pooled {
+            Some(pooled) => pooled.fetch_cursor(cursor, limit, exec_meta).await,
+            None => {
+                Self::dedicated(&mut self.upstream, &self.upstream_config)
+                    .await?
+                    .fetch_cursor(cursor, limit, exec_meta)
+                    .await
+            }
+        }
+    }
+
+    async fn close_cursor(&mut self, cursor: &str) -> Result<(), Self::Error> {
+        match &mut self.pooled {
+            Some(pooled) => pooled.close_cursor(cursor).await,
+            None => match &mut self.upstream {
+                Some(upstream) => upstream.close_cursor(cursor).await,
+                None => Ok(()),
+            },
+        }
     }
 
     async fn remove_statement(&mut self, statement_id: DeallocateId) -> Result<(), Self::Error> {
-        self.upstream().await?.remove_statement(statement_id).await
+        if let Some(replicas) = &mut self.replicas {
+            replicas.remove_statement(&statement_id);
+        }
+
+        match &mut self.pooled {
+            Some(pooled) => pooled.remove_statement(statement_id).await,
+            None => {
+                Self::dedicated(&mut self.upstream, &self.upstream_config)
+                    .await?
+                    .remove_statement(statement_id)
+                    .await
+            }
+        }
     }
 
     async fn query<'a>(&'a mut self, query: &'a str) -> Result<Self::QueryResult<'a>, Self::Error> {
-        self.upstream().await?.query(query).await
+        if let Some(replicas) = &mut self.replicas {
+            replicas.record(query);
+            if let Some(result) = replicas.query(query, false).await {
+                return result;
+            }
+        }
+
+        match &mut self.pooled {
+            Some(pooled) => pooled.query(&self.upstream_config, query, false).await,
+            None => {
+                Self::dedicated(&mut self.upstream, &self.upstream_config)
+                    .await?
+                    .query(query)
+                    .await
+            }
+        }
     }
 
     async fn simple_query<'a>(
         &'a mut self,
         query: &'a str,
     ) -> Result<Self::QueryResult<'a>, Self::Error> {
-        self.upstream().await?.simple_query(query).await
+        if let Some(replicas) = &mut self.replicas {
+            replicas.record(query);
+            if let Some(result) = replicas.query(query, true).await {
+                return result;
+            }
+        }
+
+        match &mut self.pooled {
+            Some(pooled) => pooled.query(&self.upstream_config, query, true).await,
+            None => {
+                Self::dedicated(&mut self.upstream, &self.upstream_config)
+                    .await?
+                    .simple_query(query)
+                    .await
+            }
+        }
     }
 
     // TODO: newtype RYW ticket, not just String
@@ -347,6 +674,9 @@ where
     where
         S: AsRef<str> + Send + Sync + 'a,
     {
+        if let Some(replicas) = &mut self.replicas {
+            replicas.record_write();
+        }
         self.upstream().await?.handle_ryw_write(query).await
     }
 
@@ -354,18 +684,58 @@ where
         &'a mut self,
         stmt: &StartTransactionStatement,
     ) -> Result<Self::QueryResult<'a>, Self::Error> {
-        self.upstream().await?.start_tx(stmt).await
+        if let Some(replicas) = &mut self.replicas {
+            replicas.set_in_transaction(true);
+        }
+
+        match &mut self.pooled {
+            Some(pooled) => pooled.start_tx(&self.upstream_config, stmt).await,
+            None => {
+                Self::dedicated(&mut self.upstream, &self.upstream_config)
+                    .await?
+                    .start_tx(stmt)
+                    .await
+            }
+        }
     }
 
     async fn commit(&mut self) -> Result<Self::QueryResult<'_>, Self::Error> {
-        self.upstream().await?.commit().await
+        if let Some(replicas) = &mut self.replicas {
+            replicas.set_in_transaction(false);
+        }
+
+        match &mut self.pooled {
+            Some(pooled) => pooled.end_tx(&self.upstream_config, true).await,
+            None => {
+                Self::dedicated(&mut self.upstream, &self.upstream_config)
+                    .await?
+                    .commit()
+                    .await
+            }
+        }
     }
 
     async fn rollback(&mut self) -> Result<Self::QueryResult<'_>, Self::Error> {
-        self.upstream().await?.rollback().await
+        if let Some(replicas) = &mut self.replicas {
+            replicas.set_in_transaction(false);
+        }
+
+        match &mut self.pooled {
+            Some(pooled) => pooled.end_tx(&self.upstream_config, false).await,
+            None => {
+                Self::dedicated(&mut self.upstream, &self.upstream_config)
+                    .await?
+                    .rollback()
+                    .await
+            }
+        }
     }
 
     async fn schema_search_path(&mut self) -> Result<Vec<SqlIdentifier>, Self::Error> {
         self.upstream().await?.schema_search_path().await
     }
+
+    async fn replication_lag(&mut self) -> Result<Option<Duration>, Self::Error> {
+        self.upstream().await?.replication_lag().await
+    }
 }
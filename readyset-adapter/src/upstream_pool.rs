@@ -0,0 +1,1318 @@
+//! Sharing connections to the upstream database between client connections.
+//!
+//! Without a pool, every client connected to the adapter gets its own upstream connection for as
+//! long as it stays connected. With an [`UpstreamPool`], clients instead lease a connection from a
+//! fixed-size pool when they need to run something upstream, and give it back as soon as the
+//! results have been sent, unless they're in the middle of a transaction - so upstream
+//! connections are only held for the duration of a transaction, or of a single statement outside
+//! of one.
+//!
+//! Since consecutive statements from the same client may run on different upstream connections,
+//! state that upstream databases keep per connection has to be dealt with:
+//!
+//! * Prepared statements are remembered by the client's session, and prepared again (once) on each
+//!   pooled connection they're executed on. Each connection keeps a bounded number of statements
+//!   prepared, deallocating the least recently used ones.
+//! * Session variables changed with `SET` (or the database selected with `USE`) are remembered, and
+//!   replayed on each connection leased by the session. Only the last value of each variable is
+//!   kept. Connections are reset before being leased to a different session.
+//! * Anything else that can't be recreated on another connection, such as temporary tables,
+//!   session-level locks, cursors held across transactions, or turning off autocommit, pins the
+//!   session to its current connection until the client disconnects. Pinned connections are closed
+//!   rather than returned to the pool.
+//!
+//! Some session state can't be detected; in particular, functions like `LAST_INSERT_ID()` or
+//! `lastval()` only return the expected value when run in the same transaction as the insert.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+use metrics::{decrement_gauge, gauge, histogram, increment_counter, increment_gauge};
+use nom_sql::{tokenize, Dialect, TokenKind};
+use parking_lot::{Mutex, RwLock};
+use readyset_adapter_types::DeallocateId;
+use readyset_client_metrics::recorded;
+use readyset_data::DfValue;
+use readyset_errors::ReadySetError;
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+use tracing::{debug, trace};
+
+use crate::upstream_database::{UpstreamConfig, UpstreamDatabase, UpstreamPrepare};
+
+/// The most statements kept prepared on each pooled connection. Once there are more, the least
+/// recently used ones are deallocated.
+const MAX_STATEMENTS_PER_CONNECTION: usize = 1024;
+
+/// A pool of connections to the upstream database, shared by all client connections to the
+/// adapter.
+pub struct UpstreamPool<U: UpstreamDatabase> {
+    inner: Arc<PoolInner<U>>,
+}
+
+impl<U: UpstreamDatabase> Clone for UpstreamPool<U> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+struct PoolInner<U: UpstreamDatabase> {
+    /// One permit for each connection the pool may have open at once
+    permits: Arc<Semaphore>,
+    /// How long to wait for a connection to become free before giving up
+    wait_timeout: Duration,
+    /// Connections not currently leased to any session
+    idle: Mutex<Vec<PooledConnection<U>>>,
+    /// Notified whenever a connection is returned to the pool
+    returned: Notify,
+    /// The version string of the upstream database, as of the last connection opened
+    version: RwLock<Option<String>>,
+    /// Used to give each session a unique ID
+    next_session_id: AtomicU64,
+}
+
+impl<U: UpstreamDatabase> UpstreamPool<U> {
+    /// Create a new, empty pool which opens at most `max_connections` connections to the upstream
+    /// database, and makes clients wait at most `wait_timeout` for a connection to become free.
+    pub fn new(max_connections: usize, wait_timeout: Duration) -> Self {
+        Self {
+            inner: Arc::new(PoolInner {
+                permits: Arc::new(Semaphore::new(max_connections)),
+                wait_timeout,
+                idle: Default::default(),
+                returned: Notify::new(),
+                version: Default::default(),
+                next_session_id: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Returns the version string of the upstream database, if any connection has been opened yet
+    fn version(&self) -> Option<String> {
+        self.inner.version.read().clone()
+    }
+
+    /// Take an idle connection opened with `key` out of the pool, preferring one that was last
+    /// leased by `session`
+    fn take_idle(&self, key: &str, session: u64) -> Option<PooledConnection<U>> {
+        let mut idle = self.inner.idle.lock();
+        let pos = idle
+            .iter()
+            .rposition(|c| c.key == key && c.state_owner == Some(session))
+            .or_else(|| idle.iter().rposition(|c| c.key == key))?;
+        // Keep the rest of the connections in the order they were returned in
+        let conn = idle.remove(pos);
+        gauge!(recorded::UPSTREAM_POOL_IDLE_CONNECTIONS, idle.len() as f64);
+        Some(conn)
+    }
+
+    /// Close the least recently used idle connection, to make room for a connection opened with
+    /// different credentials. Returns false if there were no idle connections.
+    fn evict_idle(&self) -> bool {
+        let mut idle = self.inner.idle.lock();
+        if idle.is_empty() {
+            return false;
+        }
+        idle.remove(0);
+        gauge!(recorded::UPSTREAM_POOL_IDLE_CONNECTIONS, idle.len() as f64);
+        true
+    }
+
+    async fn open(
+        &self,
+        upstream_config: &UpstreamConfig,
+        key: &str,
+        permit: OwnedSemaphorePermit,
+    ) -> Result<PooledConnection<U>, U::Error> {
+        debug!("Opening new pooled upstream connection");
+        let conn = U::connect(upstream_config.clone()).await?;
+        *self.inner.version.write() = Some(conn.version());
+        increment_gauge!(recorded::UPSTREAM_POOL_CONNECTIONS, 1.0);
+        Ok(PooledConnection {
+            conn,
+            key: key.to_owned(),
+            statements: LruCache::new(
+                MAX_STATEMENTS_PER_CONNECTION
+                    .try_into()
+                    .expect("1024 is not 0"),
+            ),
+            state_owner: None,
+            applied: 0,
+            dirty: false,
+            _permit: permit,
+        })
+    }
+
+    /// Lease a connection for `session`, opened with the credentials in `upstream_config`. If the
+    /// pool is full and none of its idle connections can be used, waits for one to be returned.
+    async fn lease(
+        &self,
+        upstream_config: &UpstreamConfig,
+        session: u64,
+    ) -> Result<PooledConnection<U>, U::Error> {
+        let key = upstream_config
+            .upstream_db_url
+            .as_deref()
+            .cloned()
+            .unwrap_or_default();
+        let start = Instant::now();
+        let deadline = tokio::time::Instant::now() + self.inner.wait_timeout;
+
+        let conn = loop {
+            // Register for notifications before checking the pool, so we can't miss a connection
+            // being returned in between
+            let returned = self.inner.returned.notified();
+            tokio::pin!(returned);
+            returned.as_mut().enable();
+
+            if let Some(conn) = self.take_idle(&key, session) {
+                break conn;
+            }
+
+            match Arc::clone(&self.inner.permits).try_acquire_owned() {
+                Ok(permit) => break self.open(upstream_config, &key, permit).await?,
+                // All of the connections we're allowed to open are open. If some of them are idle
+                // (but opened for different users), close one to make room for ours.
+                Err(_) if self.evict_idle() => continue,
+                Err(_) => {}
+            }
+
+            trace!("Waiting for a pooled upstream connection");
+            let permit = tokio::select! {
+                _ = returned => None,
+                permit = Arc::clone(&self.inner.permits).acquire_owned() => permit.ok(),
+                _ = tokio::time::sleep_until(deadline) => {
+                    increment_counter!(recorded::UPSTREAM_POOL_TIMEOUTS);
+                    return Err(ReadySetError::UpstreamPoolTimeout(self.inner.wait_timeout).into());
+                }
+            };
+            if let Some(permit) = permit {
+                break self.open(upstream_config, &key, permit).await?;
+            }
+        };
+
+        increment_counter!(recorded::UPSTREAM_POOL_LEASES);
+        histogram!(
+            recorded::UPSTREAM_POOL_WAIT_TIME,
+            start.elapsed().as_secs_f64()
+        );
+        Ok(conn)
+    }
+
+    /// Return a leased connection to the pool
+    fn release(&self, conn: PooledConnection<U>) {
+        let mut idle = self.inner.idle.lock();
+        idle.push(conn);
+        gauge!(recorded::UPSTREAM_POOL_IDLE_CONNECTIONS, idle.len() as f64);
+        drop(idle);
+        self.inner.returned.notify_waiters();
+    }
+}
+
+/// An [`UpstreamDatabase`] which can lease its connections from an [`UpstreamPool`] shared with
+/// other clients, instead of opening a connection of its own
+pub trait PoolableUpstream: UpstreamDatabase {
+    /// The type of the connections kept in the pool
+    type Connection: UpstreamDatabase + 'static;
+
+    /// Lease connections from `pool` from now on
+    fn use_pool(&mut self, pool: UpstreamPool<Self::Connection>);
+}
+
+/// A connection owned by an [`UpstreamPool`]
+struct PooledConnection<U: UpstreamDatabase> {
+    conn: U,
+    /// The upstream URL (including credentials) the connection was opened with
+    key: String,
+    /// Statements prepared on this connection, by their query and prepare data
+    statements: LruCache<(String, U::OwnedPrepareData), UpstreamPrepare<U>>,
+    /// The session whose session state was last applied to this connection
+    state_owner: Option<u64>,
+    /// The sequence number of the last statement in the owning session's [`SessionState`] that
+    /// has been run on this connection
+    applied: u64,
+    /// Whether this connection has session state that needs to be reset before it can be used by
+    /// a different session
+    dirty: bool,
+    /// Held for as long as the connection is open, to limit the size of the pool
+    _permit: OwnedSemaphorePermit,
+}
+
+impl<U: UpstreamDatabase> Drop for PooledConnection<U> {
+    fn drop(&mut self) {
+        decrement_gauge!(recorded::UPSTREAM_POOL_CONNECTIONS, 1.0);
+    }
+}
+
+impl<U: UpstreamDatabase> PooledConnection<U> {
+    /// Remove all session state from the connection, including prepared statements
+    async fn reset(&mut self) -> Result<(), U::Error> {
+        while let Some((_, prepare)) = self.statements.pop_lru() {
+            let _ = self
+                .conn
+                .remove_statement(DeallocateId::Numeric(prepare.statement_id))
+                .await;
+        }
+        self.conn.reset().await?;
+        self.dirty = false;
+        self.state_owner = None;
+        self.applied = 0;
+        Ok(())
+    }
+
+    /// Returns the ID of the given statement on this connection, preparing it first if necessary
+    async fn statement_id(
+        &mut self,
+        query: &str,
+        data: &U::OwnedPrepareData,
+    ) -> Result<u32, U::Error> {
+        let key = (query.to_owned(), data.clone());
+        if let Some(prepare) = self.statements.get(&key) {
+            return Ok(prepare.statement_id);
+        }
+        let prepare = self
+            .conn
+            .prepare(query, U::borrow_prepare_data(data))
+            .await?;
+        let statement_id = prepare.statement_id;
+        self.add_statement(key, prepare).await;
+        Ok(statement_id)
+    }
+
+    /// Remember a statement prepared on this connection, deallocating the least recently used
+    /// statement if there are now too many
+    async fn add_statement(
+        &mut self,
+        key: (String, U::OwnedPrepareData),
+        prepare: UpstreamPrepare<U>,
+    ) {
+        let statement_id = prepare.statement_id;
+        if let Some((_, evicted)) = self.statements.push(key, prepare) {
+            if evicted.statement_id != statement_id {
+                trace!(
+                    statement_id = evicted.statement_id,
+                    "Deallocating least recently used statement on pooled upstream connection"
+                );
+                let _ = self
+                    .conn
+                    .remove_statement(DeallocateId::Numeric(evicted.statement_id))
+                    .await;
+            }
+        }
+    }
+}
+
+/// How running a statement affects the upstream connection it runs on, beyond the statement itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SessionEffect {
+    /// No lasting effect
+    None,
+    /// Changes session state in a way that can be recreated on another connection by running the
+    /// same statement there
+    Replay,
+    /// Resets all session state
+    Discard,
+    /// Starts a transaction
+    Begin,
+    /// Ends the current transaction
+    End,
+    /// Creates session state that can't be moved to another connection
+    Pin,
+}
+
+/// Skip any whitespace and comments at the start of `query`
+pub(crate) fn strip_leading_comments(dialect: Dialect, query: &str) -> &str {
+    tokenize(dialect, query)
+        .find(|token| !token.is_trivia())
+        .map_or("", |token| &query[token.offset..])
+}
+
+/// Returns the unquoted words in `query` (skipping comments), upper-cased
+fn keywords(dialect: Dialect, query: &str) -> impl Iterator<Item = String> + '_ {
+    tokenize(dialect, query)
+        .filter(|token| token.kind == TokenKind::Word)
+        .map(|token| token.text.to_ascii_uppercase())
+}
+
+/// Work out how running `query` affects the connection it runs on, by looking at its first few
+/// keywords
+pub(crate) fn session_effect(dialect: Dialect, query: &str, in_transaction: bool) -> SessionEffect {
+    let words = keywords(dialect, query).collect::<Vec<_>>();
+
+    // Session-level advisory locks can appear anywhere in a query
+    if words.iter().any(|w| {
+        w == "GET_LOCK"
+            || w.starts_with("PG_ADVISORY_LOCK")
+            || w.starts_with("PG_TRY_ADVISORY_LOCK")
+    }) {
+        return SessionEffect::Pin;
+    }
+
+    // Cursors declared `WITH HOLD` outlive the transaction they're declared in
+    if words.windows(2).any(|w| w[0] == "WITH" && w[1] == "HOLD") {
+        return SessionEffect::Pin;
+    }
+
+    let first_words = words.iter().take(3).map(String::as_str).collect::<Vec<_>>();
+    match first_words[..] {
+        ["SET", "LOCAL", ..] => SessionEffect::None,
+        // Applies to the next transaction, which might not run on this connection
+        ["SET", "TRANSACTION", ..] if !in_transaction => SessionEffect::Pin,
+        ["SET", "TRANSACTION", ..] => SessionEffect::None,
+        ["SET", ..] if words.iter().any(|w| w == "AUTOCOMMIT") => SessionEffect::Pin,
+        ["SET", ..] | ["USE", ..] | ["RESET", ..] => SessionEffect::Replay,
+        ["DISCARD", "ALL", ..] => SessionEffect::Discard,
+        ["BEGIN", ..] | ["START", "TRANSACTION", ..] => SessionEffect::Begin,
+        ["XA", "START" | "BEGIN", ..] => SessionEffect::Begin,
+        // `XA END` and `XA PREPARE` leave the transaction on this connection until it's committed
+        // or rolled back
+        ["XA", "COMMIT" | "ROLLBACK", ..] => SessionEffect::End,
+        ["COMMIT" | "ROLLBACK" | "END" | "ABORT", "AND", "CHAIN"] => SessionEffect::None,
+        ["ROLLBACK", "TO", ..] => SessionEffect::None,
+        ["COMMIT" | "ROLLBACK" | "END" | "ABORT", ..] => SessionEffect::End,
+        ["PREPARE", "TRANSACTION", ..] => SessionEffect::End,
+        ["CREATE", "TEMP" | "TEMPORARY", ..]
+        | ["CREATE", _, "TEMP" | "TEMPORARY"]
+        | ["PREPARE", ..]
+        | ["LISTEN", ..]
+        | ["HANDLER", ..] => SessionEffect::Pin,
+        // Only outlive the transaction if run outside of one
+        ["LOCK" | "DECLARE", ..] if !in_transaction => SessionEffect::Pin,
+        _ => SessionEffect::None,
+    }
+}
+
+/// Split `s` on the commas in it that aren't inside quotes or parentheses
+fn split_top_level_commas(dialect: Dialect, s: &str) -> Vec<&str> {
+    let mut pieces = vec![];
+    let mut start = 0;
+    let mut depth = 0usize;
+    for token in tokenize(dialect, s) {
+        if token.is_punctuation('(') {
+            depth += 1;
+        } else if token.is_punctuation(')') {
+            depth = depth.saturating_sub(1);
+        } else if token.is_punctuation(',') && depth == 0 {
+            pieces.push(&s[start..token.offset]);
+            start = token.offset + 1;
+        }
+    }
+    pieces.push(&s[start..]);
+    pieces
+}
+
+/// Returns the name of the variable set by an assignment in a `SET` or `RESET` statement, such as
+/// `search_path` for `search_path TO public` or `x` for `@@SESSION.x = 1`
+fn variable_name(assignment: &str) -> String {
+    let assignment = assignment.trim().to_ascii_lowercase();
+    let mut name = assignment.as_str();
+    for prefix in ["session ", "@@session.", "@@local.", "@@"] {
+        if let Some(rest) = name.strip_prefix(prefix) {
+            name = rest.trim_start();
+        }
+    }
+    name.split(|c: char| c.is_whitespace() || matches!(c, '=' | ':' | ';'))
+        .next()
+        .unwrap_or_default()
+        .to_owned()
+}
+
+/// The statements run by a client that changed session state, to be replayed on the other
+/// connections the client's statements run on.
+///
+/// Only the last statement setting each variable is kept, so clients that keep changing the same
+/// variables don't make the state grow without bound. Each statement is given a sequence number,
+/// so that connections which have already run some of the statements can catch up by running
+/// only the ones with higher numbers.
+#[derive(Debug, Default)]
+pub(crate) struct SessionState {
+    /// The variable each statement sets, the statement, and its sequence number, in the order the
+    /// statements were run
+    statements: Vec<(String, String, u64)>,
+    /// The sequence number of the last statement recorded
+    last: u64,
+}
+
+impl SessionState {
+    fn push(&mut self, variable: String, statement: String) {
+        self.statements.retain(|(v, _, _)| *v != variable);
+        self.last += 1;
+        self.statements.push((variable, statement, self.last));
+    }
+
+    /// Record that `statement`, which has an effect of [`SessionEffect::Replay`] or
+    /// [`SessionEffect::Discard`], was run. Returns the sequence number of the last statement now
+    /// in the state.
+    pub(crate) fn record(&mut self, dialect: Dialect, statement: &str) -> u64 {
+        let statement = strip_leading_comments(dialect, statement);
+        let words = keywords(dialect, statement).take(2).collect::<Vec<_>>();
+
+        match words.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+            ["DISCARD", ..] | ["RESET", "ALL"] => {
+                self.statements.clear();
+                self.push(String::new(), statement.to_owned());
+            }
+            ["USE", ..] => self.push("use".to_owned(), statement.to_owned()),
+            ["RESET", ..] => self.push(variable_name(&statement[5..]), statement.to_owned()),
+            ["SET", ..] => {
+                let assignments = statement[3..].trim_start();
+                let pieces = split_top_level_commas(dialect, assignments);
+                // MySQL allows setting several variables in one statement, which we split up so
+                // that each variable can be replaced on its own
+                if pieces.len() > 1 && pieces.iter().all(|piece| piece.contains('=')) {
+                    for piece in pieces {
+                        let piece = piece.trim();
+                        self.push(variable_name(piece), format!("SET {piece}"));
+                    }
+                } else {
+                    self.push(variable_name(assignments), statement.to_owned());
+                }
+            }
+            _ => self.push(statement.to_owned(), statement.to_owned()),
+        }
+        self.last
+    }
+
+    /// Forget all session state
+    pub(crate) fn clear(&mut self) {
+        self.statements.clear();
+    }
+
+    /// Returns the statements with sequence numbers greater than `applied`, along with their
+    /// sequence numbers, in the order they should be run
+    pub(crate) fn since(&self, applied: u64) -> impl Iterator<Item = (u64, &str)> {
+        let start = self
+            .statements
+            .partition_point(|(_, _, seq)| *seq <= applied);
+        self.statements[start..]
+            .iter()
+            .map(|(_, statement, seq)| (*seq, statement.as_str()))
+    }
+}
+
+/// A single client connection's view of an [`UpstreamPool`], which leases connections from the
+/// pool as needed and keeps track of the session state that needs to follow the client from one
+/// connection to the next.
+pub(crate) struct PooledSession<U: UpstreamDatabase> {
+    pool: UpstreamPool<U>,
+    id: u64,
+    /// The connection currently leased to this session, if any
+    lease: Option<PooledConnection<U>>,
+    /// Statements prepared by the client, by the statement ID we gave the client
+    statements: HashMap<u32, (String, U::OwnedPrepareData)>,
+    next_statement_id: u32,
+    /// Statements run by the client that changed session state, to be replayed on each connection
+    /// leased by this session
+    session_state: SessionState,
+    in_transaction: bool,
+    /// Whether the session has to keep its current connection until the client disconnects
+    pinned: bool,
+    /// Cursors declared `WITH HOLD` on the leased connection, which keep the session on that
+    /// connection until they're closed
+    held_cursors: HashSet<String>,
+}
+
+impl<U: UpstreamDatabase> PooledSession<U> {
+    pub(crate) fn new(pool: UpstreamPool<U>) -> Self {
+        let id = pool.inner.next_session_id.fetch_add(1, Ordering::Relaxed);
+        Self {
+            pool,
+            id,
+            lease: None,
+            statements: HashMap::new(),
+            next_statement_id: 0,
+            session_state: SessionState::default(),
+            in_transaction: false,
+            pinned: false,
+            held_cursors: HashSet::new(),
+        }
+    }
+
+    /// Returns the connection leased to the session, leasing one from the pool first if needed.
+    ///
+    /// This takes the session's fields separately, so that callers can keep using the others
+    /// while the returned connection is borrowed.
+    async fn conn<'a>(
+        pool: &UpstreamPool<U>,
+        id: u64,
+        lease: &'a mut Option<PooledConnection<U>>,
+        session_state: &SessionState,
+        upstream_config: &UpstreamConfig,
+    ) -> Result<&'a mut PooledConnection<U>, U::Error> {
+        if let Some(conn) = lease {
+            return Ok(conn);
+        }
+
+        let mut conn = pool.lease(upstream_config, id).await?;
+        if conn.state_owner != Some(id) {
+            if conn.dirty {
+                conn.reset().await?;
+            }
+            conn.state_owner = Some(id);
+            conn.applied = 0;
+        }
+        // The connection may have been used by this session before, in which case it only needs
+        // to catch up on what changed since
+        for (seq, statement) in session_state.since(conn.applied) {
+            conn.conn.query(statement).await?;
+            conn.dirty = true;
+            conn.applied = seq;
+        }
+        Ok(lease.insert(conn))
+    }
+
+    /// Update the session's state to account for `query`, which has the given effect, having run
+    /// on the leased connection. Returns the sequence number of the last [`SessionState`]
+    /// statement the connection has run, if running `query` changed it.
+    fn apply_effect(
+        effect: SessionEffect,
+        succeeded: bool,
+        query: &str,
+        session_state: &mut SessionState,
+        in_transaction: &mut bool,
+    ) -> Option<u64> {
+        match effect {
+            SessionEffect::Replay | SessionEffect::Discard if succeeded => {
+                Some(session_state.record(U::SQL_DIALECT, query))
+            }
+            SessionEffect::Begin => {
+                *in_transaction = succeeded;
+                None
+            }
+            SessionEffect::End => {
+                *in_transaction = false;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Give the leased connection back to the pool, unless the session needs to keep it
+    pub(crate) fn release(&mut self) {
+        if self.in_transaction || self.pinned || !self.held_cursors.is_empty() {
+            return;
+        }
+        if let Some(conn) = self.lease.take() {
+            self.pool.release(conn);
+        }
+    }
+
+    /// Give up the leased connection, without returning it to the pool if it's in a state that
+    /// can't be cleaned up
+    fn discard_lease(&mut self) {
+        if let Some(conn) = self.lease.take() {
+            if self.in_transaction || self.pinned || !self.held_cursors.is_empty() {
+                debug!("Closing pooled upstream connection with unrecoverable session state");
+                drop(conn);
+                self.pool.inner.returned.notify_waiters();
+            } else {
+                self.pool.release(conn);
+            }
+        }
+        self.in_transaction = false;
+        self.pinned = false;
+        self.held_cursors.clear();
+    }
+
+    /// Start running with different credentials. Connections opened with the old credentials are
+    /// never leased to this session again.
+    pub(crate) fn set_credentials(&mut self) {
+        self.discard_lease();
+    }
+
+    fn pin(&mut self) {
+        if !self.pinned {
+            debug!("Pinning session to its pooled upstream connection");
+            increment_counter!(recorded::UPSTREAM_POOL_PINNED_SESSIONS);
+            self.pinned = true;
+        }
+    }
+
+    pub(crate) fn version(&self) -> String {
+        match &self.lease {
+            Some(conn) => conn.conn.version(),
+            None => self
+                .pool
+                .version()
+                .unwrap_or_else(|| U::DEFAULT_DB_VERSION.to_owned()),
+        }
+    }
+
+    pub(crate) fn database(&self) -> Option<&str> {
+        self.lease.as_ref().and_then(|conn| conn.conn.database())
+    }
+
+    /// Run some operation on the leased connection which has no effect on the session's state
+    pub(crate) async fn with_conn<'a>(
+        &'a mut self,
+        upstream_config: &UpstreamConfig,
+    ) -> Result<&'a mut U, U::Error> {
+        let conn = Self::conn(
+            &self.pool,
+            self.id,
+            &mut self.lease,
+            &self.session_state,
+            upstream_config,
+        )
+        .await?;
+        Ok(&mut conn.conn)
+    }
+
+    pub(crate) async fn query<'a>(
+        &'a mut self,
+        upstream_config: &UpstreamConfig,
+        query: &'a str,
+        simple: bool,
+    ) -> Result<U::QueryResult<'a>, U::Error> {
+        let effect = session_effect(U::SQL_DIALECT, query, self.in_transaction);
+        if effect == SessionEffect::Pin {
+            self.pin();
+        }
+        // The result borrows the connection, so update our state before handing it back. Whether
+        // the statement succeeded is known as soon as it returns, even if its rows aren't read
+        // yet.
+        let conn = Self::conn(
+            &self.pool,
+            self.id,
+            &mut self.lease,
+            &self.session_state,
+            upstream_config,
+        )
+        .await?;
+        let result = if simple {
+            conn.conn.simple_query(query).await
+        } else {
+            conn.conn.query(query).await
+        };
+        if let Some(applied) = Self::apply_effect(
+            effect,
+            result.is_ok(),
+            query,
+            &mut self.session_state,
+            &mut self.in_transaction,
+        ) {
+            conn.dirty = true;
+            conn.applied = applied;
+        }
+        result
+    }
+
+    pub(crate) async fn prepare<'a>(
+        &mut self,
+        upstream_config: &UpstreamConfig,
+        query: &str,
+        data: U::PrepareData<'a>,
+    ) -> Result<UpstreamPrepare<U>, U::Error> {
+        let owned = U::own_prepare_data(&data);
+        let conn = Self::conn(
+            &self.pool,
+            self.id,
+            &mut self.lease,
+            &self.session_state,
+            upstream_config,
+        )
+        .await?;
+        let key = (query.to_owned(), owned.clone());
+        let meta = match conn.statements.get(&key) {
+            Some(prepare) => prepare.meta.clone(),
+            None => {
+                let prepare = conn.conn.prepare(query, data).await?;
+                let meta = prepare.meta.clone();
+                conn.add_statement(key, prepare).await;
+                meta
+            }
+        };
+
+        let statement_id = self.next_statement_id;
+        self.next_statement_id += 1;
+        self.statements
+            .insert(statement_id, (query.to_owned(), owned));
+        Ok(UpstreamPrepare { statement_id, meta })
+    }
+
+    pub(crate) async fn execute<'a>(
+        &'a mut self,
+        upstream_config: &UpstreamConfig,
+        statement_id: u32,
+        params: &[DfValue],
+        exec_meta: U::ExecMeta<'_>,
+    ) -> Result<U::QueryResult<'a>, U::Error> {
+        // Prepared statements can start and end transactions or change session state just like
+        // queries can
+        let (query, _) = self
+            .statements
+            .get(&statement_id)
+            .ok_or(ReadySetError::PreparedStatementMissing { statement_id })?;
+        let effect = match session_effect(U::SQL_DIALECT, query, self.in_transaction) {
+            // The statement can't be replayed without its parameters
+            SessionEffect::Replay if !params.is_empty() => SessionEffect::Pin,
+            effect => effect,
+        };
+        if effect == SessionEffect::Pin {
+            self.pin();
+        }
+
+        let (query, data) = self
+            .statements
+            .get(&statement_id)
+            .ok_or(ReadySetError::PreparedStatementMissing { statement_id })?;
+        let conn = Self::conn(
+            &self.pool,
+            self.id,
+            &mut self.lease,
+            &self.session_state,
+            upstream_config,
+        )
+        .await?;
+        let id = conn.statement_id(query, data).await?;
+        let result = conn.conn.execute(id, params, exec_meta).await;
+        if let Some(applied) = Self::apply_effect(
+            effect,
+            result.is_ok(),
+            query,
+            &mut self.session_state,
+            &mut self.in_transaction,
+        ) {
+            conn.dirty = true;
+            conn.applied = applied;
+        }
+        result
+    }
+
+    pub(crate) async fn declare_cursor(
+        &mut self,
+        upstream_config: &UpstreamConfig,
+        statement_id: u32,
+        params: &[DfValue],
+        cursor: &str,
+        hold: bool,
+    ) -> Result<(), U::Error> {
+        let (query, data) = self
+            .statements
+            .get(&statement_id)
+            .ok_or(ReadySetError::PreparedStatementMissing { statement_id })?;
+        let conn = Self::conn(
+            &self.pool,
+            self.id,
+            &mut self.lease,
+            &self.session_state,
+            upstream_config,
+        )
+        .await?;
+        let id = conn.statement_id(query, data).await?;
+        conn.conn.declare_cursor(id, params, cursor, hold).await?;
+        if hold {
+            self.held_cursors.insert(cursor.to_owned());
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn fetch_cursor<'a>(
+        &'a mut self,
+        cursor: &str,
+        limit: u32,
+        exec_meta: U::ExecMeta<'_>,
+    ) -> Result<U::QueryResult<'a>, U::Error> {
+        // A cursor only exists on the connection it was declared on, which the session keeps
+        // until the cursor is closed, either explicitly or along with its transaction
+        let conn = self.lease.as_mut().ok_or_else(|| {
+            ReadySetError::Internal(format!("Cursor {cursor} is not open on any connection"))
+        })?;
+        conn.conn.fetch_cursor(cursor, limit, exec_meta).await
+    }
+
+    pub(crate) async fn close_cursor(&mut self, cursor: &str) -> Result<(), U::Error> {
+        self.held_cursors.remove(cursor);
+        match &mut self.lease {
+            Some(conn) => conn.conn.close_cursor(cursor).await,
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) async fn remove_statement(
+        &mut self,
+        statement_id: DeallocateId,
+    ) -> Result<(), U::Error> {
+        match statement_id {
+            // Statements stay prepared on the pooled connections, where other sessions can reuse
+            // them, until the connections are reset
+            DeallocateId::Numeric(statement_id) => {
+                self.statements
+                    .remove(&statement_id)
+                    .ok_or(ReadySetError::PreparedStatementMissing { statement_id })?;
+                Ok(())
+            }
+            DeallocateId::All => {
+                self.statements.clear();
+                Ok(())
+            }
+            // Named statements were prepared with SQL `PREPARE`, which pinned the session
+            DeallocateId::Named(_) => match &mut self.lease {
+                Some(conn) => conn.conn.remove_statement(statement_id).await,
+                None => Ok(()),
+            },
+        }
+    }
+
+    /// Switch the leased connection to a different user. Since the connection's credentials no
+    /// longer match the ones it was opened with, this pins the session to it.
+    pub(crate) async fn change_user(
+        &mut self,
+        upstream_config: &UpstreamConfig,
+        user: &str,
+        password: &str,
+        database: &str,
+    ) -> Result<(), U::Error> {
+        self.pin();
+        let conn = Self::conn(
+            &self.pool,
+            self.id,
+            &mut self.lease,
+            &self.session_state,
+            upstream_config,
+        )
+        .await?;
+        conn.conn.change_user(user, password, database).await?;
+        // Changing user drops all session state on the upstream, including prepared statements
+        conn.statements.clear();
+        self.statements.clear();
+        self.session_state.clear();
+        Ok(())
+    }
+
+    /// Forget all session state, for example because the client asked for its connection to be
+    /// reset. A pinned session stays pinned, since resetting the connection doesn't undo
+    /// everything that can pin it, such as switching users.
+    pub(crate) async fn reset(&mut self) -> Result<(), U::Error> {
+        self.statements.clear();
+        self.session_state.clear();
+        self.in_transaction = false;
+        // Resetting the connection closes all of its cursors
+        self.held_cursors.clear();
+        // Idle connections this session used before still have its old state, so make sure
+        // they're reset before the session uses them again
+        self.id = self
+            .pool
+            .inner
+            .next_session_id
+            .fetch_add(1, Ordering::Relaxed);
+        if let Some(conn) = &mut self.lease {
+            conn.reset().await?;
+            conn.state_owner = Some(self.id);
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn start_tx<'a>(
+        &'a mut self,
+        upstream_config: &UpstreamConfig,
+        stmt: &nom_sql::StartTransactionStatement,
+    ) -> Result<U::QueryResult<'a>, U::Error> {
+        let conn = Self::conn(
+            &self.pool,
+            self.id,
+            &mut self.lease,
+            &self.session_state,
+            upstream_config,
+        )
+        .await?;
+        let result = conn.conn.start_tx(stmt).await;
+        self.in_transaction = result.is_ok();
+        result
+    }
+
+    pub(crate) async fn end_tx<'a>(
+        &'a mut self,
+        upstream_config: &UpstreamConfig,
+        commit: bool,
+    ) -> Result<U::QueryResult<'a>, U::Error> {
+        self.in_transaction = false;
+        let conn = Self::conn(
+            &self.pool,
+            self.id,
+            &mut self.lease,
+            &self.session_state,
+            upstream_config,
+        )
+        .await?;
+        if commit {
+            conn.conn.commit().await
+        } else {
+            conn.conn.rollback().await
+        }
+    }
+}
+
+impl<U: UpstreamDatabase> Drop for PooledSession<U> {
+    fn drop(&mut self) {
+        self.discard_lease();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_upstream::{self, FakeUpstream};
+
+    fn pool(max_connections: usize) -> UpstreamPool<FakeUpstream> {
+        UpstreamPool::new(max_connections, Duration::from_secs(1))
+    }
+
+    /// Returns the connection and statement of each entry in the fake upstream's log
+    fn log() -> Vec<(usize, String)> {
+        fake_upstream::take_log()
+            .into_iter()
+            .map(|entry| (entry.connection, entry.statement))
+            .collect()
+    }
+
+    #[test]
+    fn session_effects() {
+        use SessionEffect::*;
+
+        assert_eq!(
+            session_effect(Dialect::MySQL, "SELECT * FROM t", false),
+            None
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "INSERT INTO t VALUES (1)", false),
+            None
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "set search_path = foo", false),
+            Replay
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "SET NAMES utf8mb4", false),
+            Replay
+        );
+        assert_eq!(session_effect(Dialect::MySQL, "USE db", false), Replay);
+        assert_eq!(
+            session_effect(Dialect::MySQL, "/* app */ SET @x = 1", false),
+            Replay
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "SET LOCAL work_mem = '1GB'", true),
+            None
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "SET autocommit = 0", false),
+            Pin
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "DISCARD ALL", false),
+            Discard
+        );
+        assert_eq!(session_effect(Dialect::MySQL, "begin", false), Begin);
+        assert_eq!(
+            session_effect(Dialect::MySQL, "START TRANSACTION READ ONLY", false),
+            Begin
+        );
+        assert_eq!(session_effect(Dialect::MySQL, "COMMIT", true), End);
+        assert_eq!(session_effect(Dialect::MySQL, "ROLLBACK;", true), End);
+        assert_eq!(
+            session_effect(Dialect::MySQL, "ROLLBACK TO SAVEPOINT a", true),
+            None
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "COMMIT AND CHAIN", true),
+            None
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "CREATE TEMPORARY TABLE t (x int)", false),
+            Pin
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "CREATE GLOBAL TEMP TABLE t (x int)", false),
+            Pin
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "PREPARE s FROM 'SELECT 1'", false),
+            Pin
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "SELECT GET_LOCK('a', 10)", false),
+            Pin
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "select pg_advisory_lock(1)", false),
+            Pin
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "select pg_advisory_xact_lock(1)", true),
+            None
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "LOCK TABLES t READ", false),
+            Pin
+        );
+        assert_eq!(session_effect(Dialect::MySQL, "LOCK TABLE t", true), None);
+        assert_eq!(
+            session_effect(Dialect::PostgreSQL, "DECLARE c CURSOR FOR SELECT 1", true),
+            None
+        );
+        assert_eq!(
+            session_effect(
+                Dialect::PostgreSQL,
+                "DECLARE c CURSOR WITH HOLD FOR SELECT 1",
+                true
+            ),
+            Pin
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "XA START 'xid'", false),
+            Begin
+        );
+        assert_eq!(session_effect(Dialect::MySQL, "XA END 'xid'", true), None);
+        assert_eq!(
+            session_effect(Dialect::MySQL, "XA PREPARE 'xid'", true),
+            None
+        );
+        assert_eq!(session_effect(Dialect::MySQL, "XA COMMIT 'xid'", true), End);
+
+        // Keywords in strings and comments don't count
+        assert_eq!(
+            session_effect(Dialect::MySQL, "SET x = 'autocommit'", false),
+            Replay
+        );
+        assert_eq!(
+            session_effect(
+                Dialect::PostgreSQL,
+                "SELECT 'get_lock(' /* COMMIT */",
+                false
+            ),
+            None
+        );
+        assert_eq!(
+            session_effect(Dialect::MySQL, "# a comment\nBEGIN", false),
+            Begin
+        );
+    }
+
+    #[test]
+    fn session_state_keeps_last_value() {
+        let mut state = SessionState::default();
+        state.record(Dialect::MySQL, "SET search_path = a");
+        state.record(Dialect::MySQL, "USE db");
+        state.record(Dialect::MySQL, "SET @a = 1, @@SESSION.b = 'x,y'");
+        state.record(Dialect::MySQL, "set search_path to b");
+        let applied = state.record(Dialect::MySQL, "SET @a := 2");
+        state.record(Dialect::MySQL, "RESET b");
+        state.record(Dialect::MySQL, "SET NAMES utf8mb4");
+
+        assert_eq!(
+            state.since(0).map(|(_, s)| s).collect::<Vec<_>>(),
+            vec![
+                "USE db",
+                "set search_path to b",
+                "SET @a := 2",
+                "RESET b",
+                "SET NAMES utf8mb4"
+            ]
+        );
+        assert_eq!(
+            state.since(applied).map(|(_, s)| s).collect::<Vec<_>>(),
+            vec!["RESET b", "SET NAMES utf8mb4"]
+        );
+
+        state.record(Dialect::MySQL, "RESET ALL");
+        assert_eq!(
+            state.since(0).map(|(_, s)| s).collect::<Vec<_>>(),
+            vec!["RESET ALL"]
+        );
+    }
+
+    #[tokio::test]
+    async fn lease_and_release() {
+        let config = fake_upstream::config("fake://upstream");
+        let pool = pool(1);
+        let mut a = PooledSession::new(pool.clone());
+        let mut b = PooledSession::new(pool);
+        log();
+
+        a.query(&config, "SET search_path = foo", false)
+            .await
+            .unwrap();
+        a.release();
+        assert!(a.lease.is_none());
+        a.query(&config, "SELECT 1", false).await.unwrap();
+        a.release();
+        let a_log = log();
+        let conn = a_log[0].0;
+        assert_eq!(
+            a_log,
+            vec![
+                (conn, "SET search_path = foo".to_owned()),
+                (conn, "SELECT 1".to_owned())
+            ]
+        );
+
+        // The only connection is reused, but has to be reset first
+        b.query(&config, "SELECT 2", false).await.unwrap();
+        b.release();
+        assert_eq!(
+            log(),
+            vec![(conn, "reset".to_owned()), (conn, "SELECT 2".to_owned())]
+        );
+
+        // And then the first session's state is replayed on it
+        a.query(&config, "SELECT 3", false).await.unwrap();
+        a.release();
+        assert_eq!(
+            log(),
+            vec![
+                (conn, "SET search_path = foo".to_owned()),
+                (conn, "SELECT 3".to_owned())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn pinned_session_keeps_connection() {
+        let config = fake_upstream::config("fake://upstream");
+        let pool = pool(2);
+        let mut a = PooledSession::new(pool.clone());
+        let mut b = PooledSession::new(pool);
+        log();
+
+        a.query(&config, "CREATE TEMPORARY TABLE t (x int)", false)
+            .await
+            .unwrap();
+        a.release();
+        assert!(a.pinned);
+        assert!(a.lease.is_some());
+
+        b.query(&config, "SELECT 1", false).await.unwrap();
+        b.release();
+        a.query(&config, "SELECT * FROM t", false).await.unwrap();
+        a.release();
+
+        let entries = log();
+        assert_eq!(entries[0].0, entries[2].0);
+        assert_ne!(entries[0].0, entries[1].0);
+    }
+
+    #[tokio::test]
+    async fn prepared_transaction_keeps_lease() {
+        let config = fake_upstream::config("fake://upstream");
+        let pool = pool(2);
+        let mut a = PooledSession::new(pool.clone());
+        let mut b = PooledSession::new(pool);
+
+        let begin = a.prepare(&config, "BEGIN", ()).await.unwrap().statement_id;
+        let commit = a.prepare(&config, "COMMIT", ()).await.unwrap().statement_id;
+        a.release();
+        log();
+
+        a.execute(&config, begin, &[], ()).await.unwrap();
+        a.release();
+        assert!(a.in_transaction);
+        assert!(a.lease.is_some());
+
+        // Another session can't use the connection in the middle of the transaction
+        b.query(&config, "SELECT 1", false).await.unwrap();
+        b.release();
+
+        a.execute(&config, commit, &[], ()).await.unwrap();
+        a.release();
+        assert!(!a.in_transaction);
+        assert!(a.lease.is_none());
+
+        let entries = log();
+        assert_eq!(entries[0].1, "execute BEGIN");
+        assert_eq!(entries[1].1, "SELECT 1");
+        assert_eq!(entries[2].1, "execute COMMIT");
+        assert_eq!(entries[0].0, entries[2].0);
+        assert_ne!(entries[0].0, entries[1].0);
+    }
+
+    #[tokio::test]
+    async fn held_cursor_keeps_lease() {
+        let config = fake_upstream::config("fake://upstream");
+        let pool = pool(2);
+        let mut a = PooledSession::new(pool.clone());
+        let mut b = PooledSession::new(pool);
+
+        let select = a
+            .prepare(&config, "SELECT * FROM t", ())
+            .await
+            .unwrap()
+            .statement_id;
+        a.release();
+        log();
+
+        a.declare_cursor(&config, select, &[], "c", true)
+            .await
+            .unwrap();
+        a.release();
+        assert!(a.lease.is_some());
+
+        // The cursor's rows can only be fetched from the connection it was declared on
+        b.query(&config, "SELECT 1", false).await.unwrap();
+        b.release();
+        a.fetch_cursor("c", 10, ()).await.unwrap();
+        a.release();
+        assert!(a.lease.is_some());
+
+        a.close_cursor("c").await.unwrap();
+        a.release();
+        assert!(a.lease.is_none());
+
+        let entries = log();
+        assert_eq!(entries[0].1, "declare c for SELECT * FROM t");
+        assert_eq!(entries[1].1, "SELECT 1");
+        assert_eq!(entries[2].1, "fetch 10 from c");
+        assert_eq!(entries[3].1, "close c");
+        assert_eq!(entries[0].0, entries[2].0);
+        assert_eq!(entries[0].0, entries[3].0);
+        assert_ne!(entries[0].0, entries[1].0);
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used_statements() {
+        let config = fake_upstream::config("fake://upstream");
+        let mut session = PooledSession::new(pool(1));
+        let first = session
+            .prepare(&config, "SELECT 0", ())
+            .await
+            .unwrap()
+            .statement_id;
+        for i in 1..=MAX_STATEMENTS_PER_CONNECTION {
+            session
+                .prepare(&config, &format!("SELECT {i}"), ())
+                .await
+                .unwrap();
+        }
+        let entries = log();
+        assert_eq!(
+            entries.last().map(|(_, s)| s.as_str()),
+            Some("deallocate SELECT 0")
+        );
+
+        // The session can still execute the statement, which is prepared again
+        session.execute(&config, first, &[], ()).await.unwrap();
+        let entries = log();
+        assert_eq!(entries[0].1, "prepare SELECT 0");
+        assert_eq!(entries.last().unwrap().1, "execute SELECT 0");
+    }
+
+    #[test]
+    fn leading_comments() {
+        assert_eq!(
+            strip_leading_comments(Dialect::MySQL, "  SELECT 1"),
+            "SELECT 1"
+        );
+        assert_eq!(
+            strip_leading_comments(Dialect::MySQL, "/* a */ -- b\n /* c */SELECT 1"),
+            "SELECT 1"
+        );
+        assert_eq!(
+            strip_leading_comments(Dialect::MySQL, "/* unterminated"),
+            ""
+        );
+    }
+}
@@ -0,0 +1,730 @@
+//! Routing proxied reads to read replicas of the upstream database.
+//!
+//! When [`UpstreamReplicas`] are configured, read-only statements that a client runs outside of a
+//! transaction are sent to one of the replicas, picked round-robin among those that are currently
+//! healthy, instead of to the upstream database itself. Everything else - writes, statements run
+//! inside a transaction, and locking reads such as `SELECT ... FOR UPDATE` - keeps going to the
+//! upstream database.
+//!
+//! [`UpstreamReplicas::run`] periodically connects to each replica and asks how far behind the
+//! upstream database it is. Replicas that can't be reached, that aren't replicating, or that lag
+//! behind by more than the configured maximum don't receive any reads until they catch up again.
+//!
+//! Replicas may be up to the configured maximum lag behind the upstream database, so after a
+//! client writes to the upstream database its reads keep going to the upstream database for that
+//! long, to make sure the client sees its own writes.
+//!
+//! Each client opens its own connections to the replicas, the first time it sends them a query.
+//! Session variables the client changes with `SET` (or `USE`) are replayed on those connections.
+//! Clients that create session state that only exists on the upstream database, such as temporary
+//! tables, stop using the replicas until they disconnect.
+//!
+//! Whether a statement is read-only is decided by looking at its text, so reads that call
+//! functions with side effects defined in the upstream database itself may end up on a replica.
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use metrics::{gauge, increment_counter};
+use nom_sql::{tokenize, Dialect, TokenKind};
+use readyset_adapter_types::DeallocateId;
+use readyset_client_metrics::recorded;
+use readyset_data::DfValue;
+use readyset_errors::ReadySetError;
+use readyset_util::shutdown::ShutdownReceiver;
+use tokio::select;
+use tracing::{debug, info, warn};
+
+use crate::upstream_database::{IsFatalError, UpstreamConfig, UpstreamDatabase};
+use crate::upstream_pool::{session_effect, PoolableUpstream, SessionEffect, SessionState};
+
+/// Clauses of a `SELECT` statement which mean it either takes locks or writes data, and so has to
+/// run on the upstream database
+const NOT_READ_ONLY_CLAUSES: &[&[&str]] = &[
+    &["FOR", "UPDATE"],
+    &["FOR", "NO", "KEY", "UPDATE"],
+    &["FOR", "SHARE"],
+    &["FOR", "KEY", "SHARE"],
+    &["LOCK", "IN", "SHARE", "MODE"],
+    &["INTO"],
+];
+
+/// Functions which either take locks, write data, or depend on session state of the connection
+/// they run on, and so have to run on the upstream database
+const NOT_READ_ONLY_FUNCTIONS: &[&str] = &[
+    "NEXTVAL",
+    "SETVAL",
+    "CURRVAL",
+    "LASTVAL",
+    "LAST_INSERT_ID",
+    "FOUND_ROWS",
+    "ROW_COUNT",
+    "GET_LOCK",
+    "RELEASE_LOCK",
+];
+
+/// Prefixes of the names of PostgreSQL's advisory lock functions
+const ADVISORY_LOCK_FUNCTION_PREFIXES: &[&str] = &["PG_ADVISORY", "PG_TRY_ADVISORY"];
+
+/// Returns true if `query` only reads data, and may run on a replica
+fn is_read_only(dialect: Dialect, query: &str) -> bool {
+    let tokens = tokenize(dialect, query)
+        .filter(|token| !token.is_trivia())
+        .collect::<Vec<_>>();
+    let first_word = tokens.iter().find(|token| token.kind == TokenKind::Word);
+    if !first_word.is_some_and(|token| token.is_keyword("SELECT")) {
+        return false;
+    }
+
+    !tokens.iter().enumerate().any(|(idx, token)| {
+        let rest = &tokens[idx..];
+        let clause = NOT_READ_ONLY_CLAUSES.iter().any(|clause| {
+            rest.len() >= clause.len()
+                && clause
+                    .iter()
+                    .zip(rest)
+                    .all(|(keyword, token)| token.is_keyword(keyword))
+        });
+        let function = token.kind == TokenKind::Word
+            && rest.get(1).is_some_and(|next| next.is_punctuation('('))
+            && {
+                let name = token.text.to_ascii_uppercase();
+                NOT_READ_ONLY_FUNCTIONS.contains(&name.as_str())
+                    || ADVISORY_LOCK_FUNCTION_PREFIXES
+                        .iter()
+                        .any(|prefix| name.starts_with(prefix))
+            };
+        clause || function
+    })
+}
+
+/// An [`UpstreamDatabase`] which can send read-only statements to [`UpstreamReplicas`]
+pub trait ReplicaRoutingUpstream: PoolableUpstream {
+    /// Send read-only statements to `replicas` from now on
+    fn use_replicas(&mut self, replicas: UpstreamReplicas<Self::Connection>);
+}
+
+/// The set of read replicas of the upstream database, shared by all client connections to the
+/// adapter
+pub struct UpstreamReplicas<U> {
+    inner: Arc<ReplicasInner>,
+    _upstream: PhantomData<fn() -> U>,
+}
+
+impl<U> Clone for UpstreamReplicas<U> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            _upstream: PhantomData,
+        }
+    }
+}
+
+struct ReplicasInner {
+    replicas: Vec<Replica>,
+    /// Replicas lagging further behind the upstream database than this don't receive reads
+    max_lag: Duration,
+    /// Used to pick replicas round-robin
+    next: AtomicUsize,
+}
+
+struct Replica {
+    /// Used to connect to the replica, with the adapter's own credentials
+    upstream_config: UpstreamConfig,
+    /// Whether the replica was reachable and caught up when it was last checked
+    healthy: AtomicBool,
+    /// Incremented every time the replica stops being healthy, so that clients know to reopen
+    /// their connections to it
+    generation: AtomicU64,
+}
+
+impl<U: UpstreamDatabase> UpstreamReplicas<U> {
+    /// Create a new set of replicas, connecting to each one with the corresponding config.
+    ///
+    /// Replicas are considered unhealthy until [`run`](Self::run) has checked them once.
+    pub fn new(replica_configs: Vec<UpstreamConfig>, max_lag: Duration) -> Self {
+        Self {
+            inner: Arc::new(ReplicasInner {
+                replicas: replica_configs
+                    .into_iter()
+                    .map(|upstream_config| Replica {
+                        upstream_config,
+                        healthy: AtomicBool::new(false),
+                        generation: AtomicU64::new(0),
+                    })
+                    .collect(),
+                max_lag,
+                next: AtomicUsize::new(0),
+            }),
+            _upstream: PhantomData,
+        }
+    }
+
+    /// Returns the index of the next healthy replica, or None if no replicas are healthy
+    fn pick(&self) -> Option<usize> {
+        let replicas = &self.inner.replicas;
+        let start = self.inner.next.fetch_add(1, Ordering::Relaxed);
+        (0..replicas.len())
+            .map(|i| (start + i) % replicas.len())
+            .find(|&idx| replicas[idx].healthy.load(Ordering::Acquire))
+    }
+
+    fn generation(&self, idx: usize) -> u64 {
+        self.inner.replicas[idx].generation.load(Ordering::Acquire)
+    }
+
+    fn set_healthy(&self, idx: usize, healthy: bool) {
+        let replica = &self.inner.replicas[idx];
+        let was_healthy = replica.healthy.swap(healthy, Ordering::AcqRel);
+        if was_healthy && !healthy {
+            replica.generation.fetch_add(1, Ordering::AcqRel);
+        }
+        if was_healthy != healthy {
+            info!(replica = idx, healthy, "Upstream replica health changed");
+        }
+        gauge!(
+            recorded::UPSTREAM_REPLICA_HEALTHY,
+            if healthy { 1.0 } else { 0.0 },
+            "replica" => idx.to_string()
+        );
+    }
+
+    /// Record the result of running a statement on the replica at `idx`, taking it out of
+    /// rotation if its connection was lost
+    fn note_result<T>(&self, idx: usize, result: &Result<T, U::Error>) {
+        match result {
+            Ok(_) => increment_counter!(
+                recorded::UPSTREAM_REPLICA_QUERIES,
+                "replica" => idx.to_string()
+            ),
+            Err(error) if error.is_fatal() => self.set_healthy(idx, false),
+            Err(_) => {}
+        }
+    }
+
+    /// Check how far behind the upstream database the replica at `idx` is, using (and if needed
+    /// opening) the given connection to it. Returns whether the replica should receive reads.
+    async fn check(&self, idx: usize, conn: &mut Option<U>) -> bool {
+        let replica = &self.inner.replicas[idx];
+        if conn.is_none() {
+            match U::connect(replica.upstream_config.clone()).await {
+                Ok(c) => *conn = Some(c),
+                Err(error) => {
+                    warn!(replica = idx, %error, "Could not connect to upstream replica");
+                    return false;
+                }
+            }
+        }
+        let Some(c) = conn.as_mut() else {
+            return false;
+        };
+
+        match c.replication_lag().await {
+            Ok(Some(lag)) => {
+                gauge!(
+                    recorded::UPSTREAM_REPLICA_LAG,
+                    lag.as_secs_f64(),
+                    "replica" => idx.to_string()
+                );
+                if lag > self.inner.max_lag {
+                    debug!(replica = idx, ?lag, "Upstream replica is lagging behind");
+                    false
+                } else {
+                    true
+                }
+            }
+            Ok(None) => {
+                warn!(replica = idx, "Upstream replica is not replicating");
+                false
+            }
+            Err(error) => {
+                warn!(replica = idx, %error, "Could not check upstream replica lag");
+                *conn = None;
+                false
+            }
+        }
+    }
+
+    /// Check the health of every replica once every `interval`, until `shutdown_recv` is
+    /// signalled
+    pub async fn run(self, interval: Duration, mut shutdown_recv: ShutdownReceiver) {
+        let mut interval = tokio::time::interval(interval);
+        let fut = async {
+            let mut conns = self.inner.replicas.iter().map(|_| None).collect::<Vec<_>>();
+            loop {
+                interval.tick().await;
+                for (idx, conn) in conns.iter_mut().enumerate() {
+                    let healthy = self.check(idx, conn).await;
+                    self.set_healthy(idx, healthy);
+                }
+            }
+        };
+        select! {
+            biased;
+            _ = shutdown_recv.recv() => {
+                info!("Upstream replica health checks shutting down after shut down signal received");
+            }
+            _ = fut => unreachable!(),
+        }
+    }
+}
+
+/// A single client connection's connection to one replica
+struct ReplicaConnection<U: UpstreamDatabase> {
+    conn: U,
+    /// The [`Replica::generation`] the connection was opened in
+    generation: u64,
+    /// The sequence number of the last of the session's `session_state` statements that has been
+    /// run on this connection
+    applied: u64,
+    /// Statements prepared on this connection, by their query and prepare data
+    statements: HashMap<(String, U::OwnedPrepareData), u32>,
+}
+
+/// A single client connection's view of the [`UpstreamReplicas`], which decides which of the
+/// client's statements may run on a replica and keeps the client's connections to the replicas
+/// in the same state as its connection to the upstream database.
+pub(crate) struct ReplicaSession<U: UpstreamDatabase> {
+    replicas: UpstreamReplicas<U>,
+    /// Connections to each replica, opened the first time they're needed
+    conns: Vec<Option<ReplicaConnection<U>>>,
+    /// Credentials to connect to the replicas with, if different from the adapter's own
+    credentials: Option<(String, String)>,
+    /// Statements prepared by the client, by their statement ID
+    statements: HashMap<u32, (String, U::OwnedPrepareData)>,
+    /// Statements run by the client that changed session state
+    session_state: SessionState,
+    in_transaction: bool,
+    /// Set once the client creates session state that only exists on the upstream database
+    pinned: bool,
+    /// When the client last wrote to the upstream database
+    last_write: Option<Instant>,
+    /// Set if the client wrote to the upstream database in its current transaction, whose writes
+    /// only reach the replicas once it commits
+    wrote_in_transaction: bool,
+}
+
+impl<U: UpstreamDatabase> ReplicaSession<U> {
+    pub(crate) fn new(replicas: UpstreamReplicas<U>) -> Self {
+        let conns = replicas.inner.replicas.iter().map(|_| None).collect();
+        Self {
+            replicas,
+            conns,
+            credentials: None,
+            statements: HashMap::new(),
+            session_state: SessionState::default(),
+            in_transaction: false,
+            pinned: false,
+            last_write: None,
+            wrote_in_transaction: false,
+        }
+    }
+
+    fn close_all(&mut self) {
+        self.conns.iter_mut().for_each(|conn| *conn = None);
+    }
+
+    /// Connect to the replicas with different credentials from now on
+    pub(crate) fn set_credentials(&mut self, user: &str, password: &str) {
+        self.credentials = Some((user.to_owned(), password.to_owned()));
+        self.close_all();
+    }
+
+    /// Send every statement to the upstream database from now on
+    pub(crate) fn pin(&mut self) {
+        if !self.pinned {
+            debug!("Sending all further statements from this session to the upstream database");
+            self.pinned = true;
+        }
+    }
+
+    pub(crate) fn set_in_transaction(&mut self, in_transaction: bool) {
+        if in_transaction {
+            self.in_transaction = true;
+        } else {
+            self.end_transaction();
+        }
+    }
+
+    fn end_transaction(&mut self) {
+        self.in_transaction = false;
+        if std::mem::take(&mut self.wrote_in_transaction) {
+            self.record_write();
+        }
+    }
+
+    /// Record that the client just wrote to the upstream database, so that its reads aren't sent
+    /// to replicas that might not have the write yet
+    pub(crate) fn record_write(&mut self) {
+        self.last_write = Some(Instant::now());
+        self.wrote_in_transaction = self.in_transaction;
+    }
+
+    /// Update the session's state to account for `query` being run on the upstream database.
+    ///
+    /// This is called before the statement runs, so session state changes that end up failing
+    /// upstream are replayed on replicas too; if they fail there as well, the session stops
+    /// using the replicas.
+    pub(crate) fn record(&mut self, query: &str) {
+        match session_effect(U::SQL_DIALECT, query, self.in_transaction) {
+            SessionEffect::None if !is_read_only(U::SQL_DIALECT, query) => self.record_write(),
+            SessionEffect::None => {}
+            SessionEffect::Replay => {
+                self.session_state.record(U::SQL_DIALECT, query);
+            }
+            SessionEffect::Discard => {
+                self.session_state.clear();
+                self.close_all();
+            }
+            SessionEffect::Begin => self.in_transaction = true,
+            SessionEffect::End => self.end_transaction(),
+            SessionEffect::Pin => self.pin(),
+        }
+    }
+
+    /// Update the session's state to account for the statement the client prepared with
+    /// `statement_id` being executed on the upstream database with the given parameters
+    pub(crate) fn record_execute(&mut self, statement_id: u32, params: &[DfValue]) {
+        let Some((query, _)) = self.statements.get(&statement_id) else {
+            return;
+        };
+        match session_effect(U::SQL_DIALECT, query, self.in_transaction) {
+            SessionEffect::None if !is_read_only(U::SQL_DIALECT, query) => self.record_write(),
+            SessionEffect::None => {}
+            // The statement can't be replayed without its parameters
+            SessionEffect::Replay if !params.is_empty() => self.pin(),
+            _ => {
+                let query = query.clone();
+                self.record(&query);
+            }
+        }
+    }
+
+    /// Remember a statement prepared by the client, so that it can be prepared on a replica if
+    /// it's read-only, and so that executing it updates the session's state
+    pub(crate) fn add_statement(
+        &mut self,
+        statement_id: u32,
+        query: &str,
+        data: U::OwnedPrepareData,
+    ) {
+        self.statements
+            .insert(statement_id, (query.to_owned(), data));
+    }
+
+    pub(crate) fn remove_statement(&mut self, statement_id: &DeallocateId) {
+        match statement_id {
+            DeallocateId::Numeric(statement_id) => {
+                self.statements.remove(statement_id);
+            }
+            DeallocateId::All => self.statements.clear(),
+            DeallocateId::Named(_) => {}
+        }
+    }
+
+    /// Forget all session state, because the client's connection to the upstream database was
+    /// reset
+    pub(crate) fn reset(&mut self) {
+        self.statements.clear();
+        self.session_state.clear();
+        self.in_transaction = false;
+        self.pinned = false;
+        self.last_write = None;
+        self.wrote_in_transaction = false;
+        self.close_all();
+    }
+
+    /// Pick a replica for a read-only statement, making sure this session's connection to it is
+    /// open and up to date. Returns None if the statement should run on the upstream database.
+    async fn ready_replica(&mut self) -> Option<usize> {
+        if self.in_transaction || self.pinned {
+            return None;
+        }
+        if self
+            .last_write
+            .is_some_and(|at| at.elapsed() < self.replicas.inner.max_lag)
+        {
+            return None;
+        }
+        let idx = self.replicas.pick()?;
+        let generation = self.replicas.generation(idx);
+
+        // Connections opened before the replica last became unhealthy may well be broken
+        if !matches!(&self.conns[idx], Some(conn) if conn.generation == generation) {
+            let mut upstream_config = self.replicas.inner.replicas[idx].upstream_config.clone();
+            if let Some((user, password)) = &self.credentials {
+                upstream_config = match upstream_config.with_credentials(user, password) {
+                    Ok(config) => config,
+                    Err(error) => {
+                        warn!(%error, "Could not set upstream replica credentials");
+                        return None;
+                    }
+                };
+            }
+            match U::connect(upstream_config).await {
+                Ok(conn) => {
+                    self.conns[idx] = Some(ReplicaConnection {
+                        conn,
+                        generation,
+                        applied: 0,
+                        statements: HashMap::new(),
+                    })
+                }
+                Err(error) => {
+                    warn!(replica = idx, %error, "Could not connect to upstream replica");
+                    self.replicas.set_healthy(idx, false);
+                    return None;
+                }
+            }
+        }
+
+        let conn = self.conns[idx].as_mut()?;
+
+        for (seq, statement) in self.session_state.since(conn.applied) {
+            if let Err(error) = conn.conn.query(statement).await {
+                warn!(
+                    replica = idx,
+                    %error,
+                    "Could not replay session state on upstream replica"
+                );
+                self.pinned = true;
+                return None;
+            }
+            conn.applied = seq;
+        }
+
+        Some(idx)
+    }
+
+    /// Run `query` on a replica, if it's read-only and a replica is available. Returns None if the
+    /// query should run on the upstream database instead.
+    pub(crate) async fn query<'a>(
+        &'a mut self,
+        query: &'a str,
+        simple: bool,
+    ) -> Option<Result<U::QueryResult<'a>, U::Error>> {
+        if !is_read_only(U::SQL_DIALECT, query) {
+            return None;
+        }
+        let idx = self.ready_replica().await?;
+
+        let Self {
+            conns, replicas, ..
+        } = self;
+        let conn = &mut conns[idx].as_mut()?.conn;
+        let result = if simple {
+            conn.simple_query(query).await
+        } else {
+            conn.query(query).await
+        };
+        replicas.note_result(idx, &result);
+        Some(result)
+    }
+
+    /// Prepare the statement the client prepared with `statement_id` on a replica, if it's
+    /// read-only and a replica is available. Returns the index of the replica and the ID of the
+    /// statement on it, or None if the statement should run on the upstream database instead.
+    pub(crate) async fn ready_statement(&mut self, statement_id: u32) -> Option<(usize, u32)> {
+        let key = self
+            .statements
+            .get(&statement_id)
+            .filter(|(query, _)| is_read_only(U::SQL_DIALECT, query))?
+            .clone();
+        let idx = self.ready_replica().await?;
+        let conn = self.conns[idx].as_mut()?;
+        if let Some(id) = conn.statements.get(&key) {
+            return Some((idx, *id));
+        }
+
+        let result = conn
+            .conn
+            .prepare(key.0.as_str(), U::borrow_prepare_data(&key.1))
+            .await;
+        match result {
+            Ok(prepare) => {
+                conn.statements.insert(key, prepare.statement_id);
+                Some((idx, prepare.statement_id))
+            }
+            Err(error) => {
+                debug!(
+                    replica = idx,
+                    %error,
+                    "Could not prepare statement on upstream replica"
+                );
+                if error.is_fatal() {
+                    self.replicas.set_healthy(idx, false);
+                }
+                None
+            }
+        }
+    }
+
+    /// Execute a statement returned by [`ready_statement`](Self::ready_statement)
+    pub(crate) async fn execute<'a>(
+        &'a mut self,
+        idx: usize,
+        replica_statement_id: u32,
+        params: &[DfValue],
+        exec_meta: U::ExecMeta<'_>,
+    ) -> Result<U::QueryResult<'a>, U::Error> {
+        let Self {
+            conns, replicas, ..
+        } = self;
+        let Some(conn) = conns[idx].as_mut() else {
+            return Err(ReadySetError::Internal(format!(
+                "No connection to upstream replica {idx} to execute statement on"
+            ))
+            .into());
+        };
+        let conn = &mut conn.conn;
+        let result = conn.execute(replica_statement_id, params, exec_meta).await;
+        replicas.note_result(idx, &result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_upstream::{self, FakeUpstream};
+
+    fn session(urls: &[&str]) -> ReplicaSession<FakeUpstream> {
+        let replicas = UpstreamReplicas::new(
+            urls.iter().map(|url| fake_upstream::config(url)).collect(),
+            Duration::from_secs(1),
+        );
+        for idx in 0..urls.len() {
+            replicas.set_healthy(idx, true);
+        }
+        ReplicaSession::new(replicas)
+    }
+
+    /// Returns the URL and statement of each entry in the fake upstream's log
+    fn log() -> Vec<(String, String)> {
+        fake_upstream::take_log()
+            .into_iter()
+            .map(|entry| (entry.url, entry.statement))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn routes_reads_to_replicas() {
+        let mut session = session(&["fake://a", "fake://b"]);
+        log();
+
+        session.record("SET search_path = foo");
+        assert!(session.query("SELECT 1", false).await.is_some());
+        assert!(session.query("SELECT 2", false).await.is_some());
+        assert!(session
+            .query("INSERT INTO t VALUES (1)", false)
+            .await
+            .is_none());
+        assert!(session
+            .query("SELECT * FROM t FOR UPDATE", false)
+            .await
+            .is_none());
+
+        let mut entries = log();
+        entries.sort();
+        assert_eq!(
+            entries,
+            vec![
+                ("fake://a".to_owned(), "SELECT 1".to_owned()),
+                ("fake://a".to_owned(), "SET search_path = foo".to_owned()),
+                ("fake://b".to_owned(), "SELECT 2".to_owned()),
+                ("fake://b".to_owned(), "SET search_path = foo".to_owned()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_unhealthy_replicas() {
+        fake_upstream::set_replication_lag("fake://lagging", Some(Duration::from_secs(10)));
+        fake_upstream::set_replication_lag("fake://stopped", None);
+        let mut session = session(&["fake://lagging", "fake://stopped"]);
+        for idx in 0..2 {
+            let healthy = session.replicas.check(idx, &mut None).await;
+            session.replicas.set_healthy(idx, healthy);
+        }
+        assert!(session.query("SELECT 1", false).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn transactions_stay_upstream() {
+        let mut session = session(&["fake://a"]);
+
+        session.record("BEGIN");
+        assert!(session.query("SELECT 1", false).await.is_none());
+        session.record("COMMIT");
+        assert!(session.query("SELECT 1", false).await.is_some());
+
+        session.add_statement(0, "START TRANSACTION", ());
+        session.add_statement(1, "SELECT 1", ());
+        session.add_statement(2, "COMMIT", ());
+        session.record_execute(0, &[]);
+        assert!(session.ready_statement(1).await.is_none());
+        session.record_execute(2, &[]);
+        assert!(session.ready_statement(1).await.is_some());
+
+        session.record("CREATE TEMPORARY TABLE t (x int)");
+        assert!(session.query("SELECT 1", false).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn reads_after_writes_stay_upstream() {
+        let mut session = session(&["fake://a"]);
+        session.record("INSERT INTO t VALUES (1)");
+        assert!(session.query("SELECT * FROM t", false).await.is_none());
+
+        session.add_statement(0, "SELECT * FROM t", ());
+        assert!(session.ready_statement(0).await.is_none());
+
+        // Replicas allowed to lag behind by no time at all have every write already
+        let replicas =
+            UpstreamReplicas::new(vec![fake_upstream::config("fake://a")], Duration::ZERO);
+        replicas.set_healthy(0, true);
+        let mut session = ReplicaSession::<FakeUpstream>::new(replicas);
+        session.record("INSERT INTO t VALUES (1)");
+        assert!(session.query("SELECT * FROM t", false).await.is_some());
+    }
+
+    #[test]
+    fn read_only_statements() {
+        assert!(is_read_only(Dialect::MySQL, "SELECT * FROM t WHERE x = 1"));
+        assert!(is_read_only(Dialect::MySQL, "select count(*) from t"));
+        assert!(is_read_only(Dialect::MySQL, "/* app */ SELECT 1"));
+        assert!(is_read_only(Dialect::MySQL, "(SELECT 1) UNION (SELECT 2)"));
+        assert!(is_read_only(Dialect::MySQL, "SELECT * FROM updates"));
+
+        assert!(!is_read_only(Dialect::MySQL, "INSERT INTO t VALUES (1)"));
+        assert!(!is_read_only(Dialect::MySQL, "UPDATE t SET x = 1"));
+        assert!(!is_read_only(Dialect::MySQL, "SELECT * FROM t FOR UPDATE"));
+        assert!(!is_read_only(Dialect::MySQL, "SELECT * FROM t\nFOR  SHARE"));
+        assert!(!is_read_only(
+            Dialect::MySQL,
+            "SELECT * FROM t LOCK IN SHARE MODE"
+        ));
+        assert!(!is_read_only(Dialect::MySQL, "SELECT * INTO t2 FROM t"));
+        assert!(!is_read_only(Dialect::MySQL, "SELECT x INTO @x FROM t"));
+        assert!(!is_read_only(Dialect::MySQL, "SELECT nextval('seq')"));
+        assert!(!is_read_only(Dialect::MySQL, "SELECT LAST_INSERT_ID()"));
+        assert!(!is_read_only(Dialect::MySQL, "SELECT pg_advisory_lock(1)"));
+        assert!(!is_read_only(
+            Dialect::PostgreSQL,
+            "WITH d AS (DELETE FROM t RETURNING *) SELECT * FROM d"
+        ));
+
+        // Keywords in strings and comments don't count
+        assert!(is_read_only(
+            Dialect::MySQL,
+            "SELECT 'for update', `into` FROM t # FOR UPDATE"
+        ));
+        assert!(is_read_only(
+            Dialect::PostgreSQL,
+            "SELECT $$nextval($$ /* INTO */ FROM t"
+        ));
+    }
+}
@@ -0,0 +1,64 @@
+//! Tests for loading table privileges from a real upstream database
+
+use std::env;
+
+use database_utils::{DatabaseURL, QueryableConnection};
+use nom_sql::{parse_select_statement, Dialect};
+use readyset_adapter::privileges::{Grants, TablePrivileges};
+
+fn mysql_url() -> DatabaseURL {
+    format!(
+        "mysql://root:noria@{}:{}/mysql",
+        env::var("MYSQL_HOST").unwrap_or_else(|_| "127.0.0.1".into()),
+        env::var("MYSQL_TCP_PORT").unwrap_or_else(|_| "3306".into()),
+    )
+    .parse()
+    .unwrap()
+}
+
+fn can_read(privileges: &TablePrivileges, user: &str, query: &str) -> bool {
+    let statement = parse_select_statement(Dialect::MySQL, query).unwrap();
+    privileges.can_read(user, &statement, &["privileges_roles".into()])
+}
+
+/// Users get the privileges of their default roles, and of the roles granted to those roles, but
+/// not of roles they have to activate themselves. Needs MySQL 8.0 or later, with
+/// `activate_all_roles_on_login` disabled (as it is by default).
+#[tokio::test(flavor = "multi_thread")]
+async fn mysql_roles() {
+    let mut conn = mysql_url().connect(None).await.unwrap();
+    for q in [
+        "DROP DATABASE IF EXISTS privileges_roles",
+        "CREATE DATABASE privileges_roles",
+        "CREATE TABLE privileges_roles.t1 (x INT)",
+        "CREATE TABLE privileges_roles.t2 (x INT)",
+        "CREATE TABLE privileges_roles.t3 (x INT)",
+        "DROP USER IF EXISTS role_default, role_granted",
+        "DROP ROLE IF EXISTS privileges_reader, privileges_nested",
+        "CREATE ROLE privileges_reader, privileges_nested",
+        "GRANT SELECT ON privileges_roles.t1 TO privileges_reader",
+        "GRANT SELECT ON privileges_roles.t2 TO privileges_nested",
+        "GRANT privileges_nested TO privileges_reader",
+        "CREATE USER role_default, role_granted",
+        "GRANT privileges_reader TO role_default, role_granted",
+        "SET DEFAULT ROLE privileges_reader TO role_default",
+    ] {
+        conn.query_drop(q).await.unwrap();
+    }
+
+    let privileges = TablePrivileges::new();
+    privileges.set(Grants::load(&mut conn).await.unwrap());
+
+    assert!(can_read(&privileges, "role_default", "SELECT * FROM t1"));
+    assert!(can_read(&privileges, "role_default", "SELECT * FROM t2"));
+    assert!(!can_read(&privileges, "role_default", "SELECT * FROM t3"));
+    assert!(!can_read(&privileges, "role_granted", "SELECT * FROM t1"));
+
+    for q in [
+        "DROP USER role_default, role_granted",
+        "DROP ROLE privileges_reader, privileges_nested",
+        "DROP DATABASE privileges_roles",
+    ] {
+        conn.query_drop(q).await.unwrap();
+    }
+}
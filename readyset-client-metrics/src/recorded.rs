@@ -72,6 +72,67 @@ pub const CLIENT_CONNECTIONS_CLOSED: &str = "readyset_noria_client_conns_closed"
 /// on behalf of client connections.
 pub const CLIENT_UPSTREAM_CONNECTIONS: &str = "readyset_client_upstream_connections";
 
+/// Gauge: The number of connections to the upstream database currently open in the adapter's
+/// shared upstream connection pool, whether leased to a client or idle.
+pub const UPSTREAM_POOL_CONNECTIONS: &str = "readyset_upstream_pool_connections";
+
+/// Gauge: The number of connections to the upstream database sitting idle in the adapter's shared
+/// upstream connection pool.
+pub const UPSTREAM_POOL_IDLE_CONNECTIONS: &str = "readyset_upstream_pool_idle_connections";
+
+/// Counter: The number of times a client leased a connection from the adapter's shared upstream
+/// connection pool.
+pub const UPSTREAM_POOL_LEASES: &str = "readyset_upstream_pool_leases";
+
+/// Histogram: The time in seconds clients spent waiting to lease a connection from the adapter's
+/// shared upstream connection pool.
+pub const UPSTREAM_POOL_WAIT_TIME: &str = "readyset_upstream_pool_wait_time";
+
+/// Counter: The number of times a client gave up waiting to lease a connection from the adapter's
+/// shared upstream connection pool.
+pub const UPSTREAM_POOL_TIMEOUTS: &str = "readyset_upstream_pool_timeouts";
+
+/// Counter: The number of client connections that were pinned to a single pooled upstream
+/// connection for the rest of their session, because they created session state (such as
+/// temporary tables or locks) that can't be moved between upstream connections.
+pub const UPSTREAM_POOL_PINNED_SESSIONS: &str = "readyset_upstream_pool_pinned_sessions";
+
+/// Counter: The number of proxied queries that were run on a read replica of the upstream
+/// database, rather than on the upstream database itself.
+///
+/// | Tag | Description |
+/// | --- | ----------- |
+/// | replica | The index of the replica in the list of configured replica URLs. |
+pub const UPSTREAM_REPLICA_QUERIES: &str = "readyset_upstream_replica_queries";
+
+/// Gauge: How far behind the upstream database, in seconds, a read replica was when it was last
+/// checked.
+///
+/// | Tag | Description |
+/// | --- | ----------- |
+/// | replica | The index of the replica in the list of configured replica URLs. |
+pub const UPSTREAM_REPLICA_LAG: &str = "readyset_upstream_replica_lag";
+
+/// Gauge: Whether a read replica of the upstream database is currently receiving reads. 1 if the
+/// replica is reachable and within the configured maximum lag, 0 otherwise.
+///
+/// | Tag | Description |
+/// | --- | ----------- |
+/// | replica | The index of the replica in the list of configured replica URLs. |
+pub const UPSTREAM_REPLICA_HEALTHY: &str = "readyset_upstream_replica_healthy";
+
+/// Gauge: An upper bound, in seconds, on how long ago writes which have not been replicated into
+/// caches yet were committed in the upstream database. Only recorded once ReadySet has replicated
+/// up to a position read from the upstream.
+pub const CACHE_STALENESS: &str = "readyset_cache_staleness";
+
+/// Counter: The number of statements which carried a `readyset:` hint comment.
+///
+/// | Tag | Description |
+/// | --- | ----------- |
+/// | hint | The hint: `proxy`, `cache-only`, or `max_staleness`. |
+pub const QUERY_HINTS: &str = "readyset_query_hints";
+
 /// Counter: The number of `EventType` operations received (query / prepare / execute).
 pub const QUERY_LOG_EVENT_TYPE: &str = "readyset_query_log_event_type";
 
@@ -94,6 +94,7 @@ impl Adapter for MySQLAdapter {
             Backend {
                 noria: backend,
                 enable_statement_logging: false,
+                compression_threshold: None,
             },
             s,
             false,
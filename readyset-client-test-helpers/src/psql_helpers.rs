@@ -109,9 +109,11 @@ pub async fn last_query_info(conn: &Client) -> QueryInfo {
 
     let destination = QueryDestination::try_from(row.get("Query_destination").unwrap()).unwrap();
     let noria_error = row.get("ReadySet_error").unwrap().to_owned();
+    let hints = row.get("Query_hints").unwrap().to_owned();
 
     QueryInfo {
         destination,
         noria_error,
+        hints,
     }
 }
@@ -0,0 +1,75 @@
+//! Types describing a backup of a ReadySet deployment's state.
+//!
+//! A backup is a directory containing:
+//!
+//! * A [RocksDB checkpoint][checkpoint] of the persistent state of every base table, in the
+//!   [`TABLES_DIR`] subdirectory. Each checkpoint contains both the rows of that table and the
+//!   replication offset they were written at.
+//! * The controller state (the dataflow graph and the recipe), in [`CONTROLLER_STATE_FILE`].
+//! * The `CREATE CACHE` and `DROP CACHE` statements run against the deployment, in
+//!   [`CACHE_DDL_REQUESTS_FILE`].
+//! * A [`BackupManifest`] describing all of the above, in [`MANIFEST_FILE`]. The manifest is
+//!   written last, so a directory without one is an incomplete backup.
+//!
+//! The base tables are checkpointed by the workers running them, so backups can only be taken of
+//! deployments whose base tables all run on the same host as the controller.
+//!
+//! [checkpoint]: https://github.com/facebook/rocksdb/wiki/Checkpoints
+
+use std::cmp::Ordering;
+
+use nom_sql::Relation;
+use replication_offset::ReplicationOffset;
+use serde::{Deserialize, Serialize};
+
+/// The version of the backup format written by this version of ReadySet
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Name of the file, within a backup directory, containing the [`BackupManifest`]
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Name of the file, within a backup directory, containing the serialized controller state
+pub const CONTROLLER_STATE_FILE: &str = "controller_state.msgpack";
+
+/// Name of the file, within a backup directory, containing the cache DDL requests
+pub const CACHE_DDL_REQUESTS_FILE: &str = "cache_ddl_requests.json";
+
+/// Name of the directory, within a backup directory, containing the base table checkpoints
+pub const TABLES_DIR: &str = "tables";
+
+/// A description of the contents of a backup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// The version of the backup format, compared against [`BACKUP_FORMAT_VERSION`] on restore
+    pub format_version: u32,
+    /// The prefix of the file names of the base table checkpoints, which is derived from the name
+    /// of the deployment the backup was taken from
+    pub db_filename_prefix: String,
+    /// The replication offset of the schema at the time of the backup
+    pub schema_replication_offset: Option<ReplicationOffset>,
+    /// The base tables included in the backup
+    pub tables: Vec<BackupTable>,
+}
+
+/// A single base table (shard) included in a backup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupTable {
+    /// The name of the table
+    pub table: Relation,
+    /// The name of the checkpoint of the table, within the [`TABLES_DIR`] of the backup
+    pub file_name: String,
+    /// The replication offset the table's checkpoint was taken at
+    pub replication_offset: Option<ReplicationOffset>,
+}
+
+impl BackupManifest {
+    /// Returns the minimum replication offset of all the tables in the backup, which is the
+    /// position that replication will resume from once the backup is restored.
+    pub fn min_replication_offset(&self) -> Option<&ReplicationOffset> {
+        self.tables
+            .iter()
+            .filter_map(|t| t.replication_offset.as_ref())
+            .chain(self.schema_replication_offset.as_ref())
+            .min_by(|a, b| a.try_partial_cmp(b).unwrap_or(Ordering::Equal))
+    }
+}
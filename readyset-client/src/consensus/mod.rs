@@ -283,6 +283,23 @@ pub trait AuthorityControl: Send + Sync {
         .await
     }
 
+    /// Replaces the provided statement in the store with `new_cache_ddl_req`, keeping its position
+    /// relative to the other stored statements.
+    async fn replace_cache_ddl_request(
+        &self,
+        cache_ddl_req: CacheDDLRequest,
+        new_cache_ddl_req: CacheDDLRequest,
+    ) -> ReadySetResult<()> {
+        let cache_ddl_req = serde_json::ser::to_string(&cache_ddl_req)?;
+        let new_cache_ddl_req = serde_json::ser::to_string(&new_cache_ddl_req)?;
+        modify_cache_ddl_requests(self, move |stmts| {
+            for stmt in stmts.iter_mut().filter(|stmt| **stmt == cache_ddl_req) {
+                stmt.clone_from(&new_cache_ddl_req);
+            }
+        })
+        .await
+    }
+
     /// Removes all stored cache ddl requests
     async fn remove_all_cache_ddl_requests(&self) -> ReadySetResult<()> {
         modify_cache_ddl_requests(self, move |stmts| {
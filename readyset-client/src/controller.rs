@@ -2,6 +2,7 @@ use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::future::Future;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
@@ -11,6 +12,7 @@ use hyper::client::HttpConnector;
 use nom_sql::{NonReplicatedRelation, Relation};
 use parking_lot::RwLock;
 use petgraph::graph::NodeIndex;
+use readyset_data::Dialect;
 use readyset_errors::{
     internal, internal_err, rpc_err, rpc_err_no_downcast, ReadySetError, ReadySetResult,
 };
@@ -24,6 +26,7 @@ use tower_service::Service;
 use tracing::{debug, trace};
 use url::Url;
 
+use crate::backup::BackupManifest;
 use crate::consensus::{Authority, AuthorityControl};
 use crate::debug::info::{GraphInfo, MaterializationInfo, NodeSize};
 use crate::debug::stats;
@@ -330,7 +333,7 @@ impl Default for GraphvizOptions {
 pub struct ReadySetHandle {
     handle: tower::util::Either<Controller, RawController>,
     domains: Arc<Mutex<HashMap<(SocketAddr, usize), TableRpc>>>,
-    views: Arc<Mutex<HashMap<(SocketAddr, usize), ViewRpc>>>,
+    views: Arc<Mutex<HashMap<(Vec<SocketAddr>, usize), ViewRpc>>>,
     request_timeout: Option<Duration>,
     migration_timeout: Option<Duration>,
 }
@@ -783,6 +786,44 @@ impl ReadySetHandle {
         remove_query(name: &Relation) -> u64
     );
 
+    /// Rebuild all the dataflow nodes for the cached query with the given name, sharded `shards`
+    /// ways. The existing nodes for the query keep serving reads until the new ones are ready.
+    ///
+    /// Any other caches that share dataflow nodes with the query are resharded along with it. The
+    /// number of shards is recorded in the stored `CREATE CACHE` statements for the resharded
+    /// caches, so they keep it if they're ever recreated.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn reshard_query(
+        &mut self,
+        name: &Relation,
+        shards: usize,
+        dialect: Dialect,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc(
+            "reshard_query",
+            (name, shards, dialect),
+            self.migration_timeout,
+        )
+    }
+
+    /// Change the number of times the reader for the cached query with the given name is
+    /// replicated. Replicas are added or removed in place, and the rest of the reader replicas
+    /// keep serving reads while that happens.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn set_reader_replicas(
+        &mut self,
+        name: &Relation,
+        replicas: usize,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc(
+            "set_reader_replicas",
+            (name, replicas),
+            self.migration_timeout,
+        )
+    }
+
     simple_request!(
         /// Remove all non-base nodes from the graph
         ///
@@ -790,6 +831,66 @@ impl ReadySetHandle {
         remove_all_queries()
     );
 
+    /// Start replicating the given tables from the upstream database, returning an error if any
+    /// of them don't exist upstream. The tables are snapshotted in the background, without
+    /// interrupting replication of any other tables.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn add_replicated_tables(
+        &mut self,
+        tables: Vec<Relation>,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc("add_replicated_tables", tables, self.migration_timeout)
+    }
+
+    /// Stop replicating the given tables from the upstream database, and remove them (along with
+    /// any caches that depend on them) from ReadySet. Returns once the tables have been removed.
+    ///
+    /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+    pub fn drop_replicated_tables(
+        &mut self,
+        tables: Vec<Relation>,
+    ) -> impl Future<Output = ReadySetResult<()>> + '_ {
+        self.rpc("drop_replicated_tables", tables, self.migration_timeout)
+    }
+
+    simple_request!(
+        /// Record that the given tables were added to replication (if `replicate` is true) or
+        /// dropped from it at runtime, so that the change outlives the replicator.
+        ///
+        /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+        record_replicated_tables(tables: Vec<Relation>, replicate: bool,) -> ()
+    );
+
+    simple_request!(
+        /// Get the tables that were added to (`true`) or dropped from (`false`) replication at
+        /// runtime, which take precedence over the tables the replicator is configured to
+        /// replicate.
+        ///
+        /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+        replicated_table_changes() -> HashMap<Relation, bool>
+    );
+
+    simple_request!(
+        /// Discard all data for the given table, and snapshot it again from the upstream
+        /// database without interrupting replication of any other tables.
+        ///
+        /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+        resnapshot_table(table: Relation) -> ()
+    );
+
+    simple_request!(
+        /// Write a backup of the state of the deployment - a checkpoint of every base table, along
+        /// with the controller state and the recipe - into the given directory, which must not
+        /// exist or be empty. The directory is written on the host running the controller, which
+        /// must also be running every base table.
+        ///
+        /// See [the `backup` module](crate::backup) for more information.
+        ///
+        /// `Self::poll_ready` must have returned `Async::Ready` before you call this method.
+        backup(dir: PathBuf) -> BackupManifest
+    );
+
     simple_request!(
         /// Set the replication offset for the schema, which is stored with the recipe.
         ///
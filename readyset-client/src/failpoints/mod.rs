@@ -35,3 +35,6 @@ pub const START_INNER_POSTGRES: &str = "start-inner-postgres";
 pub const LOAD_CONTROLLER_STATE: &str = "load-controller-state";
 /// Injects a failpoint at the beginning of DfState::extend_recipe
 pub const EXTEND_RECIPE: &str = "extend-recipe";
+/// Imitates a failure of a domain which contains neither base tables nor readers while it's
+/// processing a packet
+pub const DOMAIN_PROCESS_PACKET: &str = "domain-process-packet";
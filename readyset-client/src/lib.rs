@@ -254,6 +254,7 @@ use tokio_tower::multiplex;
 #[cfg(feature = "failure_injection")]
 pub mod failpoints;
 
+pub mod backup;
 pub mod consistency;
 mod controller;
 pub mod metrics;
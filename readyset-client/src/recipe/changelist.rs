@@ -201,6 +201,8 @@ impl ChangeList {
                                 name,
                                 inner,
                                 always,
+                                replicas,
+                                shards,
                                 ..
                             }) => {
                                 let statement = match inner {
@@ -223,6 +225,8 @@ impl ChangeList {
                                     name,
                                     statement,
                                     always,
+                                    replicas,
+                                    shards,
                                 }))
                             }
                             SqlQuery::AlterTable(ats) => changes.push(Change::AlterTable(ats)),
@@ -327,6 +331,12 @@ pub struct CreateCache {
     /// If set to `true`, execution of this cache will bypass transaction handling in the
     /// adapter
     pub always: bool,
+    /// The number of times to replicate the reader for this cache, overriding the replication
+    /// strategy of the deployment
+    pub replicas: Option<usize>,
+    /// The number of shards to shard the dataflow nodes for this cache by, overriding the sharding
+    /// of the deployment
+    pub shards: Option<usize>,
 }
 
 /// Metadata about a PostgreSQL table
@@ -422,6 +432,8 @@ impl Change {
             name: Some(name.into()),
             statement: Box::new(statement),
             always,
+            replicas: None,
+            shards: None,
         })
     }
 
@@ -512,6 +524,8 @@ impl Change {
                         name,
                         inner,
                         always,
+                        replicas,
+                        shards,
                         ..
                     }) => {
                         let mut statement = match inner {
@@ -538,6 +552,8 @@ impl Change {
                             name,
                             statement,
                             always,
+                            replicas,
+                            shards,
                         })
                     }
                     SqlQuery::DropCache(dcs) => Change::Drop {
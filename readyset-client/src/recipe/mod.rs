@@ -109,6 +109,10 @@ impl From<CacheExpr> for CreateCacheStatement {
             name: Some(value.name),
             inner: Ok(CacheInner::Statement(Box::new(value.statement))),
             always: value.always,
+            // The number of reader replicas and shards of a cache are tracked by the controller,
+            // rather than in the recipe
+            replicas: None,
+            shards: None,
             // CacheExpr represents a migrated query, and the below fields are not relevant for an
             // already-migrated query
             concurrently: false,
@@ -24,8 +24,6 @@ use nom_sql::{
 };
 use petgraph::graph::NodeIndex;
 use proptest::arbitrary::Arbitrary;
-use rand::prelude::IteratorRandom;
-use rand::thread_rng;
 use readyset_data::{Bound, BoundedRange, DfType, DfValue, IntoBoundedRange, RangeBounds};
 use readyset_errors::{
     internal, internal_err, rpc_err, unsupported, view_err, ReadySetError, ReadySetResult,
@@ -44,7 +42,7 @@ use tower::buffer::Buffer;
 use tower::limit::concurrency::ConcurrencyLimit;
 use tower::timeout::Timeout;
 use tower_service::Service;
-use tracing::{debug_span, error, instrument, trace};
+use tracing::{debug_span, error, instrument, trace, warn};
 use tracing_futures::Instrument;
 use vec1::{vec1, Vec1};
 
@@ -340,7 +338,7 @@ impl ColumnSchema {
 }
 
 fn make_views_stream(
-    addr: SocketAddr,
+    addrs: Vec<SocketAddr>,
     timeout: Duration,
 ) -> impl futures_util::stream::TryStream<
     Ok = tower::discover::Change<usize, InnerService>,
@@ -348,16 +346,46 @@ fn make_views_stream(
 > {
     // TODO: use whatever comes out of https://github.com/tower-rs/tower/issues/456 instead of
     // creating _all_ the connections every time.
-    (0..crate::VIEW_POOL_SIZE)
-        .map(|i| async move {
-            let svc = Endpoint { addr, timeout }.call(()).await?;
-            Ok(tower::discover::Change::Insert(i, svc))
+    let connections = addrs.len() * crate::VIEW_POOL_SIZE;
+    let mut failed = 0;
+    addrs
+        .into_iter()
+        .enumerate()
+        .flat_map(|(replica, addr)| {
+            (0..crate::VIEW_POOL_SIZE).map(move |i| async move {
+                let svc = Endpoint { addr, timeout }.call(()).await?;
+                Ok::<_, tokio::io::Error>(tower::discover::Change::Insert(
+                    replica * crate::VIEW_POOL_SIZE + i,
+                    svc,
+                ))
+            })
         })
         .collect::<futures_util::stream::FuturesUnordered<_>>()
+        .filter_map(move |res| {
+            // An error from the stream fails the whole `Balance`, so leave out connections to
+            // replicas that can't be reached (for example because they were just removed) and
+            // balance across the rest, unless there aren't any. Connections that fail later on are
+            // dropped by `Balance` itself.
+            future::ready(match res {
+                Ok(change) => Some(Ok(change)),
+                Err(error) => {
+                    failed += 1;
+                    if failed == connections {
+                        Some(Err(error))
+                    } else {
+                        warn!(%error, "Could not connect to reader replica");
+                        None
+                    }
+                }
+            })
+        })
 }
 
-fn make_views_discover(addr: SocketAddr, timeout: Duration) -> Discover {
-    make_views_stream(addr, timeout)
+/// Discover connections to all of the given `addrs`, which should be the addresses of the
+/// replicas of a single reader shard, so that requests to that shard are load-balanced across all
+/// of its replicas
+fn make_views_discover(addrs: Vec<SocketAddr>, timeout: Duration) -> Discover {
+    make_views_stream(addrs, timeout)
 }
 
 // Unpin + Send bounds are needed due to https://github.com/rust-lang/rust/issues/55997
@@ -925,59 +953,56 @@ impl ReaderHandleBuilder {
     /// Build a [`ReaderHandle`] out of a [`ReaderHandleBuilder`].
     ///
     /// If `replica` is specified, this selects the reader replica with that index, returning an
-    /// error if the index is out of bounds. Otherwise, a replica is selected at random
+    /// error if the index is out of bounds. Otherwise, reads to each shard are load-balanced
+    /// across all of the running replicas of that shard
     pub async fn build(
         &self,
         replica: Option<usize>,
-        rpcs: Arc<Mutex<HashMap<(SocketAddr, usize), ViewRpc>>>,
+        rpcs: Arc<Mutex<HashMap<(Vec<SocketAddr>, usize), ViewRpc>>>,
     ) -> ReadySetResult<ReaderHandle> {
         let node = self.node;
 
-        let shards = match replica {
-            Some(replica) => self.replica_shard_addrs.get(replica),
-            None if self.replica_shard_addrs.num_rows() == 1 => Some(&self.replica_shard_addrs[0]),
-            None => self
-                .replica_shard_addrs
-                .rows()
-                .filter(|a| a.iter().all(|addr| addr.is_some()))
-                .choose(&mut thread_rng()),
-        }
-        .ok_or_else(|| {
-            if replica.is_none() && self.replica_shard_addrs.num_rows() != 1 {
-                ReadySetError::ReaderReplicaNotRunning { replica: 0, node }
-            } else {
+        let replica_rows = match replica {
+            Some(replica) => vec![self.replica_shard_addrs.get(replica).ok_or_else(|| {
                 ReadySetError::ViewReplicaOutOfBounds {
-                    replica: replica.unwrap_or(0),
+                    replica,
                     view_name: self.name.clone().display_unquoted().to_string(),
                     num_replicas: self.replica_shard_addrs.num_rows(),
                 }
-            }
-        })?;
+            })?],
+            None => self.replica_shard_addrs.rows().collect(),
+        };
 
         let columns = self.columns.clone();
         let schema = self.schema.clone();
         let key_mapping = self.key_mapping.clone();
 
-        let mut addrs = Vec::with_capacity(shards.len());
-        let mut conns = Vec::with_capacity(shards.len());
+        let num_shards = self.replica_shard_addrs.row_size();
+        let mut addrs = Vec::with_capacity(num_shards);
+        let mut conns = Vec::with_capacity(num_shards);
 
-        for (shardi, shard_addr) in shards.iter().enumerate() {
+        for shardi in 0..num_shards {
             use std::collections::hash_map::Entry;
 
-            let Some(shard_addr) = *shard_addr else {
+            // The addresses of all the running replicas of this shard
+            let shard_addrs = replica_rows
+                .iter()
+                .filter_map(|row| row.get(shardi).copied().flatten())
+                .collect::<Vec<_>>();
+            let Some(first_addr) = shard_addrs.first().copied() else {
                 return Err(ReadySetError::ReaderReplicaNotRunning {
                     replica: replica.unwrap_or(0),
                     node,
                 });
             };
 
-            addrs.push(shard_addr);
+            addrs.push(first_addr);
 
             // one entry per shard so that we can send sharded requests in parallel even if
             // they happen to be targeting the same machine.
             let mut rpcs = rpcs.lock().await;
             #[allow(clippy::significant_drop_in_scrutinee)]
-            let s = match rpcs.entry((shard_addr, shardi)) {
+            let s = match rpcs.entry((shard_addrs.clone(), shardi)) {
                 Entry::Occupied(e) => e.get().clone(),
                 Entry::Vacant(h) => {
                     // TODO: maybe always use the same local port?
@@ -985,7 +1010,7 @@ impl ReaderHandleBuilder {
                         Timeout::new(
                             ConcurrencyLimit::new(
                                 Balance::new(make_views_discover(
-                                    shard_addr,
+                                    shard_addrs,
                                     self.view_request_timeout,
                                 )),
                                 crate::PENDING_LIMIT,
@@ -996,7 +1021,7 @@ impl ReaderHandleBuilder {
                     );
                     tokio::spawn(w.instrument(debug_span!(
                         "view_worker",
-                        addr = %shard_addr,
+                        addr = %first_addr,
                         shard = shardi
                     )));
                     h.insert(c.clone());
@@ -1028,7 +1053,7 @@ impl ViewBuilder {
     pub async fn build(
         &self,
         replica: Option<usize>,
-        rpcs: Arc<Mutex<HashMap<(SocketAddr, usize), ViewRpc>>>,
+        rpcs: Arc<Mutex<HashMap<(Vec<SocketAddr>, usize), ViewRpc>>>,
     ) -> ReadySetResult<View> {
         match self {
             ViewBuilder::Single(builder) => Ok(View::Single(builder.build(replica, rpcs).await?)),
@@ -1357,7 +1382,10 @@ impl ReaderHandle {
         &self.name
     }
 
-    /// Returns a reference to the list of socket addresses for the view's shards
+    /// Returns a reference to the list of socket addresses for the view's shards.
+    ///
+    /// If reads to a shard are load-balanced across multiple replicas, this is the address of the
+    /// first of those replicas
     #[must_use]
     pub fn shard_addrs(&self) -> &[SocketAddr] {
         self.shard_addrs.as_ref()
@@ -1,9 +1,10 @@
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::sync::Arc;
 
 use dataflow_expression::{Expr, PostLookup, PostLookupAggregates};
 use nom_sql::OrderType;
-use readyset_data::DfValue;
+use readyset_data::{CompactRow, DfValue};
 use readyset_util::nonmaxusize::NonMaxUsize;
 use smallvec::SmallVec;
 use streaming_iterator::StreamingIterator;
@@ -18,7 +19,61 @@ pub type Key = Box<[DfValue]>;
 pub type Row = Box<[DfValue]>;
 
 /// A shared set of rows, returned for a given single key in a reader, under a [`triomphe::Arc`]
-pub type SharedRows = triomphe::Arc<SmallVec<[Row; 1]>>;
+#[derive(Debug, Clone)]
+pub enum SharedRows {
+    /// Rows stored as slices of values, which are returned as they are
+    Rows(triomphe::Arc<SmallVec<[Row; 1]>>),
+    /// Rows stored as [`CompactRow`]s, which are only decoded one at a time as they're iterated
+    /// over
+    Compact(triomphe::Arc<SmallVec<[CompactRow; 1]>>),
+}
+
+impl Default for SharedRows {
+    fn default() -> Self {
+        SharedRows::Rows(Default::default())
+    }
+}
+
+impl From<triomphe::Arc<SmallVec<[Row; 1]>>> for SharedRows {
+    fn from(rows: triomphe::Arc<SmallVec<[Row; 1]>>) -> Self {
+        SharedRows::Rows(rows)
+    }
+}
+
+impl From<triomphe::Arc<SmallVec<[CompactRow; 1]>>> for SharedRows {
+    fn from(rows: triomphe::Arc<SmallVec<[CompactRow; 1]>>) -> Self {
+        SharedRows::Compact(rows)
+    }
+}
+
+impl SharedRows {
+    /// Returns the number of rows
+    pub fn len(&self) -> usize {
+        match self {
+            SharedRows::Rows(rows) => rows.len(),
+            SharedRows::Compact(rows) => rows.len(),
+        }
+    }
+
+    /// Returns true if there are no rows
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the row at `idx`, decoding it if it's stored as a [`CompactRow`]
+    pub fn get(&self, idx: usize) -> Option<Cow<'_, [DfValue]>> {
+        match self {
+            SharedRows::Rows(rows) => rows.get(idx).map(|row| Cow::Borrowed(&row[..])),
+            SharedRows::Compact(rows) => rows.get(idx).map(|row| Cow::Owned(row.iter().collect())),
+        }
+    }
+
+    /// Returns an iterator over the rows, decoding each row stored as a [`CompactRow`] as it's
+    /// reached
+    pub fn iter(&self) -> impl Iterator<Item = Cow<'_, [DfValue]>> + '_ {
+        (0..self.len()).filter_map(|idx| self.get(idx))
+    }
+}
 
 /// A list of [`SharedRows`], combining the lookup results for multiple keys
 pub type SharedResults = SmallVec<[SharedRows; 1]>;
@@ -101,6 +156,8 @@ struct SingleKeyIterator {
     data: SharedRows,
     // The next row in the current set to return
     row: Option<NonMaxUsize>,
+    // The current row, decoded, if the data is stored as compact rows
+    decoded: Vec<DfValue>,
 }
 
 /// An iterator over a multiple sets of cached results
@@ -112,6 +169,33 @@ struct MultiKeyIterator {
     set: usize,
     // The next row in the current set to return
     row: Option<NonMaxUsize>,
+    // The current row, decoded, if the current set is stored as compact rows
+    decoded: Vec<DfValue>,
+}
+
+/// Decode the row at `row` in `data` into `decoded`, if `data` is stored as compact rows
+#[inline(always)]
+fn decode_row(data: &SharedRows, row: usize, decoded: &mut Vec<DfValue>) {
+    if let SharedRows::Compact(rows) = data {
+        decoded.clear();
+        if let Some(compact) = rows.get(row) {
+            decoded.extend(compact.iter());
+        }
+    }
+}
+
+/// Returns the row at `row` in `data`, or, if `data` is stored as compact rows, `decoded` (which
+/// must hold that row decoded by [`decode_row`])
+#[inline(always)]
+fn current_row<'a>(
+    data: &'a SharedRows,
+    row: usize,
+    decoded: &'a [DfValue],
+) -> Option<&'a [DfValue]> {
+    match data {
+        SharedRows::Rows(rows) => rows.get(row).map(|r| &r[..]),
+        SharedRows::Compact(rows) => (row < rows.len()).then_some(decoded),
+    }
 }
 
 /// An iterator over multiple sets of cached results with an order by clause
@@ -187,15 +271,17 @@ impl ResultIterator {
                     order_by: order_by.clone().into(),
                 };
 
-                debug_assert!(data
-                    .iter()
-                    .all(|s| { s.is_sorted_by(|a, b| Some(comparator.cmp(a, b))) }));
+                debug_assert!(data.iter().all(|s| {
+                    s.iter()
+                        .is_sorted_by(|a, b| Some(comparator.cmp(&a[..], &b[..])))
+                }));
 
                 ResultIteratorInner::MultiKeyMerge(MergeIterator::new(data, comparator))
             }
             // if there's an aggregation, but only one key in the result set, we can return a
-            // simple iterator as results are already aggregated in the dataflow graph.
-            (None, Some(_)) if data.len() == 1 => {
+            // simple iterator as results are already aggregated in the dataflow graph (unless the
+            // aggregated values still need to be finalized).
+            (None, Some(aggregates)) if data.len() == 1 && !aggregates.requires_finalization() => {
                 ResultIteratorInner::MultiKey(MultiKeyIterator::new(data))
             }
             (None, Some(aggregates)) => {
@@ -218,9 +304,10 @@ impl ResultIterator {
                             .collect(),
                     };
 
-                    debug_assert!(data
-                        .iter()
-                        .all(|s| { s.is_sorted_by(|a, b| Some(comparator.cmp(a, b))) }));
+                    debug_assert!(data.iter().all(|s| {
+                        s.iter()
+                            .is_sorted_by(|a, b| Some(comparator.cmp(&a[..], &b[..])))
+                    }));
 
                     ResultIteratorInner::MultiKeyAggregateMerge(AggregateIterator {
                         inner: Box::new(ResultIteratorInner::MultiKeyMerge(MergeIterator::new(
@@ -235,7 +322,11 @@ impl ResultIterator {
             // if there's an order by clause with an aggregate, yet the result set has only one row
             // for a single key, return a simple iterator as there's nothing to order
             // (it's a single row)
-            (Some(_), Some(_)) if data.len() == 1 && data.first().is_some_and(|v| v.len() == 1) => {
+            (Some(_), Some(aggregates))
+                if data.len() == 1
+                    && data.first().is_some_and(|v| v.len() == 1)
+                    && !aggregates.requires_finalization() =>
+            {
                 ResultIteratorInner::MultiKey(MultiKeyIterator::new(data))
             }
             (Some(order_by), Some(aggregates)) => {
@@ -250,9 +341,10 @@ impl ResultIterator {
                         .collect(),
                 };
 
-                debug_assert!(data
-                    .iter()
-                    .all(|s| { s.is_sorted_by(|a, b| Some(comparator.cmp(a, b))) }));
+                debug_assert!(data.iter().all(|s| {
+                    s.iter()
+                        .is_sorted_by(|a, b| Some(comparator.cmp(&a[..], &b[..])))
+                }));
 
                 let temp_iter = ResultIterator {
                     inner: ResultIteratorInner::MultiKeyAggregateMerge(AggregateIterator {
@@ -392,7 +484,11 @@ impl StreamingIterator for OwnedResultIterator {
 
 impl SingleKeyIterator {
     pub(crate) fn new(data: SharedRows) -> Self {
-        SingleKeyIterator { data, row: None }
+        SingleKeyIterator {
+            data,
+            row: None,
+            decoded: Vec::new(),
+        }
     }
 }
 
@@ -401,15 +497,20 @@ impl StreamingIterator for SingleKeyIterator {
 
     #[inline(always)]
     fn advance(&mut self) {
-        match self.row.as_mut() {
-            None => self.row = Some(NonMaxUsize::zero()),
-            Some(row) => row.inc(),
-        }
+        let row = match self.row.as_mut() {
+            Some(row) => {
+                row.inc();
+                row
+            }
+            None => self.row.get_or_insert(NonMaxUsize::zero()),
+        };
+        decode_row(&self.data, **row, &mut self.decoded);
     }
 
     #[inline(always)]
     fn get(&self) -> Option<&Self::Item> {
-        self.row.and_then(|row| self.data.get(*row)).map(|r| &r[..])
+        self.row
+            .and_then(|row| current_row(&self.data, *row, &self.decoded))
     }
 }
 
@@ -419,6 +520,7 @@ impl MultiKeyIterator {
             data,
             set: 0,
             row: None,
+            decoded: Vec::new(),
         }
     }
 }
@@ -437,11 +539,12 @@ impl StreamingIterator for MultiKeyIterator {
         };
 
         while let Some(results) = self.data.get(self.set) {
-            if results.get(**row).is_none() {
+            if **row >= results.len() {
                 // Skip empty sets
                 self.set += 1;
                 *row = NonMaxUsize::zero();
             } else {
+                decode_row(results, **row, &mut self.decoded);
                 break;
             }
         }
@@ -449,9 +552,11 @@ impl StreamingIterator for MultiKeyIterator {
 
     #[inline(always)]
     fn get(&self) -> Option<&Self::Item> {
-        self.row
-            .and_then(|row| self.data.get(self.set).and_then(|s| s.get(*row)))
-            .map(|r| &r[..])
+        self.row.and_then(|row| {
+            self.data
+                .get(self.set)
+                .and_then(|s| current_row(s, *row, &self.decoded))
+        })
     }
 }
 
@@ -539,6 +644,14 @@ impl StreamingIterator for AggregateIterator {
             self.advance_filtered();
         }
 
+        for agg in &self.aggregate.aggregates {
+            let col = agg.column;
+            aggregate_row[col] = agg
+                .function
+                .finalize(std::mem::take(&mut aggregate_row[col]))
+                .expect("no fail");
+        }
+
         self.out_row = Some(aggregate_row)
     }
 
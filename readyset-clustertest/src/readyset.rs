@@ -287,6 +287,250 @@ async fn replicated_readers() {
     deployment.teardown().await.unwrap();
 }
 
+#[clustertest]
+async fn set_reader_replicas() {
+    let mut deployment = DeploymentBuilder::new(DatabaseType::MySQL, "ct_set_reader_replicas")
+        .with_servers(2, ServerParams::default())
+        .start()
+        .await
+        .unwrap();
+    let lh = deployment.leader_handle();
+
+    lh.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, val int);
+         CREATE CACHE q FROM SELECT id, val FROM t WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = lh.table("t").await.unwrap();
+    t.insert_many(vec![
+        vec![DfValue::from(1), DfValue::from(1)],
+        vec![DfValue::from(2), DfValue::from(2)],
+    ])
+    .await
+    .unwrap();
+
+    // Without any replication configured, there's only one reader replica
+    lh.view_with_replica("q", 1).await.unwrap_err();
+    let mut view_0 = lh
+        .view_with_replica("q", 0)
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+
+    // The existing replica keeps running while the new one is added
+    lh.set_reader_replicas(&"q".into(), 2).await.unwrap();
+
+    let mut view_1 = lh
+        .view_with_replica("q", 1)
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+    assert_ne!(view_0.shard_addrs(), view_1.shard_addrs());
+    assert_eq!(
+        view_0.lookup(&[1.into()], true).await.unwrap().into_vec(),
+        vec![vec![DfValue::from(1), DfValue::from(1)]]
+    );
+    assert_eq!(
+        view_1.lookup(&[2.into()], true).await.unwrap().into_vec(),
+        vec![vec![DfValue::from(2), DfValue::from(2)]]
+    );
+
+    // A view that doesn't ask for a particular replica is load-balanced across both of them
+    let mut view = lh.view("q").await.unwrap().into_reader_handle().unwrap();
+    for _ in 0..10 {
+        assert_eq!(
+            view.lookup(&[1.into()], true).await.unwrap().into_vec(),
+            vec![vec![DfValue::from(1), DfValue::from(1)]]
+        );
+    }
+
+    lh.set_reader_replicas(&"q".into(), 1).await.unwrap();
+    lh.view_with_replica("q", 1).await.unwrap_err();
+    assert_eq!(
+        view_0.lookup(&[2.into()], true).await.unwrap().into_vec(),
+        vec![vec![DfValue::from(2), DfValue::from(2)]]
+    );
+    let mut view = lh.view("q").await.unwrap().into_reader_handle().unwrap();
+    assert_eq!(
+        view.lookup(&[2.into()], true).await.unwrap().into_vec(),
+        vec![vec![DfValue::from(2), DfValue::from(2)]]
+    );
+
+    deployment.teardown().await.unwrap();
+}
+
+#[clustertest]
+async fn domain_failure_keeps_downstream_readers() {
+    let mut deployment = DeploymentBuilder::new(
+        DatabaseType::MySQL,
+        "ct_domain_failure_keeps_downstream_readers",
+    )
+    .with_servers(2, ServerParams::default())
+    .reader_replicas(2)
+    .start()
+    .await
+    .unwrap();
+    let lh = deployment.leader_handle();
+
+    lh.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, val int);
+         CREATE CACHE q FROM SELECT id, sum(val) FROM t WHERE id = ? GROUP BY id;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = lh.table("t").await.unwrap();
+    t.insert_many(vec![
+        vec![DfValue::from(1), DfValue::from(1)],
+        vec![DfValue::from(1), DfValue::from(2)],
+        vec![DfValue::from(2), DfValue::from(3)],
+    ])
+    .await
+    .unwrap();
+
+    let mut view_0 = lh
+        .view_with_replica("q", 0)
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+    let mut view_1 = lh
+        .view_with_replica("q", 1)
+        .await
+        .unwrap()
+        .into_reader_handle()
+        .unwrap();
+
+    let sum = |id: i32, sum: i32| {
+        vec![vec![
+            DfValue::from(id),
+            DfValue::from(Decimal::from_i32(sum)),
+        ]]
+    };
+    assert_eq!(
+        view_0.lookup(&[1.into()], true).await.unwrap().into_vec(),
+        sum(1, 3)
+    );
+    assert_eq!(
+        view_1.lookup(&[1.into()], true).await.unwrap().into_vec(),
+        sum(1, 3)
+    );
+
+    // Make the domain containing the aggregate (the only domain without base tables or readers)
+    // fail the next time it processes a packet
+    for server_handle in deployment.server_handles().values_mut() {
+        server_handle
+            .set_failpoint(failpoints::DOMAIN_PROCESS_PACKET, "1*return")
+            .await;
+    }
+    t.insert(vec![DfValue::from(1), DfValue::from(4)])
+        .await
+        .unwrap();
+
+    // While the aggregate's domain is recovered, the readers downstream of it keep running and
+    // serving the results they had before it failed. Once it's been recovered, their state is
+    // refilled with results that include the write that was lost when it failed.
+    eventually! {
+        let key_1_0 = view_0.lookup(&[1.into()], true).await.unwrap().into_vec();
+        let key_1_1 = view_1.lookup(&[1.into()], true).await.unwrap().into_vec();
+        assert!(key_1_0 == sum(1, 3) || key_1_0 == sum(1, 7));
+        assert!(key_1_1 == sum(1, 3) || key_1_1 == sum(1, 7));
+        key_1_0 == sum(1, 7) && key_1_1 == sum(1, 7)
+    }
+
+    // Keys that weren't filled before the failure can be replayed through the recovered domain
+    assert_eq!(
+        view_0.lookup(&[2.into()], true).await.unwrap().into_vec(),
+        sum(2, 3)
+    );
+    assert_eq!(
+        view_1.lookup(&[2.into()], true).await.unwrap().into_vec(),
+        sum(2, 3)
+    );
+
+    deployment.teardown().await.unwrap();
+}
+
+#[clustertest]
+async fn domain_failure_recreates_fully_materialized_descendants() {
+    let mut deployment = DeploymentBuilder::new(
+        DatabaseType::MySQL,
+        "ct_domain_failure_recreates_fully_materialized_descendants",
+    )
+    .with_servers(2, ServerParams::default())
+    .allow_full_materialization()
+    .start()
+    .await
+    .unwrap();
+    let lh = deployment.leader_handle();
+
+    lh.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, val int);
+         CREATE CACHE q FROM SELECT sum(val) FROM t;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = lh.table("t").await.unwrap();
+    t.insert_many(vec![
+        vec![DfValue::from(1), DfValue::from(1)],
+        vec![DfValue::from(1), DfValue::from(2)],
+        vec![DfValue::from(2), DfValue::from(3)],
+    ])
+    .await
+    .unwrap();
+
+    let total = |sum: i32| vec![vec![DfValue::from(Decimal::from_i32(sum))]];
+    let mut view = lh.view("q").await.unwrap().into_reader_handle().unwrap();
+    eventually!(view.lookup(&[0.into()], true).await.unwrap().into_vec() == total(6));
+
+    // Make the domain containing the aggregate (the only domain without base tables or readers)
+    // fail the next time it processes a packet
+    for server_handle in deployment.server_handles().values_mut() {
+        server_handle
+            .set_failpoint(failpoints::DOMAIN_PROCESS_PACKET, "1*return")
+            .await;
+    }
+    t.insert(vec![DfValue::from(1), DfValue::from(4)])
+        .await
+        .unwrap();
+
+    // The cache is fully materialized, so its state can't be refilled by replays once the
+    // aggregate's domain has been recovered. Instead, the reader's domain is recreated along with
+    // the aggregate's, and rebuilt from the base table, including the write that was lost when the
+    // aggregate's domain failed.
+    let lh = deployment.leader_handle();
+    eventually! {
+        let Ok(view) = lh.view("q").await else {
+            return false;
+        };
+        let Ok(mut view) = view.into_reader_handle() else {
+            return false;
+        };
+        view.lookup(&[0.into()], true)
+            .await
+            .is_ok_and(|res| res.into_vec() == total(10))
+    }
+
+    deployment.teardown().await.unwrap();
+}
+
 #[clustertest]
 async fn replicated_readers_with_unions() {
     let mut deployment =
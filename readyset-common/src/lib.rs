@@ -7,6 +7,7 @@ pub mod ulimit;
 
 use petgraph::prelude::*;
 pub use readyset_client::internal::{Index, IndexType};
+use readyset_data::CompactRow;
 pub use readyset_data::DfValue;
 use serde::{Deserialize, Serialize};
 
@@ -81,6 +82,20 @@ impl SizeOf for Box<[DfValue]> {
     }
 }
 
+impl SizeOf for CompactRow {
+    fn deep_size_of(&self) -> u64 {
+        self.size_of() + self.encoded_len() as u64
+    }
+
+    fn size_of(&self) -> u64 {
+        std::mem::size_of::<Self>() as u64
+    }
+
+    fn is_empty(&self) -> bool {
+        false
+    }
+}
+
 /// A reference to a node, and potentially a partial index on that node
 ///
 /// The index is only included if partial materialization is possible; if it's present, it
@@ -149,4 +164,41 @@ mod tests {
         assert_eq!(rec.size_of(), 24 + 3 * 16);
         assert_eq!(rec.deep_size_of(), 24 + 3 * 16 + (8 + 16));
     }
+
+    #[test]
+    fn compact_row_mem_size() {
+        use readyset_data::TinyText;
+
+        let rows = (0..100i64)
+            .map(|n| {
+                vec![
+                    DfValue::from(n),
+                    DfValue::from(format!("a somewhat longer description of row {n}")),
+                    DfValue::TinyText(TinyText::from_slice(b"short").unwrap()),
+                    if n % 2 == 0 {
+                        DfValue::None
+                    } else {
+                        DfValue::from(n)
+                    },
+                    DfValue::try_from(n as f64 / 3.0).unwrap(),
+                ]
+                .into_boxed_slice()
+            })
+            .collect::<Vec<_>>();
+
+        for row in &rows {
+            // Box<[DfValue]>'s pointer and length
+            assert_eq!(CompactRow::new(row).size_of(), 16);
+        }
+
+        let boxed = rows.iter().map(|r| r.deep_size_of()).sum::<u64>();
+        let compact = rows
+            .iter()
+            .map(|r| CompactRow::new(r).deep_size_of())
+            .sum::<u64>();
+        assert!(
+            compact * 4 < boxed * 3,
+            "CompactRow = {compact} bytes, Box<[DfValue]> = {boxed} bytes"
+        );
+    }
 }
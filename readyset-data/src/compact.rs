@@ -0,0 +1,387 @@
+//! A compact, contiguous encoding for rows of [`DfValue`]s.
+//!
+//! A `Box<[DfValue]>` costs 16 bytes per value, plus a separate allocation for every text, byte
+//! array or numeric value too large to be stored inline. A [`CompactRow`] instead serializes the
+//! whole row into a single byte buffer: each value is written as a one-byte tag followed by a
+//! variable-length payload (integers are varint-encoded, and strings and byte arrays are written
+//! inline after their length). Values are decoded on demand, trading some read latency for a
+//! significantly smaller footprint in the map.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::{Collation, DfValue};
+
+const TAG_NONE: u8 = 0;
+const TAG_INT: u8 = 1;
+const TAG_UNSIGNED_INT: u8 = 2;
+const TAG_FLOAT: u8 = 3;
+const TAG_DOUBLE: u8 = 4;
+const TAG_TEXT_UTF8: u8 = 5;
+const TAG_TEXT_CITEXT: u8 = 6;
+const TAG_BYTE_ARRAY: u8 = 7;
+const TAG_MAX: u8 = 8;
+/// Any other value, serialized with bincode
+const TAG_OTHER: u8 = 9;
+
+/// A row of [`DfValue`]s, serialized into a single contiguous buffer.
+///
+/// Rows compare, and are ordered, exactly as the `[DfValue]` they were encoded from.
+#[derive(Clone)]
+pub struct CompactRow(Box<[u8]>);
+
+impl CompactRow {
+    /// Encode the given row.
+    pub fn new(row: &[DfValue]) -> Self {
+        let mut buf = Vec::with_capacity(row.len() * 2);
+        for value in row {
+            encode_value(value, &mut buf);
+        }
+        Self(buf.into_boxed_slice())
+    }
+
+    /// Returns an iterator which lazily decodes the values in this row.
+    pub fn iter(&self) -> CompactRowIter<'_> {
+        CompactRowIter { buf: &self.0 }
+    }
+
+    /// Decode the value of the column at `idx`, skipping over (but not decoding) all the columns
+    /// before it. Returns `None` if the row has fewer than `idx + 1` columns.
+    pub fn get(&self, idx: usize) -> Option<DfValue> {
+        let mut buf: &[u8] = &self.0;
+        for _ in 0..idx {
+            skip_value(&mut buf)?;
+        }
+        decode_value(&mut buf)
+    }
+
+    /// Decode all the values in this row.
+    pub fn to_row(&self) -> Box<[DfValue]> {
+        self.iter().collect()
+    }
+
+    /// Returns the number of bytes in the encoded representation of this row.
+    pub fn encoded_len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Compare the values of the column at `idx` in this row and `other`, without decoding either
+    /// row. A row with fewer than `idx + 1` columns compares less than one which has that column.
+    pub fn cmp_column(&self, other: &Self, idx: usize) -> Ordering {
+        cmp_encoded(&mut self.column(idx), &mut other.column(idx))
+    }
+
+    /// Returns the encoded bytes of this row starting at the column at `idx`, or an empty slice if
+    /// the row has fewer than `idx + 1` columns.
+    fn column(&self, idx: usize) -> &[u8] {
+        let mut buf: &[u8] = &self.0;
+        for _ in 0..idx {
+            if skip_value(&mut buf).is_none() {
+                return &[];
+            }
+        }
+        buf
+    }
+}
+
+impl From<&[DfValue]> for CompactRow {
+    fn from(row: &[DfValue]) -> Self {
+        Self::new(row)
+    }
+}
+
+impl PartialEq for CompactRow {
+    fn eq(&self, other: &Self) -> bool {
+        // Values which compare equal can have different encodings (eg `Int(1)` and
+        // `UnsignedInt(1)`), so fall back to comparing the decoded values
+        self.0 == other.0 || self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for CompactRow {}
+
+impl PartialOrd for CompactRow {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CompactRow {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (mut a, mut b): (&[u8], &[u8]) = (&self.0, &other.0);
+        loop {
+            if a.is_empty() || b.is_empty() {
+                // Like slices, a row which is a prefix of another row sorts first
+                return b.is_empty().cmp(&a.is_empty());
+            }
+            match cmp_encoded(&mut a, &mut b) {
+                Ordering::Equal => {}
+                ord => return ord,
+            }
+        }
+    }
+}
+
+impl fmt::Debug for CompactRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// An iterator over the decoded values of a [`CompactRow`]
+#[derive(Clone)]
+pub struct CompactRowIter<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> fmt::Debug for CompactRowIter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a> Iterator for CompactRowIter<'a> {
+    type Item = DfValue;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        decode_value(&mut self.buf)
+    }
+}
+
+fn write_varint(mut n: u64, buf: &mut Vec<u8>) {
+    while n >= 0x80 {
+        buf.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    buf.push(n as u8);
+}
+
+fn read_varint(buf: &mut &[u8]) -> Option<u64> {
+    let mut n = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, rest) = buf.split_first()?;
+        *buf = rest;
+        n |= u64::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            return Some(n);
+        }
+    }
+    None
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if buf.len() < len {
+        return None;
+    }
+    let (bytes, rest) = buf.split_at(len);
+    *buf = rest;
+    Some(bytes)
+}
+
+fn write_bytes(bytes: &[u8], buf: &mut Vec<u8>) {
+    write_varint(bytes.len() as u64, buf);
+    buf.extend_from_slice(bytes);
+}
+
+fn read_bytes<'a>(buf: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let len = read_varint(buf)? as usize;
+    take(buf, len)
+}
+
+fn encode_value(value: &DfValue, buf: &mut Vec<u8>) {
+    match value {
+        DfValue::None => buf.push(TAG_NONE),
+        DfValue::Int(n) => {
+            buf.push(TAG_INT);
+            // zig-zag encode, so that small negative numbers stay small
+            write_varint(((*n << 1) ^ (*n >> 63)) as u64, buf);
+        }
+        DfValue::UnsignedInt(n) => {
+            buf.push(TAG_UNSIGNED_INT);
+            write_varint(*n, buf);
+        }
+        DfValue::Float(f) => {
+            buf.push(TAG_FLOAT);
+            buf.extend_from_slice(&f.to_le_bytes());
+        }
+        DfValue::Double(f) => {
+            buf.push(TAG_DOUBLE);
+            buf.extend_from_slice(&f.to_le_bytes());
+        }
+        DfValue::Text(_) | DfValue::TinyText(_) => {
+            #[allow(clippy::unwrap_used)] // Just checked that the value is text
+            let (s, collation) = value.as_str_and_collation().unwrap();
+            buf.push(match collation {
+                Collation::Utf8 => TAG_TEXT_UTF8,
+                Collation::Citext => TAG_TEXT_CITEXT,
+            });
+            write_bytes(s.as_bytes(), buf);
+        }
+        DfValue::ByteArray(bytes) => {
+            buf.push(TAG_BYTE_ARRAY);
+            write_bytes(bytes, buf);
+        }
+        DfValue::Max => buf.push(TAG_MAX),
+        _ => {
+            buf.push(TAG_OTHER);
+            // The only values that fail to serialize are `PassThrough` values, which are never
+            // allowed into the dataflow graph
+            #[allow(clippy::expect_used)]
+            let bytes = bincode::serialize(value).expect("DfValue in a reader must serialize");
+            write_bytes(&bytes, buf);
+        }
+    }
+}
+
+fn decode_value(buf: &mut &[u8]) -> Option<DfValue> {
+    let (&tag, rest) = buf.split_first()?;
+    *buf = rest;
+    Some(match tag {
+        TAG_NONE => DfValue::None,
+        TAG_INT => DfValue::Int(decode_int(read_varint(buf)?)),
+        TAG_UNSIGNED_INT => DfValue::UnsignedInt(read_varint(buf)?),
+        TAG_FLOAT => DfValue::Float(f32::from_le_bytes(take(buf, 4)?.try_into().ok()?)),
+        TAG_DOUBLE => DfValue::Double(f64::from_le_bytes(take(buf, 8)?.try_into().ok()?)),
+        TAG_TEXT_UTF8 | TAG_TEXT_CITEXT => {
+            let bytes = read_bytes(buf)?;
+            // SAFETY: text bytes are only ever written by `encode_value`, from a `&str`
+            let s = unsafe { std::str::from_utf8_unchecked(bytes) };
+            let collation = if tag == TAG_TEXT_CITEXT {
+                Collation::Citext
+            } else {
+                Collation::Utf8
+            };
+            DfValue::from_str_and_collation(s, collation)
+        }
+        TAG_BYTE_ARRAY => DfValue::ByteArray(std::sync::Arc::new(read_bytes(buf)?.to_vec())),
+        TAG_MAX => DfValue::Max,
+        TAG_OTHER => bincode::deserialize(read_bytes(buf)?).ok()?,
+        _ => return None,
+    })
+}
+
+fn decode_int(n: u64) -> i64 {
+    ((n >> 1) as i64) ^ -((n & 1) as i64)
+}
+
+/// Compare the next value encoded in `a` with the next value encoded in `b`, advancing both past
+/// the values compared.
+///
+/// Values of the same type whose encodings sort the same way as the values themselves are compared
+/// without being decoded; any other pair of values is decoded (one value at a time, never a whole
+/// row) and compared as [`DfValue`]s.
+fn cmp_encoded(a: &mut &[u8], b: &mut &[u8]) -> Ordering {
+    if let (Some(&tag), Some(&other_tag)) = (a.first(), b.first()) {
+        if tag == other_tag {
+            let (mut rest_a, mut rest_b) = (&a[1..], &b[1..]);
+            let ord = match tag {
+                TAG_NONE | TAG_MAX => Some(Ordering::Equal),
+                TAG_INT => read_varint(&mut rest_a)
+                    .zip(read_varint(&mut rest_b))
+                    .map(|(x, y)| decode_int(x).cmp(&decode_int(y))),
+                TAG_UNSIGNED_INT => read_varint(&mut rest_a)
+                    .zip(read_varint(&mut rest_b))
+                    .map(|(x, y)| x.cmp(&y)),
+                // utf8-collated text compares bytewise, as do byte arrays
+                TAG_TEXT_UTF8 | TAG_BYTE_ARRAY => read_bytes(&mut rest_a)
+                    .zip(read_bytes(&mut rest_b))
+                    .map(|(x, y)| x.cmp(y)),
+                _ => None,
+            };
+            if let Some(ord) = ord {
+                *a = rest_a;
+                *b = rest_b;
+                return ord;
+            }
+        }
+    }
+    decode_value(a).cmp(&decode_value(b))
+}
+
+fn skip_value(buf: &mut &[u8]) -> Option<()> {
+    let (&tag, rest) = buf.split_first()?;
+    *buf = rest;
+    match tag {
+        TAG_NONE | TAG_MAX => {}
+        TAG_INT | TAG_UNSIGNED_INT => {
+            read_varint(buf)?;
+        }
+        TAG_FLOAT => {
+            take(buf, 4)?;
+        }
+        TAG_DOUBLE => {
+            take(buf, 8)?;
+        }
+        TAG_TEXT_UTF8 | TAG_TEXT_CITEXT | TAG_BYTE_ARRAY | TAG_OTHER => {
+            read_bytes(buf)?;
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn arbitrary_row() -> impl Strategy<Value = Vec<DfValue>> {
+        proptest::collection::vec(
+            any::<DfValue>().prop_filter("PassThrough values never reach readers", |v| {
+                !matches!(v, DfValue::PassThrough(_))
+            }),
+            0..8,
+        )
+    }
+
+    #[test]
+    fn round_trip_example_row() {
+        let row = DfValue::example_row();
+        let compact = CompactRow::new(&row);
+        assert_eq!(compact.to_row().as_ref(), row.as_slice());
+        for (i, value) in row.iter().enumerate() {
+            assert_eq!(compact.get(i).as_ref(), Some(value));
+        }
+        assert_eq!(compact.get(row.len()), None);
+    }
+
+    #[test]
+    fn preserves_collation() {
+        let row = vec![
+            DfValue::from_str_and_collation("AbC", Collation::Citext),
+            DfValue::from_str_and_collation(&"x".repeat(64), Collation::Citext),
+        ];
+        let decoded = CompactRow::new(&row).to_row();
+        assert_eq!(decoded[0].collation(), Some(Collation::Citext));
+        assert_eq!(decoded[1].collation(), Some(Collation::Citext));
+    }
+
+    #[test]
+    fn equal_across_encodings() {
+        assert_eq!(
+            CompactRow::new(&[DfValue::Int(1)]),
+            CompactRow::new(&[DfValue::UnsignedInt(1)])
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(row in arbitrary_row()) {
+            prop_assert_eq!(CompactRow::new(&row).to_row().as_ref(), row.as_slice());
+        }
+
+        #[test]
+        fn order_matches_values(a in arbitrary_row(), b in arbitrary_row()) {
+            prop_assert_eq!(CompactRow::new(&a).cmp(&CompactRow::new(&b)), a.cmp(&b));
+        }
+
+        #[test]
+        fn column_order_matches_values(a in arbitrary_row(), b in arbitrary_row(), idx in 0..8usize) {
+            prop_assert_eq!(
+                CompactRow::new(&a).cmp_column(&CompactRow::new(&b), idx),
+                a.get(idx).cmp(&b.get(idx))
+            );
+        }
+    }
+}
@@ -32,6 +32,7 @@ use uuid::Uuid;
 
 mod array;
 mod collation;
+mod compact;
 pub mod dialect;
 mod r#enum;
 mod float;
@@ -48,6 +49,7 @@ use proptest::prelude::*;
 
 pub use crate::array::Array;
 pub use crate::collation::Collation;
+pub use crate::compact::{CompactRow, CompactRowIter};
 pub use crate::dialect::Dialect;
 pub use crate::r#type::{DfType, PgEnumMetadata, PgTypeCategory};
 pub use crate::ranges::{Bound, BoundedRange, IntoBoundedRange, RangeBounds};
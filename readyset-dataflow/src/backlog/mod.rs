@@ -1,14 +1,16 @@
 use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::sync::Arc;
+use std::time::Duration;
 
 use ahash::RandomState;
 use common::SizeOf;
 use dataflow_expression::{PostLookup, ReaderProcessing};
 use nom_sql::Relation;
-use reader_map::{EvictionQuantity, EvictionStrategy};
+use reader_map::refs::Values;
+use reader_map::{CompactRow, EvictionQuantity, EvictionStrategy};
 use readyset_client::consistency::Timestamp;
-use readyset_client::results::SharedResults;
+use readyset_client::results::{SharedResults, SharedRows};
 use readyset_client::KeyComparison;
 use readyset_data::Bound;
 use vec1::Vec1;
@@ -26,6 +28,38 @@ pub(crate) type ReaderUpdatedSender = tokio::sync::broadcast::Sender<ReaderNotif
 pub(crate) trait Trigger =
     Fn(&mut dyn Iterator<Item = KeyComparison>, Relation) -> bool + 'static + Send + Sync;
 
+/// The representation of the rows stored in the map of a reader
+trait ReaderRow: Ord + Clone + SizeOf {
+    /// Convert a row from the dataflow graph into this representation
+    fn from_row(row: Vec<DfValue>) -> Self;
+
+    /// Convert the rows stored for a single key into the form returned to readers
+    fn to_shared_rows(values: &Values<Self>) -> SharedRows;
+}
+
+impl ReaderRow for Box<[DfValue]> {
+    fn from_row(row: Vec<DfValue>) -> Self {
+        row.into_boxed_slice()
+    }
+
+    fn to_shared_rows(values: &Values<Self>) -> SharedRows {
+        // The rows are already in the form we return, so this is just a reference count bump
+        SharedRows::Rows(values.as_ref().clone())
+    }
+}
+
+impl ReaderRow for CompactRow {
+    fn from_row(row: Vec<DfValue>) -> Self {
+        CompactRow::new(&row)
+    }
+
+    fn to_shared_rows(values: &Values<Self>) -> SharedRows {
+        // Rows are only decoded as the results are iterated over, so this is also just a reference
+        // count bump
+        SharedRows::Compact(values.as_ref().clone())
+    }
+}
+
 /// Allocate a new end-user facing result table.
 ///
 /// # Invariants:
@@ -35,8 +69,16 @@ pub(crate) fn new(
     cols: usize,
     index: Index,
     reader_processing: ReaderProcessing,
+    compact_rows: bool,
 ) -> (SingleReadHandle, WriteHandle) {
-    new_inner(cols, index, None, EvictionKind::Random, reader_processing)
+    new_inner(
+        cols,
+        index,
+        None,
+        EvictionKind::Random,
+        reader_processing,
+        compact_rows,
+    )
 }
 
 /// Allocate a new partially materialized end-user facing result table.
@@ -48,6 +90,7 @@ pub(crate) fn new(
 /// * `cols` - the number of columns in this table
 /// * `index` - the index for the reader
 /// * `trigger` - function to call to trigger an upquery and replay
+/// * `compact_rows` - whether to store rows as [`CompactRow`]s, trading read latency for memory
 ///
 /// # Invariants:
 ///
@@ -58,6 +101,7 @@ pub(crate) fn new_partial<F>(
     trigger: F,
     eviction_kind: EvictionKind,
     reader_processing: ReaderProcessing,
+    compact_rows: bool,
 ) -> (SingleReadHandle, WriteHandle)
 where
     F: Trigger,
@@ -68,6 +112,7 @@ where
         Some(Arc::new(trigger)),
         eviction_kind,
         reader_processing,
+        compact_rows,
     )
 }
 
@@ -80,6 +125,7 @@ fn new_inner(
     trigger: Option<Arc<dyn Trigger>>,
     eviction_kind: EvictionKind,
     reader_processing: ReaderProcessing,
+    compact_rows: bool,
 ) -> (SingleReadHandle, WriteHandle) {
     let contiguous = {
         let mut contiguous = true;
@@ -100,6 +146,7 @@ fn new_inner(
         EvictionKind::Random => EvictionStrategy::new_random(),
         EvictionKind::LRU => EvictionStrategy::new_lru(),
         EvictionKind::Generational => EvictionStrategy::new_generational(),
+        EvictionKind::CostAware => EvictionStrategy::new_cost_aware(),
     };
 
     let ReaderProcessing {
@@ -131,10 +178,12 @@ fn new_inner(
     }
 
     #[allow(clippy::unreachable)] // Documented invariant.
-    let (w, r) = match index.len() {
-        0 => unreachable!(),
-        1 => make!(Single),
-        _ => make!(Many),
+    let (w, r) = match (index.len(), compact_rows) {
+        (0, _) => unreachable!(),
+        (1, false) => make!(Single),
+        (1, true) => make!(CompactSingle),
+        (_, false) => make!(Many),
+        (_, true) => make!(CompactMany),
     };
 
     let (notifier, receiver) = tokio::sync::broadcast::channel(1);
@@ -233,11 +282,11 @@ impl<'a> MutWriteHandleEntry<'a> {
             .handle
             .handle
             .read()
-            .get(&self.key)
-            .map(|rs| {
-                rs.iter().map(SizeOf::deep_size_of).sum::<u64>() as usize
-                    + self.key_value_size(&self.key)
-            })
+            .rows_size(&KeyComparison::Equal(
+                #[allow(clippy::unwrap_used)] // Keys into readers are never empty
+                Vec1::try_from(&self.key[..]).unwrap(),
+            ))
+            .map(|size| size as usize + self.key_value_size(&self.key))
             .unwrap_or(0);
         self.handle.mem_size = self.handle.mem_size.saturating_sub(size);
         self.handle.handle.empty(self.key);
@@ -348,6 +397,13 @@ impl WriteHandle {
         self.evict_inner(request).0
     }
 
+    /// Evict every key from state, returning the number of bytes freed
+    pub(crate) fn evict_all(&mut self) -> u64 {
+        let (bytes_freed, _) = self.handle.evict(EvictionQuantity::Quantity(usize::MAX));
+        self.mem_size = 0;
+        bytes_freed
+    }
+
     /// Evict a single key from state
     pub(crate) fn evict_random(&mut self) -> (u64, Option<Vec<DfValue>>) {
         let request = EvictionQuantity::SingleKey;
@@ -365,16 +421,7 @@ impl WriteHandle {
                 // We don't want to clone things more than once, so construct the range key, then
                 // deconstruct it again
                 let range_key = KeyComparison::Range((start, end));
-                let size = self
-                    .handle
-                    .read()
-                    .get_multi(std::slice::from_ref(&range_key))
-                    .map(|rs| {
-                        rs.iter()
-                            .flat_map(|rs| rs.iter().map(SizeOf::deep_size_of))
-                            .sum::<u64>()
-                    })
-                    .unwrap_or(0);
+                let size = self.handle.read().rows_size(&range_key).unwrap_or(0);
 
                 self.mem_size = self.mem_size.saturating_sub(size as usize);
                 if let KeyComparison::Range(range) = range_key {
@@ -415,6 +462,15 @@ impl WriteHandle {
         Ok(())
     }
 
+    /// Record how long the replay that filled `key` took, so that eviction strategies which take
+    /// the cost of recomputing keys into account can make use of it. Only equality keys have their
+    /// cost recorded.
+    pub(crate) fn record_replay_cost(&self, key: &KeyComparison, cost: Duration) {
+        if let KeyComparison::Equal(k) = key {
+            self.handle.record_cost(k.as_vec(), cost);
+        }
+    }
+
     /// Increment the eviction epoch, and notify readers
     pub(crate) fn notify_readers_of_eviction(&mut self) -> ReadySetResult<()> {
         self.eviction_epoch += 1;
@@ -585,16 +641,20 @@ impl SingleReadHandle {
 #[cfg(test)]
 #[allow(clippy::panic)]
 mod tests {
-    use readyset_client::results::SharedRows;
+    use readyset_client::results::ResultIterator;
     use readyset_data::Bound;
 
     use super::*;
 
     impl SingleReadHandle {
-        fn get<'a>(&self, key: &'a [DfValue]) -> Result<SharedRows, LookupError<'a>> {
+        fn get<'a>(&self, key: &'a [DfValue]) -> Result<Vec<Box<[DfValue]>>, LookupError<'a>> {
             match self.handle.get(key) {
-                Err(e) if e.is_miss() && self.trigger.is_none() => Ok(SharedRows::default()),
-                r => r,
+                Err(e) if e.is_miss() && self.trigger.is_none() => Ok(vec![]),
+                r => r.map(|rows| {
+                    rows.iter()
+                        .map(|row| row.into_owned().into_boxed_slice())
+                        .collect()
+                }),
             }
         }
     }
@@ -603,7 +663,12 @@ mod tests {
     fn store_works() {
         let a = vec![1i32.into(), "a".into()].into_boxed_slice();
 
-        let (r, mut w) = new(2, Index::hash_map(vec![0]), ReaderProcessing::default());
+        let (r, mut w) = new(
+            2,
+            Index::hash_map(vec![0]),
+            ReaderProcessing::default(),
+            false,
+        );
 
         w.swap();
 
@@ -627,7 +692,12 @@ mod tests {
         use std::thread;
 
         let n = 1_000;
-        let (r, mut w) = new(1, Index::hash_map(vec![0]), ReaderProcessing::default());
+        let (r, mut w) = new(
+            1,
+            Index::hash_map(vec![0]),
+            ReaderProcessing::default(),
+            false,
+        );
         let jh = thread::spawn(move || {
             for i in 0..n {
                 w.add(vec![Record::Positive(vec![i.into()])]);
@@ -656,7 +726,12 @@ mod tests {
         let a = vec![1i32.into(), "a".into()].into_boxed_slice();
         let b = vec![1i32.into(), "b".into()].into_boxed_slice();
 
-        let (r, mut w) = new(2, Index::hash_map(vec![0]), ReaderProcessing::default());
+        let (r, mut w) = new(
+            2,
+            Index::hash_map(vec![0]),
+            ReaderProcessing::default(),
+            false,
+        );
         w.add(vec![Record::Positive(a.to_vec())]);
         w.swap();
         w.add(vec![Record::Positive(b.to_vec())]);
@@ -671,7 +746,12 @@ mod tests {
         let b = vec![1i32.into(), "b".into()].into_boxed_slice();
         let c = vec![1i32.into(), "c".into()].into_boxed_slice();
 
-        let (r, mut w) = new(2, Index::hash_map(vec![0]), ReaderProcessing::default());
+        let (r, mut w) = new(
+            2,
+            Index::hash_map(vec![0]),
+            ReaderProcessing::default(),
+            false,
+        );
         w.add(vec![Record::Positive(a.to_vec())]);
         w.add(vec![Record::Positive(b.to_vec())]);
         w.swap();
@@ -687,7 +767,12 @@ mod tests {
         let a = vec![1i32.into(), "a".into()].into_boxed_slice();
         let b = vec![1i32.into(), "b".into()].into_boxed_slice();
 
-        let (r, mut w) = new(2, Index::hash_map(vec![0]), ReaderProcessing::default());
+        let (r, mut w) = new(
+            2,
+            Index::hash_map(vec![0]),
+            ReaderProcessing::default(),
+            false,
+        );
         w.add(vec![Record::Positive(a.to_vec())]);
         w.add(vec![Record::Positive(b.to_vec())]);
         w.add(vec![Record::Negative(a.to_vec())]);
@@ -702,7 +787,12 @@ mod tests {
         let a = vec![1i32.into(), "a".into()].into_boxed_slice();
         let b = vec![1i32.into(), "b".into()].into_boxed_slice();
 
-        let (r, mut w) = new(2, Index::hash_map(vec![0]), ReaderProcessing::default());
+        let (r, mut w) = new(
+            2,
+            Index::hash_map(vec![0]),
+            ReaderProcessing::default(),
+            false,
+        );
         w.add(vec![Record::Positive(a.to_vec())]);
         w.add(vec![Record::Positive(b.to_vec())]);
         w.swap();
@@ -719,7 +809,12 @@ mod tests {
         let b = vec![1i32.into(), "b".into()].into_boxed_slice();
         let c = vec![1i32.into(), "c".into()].into_boxed_slice();
 
-        let (r, mut w) = new(2, Index::hash_map(vec![0]), ReaderProcessing::default());
+        let (r, mut w) = new(
+            2,
+            Index::hash_map(vec![0]),
+            ReaderProcessing::default(),
+            false,
+        );
         w.add(vec![
             Record::Positive(a.to_vec()),
             Record::Positive(b.to_vec()),
@@ -741,6 +836,108 @@ mod tests {
         assert_eq!(r.get(&a[0..1]).unwrap()[0], b);
     }
 
+    #[test]
+    fn compact_rows() {
+        let a = vec![1i32.into(), "a".into()].into_boxed_slice();
+        let b = vec![1i32.into(), "b".into()].into_boxed_slice();
+
+        let (r, mut w) = new(
+            2,
+            Index::hash_map(vec![0]),
+            ReaderProcessing::default(),
+            true,
+        );
+        w.add(vec![
+            Record::Positive(a.to_vec()),
+            Record::Positive(b.to_vec()),
+        ]);
+        w.swap();
+
+        assert_eq!(r.get(&a[0..1]).unwrap().len(), 2);
+        assert_eq!(r.get(&a[0..1]).unwrap()[0], a);
+        assert_eq!(r.get(&a[0..1]).unwrap()[1], b);
+
+        w.add(vec![Record::Negative(a.to_vec())]);
+        w.swap();
+
+        assert_eq!(r.get(&a[0..1]).unwrap().len(), 1);
+        assert_eq!(r.get(&a[0..1]).unwrap()[0], b);
+    }
+
+    #[test]
+    fn compact_rows_result_iterator() {
+        let rows = (0..3)
+            .map(|i: i32| vec![0.into(), i.into(), format!("row number {i}").into()])
+            .collect::<Vec<_>>();
+
+        let (r, mut w) = new(
+            3,
+            Index::hash_map(vec![0]),
+            ReaderProcessing::default(),
+            true,
+        );
+        w.add(rows.iter().cloned().map(Record::Positive));
+        w.swap();
+
+        let results = r
+            .get_multi(&[KeyComparison::Equal(vec1![0.into()])])
+            .unwrap();
+        assert_eq!(
+            ResultIterator::new(results, &Default::default(), None, None, None).into_vec(),
+            rows
+        );
+    }
+
+    #[test]
+    fn compact_rows_use_less_memory() {
+        let rows = (0..100)
+            .map(|i: i32| vec![i.into(), format!("row number {i}").into(), DfValue::None])
+            .map(Record::Positive)
+            .collect::<Vec<_>>();
+
+        let (_, mut boxed) = new(
+            3,
+            Index::hash_map(vec![0]),
+            ReaderProcessing::default(),
+            false,
+        );
+        boxed.add(rows.clone());
+        let (_, mut compact) = new(
+            3,
+            Index::hash_map(vec![0]),
+            ReaderProcessing::default(),
+            true,
+        );
+        compact.add(rows);
+
+        assert!(compact.deep_size_of() < boxed.deep_size_of());
+    }
+
+    #[test]
+    fn compact_rows_mark_hole() {
+        let (r, mut w) = new_partial(
+            2,
+            Index::hash_map(vec![0]),
+            |_: &mut dyn Iterator<Item = KeyComparison>, _| true,
+            EvictionKind::Random,
+            ReaderProcessing::default(),
+            true,
+        );
+        w.swap();
+
+        let key = vec1![DfValue::from(0)];
+        w.mark_filled(key.clone().into()).unwrap();
+        w.add(vec![Record::Positive(vec![0.into(), "a".into()])]);
+        w.swap();
+        assert_eq!(r.get(&key).unwrap().len(), 1);
+
+        let freed = w.mark_hole(&key.clone().into()).unwrap();
+        w.swap();
+        assert!(freed > 0);
+        assert_eq!(w.deep_size_of(), 0);
+        assert!(r.get(&key).err().unwrap().is_miss());
+    }
+
     #[test]
     fn find_missing_partial() {
         let (r, mut w) = new_partial(
@@ -749,6 +946,7 @@ mod tests {
             |_: &mut dyn Iterator<Item = KeyComparison>, _| true,
             EvictionKind::Random,
             ReaderProcessing::default(),
+            false,
         );
         w.swap();
 
@@ -774,6 +972,7 @@ mod tests {
                 |_: &mut dyn Iterator<Item = KeyComparison>, _| true,
                 EvictionKind::Random,
                 ReaderProcessing::default(),
+                false,
             );
             w.swap();
 
@@ -793,6 +992,7 @@ mod tests {
                 |_: &mut dyn Iterator<Item = KeyComparison>, _| true,
                 EvictionKind::Random,
                 ReaderProcessing::default(),
+                false,
             );
             w.swap();
 
@@ -823,6 +1023,7 @@ mod tests {
                 |_: &mut dyn Iterator<Item = KeyComparison>, _| true,
                 EvictionKind::Random,
                 ReaderProcessing::default(),
+                false,
             );
             w.swap();
 
@@ -844,6 +1045,7 @@ mod tests {
                 |_: &mut dyn Iterator<Item = KeyComparison>, _| true,
                 EvictionKind::Random,
                 ReaderProcessing::default(),
+                false,
             );
             w.swap();
 
@@ -3,9 +3,10 @@ use std::collections::HashSet;
 use std::convert::TryInto;
 
 use ahash::RandomState;
-use common::DfValue;
+use common::{DfValue, SizeOf};
 use dataflow_expression::PreInsertion;
 use reader_map::refs::Miss;
+use reader_map::CompactRow;
 use readyset_client::consistency::Timestamp;
 use readyset_client::results::{SharedResults, SharedRows};
 use readyset_client::KeyComparison;
@@ -15,31 +16,38 @@ use serde::{Deserialize, Serialize};
 use tracing::warn;
 use vec1::{vec1, Vec1};
 
+use super::ReaderRow;
+
 /// A [`ReadHandle`] to a map whose key is a single [`DfValue`], for faster lookup (compared to a
 /// Vec with len == 1)
-type HandleSingle = reader_map::handles::ReadHandle<
-    DfValue,
-    Box<[DfValue]>,
-    PreInsertion,
-    i64,
-    Timestamp,
-    RandomState,
->;
+type HandleSingle<R = Box<[DfValue]>> =
+    reader_map::handles::ReadHandle<DfValue, R, PreInsertion, i64, Timestamp, RandomState>;
 
 /// A [`ReadHandle`] to a map whose key is a [`Vec<DfValue>`]
-type HandleMany = reader_map::handles::ReadHandle<
-    Vec<DfValue>,
-    Box<[DfValue]>,
-    PreInsertion,
-    i64,
-    Timestamp,
-    RandomState,
->;
+type HandleMany<R = Box<[DfValue]>> =
+    reader_map::handles::ReadHandle<Vec<DfValue>, R, PreInsertion, i64, Timestamp, RandomState>;
 
 #[derive(Clone, Debug)]
 pub(super) enum Handle {
     Single(HandleSingle),
     Many(HandleMany),
+    /// Like [`Handle::Single`], but storing rows as [`CompactRow`]s
+    CompactSingle(HandleSingle<CompactRow>),
+    /// Like [`Handle::Many`], but storing rows as [`CompactRow`]s
+    CompactMany(HandleMany<CompactRow>),
+}
+
+/// Match on a [`Handle`], running the same (generic) code for both row representations of a
+/// single-column or multi-column key
+macro_rules! dispatch {
+    ($handle:expr, Single($single:pat) => $single_body:expr, Many($many:pat) => $many_body:expr $(,)?) => {
+        match $handle {
+            Handle::Single($single) => $single_body,
+            Handle::CompactSingle($single) => $single_body,
+            Handle::Many($many) => $many_body,
+            Handle::CompactMany($many) => $many_body,
+        }
+    };
 }
 
 /// An error that could occur during an equality or range lookup to a reader node.
@@ -89,28 +97,27 @@ impl<'a, T> LookupError<'a, T> {
 
 impl Handle {
     pub(super) fn timestamp(&self) -> Option<Timestamp> {
-        match *self {
-            Handle::Single(ref h) => h.timestamp().ok(),
-            Handle::Many(ref h) => h.timestamp().ok(),
-        }
+        dispatch!(
+            self,
+            Single(h) => h.timestamp().ok(),
+            Many(h) => h.timestamp().ok(),
+        )
     }
 
     pub(super) fn len(&self) -> usize {
-        match *self {
-            Handle::Single(ref h) => h.len(),
-            Handle::Many(ref h) => h.len(),
-        }
+        dispatch!(self, Single(h) => h.len(), Many(h) => h.len())
     }
 
     pub(super) fn keys(&self) -> Vec<Vec<DfValue>> {
-        match *self {
-            Handle::Single(ref h) => h.map_into(|k, _| vec![k.clone()]),
-            Handle::Many(ref h) => h.map_into(|ks, _| ks.clone()),
-        }
+        dispatch!(
+            self,
+            Single(h) => h.map_into(|k, _| vec![k.clone()]),
+            Many(h) => h.map_into(|ks, _| ks.clone()),
+        )
     }
 
-    fn get_multi_single_handle<'a, T, F: Fn() -> T>(
-        handle: &HandleSingle,
+    fn get_multi_single_handle<'a, R: ReaderRow, T, F: Fn() -> T>(
+        handle: &HandleSingle<R>,
         keys: &'a [KeyComparison],
         miss_meta: F,
     ) -> Result<SharedResults, LookupError<'a, T>> {
@@ -131,7 +138,7 @@ impl Handle {
                     hits.push(Default::default())
                 }
                 KeyComparison::Equal(k) => match map.get(&k[0]) {
-                    Some(v) => hits.push(v.as_ref().clone()),
+                    Some(v) => hits.push(R::to_shared_rows(v)),
                     None => misses.push(Cow::Borrowed(key)),
                 },
                 KeyComparison::Range((start, end)) => {
@@ -144,7 +151,7 @@ impl Handle {
                     let start_bound = start.as_ref().map(|v| &v[0]);
                     let end_bound = end.as_ref().map(|v| &v[0]);
                     match map.range(&(start_bound, end_bound)) {
-                        Ok(hit) => hits.extend(hit.map(|(_, v)| v.as_ref().clone())),
+                        Ok(hit) => hits.extend(hit.map(|(_, v)| R::to_shared_rows(v))),
                         Err(Miss(miss)) => misses.extend(miss.into_iter().map(|(start, end)| {
                             Cow::Owned(KeyComparison::Range((
                                 start.map(|s| vec1![s]),
@@ -163,8 +170,8 @@ impl Handle {
         }
     }
 
-    fn get_multi_many_handle<'a, T, F: Fn() -> T>(
-        handle: &HandleMany,
+    fn get_multi_many_handle<'a, R: ReaderRow, T, F: Fn() -> T>(
+        handle: &HandleMany<R>,
         keys: &'a [KeyComparison],
         miss_meta: F,
     ) -> Result<SharedResults, LookupError<'a, T>> {
@@ -185,7 +192,7 @@ impl Handle {
                     hits.push(Default::default())
                 }
                 KeyComparison::Equal(k) => match map.get(k.as_slice()) {
-                    Some(v) => hits.push(v.as_ref().clone()),
+                    Some(v) => hits.push(R::to_shared_rows(v)),
                     None => misses.push(Cow::Borrowed(key)),
                 },
                 KeyComparison::Range((start, end)) => {
@@ -199,7 +206,7 @@ impl Handle {
                         start.as_ref().map(|v| v.as_slice()),
                         end.as_ref().map(|v| v.as_slice()),
                     )) {
-                        Ok(hit) => hits.extend(hit.map(|(_, v)| v.as_ref().clone())),
+                        Ok(hit) => hits.extend(hit.map(|(_, v)| R::to_shared_rows(v))),
                         Err(Miss(miss)) => misses.extend(miss.into_iter().map(|(start, end)| {
                             Cow::Owned(KeyComparison::Range((
                                 start.map(|s| Vec1::try_from_vec(s).unwrap()),
@@ -224,10 +231,11 @@ impl Handle {
         &self,
         keys: &'a [KeyComparison],
     ) -> Result<SharedResults, LookupError<'a>> {
-        match self {
-            Handle::Single(h) => Self::get_multi_single_handle(h, keys, || {}),
-            Handle::Many(h) => Self::get_multi_many_handle(h, keys, || {}),
-        }
+        dispatch!(
+            self,
+            Single(h) => Self::get_multi_single_handle(h, keys, || {}),
+            Many(h) => Self::get_multi_many_handle(h, keys, || {}),
+        )
     }
 
     /// Retrieve results for multiple keys from the map under the same read guard, assuring that all
@@ -239,15 +247,17 @@ impl Handle {
         keys: &'a [KeyComparison],
         miss_meta: F,
     ) -> Result<SharedResults, LookupError<'a, T>> {
-        match self {
-            Handle::Single(h) => Self::get_multi_single_handle(h, keys, miss_meta),
-            Handle::Many(h) => Self::get_multi_many_handle(h, keys, miss_meta),
-        }
+        dispatch!(
+            self,
+            Single(h) => Self::get_multi_single_handle(h, keys, miss_meta),
+            Many(h) => Self::get_multi_many_handle(h, keys, miss_meta),
+        )
     }
 
     pub(super) fn get<'a>(&self, key: &'a [DfValue]) -> Result<SharedRows, LookupError<'a>> {
-        match self {
-            Handle::Single(h) => {
+        dispatch!(
+            self,
+            Single(h) => {
                 let map = h.enter()?;
                 let v = map.get(&key[0]).ok_or_else(|| {
                     LookupError::Miss((
@@ -255,9 +265,9 @@ impl Handle {
                         (),
                     ))
                 })?;
-                Ok(v.as_ref().clone())
-            }
-            Handle::Many(h) => {
+                Ok(ReaderRow::to_shared_rows(v))
+            },
+            Many(h) => {
                 let map = h.enter()?;
                 let v = map.get(key).ok_or_else(|| {
                     LookupError::Miss((
@@ -265,9 +275,9 @@ impl Handle {
                         (),
                     ))
                 })?;
-                Ok(v.as_ref().clone())
-            }
-        }
+                Ok(ReaderRow::to_shared_rows(v))
+            },
+        )
     }
 
     /// Returns Ok(true) if this handle contains the given key, Ok(false) if it doesn't, or an error
@@ -275,17 +285,18 @@ impl Handle {
     ///
     /// This is equivalent to testing if `get` returns an Err other than `NotReady`
     pub(super) fn contains_key(&self, key: &[DfValue]) -> reader_map::Result<bool> {
-        match *self {
-            Handle::Single(ref h) => {
+        dispatch!(
+            self,
+            Single(h) => {
                 assert_eq!(key.len(), 1);
                 let map = h.enter()?;
                 Ok(map.contains_key(&key[0]))
-            }
-            Handle::Many(ref h) => {
+            },
+            Many(h) => {
                 let map = h.enter()?;
                 Ok(map.contains_key(key))
-            }
-        }
+            },
+        )
     }
 
     /// Returns Ok(true) if this handle fully contains the given key range, Ok(false) if any of the
@@ -296,8 +307,9 @@ impl Handle {
     where
         R: RangeBounds<Vec<DfValue>>,
     {
-        match *self {
-            Handle::Single(ref h) => {
+        dispatch!(
+            self,
+            Single(h) => {
                 let map = h.enter()?;
                 let start_bound = range.start_bound().map(|v| {
                     assert!(v.len() == 1);
@@ -308,12 +320,12 @@ impl Handle {
                     &v[0]
                 });
                 Ok(map.contains_range(&(start_bound, end_bound)))
-            }
-            Handle::Many(ref h) => {
+            },
+            Many(h) => {
                 let map = h.enter()?;
                 Ok(map.contains_range(&(range.start_bound(), range.end_bound())))
-            }
-        }
+            },
+        )
     }
 
     /// Returns Ok(true) if this handle partially contains the given key range, Ok(false) if all of
@@ -322,8 +334,9 @@ impl Handle {
     where
         R: RangeBounds<Vec<DfValue>>,
     {
-        match *self {
-            Handle::Single(ref h) => {
+        dispatch!(
+            self,
+            Single(h) => {
                 let map = h.enter()?;
                 let start_bound = range.start_bound().map(|v| {
                     assert!(v.len() == 1);
@@ -334,20 +347,56 @@ impl Handle {
                     &v[0]
                 });
                 Ok(map.overlaps_range(&(start_bound, end_bound)))
-            }
-            Handle::Many(ref h) => {
+            },
+            Many(h) => {
                 let map = h.enter()?;
                 Ok(map.overlaps_range(&(range.start_bound(), range.end_bound())))
-            }
-        }
+            },
+        )
     }
 
     /// Returns true if the corresponding write handle has been dropped
     pub(super) fn was_dropped(&self) -> bool {
-        match self {
-            Handle::Single(h) => h.was_dropped(),
-            Handle::Many(h) => h.was_dropped(),
+        dispatch!(self, Single(h) => h.was_dropped(), Many(h) => h.was_dropped())
+    }
+
+    /// Returns the total size in bytes of the rows stored for the given key, as they are
+    /// represented in the map, or `None` if the key is missing or the map is not able to accept
+    /// reads
+    pub(super) fn rows_size(&self, key: &KeyComparison) -> Option<u64> {
+        fn sum<'a, R: ReaderRow + 'a>(rows: impl Iterator<Item = &'a R>) -> u64 {
+            rows.map(SizeOf::deep_size_of).sum()
         }
+
+        dispatch!(
+            self,
+            Single(h) => {
+                let map = h.enter().ok()?;
+                match key {
+                    KeyComparison::Equal(k) => map.get(&k[0]).map(|v| sum(v.iter())),
+                    KeyComparison::Range((start, end)) => map
+                        .range(&(
+                            start.as_ref().map(|v| &v[0]),
+                            end.as_ref().map(|v| &v[0]),
+                        ))
+                        .ok()
+                        .map(|hit| hit.map(|(_, v)| sum(v.iter())).sum()),
+                }
+            },
+            Many(h) => {
+                let map = h.enter().ok()?;
+                match key {
+                    KeyComparison::Equal(k) => map.get(k.as_slice()).map(|v| sum(v.iter())),
+                    KeyComparison::Range((start, end)) => map
+                        .range::<_, [DfValue]>(&(
+                            start.as_ref().map(|v| v.as_slice()),
+                            end.as_ref().map(|v| v.as_slice()),
+                        ))
+                        .ok()
+                        .map(|hit| hit.map(|(_, v)| sum(v.iter())).sum()),
+                }
+            },
+        )
     }
 }
 
@@ -393,7 +442,7 @@ mod tests {
             let (mut w, handle) = make_many();
             w.insert(key.to_vec(), val.clone());
             w.publish();
-            assert_eq!(handle.get(&key[..]).unwrap()[0], val);
+            assert_eq!(handle.get(&key[..]).unwrap().get(0).unwrap(), &val[..]);
         }
     }
 
@@ -419,7 +468,7 @@ mod tests {
         assert_eq!(
             res.iter()
                 .flat_map(|rs| rs.iter())
-                .cloned()
+                .map(|row| row.into_owned().into_boxed_slice())
                 .collect::<Vec<_>>(),
             (2i32..=3)
                 .map(|n| vec![DfValue::from(n), DfValue::from(n)].into_boxed_slice())
@@ -468,7 +517,7 @@ mod tests {
         assert_eq!(
             res.iter()
                 .flat_map(|rs| rs.iter())
-                .cloned()
+                .map(|row| row.into_owned().into_boxed_slice())
                 .collect::<Vec<_>>(),
             (2i32..=3)
                 .map(|n: i32| vec![DfValue::from(n), DfValue::from(n)].into_boxed_slice())
@@ -500,7 +549,7 @@ mod tests {
         assert_eq!(
             res.iter()
                 .flat_map(|rs| rs.iter())
-                .cloned()
+                .map(|row| row.into_owned().into_boxed_slice())
                 .collect::<Vec<_>>(),
             (1i32..=2)
                 .map(|n| vec![DfValue::from(n), DfValue::from(n)].into_boxed_slice())
@@ -536,7 +585,7 @@ mod tests {
         assert_eq!(
             res.iter()
                 .flat_map(|rs| rs.iter())
-                .cloned()
+                .map(|row| row.into_owned().into_boxed_slice())
                 .collect::<Vec<_>>(),
             (1i32..=2)
                 .map(|n| vec![DfValue::from(n), DfValue::from(n)].into_boxed_slice())
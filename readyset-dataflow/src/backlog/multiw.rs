@@ -1,76 +1,84 @@
+use std::time::Duration;
+
 use ahash::RandomState;
 use dataflow_expression::PreInsertion;
-use reader_map::EvictionQuantity;
+use reader_map::{CompactRow, EvictionQuantity};
 use readyset_client::consistency::Timestamp;
 use readyset_data::Bound;
 use readyset_util::ranges::RangeBounds;
 
-use super::{key_to_single, Key};
+use super::{key_to_single, Key, ReaderRow};
 use crate::prelude::*;
 
+type HandleSingle<R = Box<[DfValue]>> =
+    reader_map::handles::WriteHandle<DfValue, R, PreInsertion, i64, Timestamp, RandomState>;
+
+type HandleMany<R = Box<[DfValue]>> =
+    reader_map::handles::WriteHandle<Vec<DfValue>, R, PreInsertion, i64, Timestamp, RandomState>;
+
 pub(super) enum Handle {
-    Single(
-        reader_map::handles::WriteHandle<
-            DfValue,
-            Box<[DfValue]>,
-            PreInsertion,
-            i64,
-            Timestamp,
-            RandomState,
-        >,
-    ),
-    Many(
-        reader_map::handles::WriteHandle<
-            Vec<DfValue>,
-            Box<[DfValue]>,
-            PreInsertion,
-            i64,
-            Timestamp,
-            RandomState,
-        >,
-    ),
+    Single(HandleSingle),
+    Many(HandleMany),
+    /// Like [`Handle::Single`], but storing rows as [`CompactRow`]s
+    CompactSingle(HandleSingle<CompactRow>),
+    /// Like [`Handle::Many`], but storing rows as [`CompactRow`]s
+    CompactMany(HandleMany<CompactRow>),
+}
+
+/// Match on a [`Handle`], running the same (generic) code for both row representations of a
+/// single-column or multi-column key
+macro_rules! dispatch {
+    ($handle:expr, Single($single:pat) => $single_body:expr, Many($many:pat) => $many_body:expr $(,)?) => {
+        match $handle {
+            Handle::Single($single) => $single_body,
+            Handle::CompactSingle($single) => $single_body,
+            Handle::Many($many) => $many_body,
+            Handle::CompactMany($many) => $many_body,
+        }
+    };
 }
 
 impl Handle {
     pub fn base_value_size(&self) -> usize {
-        match *self {
-            Handle::Single(ref h) => h.base_value_size(),
-            Handle::Many(ref h) => h.base_value_size(),
-        }
+        dispatch!(
+            self,
+            Single(h) => h.base_value_size(),
+            Many(h) => h.base_value_size(),
+        )
     }
 
     pub fn is_empty(&self) -> bool {
-        match *self {
-            Handle::Single(ref h) => h.is_empty(),
-            Handle::Many(ref h) => h.is_empty(),
-        }
+        dispatch!(self, Single(h) => h.is_empty(), Many(h) => h.is_empty())
     }
 
     pub fn clear(&mut self, k: Key) {
-        match *self {
-            Handle::Single(ref mut h) => {
+        dispatch!(
+            self,
+            Single(h) => {
                 h.clear(key_to_single(k).into_owned());
-            }
-            Handle::Many(ref mut h) => {
+            },
+            Many(h) => {
                 h.clear(k.into_owned());
-            }
-        }
+            },
+        )
     }
 
     pub fn empty(&mut self, k: Key) {
-        match *self {
-            Handle::Single(ref mut h) => {
+        dispatch!(
+            self,
+            Single(h) => {
                 h.remove_entry(key_to_single(k).into_owned());
-            }
-            Handle::Many(ref mut h) => {
+            },
+            Many(h) => {
                 h.remove_entry(k.into_owned());
-            }
-        }
+            },
+        )
     }
 
     pub fn empty_range(&mut self, range: (Bound<Vec<DfValue>>, Bound<Vec<DfValue>>)) {
-        match self {
-            Handle::Single(h) => {
+        dispatch!(
+            self,
+            Single(h) => {
                 h.remove_range((
                     range.0.map(|mut r| {
                         debug_assert_eq!(r.len(), 1);
@@ -81,11 +89,11 @@ impl Handle {
                         r.pop().unwrap()
                     }),
                 ));
-            }
-            Handle::Many(h) => {
+            },
+            Many(h) => {
                 h.remove_range(range);
-            }
-        }
+            },
+        )
     }
 
     /// Evict keys that were selected by the assigned eviction strategy from the state. The amount
@@ -95,8 +103,9 @@ impl Handle {
     /// the key that was evicted.
     pub fn evict(&mut self, keys_to_evict: EvictionQuantity) -> (u64, Option<Vec<DfValue>>) {
         let base_value_size = self.base_value_size() as u64;
-        match *self {
-            Handle::Single(ref mut h) => {
+        dispatch!(
+            self,
+            Single(h) => {
                 let (bytes, key) = h.evict_keys(keys_to_evict, |k, v| {
                     // Each row's state is composed of: The key, the set of Values in the row
                     // (DfValues) and the bytes required to hold the Row data
@@ -107,22 +116,37 @@ impl Handle {
                 });
                 // Convert the DfValue key to a Vec<DfValue>
                 (bytes, key.map(|k| vec![k]))
-            }
-            Handle::Many(ref mut h) => h.evict_keys(keys_to_evict, |k, v| {
+            },
+            Many(h) => h.evict_keys(keys_to_evict, |k, v| {
                 k.deep_size_of() + v.iter().map(|r| r.deep_size_of()).sum::<u64>() + base_value_size
             }),
-        }
+        )
+    }
+
+    /// Record how long it took to compute the rows for the given key, for use by the eviction
+    /// strategy
+    pub fn record_cost(&self, key: &[DfValue], cost: Duration) {
+        dispatch!(
+            self,
+            Single(h) => {
+                if let Some(k) = key.first() {
+                    h.record_cost(k, cost);
+                }
+            },
+            Many(h) => h.record_cost(key, cost),
+        )
     }
 
     pub fn refresh(&mut self) {
-        match *self {
-            Handle::Single(ref mut h) => {
+        dispatch!(
+            self,
+            Single(h) => {
                 h.publish();
-            }
-            Handle::Many(ref mut h) => {
+            },
+            Many(h) => {
                 h.publish();
-            }
-        }
+            },
+        )
     }
 
     pub fn add<I>(&mut self, key: &[usize], cols: usize, rs: I) -> isize
@@ -130,60 +154,65 @@ impl Handle {
         I: IntoIterator<Item = Record>,
     {
         let mut memory_delta = 0isize;
-        match *self {
-            Handle::Single(ref mut h) => {
+        dispatch!(
+            self,
+            Single(h) => {
                 assert_eq!(key.len(), 1);
                 for r in rs {
                     debug_assert!(r.len() >= cols);
                     match r {
                         Record::Positive(r) => {
-                            memory_delta += r.deep_size_of() as isize;
-                            h.insert(r[key[0]].clone(), r.into_boxed_slice());
+                            let k = r[key[0]].clone();
+                            let row = ReaderRow::from_row(r);
+                            memory_delta += SizeOf::deep_size_of(&row) as isize;
+                            h.insert(k, row);
                         }
                         Record::Negative(r) => {
                             // TODO: reader_map will remove the empty vec for a key if we remove the
                             // last record. this means that future lookups will fail, and cause a
                             // replay, which will produce an empty result. this will work, but is
                             // somewhat inefficient.
-                            memory_delta -= r.deep_size_of() as isize;
-                            h.remove_value(r[key[0]].clone(), r.into_boxed_slice());
+                            let k = r[key[0]].clone();
+                            let row = ReaderRow::from_row(r);
+                            memory_delta -= SizeOf::deep_size_of(&row) as isize;
+                            h.remove_value(k, row);
                         }
                     }
                 }
-            }
-            Handle::Many(ref mut h) => {
+            },
+            Many(h) => {
                 for r in rs {
                     debug_assert!(r.len() >= cols);
                     let key = key.iter().map(|&k| &r[k]).cloned().collect();
                     match r {
                         Record::Positive(r) => {
-                            memory_delta += r.deep_size_of() as isize;
-                            h.insert(key, r.into_boxed_slice());
+                            let row = ReaderRow::from_row(r);
+                            memory_delta += SizeOf::deep_size_of(&row) as isize;
+                            h.insert(key, row);
                         }
                         Record::Negative(r) => {
-                            memory_delta -= r.deep_size_of() as isize;
-                            h.remove_value(key, r.into_boxed_slice());
+                            let row = ReaderRow::from_row(r);
+                            memory_delta -= SizeOf::deep_size_of(&row) as isize;
+                            h.remove_value(key, row);
                         }
                     }
                 }
-            }
-        }
+            },
+        );
         memory_delta
     }
 
     pub fn set_timestamp(&mut self, t: Timestamp) {
-        match *self {
-            Handle::Single(ref mut h) => h.set_timestamp(t),
-            Handle::Many(ref mut h) => h.set_timestamp(t),
-        }
+        dispatch!(self, Single(h) => h.set_timestamp(t), Many(h) => h.set_timestamp(t))
     }
 
     pub fn insert_range<R>(&mut self, range: R)
     where
         R: RangeBounds<Vec<DfValue>>,
     {
-        match self {
-            Handle::Single(h) => {
+        dispatch!(
+            self,
+            Single(h) => {
                 h.insert_range((
                     range.start_bound().map(|r| {
                         debug_assert_eq!(r.len(), 1);
@@ -194,17 +223,19 @@ impl Handle {
                         &r[0]
                     }),
                 ));
-            }
-            Handle::Many(h) => {
+            },
+            Many(h) => {
                 h.insert_range(range);
-            }
-        }
+            },
+        )
     }
 
     pub fn read(&self) -> super::multir::Handle {
         match self {
             Handle::Single(h) => super::multir::Handle::Single((*h).clone()),
             Handle::Many(h) => super::multir::Handle::Many((*h).clone()),
+            Handle::CompactSingle(h) => super::multir::Handle::CompactSingle((*h).clone()),
+            Handle::CompactMany(h) => super::multir::Handle::CompactMany((*h).clone()),
         }
     }
 }
@@ -7,16 +7,17 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::{TryFrom, TryInto};
 use std::fmt::Debug;
 use std::ops::Bound;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use std::{cell, cmp, mem, process, time};
+use std::{cell, cmp, fs, mem, process, time};
 
 use ahash::RandomState;
 use backoff::ExponentialBackoffBuilder;
 use dataflow_state::{
     BaseTableState, EvictBytesResult, EvictKeysResult, EvictRandomResult, MaterializedNodeState,
-    PointKey, RangeKey, RangeLookupResult,
+    PointKey, RangeKey, RangeLookupResult, TieredState,
 };
 use failpoint_macros::failpoint;
 use futures_util::future::FutureExt;
@@ -52,8 +53,8 @@ use crate::domain::channel::{ChannelCoordinator, DomainReceiver, DomainSender};
 use crate::node::special::EgressTx;
 use crate::node::{NodeProcessingResult, ProcessEnv};
 use crate::payload::{
-    EvictRequest, MaterializedState, PacketDiscriminants, PrepareStateKind, PrettyReplayPath,
-    ReplayPieceContext, SourceSelection,
+    BaseTableCheckpoint, EvictRequest, MaterializedState, PacketDiscriminants, PrepareStateKind,
+    PrettyReplayPath, ReplayPieceContext, SourceSelection,
 };
 use crate::prelude::*;
 use crate::processing::ColumnMiss;
@@ -82,6 +83,12 @@ pub struct Config {
     /// Whether to emit verbose metrics for the domain.
     #[serde(default)]
     pub verbose_metrics: bool,
+
+    /// If set to `true`, readers will store their rows in a compact binary encoding rather than
+    /// as `DfValue`s, reducing the memory used by caches at the cost of decoding rows on every
+    /// read.
+    #[serde(default)]
+    pub compact_reader_rows: bool,
 }
 
 const BATCH_SIZE: usize = 256;
@@ -281,6 +288,14 @@ impl RequestedKeys {
         }
     }
 
+    /// Remove all keys from `self`
+    fn clear(&mut self) {
+        match self {
+            RequestedKeys::Points(requested) => requested.clear(),
+            RequestedKeys::Ranges(requested) => *requested = Default::default(),
+        }
+    }
+
     /// Extend `self` with the given `keys`, mutating `keys` in-place such that it contains only
     /// those keys or subranges of keys that were not already in `self`.
     ///
@@ -469,6 +484,7 @@ impl DomainBuilder {
             mode: DomainMode::Forwarding,
             waiting: Default::default(),
             reader_triggered: Default::default(),
+            reader_replays_started: Default::default(),
             replay_paths: Default::default(),
 
             ingress_inject: Default::default(),
@@ -495,6 +511,7 @@ impl DomainBuilder {
             metrics: domain_metrics::DomainMetrics::new(self.config.verbose_metrics),
 
             eviction_kind: self.config.eviction_kind,
+            compact_reader_rows: self.config.compact_reader_rows,
             remapped_keys: Default::default(),
 
             init_state_tx,
@@ -674,6 +691,11 @@ pub struct Domain {
     /// node
     reader_triggered: NodeMap<RequestedKeys>,
 
+    /// When using [`EvictionKind::CostAware`](crate::EvictionKind::CostAware) eviction, the time
+    /// at which each currently pending upquery to a reader node was requested, so that the cost of
+    /// filling each key can be recorded in the reader
+    reader_replays_started: NodeMap<HashMap<KeyComparison, time::Instant>>,
+
     /// Queue of purge operations to be performed on reader nodes at some point in the future, used
     /// as part of the implementation of materialization frontiers
     ///
@@ -708,6 +730,7 @@ pub struct Domain {
 
     metrics: domain_metrics::DomainMetrics,
     eviction_kind: crate::EvictionKind,
+    compact_reader_rows: bool,
 
     /// This channel is used to notify the replica that a base node has its persistent state
     /// initialized.
@@ -1627,12 +1650,21 @@ impl Domain {
                     PrepareStateKind::Partial {
                         strict_indices,
                         weak_indices,
+                        tiered,
                     } => {
                         if !self.state.contains_key(node) {
-                            self.state.insert(
-                                node,
-                                MaterializedNodeState::Memory(MemoryState::default()),
-                            );
+                            let state = match tiered {
+                                Some(params) => {
+                                    debug!(local = %node, ?params, "preparing tiered partial state");
+                                    MaterializedNodeState::Tiered(TieredState::new(
+                                        &format!("{}-{}-{}", self.index, self.shard(), node.id()),
+                                        &self.persistence_parameters,
+                                        params,
+                                    )?)
+                                }
+                                None => MaterializedNodeState::Memory(MemoryState::default()),
+                            };
+                            self.state.insert(node, state);
                         }
                         let state = self.state.get_mut(node).unwrap();
                         for (index, tags) in strict_indices {
@@ -1780,6 +1812,7 @@ impl Domain {
                             },
                             self.eviction_kind,
                             r.reader_processing().clone(),
+                            self.compact_reader_rows,
                         );
 
                         let shard = *self.shard.as_ref().unwrap_or(&0);
@@ -1829,8 +1862,12 @@ impl Domain {
                                     expected_type: NodeType::Reader,
                                 })?;
 
-                        let (r_part, w_part) =
-                            backlog::new(num_columns, index, r.reader_processing().clone());
+                        let (r_part, w_part) = backlog::new(
+                            num_columns,
+                            index,
+                            r.reader_processing().clone(),
+                            self.compact_reader_rows,
+                        );
 
                         let shard = *self.shard.as_ref().unwrap_or(&0);
                         // TODO(ENG-838): Don't recreate every single node on leader failure.
@@ -2280,6 +2317,10 @@ impl Domain {
                                     .get(local_index)
                                     .map(|s| s.deep_size_of())
                                     .unwrap_or(0)
+                                    + self
+                                        .auxiliary_node_states
+                                        .get(local_index)
+                                        .map_or(0, SizeOf::deep_size_of)
                             });
 
                         let mat_state = self
@@ -2343,6 +2384,9 @@ impl Domain {
             DomainRequest::RequestSnapshottingTables => {
                 Ok(Some(bincode::serialize(&self.snapshotting_base_nodes())?))
             }
+            DomainRequest::Checkpoint { dir } => Ok(Some(bincode::serialize(
+                &self.checkpoint_base_tables(&dir)?,
+            )?)),
             DomainRequest::RequestNodeSizes => {
                 let mut res = Vec::new();
                 for (local_index, node_ref) in self.nodes.iter() {
@@ -2357,7 +2401,12 @@ impl Domain {
                         }
                     } else if let Some(state) = self.state.get(local_index) {
                         // non-reader node with state
-                        res.push((node.global_addr(), state.key_count(), state.deep_size_of()))
+                        let size = state.deep_size_of()
+                            + self
+                                .auxiliary_node_states
+                                .get(local_index)
+                                .map_or(0, SizeOf::deep_size_of);
+                        res.push((node.global_addr(), state.key_count(), size))
                     }
                 }
                 Ok(Some(bincode::serialize(&res)?))
@@ -2411,6 +2460,62 @@ impl Domain {
                 let key = self.handle_eviction(req, executor)?;
                 Ok(Some(bincode::serialize(&key)?))
             }
+            DomainRequest::ClearPartialState { node, tags } => {
+                if let Some(wh) = self.reader_write_handles.get_mut(node) {
+                    // Readers only have one index, every key of which is filled by replays along
+                    // all of the reader's replay paths
+                    invariant!(wh.is_partial());
+                    wh.evict_all();
+                    wh.swap();
+                    wh.notify_readers_of_eviction()?;
+                    if let Some(requested) = self.reader_triggered.get_mut(node) {
+                        requested.clear();
+                    }
+                    self.reader_replays_started.remove(node);
+                    for waiting in self.waiting.values_mut() {
+                        waiting.redos.retain(|hole, _| hole.node != node);
+                    }
+                } else if let Some(state) = self.state.get_mut(node) {
+                    invariant!(state.is_partial());
+                    let mut rng = rand::thread_rng();
+                    let mut cleared_columns = HashSet::new();
+                    for tag in tags {
+                        while let Some(EvictRandomResult {
+                            index, key_evicted, ..
+                        }) = state.evict_random(tag, &mut rng)
+                        {
+                            if let Some(auxiliary_node_state) =
+                                self.auxiliary_node_states.get_mut(node)
+                            {
+                                let key = KeyComparison::try_from(key_evicted)
+                                    .map_err(|_| internal_err!("Empty key evicted"))?;
+                                auxiliary_node_state.evict_keys(&index.columns, &[key]);
+                            }
+                        }
+                        if let Some(index) = self
+                            .replay_paths
+                            .get(tag)
+                            .and_then(|path| path.target_index.as_ref())
+                        {
+                            cleared_columns.insert(index.columns.clone());
+                        }
+                    }
+
+                    // Any replays for the cleared keys that we're still waiting on might never
+                    // arrive, so forget about them - if they do arrive, they'll be discarded, and
+                    // any new upqueries for the same keys will be sent again
+                    for waiting in self.waiting.values_mut() {
+                        waiting.redos.retain(|hole, _| {
+                            hole.node != node || !cleared_columns.contains(&hole.column_indices)
+                        });
+                    }
+                } else {
+                    internal!("Asked to clear the state of non-materialized node {node}");
+                }
+
+                self.update_state_sizes();
+                Ok(None)
+            }
         };
 
         // What we just did might have done things like insert into `self.delayed_for_self`, so
@@ -2520,6 +2625,15 @@ impl Domain {
                     .entry(node)
                     .or_insert_with(|| RequestedKeys::new(reader_index_type));
                 already_requested.extend(&mut keys);
+                if self.eviction_kind == crate::EvictionKind::CostAware {
+                    // Ranges can be filled piecemeal, under different keys than the ones requested,
+                    // and the reader only records the cost of equality keys anyway
+                    let now = time::Instant::now();
+                    let started = self.reader_replays_started.entry(node).or_default();
+                    for key in keys.iter().filter(|k| matches!(k, KeyComparison::Equal(_))) {
+                        started.entry(key.clone()).or_insert(now);
+                    }
+                }
                 if !keys.is_empty() {
                     self.find_tags_and_replay(
                         keys,
@@ -3133,6 +3247,13 @@ impl Domain {
                                 for key in backfill_keys.iter() {
                                     trace!(?key, local = %segment.node, "Marking filled in reader");
                                     wh.mark_filled(key.clone())?;
+                                    if let Some(started) = self
+                                        .reader_replays_started
+                                        .get_mut(segment.node)
+                                        .and_then(|started| started.remove(key))
+                                    {
+                                        wh.record_replay_cost(key, started.elapsed());
+                                    }
                                 }
                             }
                         }
@@ -3954,9 +4075,15 @@ impl Domain {
             reader_write_handles: &mut NodeMap<backlog::WriteHandle>,
             nodes: &DomainNodes,
             remapped_keys: &mut RemappedKeys,
+            auxiliary_node_states: &mut AuxiliaryNodeStateMap,
         ) -> ReadySetResult<u64> {
             let mut bytes_freed = 0u64;
 
+            // Any auxiliary state the node keeps for the evicted keys is no use anymore either
+            if let Some(auxiliary_node_state) = auxiliary_node_states.get_mut(node) {
+                bytes_freed += auxiliary_node_state.evict_keys(&index.columns, keys);
+            }
+
             for (tag, path, keys) in
                 replay_paths.downstream_dependent_paths(node, index, keys, remapped_keys)
             {
@@ -4019,6 +4146,7 @@ impl Domain {
                                 reader_write_handles,
                                 nodes,
                                 remapped_keys,
+                                auxiliary_node_states,
                             )?;
                         }
                     }
@@ -4070,6 +4198,7 @@ impl Domain {
             num_bytes: &mut usize,
             state: &StateMap,
             reader_write_handles: &NodeMap<backlog::WriteHandle>,
+            auxiliary_node_states: &AuxiliaryNodeStateMap,
         ) -> Vec<(LocalNodeIndex, usize)> {
             let mut candidates: Vec<_> = nodes
                 .values()
@@ -4087,7 +4216,12 @@ impl Domain {
                         state
                             .get(local_index)
                             .filter(|state| state.is_partial())
-                            .map(|state| state.deep_size_of())
+                            .map(|state| {
+                                state.deep_size_of()
+                                    + auxiliary_node_states
+                                        .get(local_index)
+                                        .map_or(0, SizeOf::deep_size_of)
+                            })
                     }
                     .map(|s| (local_index, s))
                 })
@@ -4152,6 +4286,7 @@ impl Domain {
                         &mut num_bytes,
                         &self.state,
                         &self.reader_write_handles,
+                        &self.auxiliary_node_states,
                     )
                 };
 
@@ -4197,6 +4332,7 @@ impl Domain {
                                 &mut self.reader_write_handles,
                                 &self.nodes,
                                 &mut self.remapped_keys,
+                                &mut self.auxiliary_node_states,
                             )?;
                         }
                     } else {
@@ -4278,6 +4414,7 @@ impl Domain {
                                 &mut self.reader_write_handles,
                                 &self.nodes,
                                 &mut self.remapped_keys,
+                                &mut self.auxiliary_node_states,
                             )?;
                             self.state_size.fetch_sub(freed as usize, Ordering::AcqRel);
                         }
@@ -4383,6 +4520,7 @@ impl Domain {
                                 &mut self.reader_write_handles,
                                 &self.nodes,
                                 &mut self.remapped_keys,
+                                &mut self.auxiliary_node_states,
                             )?;
                             (bytes_freed + freed, Some(key_evicted))
                         };
@@ -4410,6 +4548,14 @@ impl Domain {
         }
     }
 
+    /// Returns true if this domain contains neither base tables nor readers
+    pub fn contains_only_internal_nodes(&self) -> bool {
+        self.nodes.values().all(|n| {
+            let n = n.borrow();
+            !n.is_base() && !n.is_reader()
+        })
+    }
+
     pub fn update_state_sizes(&mut self) {
         let mut reader_size: u64 = 0;
         let total: u64 = self
@@ -4437,7 +4583,13 @@ impl Domain {
                     self.state
                         .get(local_index)
                         .filter(|state| state.is_partial())
-                        .map(|s| s.deep_size_of())
+                        .map(|s| {
+                            s.deep_size_of()
+                                + self
+                                    .auxiliary_node_states
+                                    .get(local_index)
+                                    .map_or(0, SizeOf::deep_size_of)
+                        })
                         .unwrap_or(0)
                 }
             })
@@ -4512,6 +4664,50 @@ impl Domain {
             .collect()
     }
 
+    /// Write a checkpoint of the persistent state of every base table node in this domain into
+    /// `dir`.
+    ///
+    /// Returns an error if any of the base tables in this domain are not persisted, since the
+    /// resulting backup could not be restored.
+    pub fn checkpoint_base_tables(
+        &self,
+        dir: &Path,
+    ) -> ReadySetResult<NodeMap<BaseTableState<BaseTableCheckpoint>>> {
+        fs::create_dir_all(dir)?;
+        let mut res = NodeMap::default();
+        for (idx, n) in self.nodes.iter() {
+            let node = n.borrow();
+            if !node.is_base() || node.is_dropped() {
+                continue;
+            }
+
+            let Some(state) = self.state.get(idx) else {
+                res.insert(idx, BaseTableState::Pending);
+                continue;
+            };
+            let persistent = state.as_persistent().ok_or_else(|| {
+                internal_err!(
+                    "Base table {} is not persisted",
+                    node.name().display_unquoted()
+                )
+            })?;
+            let path = persistent.checkpoint(dir)?;
+            res.insert(
+                idx,
+                BaseTableState::Initialized(BaseTableCheckpoint {
+                    file_name: path
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy()
+                        .into_owned(),
+                    replication_offset: persistent.replication_offset().cloned(),
+                }),
+            );
+        }
+
+        Ok(res)
+    }
+
     /// If there is a pending timed purge, return the duration until it needs
     /// to happen
     pub fn next_poll_duration(&mut self) -> Option<time::Duration> {
@@ -95,6 +95,7 @@ pub enum EvictionKind {
     Random,
     LRU,
     Generational,
+    CostAware,
 }
 
 impl Display for EvictionKind {
@@ -103,6 +104,7 @@ impl Display for EvictionKind {
             Self::Random => write!(f, "random"),
             Self::LRU => write!(f, "lru"),
             Self::Generational => write!(f, "generational"),
+            Self::CostAware => write!(f, "cost-aware"),
         }
     }
 }
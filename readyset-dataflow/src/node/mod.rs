@@ -2,11 +2,13 @@ use std::collections::{HashMap, HashSet};
 
 use nom_sql::{ColumnSpecification, Relation, SqlIdentifier};
 use readyset_client::consistency::Timestamp;
+use readyset_client::KeyComparison;
 use readyset_data::{DfType, Dialect};
 use serde::{Deserialize, Serialize};
 
 use crate::ops::grouped::aggregate::AggregatorState;
 use crate::ops::grouped::concat::GroupConcatState;
+use crate::ops::grouped::extremum::ExtremumState;
 use crate::ops::{self};
 use crate::prelude::*;
 use crate::processing::LookupIndex;
@@ -191,6 +193,39 @@ impl DanglingDomainNode {
 pub enum AuxiliaryNodeState {
     Aggregation(AggregatorState),
     Concat(GroupConcatState),
+    Extremum(ExtremumState),
+}
+
+impl AuxiliaryNodeState {
+    /// Evict any state kept for the given keys, which are on the given columns of the node's
+    /// output, once those keys have been evicted from the node's materialization. Returns the
+    /// number of bytes freed.
+    pub(crate) fn evict_keys(&mut self, columns: &[usize], keys: &[KeyComparison]) -> u64 {
+        match self {
+            AuxiliaryNodeState::Extremum(state) => state.evict_keys(columns, keys),
+            AuxiliaryNodeState::Aggregation(_) | AuxiliaryNodeState::Concat(_) => 0,
+        }
+    }
+}
+
+impl SizeOf for AuxiliaryNodeState {
+    fn deep_size_of(&self) -> u64 {
+        match self {
+            AuxiliaryNodeState::Extremum(state) => state.deep_size_of(),
+            AuxiliaryNodeState::Aggregation(_) | AuxiliaryNodeState::Concat(_) => self.size_of(),
+        }
+    }
+
+    fn size_of(&self) -> u64 {
+        std::mem::size_of::<Self>() as u64
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            AuxiliaryNodeState::Extremum(state) => state.is_empty(),
+            AuxiliaryNodeState::Aggregation(_) | AuxiliaryNodeState::Concat(_) => false,
+        }
+    }
 }
 
 // external parts of Ingredient
@@ -370,8 +405,8 @@ impl Node {
                     Some(AuxiliaryNodeState::Aggregation(Default::default()))
                 }
                 NodeOperator::Concat(_) => Some(AuxiliaryNodeState::Concat(Default::default())),
-                NodeOperator::Extremum(_)
-                | NodeOperator::Join(_)
+                NodeOperator::Extremum(_) => Some(AuxiliaryNodeState::Extremum(Default::default())),
+                NodeOperator::Join(_)
                 | NodeOperator::Paginate(_)
                 | NodeOperator::Project(_)
                 | NodeOperator::Union(_)
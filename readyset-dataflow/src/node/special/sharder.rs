@@ -139,8 +139,10 @@ impl Sharder {
         num_shards: usize,
         replication: SenderReplication,
     ) {
-        debug_assert_eq!(self.txs.len(), 0);
         // TODO: add support for "shared" sharder?
+        // A sharder has only one child, so adding it again (eg after the number of replicas of its
+        // domain changes) replaces the existing txs
+        self.txs.clear();
         self.txs.extend((0..num_shards).map(|shard| {
             (
                 shard,
@@ -2,7 +2,8 @@ use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 
-pub use nom_sql::{BinaryOperator, Literal, SqlType};
+use dataflow_expression::sketch::{HyperLogLog, TDigest};
+pub use nom_sql::{BinaryOperator, Double, Literal, SqlType};
 use readyset_data::{Collation, DfType};
 use readyset_errors::{invariant, ReadySetResult};
 use serde::{Deserialize, Serialize};
@@ -22,6 +23,19 @@ pub enum Aggregation {
     Avg,
     /// Concatenates using the given separator between values.
     GroupConcat { separator: String },
+    /// Estimate the number of distinct non-null values of the `over` column, using a
+    /// [`HyperLogLog`] sketch.
+    ///
+    /// The output column contains the serialized sketch rather than the estimate itself, so that
+    /// sketches for multiple groups can be merged post-lookup; the estimate is computed by the
+    /// reader.
+    ApproxCountDistinct,
+    /// Estimate the value of the `over` column at the given percentile (between 0 and 1), using a
+    /// [`TDigest`] sketch.
+    ///
+    /// As with [`ApproxCountDistinct`](Aggregation::ApproxCountDistinct), the output column
+    /// contains the serialized sketch.
+    ApproxPercentile { percentile: Double },
 }
 
 impl Aggregation {
@@ -50,6 +64,8 @@ impl Aggregation {
                 }
             }
             Aggregation::GroupConcat { .. } => DfType::Text(/* TODO */ Collation::default()),
+            Aggregation::ApproxCountDistinct => DfType::BigInt,
+            Aggregation::ApproxPercentile { .. } => DfType::Double,
         };
 
         Ok(GroupedOperator::new(
@@ -153,6 +169,51 @@ impl Aggregator {
             _ => internal!(),
         }
     }
+
+    /// Apply diffs to the serialized sketch for a group, for the sketch-based aggregations.
+    ///
+    /// Sketches can't have values removed from them, so any negative diff means the sketch has to
+    /// be rebuilt from all the records in the group, which we signal by returning `None`.
+    fn apply_sketch(
+        &self,
+        current: Option<&DfValue>,
+        diffs: &mut dyn Iterator<Item = NumericalDiff>,
+    ) -> ReadySetResult<Option<DfValue>> {
+        match self.op {
+            Aggregation::ApproxCountDistinct => {
+                let mut hll = match current {
+                    Some(current) => HyperLogLog::from_df_value(current)?,
+                    None => HyperLogLog::new(),
+                };
+                for diff in diffs {
+                    if !diff.positive {
+                        return Ok(None);
+                    }
+                    if !diff.value.is_none() {
+                        hll.insert(&diff.value);
+                    }
+                }
+                Ok(Some((&hll).into()))
+            }
+            Aggregation::ApproxPercentile { .. } => {
+                let mut digest = match current {
+                    Some(current) => TDigest::from_df_value(current)?,
+                    None => TDigest::new(),
+                };
+                for diff in diffs {
+                    if !diff.positive {
+                        return Ok(None);
+                    }
+                    if !diff.value.is_none() {
+                        digest.insert(f64::try_from(&diff.value)?);
+                    }
+                }
+                digest.compress();
+                Ok(Some((&digest).into()))
+            }
+            _ => internal!("{:?} is not a sketch aggregation", self.op),
+        }
+    }
 }
 
 impl GroupedOperation for Aggregator {
@@ -190,6 +251,13 @@ impl GroupedOperation for Aggregator {
         diffs: &mut dyn Iterator<Item = Self::Diff>,
         auxiliary_node_state: Option<&mut AuxiliaryNodeState>,
     ) -> ReadySetResult<Option<DfValue>> {
+        if matches!(
+            self.op,
+            Aggregation::ApproxCountDistinct | Aggregation::ApproxPercentile { .. }
+        ) {
+            return self.apply_sketch(current, diffs);
+        }
+
         let apply_count = |curr: DfValue, diff: Self::Diff| -> ReadySetResult<DfValue> {
             if diff.positive {
                 &curr + &DfValue::Int(1)
@@ -237,6 +305,9 @@ impl GroupedOperation for Aggregator {
                     Aggregation::GroupConcat { separator: _ } => internal!(
                         "GroupConcats are separate from the other aggregations in the dataflow."
                     ),
+                    Aggregation::ApproxCountDistinct | Aggregation::ApproxPercentile { .. } => {
+                        internal!("Sketch aggregations are handled by apply_sketch")
+                    }
                 }
             };
 
@@ -254,6 +325,8 @@ impl GroupedOperation for Aggregator {
                 Aggregation::GroupConcat { separator: ref s } => {
                     format!("||({})", s)
                 }
+                Aggregation::ApproxCountDistinct => "~|*|".to_owned(),
+                Aggregation::ApproxPercentile { .. } => "~Pct".to_owned(),
             };
         }
 
@@ -262,6 +335,10 @@ impl GroupedOperation for Aggregator {
             Aggregation::Sum => format!("𝛴({})", self.over),
             Aggregation::Avg => format!("Avg({})", self.over),
             Aggregation::GroupConcat { separator: ref s } => format!("||({}, {})", s, self.over),
+            Aggregation::ApproxCountDistinct => format!("~|DISTINCT {}|", self.over),
+            Aggregation::ApproxPercentile { ref percentile } => {
+                format!("~Pct({}, {})", self.over, percentile.value)
+            }
         };
         let group_cols = self
             .group
@@ -297,7 +374,10 @@ impl GroupedOperation for Aggregator {
     }
 
     fn can_lose_state(&self) -> bool {
-        false
+        matches!(
+            self.op,
+            Aggregation::ApproxCountDistinct | Aggregation::ApproxPercentile { .. }
+        )
     }
 }
 
@@ -1129,4 +1209,38 @@ mod tests {
             .into()
         );
     }
+
+    #[test]
+    fn approx_count_distinct_rebuilds_on_delete() {
+        let mut c = ops::test::MockGraph::new();
+        let s = c.add_base("source", &["x", "y"]);
+        c.set_op(
+            "agg",
+            &["x", "ys"],
+            Aggregation::ApproxCountDistinct
+                .over(s.as_global(), 1, &[0], &DfType::DEFAULT_TEXT)
+                .unwrap(),
+            true,
+        );
+
+        let estimate = |rs: Records| -> (u64, DfValue) {
+            let r = rs.into_iter().find(|r| r.is_positive()).unwrap().into_row();
+            (
+                HyperLogLog::from_df_value(&r[1]).unwrap().estimate(),
+                r[2].clone(),
+            )
+        };
+
+        c.narrow_one_row(vec![1.into(), "a".into()], true);
+        c.narrow_one_row(vec![1.into(), "b".into()], true);
+        let rs = c.narrow_one_row(vec![1.into(), "a".into()], true);
+        assert_eq!(estimate(rs), (2, 3.into()));
+
+        // Sketches can't have values removed, so the group is recomputed from the parent
+        c.seed(s, vec![1.into(), "a".into()]);
+        c.seed(s, vec![1.into(), "a".into()]);
+        let rs = c.narrow_one_row((vec![1.into(), "b".into()], false), true);
+        assert_eq!(rs.len(), 2);
+        assert_eq!(estimate(rs), (1, 2.into()));
+    }
 }
@@ -1,5 +1,9 @@
+use std::collections::{BTreeMap, HashMap};
+
+use readyset_client::KeyComparison;
 use readyset_data::DfType;
 use readyset_errors::{invariant, ReadySetResult};
+use readyset_util::Indices;
 use serde::{Deserialize, Serialize};
 
 use crate::node::AuxiliaryNodeState;
@@ -37,9 +41,29 @@ impl Extremum {
                 op: self,
                 over,
                 group: group_by.into(),
+                buffer_size: 0,
             },
         )
     }
+
+    /// Returns true if `a` is strictly more extreme than `b` according to this operation
+    fn is_more_extreme(&self, a: &DfValue, b: &DfValue) -> bool {
+        match self {
+            Extremum::Min => a < b,
+            Extremum::Max => a > b,
+        }
+    }
+}
+
+impl GroupedOperator<ExtremumOperator> {
+    /// Keep a buffer of up to `buffer_size` of the most extreme values of each group, so that
+    /// deleting the current extreme value of a group can usually be answered from the buffer
+    /// rather than by recomputing the group from the parent. A `buffer_size` of 0 (the default)
+    /// disables buffering.
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.inner.buffer_size = buffer_size;
+        self
+    }
 }
 
 /// `ExtremumOperator` implements a Dataflow node that performs common aggregation operations such
@@ -54,11 +78,21 @@ impl Extremum {
 /// incoming record. The output record is constructed by concatenating the columns identifying the
 /// group, and appending the aggregated value. For example, for a sum with `self.over == 1`, a
 /// previous sum of `3`, and an incoming record with `[a, 1, x]`, the output would be `[a, x, 4]`.
+///
+/// If the extreme value of a group is deleted, the new extreme value can't be computed from the
+/// output alone, so the group has to be recomputed from the parent. To avoid that for most
+/// deletes, the operator can be configured (with [`GroupedOperator::with_buffer_size`]) to keep a
+/// bounded buffer of the most extreme values of each group in its [`ExtremumState`], falling back
+/// to recomputing the group only once every value in the buffer has been deleted.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtremumOperator {
     op: Extremum,
     over: usize,
     group: Vec<usize>,
+    /// Maximum number of candidate values to keep in the buffer for each group, or 0 to disable
+    /// buffering
+    #[serde(default)]
+    buffer_size: usize,
 }
 
 pub enum DiffType {
@@ -67,39 +101,197 @@ pub enum DiffType {
     None,
 }
 
-impl GroupedOperation for ExtremumOperator {
-    type Diff = DiffType;
+pub struct ExtremumDiff {
+    diff: DiffType,
+    /// The values of the group-by columns of the record, if buffering is enabled
+    group: Vec<DfValue>,
+}
 
-    fn setup(&mut self, parent: &Node) -> ReadySetResult<()> {
-        invariant!(
-            self.over < parent.columns().len(),
-            "cannot aggregate over non-existing column"
-        );
-        Ok(())
+/// A bounded, ordered multiset of the most extreme values of a single group.
+///
+/// The buffer maintains the invariant that every value in the group which is *not* in the buffer
+/// is no more extreme than the least extreme value in the buffer, so as long as the buffer is
+/// non-empty its most extreme value is the extreme value of the whole group.
+#[derive(Debug, Default)]
+struct CandidateBuffer {
+    /// Values in the buffer, along with the number of times each one occurs
+    values: BTreeMap<DfValue, usize>,
+    /// Total number of values in the buffer, including duplicates
+    len: usize,
+    /// Set to `false` once any value in the group has been pushed out of the buffer. While this
+    /// is `true`, the buffer contains every (non-null) value in the group.
+    complete: bool,
+}
+
+impl CandidateBuffer {
+    fn new() -> Self {
+        Self {
+            complete: true,
+            ..Default::default()
+        }
     }
 
-    fn group_by(&self) -> &[usize] {
-        &self.group[..]
+    fn most_extreme(&self, op: &Extremum) -> Option<&DfValue> {
+        match op {
+            Extremum::Min => self.values.keys().next(),
+            Extremum::Max => self.values.keys().next_back(),
+        }
     }
 
-    fn to_diff(&self, r: &[DfValue], pos: bool) -> ReadySetResult<Self::Diff> {
-        #[allow(clippy::indexing_slicing)] // Invariant documented.
-        let v = &r[self.over];
-        if let DfValue::None = *v {
-            Ok(DiffType::None)
-        } else if pos {
-            Ok(DiffType::Insert(v.clone()))
-        } else {
-            Ok(DiffType::Remove(v.clone()))
+    fn least_extreme(&self, op: &Extremum) -> Option<&DfValue> {
+        match op {
+            Extremum::Min => self.values.keys().next_back(),
+            Extremum::Max => self.values.keys().next(),
         }
     }
 
-    fn apply(
+    /// Returns the extreme value of the group, or [`None`] if that can't be determined from the
+    /// buffer
+    fn value(&self, op: &Extremum) -> Option<DfValue> {
+        match self.most_extreme(op) {
+            Some(v) => Some(v.clone()),
+            // If we've never dropped a value, an empty buffer means the group has only nulls
+            None if self.complete => Some(DfValue::None),
+            None => None,
+        }
+    }
+
+    fn insert(&mut self, v: DfValue, op: &Extremum, capacity: usize) {
+        if !self.complete {
+            if let Some(least) = self.least_extreme(op) {
+                if op.is_more_extreme(least, &v) {
+                    // There might be values outside the buffer that are more extreme than this
+                    // one, so it has to stay outside too
+                    return;
+                }
+            }
+        }
+
+        *self.values.entry(v).or_default() += 1;
+        self.len += 1;
+
+        if self.len > capacity {
+            let least = self.least_extreme(op).cloned();
+            if let Some(least) = least {
+                self.remove(&least);
+                self.complete = false;
+            }
+        }
+    }
+
+    /// Remove a single occurrence of `v` from the buffer, if it's present. Values that aren't in
+    /// the buffer must be outside of it, so they can be ignored.
+    fn remove(&mut self, v: &DfValue) {
+        if let Some(count) = self.values.get_mut(v) {
+            *count -= 1;
+            self.len -= 1;
+            if *count == 0 {
+                self.values.remove(v);
+            }
+        }
+    }
+
+    /// Returns true if every value that was in the buffer has been removed while there are
+    /// still values in the group we've lost track of, meaning the group has to be recomputed
+    fn is_exhausted(&self) -> bool {
+        self.len == 0 && !self.complete
+    }
+}
+
+impl SizeOf for CandidateBuffer {
+    fn deep_size_of(&self) -> u64 {
+        self.size_of()
+            + self
+                .values
+                .keys()
+                .map(|v| v.deep_size_of() + std::mem::size_of::<usize>() as u64)
+                .sum::<u64>()
+    }
+
+    fn size_of(&self) -> u64 {
+        std::mem::size_of::<Self>() as u64
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Auxiliary State for an Extremum node, which is owned by a Domain
+#[derive(Debug, Default)]
+pub struct ExtremumState {
+    /// Candidate buffers for each group, keyed by the values of the group-by columns
+    buffers: HashMap<Vec<DfValue>, CandidateBuffer>,
+}
+
+impl ExtremumState {
+    /// Drop the buffers for all the groups matching any of the given keys, which are on the given
+    /// columns of the node's output, returning the number of bytes freed
+    pub(crate) fn evict_keys(&mut self, columns: &[usize], keys: &[KeyComparison]) -> u64 {
+        let Some(group_len) = self.buffers.keys().next().map(|group| group.len()) else {
+            return 0;
+        };
+        // The group-by columns come first in the node's output, so we can only tell which groups
+        // match keys that are on some of those columns
+        if columns.iter().any(|col| *col >= group_len) {
+            return 0;
+        }
+
+        let mut bytes_freed = 0;
+        let mut other_keys = vec![];
+        for key in keys {
+            match key.equal() {
+                // Keys on all the group-by columns are the groups themselves
+                Some(group) if columns.iter().copied().eq(0..group_len) => {
+                    if let Some((group, buffer)) = self.buffers.remove_entry(group.as_slice()) {
+                        bytes_freed += group.deep_size_of() + buffer.deep_size_of();
+                    }
+                }
+                _ => other_keys.push(key),
+            }
+        }
+
+        if !other_keys.is_empty() {
+            self.buffers.retain(|group, buffer| {
+                #[allow(clippy::indexing_slicing)] // Checked against the group length above
+                let evicted = other_keys
+                    .iter()
+                    .any(|key| key.contains(columns.iter().map(|col| &group[*col])));
+                if evicted {
+                    bytes_freed += group.deep_size_of() + buffer.deep_size_of();
+                }
+                !evicted
+            });
+        }
+        bytes_freed
+    }
+}
+
+impl SizeOf for ExtremumState {
+    fn deep_size_of(&self) -> u64 {
+        self.size_of()
+            + self
+                .buffers
+                .iter()
+                .map(|(group, buffer)| group.deep_size_of() + buffer.deep_size_of())
+                .sum::<u64>()
+    }
+
+    fn size_of(&self) -> u64 {
+        std::mem::size_of::<Self>() as u64
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+}
+
+impl ExtremumOperator {
+    fn apply_unbuffered(
         &self,
         current: Option<&DfValue>,
-        diffs: &mut dyn Iterator<Item = Self::Diff>,
-        _auxiliary_node_state: Option<&mut AuxiliaryNodeState>,
-    ) -> ReadySetResult<Option<DfValue>> {
+        diffs: &mut dyn Iterator<Item = DiffType>,
+    ) -> Option<DfValue> {
         // Extreme values are those that are at least as extreme as the current min/max (if any).
         // let mut is_extreme_value : Box<dyn Fn(i64) -> bool> = Box::new(|_|true);
         let mut extreme_values: Vec<DfValue> = vec![];
@@ -130,12 +322,116 @@ impl GroupedOperation for ExtremumOperator {
             };
         }
 
-        let extreme = match self.op {
+        match self.op {
             Extremum::Min => extreme_values.into_iter().min(),
             Extremum::Max => extreme_values.into_iter().max(),
+        }
+    }
+
+    fn apply_buffered(
+        &self,
+        current: Option<&DfValue>,
+        diffs: &mut dyn Iterator<Item = ExtremumDiff>,
+        state: &mut ExtremumState,
+    ) -> Option<DfValue> {
+        let mut diffs = diffs.peekable();
+        let group = match diffs.peek() {
+            Some(d) => d.group.clone(),
+            None => return current.cloned(),
         };
 
-        Ok(extreme)
+        let mut buffer = match current {
+            // Either this is a new group, or we're recomputing or replaying the group from
+            // scratch, so start with an empty buffer (dropping any buffer left over from before the
+            // group was evicted, which can't be trusted anymore)
+            None => {
+                state.buffers.remove(&group);
+                CandidateBuffer::new()
+            }
+            Some(current) => match state.buffers.remove(&group) {
+                Some(buffer) if buffer.value(&self.op).as_ref() == Some(current) => buffer,
+                // If we don't have a buffer for this group (eg because it was dropped once it
+                // became empty), or it's out of sync with our output, fall back to only using the
+                // current value
+                _ => {
+                    return self.apply_unbuffered(Some(current), &mut diffs.map(|d| d.diff));
+                }
+            },
+        };
+
+        for ExtremumDiff { diff, .. } in diffs {
+            match diff {
+                DiffType::Insert(v) => buffer.insert(v, &self.op, self.buffer_size),
+                DiffType::Remove(v) => {
+                    buffer.remove(&v);
+                    if buffer.is_exhausted() {
+                        return None;
+                    }
+                }
+                DiffType::None => {}
+            }
+        }
+
+        let value = buffer.value(&self.op);
+        if buffer.len > 0 {
+            state.buffers.insert(group, buffer);
+        }
+        value
+    }
+}
+
+impl GroupedOperation for ExtremumOperator {
+    type Diff = ExtremumDiff;
+
+    fn setup(&mut self, parent: &Node) -> ReadySetResult<()> {
+        invariant!(
+            self.over < parent.columns().len(),
+            "cannot aggregate over non-existing column"
+        );
+        Ok(())
+    }
+
+    fn group_by(&self) -> &[usize] {
+        &self.group[..]
+    }
+
+    fn to_diff(&self, r: &[DfValue], pos: bool) -> ReadySetResult<Self::Diff> {
+        #[allow(clippy::indexing_slicing)] // Invariant documented.
+        let v = &r[self.over];
+        let diff = if let DfValue::None = *v {
+            DiffType::None
+        } else if pos {
+            DiffType::Insert(v.clone())
+        } else {
+            DiffType::Remove(v.clone())
+        };
+        // We only need to know which group the record is in to find its buffer
+        let group = if self.buffer_size > 0 {
+            r.cloned_indices(self.group.iter().cloned())
+                .map_err(|_| ReadySetError::InvalidRecordLength)?
+        } else {
+            vec![]
+        };
+        Ok(ExtremumDiff { diff, group })
+    }
+
+    fn apply(
+        &self,
+        current: Option<&DfValue>,
+        diffs: &mut dyn Iterator<Item = Self::Diff>,
+        auxiliary_node_state: Option<&mut AuxiliaryNodeState>,
+    ) -> ReadySetResult<Option<DfValue>> {
+        if self.buffer_size == 0 {
+            return Ok(self.apply_unbuffered(current, &mut diffs.map(|d| d.diff)));
+        }
+
+        let state = match auxiliary_node_state {
+            Some(AuxiliaryNodeState::Extremum(ref mut extremum_state)) => extremum_state,
+            Some(_) => internal!("Incorrect auxiliary state for Extremum node"),
+            None => internal!("Missing auxiliary state for Extremum node"),
+        };
+
+        Ok(self.apply_buffered(current, diffs, state))
     }
 
     fn description(&self, detailed: bool) -> String {
@@ -357,6 +653,129 @@ mod tests {
         assert!(out.is_empty());
     }
 
+    #[test]
+    fn it_answers_deletes_from_buffer() {
+        let mut c = ops::test::MockGraph::new();
+        let s = c.add_base("source", &["x", "y"]);
+        c.set_op(
+            "agg",
+            &["x", "ys"],
+            Extremum::Max
+                .over(s.as_global(), 1, &[0])
+                .with_buffer_size(2),
+            true,
+        );
+        let key = 1;
+
+        c.narrow_one_row(vec![key.into(), 4.into()], true);
+        c.narrow_one_row(vec![key.into(), 7.into()], true);
+        c.narrow_one_row(vec![key.into(), 2.into()], true);
+
+        // The parent is empty, so the new max can only have come from the buffer
+        let rs = c.narrow_one_row((vec![key.into(), 7.into()], false), true);
+        assert_eq!(
+            rs,
+            vec![
+                (
+                    vec![DfValue::from(key), DfValue::from(7), DfValue::from(3)],
+                    false
+                ),
+                (
+                    vec![DfValue::from(key), DfValue::from(4), DfValue::from(2)],
+                    true
+                )
+            ]
+            .into()
+        );
+
+        // 2 was pushed out of the buffer, so once 4 is deleted the buffer is exhausted and the
+        // group has to be recomputed from the parent
+        c.seed(s, vec![key.into(), 2.into()]);
+        let rs = c.narrow_one_row((vec![key.into(), 4.into()], false), true);
+        assert_eq!(
+            rs,
+            vec![
+                (
+                    vec![DfValue::from(key), DfValue::from(4), DfValue::from(2)],
+                    false
+                ),
+                (
+                    vec![DfValue::from(key), DfValue::from(2), DfValue::from(1)],
+                    true
+                )
+            ]
+            .into()
+        );
+    }
+
+    #[test]
+    fn buffer_keeps_most_extreme_values() {
+        let op = ExtremumOperator {
+            op: Extremum::Min,
+            over: 1,
+            group: vec![0],
+            buffer_size: 2,
+        };
+        let mut state = AuxiliaryNodeState::Extremum(Default::default());
+        let mut apply = |current: Option<i32>, rows: Vec<(i32, bool)>| {
+            let diffs = rows
+                .into_iter()
+                .map(|(v, pos)| op.to_diff(&[1.into(), v.into()], pos).unwrap())
+                .collect::<Vec<_>>();
+            op.apply(
+                current.map(DfValue::from).as_ref(),
+                &mut diffs.into_iter(),
+                Some(&mut state),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(
+            apply(None, vec![(5, true), (3, true), (8, true)]),
+            Some(3.into())
+        );
+        // 9 is less extreme than everything in the buffer, and 8 (which was pushed out of the
+        // buffer) is more extreme than it, so it must not be buffered
+        assert_eq!(apply(Some(3), vec![(9, true), (3, false)]), Some(5.into()));
+        assert_eq!(apply(Some(5), vec![(5, false)]), None);
+
+        // Recomputing the group starts a new buffer
+        assert_eq!(apply(None, vec![(8, true), (9, true)]), Some(8.into()));
+        assert_eq!(apply(Some(8), vec![(8, false)]), Some(9.into()));
+    }
+
+    #[test]
+    fn evicting_keys_drops_buffers() {
+        let op = ExtremumOperator {
+            op: Extremum::Max,
+            over: 1,
+            group: vec![0],
+            buffer_size: 2,
+        };
+        let mut state = AuxiliaryNodeState::Extremum(Default::default());
+        for group in [1, 2, 3] {
+            let diffs = [5, 6]
+                .into_iter()
+                .map(|v| op.to_diff(&[group.into(), v.into()], true).unwrap())
+                .collect::<Vec<_>>();
+            op.apply(None, &mut diffs.into_iter(), Some(&mut state))
+                .unwrap();
+        }
+        let size = state.deep_size_of();
+
+        let freed = state.evict_keys(&[0], &[KeyComparison::Equal(vec1![1.into()])]);
+        assert!(freed > 0);
+        assert_eq!(state.deep_size_of(), size - freed);
+
+        state.evict_keys(
+            &[0],
+            &[KeyComparison::from_range(
+                &(vec1![DfValue::from(2)]..=vec1![DfValue::from(3)]),
+            )],
+        );
+        assert!(state.is_empty());
+    }
+
     #[test]
     fn it_suggests_indices() {
         let me = 1.into();
@@ -1,11 +1,13 @@
 use std::collections::HashSet;
 use std::fmt::{self, Display};
+use std::path::PathBuf;
 
-use dataflow_state::MaterializedNodeState;
+use dataflow_state::{MaterializedNodeState, TieredStateParameters};
 use itertools::Itertools;
 use nom_sql::Relation;
 use readyset_client::{self, KeyComparison, PacketData, PacketTrace};
 use readyset_data::DfType;
+use replication_offset::ReplicationOffset;
 use serde::{Deserialize, Serialize};
 use strum_macros::{EnumCount, EnumDiscriminants, EnumIter, IntoStaticStr};
 use vec1::Vec1;
@@ -23,6 +25,16 @@ pub struct MaterializedState {
     pub state: Box<MaterializedNodeState>,
 }
 
+/// A checkpoint of the persistent state of a base table node, written in response to a
+/// [`DomainRequest::Checkpoint`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BaseTableCheckpoint {
+    /// The name of the directory the checkpoint was written to, within the requested directory
+    pub file_name: String,
+    /// The replication offset of the base table at the time of the checkpoint
+    pub replication_offset: Option<ReplicationOffset>,
+}
+
 /// A single segment (node that is passed through) of a replay path within a particular domain
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ReplayPathSegment {
@@ -132,6 +144,9 @@ pub enum PrepareStateKind {
         strict_indices: Vec<(Index, Vec<Tag>)>,
         /// Set of weak partial incides to create within the new state
         weak_indices: HashSet<Index>,
+        /// If set, spill keys that haven't been used recently to disk rather than evicting them,
+        /// using a [`TieredState`](dataflow_state::TieredState)
+        tiered: Option<TieredStateParameters>,
     },
     /// Setup state for a fully materialized internal node
     Full {
@@ -403,6 +418,12 @@ pub enum DomainRequest {
     /// Request a list of base table nodes that are currently involved in snapshotting.
     RequestSnapshottingTables,
 
+    /// Write a checkpoint of the persistent state of every base table node in the domain into the
+    /// given directory, and return a map from node to [`BaseTableCheckpoint`]
+    Checkpoint {
+        dir: PathBuf,
+    },
+
     /// Request a map of node indexes to approximate key counts and materialized state size in
     /// bytes
     RequestNodeSizes,
@@ -431,6 +452,18 @@ pub enum DomainRequest {
 
     /// Requests an eviction from state within this Domain.
     Evict(EvictRequest),
+
+    /// Remove all the keys filled by replays along the given replay paths from the given
+    /// partially materialized node, and forget about any of those replays it's waiting on, so
+    /// that those keys get filled by new replays.
+    ///
+    /// Sent to nodes downstream of a domain which has been recovered after failing, for the replay
+    /// paths which go through that domain, since any updates that were in flight through it when
+    /// it failed were lost.
+    ClearPartialState {
+        node: LocalNodeIndex,
+        tags: Vec<Tag>,
+    },
 }
 
 /// The primary unit of communication between nodes in the dataflow graph.
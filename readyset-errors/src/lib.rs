@@ -679,6 +679,16 @@ pub enum ReadySetError {
     #[error("Connection to the upstream database was lost: {0}")]
     UpstreamConnectionLost(String),
 
+    /// Error that no connection to the upstream database became free in the adapter's upstream
+    /// connection pool within the configured wait time
+    #[error("Timed out after {0:?} waiting for a free connection to the upstream database")]
+    UpstreamPoolTimeout(std::time::Duration),
+
+    /// Error that a statement carrying a `readyset:cache-only` hint could not be served from a
+    /// cache
+    #[error("Statement hinted as cache-only could not be served from a cache: {0}")]
+    CacheOnlyQueryNotCached(String),
+
     /// An unknown pending migration was referenced
     #[error("Unknown migration: {0}")]
     UnknownMigration(u64),
@@ -846,6 +856,12 @@ impl ReadySetError {
         self.any_cause(|e| matches!(e, Self::ViewDestroyed))
     }
 
+    /// Returns true if the error either *is* [`ReaderReplicaNotRunning`], or was *caused by*
+    /// [`ReaderReplicaNotRunning`]
+    pub fn caused_by_reader_replica_not_running(&self) -> bool {
+        self.any_cause(|e| matches!(e, Self::ReaderReplicaNotRunning { .. }))
+    }
+
     /// Returns true if the error either *is* [`SerializationFailed`], or was *caused by*
     /// [`SerializationFailed`]
     pub fn caused_by_serialization_failed(&self) -> bool {
@@ -115,7 +115,9 @@ fn is_ddl(query: &SqlQuery) -> bool {
         | SqlQuery::CreateCache(_)
         | SqlQuery::DropCache(_)
         | SqlQuery::DropAllProxiedQueries(_)
-        | SqlQuery::DropAllCaches(_) => true,
+        | SqlQuery::DropAllCaches(_)
+        | SqlQuery::AlterReadySet(_)
+        | SqlQuery::AlterCache(_) => true,
     }
 }
 
@@ -619,6 +619,7 @@ impl TestScript {
                     readyset_mysql::Backend {
                         noria: make_backend!(MySqlUpstream, MySqlQueryHandler, Dialect::MySQL,),
                         enable_statement_logging: false,
+                        compression_threshold: None,
                     },
                     s,
                     false,
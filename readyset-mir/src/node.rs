@@ -225,6 +225,7 @@ mod tests {
                     group_by: vec![Column::new(Some("base"), "b")],
                     output_column: Column::named("agg"),
                     kind: Extremum::Max,
+                    buffer_size: 0,
                 },
                 vec![Column::new(Some("base"), "b"), Column::named("agg")],
             );
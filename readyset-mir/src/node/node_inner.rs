@@ -112,6 +112,11 @@ pub enum MirNodeInner {
         output_column: Column,
         /// Which kind of extreme value to compute (minimum or maximum).
         kind: Extremum,
+        /// Number of the most extreme values to buffer for each group, so that deleting the
+        /// current extreme value can be handled without recomputing the group. 0 disables
+        /// buffering.
+        #[serde(default)]
+        buffer_size: usize,
     },
     /// Node that filters its input to only rows where a particular expression evaluates to a
     /// truthy value.
@@ -418,6 +423,14 @@ impl MirNodeInner {
                     Aggregation::GroupConcat { separator: ref s } => {
                         format!("||([{}], \"{}\")", on.name.as_str(), s.as_str())
                     }
+                    Aggregation::ApproxCountDistinct => {
+                        format!("APPROX_COUNT_DISTINCT({})", on.name.as_str())
+                    }
+                    Aggregation::ApproxPercentile { ref percentile } => format!(
+                        "APPROX_PERCENTILE({}, {})",
+                        on.name.as_str(),
+                        percentile.value
+                    ),
                 };
                 let group_cols = group_by
                     .iter()
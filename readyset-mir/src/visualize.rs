@@ -164,6 +164,12 @@ impl GraphViz for MirNodeInner {
                     AggregationKind::GroupConcat { separator: s } => {
                         format!("\\|\\|({}, \\\"{}\\\")", on, s)
                     }
+                    AggregationKind::ApproxCountDistinct => {
+                        format!("APPROX_COUNT_DISTINCT({})", on)
+                    }
+                    AggregationKind::ApproxPercentile { percentile } => {
+                        format!("APPROX_PERCENTILE({}, {})", on, percentile.value)
+                    }
                 };
                 let group_cols = group_by.iter().join(", ");
                 write!(f, "{} | γ: {}", op_string, group_cols)
@@ -278,6 +284,10 @@ impl GraphViz for MirNodeInner {
                                     PostLookupAggregateFunction::GroupConcat { .. } => "GC",
                                     PostLookupAggregateFunction::Max => "Max",
                                     PostLookupAggregateFunction::Min => "Min",
+                                    PostLookupAggregateFunction::ApproxCountDistinct => "HLL",
+                                    PostLookupAggregateFunction::ApproxPercentile { .. } => {
+                                        "TDigest"
+                                    }
                                 },
                                 &aggregate.column
                             ))
@@ -4,14 +4,16 @@ use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt::Formatter;
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 
 use futures_util::StreamExt;
 use itertools::{izip, Itertools};
 use mysql_async::consts::StatusFlags;
 use mysql_common::bigdecimal::ToPrimitive;
 use mysql_srv::{
-    CachedSchema, Column, ColumnFlags, ColumnType, InitWriter, MsqlSrvError, MySqlShim,
-    QueryResultWriter, QueryResultsResponse, RowWriter, StatementMetaWriter,
+    CachedSchema, Column, ColumnFlags, ColumnType, ColumnWriter, InitWriter, MsqlSrvError,
+    MySqlShim, QueryResultWriter, QueryResultsResponse, RowEncoder, RowSource, RowWriter,
+    StatementMetaWriter,
 };
 use readyset_adapter::backend::noria_connector::{
     MetaVariable, PreparedSelectTypes, SelectPrepareResultInner,
@@ -21,6 +23,7 @@ use readyset_adapter::backend::{
 };
 use readyset_adapter::upstream_database::LazyUpstream;
 use readyset_adapter_types::DeallocateId;
+use readyset_client::results::ResultIterator;
 use readyset_data::{DfType, DfValue, DfValueKind};
 use readyset_errors::{internal, ReadySetError};
 use readyset_util::redacted::Sensitive;
@@ -53,8 +56,8 @@ impl<'a> fmt::Display for BinaryDisplay<'a> {
     }
 }
 
-async fn write_column<W: AsyncWrite + Unpin>(
-    rw: &mut RowWriter<'_, W>,
+fn write_column<C: ColumnWriter>(
+    rw: &mut C,
     c: &DfValue,
     cs: &mysql_srv::Column,
     ty: &DfType,
@@ -161,6 +164,33 @@ async fn write_column<W: AsyncWrite + Unpin>(
     Ok(written?)
 }
 
+/// The rows of a ReadySet resultset, which are converted to MySQL values as they're sent to the
+/// client, so that the rows of a statement executed with a cursor are only converted once they're
+/// fetched
+struct ReadySetRows {
+    rows: ResultIterator,
+    column_types: Arc<[DfType]>,
+}
+
+impl RowSource for ReadySetRows {
+    fn next_row(&mut self, encoder: &mut RowEncoder<'_>) -> io::Result<bool> {
+        let Some(row) = self.rows.next() else {
+            return Ok(false);
+        };
+        let columns = encoder.columns();
+        for (c, ty, val) in izip!(columns.iter(), self.column_types.iter(), row.iter()) {
+            if let Err(e) = write_column(encoder, val, c, ty) {
+                error!(err = %e, "encountered error while attempting to write column packet");
+                return Err(match e {
+                    Error::Io(e) => e,
+                    e => io::Error::new(io::ErrorKind::Other, e.to_string()),
+                });
+            }
+        }
+        Ok(true)
+    }
+}
+
 async fn write_query_results<W: AsyncWrite + Unpin>(
     r: Result<(u64, u64), Error>,
     results: QueryResultWriter<'_, W>,
@@ -282,6 +312,9 @@ pub struct Backend {
     /// Enables logging of statements received from the client. The `Backend` only logs Query,
     /// Prepare and Execute statements.
     pub enable_statement_logging: bool,
+    /// The size, in bytes, below which packets sent to clients using protocol compression are left
+    /// uncompressed, or None to not offer compression to clients
+    pub compression_threshold: Option<usize>,
 }
 
 impl Deref for Backend {
@@ -396,7 +429,7 @@ where
                         .map(|cs| cs.column_type.clone())
                         .unwrap_or_default();
 
-                    if let Err(e) = write_column(&mut rw, val, c, &ty).await {
+                    if let Err(e) = write_column(&mut rw, val, c, &ty) {
                         return handle_column_write_err(e, rw).await;
                     }
                 }
@@ -632,7 +665,7 @@ where
         }
 
         match self.execute(id, &value_params, ()).await {
-            Ok(QueryResult::Noria(noria_connector::QueryResult::Select { mut rows, schema })) => {
+            Ok(QueryResult::Noria(noria_connector::QueryResult::Select { rows, schema })) => {
                 let CachedSchema {
                     mysql_schema,
                     column_types,
@@ -659,19 +692,14 @@ where
                     }
                 };
 
-                let mut rw = results
+                results
                     .start_with_cache(mysql_schema, preencoded_schema.clone())
-                    .await?;
-                while let Some(row) = rows.next() {
-                    for (c, ty, val) in izip!(mysql_schema.iter(), column_types.iter(), row.iter())
-                    {
-                        if let Err(e) = write_column(&mut rw, val, c, ty).await {
-                            return handle_column_write_err(e, rw).await;
-                        };
-                    }
-                    rw.end_row().await?;
-                }
-                rw.finish().await
+                    .await?
+                    .finish_with_rows(ReadySetRows {
+                        rows,
+                        column_types: column_types.clone(),
+                    })
+                    .await
             }
             execute_result => handle_execute_result(execute_result, results).await,
         }
@@ -750,6 +778,29 @@ where
         self.does_require_authentication()
     }
 
+    fn uses_upstream_authentication(&self) -> bool {
+        self.noria.uses_upstream_authentication()
+    }
+
+    fn allows_unencrypted_upstream_authentication(&self) -> bool {
+        self.noria.allows_unencrypted_upstream_authentication()
+    }
+
+    async fn authenticate_upstream(&mut self, username: &str, password: &[u8]) -> bool {
+        match std::str::from_utf8(password) {
+            Ok(password) => self.noria.authenticate_upstream(username, password).await,
+            Err(_) => false,
+        }
+    }
+
+    fn compression_threshold(&self) -> Option<usize> {
+        self.compression_threshold
+    }
+
+    fn on_command_finished(&mut self) {
+        self.noria.release_upstream();
+    }
+
     fn version(&self) -> String {
         self.noria.version()
     }
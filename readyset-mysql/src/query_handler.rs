@@ -25,15 +25,17 @@ const REQUIRED_SQL_MODES: [SqlMode; 3] = [
 
 /// The list of mysql `SQL_MODE`s that *may* be set by a client (because they don't affect query
 /// semantics)
-const ALLOWED_SQL_MODES: [SqlMode; 11] = [
+const ALLOWED_SQL_MODES: [SqlMode; 13] = [
     SqlMode::ErrorForDivisionByZero, // deprecated
     SqlMode::IgnoreSpace,            // TODO: I think this is fine, but I'm not 100% sure
+    SqlMode::NoAutoCreateUser,       // only affects GRANT; part of MariaDB's default SQL_MODE
     SqlMode::NoAutoValueOnZero,
     SqlMode::NoDirInCreate,
     SqlMode::NoEngineSubstitution,
     SqlMode::NoZeroDate,
     SqlMode::NoZeroInDate,
     SqlMode::OnlyFullGroupBy,
+    SqlMode::SimultaneousAssignment, // MariaDB only, only affects UPDATE
     SqlMode::StrictAllTables,
     SqlMode::StrictTransTables,
     SqlMode::TimeTruncateFractional,
@@ -44,9 +46,11 @@ const ALLOWED_SQL_MODES: [SqlMode; 11] = [
 ///
 /// Note that this enum only includes the SQL mode flags that are present as of mysql 8.0 - any SQL
 /// modes that have been removed since earlier versions are omitted here, as they're unsupported
-/// regardless.
+/// regardless. The MariaDB-only flags that we can allow are included as well; see [the MariaDB
+/// documentation][mariadb-docs].
 ///
 /// [mysql-docs]: https://dev.mysql.com/doc/refman/8.0/en/sql-mode.html
+/// [mariadb-docs]: https://mariadb.com/kb/en/sql-mode/
 #[derive(PartialEq, Eq, Hash)]
 enum SqlMode {
     AllowInvalidDates,
@@ -66,6 +70,7 @@ enum SqlMode {
     PadCharToFullLength,
     PipesAsConcat,
     RealAsFloat,
+    SimultaneousAssignment,
     StrictAllTables,
     StrictTransTables,
     TimeTruncateFractional,
@@ -93,6 +98,7 @@ impl FromStr for SqlMode {
             "pad_char_to_full_length" => Ok(SqlMode::PadCharToFullLength),
             "pipes_as_concat" => Ok(SqlMode::PipesAsConcat),
             "real_as_float" => Ok(SqlMode::RealAsFloat),
+            "simultaneous_assignment" => Ok(SqlMode::SimultaneousAssignment),
             "strict_all_tables" => Ok(SqlMode::StrictAllTables),
             "strict_trans_tables" => Ok(SqlMode::StrictTransTables),
             "time_truncate_fractional" => Ok(SqlMode::TimeTruncateFractional),
@@ -320,9 +326,12 @@ lazy_static! {
         "group_replication_unreachable_majority_timeout",
         "group_replication_view_change_uuid",
         "gtid_executed_compression_period",
+        "gtid_domain_id",
         "gtid_mode",
         "gtid_next",
         "gtid_purged",
+        "gtid_seq_no",
+        "gtid_strict_mode",
         "histogram_generation_max_mem_size",
         "host_cache_size",
         "identity",
@@ -522,6 +531,7 @@ lazy_static! {
         "max_seeks_for_key",
         "max_sort_length",
         "max_sp_recursion_depth",
+        "max_statement_time",
         "max_user_connections",
         "max_write_lock_count",
         "min_examined_row_limit",
@@ -710,6 +720,7 @@ lazy_static! {
         "show_create_table_verbosity",
         "show_gipk_in_create_table_and_information_schema",
         "show_old_temporals",
+        "skip_replication",
         "slave_allow_batching",
         "slave_checkpoint_group",
         "slave_checkpoint_period",
@@ -789,6 +800,8 @@ lazy_static! {
         "transaction_prealloc_size",
         "transaction_read_only",
         "transaction_write_set_extraction",
+        "tx_isolation",
+        "tx_read_only",
         "unique_checks",
         "updatable_views_with_limit",
         "use_secondary_engine",
@@ -975,6 +988,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn mariadb_default_sql_mode() {
+        let m = "STRICT_TRANS_TABLES,ERROR_FOR_DIVISION_BY_ZERO,NO_AUTO_CREATE_USER,\
+                 NO_ENGINE_SUBSTITUTION,NO_ZERO_DATE,NO_ZERO_IN_DATE,ONLY_FULL_GROUP_BY";
+        let stmt = SetStatement::Variable(SetVariables {
+            variables: vec![
+                (
+                    Variable {
+                        scope: VariableScope::Session,
+                        name: "sql_mode".into(),
+                    },
+                    Expr::Literal(Literal::from(m)),
+                ),
+                (
+                    Variable {
+                        scope: VariableScope::Session,
+                        name: "max_statement_time".into(),
+                    },
+                    Expr::Literal(Literal::Integer(0)),
+                ),
+            ],
+        });
+        assert_eq!(
+            MySqlQueryHandler::handle_set_statement(&stmt),
+            SetBehavior::Proxy
+        );
+    }
+
     #[test]
     fn all_required_sql_modes_are_allowed() {
         for mode in REQUIRED_SQL_MODES {
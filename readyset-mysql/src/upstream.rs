@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use futures_util::Stream;
@@ -219,11 +220,16 @@ impl UpstreamDatabase for MySqlUpstream {
     type QueryResult<'a> = QueryResult<'a>;
     type StatementMeta = StatementMeta;
     type PrepareData<'a> = ();
+    type OwnedPrepareData = ();
     type ExecMeta<'a> = ();
     type Error = Error;
     const DEFAULT_DB_VERSION: &'static str = "8.0.26-readyset\0";
     const SQL_DIALECT: nom_sql::Dialect = nom_sql::Dialect::MySQL;
 
+    fn own_prepare_data(_: &()) {}
+
+    fn borrow_prepare_data(_: &()) {}
+
     async fn connect(upstream_config: UpstreamConfig) -> Result<Self, Error> {
         let (conn, prepared_statements) = Self::connect_inner(upstream_config).await?;
         Ok(Self {
@@ -422,6 +428,22 @@ impl UpstreamDatabase for MySqlUpstream {
     async fn schema_search_path(&mut self) -> Result<Vec<SqlIdentifier>, Self::Error> {
         Ok(self.database().into_iter().map(|s| s.into()).collect())
     }
+
+    async fn replication_lag(&mut self) -> Result<Option<Duration>, Self::Error> {
+        // MySQL before 8.0.22 only understands `SHOW SLAVE STATUS`, and MariaDB calls the lag
+        // column `Seconds_Behind_Master` either way
+        let status: Option<Row> = match self.conn.query_first("SHOW REPLICA STATUS").await {
+            Ok(status) => status,
+            Err(_) => self.conn.query_first("SHOW SLAVE STATUS").await?,
+        };
+        Ok(status.and_then(|row| {
+            // The lag is NULL if replication isn't running
+            let lag = row
+                .get_opt::<Option<u64>, _>("Seconds_Behind_Source")
+                .or_else(|| row.get_opt("Seconds_Behind_Master"))?;
+            lag.ok().flatten().map(Duration::from_secs)
+        }))
+    }
 }
 
 impl Drop for MySqlUpstream {
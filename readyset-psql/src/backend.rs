@@ -120,6 +120,11 @@ impl Backend {
     }
 }
 
+/// The name of the upstream cursor with the given identifier
+fn cursor_name(cursor: u32) -> String {
+    format!("readyset_cursor_{cursor}")
+}
+
 impl ps::PsqlBackend for Backend {
     type Resultset = Resultset;
 
@@ -128,13 +133,28 @@ impl ps::PsqlBackend for Backend {
     }
 
     fn credentials_for_user(&self, user: &str) -> Option<ps::Credentials> {
+        if self.uses_upstream_authentication() {
+            return Some(ps::Credentials::Upstream);
+        }
         self.users
             .get(user)
             .map(|pw| ps::Credentials::CleartextPassword(pw))
     }
 
+    async fn authenticate_upstream(&mut self, user: &str, password: &str) -> bool {
+        self.inner.authenticate_upstream(user, password).await
+    }
+
+    fn allows_unencrypted_upstream_authentication(&self) -> bool {
+        self.inner.allows_unencrypted_upstream_authentication()
+    }
+
     async fn on_init(&mut self, _database: &str) -> Result<ps::CredentialsNeeded, ps::Error> {
-        if self.does_require_authentication() {
+        if self.does_require_authentication() && self.uses_upstream_authentication() {
+            // Checking credentials against the upstream needs the client's password, which SCRAM
+            // never sends us
+            Ok(ps::CredentialsNeeded::Cleartext)
+        } else if self.does_require_authentication() {
             match self.authentication_method {
                 AuthenticationMethod::Cleartext => Ok(ps::CredentialsNeeded::Cleartext),
                 AuthenticationMethod::ScramSha256 => Ok(ps::CredentialsNeeded::ScramSha256),
@@ -173,6 +193,61 @@ impl ps::PsqlBackend for Backend {
             .try_into()
     }
 
+    async fn on_execute_cursor(
+        &mut self,
+        statement_id: u32,
+        params: &[PsqlValue],
+        result_transfer_formats: &[TransferFormat],
+        cursor: u32,
+        hold: bool,
+        limit: u32,
+    ) -> Result<Option<ps::QueryResponse<Resultset>>, ps::Error> {
+        let params = params
+            .iter()
+            .map(|p| ParamRef(p).try_into())
+            .collect::<Result<Vec<DfValue>, ps::Error>>()?;
+        match self
+            .inner
+            .execute_cursor(
+                statement_id,
+                &params,
+                result_transfer_formats,
+                &cursor_name(cursor),
+                hold,
+                limit,
+            )
+            .await?
+        {
+            Some(result) => Ok(Some(QueryResponse(result).try_into()?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn on_fetch(
+        &mut self,
+        cursor: u32,
+        limit: u32,
+        result_transfer_formats: &[TransferFormat],
+    ) -> Result<Resultset, ps::Error> {
+        let response: ps::QueryResponse<Resultset> = QueryResponse(
+            self.inner
+                .fetch_cursor(&cursor_name(cursor), limit, result_transfer_formats)
+                .await?,
+        )
+        .try_into()?;
+        match response {
+            ps::QueryResponse::Select { resultset, .. } => Ok(resultset),
+            _ => Err(ps::Error::InternalError(
+                "Unexpected response to fetching from a cursor".to_string(),
+            )),
+        }
+    }
+
+    async fn on_close_cursor(&mut self, cursor: u32) -> Result<(), ps::Error> {
+        self.inner.close_cursor(&cursor_name(cursor)).await?;
+        Ok(())
+    }
+
     async fn on_close(&mut self, statement_id: DeallocateId) -> Result<(), ps::Error> {
         self.inner.remove_statement(statement_id).await?;
         Ok(())
@@ -182,6 +257,10 @@ impl ps::PsqlBackend for Backend {
         self.inner.in_transaction()
     }
 
+    fn on_ready_for_query(&mut self) {
+        self.inner.release_upstream();
+    }
+
     /// Loads any extended types from the upstream postgres, returning a map of Oid to typelen
     async fn load_extended_types(&mut self) -> Result<HashMap<Oid, i16>, ps::Error> {
         let err = |m| {
@@ -2,6 +2,7 @@ use std::fmt::Debug;
 use std::pin::Pin;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use futures::StreamExt;
@@ -27,14 +28,27 @@ use crate::Error;
 /// during connection phase if the version for the upstream server is too low.
 const MIN_UPSTREAM_VERSION: u16 = 13;
 
+/// Returns how far behind its primary a standby is, in seconds, or NULL if the database isn't a
+/// standby or isn't currently streaming WAL from its primary. A standby that has replayed
+/// everything it received is considered caught up, even if the last transaction it replayed is
+/// old, since that just means the primary is idle - but only while its WAL receiver is running,
+/// since a standby that lost its connection to the primary has also replayed everything it
+/// received.
+const REPLICATION_LAG_QUERY: &str = "SELECT CASE \
+     WHEN NOT pg_is_in_recovery() THEN NULL \
+     WHEN NOT EXISTS (SELECT 1 FROM pg_stat_wal_receiver WHERE pid IS NOT NULL) THEN NULL \
+     WHEN pg_last_wal_receive_lsn() = pg_last_wal_replay_lsn() THEN 0 \
+     ELSE EXTRACT(EPOCH FROM now() - pg_last_xact_replay_timestamp()) \
+     END::float8";
+
 /// A connector to an underlying PostgreSQL database
 pub struct PostgreSqlUpstream {
     /// This is the underlying (regular) PostgreSQL client
     client: pgsql::Client,
     /// A tokio task that handles the connection, required by `tokio_postgres` to operate
     _connection_handle: tokio::task::JoinHandle<Result<(), pgsql::Error>>,
-    /// Map from prepared statement IDs to prepared statements
-    prepared_statements: Vec<Option<pgsql::Statement>>,
+    /// Map from prepared statement IDs to prepared statements, along with their query text
+    prepared_statements: Vec<Option<(pgsql::Statement, String)>>,
     /// ID for the next prepared statement
     statement_id_counter: u32,
     /// The user used to connect to the upstream, if any
@@ -105,6 +119,11 @@ pub struct StatementMeta {
     pub schema: Vec<Column>,
 }
 
+/// Quote `ident` for use as an identifier in a SQL statement
+fn quote_identifier(ident: &str) -> String {
+    format!("\"{}\"", ident.replace('"', "\"\""))
+}
+
 /// Convert the given list of parameters for a statement that's being proxied upstream to the format
 /// that the upstream database expects, according to the given list of parameter types
 ///
@@ -168,11 +187,20 @@ impl UpstreamDatabase for PostgreSqlUpstream {
     type StatementMeta = StatementMeta;
     type QueryResult<'a> = QueryResult;
     type PrepareData<'a> = &'a [Type];
+    type OwnedPrepareData = Vec<Type>;
     type ExecMeta<'a> = &'a [TransferFormat];
     type Error = Error;
     const DEFAULT_DB_VERSION: &'static str = "13.4 (ReadySet)";
     const SQL_DIALECT: nom_sql::Dialect = nom_sql::Dialect::PostgreSQL;
 
+    fn own_prepare_data(data: &&[Type]) -> Vec<Type> {
+        data.to_vec()
+    }
+
+    fn borrow_prepare_data(data: &Vec<Type>) -> &[Type] {
+        data
+    }
+
     async fn connect(upstream_config: UpstreamConfig) -> Result<Self, Error> {
         let url = upstream_config
             .upstream_db_url
@@ -288,7 +316,7 @@ impl UpstreamDatabase for PostgreSqlUpstream {
         let statement_id = self.statement_id_counter;
         match self.prepared_statements.get_mut(statement_id as usize) {
             Some(existing) => {
-                *existing = Some(statement);
+                *existing = Some((statement, query.to_owned()));
             }
             None => {
                 let diff = (statement_id as usize) - self.prepared_statements.len();
@@ -296,7 +324,8 @@ impl UpstreamDatabase for PostgreSqlUpstream {
                 for _ in 0..diff {
                     self.prepared_statements.push(None);
                 }
-                self.prepared_statements.push(Some(statement));
+                self.prepared_statements
+                    .push(Some((statement, query.to_owned())));
             }
         }
 
@@ -340,7 +369,7 @@ impl UpstreamDatabase for PostgreSqlUpstream {
         params: &[DfValue],
         exec_meta: &'_ [TransferFormat],
     ) -> Result<Self::QueryResult<'a>, Error> {
-        let statement = self
+        let (statement, _) = self
             .prepared_statements
             .get(statement_id as usize)
             .and_then(|s| s.as_ref())
@@ -366,6 +395,92 @@ impl UpstreamDatabase for PostgreSqlUpstream {
         }
     }
 
+    async fn declare_cursor(
+        &mut self,
+        statement_id: u32,
+        params: &[DfValue],
+        cursor: &str,
+        hold: bool,
+    ) -> Result<(), Error> {
+        let (statement, query) = self
+            .prepared_statements
+            .get(statement_id as usize)
+            .and_then(|s| s.as_ref())
+            .ok_or(ReadySetError::PreparedStatementMissing { statement_id })?;
+
+        // The statement's parameters are bound to the query inside the DECLARE, so it has to be
+        // prepared with the same parameter types. Note that a cursor declared WITH HOLD outside a
+        // transaction has its results materialized by the upstream as soon as it's declared.
+        let declare = self
+            .client
+            .prepare_typed(
+                &format!(
+                    "DECLARE {} NO SCROLL CURSOR {} FOR {query}",
+                    quote_identifier(cursor),
+                    if hold { "WITH HOLD" } else { "WITHOUT HOLD" }
+                ),
+                statement.params(),
+            )
+            .await?;
+        let mut stream = Box::pin(
+            self.client
+                .generic_query_raw(
+                    &declare,
+                    &convert_params_for_upstream(params, statement.params())?,
+                    std::iter::empty(),
+                )
+                .await?,
+        );
+        while let Some(res) = stream.next().await {
+            res?;
+        }
+        Ok(())
+    }
+
+    async fn fetch_cursor<'a>(
+        &'a mut self,
+        cursor: &str,
+        limit: u32,
+        exec_meta: &'_ [TransferFormat],
+    ) -> Result<Self::QueryResult<'a>, Error> {
+        let count = if limit == 0 {
+            "ALL".to_owned()
+        } else {
+            limit.to_string()
+        };
+        let fetch = self
+            .client
+            .prepare_typed(
+                &format!("FETCH FORWARD {count} FROM {}", quote_identifier(cursor)),
+                &[],
+            )
+            .await?;
+        let mut stream = Box::pin(
+            self.client
+                .generic_query_raw(
+                    &fetch,
+                    &Vec::<DfValue>::new(),
+                    exec_meta.iter().map(|tf| (*tf).into()),
+                )
+                .await?,
+        );
+
+        match stream.next().await {
+            None | Some(Ok(GenericResult::Command(..))) => Ok(QueryResult::EmptyRead),
+            Some(Err(e)) => Err(e.into()),
+            Some(Ok(GenericResult::Row(first_row))) => {
+                Ok(QueryResult::Stream { first_row, stream })
+            }
+        }
+    }
+
+    async fn close_cursor(&mut self, cursor: &str) -> Result<(), Error> {
+        self.client
+            .simple_query(&format!("CLOSE {}", quote_identifier(cursor)))
+            .await?;
+        Ok(())
+    }
+
     async fn remove_statement(&mut self, statement_id: DeallocateId) -> Result<(), Self::Error> {
         match statement_id {
             DeallocateId::Numeric(id) => {
@@ -452,6 +567,15 @@ impl UpstreamDatabase for PostgreSqlUpstream {
             })
             .collect())
     }
+
+    async fn replication_lag(&mut self) -> Result<Option<Duration>, Self::Error> {
+        let lag: Option<f64> = self
+            .client
+            .query_one(REPLICATION_LAG_QUERY, &[])
+            .await?
+            .get(0);
+        Ok(lag.map(|secs| Duration::from_secs_f64(secs.max(0.0))))
+    }
 }
 
 impl Drop for PostgreSqlUpstream {
@@ -17,7 +17,7 @@ use tracing::info;
 
 use crate::controller::replication::ReplicationStrategy;
 use crate::handle::Handle;
-use crate::{Config, FrontierStrategy, ReuseConfigType, VolumeId};
+use crate::{Config, FrontierStrategy, PartialStateSpill, ReuseConfigType, VolumeId};
 
 /// Used to construct a worker.
 #[derive(Clone)]
@@ -66,6 +66,7 @@ impl Builder {
             );
         }
         builder.set_eviction_kind(opts.eviction_kind);
+        builder.set_compact_reader_rows(opts.compact_reader_rows);
 
         builder.set_sharding(match opts.shards {
             0 | 1 => None,
@@ -81,6 +82,11 @@ impl Builder {
         if opts.enable_packet_filters {
             builder.enable_packet_filters();
         }
+        builder.set_partial_state_spill(opts.partial_state_spill);
+        if opts.partial_state_disk_limit > 0 {
+            builder.set_partial_state_disk_limit(Some(opts.partial_state_disk_limit));
+        }
+        builder.set_partial_state_spill_min_bytes(opts.partial_state_spill_min_bytes);
 
         // TODO(fran): Reuse will be disabled until we refactor MIR to make it serializable.
         // See `noria/server/src/controller/sql/serde.rs` for details.
@@ -91,6 +97,7 @@ impl Builder {
         builder.set_allow_mixed_comparisons(opts.enable_experimental_mixed_comparisons);
         builder.set_allow_straddled_joins(opts.enable_experimental_straddled_joins);
         builder.set_allow_post_lookup(opts.enable_experimental_post_lookup);
+        builder.set_extremum_buffer_size(opts.extremum_buffer_size);
         builder.set_worker_timeout(Duration::from_secs(opts.worker_request_timeout_seconds));
         builder.set_background_recovery_interval(Duration::from_secs(
             opts.background_recovery_interval_seconds,
@@ -240,6 +247,25 @@ impl Builder {
         self.config.mir_config.allow_mixed_comparisons = allow_mixed_comparisons;
     }
 
+    /// Which partially materialized internal nodes should spill keys to disk rather than evicting
+    /// them?
+    pub fn set_partial_state_spill(&mut self, spill: PartialStateSpill) {
+        self.config.materialization_config.partial_state_spill = spill;
+    }
+
+    /// Set the maximum number of bytes each node may spill to disk, or `None` for no limit
+    pub fn set_partial_state_disk_limit(&mut self, limit: Option<u64>) {
+        self.config.materialization_config.partial_state_disk_limit = limit;
+    }
+
+    /// Set how many bytes of rows a node's state has to hold in memory before it spills keys to
+    /// disk
+    pub fn set_partial_state_spill_min_bytes(&mut self, min_bytes: u64) {
+        self.config
+            .materialization_config
+            .partial_state_spill_min_bytes = min_bytes;
+    }
+
     pub fn set_allow_straddled_joins(&mut self, allow_straddled_joins: bool) {
         self.config.materialization_config.allow_straddled_joins = allow_straddled_joins;
     }
@@ -248,6 +274,11 @@ impl Builder {
         self.config.mir_config.allow_post_lookup = allow_post_lookup;
     }
 
+    /// Set the value of [`controller::sql::Config::extremum_buffer_size`]
+    pub fn set_extremum_buffer_size(&mut self, extremum_buffer_size: Option<usize>) {
+        self.config.mir_config.extremum_buffer_size = extremum_buffer_size;
+    }
+
     /// Set the value of [`controller::sql::Config::worker_request_timeout`]
     pub fn set_worker_timeout(&mut self, worker_request_timeout: Duration) {
         self.config.worker_request_timeout = worker_request_timeout;
@@ -361,6 +392,12 @@ impl Builder {
         self.config.domain_config.eviction_kind = value;
     }
 
+    /// Sets the value of [`Config::domain_config::compact_reader_rows`]. See documentation of
+    /// that field for more information.
+    pub fn set_compact_reader_rows(&mut self, value: bool) {
+        self.config.domain_config.compact_reader_rows = value;
+    }
+
     /// Assigns a telemetry reporter to this ReadySet server
     pub fn set_telemetry_sender(&mut self, value: TelemetrySender) {
         self.telemetry = value;
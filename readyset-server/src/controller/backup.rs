@@ -0,0 +1,213 @@
+//! Creating and restoring backups of the state of a ReadySet deployment.
+//!
+//! See [`readyset_client::backup`] for a description of the layout of a backup directory.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use dataflow::DurabilityMode;
+use itertools::Itertools;
+use readyset_client::backup::{
+    BackupManifest, BACKUP_FORMAT_VERSION, CACHE_DDL_REQUESTS_FILE, CONTROLLER_STATE_FILE,
+    MANIFEST_FILE, TABLES_DIR,
+};
+use readyset_client::consensus::{Authority, AuthorityControl, CacheDDLRequest};
+use readyset_errors::{bad_request_err, internal_err, unsupported, ReadySetResult};
+use tracing::info;
+use url::Url;
+
+use super::state::DfState;
+use super::ControllerState;
+use crate::Config;
+
+/// Create a backup of the state of the deployment described by `ds` in `dir`, which must either
+/// not exist or be empty.
+///
+/// The base tables are checkpointed by the workers they run on, each into `dir` on its own host,
+/// so backups can only be taken while every base table runs on the same host as the controller at
+/// `controller_uri`.
+pub(super) async fn create(
+    ds: &DfState,
+    dir: &Path,
+    controller_uri: &Url,
+    authority: &Authority,
+) -> ReadySetResult<BackupManifest> {
+    if ds.persistence().mode != DurabilityMode::Permanent {
+        unsupported!(
+            "Backups can only be taken of deployments whose base tables are persisted permanently"
+        );
+    }
+    let remote_workers = ds
+        .base_table_workers()
+        .await?
+        .into_iter()
+        .filter(|worker| worker.host() != controller_uri.host())
+        .collect::<Vec<_>>();
+    if !remote_workers.is_empty() {
+        unsupported!(
+            "Backups can only be taken when every base table runs on the same host as the \
+             controller, but some run on {}",
+            remote_workers.iter().join(", ")
+        );
+    }
+
+    fs::create_dir_all(dir)?;
+    if fs::read_dir(dir)?.next().is_some() {
+        return Err(bad_request_err(format!(
+            "Backup directory {} is not empty",
+            dir.display()
+        )));
+    }
+
+    info!(dir = %dir.display(), "Creating backup");
+    let tables = ds.checkpoint_base_tables(&dir.join(TABLES_DIR)).await?;
+    fs::write(dir.join(CONTROLLER_STATE_FILE), rmp_serde::to_vec(ds)?)?;
+    fs::write(
+        dir.join(CACHE_DDL_REQUESTS_FILE),
+        serde_json::to_vec(&authority.cache_ddl_requests().await?)?,
+    )?;
+
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        db_filename_prefix: ds.persistence().db_filename_prefix.replace('-', "_"),
+        schema_replication_offset: ds.schema_replication_offset().clone(),
+        tables,
+    };
+    // The manifest is written last, so that its presence marks the backup as complete
+    fs::write(
+        dir.join(MANIFEST_FILE),
+        serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    info!(
+        dir = %dir.display(),
+        tables = manifest.tables.len(),
+        "Finished creating backup"
+    );
+
+    Ok(manifest)
+}
+
+/// Restore the backup in `backup_dir` into a new deployment.
+///
+/// The base table checkpoints in the backup are copied into `storage_dir`, renamed to match
+/// `db_filename_prefix` (the name of the new deployment), and the controller state and cache DDL
+/// requests in the backup are written to `authority`, replacing any already there. This must be run
+/// while no ReadySet server is running against `authority`; once the server is started, it will
+/// load the restored state, and resume replication from the replication offsets recorded in the
+/// backup.
+pub async fn restore(
+    backup_dir: &Path,
+    authority: &Authority,
+    db_filename_prefix: &str,
+    storage_dir: Option<PathBuf>,
+) -> ReadySetResult<BackupManifest> {
+    let manifest_path = backup_dir.join(MANIFEST_FILE);
+    if !manifest_path.exists() {
+        return Err(bad_request_err(format!(
+            "{} does not contain a complete backup",
+            backup_dir.display()
+        )));
+    }
+    let manifest: BackupManifest = serde_json::from_slice(&fs::read(manifest_path)?)?;
+    if manifest.format_version != BACKUP_FORMAT_VERSION {
+        unsupported!(
+            "Backup format version {} is not supported (expected version {})",
+            manifest.format_version,
+            BACKUP_FORMAT_VERSION
+        );
+    }
+
+    let db_filename_prefix = db_filename_prefix.replace('-', "_");
+    let target_dir = storage_dir.clone().unwrap_or_else(|| ".".into());
+    fs::create_dir_all(&target_dir)?;
+    for table in &manifest.tables {
+        let file_name = restored_file_name(
+            &table.file_name,
+            &manifest.db_filename_prefix,
+            &db_filename_prefix,
+        )?;
+        let target = target_dir.join(&file_name);
+        if target.exists() {
+            return Err(bad_request_err(format!(
+                "Refusing to overwrite existing base table state at {}",
+                target.display()
+            )));
+        }
+        info!(
+            table = %table.table.display_unquoted(),
+            path = %target.display(),
+            "Restoring base table"
+        );
+        copy_dir(&backup_dir.join(TABLES_DIR).join(&table.file_name), &target)?;
+    }
+
+    let mut dataflow_state: DfState =
+        rmp_serde::from_slice(&fs::read(backup_dir.join(CONTROLLER_STATE_FILE))?)?;
+    dataflow_state.relocate_persistence(db_filename_prefix, storage_dir);
+    // The config will be replaced with the config of the server once it becomes the leader
+    let state = ControllerState {
+        config: Config::default(),
+        dataflow_state,
+    };
+    authority.overwrite_controller_state(state).await?;
+
+    let cache_ddl_requests: Vec<CacheDDLRequest> =
+        serde_json::from_slice(&fs::read(backup_dir.join(CACHE_DDL_REQUESTS_FILE))?)?;
+    // The cache DDL requests are replayed against the restored controller state, so any left over
+    // from whatever was previously stored in the authority must not be replayed along with them
+    authority.remove_all_cache_ddl_requests().await?;
+    for req in cache_ddl_requests {
+        authority.add_cache_ddl_request(req).await?;
+    }
+
+    info!(dir = %backup_dir.display(), "Finished restoring backup");
+    Ok(manifest)
+}
+
+/// Returns the name that the base table checkpoint named `file_name`, taken from a deployment
+/// whose database file names were prefixed with `old_prefix`, should be given when restored into a
+/// deployment whose database file names are prefixed with `new_prefix`.
+fn restored_file_name(
+    file_name: &str,
+    old_prefix: &str,
+    new_prefix: &str,
+) -> ReadySetResult<String> {
+    let rest = file_name
+        .strip_prefix(old_prefix)
+        .and_then(|rest| rest.strip_prefix('-'))
+        .ok_or_else(|| {
+            internal_err!("Base table checkpoint {file_name} does not start with {old_prefix}")
+        })?;
+    Ok(format!("{new_prefix}-{rest}"))
+}
+
+fn copy_dir(from: &Path, to: &Path) -> ReadySetResult<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restored_file_name_replaces_prefix() {
+        assert_eq!(
+            restored_file_name("old_deployment-public-t1-0.db", "old_deployment", "new").unwrap(),
+            "new-public-t1-0.db"
+        );
+        assert_eq!(
+            restored_file_name("old-t1-3.db", "old", "new_deployment").unwrap(),
+            "new_deployment-t1-3.db"
+        );
+        restored_file_name("other-t1-0.db", "old", "new").unwrap_err();
+    }
+}
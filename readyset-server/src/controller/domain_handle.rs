@@ -43,6 +43,22 @@ impl DomainHandle {
         self.shards.row_size()
     }
 
+    /// Change the number of times this domain is replicated, either adding new replicas that
+    /// haven't been placed onto a worker yet or dropping the assignments of the highest-indexed
+    /// replicas of every shard
+    pub(super) fn set_num_replicas(&mut self, num_replicas: usize) {
+        self.shards = Array2::from_rows(
+            self.shards
+                .rows()
+                .map(|replicas| {
+                    let mut replicas = replicas.to_vec();
+                    replicas.resize(num_replicas, None);
+                    replicas
+                })
+                .collect(),
+        );
+    }
+
     /// Have all replicas of all shards of this domain been placed onto a worker?
     pub(super) fn all_replicas_placed(&self) -> bool {
         self.shards.cells().iter().all(|addr| addr.is_some())
@@ -8,6 +8,7 @@
 )]
 
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -19,15 +20,17 @@ use futures::future::Fuse;
 use futures::{Future, FutureExt};
 use hyper::Method;
 use metrics::gauge;
-use nom_sql::Relation;
-use readyset_client::consensus::{Authority, AuthorityControl};
+use nom_sql::{parse_query, DialectDisplay, Relation, SqlQuery};
+use readyset_client::consensus::{Authority, AuthorityControl, CacheDDLRequest};
 use readyset_client::debug::stats::PersistentStats;
 use readyset_client::internal::ReplicaAddress;
 use readyset_client::metrics::recorded;
+use readyset_client::query::QueryId;
+use readyset_client::recipe::changelist::{Change, CreateCache};
 use readyset_client::recipe::{ExtendRecipeResult, ExtendRecipeSpec, MigrationStatus};
 use readyset_client::status::{ReadySetControllerStatus, SnapshotStatus};
 use readyset_client::{GraphvizOptions, SingleKeyEviction, ViewCreateRequest, WorkerDescriptor};
-use readyset_errors::{internal_err, ReadySetError, ReadySetResult};
+use readyset_errors::{internal, internal_err, ReadySetError, ReadySetResult};
 use readyset_telemetry_reporter::TelemetrySender;
 use readyset_util::futures::abort_on_panic;
 use readyset_util::shutdown::ShutdownReceiver;
@@ -38,13 +41,13 @@ use reqwest::Url;
 use slotmap::{DefaultKey, Key, KeyData, SlotMap};
 use tokio::select;
 use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
-use tokio::sync::{watch, Mutex};
+use tokio::sync::{oneshot, watch, Mutex};
 use tokio::task::JoinHandle;
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
 
 use crate::controller::state::{DfState, DfStateHandle};
-use crate::controller::{ControllerState, Worker, WorkerIdentifier};
+use crate::controller::{backup, ControllerState, Worker, WorkerIdentifier};
 use crate::worker::WorkerRequestKind;
 
 /// Maximum amount of time to wait for an `extend_recipe` request to run synchronously, before we
@@ -96,6 +99,9 @@ pub struct Leader {
     pub(super) background_task_failed: mpsc::Sender<ReadySetError>,
 
     pub(super) running_recovery: Option<watch::Receiver<ReadySetResult<()>>>,
+
+    /// A channel used to send requests to the replication task, if one is running
+    controller_channel: Option<UnboundedSender<ControllerMessage>>,
 }
 
 impl Leader {
@@ -105,6 +111,7 @@ impl Leader {
     pub(super) async fn start(
         &mut self,
         notification_channel: UnboundedSender<ReplicatorMessage>,
+        controller_sender: UnboundedSender<ControllerMessage>,
         controller_channel: UnboundedReceiver<ControllerMessage>,
         telemetry_sender: TelemetrySender,
         shutdown_rx: ShutdownReceiver,
@@ -128,6 +135,8 @@ impl Leader {
             warn!(%error, "Failed to persist stats in the Authority");
         }
 
+        self.controller_channel = Some(controller_sender);
+
         // When the controller becomes the leader, we need to read updates
         // from the binlog.
         self.start_replication_task(
@@ -148,11 +157,11 @@ impl Leader {
     async fn start_replication_task(
         &mut self,
         notification_channel: UnboundedSender<ReplicatorMessage>,
-        controller_channel: UnboundedReceiver<ControllerMessage>,
+        mut controller_channel: UnboundedReceiver<ControllerMessage>,
         telemetry_sender: TelemetrySender,
         mut shutdown_rx: ShutdownReceiver,
     ) {
-        if self.replicator_config.upstream_db_url.is_none() {
+        if !self.replicator_config.has_replication_source() {
             // Controller must be notified that snapshot is completed even though we don't have an
             // upstream db. This is only relevant for tests as users will not run without an
             // upstream.
@@ -185,7 +194,7 @@ impl Leader {
                         noria,
                         config.clone(),
                         &notification_channel,
-                        &controller_channel,
+                        &mut controller_channel,
                         telemetry_sender.clone(),
                         server_startup,
                         replicator_statement_logging,
@@ -463,6 +472,17 @@ impl Leader {
                 }?;
                 return_serialized!(res);
             }
+            (&Method::POST, "/backup") => {
+                require_leader_ready()?;
+                let dir: PathBuf = bincode::deserialize(&body)?;
+                // Hold the read lock for the whole backup, so that no migrations can change the
+                // controller state while the base tables are being checkpointed
+                let res = {
+                    let ds = self.dataflow_state_handle.read().await;
+                    backup::create(&ds, &dir, &self.controller_uri, authority).await
+                }?;
+                return_serialized!(res);
+            }
             (&Method::POST, "/snapshotting_tables") => {
                 let res = {
                     let ds = self.dataflow_state_handle.read().await;
@@ -622,6 +642,47 @@ impl Leader {
                 self.dataflow_state_handle.commit(writer, authority).await?;
                 return_serialized!(result);
             }
+            (&Method::POST, "/reshard_query") => {
+                require_leader_ready()?;
+                let (query_name, shards, dialect) = bincode::deserialize(&body)?;
+                let mut writer = self.dataflow_state_handle.write().await;
+                let caches = writer
+                    .as_mut()
+                    .reshard_query(&query_name, shards, dialect)
+                    .await?;
+                // Work out how to record the new number of shards before committing anything, so
+                // that stored statements we can't update fail the reshard rather than leaving the
+                // caches to be recreated with their old number of shards
+                let ddl_replacements = Self::cache_shard_replacements(
+                    writer.as_ref(),
+                    authority.cache_ddl_requests().await?,
+                    &caches,
+                    shards,
+                )?;
+                // Committing switches view lookups over to the new nodes...
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                // ...after which the old ones can stop serving reads
+                let mut writer = self.dataflow_state_handle.write().await;
+                writer.as_mut().drop_retired_nodes(dialect).await?;
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                for (ddl_req, new_ddl_req) in ddl_replacements {
+                    authority
+                        .replace_cache_ddl_request(ddl_req, new_ddl_req)
+                        .await?;
+                }
+                return_serialized!(ReadySetResult::Ok(()));
+            }
+            (&Method::POST, "/set_reader_replicas") => {
+                require_leader_ready()?;
+                let (query_name, replicas) = bincode::deserialize(&body)?;
+                let mut writer = self.dataflow_state_handle.write().await;
+                writer
+                    .as_mut()
+                    .set_reader_replicas(&query_name, replicas)
+                    .await?;
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                return_serialized!(ReadySetResult::Ok(()));
+            }
             (&Method::POST, "/remove_all_queries") => {
                 require_leader_ready()?;
                 let mut writer = self.dataflow_state_handle.write().await;
@@ -644,6 +705,48 @@ impl Leader {
                 self.dataflow_state_handle.commit(writer, authority).await?;
                 return_serialized!(());
             }
+            (&Method::POST, "/add_replicated_tables") => {
+                require_leader_ready()?;
+                let tables: Vec<Relation> = bincode::deserialize(&body)?;
+                let (done_tx, done_rx) = oneshot::channel();
+                self.send_controller_message(ControllerMessage::AddTables {
+                    tables,
+                    done: done_tx,
+                })?;
+                Self::replication_task_reply(done_rx).await?;
+                return_serialized!(());
+            }
+            (&Method::POST, "/drop_replicated_tables") => {
+                require_leader_ready()?;
+                let tables: Vec<Relation> = bincode::deserialize(&body)?;
+                let (done_tx, done_rx) = oneshot::channel();
+                self.send_controller_message(ControllerMessage::DropTables {
+                    tables,
+                    done: done_tx,
+                })?;
+                Self::replication_task_reply(done_rx).await?;
+                return_serialized!(());
+            }
+            (&Method::POST, "/record_replicated_tables") => {
+                let (tables, replicate): (Vec<Relation>, bool) = bincode::deserialize(&body)?;
+                let mut writer = self.dataflow_state_handle.write().await;
+                writer
+                    .as_mut()
+                    .replicated_table_changes
+                    .extend(tables.into_iter().map(|table| (table, replicate)));
+                self.dataflow_state_handle.commit(writer, authority).await?;
+                return_serialized!(());
+            }
+            (&Method::POST, "/replicated_table_changes") => {
+                let ds = self.dataflow_state_handle.read().await;
+                return_serialized!(ds.replicated_table_changes.clone());
+            }
+            (&Method::POST, "/resnapshot_table") => {
+                require_leader_ready()?;
+                let table: Relation = bincode::deserialize(&body)?;
+                self.send_controller_message(ControllerMessage::ResnapshotTable { table })?;
+                return_serialized!(());
+            }
             (&Method::POST, "/domain_died") => {
                 let body = bincode::deserialize(&body)?;
                 self.handle_failed_domain(body).await?;
@@ -769,20 +872,22 @@ impl Leader {
         let mut writer = self.dataflow_state_handle.write().await;
         let ds = writer.as_mut();
 
-        // Remove references to the worker from any internal state, collecting downstream domains to
-        // kill
+        // Remove references to the worker from any internal state, collecting downstream domains
+        // which will have to be recreated along with the domains that were running on the worker.
+        // Any other downstream domains keep running, and get reconnected to the failed domains once
+        // they've been recovered.
         let mut downstream_domains = HashSet::new();
         for wi in failed {
             warn!(worker = %wi, "handling failure of worker");
             for di in ds.remove_worker(&wi) {
-                downstream_domains.extend(ds.downstream_domains(di.domain_index)?);
+                downstream_domains.extend(ds.downstream_domains_to_recreate(di.domain_index)?);
             }
         }
 
         if !downstream_domains.is_empty() {
             info!(
                 num_downstream_domains = downstream_domains.len(),
-                "Killing domains downstream of failed worker"
+                "Killing domains with full materializations downstream of failed worker"
             );
             ds.kill_domains(downstream_domains).await?;
         }
@@ -873,12 +978,14 @@ impl Leader {
 
             let mut domains_to_recover = vec![addr.domain_index];
 
-            // 2. Kill and clean up any downstream domains
-            let downstream_domains = ds.downstream_domains(addr.domain_index)?;
+            // 2. Kill and clean up any downstream domains which have to be recreated along with the
+            //    failed domain. Any other downstream domains keep running (and serving reads) while
+            //    it's recovered, and are reconnected to it afterwards.
+            let downstream_domains = ds.downstream_domains_to_recreate(addr.domain_index)?;
             if !downstream_domains.is_empty() {
                 info!(
                     num_downstream_domains = downstream_domains.len(),
-                    "Killing domains downstream of failed domain"
+                    "Killing domains with full materializations downstream of failed domain"
                 );
                 domains_to_recover.extend(downstream_domains.iter().copied());
                 ds.kill_domains(downstream_domains).await?;
@@ -919,6 +1026,93 @@ impl Leader {
         Ok(())
     }
 
+    /// Returns replacements for the stored `CREATE CACHE` statements that created the given
+    /// caches, recording their new number of shards, so that if the caches ever have to be
+    /// recreated from those statements they're sharded the same way.
+    ///
+    /// Returns an error if any of `ddl_reqs` can't be parsed, since it might have created one of
+    /// the caches.
+    fn cache_shard_replacements(
+        ds: &DfState,
+        ddl_reqs: Vec<CacheDDLRequest>,
+        caches: &[Relation],
+        shards: usize,
+    ) -> ReadySetResult<Vec<(CacheDDLRequest, CacheDDLRequest)>> {
+        let adapter_rewrite_params = ds.recipe.adapter_rewrite_params();
+        let mut replacements = vec![];
+        for ddl_req in ddl_reqs {
+            let change =
+                Change::from_cache_ddl_request(&ddl_req, adapter_rewrite_params).map_err(|e| {
+                    internal_err!(
+                        "Could not parse stored cache DDL request {}: {e}",
+                        ddl_req.unparsed_stmt
+                    )
+                })?;
+            let Change::CreateCache(CreateCache {
+                name, statement, ..
+            }) = change
+            else {
+                continue;
+            };
+            // Caches created without a name are named after their query ID by the adapter
+            let name = name.unwrap_or_else(|| {
+                QueryId::from_select(&statement, &ddl_req.schema_search_path).into()
+            });
+            if !ds
+                .recipe
+                .resolve_alias(&name)
+                .is_some_and(|name| caches.contains(name))
+            {
+                continue;
+            }
+
+            let mut stmt = match parse_query(ddl_req.dialect.into(), &ddl_req.unparsed_stmt) {
+                Ok(SqlQuery::CreateCache(stmt)) => stmt,
+                _ => internal!(
+                    "Stored cache DDL request {} is not a CREATE CACHE statement",
+                    ddl_req.unparsed_stmt
+                ),
+            };
+            stmt.shards = Some(shards);
+            let new_ddl_req = CacheDDLRequest {
+                unparsed_stmt: stmt.display(ddl_req.dialect.into()).to_string(),
+                ..ddl_req.clone()
+            };
+            replacements.push((ddl_req, new_ddl_req));
+        }
+        Ok(replacements)
+    }
+
+    /// Send a message to the replication task, returning an error if the replicator isn't running
+    /// (for example, because we don't have an upstream database)
+    fn send_controller_message(&self, message: ControllerMessage) -> ReadySetResult<()> {
+        if !self.replicator_config.has_replication_source() {
+            return Err(ReadySetError::ReplicationFailed(
+                "No upstream database is configured".into(),
+            ));
+        }
+
+        self.controller_channel
+            .as_ref()
+            .ok_or_else(|| internal_err!("Replication task has not been started"))?
+            .send(message)
+            .map_err(|_| {
+                ReadySetError::ReplicationFailed("The replication task is not running".into())
+            })
+    }
+
+    /// Wait for the replication task to reply to a message sent with
+    /// [`Self::send_controller_message`], once it has finished handling it
+    async fn replication_task_reply(
+        reply: oneshot::Receiver<ReadySetResult<()>>,
+    ) -> ReadySetResult<()> {
+        reply.await.map_err(|_| {
+            ReadySetError::ReplicationFailed(
+                "The replication task stopped before handling the request".into(),
+            )
+        })?
+    }
+
     /// Spawn a background task which notifies the controller if it fails or panics
     async fn spawn_background_task<F>(&self, fut: F)
     where
@@ -973,6 +1167,7 @@ impl Leader {
             running_migrations: Default::default(),
             background_task_failed,
             running_recovery: None,
+            controller_channel: None,
         }
     }
 }
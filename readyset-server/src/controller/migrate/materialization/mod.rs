@@ -46,6 +46,22 @@ pub enum FrontierStrategy {
     Match(String),
 }
 
+/// Policy for which partially materialized internal nodes should spill keys to disk rather than
+/// evicting them when memory is freed.
+///
+/// Keys spilled to disk are served (and promoted back into memory) by the node itself on a later
+/// lookup, rather than requiring an upquery to rebuild them. Readers never spill.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, Default, clap::ValueEnum)]
+pub enum PartialStateSpill {
+    /// Never spill partial state to disk (this is the default).
+    #[default]
+    None,
+    /// Spill the partial state of join nodes, whose upqueries are the most expensive to repeat.
+    Joins,
+    /// Spill the partial state of all partially materialized internal nodes.
+    All,
+}
+
 #[derive(Debug)]
 enum IndexObligation {
     /// An obligation to index a particular set of columns with a particular index type in a node.
@@ -95,6 +111,27 @@ pub struct Config {
     /// Defaults to [`FrontierStrategy::None`]
     pub frontier_strategy: FrontierStrategy,
 
+    /// Which partially materialized internal nodes should spill keys to disk rather than
+    /// evicting them.
+    ///
+    /// Defaults to [`PartialStateSpill::None`]
+    #[serde(default)]
+    pub partial_state_spill: PartialStateSpill,
+
+    /// The maximum number of bytes each node may spill to disk under
+    /// [`Config::partial_state_spill`], past which spilled keys are evicted for real.
+    ///
+    /// Defaults to `None` (no limit)
+    #[serde(default)]
+    pub partial_state_disk_limit: Option<u64>,
+
+    /// The number of bytes of rows a node's state has to hold in memory before it spills keys to
+    /// disk under [`Config::partial_state_spill`]. Nodes with less state evict keys as usual.
+    ///
+    /// Defaults to 0
+    #[serde(default)]
+    pub partial_state_spill_min_bytes: u64,
+
     /// Whether partial node creation is enabled at all.
     ///
     /// Defaults to true.
@@ -109,6 +146,9 @@ impl Default for Config {
             allow_straddled_joins: false,
             partial_enabled: true,
             frontier_strategy: FrontierStrategy::None,
+            partial_state_spill: PartialStateSpill::None,
+            partial_state_disk_limit: None,
+            partial_state_spill_min_bytes: 0,
         }
     }
 }
@@ -686,6 +726,20 @@ impl Materializations {
         }
         assert!(replay_obligations.is_empty());
 
+        // When recovering only some domains, their nodes might have had indexes added for the
+        // replay paths of nodes in other domains which are still running, which we won't have seen
+        // obligations for above - make sure those get recreated too.
+        if dmp.is_recovery() {
+            for ni in new {
+                if let Some(indexes) = self.have.get(ni).filter(|idxs| !idxs.is_empty()) {
+                    self.added
+                        .entry(*ni)
+                        .or_default()
+                        .extend(indexes.iter().cloned());
+                }
+            }
+        }
+
         // Mark nodes as beyond the frontier as dictated by the strategy
         for &ni in new {
             #[allow(clippy::unwrap_used)] // graph must contain nodes in new
@@ -1376,6 +1430,107 @@ impl Materializations {
         Ok(())
     }
 
+    /// Is the given node fully materialized?
+    pub(in crate::controller) fn is_fully_materialized(
+        &self,
+        graph: &Graph,
+        ni: NodeIndex,
+    ) -> bool {
+        let is_materialized = self.have.contains_key(&ni)
+            || graph[ni].as_reader().map_or(false, |r| r.is_materialized());
+        is_materialized && !self.partial.contains(&ni)
+    }
+
+    /// Reconnect the given already-running nodes, which must be downstream of the nodes in
+    /// `recovered_domains`, to the (newly recovered) replicas of those domains.
+    ///
+    /// Only partially materialized nodes with replay paths through the recovered domains are
+    /// affected. The recovered domains are told about their segments of those replay paths, and
+    /// the other domains on them are told about the paths again so that they send upqueries to the
+    /// new replicas. Then, since any updates that were in flight through the recovered domains when
+    /// they failed have been lost, and the recovered domains no longer have the keys those paths
+    /// filled, those keys are cleared, to be refilled by replays on demand. All other state
+    /// downstream of the recovered domains is kept.
+    ///
+    /// Fully materialized nodes are skipped, since their state can only be rebuilt by recreating
+    /// the domains they're in, which are recovered along with the failed domains (see
+    /// [`DfState::downstream_domains_to_recreate`](crate::controller::state::DfState::downstream_domains_to_recreate)).
+    ///
+    /// `nodes` must be in topological order.
+    pub(in crate::controller) fn reconnect_descendants(
+        &mut self,
+        graph: &Graph,
+        nodes: &[NodeIndex],
+        recovered_domains: &HashSet<DomainIndex>,
+        dmp: &mut DomainMigrationPlan,
+    ) -> ReadySetResult<()> {
+        let mut reconnect = dmp.new_like();
+        let mut clear = vec![];
+        for &ni in nodes {
+            if self.is_fully_materialized(graph, ni) {
+                debug!(
+                    node = ni.index(),
+                    "Not reconnecting fully materialized node, which is recreated instead"
+                );
+                continue;
+            }
+            if !self.partial.contains(&ni) {
+                continue;
+            }
+
+            let tags = self
+                .paths
+                .get(&ni)
+                .into_iter()
+                .flat_map(|paths| paths.iter())
+                .filter(|(_, (_, path))| {
+                    path.iter()
+                        .any(|n| recovered_domains.contains(&graph[*n].domain()))
+                })
+                .map(|(tag, _)| *tag)
+                .collect::<Vec<_>>();
+            if tags.is_empty() {
+                continue;
+            }
+
+            let mut index_on = self.have.get(&ni).cloned().unwrap_or_default();
+            if index_on.is_empty() {
+                if let Some(index) = graph[ni].as_reader().and_then(|r| r.index()) {
+                    index_on.insert(index.clone());
+                }
+            }
+
+            debug!(node = %ni.index(), "reconnecting partial node to recovered domains");
+            let mut plan = plan::Plan::new(self, graph, ni, &mut reconnect);
+            for index in index_on {
+                plan.add(index)?;
+            }
+            // The paths and indexes for the node are unchanged, so we can discard the result
+            plan.finalize()?;
+            clear.push((ni, tags));
+        }
+
+        // Domains other than the recovered ones already have everything else they need, and
+        // might not handle being told about the node again (eg by duplicating its indexes)
+        dmp.extend_messages_where(reconnect, |req| {
+            recovered_domains.contains(&req.domain)
+                || matches!(req.req, DomainRequest::SetupReplayPath { .. })
+        });
+
+        for (ni, tags) in clear {
+            let n = &graph[ni];
+            dmp.add_message(
+                n.domain(),
+                DomainRequest::ClearPartialState {
+                    node: n.local_addr(),
+                    tags,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
     /// Returns a (`NodeIndex`, `Tag`) pair for each index in a partially materialized node.
     pub(in crate::controller) fn partial_tags(&self) -> Vec<(NodeIndex, Tag)> {
         // For each partially materialized node, get each tag in self::paths
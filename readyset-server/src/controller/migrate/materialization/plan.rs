@@ -10,13 +10,16 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryFrom;
 
+use common::IndexType;
 use dataflow::payload::{ReplayPathSegment, SourceSelection, TriggerEndpoint};
 use dataflow::prelude::*;
 use dataflow::DomainRequest;
+use dataflow_state::TieredStateParameters;
 use readyset_errors::ReadySetError;
 use tracing::{debug, instrument, trace};
 use vec1::Vec1;
 
+use super::PartialStateSpill;
 use crate::controller::keys::{self, IndexRef, RawReplayPath};
 use crate::controller::migrate::DomainMigrationPlan;
 use crate::controller::state::Graphviz;
@@ -165,10 +168,10 @@ impl<'a> Plan<'a> {
     #[allow(clippy::cognitive_complexity, clippy::unreachable)]
     #[instrument(level = "debug", "index", skip(self), fields(node = ?self.node))]
     pub(super) fn add(&mut self, index_on: Index) -> Result<(), ReadySetError> {
-        // if we are recovering, we must build the paths again. Otherwise
-        // if we're full and we already have some paths added... (either this run, or from previous
-        // runs)
-        if !self.dmp.is_recovery() && !self.partial && (!self.paths.is_empty() || self.has_paths) {
+        // if we're full and we already have some paths added... (either this run, or, unless we
+        // are recovering and so must build the paths again, from previous runs)
+        if !self.partial && (!self.paths.is_empty() || (self.has_paths && !self.dmp.is_recovery()))
+        {
             // ...don't add any more replay paths, because fully materialized nodes should not have
             // one replay path per index. that would cause us to replay several times, even though
             // one full replay should always be sufficient.  we do need to keep track of the fact
@@ -877,10 +880,24 @@ impl<'a> Plan<'a> {
             let weak_indices = self.m.added_weak.remove(&self.node).unwrap_or_default();
 
             if self.partial {
-                let strict_indices = self.indexes.drain().collect();
+                let strict_indices: Vec<_> = self.indexes.drain().collect();
+                // Tiered state only spills keys of hash indices, so don't bother with it for nodes
+                // that need range lookups
+                let spill = match self.m.config.partial_state_spill {
+                    PartialStateSpill::None => false,
+                    PartialStateSpill::Joins => our_node.is_join()?,
+                    PartialStateSpill::All => true,
+                } && strict_indices
+                    .iter()
+                    .all(|(index, _)| index.index_type == IndexType::HashMap);
+                let tiered = spill.then(|| TieredStateParameters {
+                    disk_limit: self.m.config.partial_state_disk_limit,
+                    min_memory_bytes: self.m.config.partial_state_spill_min_bytes,
+                });
                 PrepareStateKind::Partial {
                     strict_indices,
                     weak_indices,
+                    tiered,
                 }
             } else {
                 let strict_indices = self.indexes.drain().map(|(k, _)| k).collect();
@@ -561,6 +561,20 @@ impl DomainMigrationPlan {
         }
     }
 
+    /// Make a new, empty [`DomainMigrationPlan`] with the same mode and domains as this one
+    pub fn new_like(&self) -> Self {
+        Self::new(self.mode, self.domains.clone())
+    }
+
+    /// Enqueue all the messages enqueued in `other` for which `f` returns `true`, discarding any
+    /// other messages, and any domains `other` would place.
+    pub fn extend_messages_where<F>(&mut self, other: DomainMigrationPlan, f: F)
+    where
+        F: FnMut(&StoredDomainRequest) -> bool,
+    {
+        self.stored.extend(other.stored.into_iter().filter(f));
+    }
+
     /// Extend the [`DomainMigrationPlan`] with all the valid domains and messages enqueued in
     /// `other`.
     pub fn extend(&mut self, other: DomainMigrationPlan) {
@@ -675,12 +689,16 @@ pub struct Migration<'df> {
     pub(super) readers: HashMap<NodeIndex, NodeIndex>,
     pub(super) worker: Option<WorkerIdentifier>,
     pub(super) dialect: Dialect,
+    /// The number of shards to shard the nodes added in this migration by, if any. Defaults to
+    /// the sharding of the whole deployment, but can be overridden with [`Self::set_sharding`]
+    pub(super) sharding: Option<usize>,
 
     pub(super) start: Instant,
 }
 
 impl<'df> Migration<'df> {
     pub(super) fn new(dataflow_state: &'df mut DfState, dialect: Dialect) -> Self {
+        let sharding = dataflow_state.sharding;
         Self {
             dataflow_state,
             changes: Default::default(),
@@ -688,10 +706,20 @@ impl<'df> Migration<'df> {
             readers: Default::default(),
             worker: None,
             dialect,
+            sharding,
             start: Instant::now(),
         }
     }
 
+    /// Shard all the nodes added in this migration `shards` ways, rather than by the sharding
+    /// configured for the deployment.
+    ///
+    /// Nodes added in this migration whose parents are sharded differently (such as base tables)
+    /// will be resharded on the way in, just like any other change of sharding key.
+    pub(super) fn set_sharding(&mut self, shards: usize) {
+        self.sharding = Some(shards);
+    }
+
     /// Add the given `Ingredient` to the dataflow graph.
     ///
     /// The returned identifier can later be used to refer to the added ingredient.
@@ -1009,11 +1037,17 @@ impl<'df> Migration<'df> {
         let mut dropped = 0;
         let columns = self.columns;
         let worker = self.worker;
+        let sharding_factor = self.sharding;
         for change in self.changes.into_iter() {
             match change {
                 NodeChanges::Add(new_nodes) => {
                     added += new_nodes.len();
-                    dmp.extend(plan_add_nodes(dataflow_state, new_nodes, &worker)?)
+                    dmp.extend(plan_add_nodes(
+                        dataflow_state,
+                        new_nodes,
+                        &worker,
+                        sharding_factor,
+                    )?)
                 }
                 NodeChanges::Drop(drop_nodes) => {
                     dropped += drop_nodes.len();
@@ -1048,6 +1082,7 @@ fn plan_add_nodes(
     dataflow_state: &mut DfState,
     mut new_nodes: HashSet<NodeIndex>,
     worker: &Option<WorkerIdentifier>,
+    sharding_factor: Option<usize>,
 ) -> ReadySetResult<DomainMigrationPlan> {
     let mut topo = topo_order(dataflow_state, &new_nodes);
 
@@ -1056,7 +1091,7 @@ fn plan_add_nodes(
     let mut local_redundant_partial: HashMap<NodeIndex, NodeIndex> = Default::default();
 
     // Shard the graph as desired
-    let mut swapped0 = if let Some(shards) = dataflow_state.sharding {
+    let mut swapped0 = if let Some(shards) = sharding_factor {
         let (t, swapped) = sharding::shard(
             &mut dataflow_state.ingredients,
             &mut new_nodes,
@@ -1234,7 +1269,7 @@ fn plan_add_nodes(
 
         topo = topo_order(dataflow_state, &new_nodes);
 
-        if let Some(shards) = dataflow_state.sharding {
+        if let Some(shards) = sharding_factor {
             sharding::validate(&dataflow_state.ingredients, &topo, shards)?
         };
 
@@ -101,6 +101,7 @@ pub(super) fn mir_node_to_flow_parts(
                         on,
                         group_by,
                         GroupedNodeType::Aggregation(kind.clone()),
+                        0,
                         mig,
                     )?)
                 }
@@ -121,6 +122,7 @@ pub(super) fn mir_node_to_flow_parts(
                     ref on,
                     ref group_by,
                     ref kind,
+                    buffer_size,
                     ..
                 } => {
                     invariant_eq!(ancestors.len(), 1);
@@ -133,6 +135,7 @@ pub(super) fn mir_node_to_flow_parts(
                         on,
                         group_by,
                         GroupedNodeType::Extremum(kind.clone()),
+                        buffer_size,
                         mig,
                     )?)
                 }
@@ -522,6 +525,7 @@ fn make_grouped_node(
     on: &Column,
     group_by: &[Column],
     kind: GroupedNodeType,
+    extremum_buffer_size: usize,
     mig: &mut Migration<'_>,
 ) -> ReadySetResult<DfNodeIndex> {
     let parent_na = graph.resolve_dataflow_node(parent).ok_or_else(|| {
@@ -586,11 +590,13 @@ fn make_grouped_node(
             mig.add_ingredient(name, cols, grouped)
         }
         GroupedNodeType::Extremum(extr) => {
-            let grouped = extr.over(
-                parent_na.address(),
-                over_col_indx,
-                group_col_indx.as_slice(),
-            );
+            let grouped = extr
+                .over(
+                    parent_na.address(),
+                    over_col_indx,
+                    group_col_indx.as_slice(),
+                )
+                .with_buffer_size(extremum_buffer_size);
             let agg_col = make_agg_col(grouped.output_col_type().or_ref(over_col_ty).clone());
             cols.push(agg_col);
             set_names(&column_names(columns), &mut cols)?;
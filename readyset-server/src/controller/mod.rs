@@ -46,6 +46,7 @@ use crate::materialization::Materializations;
 use crate::worker::{WorkerRequest, WorkerRequestKind, WorkerRequestType};
 use crate::{Config, VolumeId};
 
+pub(crate) mod backup;
 mod domain_handle;
 mod inner;
 mod keys;
@@ -246,19 +247,23 @@ impl ReplicatorChannel {
 /// Channel used to notify the replication about controller events.
 /// This is the other way around communication from Replicator Channel
 pub struct ControllerChannel {
-    _sender: UnboundedSender<ControllerMessage>,
+    sender: UnboundedSender<ControllerMessage>,
     receiver: Option<UnboundedReceiver<ControllerMessage>>,
 }
 
 impl ControllerChannel {
     fn new() -> Self {
-        let (_sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
         Self {
-            _sender,
+            sender,
             receiver: Some(receiver),
         }
     }
 
+    fn sender(&self) -> UnboundedSender<ControllerMessage> {
+        self.sender.clone()
+    }
+
     fn receiver(&mut self) -> UnboundedReceiver<ControllerMessage> {
         self.receiver.take().unwrap()
     }
@@ -435,7 +440,7 @@ impl Controller {
         shutdown_rx: ShutdownReceiver,
     ) -> Self {
         // If we don't have an upstream, we allow permissive writes to base tables.
-        let permissive_writes = config.replicator_config.upstream_db_url.is_none();
+        let permissive_writes = !config.replicator_config.has_replication_source();
         let (background_task_failed_tx, background_task_failed_rx) = mpsc::channel(1);
         Self {
             inner: Arc::new(LeaderHandle::new()),
@@ -562,6 +567,7 @@ impl Controller {
                 leader
                     .start(
                         self.replicator_channel.sender(),
+                        self.controller_channel.sender(),
                         self.controller_channel.receiver(),
                         self.telemetry_sender.clone(),
                         self.shutdown_rx.clone(),
@@ -1,17 +1,18 @@
 use std::collections::{HashMap, HashSet};
 
+use dataflow::ops::union;
 use dataflow::{PostLookupAggregate, PostLookupAggregateFunction, PostLookupAggregates};
 use mir::node::node_inner::MirNodeInner;
 use mir::node::ProjectExpr;
 use mir::{Column, NodeIndex};
 use nom_sql::analysis::ReferredColumns;
 use nom_sql::FunctionExpr::*;
-use nom_sql::{self, DialectDisplay, Expr, FieldDefinitionExpr, Relation, SqlIdentifier};
-use readyset_errors::{unsupported, ReadySetError, ReadySetResult};
+use nom_sql::{self, DialectDisplay, Expr, FieldDefinitionExpr, Literal, Relation, SqlIdentifier};
+use readyset_errors::{internal, unsupported, ReadySetError, ReadySetResult};
 use readyset_sql_passes::is_aggregate;
 
 use crate::controller::sql::mir::join::make_joins_for_aggregates;
-use crate::controller::sql::mir::SqlToMirConverter;
+use crate::controller::sql::mir::{sketch_percentile, SqlToMirConverter};
 use crate::controller::sql::query_graph::QueryGraph;
 
 // Move predicates above grouped_by nodes
@@ -142,21 +143,22 @@ pub(super) fn make_grouped(
         // Don't need to do anything if we don't have any aggregates
         return Ok(vec![]);
     }
+    if let Some(grouping_sets) = &qg.grouping_sets {
+        return make_grouping_sets(
+            mir_converter,
+            query_name,
+            name,
+            qg,
+            grouping_sets,
+            prev_node,
+            projected_exprs,
+        );
+    }
     for (function, alias) in &qg.aggregates {
         let name = mir_converter.generate_label(&name);
 
         // Convert the GROUP BY exprs into column references
-        let group_by = qg
-            .group_by
-            .iter()
-            .map(|gb_expr| match gb_expr {
-                Expr::Column(c) => c.clone(),
-                expr => nom_sql::Column {
-                    name: expr.display(nom_sql::Dialect::MySQL).to_string().into(),
-                    table: None,
-                },
-            })
-            .collect::<Vec<_>>();
+        let group_by = qg.group_by.iter().map(group_by_column).collect::<Vec<_>>();
 
         // get any parameter columns that aren't also in the group-by
         // column set
@@ -237,6 +239,167 @@ pub(super) fn make_grouped(
     Ok(agg_nodes)
 }
 
+/// Convert a GROUP BY expression into a reference to the column that the grouped nodes for that
+/// expression will emit
+fn group_by_column(gb_expr: &Expr) -> nom_sql::Column {
+    match gb_expr {
+        Expr::Column(c) => c.clone(),
+        expr => nom_sql::Column {
+            name: expr.display(nom_sql::Dialect::MySQL).to_string().into(),
+            table: None,
+        },
+    }
+}
+
+/// Make the grouped nodes for a query which groups by multiple grouping sets (`ROLLUP`, `CUBE`, or
+/// `GROUPING SETS`), as a union of the grouped nodes for each grouping set.
+///
+/// Every branch of the union projects all of the columns in any grouping set, with NULL for the
+/// columns that aren't in that branch's grouping set, followed by any parameter columns, the
+/// aggregates, and a literal for the value of each `GROUPING(...)` call in the query.
+fn make_grouping_sets(
+    mir_converter: &mut SqlToMirConverter,
+    query_name: &Relation,
+    name: Relation,
+    qg: &QueryGraph,
+    grouping_sets: &[Vec<Expr>],
+    prev_node: &mut NodeIndex,
+    projected_exprs: &HashMap<Expr, SqlIdentifier>,
+) -> ReadySetResult<Vec<NodeIndex>> {
+    // All the expressions in any grouping set, in a consistent order
+    let mut group_by_exprs: Vec<&Expr> = vec![];
+    for expr in grouping_sets.iter().flatten() {
+        if !group_by_exprs.contains(&expr) {
+            group_by_exprs.push(expr);
+        }
+    }
+    let group_by_cols = group_by_exprs
+        .iter()
+        .map(|expr| group_by_column(expr))
+        .collect::<Vec<_>>();
+    let param_cols = qg
+        .relations
+        .values()
+        .flat_map(|rel| rel.parameters.iter().map(|param| &param.col))
+        .filter(|c| !group_by_cols.contains(c))
+        .cloned()
+        .collect::<Vec<_>>();
+    let aggregate_aliases = qg.aggregates.values().collect::<Vec<_>>();
+
+    let mut output_names = HashSet::new();
+    for col in group_by_cols.iter().chain(&param_cols) {
+        // The columns of the union are matched up by name, so we can't tell apart two columns
+        // with the same name in different tables
+        if !output_names.insert(&col.name) {
+            unsupported!(
+                "Grouping sets with multiple columns named {} are not supported",
+                col.name
+            );
+        }
+    }
+
+    let mut nodes = vec![];
+    let mut branches = vec![];
+    for set in grouping_sets {
+        let mut set_qg = qg.clone();
+        set_qg.group_by = set.iter().cloned().collect();
+        set_qg.grouping_sets = None;
+
+        let mut branch = *prev_node;
+        nodes.extend(make_grouped(
+            mir_converter,
+            query_name,
+            name.clone(),
+            &set_qg,
+            &HashMap::new(),
+            &mut branch,
+            projected_exprs,
+        )?);
+
+        let emit = group_by_exprs
+            .iter()
+            .zip(&group_by_cols)
+            .map(|(expr, col)| {
+                if set.contains(expr) {
+                    ProjectExpr::Column(Column::from(col))
+                } else {
+                    ProjectExpr::Expr {
+                        alias: col.name.clone(),
+                        expr: Expr::Literal(Literal::Null),
+                    }
+                }
+            })
+            .chain(
+                param_cols
+                    .iter()
+                    .map(|col| ProjectExpr::Column(Column::from(col))),
+            )
+            .chain(
+                aggregate_aliases
+                    .iter()
+                    .map(|alias| ProjectExpr::Column(Column::named((*alias).clone()))),
+            )
+            .chain(qg.grouping_columns.iter().map(|(arguments, alias)| {
+                // Each bit of the result is set if the corresponding argument is *not* grouped
+                // by in this grouping set, with the first argument as the most significant bit
+                let mask = arguments.iter().fold(0i64, |mask, arg| {
+                    (mask << 1) | i64::from(!set.contains(arg))
+                });
+                ProjectExpr::Expr {
+                    alias: alias.clone(),
+                    expr: Expr::Literal(Literal::Integer(mask)),
+                }
+            }))
+            .collect();
+
+        let project = mir_converter.make_project_node(
+            query_name,
+            mir_converter.generate_label(&name),
+            branch,
+            emit,
+        );
+        nodes.push(project);
+        branches.push(project);
+    }
+
+    let Some(&first_branch) = branches.first() else {
+        internal!("Query has no grouping sets");
+    };
+    if branches.len() == 1 {
+        *prev_node = first_branch;
+        return Ok(nodes);
+    }
+
+    // The union emits its columns by name, but remembers the original columns in the grouping
+    // sets as aliases so that they can still be referenced by the rest of the query
+    let union_cols = mir_converter
+        .columns(first_branch)
+        .into_iter()
+        .map(|col| {
+            let mut union_col = Column::named(col.name.clone());
+            if let Some(orig) = group_by_cols
+                .iter()
+                .chain(&param_cols)
+                .find(|c| c.name == col.name && c.table.is_some())
+            {
+                union_col.add_alias(&Column::from(orig));
+            }
+            union_col
+        })
+        .collect();
+    let union = mir_converter.make_union_from_same_base(
+        query_name,
+        mir_converter.generate_label(&name),
+        branches,
+        union_cols,
+        union::DuplicateMode::UnionAll,
+    )?;
+    nodes.push(union);
+    *prev_node = union;
+
+    Ok(nodes)
+}
+
 // joinable_aggregate_nodes will take in a list of aggregate nodes and return a list of aggregate
 // nodes in the same order they appeared in the input list, and filter out nodes that should not be
 // joined. For example, we could see a projection node appear as an aggregate node in the case:
@@ -302,6 +465,10 @@ pub(super) fn post_lookup_aggregates(
         return Ok(None);
     }
 
+    if query_graph.grouping_sets.is_some() {
+        unsupported!("Grouping sets are not supported with post-lookup aggregation");
+    }
+
     let mut aggregates = vec![];
     for (function, alias) in &query_graph.aggregates {
         aggregates.push(PostLookupAggregate {
@@ -326,7 +493,13 @@ pub(super) fn post_lookup_aggregates(
                 GroupConcat { separator, .. } => PostLookupAggregateFunction::GroupConcat {
                     separator: separator.clone().unwrap_or_else(|| ",".to_owned()),
                 },
-                Call { .. } | Substring { .. } => continue,
+                ApproxCountDistinct(_) => PostLookupAggregateFunction::ApproxCountDistinct,
+                ApproxPercentile { percentile, .. } => {
+                    PostLookupAggregateFunction::ApproxPercentile {
+                        percentile: sketch_percentile(percentile)?,
+                    }
+                }
+                Call { .. } | Substring { .. } | Grouping(..) => continue,
             },
         });
     }
@@ -40,7 +40,7 @@ use crate::controller::sql::mir::grouped::{
 };
 use crate::controller::sql::mir::join::{make_cross_joins, make_joins};
 use crate::controller::sql::query_graph::{
-    to_query_graph, ExprColumn, OutputColumn, Pagination, QueryGraph,
+    is_sketch_aggregate, to_query_graph, ExprColumn, OutputColumn, Pagination, QueryGraph,
 };
 use crate::controller::sql::query_signature::Signature;
 
@@ -51,6 +51,28 @@ lazy_static! {
     pub static ref PAGE_NUMBER_COL: SqlIdentifier = "__page_number".into();
 }
 
+/// Extract the percentile argument to an approximate percentile aggregate, which must be a numeric
+/// literal between 0 and 1
+fn sketch_percentile(percentile: &Expr) -> ReadySetResult<nom_sql::Double> {
+    let value = match percentile {
+        Expr::Literal(Literal::Double(d)) => d.value,
+        Expr::Literal(Literal::Float(f)) => f.value as f64,
+        Expr::Literal(Literal::Integer(i)) => *i as f64,
+        Expr::Literal(Literal::UnsignedInteger(i)) => *i as f64,
+        _ => unsupported!(
+            "Percentile must be a numeric literal, got {:?}",
+            Sensitive(percentile)
+        ),
+    };
+    if !(0.0..=1.0).contains(&value) {
+        invalid_query!("Percentile must be between 0 and 1, got {value}");
+    }
+    Ok(nom_sql::Double {
+        value,
+        precision: 2,
+    })
+}
+
 fn value_columns_needed_for_predicates(
     value_columns: &[OutputColumn],
     predicates: &[Expr],
@@ -138,6 +160,12 @@ impl LeafBehavior {
     }
 }
 
+/// Number of candidate values `MIN` and `MAX` nodes keep for each group if
+/// [`Config::extremum_buffer_size`] isn't set. Buffering is disabled by default, since keeping
+/// the extra candidates costs memory and write-path work for every group, which only pays off for
+/// workloads that frequently delete the current extreme value of a group.
+pub(crate) const DEFAULT_EXTREMUM_BUFFER_SIZE: usize = 0;
+
 /// Configuration for how SQL is converted to MIR
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, Default)]
 pub(crate) struct Config {
@@ -164,6 +192,13 @@ pub(crate) struct Config {
     /// reader)
     #[serde(default)]
     pub(crate) allow_post_lookup: bool,
+
+    /// Number of the most extreme values that `MIN` and `MAX` nodes keep for each group, so that
+    /// deleting the current extreme value of a group can usually be handled without recomputing
+    /// the whole group. If not set, defaults to [`DEFAULT_EXTREMUM_BUFFER_SIZE`], which disables
+    /// buffering.
+    #[serde(default)]
+    pub(crate) extremum_buffer_size: Option<usize>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -404,6 +439,54 @@ impl SqlToMirConverter {
         self.remove_dependent_nodes(leaf_mn)
     }
 
+    /// Removes all the nodes for a cached query/view from MIR, *including* nodes it shares with
+    /// other queries and views via reuse, along with all the views/cached queries that depend on
+    /// any of those nodes.
+    pub(super) fn remove_query_with_shared_nodes(
+        &mut self,
+        name: &Relation,
+    ) -> ReadySetResult<MirRemovalResult> {
+        let leaf_mn = self
+            .get_relation(name)
+            .ok_or_else(|| ReadySetError::RelationNotFound {
+                relation: name.display_unquoted().to_string(),
+            })?;
+
+        // Every node in the query is downstream of one of the nodes directly below the base
+        // tables it reads from, so removing everything that depends on those nodes removes the
+        // whole query along with anything that reuses any part of it
+        let mut roots = Vec::new();
+        let mut bfs = petgraph::visit::Bfs::new(Reversed(&*self.mir_graph), leaf_mn);
+        while let Some(n) = bfs.next(Reversed(&*self.mir_graph)) {
+            if !self.mir_graph[n].is_base()
+                && self
+                    .mir_graph
+                    .neighbors_directed(n, Direction::Incoming)
+                    .any(|p| self.mir_graph[p].is_base())
+            {
+                roots.push(n);
+            }
+        }
+        if roots.is_empty() {
+            // The query doesn't read from any tables
+            roots.push(leaf_mn);
+        }
+
+        let mut result = MirRemovalResult::default();
+        for root in roots {
+            // Removing the dependents of an earlier root may have removed this one too
+            if !self.mir_graph.contains_node(root) {
+                continue;
+            }
+            let removed = self.remove_dependent_nodes(root)?;
+            result
+                .dataflow_nodes_to_remove
+                .extend(removed.dataflow_nodes_to_remove);
+            result.relations_removed.extend(removed.relations_removed);
+        }
+        Ok(result)
+    }
+
     /// Removes a base table, along with all the views/cached queries associated with it.
     pub(super) fn remove_base(&mut self, name: &Relation) -> ReadySetResult<MirRemovalResult> {
         debug!(name = %name.display_unquoted(), "Removing base node");
@@ -942,6 +1025,46 @@ impl SqlToMirConverter {
                 }),
                 false,
             ),
+            ApproxCountDistinct(box Expr::Column(col)) => mknode(
+                Column::from(col),
+                GroupedNodeType::Aggregation(Aggregation::ApproxCountDistinct),
+                false,
+            ),
+            ApproxCountDistinct(ref expr) => mknode(
+                Column::named(
+                    projected_exprs
+                        .get(expr)
+                        .cloned()
+                        .ok_or_else(|| mk_error!(expr))?,
+                ),
+                GroupedNodeType::Aggregation(Aggregation::ApproxCountDistinct),
+                false,
+            ),
+            ApproxPercentile {
+                expr: box Expr::Column(col),
+                ref percentile,
+            } => mknode(
+                Column::from(col),
+                GroupedNodeType::Aggregation(Aggregation::ApproxPercentile {
+                    percentile: sketch_percentile(percentile)?,
+                }),
+                false,
+            ),
+            ApproxPercentile {
+                ref expr,
+                ref percentile,
+            } => mknode(
+                Column::named(
+                    projected_exprs
+                        .get(expr)
+                        .cloned()
+                        .ok_or_else(|| mk_error!(expr))?,
+                ),
+                GroupedNodeType::Aggregation(Aggregation::ApproxPercentile {
+                    percentile: sketch_percentile(percentile)?,
+                }),
+                false,
+            ),
             _ => {
                 internal!("not an aggregate: {:?}", Sensitive(&function));
             }
@@ -976,6 +1099,10 @@ impl SqlToMirConverter {
                         group_by,
                         output_column,
                         kind,
+                        buffer_size: self
+                            .config
+                            .extremum_buffer_size
+                            .unwrap_or(DEFAULT_EXTREMUM_BUFFER_SIZE),
                     },
                 ),
             },
@@ -2238,7 +2365,12 @@ impl SqlToMirConverter {
                     project_order,
                 );
 
-                let post_lookup_aggregates = if view_key.index_type == IndexType::HashMap {
+                // Sketch aggregates always need to be finalized by the reader, whether or not the
+                // lookup covers more than one group
+                let has_sketch_aggregates = query_graph.aggregates.keys().any(is_sketch_aggregate);
+                let post_lookup_aggregates = if has_sketch_aggregates {
+                    post_lookup_aggregates(query_graph, query_name)?
+                } else if view_key.index_type == IndexType::HashMap {
                     // If we have aggregates under the IndexType::HashMap, they aren't necessarily
                     // post-lookup operations. For example, `select sum(col2) from t where col1 =
                     // ?`, the aggregate will be handled in the dataflow graph.
@@ -2282,8 +2414,10 @@ impl SqlToMirConverter {
 
                 let limit = query_graph.pagination.as_ref().map(|p| p.limit);
 
+                let needs_post_lookup_aggregates = post_lookup_aggregates.is_some()
+                    && !(has_sketch_aggregates && view_key.index_type == IndexType::HashMap);
                 if !self.config.allow_post_lookup
-                    && (post_lookup_aggregates.is_some() || order_by.is_some() || limit.is_some())
+                    && (needs_post_lookup_aggregates || order_by.is_some() || limit.is_some())
                 {
                     unsupported!("Queries which perform operations post-lookup are not supported");
                 }
@@ -2308,6 +2442,10 @@ impl SqlToMirConverter {
             } else {
                 trace!("Making view keys for queries instead of leaf node");
 
+                if query_graph.aggregates.keys().any(is_sketch_aggregate) {
+                    unsupported!("Approximate aggregates are not supported in subqueries");
+                }
+
                 let mut keys = Vec::with_capacity(view_key.columns.len());
                 let mut unsupported_placeholders = vec![];
                 for (column, vp) in view_key.columns {
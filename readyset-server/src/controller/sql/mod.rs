@@ -74,6 +74,17 @@ struct UncompiledView {
     schema_search_path: Vec<SqlIdentifier>,
 }
 
+/// The result of rebuilding a cached query with [`SqlIncorporator::rebuild_query`]
+#[derive(Debug, Default)]
+pub(crate) struct RebuiltQueries {
+    /// The names of all the caches that were rebuilt - the query that was asked for, along with
+    /// any other caches that shared dataflow nodes with it
+    pub(crate) caches: Vec<Relation>,
+    /// The dataflow nodes the rebuilt caches were using before, which are left in place (and keep
+    /// serving reads) until they're dropped with [`SqlIncorporator::drop_nodes`]
+    pub(crate) old_nodes: HashSet<NodeIndex>,
+}
+
 /// Schema for a SQL base node
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub(crate) struct BaseSchema {
@@ -692,6 +703,100 @@ impl SqlIncorporator {
         Ok(name)
     }
 
+    /// Build new dataflow nodes for the cached query with the given name (or alias), using the
+    /// given `mig` to track changes.
+    ///
+    /// Any nodes the query shares with other queries or views via reuse are rebuilt too, so every
+    /// cache that shared nodes with the query is rebuilt along with it (and any views they read
+    /// from are compiled again). The old nodes are *not* dropped, so they can keep serving reads
+    /// until the new ones are ready - once they are, they must be removed with
+    /// [`Self::drop_nodes`]. The rebuilt queries keep their names, aliases, and query IDs.
+    pub(crate) fn rebuild_query(
+        &mut self,
+        name: &Relation,
+        mig: &mut Migration<'_>,
+    ) -> ReadySetResult<RebuiltQueries> {
+        let Some(RecipeExpr::Cache { name, .. }) = self.registry.get(name).cloned() else {
+            return Err(ReadySetError::ViewNotFound(
+                name.display_unquoted().to_string(),
+            ));
+        };
+        trace!(query_name = %name.display_unquoted(), "rebuilding query");
+
+        let removal_result = self.mir_converter.remove_query_with_shared_nodes(&name)?;
+
+        let mut caches = vec![name.clone()];
+        for relation in &removal_result.relations_removed {
+            match self.registry.get(relation).cloned() {
+                Some(RecipeExpr::Cache { name: cache, .. })
+                    if cache == *relation && cache != name =>
+                {
+                    caches.push(cache)
+                }
+                // Views are compiled again on demand by the caches that read from them
+                Some(RecipeExpr::View {
+                    name: view,
+                    definition,
+                }) if view == *relation => {
+                    self.uncompiled_views.insert(
+                        view.clone(),
+                        UncompiledView {
+                            name: view,
+                            definition,
+                            // The definition in the registry has already been rewritten
+                            schema_search_path: vec![],
+                        },
+                    );
+                }
+                _ => {}
+            }
+            self.leaf_addresses.remove(relation);
+            self.view_schemas.remove(relation);
+        }
+
+        for cache in &caches {
+            let Some(RecipeExpr::Cache { statement, .. }) = self.registry.get(cache).cloned()
+            else {
+                internal!(
+                    "Cache {} missing from the registry",
+                    cache.display_unquoted()
+                );
+            };
+            // The statement in the registry has already been rewritten, so all of the tables it
+            // references are qualified and we don't need a schema search path
+            let mut stmt = statement;
+            let mir_leaf = self.select_query_to_mir(
+                cache.clone(),
+                &mut stmt,
+                &[],
+                None,
+                LeafBehavior::Leaf,
+                mig,
+            )?;
+            let leaf = self.mir_to_dataflow(cache.clone(), mir_leaf, mig)?;
+            self.leaf_addresses.insert(cache.clone(), leaf);
+        }
+
+        Ok(RebuiltQueries {
+            caches,
+            old_nodes: removal_result
+                .dataflow_nodes_to_remove
+                .iter()
+                .map(|df_node_idx| df_node_idx.address())
+                .collect(),
+        })
+    }
+
+    /// Drop the given dataflow nodes, which must no longer be used by any query (such as the
+    /// [`old_nodes`](RebuiltQueries::old_nodes) of a rebuilt query), along with all the ingress,
+    /// egress, and reader nodes below them, using the given `mig` to track changes.
+    pub(crate) fn drop_nodes(nodes: &HashSet<NodeIndex>, mig: &mut Migration<'_>) {
+        Self::drop_dataflow_nodes(
+            &mut nodes.iter().copied().map(DfNodeIndex::new).collect(),
+            mig,
+        );
+    }
+
     /// Add a new user-defined custom type (represented internally as a named alias for a
     /// [`DfType`]). Will return an error if a type already exists with the same name
     pub(crate) fn add_custom_type(&mut self, name: Relation, ty: DfType) {
@@ -1169,10 +1274,18 @@ impl SqlIncorporator {
             )?,
         };
 
-        if !self.registry.add_query(RecipeExpr::View {
-            name: name.clone(),
-            definition,
-        })? {
+        // A view that's being rebuilt (see `rebuild_query`) is already registered under its own
+        // name, and just needs its dataflow nodes built again
+        let rebuilding = self
+            .registry
+            .get(&name)
+            .is_some_and(|expr| *expr.name() == name);
+        if !rebuilding
+            && !self.registry.add_query(RecipeExpr::View {
+                name: name.clone(),
+                definition,
+            })?
+        {
             // The expression is already present, and we successfully added
             // a new alias for it.
             return Ok(());
@@ -1254,12 +1367,17 @@ impl SqlIncorporator {
             self.registry.remove_expression(query);
             self.view_schemas.remove(query);
         }
+        Self::drop_dataflow_nodes(&mut removal_result.dataflow_nodes_to_remove, mig);
+    }
+
+    /// Drop the given dataflow nodes, along with the ingress, egress, and reader nodes below them
+    /// (which are not present in MIR), adding the latter to `nodes`.
+    fn drop_dataflow_nodes(nodes: &mut HashSet<DfNodeIndex>, mig: &mut Migration<'_>) {
         // Sadly, we don't use `DfNodeIndex` for migrations/df state, so we need to map them
         // to `NodeIndex`.
         // TODO(fran): Replace all occurrences of Dataflow node indices for `DfNodeIndex`.
         mig.changes.drop_nodes(
-            &removal_result
-                .dataflow_nodes_to_remove
+            &nodes
                 .iter()
                 .map(|df_node_idx| df_node_idx.address())
                 .collect(),
@@ -1274,7 +1392,7 @@ impl SqlIncorporator {
                 .map(|ni| DfNodeIndex::new(ni))
         };
         let mut removed = Vec::new();
-        for node in removal_result.dataflow_nodes_to_remove.iter() {
+        for node in nodes.iter() {
             let mut stack = next_for(*node).collect::<Vec<_>>();
             while let Some(node) = stack.pop() {
                 removed.push(node);
@@ -1282,7 +1400,7 @@ impl SqlIncorporator {
                 stack.extend(next_for(node));
             }
         }
-        removal_result.dataflow_nodes_to_remove.extend(removed);
+        nodes.extend(removed);
     }
 
     fn register_query(&mut self, query_name: Relation, fields: Vec<SqlIdentifier>) {
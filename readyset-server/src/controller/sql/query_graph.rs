@@ -25,6 +25,10 @@ use serde::{Deserialize, Serialize};
 
 use super::mir::{self, PAGE_NUMBER_COL};
 
+/// The maximum number of expressions supported in a `CUBE(...)` grouping set, since the number of
+/// grouping sets it expands to is exponential in the number of expressions
+const MAX_CUBE_FIELDS: usize = 8;
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct LiteralColumn {
     pub name: SqlIdentifier,
@@ -270,6 +274,13 @@ pub struct QueryGraph {
     pub aggregates: HashMap<FunctionExpr, SqlIdentifier>,
     /// Set of expressions that appear in the GROUP BY clause
     pub group_by: HashSet<Expr>,
+    /// If the query groups by grouping sets (`ROLLUP`, `CUBE`, or `GROUPING SETS`), the expanded
+    /// list of sets of expressions that the query groups by. Every expression in each set also
+    /// appears in `group_by`.
+    pub grouping_sets: Option<Vec<Vec<Expr>>>,
+    /// Calls to the `GROUPING(...)` function in the query, represented as the arguments to the
+    /// call along with the name of the column that the call has been replaced with
+    pub grouping_columns: Vec<(Vec<Expr>, SqlIdentifier)>,
     /// Final set of projected columns in this query; may include literals in addition to the
     /// columns reflected in individual relations' `QueryGraphNode` structures.
     pub columns: Vec<OutputColumn>,
@@ -470,6 +481,8 @@ impl Hash for QueryGraph {
         let mut group_by = self.group_by.iter().collect::<Vec<_>>();
         group_by.sort();
         group_by.hash(state);
+        self.grouping_sets.hash(state);
+        self.grouping_columns.hash(state);
 
         let mut aggregates = self.aggregates.iter().collect::<Vec<_>>();
         aggregates.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
@@ -789,6 +802,50 @@ fn extract_having_aggregates(
     having_predicates
 }
 
+/// Replaces all calls to the `GROUPING(...)` function in the given expression with references to
+/// columns named after the call, recording the arguments to each call along with the name of its
+/// column in `grouping_columns`.
+fn extract_grouping_calls(expr: &mut Expr, grouping_columns: &mut Vec<(Vec<Expr>, SqlIdentifier)>) {
+    struct GroupingCallFinder<'a> {
+        grouping_columns: &'a mut Vec<(Vec<Expr>, SqlIdentifier)>,
+    }
+
+    impl<'ast, 'a> VisitorMut<'ast> for GroupingCallFinder<'a> {
+        type Error = !;
+
+        fn visit_expr(&mut self, expr: &'ast mut Expr) -> Result<(), Self::Error> {
+            if matches!(expr, Expr::Call(FunctionExpr::Grouping(_))) {
+                // FIXME(REA-2168): Use correct dialect.
+                let name: SqlIdentifier = expr.display(nom_sql::Dialect::MySQL).to_string().into();
+                let col_expr = Expr::Column(nom_sql::Column {
+                    name: name.clone(),
+                    table: None,
+                });
+                let Expr::Call(FunctionExpr::Grouping(arguments)) = mem::replace(expr, col_expr)
+                else {
+                    unreachable!("Checked matches above")
+                };
+                if !self.grouping_columns.iter().any(|(_, n)| *n == name) {
+                    self.grouping_columns.push((arguments, name));
+                }
+                Ok(())
+            } else {
+                walk_expr(self, expr)
+            }
+        }
+
+        fn visit_select_statement(
+            &mut self,
+            _: &'ast mut SelectStatement,
+        ) -> Result<(), Self::Error> {
+            // Don't walk into subqueries
+            Ok(())
+        }
+    }
+
+    let _ = GroupingCallFinder { grouping_columns }.visit_expr(expr);
+}
+
 /// Convert limit and offset fields to an optional constant numeric limit and optional placeholder
 /// for the offset
 pub(crate) fn extract_limit_offset(
@@ -847,6 +904,16 @@ fn table_expr_name(table_expr: &TableExpr) -> ReadySetResult<Relation> {
     }
 }
 
+/// Returns true if the given aggregate function is computed from a sketch, in which case the value
+/// of the aggregate in the dataflow graph is the serialized sketch, and the value returned to the
+/// client is only computed by the reader.
+pub(crate) fn is_sketch_aggregate(function: &FunctionExpr) -> bool {
+    matches!(
+        function,
+        FunctionExpr::ApproxCountDistinct(..) | FunctionExpr::ApproxPercentile { .. }
+    )
+}
+
 fn default_row_for_select(st: &SelectStatement) -> Option<Vec<DfValue>> {
     // If this is an aggregated query AND it does not contain a GROUP BY clause,
     // set default values based on the aggregation (or lack thereof) on each
@@ -869,7 +936,11 @@ fn default_row_for_select(st: &SelectStatement) -> Option<Vec<DfValue>> {
                     FunctionExpr::Max(..) => DfValue::None,
                     FunctionExpr::Min(..) => DfValue::None,
                     FunctionExpr::GroupConcat { .. } => DfValue::None,
-                    FunctionExpr::Call { .. } | FunctionExpr::Substring { .. } => DfValue::None,
+                    FunctionExpr::ApproxCountDistinct(..) => DfValue::Int(0),
+                    FunctionExpr::ApproxPercentile { .. } => DfValue::None,
+                    FunctionExpr::Call { .. }
+                    | FunctionExpr::Substring { .. }
+                    | FunctionExpr::Grouping(..) => DfValue::None,
                 },
                 _ => DfValue::None,
             })
@@ -1204,11 +1275,18 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
     // add any found aggregate functions in the HAVING clause to qg.columns, since we don't want to
     // necessarily return these in the query results.
     let mut aggregates = HashMap::new();
-    let having_predicates = if let Some(having_expr) = stmt.having.as_ref() {
+    let mut grouping_columns = vec![];
+    let mut having_predicates = if let Some(having_expr) = stmt.having.as_ref() {
         extract_having_aggregates(having_expr, &mut aggregates)
     } else {
         vec![]
     };
+    for pred in having_predicates.iter_mut() {
+        extract_grouping_calls(pred, &mut grouping_columns);
+    }
+    if aggregates.keys().any(is_sketch_aggregate) {
+        unsupported!("Approximate aggregates are not supported in the HAVING clause");
+    }
 
     let mut columns = Vec::with_capacity(stmt.fields.len());
     for field in stmt.fields.iter() {
@@ -1221,7 +1299,9 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                     .clone()
                     // FIXME(REA-2168): Use correct dialect.
                     .unwrap_or_else(|| expr.display(nom_sql::Dialect::MySQL).to_string().into());
-                match expr {
+                let mut expr = expr.clone();
+                extract_grouping_calls(&mut expr, &mut grouping_columns);
+                match &expr {
                     Expr::Literal(l) => columns.push(OutputColumn::Literal(LiteralColumn {
                         name,
                         table: None,
@@ -1256,6 +1336,12 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
                     _ => {
                         let mut expr = expr.clone();
                         let aggs = map_aggregates(&mut expr);
+                        if aggs.iter().any(|(f, _)| is_sketch_aggregate(f)) {
+                            unsupported!(
+                                "Approximate aggregates can only be projected directly, not used \
+                                 in other expressions"
+                            );
+                        }
                         aggregates.extend(aggs);
 
                         columns.push(OutputColumn::Expr(ExprColumn {
@@ -1269,22 +1355,113 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
         }
     }
 
+    let group_by_expr = |f: &FieldReference| match f {
+        FieldReference::Numeric(_) => {
+            internal!("Numeric field references should have been removed")
+        }
+        FieldReference::Expr(e) => Ok(e.clone()),
+    };
+
     let group_by = if let Some(group_by_clause) = &stmt.group_by {
         group_by_clause
-            .fields
-            .iter()
-            .map(|f| match f {
-                FieldReference::Numeric(_) => {
-                    internal!("Numeric field references should have been removed")
-                }
-                FieldReference::Expr(e) => Ok(e.clone()),
-            })
+            .all_fields()
+            .map(group_by_expr)
             .collect::<ReadySetResult<HashSet<_>>>()?
     } else {
         Default::default()
     };
 
+    let mut grouping_sets = if let Some(group_by_clause) = &stmt.group_by {
+        if let Some(nom_sql::GroupingSets::Cube(fields)) = &group_by_clause.grouping_sets {
+            if fields.len() > MAX_CUBE_FIELDS {
+                unsupported!("CUBE is only supported with up to {MAX_CUBE_FIELDS} expressions");
+            }
+        }
+        group_by_clause
+            .expanded_grouping_sets()
+            .map(|sets| {
+                sets.into_iter()
+                    .map(|set| {
+                        let mut exprs = Vec::with_capacity(set.len());
+                        for field in set {
+                            let expr = group_by_expr(field)?;
+                            if !exprs.contains(&expr) {
+                                exprs.push(expr);
+                            }
+                        }
+                        Ok(exprs)
+                    })
+                    .collect::<ReadySetResult<Vec<_>>>()
+            })
+            .transpose()?
+    } else {
+        None
+    };
+
+    for (arguments, _) in &grouping_columns {
+        if let Some(arg) = arguments.iter().find(|arg| !group_by.contains(arg)) {
+            invalid_query!(
+                "Arguments to GROUPING must be grouping expressions of the query, but {} is not",
+                arg.display(nom_sql::Dialect::MySQL)
+            );
+        }
+    }
+
+    if grouping_sets.is_none() && !grouping_columns.is_empty() {
+        // GROUPING is still allowed without grouping sets, in which case every row is part of
+        // the single grouping set of the query
+        grouping_sets = Some(vec![stmt
+            .group_by
+            .iter()
+            .flat_map(|gb| gb.fields.iter())
+            .map(group_by_expr)
+            .collect::<ReadySetResult<_>>()?]);
+    }
+
+    if let Some(sets) = &grouping_sets {
+        if aggregates.is_empty() {
+            unsupported!("Grouping sets are only supported in queries with aggregates");
+        }
+        if aggregates.keys().any(is_sketch_aggregate) {
+            unsupported!("Approximate aggregates are not supported with grouping sets");
+        }
+        // Parameters are looked up by exact value, so a parameter column can't take on NULL
+        // values in the rows for the grouping sets it doesn't appear in
+        let parameter_columns = relations
+            .values()
+            .flat_map(|rel| rel.parameters.iter().map(|p| &p.col))
+            .collect::<Vec<_>>();
+        if let Some(col) = parameter_columns.into_iter().find(|col| {
+            let expr = Expr::Column((*col).clone());
+            group_by.contains(&expr) && !sets.iter().all(|set| set.contains(&expr))
+        }) {
+            unsupported!(
+                "Parameter column {} must appear in every grouping set",
+                col.name
+            );
+        }
+    }
+
+    if stmt.distinct && aggregates.keys().any(is_sketch_aggregate) {
+        unsupported!("Approximate aggregates are not supported in SELECT DISTINCT queries");
+    }
+
     if let Some(ref order) = stmt.order {
+        let orders_by_sketch = |field: &FieldReference| match field {
+            FieldReference::Expr(Expr::Call(func)) => is_sketch_aggregate(func),
+            FieldReference::Expr(Expr::Column(Column { name, table: None })) => aggregates
+                .iter()
+                .any(|(func, alias)| is_sketch_aggregate(func) && alias == name),
+            _ => false,
+        };
+        if order
+            .order_by
+            .iter()
+            .any(|OrderBy { field, .. }| orders_by_sketch(field))
+        {
+            unsupported!("Ordering by approximate aggregates is not supported");
+        }
+
         // For each column in the `ORDER BY` clause, check if it needs to be projected
         order
             .order_by
@@ -1445,6 +1622,8 @@ pub fn to_query_graph(stmt: SelectStatement) -> ReadySetResult<QueryGraph> {
         edges,
         aggregates,
         group_by,
+        grouping_sets,
+        grouping_columns,
         columns,
         fields: stmt.fields.clone(),
         default_row,
@@ -1738,6 +1917,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn grouping_sets() {
+        let qg = make_query_graph(
+            "SELECT t.x, t.y, count(*), grouping(t.x, t.y) FROM t GROUP BY t.x, t.y WITH ROLLUP",
+        );
+        let x = Expr::Column("t.x".into());
+        let y = Expr::Column("t.y".into());
+        assert_eq!(qg.group_by, HashSet::from([x.clone(), y.clone()]));
+        assert_eq!(
+            qg.grouping_sets,
+            Some(vec![vec![x.clone(), y.clone()], vec![x.clone()], vec![]])
+        );
+        assert_eq!(
+            qg.grouping_columns,
+            vec![(vec![x, y], "grouping(`t`.`x`, `t`.`y`)".into())]
+        );
+        assert_eq!(
+            qg.columns.last().unwrap(),
+            &OutputColumn::Data {
+                alias: "grouping(`t`.`x`, `t`.`y`)".into(),
+                column: Column {
+                    name: "grouping(`t`.`x`, `t`.`y`)".into(),
+                    table: None,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn grouping_sets_with_partially_grouped_parameter() {
+        let query = parse_select_statement(
+            Dialect::MySQL,
+            "SELECT t.x, t.y, count(*) FROM t WHERE t.x = ? GROUP BY t.x, t.y WITH ROLLUP",
+        )
+        .unwrap();
+        to_query_graph(query).unwrap_err();
+
+        let qg = make_query_graph(
+            "SELECT t.x, t.y, count(*) FROM t WHERE t.x = ? GROUP BY t.x, ROLLUP(t.y)",
+        );
+        assert_eq!(
+            qg.grouping_sets,
+            Some(vec![
+                vec![Expr::Column("t.x".into()), Expr::Column("t.y".into())],
+                vec![Expr::Column("t.x".into())],
+            ])
+        );
+    }
+
     mod view_key {
         use super::*;
 
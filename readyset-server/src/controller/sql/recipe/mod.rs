@@ -1,3 +1,4 @@
+use std::collections::HashSet;
 use std::{fmt, str};
 
 use nom_sql::{Relation, SelectStatement, SqlIdentifier};
@@ -15,7 +16,7 @@ use vec1::Vec1;
 
 use super::registry::{MatchedCache, RecipeExpr};
 use super::BaseSchema;
-use crate::controller::sql::SqlIncorporator;
+use crate::controller::sql::{RebuiltQueries, SqlIncorporator};
 use crate::controller::Migration;
 
 /// Uniquely identifies an expression in the expression registry.
@@ -193,6 +194,22 @@ impl Recipe {
         self.inc.apply_changelist(changelist, mig)
     }
 
+    /// Build new dataflow nodes for the cached query with the given name (or alias), leaving the
+    /// old ones in place. See [`SqlIncorporator::rebuild_query`]
+    pub(crate) fn rebuild_query(
+        &mut self,
+        mig: &mut Migration<'_>,
+        name: &Relation,
+    ) -> ReadySetResult<RebuiltQueries> {
+        self.inc.rebuild_query(name, mig)
+    }
+
+    /// Drop the given dataflow nodes, which must no longer be used by any query. See
+    /// [`SqlIncorporator::drop_nodes`]
+    pub(crate) fn drop_nodes(mig: &mut Migration<'_>, nodes: &HashSet<NodeIndex>) {
+        SqlIncorporator::drop_nodes(nodes, mig)
+    }
+
     /// Helper method to reparent a recipe. This is needed for some of t
     pub(crate) fn sql_inc(&self) -> &SqlIncorporator {
         &self.inc
@@ -16,12 +16,13 @@ use std::cell;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::net::SocketAddr;
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Instant;
 
 use array2::Array2;
 use common::{IndexPair, Tag};
-use dataflow::payload::EvictRequest;
+use dataflow::payload::{BaseTableCheckpoint, EvictRequest};
 use dataflow::prelude::{ChannelCoordinator, DomainIndex, DomainNodes, Graph, NodeIndex};
 use dataflow::{
     BaseTableState, DomainBuilder, DomainConfig, DomainRequest, NodeMap, Packet,
@@ -35,6 +36,7 @@ use nom_sql::{NonReplicatedRelation, Relation, SqlIdentifier};
 use petgraph::visit::{Bfs, IntoNodeReferences};
 use petgraph::Direction;
 use rand::Rng;
+use readyset_client::backup::BackupTable;
 use readyset_client::builders::{
     ReaderHandleBuilder, ReusedReaderHandleBuilder, TableBuilder, ViewBuilder,
 };
@@ -45,7 +47,7 @@ use readyset_client::debug::stats::{DomainStats, GraphStats, NodeStats};
 use readyset_client::failpoints;
 use readyset_client::internal::{MaterializationStatus, ReplicaAddress};
 use readyset_client::metrics::recorded;
-use readyset_client::recipe::changelist::{Change, ChangeList};
+use readyset_client::recipe::changelist::{Change, ChangeList, CreateCache};
 use readyset_client::recipe::{CacheExpr, ExtendRecipeSpec};
 use readyset_client::{
     PersistencePoint, SingleKeyEviction, TableReplicationStatus, TableStatus, ViewCreateRequest,
@@ -53,7 +55,7 @@ use readyset_client::{
 };
 use readyset_data::{DfValue, Dialect};
 use readyset_errors::{
-    internal, internal_err, invariant_eq, NodeType, ReadySetError, ReadySetResult,
+    bad_request_err, internal, internal_err, invariant_eq, NodeType, ReadySetError, ReadySetResult,
 };
 use replication_offset::{ReplicationOffset, ReplicationOffsets};
 use serde::de::DeserializeOwned;
@@ -102,6 +104,23 @@ pub struct DfState {
 
     pub(super) replication_strategy: ReplicationStrategy,
 
+    /// Number of times to replicate the readers of individual caches, keyed by the name of the
+    /// cache, overriding the [`ReplicationStrategy`]. Set with `CREATE CACHE ... WITH (replicas =
+    /// n)` or [`Self::set_reader_replicas`]
+    #[serde(default, with = "serde_with::rust::hashmap_as_tuple_list")]
+    pub(super) reader_replicas: HashMap<Relation, usize>,
+
+    /// Dataflow nodes that are no longer used by any query, but have been left in place to keep
+    /// serving reads until the nodes replacing them are committed. See [`Self::reshard_query`]
+    #[serde(default)]
+    pub(super) retired_nodes: HashSet<NodeIndex>,
+
+    /// Tables that were added to (`true`) or dropped from (`false`) replication at runtime with
+    /// `ALTER READYSET`, which take precedence over the tables the replicator is configured to
+    /// replicate
+    #[serde(default, with = "serde_with::rust::hashmap_as_tuple_list")]
+    pub(super) replicated_table_changes: HashMap<Relation, bool>,
+
     /// Controls the persistence mode, and parameters related to persistence.
     ///
     /// Three modes are available:
@@ -176,13 +195,57 @@ impl DfState {
             workers: Default::default(),
             domain_node_index_pairs: Default::default(),
             replication_strategy,
+            reader_replicas: Default::default(),
+            retired_nodes: Default::default(),
+            replicated_table_changes: Default::default(),
         }
     }
 
+    /// Determine the number of times a domain with the given nodes should be replicated, taking
+    /// into account any per-cache override of the number of reader replicas
+    ///
+    /// # Invariants
+    ///
+    /// * Each of the nodes in `domain_nodes` must be present in `self.ingredients`
+    #[allow(clippy::indexing_slicing)] // Invariant
+    pub(super) fn num_domain_replicas(&self, domain_nodes: &[NodeIndex]) -> usize {
+        // Domains containing base tables can't be replicated, regardless of any overrides
+        if !domain_nodes.iter().any(|n| self.ingredients[*n].is_base()) {
+            let reader_replicas = domain_nodes
+                .iter()
+                .filter(|n| self.ingredients[**n].is_reader())
+                .find_map(|n| self.reader_replicas.get(self.ingredients[*n].name()));
+            if let Some(replicas) = reader_replicas {
+                return *replicas;
+            }
+        }
+
+        self.replication_strategy
+            .replicate_domain(&self.ingredients, domain_nodes)
+    }
+
     pub(super) fn schema_replication_offset(&self) -> &Option<ReplicationOffset> {
         &self.schema_replication_offset
     }
 
+    pub(super) fn persistence(&self) -> &PersistenceParameters {
+        &self.persistence
+    }
+
+    /// Change where the base tables of this (not yet running) dataflow state are stored on disk.
+    ///
+    /// Unlike [`Self::with_persistence_options`], this can be called after domains have been
+    /// created, so that a dataflow state restored from a backup can be started from a different
+    /// storage directory or under a different deployment name than the one it was backed up from.
+    pub(super) fn relocate_persistence(
+        &mut self,
+        db_filename_prefix: String,
+        storage_dir: Option<PathBuf>,
+    ) {
+        self.persistence.db_filename_prefix = db_filename_prefix;
+        self.persistence.storage_dir = storage_dir;
+    }
+
     pub(super) fn get_info(&self) -> ReadySetResult<GraphInfo> {
         let mut worker_info = HashMap::new();
         for (di, dh) in self.domains.iter() {
@@ -950,6 +1013,70 @@ impl DfState {
         .await
     }
 
+    /// Returns every worker running a replica of a domain with base tables
+    pub(super) async fn base_table_workers(&self) -> ReadySetResult<HashSet<WorkerIdentifier>> {
+        let domains = self.domains_with_base_tables().await?;
+        Ok(domains
+            .iter()
+            .filter_map(|di| self.domains.get(di))
+            .flat_map(|dh| dh.assignments().map(|(_, worker)| worker.clone()))
+            .collect())
+    }
+
+    /// Write a checkpoint of the persistent state of every base table into `dir`, and return a
+    /// description of each checkpoint that was written.
+    ///
+    /// Each domain writes its checkpoints to `dir` on the host of the worker it runs on; see
+    /// [`Self::base_table_workers`].
+    ///
+    /// Like [`Self::replication_offsets`], returns an error if any base tables are not yet
+    /// initialized.
+    pub(super) async fn checkpoint_base_tables(
+        &self,
+        dir: &Path,
+    ) -> ReadySetResult<Vec<BackupTable>> {
+        let domains = self.domains_with_base_tables().await?;
+        // Base table domains are never replicated, so each checkpoint is only written once
+        self.query_domains::<_, NodeMap<BaseTableState<BaseTableCheckpoint>>>(
+            domains.into_iter().map(|domain| {
+                (
+                    domain,
+                    DomainRequest::Checkpoint {
+                        dir: dir.to_owned(),
+                    },
+                )
+            }),
+        )
+        .try_fold(vec![], |mut acc, (domain, domain_checkpoints)| async move {
+            for replica in domain_checkpoints.into_cells() {
+                for (lni, checkpoint) in replica {
+                    #[allow(clippy::indexing_slicing)] // came from self.domains
+                    let ni = self.domain_nodes[&domain].get(lni).ok_or_else(|| {
+                        internal_err!("Domain {} returned nonexistent local node {}", domain, lni)
+                    })?;
+
+                    #[allow(clippy::indexing_slicing)] // internal invariant
+                    let table_name = self.ingredients[*ni].name();
+                    match checkpoint {
+                        BaseTableState::Initialized(checkpoint) => acc.push(BackupTable {
+                            table: table_name.clone(),
+                            file_name: checkpoint.file_name,
+                            replication_offset: checkpoint.replication_offset,
+                        }),
+                        BaseTableState::Pending => {
+                            internal!(
+                                "Table {} cannot be backed up because it is not ready yet",
+                                table_name.display_unquoted()
+                            );
+                        }
+                    }
+                }
+            }
+            Ok(acc)
+        })
+        .await
+    }
+
     pub(super) fn domain_settings(&self) -> HashMap<DomainIndex, DomainSettings> {
         self.domains
             .iter()
@@ -1549,18 +1676,85 @@ impl DfState {
         // are super entangled with the recipe and the graph.
         let mut new = self.recipe.clone();
 
-        let r = self
-            .migrate(dry_run, changelist.dialect, |mig| {
-                new.activate(mig, changelist)
-            })
-            .await;
+        // The number of replicas of the readers for any new caches has to be known before their
+        // domains are scheduled, which happens during the migration
+        let old_reader_replicas = self.reader_replicas.clone();
+        for change in &changelist.changes {
+            if let Change::CreateCache(CreateCache {
+                name,
+                replicas,
+                shards,
+                ..
+            }) = change
+            {
+                if let Some(shards) = shards {
+                    validate_shards(*shards)?;
+                }
+                if let (Some(name), Some(replicas)) = (name, replicas) {
+                    if *replicas == 0 {
+                        return Err(bad_request_err(
+                            "Caches must have at least 1 reader replica",
+                        ));
+                    }
+                    if !dry_run {
+                        self.reader_replicas.insert(name.clone(), *replicas);
+                    }
+                }
+            }
+        }
+
+        // All the nodes added in a migration are sharded the same way, so caches created with
+        // their own number of shards each get a migration of their own
+        let ChangeList {
+            changes,
+            schema_search_path,
+            dialect,
+        } = changelist;
+        let mut migrations: Vec<(Option<usize>, Vec<Change>)> = vec![];
+        for change in changes {
+            let shards = match &change {
+                Change::CreateCache(CreateCache { shards, .. }) => *shards,
+                _ => None,
+            };
+            match migrations.last_mut() {
+                Some((None, changes)) if shards.is_none() => changes.push(change),
+                _ => migrations.push((shards, vec![change])),
+            }
+        }
+        if migrations.is_empty() {
+            migrations.push((None, vec![]));
+        }
+
+        let r = async {
+            for (shards, changes) in migrations {
+                let changelist = ChangeList {
+                    changes,
+                    schema_search_path: schema_search_path.clone(),
+                    dialect,
+                };
+                self.migrate(dry_run, dialect, |mig| {
+                    if let Some(shards) = shards {
+                        mig.set_sharding(shards);
+                    }
+                    new.activate(mig, changelist)
+                })
+                .await?;
+            }
+            ReadySetResult::Ok(())
+        }
+        .await;
 
         match r {
             Ok(res) => {
                 self.recipe = new;
+                // Forget the number of reader replicas for any caches that no longer exist
+                let cache_names = self.recipe.cache_names().collect::<HashSet<_>>();
+                self.reader_replicas
+                    .retain(|name, _| cache_names.contains(name));
                 Ok(res)
             }
             Err(e) => {
+                self.reader_replicas = old_reader_replicas;
                 debug!(
                     error = %e,
                     "failed to apply recipe. Will retry periodically up to max_processing_minutes."
@@ -1624,6 +1818,134 @@ impl DfState {
         Ok(1)
     }
 
+    /// Rebuild all the dataflow nodes for the cached query with the given name (or alias),
+    /// sharded `shards` ways rather than by the sharding of the rest of the deployment, and return
+    /// the names of all the caches that were rebuilt.
+    ///
+    /// Any nodes the query shares with other caches are resharded too, so those caches are rebuilt
+    /// along with it. The new nodes are built (and, if they're fully materialized, have state
+    /// replayed into them) alongside the old ones, which are left in place to keep serving reads.
+    /// Once this state has been committed, all new view lookups for the rebuilt caches go to the
+    /// new readers, and the old nodes can be removed with [`Self::drop_retired_nodes`].
+    pub(super) async fn reshard_query(
+        &mut self,
+        query_name: &Relation,
+        shards: usize,
+        dialect: Dialect,
+    ) -> ReadySetResult<Vec<Relation>> {
+        validate_shards(shards)?;
+
+        let mut new = self.recipe.clone();
+        let rebuilt = self
+            .migrate(false, dialect, |mig| {
+                mig.set_sharding(shards);
+                new.rebuild_query(mig, query_name)
+            })
+            .await?;
+        self.recipe = new;
+        self.retired_nodes.extend(rebuilt.old_nodes);
+
+        Ok(rebuilt.caches)
+    }
+
+    /// Remove all the dataflow nodes that were left in place to serve reads while the queries
+    /// using them were being rebuilt by [`Self::reshard_query`].
+    pub(super) async fn drop_retired_nodes(&mut self, dialect: Dialect) -> ReadySetResult<()> {
+        if self.retired_nodes.is_empty() {
+            return Ok(());
+        }
+
+        let nodes = std::mem::take(&mut self.retired_nodes);
+        self.migrate(false, dialect, |mig| {
+            Recipe::drop_nodes(mig, &nodes);
+            Ok(())
+        })
+        .await
+    }
+
+    /// Change the number of times the reader for the cached query with the given name (or alias)
+    /// is replicated, overriding the replication strategy of the deployment.
+    ///
+    /// Only the replicas of the reader's domain are changed: new replicas are placed onto workers
+    /// (and filled on demand, like any other partial reader), or the highest-indexed replicas are
+    /// killed, while the remaining replicas keep serving reads throughout.
+    pub(super) async fn set_reader_replicas(
+        &mut self,
+        query_name: &Relation,
+        replicas: usize,
+    ) -> ReadySetResult<()> {
+        if replicas == 0 {
+            return Err(bad_request_err(
+                "Caches must have at least 1 reader replica",
+            ));
+        }
+        let name = self
+            .recipe
+            .resolve_alias(query_name)
+            .ok_or_else(|| ReadySetError::ViewNotFound(query_name.display_unquoted().to_string()))?
+            .clone();
+        let reader = self
+            .recipe
+            .node_addr_for(&name)
+            .ok()
+            .and_then(|ni| self.find_reader_for(ni, &name, &None))
+            .ok_or_else(|| ReadySetError::ViewNotFound(name.display_unquoted().to_string()))?;
+        #[allow(clippy::indexing_slicing)] // `find_reader_for` returns valid indices
+        let domain = self.ingredients[reader].domain();
+        let dh = self
+            .domains
+            .get(&domain)
+            .ok_or_else(|| ReadySetError::UnknownDomain {
+                domain_index: domain.index(),
+            })?;
+        let old_replicas = dh.num_replicas();
+        let removed_replicas = dh
+            .assignments()
+            .map(|(addr, _)| addr)
+            .filter(|addr| addr.replica >= replicas)
+            .collect::<Vec<_>>();
+
+        self.reader_replicas.insert(name, replicas);
+        if replicas == old_replicas {
+            return Ok(());
+        }
+
+        self.kill_replicas(removed_replicas).await?;
+        if let Some(dh) = self.domains.get_mut(&domain) {
+            dh.set_num_replicas(replicas);
+        }
+
+        let nodes = self
+            .domain_nodes
+            .get(&domain)
+            .ok_or_else(|| ReadySetError::UnknownDomain {
+                domain_index: domain.index(),
+            })?
+            .values()
+            .copied()
+            .collect::<HashSet<_>>();
+        let dmp = if replicas > old_replicas {
+            // The new replicas are unplaced, so they can be placed the same way as replicas that
+            // are being recovered after failing
+            self.plan_recovery(&HashMap::from([(domain, nodes)]))
+                .await?
+        } else {
+            // Only the domains sending to the reader need to stop sending to the killed replicas
+            let mut dmp =
+                DomainMigrationPlan::new(DomainMigrationMode::Recover, self.domain_settings());
+            routing::connect(
+                &self.ingredients,
+                &mut dmp,
+                &nodes
+                    .into_iter()
+                    .filter(|ni| self.ingredients[*ni].is_ingress())
+                    .collect(),
+            )?;
+            dmp
+        };
+        dmp.apply(self).await
+    }
+
     pub(super) async fn remove_all_queries(&mut self) -> ReadySetResult<()> {
         let changes = self
             .recipe
@@ -1657,12 +1979,17 @@ impl DfState {
         res
     }
 
-    /// Return a set of domain indexes that are logically downstream of the given domain in the
-    /// graph
+    /// Return the set of domains downstream of the given domain which need to be recreated when
+    /// it's recovered after failing, because they contain fully materialized nodes downstream of
+    /// it.
+    ///
+    /// All other domains downstream of a failed domain can keep running (and serving reads) while
+    /// it's being recovered, since the state that depends on it is all partial, and can be cleared
+    /// and refilled once it's been recovered (see [`Self::plan_recovery`]).
     ///
     /// NOTE: this is asymptotically kinda crappy right now, but ideally this is needed rarely
     /// enough and our graphs are small enough in practice that that isn't a huge deal
-    pub(super) fn downstream_domains(
+    pub(super) fn downstream_domains_to_recreate(
         &self,
         domain: DomainIndex,
     ) -> ReadySetResult<HashSet<DomainIndex>> {
@@ -1677,7 +2004,11 @@ impl DfState {
             let mut bfs = petgraph::visit::Bfs::new(&self.ingredients, *ni);
             while let Some(ni) = bfs.next(&self.ingredients) {
                 let downstream_domain = self.ingredients[ni].domain();
-                if downstream_domain != domain {
+                if downstream_domain != domain
+                    && self
+                        .materializations
+                        .is_fully_materialized(&self.ingredients, ni)
+                {
                     res.insert(downstream_domain);
                 }
             }
@@ -1686,25 +2017,80 @@ impl DfState {
         Ok(res)
     }
 
-    /// Send requests to whatever workers are running the given domain replicas to kill those
-    /// replicas, and remove them from runtime state.
+    /// Returns all the nodes downstream of `nodes` which are in running domains other than
+    /// `recovering`, in topological order.
+    fn running_descendants(
+        &self,
+        nodes: &HashSet<NodeIndex>,
+        recovering: &HashSet<DomainIndex>,
+    ) -> Vec<NodeIndex> {
+        let mut descendants = HashSet::new();
+        let mut res = vec![];
+        let mut topo = petgraph::visit::Topo::new(&self.ingredients);
+        while let Some(ni) = topo.next(&self.ingredients) {
+            if nodes.contains(&ni) || self.ingredients[ni].is_dropped() {
+                continue;
+            }
+            if !self
+                .ingredients
+                .neighbors_directed(ni, Direction::Incoming)
+                .any(|parent| nodes.contains(&parent) || descendants.contains(&parent))
+            {
+                continue;
+            }
+            descendants.insert(ni);
+
+            let domain = self.ingredients[ni].domain();
+            if !recovering.contains(&domain)
+                && self
+                    .domains
+                    .get(&domain)
+                    .map_or(false, |dh| dh.all_replicas_placed())
+            {
+                res.push(ni);
+            }
+        }
+        res
+    }
+
+    /// Kill all the replicas of the given domains, and remove them from runtime state.
     pub(super) async fn kill_domains<I>(&mut self, domains: I) -> ReadySetResult<()>
     where
         I: IntoIterator<Item = DomainIndex>,
     {
-        let mut workers_to_replicas: HashMap<_, Vec1<_>> = HashMap::new();
+        let mut replicas = vec![];
         for di in domains {
             let Some(dh) = self.domains.get(&di) else {
                 debug!(domain = %di, "domain not running, not killing");
                 continue;
             };
+            replicas.extend(dh.assignments().map(|(addr, _)| addr));
+        }
 
-            for (addr, wi) in dh.assignments() {
-                workers_to_replicas
-                    .entry(wi.clone())
-                    .and_modify(|v| v.push(addr))
-                    .or_insert_with(|| vec1![addr]);
-            }
+        self.kill_replicas(replicas).await
+    }
+
+    /// Send requests to whatever workers are running the given domain replicas to kill those
+    /// replicas, and remove them from runtime state.
+    async fn kill_replicas<I>(&mut self, replicas: I) -> ReadySetResult<()>
+    where
+        I: IntoIterator<Item = ReplicaAddress>,
+    {
+        let mut workers_to_replicas: HashMap<_, Vec1<_>> = HashMap::new();
+        for addr in replicas {
+            let Some(wi) = self
+                .domains
+                .get(&addr.domain_index)
+                .and_then(|dh| dh.assignment(addr.shard, addr.replica))
+            else {
+                debug!(replica = %addr, "replica not running, not killing");
+                continue;
+            };
+
+            workers_to_replicas
+                .entry(wi.clone())
+                .and_modify(|v| v.push(addr))
+                .or_insert_with(|| vec1![addr]);
         }
 
         let mut futs = FuturesUnordered::new();
@@ -1756,6 +2142,7 @@ impl DfState {
             .iter()
             .map(|(idx, nm)| (*idx, nm.iter().copied().collect::<Vec<_>>()))
             .collect::<HashMap<_, _>>();
+        let recovered_domains = domain_nodes.keys().copied().collect::<HashSet<_>>();
         let mut new = HashSet::new();
         {
             let mut scheduler = Scheduler::new(self, &None)?;
@@ -1794,12 +2181,43 @@ impl DfState {
 
         routing::connect(&self.ingredients, &mut dmp, &new)?;
 
+        // Domains downstream of the ones we're recovering which are still running keep their
+        // state, but need to be reconnected to the recovered domains
+        let running_descendants = self.running_descendants(&new, &recovered_domains);
+        if !running_descendants.is_empty() {
+            info!(
+                num_nodes = running_descendants.len(),
+                "Reconnecting running nodes downstream of recovered domains"
+            );
+            let mut reconnect = dmp.new_like();
+            routing::connect(
+                &self.ingredients,
+                &mut reconnect,
+                &running_descendants
+                    .iter()
+                    .copied()
+                    .filter(|ni| self.ingredients[*ni].is_ingress())
+                    .collect(),
+            )?;
+            // Only the recovered domains need to be told about their egresses' new targets
+            dmp.extend_messages_where(reconnect, |req| recovered_domains.contains(&req.domain));
+        }
+
         self.materializations
             .extend(&mut self.ingredients, &new, &dmp)?;
 
         self.materializations
             .commit(&mut self.ingredients, &new, &mut dmp)?;
 
+        if !running_descendants.is_empty() {
+            self.materializations.reconnect_descendants(
+                &self.ingredients,
+                &running_descendants,
+                &recovered_domains,
+                &mut dmp,
+            )?;
+        }
+
         Ok(dmp)
     }
 
@@ -1827,6 +2245,16 @@ impl DfState {
     }
 }
 
+/// Check that a cache can be sharded `shards` ways
+fn validate_shards(shards: usize) -> ReadySetResult<()> {
+    if shards < 2 {
+        return Err(bad_request_err(format!(
+            "Cannot shard a cache into {shards} shards; at least 2 are required"
+        )));
+    }
+    Ok(())
+}
+
 /// This structure acts as a wrapper for a [`DfStateReader`] in order to guarantee
 /// thread-safe access (read and writes) to ReadySet's dataflow state.
 ///
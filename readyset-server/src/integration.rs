@@ -1457,6 +1457,82 @@ async fn it_recovers_persisted_bases_with_volume_id() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn it_restores_backups() {
+    let dir = tempfile::tempdir().unwrap();
+    let backup_dir = dir.path().join("backup");
+    let persistence_params = |name: &str| {
+        PersistenceParameters::new(
+            DurabilityMode::Permanent,
+            Some(name.into()),
+            1,
+            Some(dir.path().join(name)),
+            0,
+        )
+    };
+
+    {
+        let authority = Arc::new(Authority::from(LocalAuthority::new()));
+        let mut g = Builder::for_tests();
+        g.set_persistence(persistence_params("it_restores_backups_source"));
+        let (mut g, shutdown_tx) = g.start(authority).await.unwrap();
+        g.backend_ready().await;
+
+        let sql = "
+            CREATE TABLE Car (id int, price int, PRIMARY KEY(id));
+            CREATE CACHE CarPrice FROM SELECT price FROM Car WHERE id = ?;
+        ";
+        g.extend_recipe(ChangeList::from_str(sql, Dialect::DEFAULT_MYSQL).unwrap())
+            .await
+            .unwrap();
+        let mut mutator = g.table("Car").await.unwrap();
+        for i in 1..10 {
+            let price = i * 10;
+            mutator.insert(vec![i.into(), price.into()]).await.unwrap();
+        }
+        // Let writes propagate:
+        sleep().await;
+
+        let manifest = g.backup(backup_dir.clone()).await.unwrap();
+        assert_eq!(manifest.tables.len(), 1);
+        shutdown_tx.shutdown().await;
+    }
+
+    // Restore into a fresh authority, under a different deployment name
+    let authority = Arc::new(Authority::from(LocalAuthority::new()));
+    let target = persistence_params("it_restores_backups_target");
+    crate::restore_backup(
+        &backup_dir,
+        &authority,
+        &target.db_filename_prefix,
+        target.storage_dir.clone(),
+    )
+    .await
+    .unwrap();
+
+    let mut g = Builder::for_tests();
+    g.set_persistence(target);
+    let (mut g, shutdown_tx) = g.start(authority).await.unwrap();
+    g.backend_ready().await;
+    {
+        let mut getter = g
+            .view("CarPrice")
+            .await
+            .unwrap()
+            .into_reader_handle()
+            .unwrap();
+
+        for i in 1..10 {
+            let price = i * 10;
+            let result = getter.lookup(&[i.into()], true).await.unwrap().into_vec();
+            assert_eq!(result.len(), 1);
+            assert_eq!(result[0][0], price.into());
+        }
+    }
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn it_doesnt_recover_persisted_bases_with_wrong_volume_id() {
     let authority = Arc::new(Authority::from(LocalAuthority::new()));
@@ -3822,6 +3898,117 @@ async fn remove_query() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn reshard_query() {
+    readyset_tracing::init_test_logging();
+    let (mut g, shutdown_tx) = start_simple("reshard_query").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, val int, PRIMARY KEY(id));
+             CREATE CACHE q FROM SELECT id, val FROM t WHERE val = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    for i in 0..10 {
+        t.insert(vec![i.into(), (i % 2).into()]).await.unwrap();
+    }
+    sleep().await;
+
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    assert_eq!(q.num_shards(), DEFAULT_SHARDING);
+    assert_eq!(
+        q.lookup(&[1.into()], true).await.unwrap().into_vec().len(),
+        5
+    );
+
+    g.reshard_query(&"q".into(), 4, Dialect::DEFAULT_MYSQL)
+        .await
+        .unwrap();
+
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    assert_eq!(q.num_shards(), 4);
+    assert_eq!(
+        q.lookup(&[1.into()], true).await.unwrap().into_vec().len(),
+        5
+    );
+
+    // Writes made after resharding should make it to the new shards
+    t.insert(vec![10.into(), 1.into()]).await.unwrap();
+    sleep().await;
+    assert_eq!(
+        q.lookup(&[1.into()], true).await.unwrap().into_vec().len(),
+        6
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reshard_query_with_shared_nodes_unsharded() {
+    readyset_tracing::init_test_logging();
+    let (mut g, shutdown_tx) = start_simple_unsharded("reshard_query_with_shared_nodes").await;
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, val int, PRIMARY KEY(id));
+             CREATE VIEW v AS SELECT id, val FROM t WHERE id > 0;
+             CREATE CACHE q FROM SELECT id, val FROM v WHERE val = ?;
+             CREATE CACHE q2 FROM SELECT id, val FROM v WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    for i in 0..10 {
+        t.insert(vec![i.into(), (i % 2).into()]).await.unwrap();
+    }
+    sleep().await;
+
+    g.reshard_query(&"q".into(), 3, Dialect::DEFAULT_MYSQL)
+        .await
+        .unwrap();
+
+    // q2 shares the nodes for the view with q, so it gets resharded along with it
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+    let mut q2 = g.view("q2").await.unwrap().into_reader_handle().unwrap();
+    assert_eq!(q.num_shards(), 3);
+    assert_eq!(q2.num_shards(), 3);
+    assert_eq!(
+        q.lookup(&[0.into()], true).await.unwrap().into_vec().len(),
+        4
+    );
+    assert_eq!(
+        q2.lookup(&[3.into()], true).await.unwrap().into_vec(),
+        vec![vec![DfValue::from(3), DfValue::from(1)]]
+    );
+
+    // Caches can also be created with their own number of shards
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE CACHE q3 WITH (shards = 2) FROM SELECT id, val FROM t WHERE id = ?;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+    let mut q3 = g.view("q3").await.unwrap().into_reader_handle().unwrap();
+    assert_eq!(q3.num_shards(), 2);
+    assert_eq!(
+        q3.lookup(&[4.into()], true).await.unwrap().into_vec(),
+        vec![vec![DfValue::from(4), DfValue::from(0)]]
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
 macro_rules! get {
     ($private:ident, $public:ident, $uid:expr, $aid:expr) => {{
         // combine private and public results
@@ -5371,6 +5558,193 @@ async fn aggregate_missing_columns() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn max_with_deletes_past_buffer() {
+    readyset_tracing::init_test_logging();
+
+    const EXTREMUM_BUFFER_SIZE: usize = 4;
+    let mut builder = Builder::for_tests();
+    builder.set_sharding(None);
+    builder.set_persistence(get_persistence_params("max_with_deletes_past_buffer"));
+    builder.set_extremum_buffer_size(Some(EXTREMUM_BUFFER_SIZE));
+    let (mut g, shutdown_tx) = builder.start_local().await.unwrap();
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, cat int, price int, PRIMARY KEY(id));
+             CREATE CACHE q FROM SELECT cat, max(price) FROM t WHERE cat = ? GROUP BY cat;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+
+    // More rows than the number of candidates buffered for each group, so that deleting the
+    // largest values one by one eventually has to recompute the group
+    let num_rows = EXTREMUM_BUFFER_SIZE as i32 * 2;
+    t.insert_many(
+        (0..num_rows).map(|i| vec![DfValue::from(i), DfValue::from(1), DfValue::from(i)]),
+    )
+    .await
+    .unwrap();
+    sleep().await;
+
+    for id in (1..num_rows).rev() {
+        assert_eq!(
+            q.lookup(&[1.into()], true).await.unwrap().into_vec(),
+            vec![vec![DfValue::from(1), DfValue::from(id)]]
+        );
+        t.delete(vec![id.into()]).await.unwrap();
+        sleep().await;
+    }
+
+    assert_eq!(
+        q.lookup(&[1.into()], true).await.unwrap().into_vec(),
+        vec![vec![DfValue::from(1), DfValue::from(0)]]
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn approximate_aggregates() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("approximate_aggregates").await;
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, cat int, val int, PRIMARY KEY(id));
+             CREATE CACHE q FROM
+                SELECT cat, approx_count_distinct(val), approx_percentile(val, 0.5)
+                FROM t WHERE cat = ? GROUP BY cat;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+
+    t.insert_many(
+        (0..100).map(|i| vec![DfValue::from(i), DfValue::from(1), DfValue::from(i % 10)]),
+    )
+    .await
+    .unwrap();
+    sleep().await;
+
+    let check = |rows: Vec<Vec<DfValue>>, distinct: i64, median: (f64, f64)| {
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0], DfValue::from(1));
+        assert_eq!(rows[0][1], DfValue::from(distinct));
+        let estimate = f64::try_from(&rows[0][2]).unwrap();
+        assert!(
+            (median.0..=median.1).contains(&estimate),
+            "median estimate was {estimate}"
+        );
+    };
+
+    check(
+        q.lookup(&[1.into()], true).await.unwrap().into_vec(),
+        10,
+        (4.0, 5.0),
+    );
+
+    // Deleting rows can't be applied to the sketches directly, so the groups are rebuilt from
+    // the base table
+    for id in (0..100).filter(|i| i % 10 >= 8) {
+        t.delete(vec![DfValue::from(id)]).await.unwrap();
+    }
+    sleep().await;
+
+    check(
+        q.lookup(&[1.into()], true).await.unwrap().into_vec(),
+        8,
+        (3.0, 4.0),
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn group_by_with_rollup() {
+    let (mut g, shutdown_tx) = start_simple_unsharded("group_by_with_rollup").await;
+
+    g.extend_recipe(
+        ChangeList::from_str(
+            "CREATE TABLE t (id int, region int, city int, PRIMARY KEY(id));
+             CREATE CACHE q FROM
+                SELECT region, city, count(*), grouping(region, city)
+                FROM t GROUP BY region, city WITH ROLLUP;",
+            Dialect::DEFAULT_MYSQL,
+        )
+        .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    let mut t = g.table("t").await.unwrap();
+    let mut q = g.view("q").await.unwrap().into_reader_handle().unwrap();
+
+    t.insert_many(vec![
+        vec![DfValue::from(1), DfValue::from(1), DfValue::from(10)],
+        vec![DfValue::from(2), DfValue::from(1), DfValue::from(10)],
+        vec![DfValue::from(3), DfValue::from(1), DfValue::from(20)],
+        vec![DfValue::from(4), DfValue::from(2), DfValue::from(30)],
+    ])
+    .await
+    .unwrap();
+    sleep().await;
+
+    let row = |region: Option<i32>, city: Option<i32>, count: i32, grouping: i32| {
+        vec![
+            region.map_or(DfValue::None, DfValue::from),
+            city.map_or(DfValue::None, DfValue::from),
+            DfValue::from(count),
+            DfValue::from(grouping),
+        ]
+    };
+    let sorted = |mut rows: Vec<Vec<DfValue>>| {
+        rows.sort();
+        rows
+    };
+
+    let mut expected = vec![
+        row(Some(1), Some(10), 2, 0),
+        row(Some(1), Some(20), 1, 0),
+        row(Some(2), Some(30), 1, 0),
+        row(Some(1), None, 3, 1),
+        row(Some(2), None, 1, 1),
+        row(None, None, 4, 3),
+    ];
+    expected.sort();
+    assert_eq!(
+        sorted(q.lookup(&[0.into()], true).await.unwrap().into_vec()),
+        expected
+    );
+
+    t.delete(vec![DfValue::from(4)]).await.unwrap();
+    sleep().await;
+
+    let mut expected = vec![
+        row(Some(1), Some(10), 2, 0),
+        row(Some(1), Some(20), 1, 0),
+        row(Some(1), None, 3, 1),
+        row(None, None, 3, 3),
+    ];
+    expected.sort();
+    assert_eq!(
+        sorted(q.lookup(&[0.into()], true).await.unwrap().into_vec()),
+        expected
+    );
+
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn post_join_filter() {
     let (mut g, shutdown_tx) = start_simple_unsharded("post_join_filter").await;
@@ -8839,6 +9213,8 @@ async fn multiple_simultaneous_migrations() {
                         .unwrap()
                 ),
                 always: false,
+                replicas: None,
+                shards: None,
             }),
             Dialect::DEFAULT_MYSQL
         )),
@@ -8850,6 +9226,8 @@ async fn multiple_simultaneous_migrations() {
                         .unwrap()
                 ),
                 always: false,
+                replicas: None,
+                shards: None,
             }),
             Dialect::DEFAULT_MYSQL
         ))
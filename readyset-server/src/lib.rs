@@ -428,12 +428,13 @@ pub enum ReuseConfigType {
     Full,
 }
 
+pub use controller::backup::restore as restore_backup;
 use controller::migrate::materialization;
-pub use controller::migrate::materialization::FrontierStrategy;
+pub use controller::migrate::materialization::{FrontierStrategy, PartialStateSpill};
 pub use controller::replication::{ReplicationOptions, ReplicationStrategy};
 use controller::sql;
 use database_utils::UpstreamConfig;
-pub use dataflow::{DurabilityMode, PersistenceParameters};
+pub use dataflow::{DurabilityMode, EvictionKind, PersistenceParameters};
 pub use petgraph::graph::NodeIndex;
 pub use readyset_client::consensus::{Authority, LocalAuthority};
 pub use readyset_client::*;
@@ -519,6 +520,7 @@ impl Default for Config {
                 table_request_timeout: Duration::from_millis(1800000),
                 eviction_kind: dataflow::EvictionKind::Random,
                 verbose_metrics: false,
+                compact_reader_rows: false,
             },
             persistence: Default::default(),
             min_workers: 1,
@@ -584,6 +586,44 @@ pub struct WorkerOptions {
     #[arg(long = "eviction-policy", default_value_t = dataflow::EvictionKind::LRU, hide = true)]
     pub eviction_kind: dataflow::EvictionKind,
 
+    /// Which partially materialized internal nodes should spill keys that haven't been read
+    /// recently to disk rather than evicting them when the memory limit is reached. `joins`
+    /// spills only the state of join nodes, whose upqueries are the most expensive to repeat.
+    #[arg(
+        long,
+        value_enum,
+        default_value = "none",
+        env = "PARTIAL_STATE_SPILL",
+        hide = true
+    )]
+    pub partial_state_spill: PartialStateSpill,
+
+    /// Maximum number of bytes of partial state each node may spill to disk with
+    /// `--partial-state-spill`, past which spilled keys are evicted. (0 = unlimited)
+    #[arg(
+        long,
+        default_value = "0",
+        env = "PARTIAL_STATE_DISK_LIMIT",
+        hide = true
+    )]
+    pub partial_state_disk_limit: u64,
+
+    /// Number of bytes of rows a node's partial state has to hold in memory before it spills keys
+    /// to disk with `--partial-state-spill`. Nodes with less state evict keys as usual.
+    #[arg(
+        long,
+        default_value = "0",
+        env = "PARTIAL_STATE_SPILL_MIN_BYTES",
+        hide = true
+    )]
+    pub partial_state_spill_min_bytes: u64,
+
+    /// Store the rows of cached queries in a compact binary encoding rather than as individual
+    /// values. This can significantly reduce the memory used by caches, at the cost of some
+    /// extra latency decoding rows on every read.
+    #[arg(long, env = "COMPACT_READER_ROWS", hide = true)]
+    pub compact_reader_rows: bool,
+
     /// Disable partial
     #[arg(long = "nopartial", hide = true)]
     pub no_partial: bool,
@@ -635,6 +675,12 @@ pub struct WorkerOptions {
     #[arg(long, env = "EXPERIMENTAL_POST_LOOKUP_SUPPORT", hide = true)]
     pub enable_experimental_post_lookup: bool,
 
+    /// Number of the most extreme values that MIN and MAX operators keep for each group, so that
+    /// deleting the current minimum or maximum of a group can usually be handled without
+    /// recomputing the whole group. Defaults to 0, which disables buffering.
+    #[arg(long, env = "EXTREMUM_BUFFER_SIZE", hide = true)]
+    pub extremum_buffer_size: Option<usize>,
+
     /// Directory in which to store replicated table data. If not specified, defaults to the
     /// current working directory.
     #[arg(long, env = "STORAGE_DIR", conflicts_with = "db_dir")]
@@ -589,7 +589,7 @@ fn get_reader_from_cache<'a>(
 
 #[cfg(test)]
 mod readreply {
-    use readyset_client::results::SharedResults;
+    use readyset_client::results::{SharedResults, SharedRows};
     use readyset_client::{LookupResult, ReadReply, ReadReplyStats, Tagged};
     use readyset_data::DfValue;
     use readyset_errors::ReadySetError;
@@ -671,11 +671,11 @@ mod readreply {
     {
         data.into_iter()
             .map(|rows| {
-                triomphe::Arc::new(
+                SharedRows::Rows(triomphe::Arc::new(
                     rows.into_iter()
                         .map(|row| row.into_iter().collect())
                         .collect(),
-                )
+                ))
             })
             .collect()
     }
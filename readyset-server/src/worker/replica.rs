@@ -9,11 +9,14 @@ use anyhow::{self, Context as AnyhowContext};
 use dataflow::payload::{MaterializedState, SourceChannelIdentifier};
 use dataflow::prelude::Executor;
 use dataflow::{Domain, DomainReceiver, DomainRequest, DualTcpStream, Packet};
+use failpoint_macros::set_failpoint;
 use futures_util::sink::{Sink, SinkExt};
 use futures_util::stream::StreamExt;
 use futures_util::FutureExt;
 use readyset_client::internal::ReplicaAddress;
-use readyset_client::{KeyComparison, PacketData, PacketPayload, Tagged, CONNECTION_FROM_BASE};
+use readyset_client::{
+    failpoints, KeyComparison, PacketData, PacketPayload, Tagged, CONNECTION_FROM_BASE,
+};
 use readyset_errors::ReadySetResult;
 use strawpoll::Strawpoll;
 use time::Duration;
@@ -448,6 +451,11 @@ impl Replica {
                                 _ => None,
                             };
 
+                            set_failpoint!(
+                                failpoints::DOMAIN_PROCESS_PACKET,
+                                domain.contains_only_internal_nodes(),
+                                |_| Err(anyhow::anyhow!("injected failure processing packet"))
+                            );
                             span.in_scope(|| domain.handle_packet(packet, out))?;
 
                             if let Some((tag, conn)) = ack {
@@ -41,8 +41,7 @@ impl NormalizeTopKWithAggregate for SelectStatement {
                         {
                             // ...must either appear in the group by clause...
                             let in_group_by_clause = group_by
-                                .fields
-                                .iter()
+                                .all_fields()
                                 .any(|group_by_field| order_field == group_by_field);
 
                             // ...or reference the result of an aggregate...
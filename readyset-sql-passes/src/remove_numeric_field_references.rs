@@ -32,7 +32,7 @@ impl RemoveNumericFieldReferences for SelectStatement {
         };
 
         if let Some(gb) = &mut self.group_by {
-            for field in &mut gb.fields {
+            for field in gb.all_fields_mut() {
                 if let FieldReference::Numeric(n) = field {
                     *field = FieldReference::Expr(lookup_field(*n as _)?);
                 }
@@ -80,7 +80,8 @@ mod tests {
         assert_eq!(
             result.group_by,
             Some(GroupByClause {
-                fields: vec![FieldReference::Expr(Expr::Column("id".into()))]
+                fields: vec![FieldReference::Expr(Expr::Column("id".into()))],
+                grouping_sets: None,
             })
         )
     }
@@ -0,0 +1,95 @@
+//! Creates and restores backups of the state of a ReadySet deployment.
+//!
+//! A backup contains a checkpoint of every base table along with the replication offset it was
+//! taken at, the controller state, and the list of caches, so a deployment restored from a backup
+//! can resume replication from where the backup left off rather than re-snapshotting every table.
+//!
+//! # Example
+//!
+//! Create a backup of a running deployment:
+//!
+//! ```bash
+//! cargo run --bin backup -- --deployment readyset create /backups/readyset-2023-01-01
+//! ```
+//!
+//! Restore it into a new deployment, which must not be running yet:
+//!
+//! ```bash
+//! cargo run --bin backup -- --deployment readyset restore /backups/readyset-2023-01-01 \
+//!     --storage-dir /var/lib/readyset
+//! ```
+//!
+//! When replicating from PostgreSQL, restore the backup under the same deployment name it was
+//! taken from, so that the restored deployment reuses the existing replication slot. In all cases
+//! the upstream database must still retain its replication log from the offsets recorded in the
+//! backup.
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+use readyset_client::consensus::AuthorityType;
+use readyset_client::ReadySetHandle;
+
+#[derive(Parser)]
+#[command(name = "backup")]
+struct Backup {
+    #[arg(short, long, env("AUTHORITY_ADDRESS"), default_value("127.0.0.1:8500"))]
+    authority_address: String,
+
+    #[arg(long, env("AUTHORITY"), default_value("consul"), value_parser = ["consul", "standalone"])]
+    authority: AuthorityType,
+
+    #[arg(short, long, env("DEPLOYMENT"))]
+    deployment: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a backup of a running deployment.
+    Create {
+        /// The directory to write the backup to, on the host running the ReadySet server. Must
+        /// either not exist or be empty.
+        dir: PathBuf,
+    },
+    /// Restore a backup into a deployment that is not currently running.
+    Restore {
+        /// The directory containing the backup.
+        dir: PathBuf,
+
+        /// The directory the restored deployment's ReadySet server will store its base tables in.
+        /// Defaults to the current working directory, like the server's `--storage-dir` option.
+        #[arg(long, env = "STORAGE_DIR")]
+        storage_dir: Option<PathBuf>,
+    },
+}
+
+impl Backup {
+    pub async fn run(self) -> anyhow::Result<()> {
+        let authority = self
+            .authority
+            .to_authority(&self.authority_address, &self.deployment);
+
+        let manifest = match self.command {
+            Command::Create { dir } => {
+                let mut handle: ReadySetHandle = ReadySetHandle::new(authority).await;
+                handle.ready().await?;
+                handle.backup(dir).await?
+            }
+            Command::Restore { dir, storage_dir } => {
+                readyset_server::restore_backup(&dir, &authority, &self.deployment, storage_dir)
+                    .await?
+            }
+        };
+
+        println!("{}", serde_json::to_string_pretty(&manifest)?);
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let backup = Backup::parse();
+    backup.run().await
+}
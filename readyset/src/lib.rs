@@ -29,11 +29,17 @@ use metrics_exporter_prometheus::PrometheusBuilder;
 use nom_sql::{Relation, SqlIdentifier};
 use readyset_adapter::backend::noria_connector::{NoriaConnector, ReadBehavior};
 use readyset_adapter::backend::MigrationMode;
+use readyset_adapter::cache_staleness::CacheStaleness;
+use readyset_adapter::http_cache_reads::{HttpAuthentication, HttpCacheReads};
 use readyset_adapter::http_router::NoriaAdapterHttpRouter;
 use readyset_adapter::metrics_handle::MetricsHandle;
 use readyset_adapter::migration_handler::MigrationHandler;
+use readyset_adapter::privileges::TablePrivileges;
 use readyset_adapter::proxied_queries_reporter::ProxiedQueriesReporter;
 use readyset_adapter::query_status_cache::{MigrationStyle, QueryStatusCache};
+use readyset_adapter::upstream_auth::UpstreamAuthCache;
+use readyset_adapter::upstream_pool::UpstreamPool;
+use readyset_adapter::upstream_replicas::{ReplicaRoutingUpstream, UpstreamReplicas};
 use readyset_adapter::views_synchronizer::ViewsSynchronizer;
 use readyset_adapter::{
     Backend, BackendBuilder, DeploymentMode, QueryHandler, ReadySetStatusReporter, UpstreamDatabase,
@@ -75,7 +81,7 @@ const UPSTREAM_CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
 const UPSTREAM_CONNECTION_RETRY_INTERVAL: Duration = Duration::from_secs(1);
 
 pub trait ConnectionHandler {
-    type UpstreamDatabase: UpstreamDatabase;
+    type UpstreamDatabase: ReplicaRoutingUpstream;
     type Handler: QueryHandler;
 
     fn process_connection(
@@ -174,12 +180,12 @@ pub struct Options {
     deployment: String,
 
     /// Database engine protocol to emulate. If omitted, will be inferred from the
-    /// `upstream-db-url`
+    /// `upstream-db-url` or `cdc-source-type`
     #[arg(
         long,
         env = "DATABASE_TYPE",
         value_enum,
-        required_unless_present("upstream_db_url"),
+        required_unless_present_any(["upstream_db_url", "cdc_source"]),
         hide = true
     )]
     pub database_type: Option<DatabaseType>,
@@ -288,6 +294,142 @@ pub struct Options {
     #[arg(long, env = "ALLOWED_PASSWORD", short = 'p', hide = true)]
     password: Option<RedactedString>,
 
+    /// Authenticate database connections by connecting to the upstream database with the
+    /// credentials provided by the client, instead of checking them against --username and
+    /// --password. Each connection's proxied queries then run in the upstream database as the
+    /// client's own user.
+    ///
+    /// Clients have to send their password in cleartext for it to be checked against the upstream
+    /// database, so clients are refused unless they connect with TLS (which is only supported for
+    /// PostgreSQL), or --allow-unencrypted-upstream-authentication is set.
+    #[arg(
+        long,
+        env = "UPSTREAM_AUTHENTICATION",
+        conflicts_with = "allow_unauthenticated_connections"
+    )]
+    upstream_authentication: bool,
+
+    /// How long, in seconds, to remember credentials that were successfully checked against the
+    /// upstream database with --upstream-authentication. Connections using remembered credentials
+    /// are accepted without first connecting to the upstream database. If set to 0, every
+    /// connection is checked against the upstream database.
+    #[arg(
+        long,
+        env = "UPSTREAM_AUTHENTICATION_CACHE_TTL_SECONDS",
+        default_value = "300"
+    )]
+    upstream_authentication_cache_ttl_seconds: u64,
+
+    /// Allow clients to be authenticated with --upstream-authentication over connections that
+    /// don't use TLS, which sends their passwords over the network in cleartext. Only use this on
+    /// trusted networks.
+    #[arg(
+        long,
+        env = "ALLOW_UNENCRYPTED_UPSTREAM_AUTHENTICATION",
+        requires = "upstream_authentication"
+    )]
+    allow_unencrypted_upstream_authentication: bool,
+
+    /// Only serve reads from caches to clients whose upstream user may `SELECT` from every table
+    /// the query reads from. Reads from other clients are proxied to the upstream database, which
+    /// returns its usual permission errors. Requires --upstream-authentication.
+    ///
+    /// Privileges are read from the upstream database's catalog using the credentials in
+    /// --upstream-db-url, which must be able to see the grants of every user.
+    #[arg(
+        long,
+        env = "ENFORCE_UPSTREAM_PRIVILEGES",
+        requires = "upstream_authentication"
+    )]
+    enforce_upstream_privileges: bool,
+
+    /// How often, in seconds, to reload table privileges from the upstream database with
+    /// --enforce-upstream-privileges.
+    #[arg(
+        long,
+        env = "UPSTREAM_PRIVILEGES_REFRESH_INTERVAL_SECONDS",
+        default_value = "60"
+    )]
+    upstream_privileges_refresh_interval_seconds: u64,
+
+    /// Share a pool of at most this many connections to the upstream database between all client
+    /// connections, instead of opening an upstream connection for each client. Clients lease a
+    /// connection from the pool for the duration of each transaction, or of each statement run
+    /// outside of a transaction.
+    ///
+    /// Session variables changed with `SET` and prepared statements follow clients between pooled
+    /// connections. Clients that create other session state, such as temporary tables or
+    /// session-level locks, keep their pooled connection until they disconnect.
+    #[arg(long, env = "UPSTREAM_POOL_SIZE")]
+    upstream_pool_size: Option<usize>,
+
+    /// How long, in milliseconds, clients wait for a connection in the pool configured with
+    /// --upstream-pool-size to become free before giving up with an error.
+    #[arg(long, env = "UPSTREAM_POOL_WAIT_TIMEOUT_MS", default_value = "5000")]
+    upstream_pool_wait_timeout_ms: u64,
+
+    /// URL of a read replica of the upstream database. May be passed multiple times, or as a
+    /// comma-separated list.
+    ///
+    /// Read-only statements that are proxied to the upstream database outside of a transaction
+    /// are spread round-robin over the replicas that are reachable and caught up. Writes,
+    /// transactions, and locking reads such as `SELECT ... FOR UPDATE` still go to
+    /// --upstream-db-url.
+    #[arg(
+        long = "upstream-replica-url",
+        env = "UPSTREAM_REPLICA_URLS",
+        value_delimiter = ','
+    )]
+    upstream_replica_urls: Vec<RedactedString>,
+
+    /// Stop sending reads to replicas configured with --upstream-replica-url that lag further
+    /// than this many seconds behind the upstream database, until they catch up again. Reads from
+    /// clients that wrote to the upstream database less than this many seconds ago aren't sent to
+    /// replicas either, so that clients always see their own writes.
+    #[arg(long, env = "UPSTREAM_REPLICA_MAX_LAG_SECONDS", default_value = "10")]
+    upstream_replica_max_lag_seconds: u64,
+
+    /// How often, in seconds, to check whether the replicas configured with
+    /// --upstream-replica-url are reachable and caught up.
+    #[arg(
+        long,
+        env = "UPSTREAM_REPLICA_HEALTH_CHECK_INTERVAL_SECONDS",
+        default_value = "5"
+    )]
+    upstream_replica_health_check_interval_seconds: u64,
+
+    /// Track how far caches are behind the upstream database, so that reads with a
+    /// `readyset:max_staleness` hint can be served from caches known to be at most that far
+    /// behind. Tracking starts once the first such read is received. Without this, those reads are
+    /// always proxied.
+    #[arg(long, env = "CACHE_STALENESS_TRACKING")]
+    cache_staleness_tracking: bool,
+
+    /// How often, in milliseconds, to check how far caches are behind the upstream database, if
+    /// --cache-staleness-tracking is set.
+    #[arg(
+        long,
+        env = "CACHE_STALENESS_CHECK_INTERVAL_MS",
+        default_value = "1000"
+    )]
+    cache_staleness_check_interval_ms: u64,
+
+    /// Serve reads from caches over HTTP, as JSON, on /caches/<name> of the HTTP server listening
+    /// on --metrics-address. Requests are authenticated with the same credentials as SQL clients.
+    #[arg(long, env = "HTTP_CACHE_READS")]
+    http_cache_reads: bool,
+
+    /// Accept credentials sent with HTTP basic authentication for the cache reads enabled with
+    /// --http-cache-reads. The HTTP server doesn't support TLS, so this sends passwords over the
+    /// network in cleartext; without it, only --allow-unauthenticated-connections lets clients
+    /// read from caches over HTTP. Only use this on trusted networks.
+    #[arg(
+        long,
+        env = "ALLOW_UNENCRYPTED_HTTP_AUTHENTICATION",
+        requires = "http_cache_reads"
+    )]
+    allow_unencrypted_http_authentication: bool,
+
     /// Enable recording and exposing Prometheus metrics
     #[arg(long, env = "PROMETHEUS_METRICS", default_value = "true", hide = true)]
     prometheus_metrics: bool,
@@ -316,6 +458,10 @@ pub struct Options {
     #[command(flatten)]
     pub tracing: readyset_tracing::Options,
 
+    /// readyset-mysql-specific options
+    #[command(flatten)]
+    pub mysql_options: mysql::Options,
+
     /// readyset-psql-specific options
     #[command(flatten)]
     pub psql_options: psql::Options,
@@ -440,14 +586,15 @@ pub struct Options {
 
 impl Options {
     /// Return the configured database type, either explicitly set by the user or inferred from the
-    /// upstream DB URL
+    /// upstream DB URL or the `--cdc-source-type`
     pub fn database_type(&self) -> anyhow::Result<DatabaseType> {
         let infer_from_db_url = |db_url: &str| Ok(db_url.parse::<DatabaseURL>()?.database_type());
+        let replicator_config = &self.server_worker_options.replicator_config;
 
-        match (
-            self.database_type,
-            &self.server_worker_options.replicator_config.upstream_db_url,
-        ) {
+        match (self.database_type, &replicator_config.upstream_db_url) {
+            (None, None) if replicator_config.cdc_source.is_some() => {
+                Ok(replicator_config.cdc_source_type)
+            }
             (None, None) => bail!("One of either --database-type or --upstream-db-url is required"),
             (None, Some(url)) => infer_from_db_url(url),
             (Some(dt), None) => Ok(dt),
@@ -598,7 +745,7 @@ where
         }
 
         let users: &'static HashMap<String, String> = Box::leak(Box::new(
-            if !options.allow_unauthenticated_connections {
+            if !options.allow_unauthenticated_connections && !options.upstream_authentication {
                 HashMap::from([{
                     let upstream_url = upstream_config
                         .upstream_db_url
@@ -645,6 +792,25 @@ where
             },
         ));
 
+        if options.upstream_authentication && upstream_config.upstream_db_url.is_none() {
+            bail!("--upstream-authentication requires --upstream-db-url");
+        }
+        let upstream_auth = options.upstream_authentication.then(|| {
+            UpstreamAuthCache::new(Duration::from_secs(
+                options.upstream_authentication_cache_ttl_seconds,
+            ))
+        });
+
+        if options.upstream_pool_size.is_some() && upstream_config.upstream_db_url.is_none() {
+            bail!("--upstream-pool-size requires --upstream-db-url");
+        }
+        let upstream_pool = options.upstream_pool_size.map(|max_connections| {
+            UpstreamPool::new(
+                max_connections,
+                Duration::from_millis(options.upstream_pool_wait_timeout_ms),
+            )
+        });
+
         info!(version = %VERSION_STR_ONELINE);
 
         if options.allow_unsupported_set {
@@ -807,6 +973,67 @@ where
             rt.handle().spawn(report_allocator_metrics(alloc_shutdown));
         }
 
+        let table_privileges = if options.enforce_upstream_privileges {
+            rs_connect.in_scope(|| info!("Spawning upstream table privileges loader"));
+            let upstream_url = upstream_config
+                .upstream_db_url
+                .as_ref()
+                .map(|url| url.parse::<DatabaseURL>())
+                .transpose()?
+                .ok_or_else(|| {
+                    anyhow!("--enforce-upstream-privileges requires --upstream-db-url")
+                })?;
+            let table_privileges = TablePrivileges::new();
+            rt.handle().spawn(table_privileges.clone().run(
+                upstream_url,
+                Duration::from_secs(options.upstream_privileges_refresh_interval_seconds),
+                shutdown_rx.clone(),
+            ));
+            Some(table_privileges)
+        } else {
+            None
+        };
+
+        let upstream_replicas = if !options.upstream_replica_urls.is_empty() {
+            if upstream_config.upstream_db_url.is_none() {
+                bail!("--upstream-replica-url requires --upstream-db-url");
+            }
+            rs_connect.in_scope(|| info!("Spawning upstream replica health checks"));
+            let upstream_replicas = UpstreamReplicas::new(
+                options
+                    .upstream_replica_urls
+                    .iter()
+                    .map(|url| UpstreamConfig {
+                        upstream_db_url: Some(url.clone()),
+                        ..upstream_config.clone()
+                    })
+                    .collect(),
+                Duration::from_secs(options.upstream_replica_max_lag_seconds),
+            );
+            rt.handle().spawn(upstream_replicas.clone().run(
+                Duration::from_secs(options.upstream_replica_health_check_interval_seconds),
+                shutdown_rx.clone(),
+            ));
+            Some(upstream_replicas)
+        } else {
+            None
+        };
+
+        let cache_staleness = match &upstream_config.upstream_db_url {
+            Some(upstream_url) if options.cache_staleness_tracking => {
+                rs_connect.in_scope(|| info!("Spawning cache staleness tracker"));
+                let cache_staleness = CacheStaleness::new();
+                rt.handle().spawn(cache_staleness.clone().run(
+                    upstream_url.parse::<DatabaseURL>()?,
+                    rh.clone(),
+                    Duration::from_millis(options.cache_staleness_check_interval_ms),
+                    shutdown_rx.clone(),
+                ));
+                Some(cache_staleness)
+            }
+            _ => None,
+        };
+
         // Gate query log code path on the log flag existing.
         let qlog_sender = if options.query_log_mode.is_enabled() {
             rs_connect.in_scope(|| info!("Query logs are enabled. Spawning query logger"));
@@ -898,6 +1125,33 @@ where
         } else {
             (None, None)
         };
+        let cache_reads = options.http_cache_reads.then(|| {
+            rs_connect.in_scope(|| info!("Serving cache reads over HTTP"));
+            let authentication = match &upstream_auth {
+                Some(cache) => HttpAuthentication::Upstream {
+                    cache: cache.clone(),
+                    upstream_config: upstream_config.clone(),
+                },
+                None if !options.allow_unauthenticated_connections => {
+                    HttpAuthentication::Users(users.clone())
+                }
+                None => HttpAuthentication::None,
+            };
+            Arc::new(HttpCacheReads::new(
+                rh.clone(),
+                auto_increments.clone(),
+                view_name_cache.clone(),
+                view_cache.clone(),
+                noria_read_behavior,
+                self.expr_dialect,
+                self.parse_dialect,
+                adapter_rewrite_params,
+                authentication,
+                options.allow_unencrypted_http_authentication,
+                table_privileges.clone(),
+            ))
+        });
+        let http_cache_reads = cache_reads.clone();
         let http_server = NoriaAdapterHttpRouter {
             listen_addr: options.metrics_address,
             prometheus_handle: prometheus_handle.clone(),
@@ -905,6 +1159,7 @@ where
             failpoint_channel: tx,
             metrics: Default::default(),
             status_reporter: status_reporter.clone(),
+            cache_reads,
         };
 
         let router_shutdown_rx = shutdown_rx.clone();
@@ -931,6 +1186,25 @@ where
             no_upstream_connections,
         ));
 
+        if let Some(cache_reads) = http_cache_reads {
+            let schema_search_path = Arc::clone(&schema_search_path);
+            let mut shutdown_rx = shutdown_rx.clone();
+            rt.handle().spawn(async move {
+                let ssp_retry_loop = async {
+                    loop {
+                        if let Ok(ssp) = &*schema_search_path.read().await {
+                            break ssp.clone();
+                        }
+                        sleep(UPSTREAM_CONNECTION_RETRY_INTERVAL).await
+                    }
+                };
+                tokio::select! {
+                    ssp = ssp_retry_loop => cache_reads.set_schema_search_path(ssp),
+                    _ = shutdown_rx.recv() => {}
+                }
+            });
+        }
+
         if let MigrationMode::OutOfBand = migration_mode {
             set_failpoint!("adapter-out-of-band");
             let rh = rh.clone();
@@ -1101,6 +1375,10 @@ where
                 .users(users.clone())
                 .allow_cache_ddl(allow_cache_ddl)
                 .require_authentication(!options.allow_unauthenticated_connections)
+                .upstream_auth(upstream_auth.clone())
+                .allow_unencrypted_upstream_auth(options.allow_unencrypted_upstream_authentication)
+                .table_privileges(table_privileges.clone())
+                .cache_staleness(cache_staleness.clone())
                 .dialect(self.parse_dialect)
                 .query_log_sender(qlog_sender.clone())
                 .query_log_mode(Some(options.query_log_mode))
@@ -1128,6 +1406,8 @@ where
             });
 
             let upstream_config = upstream_config.clone();
+            let upstream_pool = upstream_pool.clone();
+            let upstream_replicas = upstream_replicas.clone();
             let schema_search_path = Arc::clone(&schema_search_path);
             let status_reporter_clone = status_reporter.clone();
             let fut = async move {
@@ -1139,7 +1419,16 @@ where
                 .map_err(|e| format!("Error connecting to upstream database: {}", e));
 
                 match upstream_res {
-                    Ok(upstream) => {
+                    Ok(mut upstream) => {
+                        if let Some(upstream) = &mut upstream {
+                            if let Some(pool) = upstream_pool {
+                                upstream.use_pool(pool);
+                            }
+                            if let Some(replicas) = upstream_replicas {
+                                upstream.use_replicas(replicas);
+                            }
+                        }
+
                         if let Err(e) =
                             telemetry_sender.send_event(TelemetryEvent::UpstreamConnected)
                         {
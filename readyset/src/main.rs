@@ -12,9 +12,10 @@ fn main() -> anyhow::Result<()> {
         DatabaseType::MySQL => NoriaAdapter {
             description: "MySQL adapter for ReadySet.",
             default_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 3307),
-            connection_handler: MySqlHandler {
+            connection_handler: MySqlHandler::new(readyset::mysql::Config {
+                options: options.mysql_options.clone(),
                 enable_statement_logging: options.tracing.statement_logging,
-            },
+            }),
             database_type: DatabaseType::MySQL,
             parse_dialect: nom_sql::Dialect::MySQL,
             expr_dialect: readyset_data::Dialect::DEFAULT_MYSQL,
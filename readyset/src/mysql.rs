@@ -1,3 +1,4 @@
+use clap::Parser;
 use mysql_srv::MySqlIntermediary;
 use readyset_adapter::upstream_database::LazyUpstream;
 use readyset_mysql::{MySqlQueryHandler, MySqlUpstream};
@@ -6,10 +7,45 @@ use tracing::{error, instrument};
 
 use crate::ConnectionHandler;
 
+// readyset-mysql specific options
+#[derive(Clone, Debug, Parser)]
+pub struct Options {
+    /// Don't offer protocol compression to MySQL clients.
+    ///
+    /// By default, clients that ask for it can compress the packets they exchange with ReadySet
+    /// using either zlib or zstd.
+    #[arg(long, env = "DISABLE_MYSQL_COMPRESSION")]
+    disable_mysql_compression: bool,
+
+    /// The size, in bytes, below which packets sent to MySQL clients using protocol compression
+    /// are left uncompressed.
+    #[arg(long, env = "MYSQL_COMPRESSION_THRESHOLD", default_value = "50")]
+    mysql_compression_threshold: usize,
+}
+
+/// Contains mysql-srv specific `Options` and whether to enable statement logging.
+pub struct Config {
+    pub options: Options,
+    pub enable_statement_logging: bool,
+}
+
 #[derive(Clone, Copy)]
 pub struct MySqlHandler {
     /// Whether to log statements received by the client
     pub enable_statement_logging: bool,
+    /// The size below which packets sent to clients using protocol compression are left
+    /// uncompressed, or None to not offer compression to clients
+    pub compression_threshold: Option<usize>,
+}
+
+impl MySqlHandler {
+    pub fn new(config: Config) -> MySqlHandler {
+        MySqlHandler {
+            enable_statement_logging: config.enable_statement_logging,
+            compression_threshold: (!config.options.disable_mysql_compression)
+                .then_some(config.options.mysql_compression_threshold),
+        }
+    }
 }
 
 impl ConnectionHandler for MySqlHandler {
@@ -26,6 +62,7 @@ impl ConnectionHandler for MySqlHandler {
             readyset_mysql::Backend {
                 noria: backend,
                 enable_statement_logging: self.enable_statement_logging,
+                compression_threshold: self.compression_threshold,
             },
             stream,
             self.enable_statement_logging,
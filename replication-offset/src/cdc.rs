@@ -0,0 +1,37 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ReplicationOffset;
+
+/// Represents a position within a newline-delimited stream of change events, such as a file of
+/// Debezium change events.
+///
+/// The position is the number of bytes of the stream that have been consumed, which is always at
+/// the start of a line. Since a stream can be read from more than once (for example, a file can be
+/// re-read from the start, or a producer can replay a topic into a socket), positions are only
+/// meaningful relative to the start of the stream they were read from.
+#[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct CdcPosition {
+    /// The number of bytes of the stream consumed up to and including the event at this position
+    pub offset: u64,
+}
+
+impl CdcPosition {
+    /// Constructs a [`CdcPosition`] pointing at the given byte offset in the stream
+    pub fn new(offset: u64) -> Self {
+        Self { offset }
+    }
+}
+
+impl From<CdcPosition> for ReplicationOffset {
+    fn from(value: CdcPosition) -> Self {
+        Self::Cdc(value)
+    }
+}
+
+impl fmt::Display for CdcPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "byte {}", self.offset)
+    }
+}
@@ -1,5 +1,6 @@
 //! Data types for implementing snapshot and streaming replication from an upstream database.
 
+pub mod cdc;
 pub mod mysql;
 pub mod postgres;
 
@@ -9,6 +10,7 @@ use std::collections::HashMap;
 use std::fmt;
 use std::hash::Hash;
 
+use cdc::CdcPosition;
 use mysql::MySqlPosition;
 use nom_sql::Relation;
 use postgres::PostgresPosition;
@@ -28,6 +30,7 @@ use serde::{Deserialize, Serialize};
 pub enum ReplicationOffset {
     MySql(MySqlPosition),
     Postgres(PostgresPosition),
+    Cdc(CdcPosition),
 }
 
 impl TryFrom<ReplicationOffset> for MySqlPosition {
@@ -38,7 +41,7 @@ impl TryFrom<ReplicationOffset> for MySqlPosition {
             Ok(offset)
         } else {
             Err(internal_err!(
-                "cannot extract MySqlPosition from Postgres ReplicationOffset"
+                "cannot extract MySqlPosition from non-MySQL ReplicationOffset"
             ))
         }
     }
@@ -60,7 +63,7 @@ impl TryFrom<ReplicationOffset> for PostgresPosition {
             Ok(offset)
         } else {
             Err(internal_err!(
-                "cannot extract PostgresPosition from MySQL ReplicationOffset"
+                "cannot extract PostgresPosition from non-Postgres ReplicationOffset"
             ))
         }
     }
@@ -74,11 +77,34 @@ impl TryFrom<&ReplicationOffset> for PostgresPosition {
     }
 }
 
+impl TryFrom<ReplicationOffset> for CdcPosition {
+    type Error = ReadySetError;
+
+    fn try_from(offset: ReplicationOffset) -> Result<Self, Self::Error> {
+        if let ReplicationOffset::Cdc(offset) = offset {
+            Ok(offset)
+        } else {
+            Err(internal_err!(
+                "cannot extract CdcPosition from non-CDC ReplicationOffset"
+            ))
+        }
+    }
+}
+
+impl TryFrom<&ReplicationOffset> for CdcPosition {
+    type Error = ReadySetError;
+
+    fn try_from(offset: &ReplicationOffset) -> Result<Self, Self::Error> {
+        offset.clone().try_into()
+    }
+}
+
 impl fmt::Display for ReplicationOffset {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::MySql(pos) => write!(f, "{pos}"),
             Self::Postgres(pos) => write!(f, "{pos}"),
+            Self::Cdc(pos) => write!(f, "{pos}"),
         }
     }
 }
@@ -88,6 +114,7 @@ impl PartialOrd for ReplicationOffset {
         match (self, other) {
             (Self::MySql(pos), Self::MySql(other_pos)) => pos.partial_cmp(other_pos),
             (Self::Postgres(pos), Self::Postgres(other_pos)) => pos.partial_cmp(other_pos),
+            (Self::Cdc(pos), Self::Cdc(other_pos)) => pos.partial_cmp(other_pos),
             _ => None,
         }
     }
@@ -117,6 +144,7 @@ impl ReplicationOffset {
                 offset.try_partial_cmp(other_offset)
             }
             (Self::Postgres(offset), Self::Postgres(other_offset)) => Ok(offset.cmp(other_offset)),
+            (Self::Cdc(offset), Self::Cdc(other_offset)) => Ok(offset.cmp(other_offset)),
             _ => Err(internal_err!(
                 "Cannot compare replication offsets from different database backends"
             )),
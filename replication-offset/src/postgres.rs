@@ -46,6 +46,13 @@ impl PostgresPosition {
         }
     }
 
+    /// Constructs a [`PostgresPosition`] that points to the start of any transaction that commits
+    /// at or after `end`, such as the end of the WAL reported in a keepalive message. Every
+    /// transaction that committed before `end` is before this position.
+    pub fn wal_end(end: Lsn) -> Self {
+        Self::commit_start(CommitLsn(end.0))
+    }
+
     /// Consumes `self`, constructing a new [`PostgresPosition`] with `self`'s [`CommitLsn`] and the
     /// given [`Lsn`].
     pub fn with_lsn(self, lsn: impl Into<Lsn>) -> Self {
@@ -179,6 +186,13 @@ mod tests {
         assert!(pos1 > pos2);
     }
 
+    #[test]
+    fn test_wal_end_after_earlier_commits() {
+        let end = PostgresPosition::wal_end(Lsn(100));
+        assert!(end > PostgresPosition::commit_end(CommitLsn(99)));
+        assert!(end <= PostgresPosition::commit_start(CommitLsn(100)));
+    }
+
     #[test]
     fn test_commit_lsn_round_trip() {
         assert_eq!(
@@ -0,0 +1,440 @@
+use std::collections::{HashMap, HashSet};
+use std::io::SeekFrom;
+use std::os::unix::fs::FileTypeExt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use nom_sql::{Dialect, Relation};
+use readyset_client::recipe::changelist::Change;
+use readyset_client::TableOperation;
+use readyset_errors::{ReadySetError, ReadySetResult};
+use replication_offset::cdc::CdcPosition;
+use replication_offset::ReplicationOffset;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, BufReader};
+use tokio::net::TcpStream;
+use tracing::{debug, info, warn};
+
+use super::event::{create_table, ChangeEvent, Operation, TableSchema};
+use crate::noria_adapter::{Connector, ReplicationAction};
+
+/// How long to wait before checking whether a file we've read to the end has grown
+const FILE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Where to read a stream of change events from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CdcSource {
+    /// A file or a named pipe
+    Path(PathBuf),
+    /// A TCP socket, given as `host:port`
+    Tcp(String),
+}
+
+impl CdcSource {
+    /// Parse the value of `--cdc-source`: either `tcp://<host>:<port>`, or a path
+    pub(crate) fn parse(source: &str) -> Self {
+        match source.strip_prefix("tcp://") {
+            Some(addr) => Self::Tcp(addr.to_owned()),
+            None => Self::Path(source.into()),
+        }
+    }
+}
+
+/// A connector that reads a newline-delimited stream of Debezium change events, and converts them
+/// to [`ReplicationAction`]s.
+///
+/// The stream carries no DDL of its own, so the connector creates each table that doesn't exist in
+/// ReadySet yet the first time it sees an event for it, using the schema embedded in the event. If
+/// a later event describes a different schema than that of the table in ReadySet, the connector
+/// asks for the table to be replayed with the new schema (see [`ReplicationAction::ReplayTable`]).
+/// Rows are always mapped onto the schema of the table in ReadySet by column name, so that events
+/// from before a schema change can be replayed into the table with the new schema.
+pub(crate) struct CdcConnector {
+    /// The stream we're reading events from
+    reader: Box<dyn AsyncBufRead + Send + Sync + Unpin>,
+    /// Whether `reader` is a regular file, which we keep following when we reach its end
+    follow: bool,
+    /// The position just after the last line we read
+    position: CdcPosition,
+    /// The dialect used to map the source of each event to a table name
+    dialect: Dialect,
+    /// The schema of each table that exists in ReadySet
+    tables: HashMap<Relation, TableSchema>,
+    /// Tables we stopped replicating because of an error, whose events we skip
+    failed_tables: HashSet<Relation>,
+    /// The line most recently read from the stream. When following a file, this may hold a
+    /// partially written line until the rest of it is written.
+    line: String,
+}
+
+impl CdcConnector {
+    /// Open the given source, and skip to `position` within it. `tables` holds the schema of each
+    /// table that already exists in ReadySet.
+    ///
+    /// Files are seeked to `position` directly. Named pipes and sockets can't be rewound, so the
+    /// first `position` bytes read from them are discarded, on the assumption that the producer
+    /// starts over from the start of the stream every time we connect.
+    pub(crate) async fn connect(
+        source: &CdcSource,
+        position: CdcPosition,
+        dialect: Dialect,
+        tables: HashMap<Relation, TableSchema>,
+    ) -> ReadySetResult<Self> {
+        let mut reader: Box<dyn AsyncBufRead + Send + Sync + Unpin> = match source {
+            CdcSource::Path(path) => {
+                let mut file = tokio::fs::File::open(path).await?;
+                if !file.metadata().await?.file_type().is_fifo() {
+                    file.seek(SeekFrom::Start(position.offset)).await?;
+                    return Ok(Self::new(
+                        Box::new(BufReader::new(file)),
+                        true,
+                        position,
+                        dialect,
+                        tables,
+                    ));
+                }
+                Box::new(BufReader::new(file))
+            }
+            CdcSource::Tcp(addr) => Box::new(BufReader::new(TcpStream::connect(addr).await?)),
+        };
+
+        let skipped = tokio::io::copy(
+            &mut (&mut reader).take(position.offset),
+            &mut tokio::io::sink(),
+        )
+        .await?;
+        if skipped < position.offset {
+            return Err(ReadySetError::UpstreamConnectionLost(format!(
+                "CDC stream ended after {skipped} bytes, before the last applied position \
+                 ({position})"
+            )));
+        }
+
+        Ok(Self::new(reader, false, position, dialect, tables))
+    }
+
+    fn new(
+        reader: Box<dyn AsyncBufRead + Send + Sync + Unpin>,
+        follow: bool,
+        position: CdcPosition,
+        dialect: Dialect,
+        tables: HashMap<Relation, TableSchema>,
+    ) -> Self {
+        info!(%position, "Reading change events");
+        Self {
+            reader,
+            follow,
+            position,
+            dialect,
+            tables,
+            failed_tables: HashSet::new(),
+            line: String::new(),
+        }
+    }
+
+    /// Read the next complete line from the stream into `self.line`, and advance `self.position`
+    /// past it
+    async fn read_line(&mut self) -> ReadySetResult<()> {
+        self.line.clear();
+        let mut read = 0;
+        loop {
+            let n = self.reader.read_line(&mut self.line).await?;
+            read += n;
+            if self.line.ends_with('\n') {
+                break;
+            }
+            if !self.follow {
+                return Err(ReadySetError::UpstreamConnectionLost(
+                    "CDC stream was closed".to_string(),
+                ));
+            }
+            // We've reached the end of the file, possibly in the middle of a line that's still
+            // being written; wait for it to grow
+            tokio::time::sleep(FILE_POLL_INTERVAL).await;
+        }
+        self.position.offset += read as u64;
+        Ok(())
+    }
+
+    /// Convert a single change event to the actions needed to apply it
+    fn actions_for_event(&mut self, event: ChangeEvent) -> Vec<ReplicationAction> {
+        let mut actions = vec![];
+        let mut operation = event.operation;
+        if let Some(columns) = event.columns {
+            let schema = TableSchema::from_columns(&columns);
+            match self.tables.get(&event.table) {
+                None => {
+                    debug!(
+                        table = %event.table.display_unquoted(),
+                        "Creating table from change event"
+                    );
+                    actions.push(ReplicationAction::DdlChange {
+                        schema: event
+                            .table
+                            .schema
+                            .as_ref()
+                            .map(ToString::to_string)
+                            .unwrap_or_default(),
+                        changes: vec![Change::CreateTable {
+                            statement: create_table(event.table.clone(), &columns),
+                            pg_meta: None,
+                        }],
+                    });
+                    self.tables.insert(event.table.clone(), schema);
+                }
+                Some(known) if *known == schema => {}
+                Some(known) => {
+                    // Either the schema of the table changed, or the table is being replayed with
+                    // the schema it has now and this event is from before the change. Rows are
+                    // mapped onto the schema of the table in ReadySet either way; the replay
+                    // request is ignored while replaying.
+                    debug!(table = %event.table.display_unquoted(), "Schema of table changed");
+                    actions.push(ReplicationAction::ReplayTable {
+                        statement: create_table(event.table.clone(), &columns),
+                    });
+                    let map_row = |row| known.map_row(&columns, row);
+                    operation = match operation {
+                        Operation::Insert(row) => Operation::Insert(map_row(row)),
+                        Operation::Update { before, after } => Operation::Update {
+                            before: map_row(before),
+                            after: map_row(after),
+                        },
+                        Operation::Delete(row) => Operation::Delete(map_row(row)),
+                        Operation::Truncate => Operation::Truncate,
+                    };
+                }
+            }
+        }
+
+        let table_actions = match operation {
+            Operation::Insert(row) => vec![TableOperation::Insert(row)],
+            Operation::Update { before, after } => vec![
+                TableOperation::DeleteRow { row: before },
+                TableOperation::Insert(after),
+            ],
+            Operation::Delete(row) => vec![TableOperation::DeleteRow { row }],
+            Operation::Truncate => vec![TableOperation::Truncate],
+        };
+        actions.push(ReplicationAction::TableAction {
+            table: event.table,
+            actions: table_actions,
+            txid: None,
+        });
+
+        actions
+    }
+}
+
+#[async_trait]
+impl Connector for CdcConnector {
+    async fn next_action(
+        &mut self,
+        _: &ReplicationOffset,
+        until: Option<&ReplicationOffset>,
+    ) -> ReadySetResult<(Vec<ReplicationAction>, ReplicationOffset)> {
+        loop {
+            self.read_line().await?;
+
+            let event = match ChangeEvent::parse(&self.line, self.dialect) {
+                Ok(Some(event)) if !self.failed_tables.contains(&event.table) => Some(event),
+                Ok(_) => None,
+                Err(ReadySetError::TableError { table, source }) => {
+                    if self.failed_tables.insert(table.clone()) {
+                        warn!(
+                            table = %table.display_unquoted(),
+                            error = %source,
+                            "Skipping change events for table"
+                        );
+                        return Err(ReadySetError::TableError { table, source });
+                    }
+                    None
+                }
+                Err(error) => {
+                    return Err(ReadySetError::ReplicationFailed(format!(
+                        "Could not read change event ending at {}: {error}",
+                        self.position
+                    )))
+                }
+            };
+
+            if let Some(event) = event {
+                return Ok((self.actions_for_event(event), self.position.into()));
+            }
+
+            let position = ReplicationOffset::from(self.position);
+            if until.is_some_and(|until| position >= *until) {
+                return Ok((vec![ReplicationAction::LogPosition], position));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn event(op: &str, id: i32) -> String {
+        event_with_columns(op, &[("id", id)])
+    }
+
+    /// An event for a row of `public.t` with the given `int32` columns
+    fn event_with_columns(op: &str, columns: &[(&str, i32)]) -> String {
+        let row_schema = |field: &str| {
+            serde_json::json!({
+                "type": "struct",
+                "optional": true,
+                "field": field,
+                "fields": columns
+                    .iter()
+                    .map(|(name, _)| serde_json::json!({
+                        "type": "int32",
+                        "optional": false,
+                        "field": name
+                    }))
+                    .collect::<Vec<_>>()
+            })
+        };
+        let row = serde_json::Value::Object(
+            columns
+                .iter()
+                .map(|(name, value)| (name.to_string(), (*value).into()))
+                .collect(),
+        );
+        let (before, after) = match op {
+            "d" => (row, serde_json::Value::Null),
+            _ => (serde_json::Value::Null, row),
+        };
+        let mut line = serde_json::json!({
+            "schema": { "type": "struct", "fields": [row_schema("before"), row_schema("after")] },
+            "payload": {
+                "before": before,
+                "after": after,
+                "source": { "connector": "postgresql", "db": "app", "schema": "public", "table": "t" },
+                "op": op
+            }
+        })
+        .to_string();
+        line.push('\n');
+        line
+    }
+
+    fn table() -> Relation {
+        Relation {
+            schema: Some("public".into()),
+            name: "t".into(),
+        }
+    }
+
+    fn connector(stream: String) -> CdcConnector {
+        CdcConnector::new(
+            Box::new(BufReader::new(std::io::Cursor::new(stream.into_bytes()))),
+            false,
+            CdcPosition::default(),
+            Dialect::PostgreSQL,
+            HashMap::new(),
+        )
+    }
+
+    async fn next(
+        connector: &mut CdcConnector,
+    ) -> ReadySetResult<(Vec<ReplicationAction>, ReplicationOffset)> {
+        connector
+            .next_action(&CdcPosition::default().into(), None)
+            .await
+    }
+
+    #[tokio::test]
+    async fn creates_table_on_first_event() {
+        let first = event("r", 1);
+        let second = event("d", 1);
+        let mut connector = connector(format!("{first}{second}"));
+
+        let (actions, pos) = next(&mut connector).await.unwrap();
+        assert_eq!(pos, CdcPosition::new(first.len() as u64).into());
+        assert!(matches!(
+            actions.as_slice(),
+            [
+                ReplicationAction::DdlChange { schema, .. },
+                ReplicationAction::TableAction { actions, .. },
+            ] if schema == "public" && matches!(actions.as_slice(), [TableOperation::Insert(_)])
+        ));
+
+        let (actions, pos) = next(&mut connector).await.unwrap();
+        assert_eq!(
+            pos,
+            CdcPosition::new((first.len() + second.len()) as u64).into()
+        );
+        assert!(matches!(
+            actions.as_slice(),
+            [ReplicationAction::TableAction { actions, .. }]
+                if matches!(actions.as_slice(), [TableOperation::DeleteRow { .. }])
+        ));
+    }
+
+    #[tokio::test]
+    async fn closed_stream_is_lost_connection() {
+        let mut connector = connector(event("c", 1));
+        next(&mut connector).await.unwrap();
+        assert!(matches!(
+            next(&mut connector).await,
+            Err(ReadySetError::UpstreamConnectionLost(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn schema_change_replays_table() {
+        let first = event_with_columns("r", &[("id", 1)]);
+        let second = event_with_columns("c", &[("x", 3), ("id", 2)]);
+        let mut connector = connector(format!("{first}{second}"));
+        next(&mut connector).await.unwrap();
+
+        let (actions, _) = next(&mut connector).await.unwrap();
+        assert!(matches!(
+            actions.as_slice(),
+            [
+                ReplicationAction::ReplayTable { statement },
+                ReplicationAction::TableAction { actions, .. },
+            ] if statement.table == table()
+                && actions == &[TableOperation::Insert(vec![2.into()])]
+        ));
+    }
+
+    #[tokio::test]
+    async fn reconnect_resumes_from_position() {
+        let first = event("r", 1);
+        let second = event("c", 2);
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(format!("{first}{second}").as_bytes())
+            .unwrap();
+
+        let columns = match ChangeEvent::parse(&first, Dialect::PostgreSQL) {
+            Ok(Some(ChangeEvent {
+                columns: Some(columns),
+                ..
+            })) => columns,
+            res => panic!("Unexpected parse result: {res:?}"),
+        };
+        let mut connector = CdcConnector::connect(
+            &CdcSource::Path(file.path().to_owned()),
+            CdcPosition::new(first.len() as u64),
+            Dialect::PostgreSQL,
+            HashMap::from([(table(), TableSchema::from_columns(&columns))]),
+        )
+        .await
+        .unwrap();
+        let (actions, pos) = next(&mut connector).await.unwrap();
+
+        assert_eq!(
+            pos,
+            CdcPosition::new((first.len() + second.len()) as u64).into()
+        );
+        // The table already exists, so it isn't created again
+        assert!(matches!(
+            actions.as_slice(),
+            [ReplicationAction::TableAction { table: t, actions, .. }]
+                if *t == table() && actions == &[TableOperation::Insert(vec![2.into()])]
+        ));
+    }
+}
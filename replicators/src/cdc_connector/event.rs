@@ -0,0 +1,727 @@
+//! Parsing of Debezium change events, as written by Kafka Connect's JSON converter with schemas
+//! enabled (`value.converter.schemas.enable=true`).
+//!
+//! Each event is a JSON object with a `schema` describing the shape of the event, and a `payload`
+//! holding the event itself:
+//!
+//! ```json
+//! {
+//!   "schema": { "type": "struct", "fields": [{ "field": "before", ... }, ...] },
+//!   "payload": {
+//!     "before": null,
+//!     "after": { "id": 1, "name": "Alice" },
+//!     "source": { "connector": "postgresql", "db": "app", "schema": "public", "table": "users" },
+//!     "op": "c"
+//!   }
+//! }
+//! ```
+
+use base64::Engine;
+use chrono::{DateTime, NaiveTime};
+use nom_sql::{
+    Column, ColumnConstraint, ColumnSpecification, CreateTableBody, CreateTableStatement, Dialect,
+    Relation, SqlIdentifier, SqlType, TableKey,
+};
+use readyset_data::DfValue;
+use readyset_errors::{ReadySetError, ReadySetResult};
+use rust_decimal::Decimal;
+use serde_json::Value;
+
+/// The type of a single column in a change event, as described by the event's schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldType {
+    Int16,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    Boolean,
+    String,
+    Bytes,
+    /// Days since the epoch
+    Date,
+    /// Time since midnight, in units of `1 / units_per_sec` seconds
+    Time {
+        units_per_sec: i64,
+    },
+    /// Time since the epoch, in units of `1 / units_per_sec` seconds
+    Timestamp {
+        units_per_sec: i64,
+    },
+    /// An ISO 8601 timestamp with a time zone offset
+    ZonedTimestamp,
+    /// A big-endian two's complement unscaled value, with the given scale
+    Decimal {
+        precision: Option<u16>,
+        scale: u8,
+    },
+}
+
+/// A single column of a table, as described by the schema of a change event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Field {
+    name: SqlIdentifier,
+    ty: FieldType,
+    optional: bool,
+}
+
+impl Field {
+    /// Parse a single entry of the `fields` of a Debezium row schema
+    fn from_schema(schema: &Value) -> Result<Self, String> {
+        let name = schema
+            .get("field")
+            .and_then(Value::as_str)
+            .ok_or("Column schema is missing its name")?;
+        let ty = schema.get("type").and_then(Value::as_str).unwrap_or("");
+        let logical_type = schema.get("name").and_then(Value::as_str);
+        let parameter = |name: &str| {
+            schema
+                .get("parameters")
+                .and_then(|params| params.get(name))
+                .and_then(Value::as_str)
+                .and_then(|param| param.parse().ok())
+        };
+
+        let ty = match (ty, logical_type) {
+            ("int32", Some("io.debezium.time.Date")) => FieldType::Date,
+            ("int32", Some("io.debezium.time.Time")) => FieldType::Time {
+                units_per_sec: 1_000,
+            },
+            ("int64", Some("io.debezium.time.MicroTime")) => FieldType::Time {
+                units_per_sec: 1_000_000,
+            },
+            ("int64", Some("io.debezium.time.NanoTime")) => FieldType::Time {
+                units_per_sec: 1_000_000_000,
+            },
+            ("int64", Some("io.debezium.time.Timestamp")) => FieldType::Timestamp {
+                units_per_sec: 1_000,
+            },
+            ("int64", Some("io.debezium.time.MicroTimestamp")) => FieldType::Timestamp {
+                units_per_sec: 1_000_000,
+            },
+            ("int64", Some("io.debezium.time.NanoTimestamp")) => FieldType::Timestamp {
+                units_per_sec: 1_000_000_000,
+            },
+            ("string", Some("io.debezium.time.ZonedTimestamp")) => FieldType::ZonedTimestamp,
+            ("bytes", Some("org.apache.kafka.connect.data.Decimal")) => FieldType::Decimal {
+                precision: parameter("connect.decimal.precision"),
+                scale: parameter("scale").ok_or("Decimal column schema is missing its scale")?,
+            },
+            ("int8" | "int16", _) => FieldType::Int16,
+            ("int32", _) => FieldType::Int32,
+            ("int64", _) => FieldType::Int64,
+            ("float32", _) => FieldType::Float32,
+            ("float64", _) => FieldType::Float64,
+            ("boolean", _) => FieldType::Boolean,
+            // This also covers JSON, UUIDs, enums and the like, which we replicate as text
+            ("string", _) => FieldType::String,
+            ("bytes", _) => FieldType::Bytes,
+            (ty, logical_type) => {
+                return Err(format!(
+                    "Unsupported type {}{} for column {name}",
+                    ty,
+                    logical_type
+                        .map(|logical_type| format!(" ({logical_type})"))
+                        .unwrap_or_default()
+                ))
+            }
+        };
+
+        Ok(Self {
+            name: name.into(),
+            ty,
+            optional: schema
+                .get("optional")
+                .and_then(Value::as_bool)
+                .unwrap_or(true),
+        })
+    }
+
+    fn column_specification(&self) -> ColumnSpecification {
+        let sql_type = match self.ty {
+            FieldType::Int16 => SqlType::SmallInt(None),
+            FieldType::Int32 => SqlType::Int(None),
+            FieldType::Int64 => SqlType::BigInt(None),
+            FieldType::Float32 => SqlType::Real,
+            FieldType::Float64 => SqlType::Double,
+            FieldType::Boolean => SqlType::Bool,
+            FieldType::String => SqlType::Text,
+            FieldType::Bytes => SqlType::ByteArray,
+            FieldType::Date => SqlType::Date,
+            FieldType::Time { .. } => SqlType::Time,
+            FieldType::Timestamp { .. } => SqlType::Timestamp,
+            FieldType::ZonedTimestamp => SqlType::TimestampTz,
+            FieldType::Decimal { precision, scale } => {
+                SqlType::Numeric(precision.map(|precision| (precision, Some(scale))))
+            }
+        };
+        let constraints = if self.optional {
+            vec![]
+        } else {
+            vec![ColumnConstraint::NotNull]
+        };
+
+        ColumnSpecification::with_constraints(
+            Column::from(self.name.as_str()),
+            sql_type,
+            constraints,
+        )
+    }
+
+    /// Convert the JSON representation of a value of this column to a [`DfValue`]
+    fn value(&self, value: &Value) -> ReadySetResult<DfValue> {
+        if value.is_null() {
+            return Ok(DfValue::None);
+        }
+
+        let invalid = || {
+            ReadySetError::ReplicationFailed(format!(
+                "Invalid value {value} for column {}",
+                self.name
+            ))
+        };
+        let int = || value.as_i64().ok_or_else(invalid);
+        let bytes = || {
+            value
+                .as_str()
+                .and_then(|encoded| {
+                    base64::engine::general_purpose::STANDARD
+                        .decode(encoded)
+                        .ok()
+                })
+                .ok_or_else(invalid)
+        };
+
+        Ok(match self.ty {
+            FieldType::Int16 | FieldType::Int32 | FieldType::Int64 => int()?.into(),
+            FieldType::Float32 | FieldType::Float64 => {
+                DfValue::try_from(value.as_f64().ok_or_else(invalid)?)?
+            }
+            FieldType::Boolean => value.as_bool().ok_or_else(invalid)?.into(),
+            FieldType::String => value.as_str().ok_or_else(invalid)?.into(),
+            FieldType::Bytes => bytes()?.into(),
+            FieldType::Date => int()?
+                .checked_mul(24 * 60 * 60)
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .ok_or_else(invalid)?
+                .date_naive()
+                .into(),
+            FieldType::Time { units_per_sec } => {
+                let (secs, nanos) = split_units(int()?, units_per_sec);
+                NaiveTime::from_num_seconds_from_midnight_opt(
+                    secs.try_into().map_err(|_| invalid())?,
+                    nanos,
+                )
+                .ok_or_else(invalid)?
+                .into()
+            }
+            FieldType::Timestamp { units_per_sec } => {
+                let (secs, nanos) = split_units(int()?, units_per_sec);
+                DateTime::from_timestamp(secs, nanos)
+                    .ok_or_else(invalid)?
+                    .naive_utc()
+                    .into()
+            }
+            FieldType::ZonedTimestamp => {
+                DateTime::parse_from_rfc3339(value.as_str().ok_or_else(invalid)?)
+                    .map_err(|_| invalid())?
+                    .into()
+            }
+            FieldType::Decimal { scale, .. } => {
+                let bytes = bytes()?;
+                if bytes.is_empty() || bytes.len() > 16 {
+                    return Err(invalid());
+                }
+                // Sign-extend the big-endian two's complement value to 128 bits
+                let fill = if bytes[0] & 0x80 != 0 { 0xff } else { 0 };
+                let mut buf = [fill; 16];
+                buf[16 - bytes.len()..].copy_from_slice(&bytes);
+                Decimal::try_from_i128_with_scale(i128::from_be_bytes(buf), scale.into())
+                    .map_err(|_| invalid())?
+                    .into()
+            }
+        })
+    }
+}
+
+/// Split a count of `1 / units_per_sec` seconds into whole seconds and nanoseconds
+fn split_units(value: i64, units_per_sec: i64) -> (i64, u32) {
+    let nanos = value.rem_euclid(units_per_sec) * (1_000_000_000 / units_per_sec);
+    (value.div_euclid(units_per_sec), nanos as u32)
+}
+
+/// The change to a table recorded by a [`ChangeEvent`]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Operation {
+    /// A row was inserted, or read as part of the initial snapshot of the table
+    Insert(Vec<DfValue>),
+    /// A row was updated
+    Update {
+        before: Vec<DfValue>,
+        after: Vec<DfValue>,
+    },
+    /// A row was deleted
+    Delete(Vec<DfValue>),
+    /// All rows were deleted from the table
+    Truncate,
+}
+
+/// A single Debezium change event
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ChangeEvent {
+    /// The table that was changed
+    pub(crate) table: Relation,
+    /// The columns of the table at the time of the change, if the event describes them (truncate
+    /// events don't)
+    pub(crate) columns: Option<Vec<Field>>,
+    /// The change itself
+    pub(crate) operation: Operation,
+}
+
+impl ChangeEvent {
+    /// Parse a single line of a newline-delimited stream of change events.
+    ///
+    /// Returns `Ok(None)` for lines that don't describe a change to a row, such as blank lines,
+    /// tombstones, heartbeats and schema change events. Errors that only affect a single table,
+    /// such as columns with unsupported types, are returned as [`ReadySetError::TableError`].
+    pub(crate) fn parse(line: &str, dialect: Dialect) -> ReadySetResult<Option<Self>> {
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(None);
+        }
+
+        let event: Value = serde_json::from_str(line)
+            .map_err(|e| ReadySetError::ReplicationFailed(format!("Invalid change event: {e}")))?;
+        if event.is_null() {
+            // A tombstone, emitted after a delete to allow log compaction
+            return Ok(None);
+        }
+
+        let (Some(schema), Some(payload)) = (event.get("schema"), event.get("payload")) else {
+            return Err(ReadySetError::ReplicationFailed(
+                "Change events must include their schema; set `value.converter.schemas.enable` to \
+                 `true` in the Debezium connector's configuration"
+                    .into(),
+            ));
+        };
+        let Some(op) = payload.get("op").and_then(Value::as_str) else {
+            return Ok(None);
+        };
+
+        let source_field = |name: &str| {
+            payload
+                .get("source")
+                .and_then(|source| source.get(name))
+                .and_then(Value::as_str)
+                .ok_or_else(|| {
+                    ReadySetError::ReplicationFailed(format!(
+                        "Change event is missing `source.{name}`"
+                    ))
+                })
+        };
+        let table = Relation {
+            schema: Some(
+                source_field(match dialect {
+                    Dialect::MySQL => "db",
+                    Dialect::PostgreSQL => "schema",
+                })?
+                .into(),
+            ),
+            name: source_field("table")?.into(),
+        };
+        let table_error = |source: ReadySetError| ReadySetError::TableError {
+            table: table.clone(),
+            source: Box::new(source),
+        };
+
+        let row_schema = |name: &str| -> ReadySetResult<Vec<Field>> {
+            schema
+                .get("fields")
+                .and_then(Value::as_array)
+                .and_then(|fields| {
+                    fields
+                        .iter()
+                        .find(|field| field.get("field").and_then(Value::as_str) == Some(name))
+                })
+                .and_then(|field| field.get("fields"))
+                .and_then(Value::as_array)
+                .ok_or_else(|| {
+                    ReadySetError::ReplicationFailed(format!(
+                        "Change event schema does not describe `{name}`"
+                    ))
+                })?
+                .iter()
+                .map(Field::from_schema)
+                .collect::<Result<_, _>>()
+                .map_err(|e| table_error(ReadySetError::Unsupported(e)))
+        };
+        let row = |columns: &[Field], name: &str| -> ReadySetResult<Vec<DfValue>> {
+            let row = payload
+                .get(name)
+                .filter(|row| !row.is_null())
+                .ok_or_else(|| {
+                    table_error(ReadySetError::Unsupported(format!(
+                    "Change event for operation `{op}` is missing `{name}`. Updates and deletes \
+                     must include the previous values of the row (in Postgres, set the table's \
+                     REPLICA IDENTITY to FULL)"
+                )))
+                })?;
+            columns
+                .iter()
+                .map(|column| column.value(&row[column.name.as_str()]))
+                .collect()
+        };
+
+        let (columns, operation) = match op {
+            "c" | "r" => {
+                let columns = row_schema("after")?;
+                let after = row(&columns, "after")?;
+                (Some(columns), Operation::Insert(after))
+            }
+            "u" => {
+                let columns = row_schema("after")?;
+                let before = row(&columns, "before")?;
+                let after = row(&columns, "after")?;
+                (Some(columns), Operation::Update { before, after })
+            }
+            "d" => {
+                let columns = row_schema("before")?;
+                let before = row(&columns, "before")?;
+                (Some(columns), Operation::Delete(before))
+            }
+            "t" => (None, Operation::Truncate),
+            op => {
+                return Err(ReadySetError::ReplicationFailed(format!(
+                    "Unknown change event operation `{op}`"
+                )))
+            }
+        };
+
+        Ok(Some(Self {
+            table,
+            columns,
+            operation,
+        }))
+    }
+}
+
+/// Build the body of a `CREATE TABLE` statement for a table with the given columns
+fn create_table_body(columns: &[Field]) -> CreateTableBody {
+    CreateTableBody {
+        fields: columns.iter().map(Field::column_specification).collect(),
+        keys: None,
+    }
+}
+
+/// Build a `CREATE TABLE` statement for a table with the given columns
+pub(crate) fn create_table(table: Relation, columns: &[Field]) -> CreateTableStatement {
+    CreateTableStatement {
+        if_not_exists: false,
+        table,
+        body: Ok(create_table_body(columns)),
+        options: Ok(vec![]),
+    }
+}
+
+/// The name, type and nullability of each column of a table, in order.
+///
+/// This is all of a table's schema that we derive from change events, so it decides whether the
+/// columns of an event match the table in ReadySet. Column names are compared case-insensitively,
+/// and types by the kind of value they hold (see [`normalized_type`]), so that a table that was
+/// created some other way than from a change event (or that ReadySet reports a little differently
+/// than it was created) isn't mistaken for one whose schema changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TableSchema(Vec<(String, SqlType, bool)>);
+
+impl TableSchema {
+    /// The schema of a table created with the given `CREATE TABLE` body
+    pub(crate) fn from_body(body: &CreateTableBody) -> Self {
+        let primary_key = body
+            .keys
+            .iter()
+            .flatten()
+            .find_map(|key| match key {
+                TableKey::PrimaryKey { columns, .. } => Some(columns.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        Self(
+            body.fields
+                .iter()
+                .map(|spec| {
+                    let not_null = spec.constraints.iter().any(|constraint| {
+                        matches!(
+                            constraint,
+                            ColumnConstraint::NotNull | ColumnConstraint::PrimaryKey
+                        )
+                    }) || primary_key
+                        .iter()
+                        .any(|column| column.name == spec.column.name);
+                    (
+                        spec.column.name.to_lowercase(),
+                        normalized_type(&spec.sql_type),
+                        not_null,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// The schema of a table created for the given columns by [`create_table`]
+    pub(crate) fn from_columns(columns: &[Field]) -> Self {
+        Self::from_body(&create_table_body(columns))
+    }
+
+    /// Rearrange a row with the given columns to match this schema, by column name. Columns of
+    /// this schema the row doesn't have are `NULL`, and columns of the row this schema doesn't
+    /// have are dropped.
+    pub(crate) fn map_row(&self, columns: &[Field], mut row: Vec<DfValue>) -> Vec<DfValue> {
+        self.0
+            .iter()
+            .map(|(name, _, _)| {
+                columns
+                    .iter()
+                    .position(|column| column.name.to_lowercase() == *name)
+                    .map(|idx| std::mem::take(&mut row[idx]))
+                    .unwrap_or(DfValue::None)
+            })
+            .collect()
+    }
+}
+
+/// The type a column of the given type would have if it were created from a change event, which is
+/// the same for all types that Debezium represents the same way (for example, all integer types
+/// that fit in 32 bits, or all string types regardless of their length)
+fn normalized_type(ty: &SqlType) -> SqlType {
+    match ty {
+        SqlType::TinyInt(_)
+        | SqlType::UnsignedTinyInt(_)
+        | SqlType::SmallInt(_)
+        | SqlType::Int2 => SqlType::SmallInt(None),
+        SqlType::UnsignedSmallInt(_)
+        | SqlType::MediumInt(_)
+        | SqlType::UnsignedMediumInt(_)
+        | SqlType::Int(_)
+        | SqlType::Int4
+        | SqlType::Serial => SqlType::Int(None),
+        SqlType::UnsignedInt(_)
+        | SqlType::BigInt(_)
+        | SqlType::UnsignedBigInt(_)
+        | SqlType::Int8
+        | SqlType::BigSerial => SqlType::BigInt(None),
+        SqlType::Float | SqlType::Real => SqlType::Real,
+        SqlType::Char(_)
+        | SqlType::VarChar(_)
+        | SqlType::TinyText
+        | SqlType::MediumText
+        | SqlType::LongText
+        | SqlType::Text
+        | SqlType::Citext
+        | SqlType::QuotedChar
+        | SqlType::Enum(_)
+        | SqlType::Json
+        | SqlType::Jsonb
+        | SqlType::Uuid
+        | SqlType::MacAddr
+        | SqlType::Inet => SqlType::Text,
+        SqlType::Binary(_)
+        | SqlType::VarBinary(_)
+        | SqlType::TinyBlob
+        | SqlType::Blob
+        | SqlType::MediumBlob
+        | SqlType::LongBlob
+        | SqlType::ByteArray => SqlType::ByteArray,
+        // The scale of a decimal is part of each value in a change event, and the precision is
+        // only sometimes given
+        SqlType::Numeric(_) | SqlType::Decimal(..) => SqlType::Numeric(None),
+        SqlType::DateTime(_) | SqlType::Timestamp => SqlType::Timestamp,
+        ty => ty.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use nom_sql::DialectDisplay;
+
+    use super::*;
+
+    fn event(op: &str, before: Value, after: Value) -> String {
+        let row_schema = |field: &str| {
+            serde_json::json!({
+                "type": "struct",
+                "optional": true,
+                "field": field,
+                "fields": [
+                    { "type": "int32", "optional": false, "field": "id" },
+                    { "type": "string", "optional": true, "field": "name" },
+                    {
+                        "type": "int32",
+                        "optional": true,
+                        "name": "io.debezium.time.Date",
+                        "field": "birthday"
+                    },
+                    {
+                        "type": "bytes",
+                        "optional": true,
+                        "name": "org.apache.kafka.connect.data.Decimal",
+                        "parameters": { "scale": "2", "connect.decimal.precision": "10" },
+                        "field": "balance"
+                    }
+                ]
+            })
+        };
+        serde_json::json!({
+            "schema": {
+                "type": "struct",
+                "fields": [row_schema("before"), row_schema("after")]
+            },
+            "payload": {
+                "before": before,
+                "after": after,
+                "source": {
+                    "connector": "postgresql",
+                    "db": "app",
+                    "schema": "public",
+                    "table": "users"
+                },
+                "op": op
+            }
+        })
+        .to_string()
+    }
+
+    fn row(id: i32, name: &str) -> Value {
+        // -12.34, as a big-endian two's complement unscaled value
+        serde_json::json!({ "id": id, "name": name, "birthday": 1, "balance": "+y4=" })
+    }
+
+    fn df_row(id: i32, name: &str) -> Vec<DfValue> {
+        vec![
+            id.into(),
+            name.into(),
+            NaiveDate::from_ymd_opt(1970, 1, 2).unwrap().into(),
+            Decimal::new(-1234, 2).into(),
+        ]
+    }
+
+    #[test]
+    fn parse_insert() {
+        let event = ChangeEvent::parse(&event("c", Value::Null, row(1, "a")), Dialect::PostgreSQL)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            event.table,
+            Relation {
+                schema: Some("public".into()),
+                name: "users".into()
+            }
+        );
+        assert_eq!(event.operation, Operation::Insert(df_row(1, "a")));
+
+        let create_table = create_table(event.table, &event.columns.unwrap());
+        assert_eq!(
+            create_table.display(Dialect::PostgreSQL).to_string(),
+            "CREATE TABLE \"public\".\"users\" (\"id\" INT NOT NULL, \"name\" TEXT, \"birthday\" \
+             DATE, \"balance\" NUMERIC(10, 2))"
+        );
+    }
+
+    #[test]
+    fn parse_update_and_delete() {
+        let update = ChangeEvent::parse(&event("u", row(1, "a"), row(1, "b")), Dialect::MySQL)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            update.table,
+            Relation {
+                schema: Some("app".into()),
+                name: "users".into()
+            }
+        );
+        assert_eq!(
+            update.operation,
+            Operation::Update {
+                before: df_row(1, "a"),
+                after: df_row(1, "b")
+            }
+        );
+
+        let delete = ChangeEvent::parse(&event("d", row(1, "b"), Value::Null), Dialect::MySQL)
+            .unwrap()
+            .unwrap();
+        assert_eq!(delete.operation, Operation::Delete(df_row(1, "b")));
+    }
+
+    #[test]
+    fn map_row_by_column_name() {
+        let event = ChangeEvent::parse(&event("c", Value::Null, row(1, "a")), Dialect::PostgreSQL)
+            .unwrap()
+            .unwrap();
+        let columns = event.columns.unwrap();
+        let Operation::Insert(row) = event.operation else {
+            panic!("Expected an insert")
+        };
+
+        let schema = TableSchema(vec![
+            ("name".into(), SqlType::Text, false),
+            ("email".into(), SqlType::Text, false),
+            ("id".into(), SqlType::Int(None), true),
+        ]);
+        assert_ne!(schema, TableSchema::from_columns(&columns));
+        assert_eq!(
+            schema.map_row(&columns, row),
+            vec!["a".into(), DfValue::None, 1.into()]
+        );
+    }
+
+    #[test]
+    fn schema_comparison_is_normalized() {
+        let event = ChangeEvent::parse(&event("c", Value::Null, row(1, "a")), Dialect::MySQL)
+            .unwrap()
+            .unwrap();
+        let columns = event.columns.unwrap();
+
+        let body = match nom_sql::parse_query(
+            Dialect::MySQL,
+            "CREATE TABLE users (ID int(11), Name varchar(255), birthday date, \
+             balance decimal(10, 2), PRIMARY KEY (ID))",
+        ) {
+            Ok(nom_sql::SqlQuery::CreateTable(CreateTableStatement { body: Ok(body), .. })) => body,
+            res => panic!("Unexpected parse result: {res:?}"),
+        };
+        assert_eq!(
+            TableSchema::from_body(&body),
+            TableSchema::from_columns(&columns)
+        );
+    }
+
+    #[test]
+    fn update_without_before_is_table_error() {
+        let err = ChangeEvent::parse(&event("u", Value::Null, row(1, "b")), Dialect::PostgreSQL)
+            .unwrap_err();
+        assert!(matches!(err, ReadySetError::TableError { .. }));
+    }
+
+    #[test]
+    fn skip_non_row_events() {
+        assert_eq!(ChangeEvent::parse("", Dialect::PostgreSQL).unwrap(), None);
+        assert_eq!(
+            ChangeEvent::parse("null", Dialect::PostgreSQL).unwrap(),
+            None
+        );
+        assert_eq!(
+            ChangeEvent::parse(
+                r#"{"schema": {}, "payload": {"ts_ms": 1}}"#,
+                Dialect::PostgreSQL
+            )
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn schemaless_events_are_rejected() {
+        ChangeEvent::parse(r#"{"op": "c", "after": {"id": 1}}"#, Dialect::PostgreSQL).unwrap_err();
+    }
+}
@@ -0,0 +1,19 @@
+//! Replication from a newline-delimited stream of [Debezium](https://debezium.io/) change events,
+//! read from a file, a named pipe or a TCP socket.
+//!
+//! This allows ReadySet to cache data from any database Debezium can capture changes from, without
+//! connecting to the database itself. There is no snapshot step: Debezium starts each stream with
+//! a snapshot of its own, which is emitted as a series of `read` events.
+//!
+//! Replication offsets are byte offsets into the stream. Files are seeked to the last applied
+//! offset when the replicator restarts, but named pipes and sockets can't be, and carry no
+//! information about where in the stream they start. For those, the producer must replay the
+//! stream from its start every time ReadySet connects: the first bytes read, up to the last applied
+//! offset, are discarded as events that were already applied. A producer that instead carries on
+//! from where the last connection left off will have that many bytes of new events skipped.
+
+mod connector;
+mod event;
+
+pub(crate) use connector::{CdcConnector, CdcSource};
+pub(crate) use event::TableSchema;
@@ -6,6 +6,7 @@
     iter_intersperse,
     let_chains
 )]
+pub(crate) mod cdc_connector;
 pub mod db_util;
 pub(crate) mod mysql_connector;
 pub(crate) mod noria_adapter;
@@ -18,9 +19,11 @@ use metrics::{register_gauge, Gauge};
 use nom_sql::Relation;
 pub use noria_adapter::{cleanup, NoriaAdapter};
 use readyset_client::metrics::recorded;
-use readyset_errors::ReadySetError;
+use readyset_errors::{ReadySetError, ReadySetResult};
+pub use replication_offset::cdc::CdcPosition;
 pub use replication_offset::mysql::MySqlPosition;
 pub use replication_offset::postgres::PostgresPosition;
+use tokio::sync::oneshot;
 use tracing::info;
 
 /// Event notifications sent from the replicator to the controller.
@@ -40,6 +43,19 @@ pub enum ReplicatorMessage {
 pub enum ControllerMessage {
     /// Drop the specified table and require a new partial snapshot
     ResnapshotTable { table: Relation },
+    /// Start replicating the specified tables, which requires a new partial snapshot. `done` is
+    /// sent the result once the tables have been added to the set of replicated tables, before
+    /// they're snapshotted.
+    AddTables {
+        tables: Vec<Relation>,
+        done: oneshot::Sender<ReadySetResult<()>>,
+    },
+    /// Stop replicating the specified tables, and remove them from ReadySet. `done` is sent the
+    /// result once the tables have been removed.
+    DropTables {
+        tables: Vec<Relation>,
+        done: oneshot::Sender<ReadySetResult<()>>,
+    },
 }
 
 /// A handle to the metric we use to track the number of tables currently snapshotting. To use this
@@ -3,13 +3,15 @@ use std::convert::{TryFrom, TryInto};
 use std::io;
 
 use async_trait::async_trait;
-use binlog::consts::{BinlogChecksumAlg, EventType};
+use binlog::consts::{BinlogChecksumAlg, EventType, UnknownEventType};
+use database_utils::MySqlFlavor;
 use metrics::counter;
 use mysql::binlog::events::StatusVarVal;
 use mysql::binlog::jsonb::{self, JsonbToJsonError};
 use mysql::prelude::Queryable;
 use mysql_async as mysql;
 use mysql_common::binlog;
+use mysql_common::binlog::events::RowsEventData;
 use mysql_common::binlog::row::BinlogRow;
 use mysql_common::binlog::value::BinlogValue;
 use nom_sql::{Relation, SqlIdentifier};
@@ -22,10 +24,25 @@ use replication_offset::mysql::MySqlPosition;
 use replication_offset::ReplicationOffset;
 use tracing::{error, info, warn};
 
+use super::mariadb;
 use crate::noria_adapter::{Connector, ReplicationAction};
 
 const CHECKSUM_QUERY: &str = "SET @source_binlog_checksum='CRC32'";
+/// MariaDB only recognizes the pre-8.0.26 MySQL name of the checksum variable
+const MARIADB_CHECKSUM_QUERY: &str = "SET @master_binlog_checksum='CRC32'";
+/// Tells a MariaDB primary that we understand MariaDB GTID events (`MARIA_SLAVE_CAPABILITY_GTID`).
+/// Without this, the primary rewrites them into events understood by old MySQL replicas.
+const MARIADB_CAPABILITY_QUERY: &str = "SET @mariadb_slave_capability=4";
+/// Asks the primary to send a heartbeat event after each second without any other events, so that
+/// we can report our position as caught up while the binlog is idle (in nanoseconds). Both MySQL
+/// and MariaDB recognize the pre-8.0.26 MySQL name of the variable.
+const HEARTBEAT_QUERY: &str = "SET @master_heartbeat_period=1000000000";
 const DEFAULT_SERVER_ID: u32 = u32::MAX - 55;
+/// Event type codes for MariaDB-specific binlog events, which are not known to `mysql_common`
+const MARIADB_GTID_EVENT: u8 = 162;
+const MARIADB_START_ENCRYPTION_EVENT: u8 = 164;
+const MARIADB_QUERY_COMPRESSED_EVENT: u8 = 165;
+const MARIADB_DELETE_ROWS_COMPRESSED_EVENT: u8 = 171;
 const MAX_POSITION_TIME: u64 = 10;
 
 /// A connector that connects to a MySQL server and starts reading binlogs from a given position.
@@ -42,9 +59,13 @@ const MAX_POSITION_TIME: u64 = 10;
 /// * `REPLICATION CLIENT` - to use SHOW MASTER STATUS, SHOW SLAVE STATUS, and SHOW BINARY LOGS;
 ///
 /// The connector must also be assigned a unique `server_id` value
+///
+/// MariaDB upstreams are supported as well; see [`MySqlFlavor`].
 pub(crate) struct MySqlBinlogConnector {
     /// This is the underlying (regular) MySQL connection
     connection: mysql::Conn,
+    /// Whether the upstream is MySQL or MariaDB, which determines the binlog events we expect
+    flavor: MySqlFlavor,
     /// Reader is a decoder for binlog events
     reader: binlog::EventStreamReader,
     /// The binlog "slave" must be assigned a unique `server_id` in the replica topology
@@ -53,13 +74,23 @@ pub(crate) struct MySqlBinlogConnector {
     /// If we just want to continue reading the binlog from a previous point
     next_position: MySqlPosition,
     /// The GTID of the current transaction. Table modification events will have
-    /// the current GTID attached if enabled in mysql.
+    /// the current GTID attached if enabled in mysql. For MariaDB, this is the sequence number of
+    /// the transaction's GTID.
     current_gtid: Option<u64>,
     /// Whether to log statements received by the connector
     enable_statement_logging: bool,
     /// Timestamp of the last reported position. This is use to ensure we keep the distance
     /// between min/max position as short as possible.
     last_reported_pos_ts: std::time::Instant,
+    /// A second connection to a MariaDB upstream, used to look up the `UUID` columns of tables,
+    /// since `connection` is busy streaming the binlog. `None` for MySQL upstreams.
+    metadata_connection: Option<mysql::Conn>,
+    /// The positions of the `UUID` columns of each table we've seen rows for, along with the ID of
+    /// the table map they were looked up for. MariaDB writes `UUID` values to the binlog in their
+    /// binary storage format, and the table map events don't tell `UUID` columns apart from
+    /// `BINARY(16)` columns, so these are looked up in the schema. The server assigns a new table
+    /// ID whenever a table's definition changes, so each ID stands for one version of the table.
+    uuid_columns: HashMap<Relation, (u64, Vec<usize>)>,
 }
 
 impl MySqlBinlogConnector {
@@ -73,7 +104,14 @@ impl MySqlBinlogConnector {
     /// know what type of checksum we support (NONE and CRC32 are the options), NONE seems to work
     /// but others use CRC32 🤷‍♂️
     async fn register_as_replica(&mut self) -> mysql::Result<()> {
-        self.connection.query_drop(CHECKSUM_QUERY).await?;
+        match self.flavor {
+            MySqlFlavor::MySQL => self.connection.query_drop(CHECKSUM_QUERY).await?,
+            MySqlFlavor::MariaDB => {
+                self.connection.query_drop(MARIADB_CHECKSUM_QUERY).await?;
+                self.connection.query_drop(MARIADB_CAPABILITY_QUERY).await?;
+            }
+        }
+        self.connection.query_drop(HEARTBEAT_QUERY).await?;
 
         let cmd = mysql_common::packets::ComRegisterSlave::new(self.server_id());
         self.connection.write_command(&cmd).await?;
@@ -129,8 +167,16 @@ impl MySqlBinlogConnector {
         server_id: Option<u32>,
         enable_statement_logging: bool,
     ) -> ReadySetResult<Self> {
+        let mysql_opts: mysql::Opts = mysql_opts.into();
+        let mut connection = mysql::Conn::new(mysql_opts.clone()).await?;
+        let flavor = MySqlFlavor::detect(&mut connection).await?;
+        let metadata_connection = match flavor {
+            MySqlFlavor::MySQL => None,
+            MySqlFlavor::MariaDB => Some(mysql::Conn::new(mysql_opts).await?),
+        };
         let mut connector = MySqlBinlogConnector {
-            connection: mysql::Conn::new(mysql_opts).await?,
+            connection,
+            flavor,
             reader: binlog::EventStreamReader::new(binlog::consts::BinlogVersion::Version4),
             server_id,
             next_position,
@@ -138,6 +184,8 @@ impl MySqlBinlogConnector {
             enable_statement_logging,
             last_reported_pos_ts: std::time::Instant::now()
                 - std::time::Duration::from_secs(MAX_POSITION_TIME),
+            metadata_connection,
+            uuid_columns: HashMap::new(),
         };
 
         connector.register_as_replica().await?;
@@ -225,24 +273,32 @@ impl MySqlBinlogConnector {
     ///
     /// # Arguments
     ///
-    /// * `wr_event` - the write rows event to process
+    /// * `wr_event` - the write rows event to process, either a WRITE_ROWS_EVENT or (for MariaDB) a
+    ///   WRITE_ROWS_EVENT_V1
     async fn process_event_write_rows(
         &mut self,
-        wr_event: mysql_common::binlog::events::WriteRowsEvent<'_>,
+        wr_event: RowsEventData<'_>,
     ) -> mysql::Result<ReplicationAction> {
         if self.enable_statement_logging {
             info!(target: "replicator_statement", "{:?}", wr_event);
         }
         // Retrieve the corresponding TABLE_MAP_EVENT
-        let tme = self.reader.get_tme(wr_event.table_id()).ok_or_else(|| {
-            mysql_async::Error::Other(Box::new(internal_err!(
-                "TME not found for WRITE_ROWS_EVENT"
-            )))
-        })?;
+        let tme = self
+            .reader
+            .get_tme(wr_event.table_id())
+            .cloned()
+            .ok_or_else(|| {
+                mysql_async::Error::Other(Box::new(internal_err!(
+                    "TME not found for WRITE_ROWS_EVENT"
+                )))
+            })?;
+        let Some(uuid_columns) = self.uuid_columns(&tme).await? else {
+            return Ok(resnapshot_table_action(&tme));
+        };
 
         let mut inserted_rows = Vec::new();
 
-        for row in wr_event.rows(tme) {
+        for row in wr_event.rows(&tme) {
             // For each row in the event we produce a vector of ReadySet types that
             // represent that row
             inserted_rows.push(readyset_client::TableOperation::Insert(
@@ -252,7 +308,8 @@ impl MySqlBinlogConnector {
                             "Missing data in WRITE_ROWS_EVENT"
                         )))
                     })?,
-                    tme,
+                    &tme,
+                    &uuid_columns,
                 )?,
             ));
         }
@@ -272,25 +329,33 @@ impl MySqlBinlogConnector {
     ///
     /// # Arguments
     ///
-    /// * `ur_event` - the update rows event to process
+    /// * `ur_event` - the update rows event to process, either an UPDATE_ROWS_EVENT or (for
+    ///   MariaDB) an UPDATE_ROWS_EVENT_V1
     async fn process_event_update_rows(
         &mut self,
-        ur_event: mysql_common::binlog::events::UpdateRowsEvent<'_>,
+        ur_event: RowsEventData<'_>,
     ) -> mysql::Result<ReplicationAction> {
         if self.enable_statement_logging {
             info!(target: "replicator_statement", "{:?}", ur_event);
         }
         // Retrieve the corresponding TABLE_MAP_EVENT
-        let tme = self.reader.get_tme(ur_event.table_id()).ok_or_else(|| {
-            mysql_async::Error::Other(Box::new(internal_err!(
-                "TME not found for UPDATE_ROWS_EVENT {:?}",
-                ur_event
-            )))
-        })?;
+        let tme = self
+            .reader
+            .get_tme(ur_event.table_id())
+            .cloned()
+            .ok_or_else(|| {
+                mysql_async::Error::Other(Box::new(internal_err!(
+                    "TME not found for UPDATE_ROWS_EVENT {:?}",
+                    ur_event
+                )))
+            })?;
+        let Some(uuid_columns) = self.uuid_columns(&tme).await? else {
+            return Ok(resnapshot_table_action(&tme));
+        };
 
         let mut updated_rows = Vec::new();
 
-        for row in ur_event.rows(tme) {
+        for row in ur_event.rows(&tme) {
             // For each row in the event we produce a pair of ReadySet table operations
             // to delete the previous entry and insert the new
             // one
@@ -303,7 +368,8 @@ impl MySqlBinlogConnector {
                             row
                         )))
                     })?,
-                    tme,
+                    &tme,
+                    &uuid_columns,
                 )?,
             });
 
@@ -315,7 +381,8 @@ impl MySqlBinlogConnector {
                             row
                         )))
                     })?,
-                    tme,
+                    &tme,
+                    &uuid_columns,
                 )?,
             ));
         }
@@ -335,25 +402,33 @@ impl MySqlBinlogConnector {
     ///
     /// # Arguments
     ///
-    /// * `dr_event` - the delete rows event to process
+    /// * `dr_event` - the delete rows event to process, either a DELETE_ROWS_EVENT or (for MariaDB)
+    ///   a DELETE_ROWS_EVENT_V1
     async fn process_event_delete_rows(
         &mut self,
-        dr_event: mysql_common::binlog::events::DeleteRowsEvent<'_>,
+        dr_event: RowsEventData<'_>,
     ) -> mysql::Result<ReplicationAction> {
         if self.enable_statement_logging {
             info!(target: "replicator_statement", "{:?}", dr_event);
         }
         // Retrieve the corresponding TABLE_MAP_EVENT
-        let tme = self.reader.get_tme(dr_event.table_id()).ok_or_else(|| {
-            mysql_async::Error::Other(Box::new(internal_err!(
-                "TME not found for UPDATE_ROWS_EVENT {:?}",
-                dr_event
-            )))
-        })?;
+        let tme = self
+            .reader
+            .get_tme(dr_event.table_id())
+            .cloned()
+            .ok_or_else(|| {
+                mysql_async::Error::Other(Box::new(internal_err!(
+                    "TME not found for UPDATE_ROWS_EVENT {:?}",
+                    dr_event
+                )))
+            })?;
+        let Some(uuid_columns) = self.uuid_columns(&tme).await? else {
+            return Ok(resnapshot_table_action(&tme));
+        };
 
         let mut deleted_rows = Vec::new();
 
-        for row in dr_event.rows(tme) {
+        for row in dr_event.rows(&tme) {
             // For each row in the event we produce a vector of ReadySet types that
             // represent that row
             deleted_rows.push(readyset_client::TableOperation::DeleteRow {
@@ -363,7 +438,8 @@ impl MySqlBinlogConnector {
                             "Missing data in DELETE_ROWS_EVENT"
                         )))
                     })?,
-                    tme,
+                    &tme,
+                    &uuid_columns,
                 )?,
             });
         }
@@ -416,7 +492,7 @@ impl MySqlBinlogConnector {
             _ => return self.try_non_ddl_action_from_query(q_event, is_last),
         };
 
-        let changes = match ChangeList::from_str(q_event.query(), Dialect::DEFAULT_MYSQL) {
+        let mut changes = match ChangeList::from_str(q_event.query(), Dialect::DEFAULT_MYSQL) {
             Ok(changelist) => changelist.changes,
             Err(error) => {
                 warn!(%error, "Error extending recipe, DDL statement will not be used");
@@ -427,9 +503,67 @@ impl MySqlBinlogConnector {
             }
         };
 
+        if self.flavor.is_mariadb() {
+            for change in &mut changes {
+                mariadb::uuid_columns_as_char(change);
+            }
+            // The statement may have changed the columns of any table
+            self.uuid_columns.clear();
+        }
+
         Ok(ReplicationAction::DdlChange { schema, changes })
     }
 
+    /// Returns the positions of the MariaDB `UUID` columns of the table the given table map event
+    /// is for, looking them up on the upstream the first time we see rows for this version of the
+    /// table.
+    ///
+    /// The upstream can only tell us the table's current columns, which are no longer the ones the
+    /// rows were written with if the table was altered since. Returns `None` if the number of
+    /// columns doesn't match the table map, in which case the table has to be resnapshotted.
+    async fn uuid_columns(
+        &mut self,
+        tme: &binlog::events::TableMapEvent<'_>,
+    ) -> mysql::Result<Option<Vec<usize>>> {
+        let Some(metadata_connection) = &mut self.metadata_connection else {
+            return Ok(Some(vec![]));
+        };
+        let table = Relation {
+            schema: Some(tme.database_name().into()),
+            name: tme.table_name().into(),
+        };
+        if let Some((table_id, columns)) = self.uuid_columns.get(&table) {
+            if *table_id == tme.table_id() {
+                return Ok(Some(columns.clone()));
+            }
+        }
+
+        let data_types: Vec<String> = metadata_connection
+            .exec(
+                "SELECT DATA_TYPE FROM information_schema.COLUMNS \
+                 WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+                (&*tme.database_name(), &*tme.table_name()),
+            )
+            .await?;
+        if data_types.len() as u64 != tme.columns_count() {
+            warn!(
+                table = %table.display_unquoted(),
+                "Table was altered since its rows were written to the binlog"
+            );
+            return Ok(None);
+        }
+
+        let columns = data_types
+            .iter()
+            .enumerate()
+            .filter(|(_, data_type)| data_type.eq_ignore_ascii_case("uuid"))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        self.uuid_columns
+            .insert(table, (tme.table_id(), columns.clone()));
+        Ok(Some(columns))
+    }
+
     /// Attempt to produce a non-DDL [`ReplicationAction`] from the give query.
     ///
     /// `COMMIT` queries are issued for writes on non-transactional storage engines such as MyISAM.
@@ -572,7 +706,9 @@ impl MySqlBinlogConnector {
                 }
                 EventType::WRITE_ROWS_EVENT => {
                     let binlog_action = self
-                        .process_event_write_rows(binlog_ev.read_event()?)
+                        .process_event_write_rows(RowsEventData::WriteRowsEvent(
+                            binlog_ev.read_event()?,
+                        ))
                         .await?;
                     self.merge_table_actions(&mut hash_actions, binlog_action)
                         .await;
@@ -580,7 +716,9 @@ impl MySqlBinlogConnector {
 
                 EventType::UPDATE_ROWS_EVENT => {
                     let binlog_action = self
-                        .process_event_update_rows(binlog_ev.read_event()?)
+                        .process_event_update_rows(RowsEventData::UpdateRowsEvent(
+                            binlog_ev.read_event()?,
+                        ))
                         .await?;
                     self.merge_table_actions(&mut hash_actions, binlog_action)
                         .await;
@@ -588,7 +726,9 @@ impl MySqlBinlogConnector {
 
                 EventType::DELETE_ROWS_EVENT => {
                     let binlog_action = self
-                        .process_event_delete_rows(binlog_ev.read_event()?)
+                        .process_event_delete_rows(RowsEventData::DeleteRowsEvent(
+                            binlog_ev.read_event()?,
+                        ))
                         .await?;
                     self.merge_table_actions(&mut hash_actions, binlog_action)
                         .await;
@@ -628,6 +768,72 @@ impl MySqlBinlogConnector {
         false
     }
 
+    /// Process a MariaDB-specific binlog event, which `mysql_common` does not know how to decode.
+    ///
+    /// The only such event we act on is MARIADB_GTID_EVENT, which marks the start of a transaction
+    /// (much like MySQL's GTID_EVENT) and whose post-header consists of the 8-byte sequence number
+    /// followed by the 4-byte replication domain ID. The server ID of the GTID is the server ID of
+    /// the event itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - the event type code of the event
+    /// * `event` - the raw binlog event
+    fn process_mariadb_event(
+        &mut self,
+        code: u8,
+        event: &binlog::events::Event,
+    ) -> mysql::Result<()> {
+        match code {
+            MARIADB_GTID_EVENT => {
+                let data = event.data();
+                let (Some(sequence_number), Some(domain_id)) = (
+                    data.get(..8)
+                        .map(|b| u64::from_le_bytes(b.try_into().expect("8 bytes"))),
+                    data.get(8..12)
+                        .map(|b| u32::from_le_bytes(b.try_into().expect("4 bytes"))),
+                ) else {
+                    return Err(mysql_async::Error::Other(Box::new(internal_err!(
+                        "Malformed MARIADB_GTID_EVENT"
+                    ))));
+                };
+                if self.enable_statement_logging {
+                    // MariaDB GTIDs are written as `domain-server-sequence`
+                    let gtid = format!(
+                        "{domain_id}-{}-{sequence_number}",
+                        event.header().server_id()
+                    );
+                    info!(target: "replicator_statement", %gtid, "MARIADB_GTID_EVENT");
+                }
+                self.current_gtid = Some(sequence_number);
+            }
+            MARIADB_START_ENCRYPTION_EVENT => {
+                return Err(mysql_async::Error::Other(Box::new(
+                    ReadySetError::ReplicationFailed(
+                        "Encrypted MariaDB binlogs are not supported".to_string(),
+                    ),
+                )));
+            }
+            MARIADB_QUERY_COMPRESSED_EVENT..=MARIADB_DELETE_ROWS_COMPRESSED_EVENT => {
+                return Err(mysql_async::Error::Other(Box::new(
+                    ReadySetError::ReplicationFailed(
+                        "Compressed MariaDB binlog events are not supported; \
+                         set log_bin_compress to OFF"
+                            .to_string(),
+                    ),
+                )));
+            }
+            // ANNOTATE_ROWS_EVENT, BINLOG_CHECKPOINT_EVENT, GTID_LIST_EVENT and any other events
+            // don't affect replication
+            _ => {
+                if self.enable_statement_logging {
+                    info!(target: "replicator_statement", code, "unhandled MariaDB event");
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Process binlog events until an actionable event occurs.
     ///
     /// # Arguments
@@ -660,12 +866,23 @@ impl MySqlBinlogConnector {
                 }
                 None => false,
             };
-            match binlog_event.header().event_type().map_err(|ev| {
-                mysql_async::Error::Other(Box::new(internal_err!(
-                    "Unknown binlog event type {}",
-                    ev
-                )))
-            })? {
+            let event_type = match binlog_event.header().event_type() {
+                Ok(event_type) => event_type,
+                Err(UnknownEventType(code)) if self.flavor.is_mariadb() => {
+                    self.process_mariadb_event(code, &binlog_event)?;
+                    if is_last {
+                        return Ok((vec![ReplicationAction::LogPosition], &self.next_position));
+                    }
+                    continue;
+                }
+                Err(ev) => {
+                    return Err(mysql_async::Error::Other(Box::new(internal_err!(
+                        "Unknown binlog event type {}",
+                        ev
+                    ))))
+                }
+            };
+            match event_type {
                 EventType::ROTATE_EVENT => {
                     return Ok((
                         vec![
@@ -712,8 +929,10 @@ impl MySqlBinlogConnector {
                 EventType::WRITE_ROWS_EVENT => {
                     return Ok((
                         vec![
-                            self.process_event_write_rows(binlog_event.read_event()?)
-                                .await?,
+                            self.process_event_write_rows(RowsEventData::WriteRowsEvent(
+                                binlog_event.read_event()?,
+                            ))
+                            .await?,
                         ],
                         &self.next_position,
                     ));
@@ -722,8 +941,10 @@ impl MySqlBinlogConnector {
                 EventType::UPDATE_ROWS_EVENT => {
                     return Ok((
                         vec![
-                            self.process_event_update_rows(binlog_event.read_event()?)
-                                .await?,
+                            self.process_event_update_rows(RowsEventData::UpdateRowsEvent(
+                                binlog_event.read_event()?,
+                            ))
+                            .await?,
                         ],
                         &self.next_position,
                     ));
@@ -732,8 +953,10 @@ impl MySqlBinlogConnector {
                 EventType::DELETE_ROWS_EVENT => {
                     return Ok((
                         vec![
-                            self.process_event_delete_rows(binlog_event.read_event()?)
-                                .await?,
+                            self.process_event_delete_rows(RowsEventData::DeleteRowsEvent(
+                                binlog_event.read_event()?,
+                            ))
+                            .await?,
                         ],
                         &self.next_position,
                     ));
@@ -756,15 +979,51 @@ impl MySqlBinlogConnector {
                     continue;
                 }
 
-                EventType::WRITE_ROWS_EVENT_V1 => unimplemented!(), /* The V1 event numbers are */
-                // used from 5.1.16 until
-                // mysql-5.6.
-                EventType::UPDATE_ROWS_EVENT_V1 => unimplemented!(), /* The V1 event numbers are */
-                // used from 5.1.16 until
-                // mysql-5.6.
-                EventType::DELETE_ROWS_EVENT_V1 => unimplemented!(), /* The V1 event numbers are */
-                // used from 5.1.16 until
-                // mysql-5.6.
+                // The V1 event numbers are used from MySQL 5.1.16 until MySQL 5.6, and are still
+                // used by MariaDB.
+                EventType::WRITE_ROWS_EVENT_V1 => {
+                    return Ok((
+                        vec![
+                            self.process_event_write_rows(RowsEventData::WriteRowsEventV1(
+                                binlog_event.read_event()?,
+                            ))
+                            .await?,
+                        ],
+                        &self.next_position,
+                    ));
+                }
+
+                EventType::UPDATE_ROWS_EVENT_V1 => {
+                    return Ok((
+                        vec![
+                            self.process_event_update_rows(RowsEventData::UpdateRowsEventV1(
+                                binlog_event.read_event()?,
+                            ))
+                            .await?,
+                        ],
+                        &self.next_position,
+                    ));
+                }
+
+                EventType::DELETE_ROWS_EVENT_V1 => {
+                    return Ok((
+                        vec![
+                            self.process_event_delete_rows(RowsEventData::DeleteRowsEventV1(
+                                binlog_event.read_event()?,
+                            ))
+                            .await?,
+                        ],
+                        &self.next_position,
+                    ));
+                }
+
+                EventType::HEARTBEAT_EVENT => {
+                    // The primary only sends heartbeats once it has sent us every event in the
+                    // binlog, so we're caught up to its current position even if none of the
+                    // events since we last reported it changed any replicated tables
+                    return Ok((vec![ReplicationAction::LogPosition], &self.next_position));
+                }
+
                 EventType::GTID_EVENT => {
                     // GTID stands for Global Transaction Identifier It is composed of two parts:
                     // SID for Source Identifier, and GNO for Group Number. The basic idea is to
@@ -795,8 +1054,7 @@ impl MySqlBinlogConnector {
                 EventType::START_EVENT_V3 // Old version of FORMAT_DESCRIPTION_EVENT
                 | EventType::FORMAT_DESCRIPTION_EVENT // A descriptor event that is written to the beginning of each binary log file. This event is used as of MySQL 5.0; it supersedes START_EVENT_V3.
                 | EventType::STOP_EVENT // Written when mysqld stops
-                | EventType::INCIDENT_EVENT // The event is used to inform the slave that something out of the ordinary happened on the master that might cause the database to be in an inconsistent state.
-                | EventType::HEARTBEAT_EVENT => {} // The event is originated by master's dump thread and sent straight to slave without being logged. Slave itself does not store it in relay log but rather uses a data for immediate checks and throws away the event.
+                | EventType::INCIDENT_EVENT => {} // The event is used to inform the slave that something out of the ordinary happened on the master that might cause the database to be in an inconsistent state.
 
                 EventType::UNKNOWN_EVENT | EventType::SLAVE_EVENT => {} // Ignored events
 
@@ -897,13 +1155,39 @@ fn binlog_val_to_noria_val(
     }
 }
 
+/// Returns an action to resnapshot the table the given table map event is for, used when we can't
+/// tell how to decode its rows.
+fn resnapshot_table_action(tme: &binlog::events::TableMapEvent<'_>) -> ReplicationAction {
+    ReplicationAction::ResnapshotTable {
+        table: Relation {
+            schema: Some(tme.database_name().into()),
+            name: tme.table_name().into(),
+        },
+        absorbed: vec![],
+    }
+}
+
+/// Converts a row from a rows event into ReadySet values. The values of the columns at
+/// `uuid_columns` are decoded from MariaDB's binary `UUID` format into text.
 fn binlog_row_to_noria_row(
     binlog_row: &BinlogRow,
     tme: &binlog::events::TableMapEvent<'static>,
+    uuid_columns: &[usize],
 ) -> mysql::Result<Vec<DfValue>> {
     (0..binlog_row.len())
         .map(|idx| {
             match binlog_row.as_ref(idx).unwrap() {
+                BinlogValue::Value(mysql_common::value::Value::Bytes(bytes))
+                    if uuid_columns.contains(&idx) =>
+                {
+                    mariadb::uuid_record_to_string(bytes)
+                        .map(DfValue::from)
+                        .ok_or_else(|| {
+                            mysql_async::Error::Other(Box::new(internal_err!(
+                                "Invalid UUID value in binlog"
+                            )))
+                        })
+                }
                 BinlogValue::Value(val) => {
                     let (kind, meta) = (
                         tme.get_column_type(idx)
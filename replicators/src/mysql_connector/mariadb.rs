@@ -0,0 +1,178 @@
+//! Support for MariaDB's native `UUID` column type.
+//!
+//! We don't support `UUID` columns in MySQL-dialect tables, but MariaDB sends `UUID` values to
+//! clients in their canonical 36-character text form, so we replicate them as `CHAR(36)` columns.
+//! This has to happen in every place a table's schema or rows reach ReadySet: when snapshotting,
+//! when replicating `CREATE TABLE` and `ALTER TABLE` statements, and when decoding the binlog,
+//! where `UUID` values are written in MariaDB's 16-byte storage format rather than as text.
+
+use nom_sql::{AlterTableDefinition, ColumnSpecification, SqlType};
+use readyset_client::recipe::changelist::Change;
+
+/// Rewrites the type of the given column to `CHAR(36)` if it's a `UUID` column. Returns `true` if
+/// the column was rewritten.
+pub(crate) fn uuid_column_as_char(spec: &mut ColumnSpecification) -> bool {
+    if spec.sql_type == SqlType::Uuid {
+        spec.sql_type = SqlType::Char(Some(36));
+        true
+    } else {
+        false
+    }
+}
+
+/// Rewrites any `UUID` columns created by the given `CREATE TABLE` or `ALTER TABLE` change to
+/// `CHAR(36)`. Returns `true` if any column was rewritten.
+pub(crate) fn uuid_columns_as_char(change: &mut Change) -> bool {
+    let mut rewritten = false;
+    match change {
+        Change::CreateTable { statement, .. } => {
+            if let Ok(body) = &mut statement.body {
+                for field in &mut body.fields {
+                    rewritten |= uuid_column_as_char(field);
+                }
+            }
+        }
+        Change::AlterTable(statement) => {
+            if let Ok(definitions) = &mut statement.definitions {
+                for definition in definitions {
+                    match definition {
+                        AlterTableDefinition::AddColumn(spec)
+                        | AlterTableDefinition::ChangeColumn { spec, .. } => {
+                            rewritten |= uuid_column_as_char(spec);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    rewritten
+}
+
+/// Decodes a `UUID` value as written to the binlog into its canonical text form, or returns `None`
+/// if the value isn't a valid `UUID` record.
+///
+/// MariaDB stores RFC 4122 UUIDs of versions 1 through 5 with their five segments in reverse
+/// order, so that time-based UUIDs sort by time, and all other UUIDs as they are. The check for
+/// which of these applies mirrors the one MariaDB makes when reading a record. Trailing zero bytes
+/// may be stripped from the value, as for any fixed-length binary column.
+pub(crate) fn uuid_record_to_string(record: &[u8]) -> Option<String> {
+    if record.len() > 16 {
+        return None;
+    }
+    let mut bytes = [0u8; 16];
+    bytes[..record.len()].copy_from_slice(record);
+
+    let mut uuid = bytes;
+    if bytes[2] > 0 && bytes[2] < 0x60 && bytes[0] & 0x80 != 0 {
+        // The record is laid out as clock sequence (2 bytes), time high and version (2), time mid
+        // (2), time low (4), then node (6)
+        uuid[0..4].copy_from_slice(&bytes[6..10]);
+        uuid[4..6].copy_from_slice(&bytes[4..6]);
+        uuid[6..8].copy_from_slice(&bytes[2..4]);
+        uuid[8..10].copy_from_slice(&bytes[0..2]);
+    }
+
+    let hex = |range: std::ops::Range<usize>| {
+        uuid[range]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>()
+    };
+    Some(format!(
+        "{}-{}-{}-{}-{}",
+        hex(0..4),
+        hex(4..6),
+        hex(6..8),
+        hex(8..10),
+        hex(10..16)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use readyset_client::recipe::changelist::ChangeList;
+
+    use super::*;
+
+    fn change(sql: &str) -> Change {
+        ChangeList::from_str(sql, readyset_data::Dialect::DEFAULT_MYSQL)
+            .unwrap()
+            .changes
+            .remove(0)
+    }
+
+    #[test]
+    fn create_table_uuid_columns_as_char() {
+        let mut create_table = change("CREATE TABLE t (id int, u uuid)");
+        assert!(uuid_columns_as_char(&mut create_table));
+        let Change::CreateTable { statement, .. } = create_table else {
+            panic!("Expected CREATE TABLE")
+        };
+        let fields = statement.body.unwrap().fields;
+        assert_eq!(fields[0].sql_type, SqlType::Int(None));
+        assert_eq!(fields[1].sql_type, SqlType::Char(Some(36)));
+    }
+
+    #[test]
+    fn alter_table_uuid_columns_as_char() {
+        let mut alter_table =
+            change("ALTER TABLE t ADD COLUMN u uuid, CHANGE COLUMN v w uuid, DROP COLUMN x");
+        assert!(uuid_columns_as_char(&mut alter_table));
+        let Change::AlterTable(statement) = alter_table else {
+            panic!("Expected ALTER TABLE")
+        };
+        let definitions = statement.definitions.unwrap();
+        assert!(matches!(
+            &definitions[0],
+            AlterTableDefinition::AddColumn(spec) if spec.sql_type == SqlType::Char(Some(36))
+        ));
+        assert!(matches!(
+            &definitions[1],
+            AlterTableDefinition::ChangeColumn { spec, .. }
+                if spec.sql_type == SqlType::Char(Some(36))
+        ));
+    }
+
+    #[test]
+    fn tables_without_uuid_columns_unchanged() {
+        let mut create_table = change("CREATE TABLE t (id int)");
+        assert!(!uuid_columns_as_char(&mut create_table));
+    }
+
+    #[test]
+    fn swapped_uuid_record() {
+        // The record for the version 1 UUID 6ccd780c-baba-1026-9564-5b8c656024db
+        let record = [
+            0x95, 0x64, 0x10, 0x26, 0xba, 0xba, 0x6c, 0xcd, 0x78, 0x0c, 0x5b, 0x8c, 0x65, 0x60,
+            0x24, 0xdb,
+        ];
+        assert_eq!(
+            uuid_record_to_string(&record).unwrap(),
+            "6ccd780c-baba-1026-9564-5b8c656024db"
+        );
+    }
+
+    #[test]
+    fn unswapped_uuid_record() {
+        // Version 7 UUIDs are stored as they are
+        let record = [
+            0x01, 0x8f, 0xd8, 0xf0, 0x6d, 0x2e, 0x7c, 0x3a, 0x9b, 0x1e, 0x4f, 0x2a, 0x11, 0x22,
+            0x33, 0x44,
+        ];
+        assert_eq!(
+            uuid_record_to_string(&record).unwrap(),
+            "018fd8f0-6d2e-7c3a-9b1e-4f2a11223344"
+        );
+    }
+
+    #[test]
+    fn uuid_record_with_stripped_zeros() {
+        assert_eq!(
+            uuid_record_to_string(&[0x01, 0x8f]).unwrap(),
+            "018f0000-0000-0000-0000-000000000000"
+        );
+        assert_eq!(uuid_record_to_string(&[0; 17]), None);
+    }
+}
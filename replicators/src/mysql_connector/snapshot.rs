@@ -4,6 +4,7 @@ use std::fmt::{self, Display};
 use std::future;
 use std::time::Instant;
 
+use database_utils::MySqlFlavor;
 use futures::future::TryFutureExt;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
@@ -21,6 +22,7 @@ use tokio::task::JoinHandle;
 use tracing::{debug, error, info, info_span, warn};
 use tracing_futures::Instrument;
 
+use super::mariadb;
 use crate::db_util::DatabaseSchemas;
 use crate::table_filter::TableFilter;
 use crate::TablesSnapshottingGaugeGuard;
@@ -48,6 +50,8 @@ pub enum TableKind {
 pub(crate) struct MySqlReplicator {
     /// This is the underlying (regular) MySQL connection
     pub(crate) pool: mysql::Pool,
+    /// Whether the upstream is MySQL or MariaDB
+    pub(crate) flavor: MySqlFlavor,
     /// Filters out the desired tables to snapshot and replicate
     pub(crate) table_filter: TableFilter,
 }
@@ -145,7 +149,7 @@ impl MySqlReplicator {
         let mut tx = self.pool.start_transaction(tx_opts()).await?;
 
         let _ = tx
-            .query_drop("SET SESSION MAX_EXECUTION_TIME=0")
+            .query_drop(self.disable_statement_timeout_query())
             .await
             .map_err(log_err);
 
@@ -206,6 +210,10 @@ impl MySqlReplicator {
         for (db, table) in replicated_tables.iter() {
             let res = create_for_table(&mut tx, db, table, TableKind::BaseTable)
                 .map_err(|e| e.into())
+                .map_ok(|create_table| match self.flavor {
+                    MySqlFlavor::MySQL => create_table,
+                    MySqlFlavor::MariaDB => mariadb_uuid_columns_as_char(create_table),
+                })
                 .and_then(|create_table| {
                     debug!(%create_table, "Extending recipe");
                     db_schemas.extend_create_schema_for_table(
@@ -331,7 +339,7 @@ impl MySqlReplicator {
             .map_err(log_err)?;
 
         let _ = tx
-            .query_drop("SET SESSION MAX_EXECUTION_TIME=0")
+            .query_drop(self.disable_statement_timeout_query())
             .await
             .map_err(log_err);
 
@@ -347,25 +355,37 @@ impl MySqlReplicator {
         })
     }
 
+    /// Returns the statement that disables the server-side statement timeout for the current
+    /// session, so that dumping large tables doesn't time out
+    fn disable_statement_timeout_query(&self) -> &'static str {
+        match self.flavor {
+            MySqlFlavor::MySQL => "SET SESSION MAX_EXECUTION_TIME=0",
+            MySqlFlavor::MariaDB => "SET SESSION max_statement_time=0",
+        }
+    }
+
     /// Get MySQL Server Version
     async fn get_mysql_version(&self) -> mysql::Result<u32> {
         let mut conn = self.pool.get_conn().await?;
         let version: mysql::Row = conn.query_first("SELECT VERSION()").await?.unwrap();
         let version: String = version.get(0).expect("MySQL version");
-        let version_parts: Vec<&str> = version.split('.').collect();
-        let major = version_parts[0].parse::<u32>().unwrap();
-        let minor = version_parts[1].parse::<u32>().unwrap();
-        let patch = version_parts[2].parse::<u32>().unwrap();
-        Ok(major * 10000 + minor * 100 + patch)
+        MySqlFlavor::parse_version_number(&version).ok_or_else(|| {
+            mysql_async::Error::Other(Box::new(internal_err!(
+                "Could not parse server version {version}"
+            )))
+        })
     }
 
     /// Use the SHOW MASTER STATUS or SHOW BINARY LOG STATUS statement to determine
     /// the current binary log file name and position.
     async fn get_binlog_position(&self) -> mysql::Result<MySqlPosition> {
         let mut conn = self.pool.get_conn().await?;
-        let query = match self.get_mysql_version().await {
-            Ok(version) => {
-                if version >= 80400 {
+        let query = match self.flavor {
+            // MariaDB supports SHOW MASTER STATUS in all versions, and its version numbers are not
+            // comparable with MySQL's
+            MySqlFlavor::MariaDB => "SHOW MASTER STATUS",
+            MySqlFlavor::MySQL => {
+                if self.get_mysql_version().await? >= 80400 {
                     // MySQL 8.4.0 and above
                     "SHOW BINARY LOG STATUS"
                 } else {
@@ -373,9 +393,6 @@ impl MySqlReplicator {
                     "SHOW MASTER STATUS"
                 }
             }
-            Err(err) => {
-                return Err(err);
-            }
         };
 
         let pos: mysql::Row = conn.query_first(query).await?.ok_or_else(|| {
@@ -488,7 +505,7 @@ impl MySqlReplicator {
     /// If `full_snapshot` is set to `true`, *all* tables will be snapshotted, even those that
     /// already have replication offsets in ReadySet.
     pub(crate) async fn snapshot_to_noria(
-        mut self,
+        &mut self,
         noria: &mut readyset_client::ReadySetHandle,
         db_schemas: &mut DatabaseSchemas,
         snapshot_report_interval_secs: u16,
@@ -505,7 +522,7 @@ impl MySqlReplicator {
             .await;
 
         // Wait for all connections to finish, not strictly necessary
-        self.pool.disconnect().await?;
+        self.pool.clone().disconnect().await?;
         result
     }
 
@@ -747,6 +764,32 @@ impl TableDumper {
     }
 }
 
+/// Rewrite any MariaDB `UUID` columns in the given `CREATE TABLE` statement to `CHAR(36)`, so that
+/// the table can be snapshotted with the values stored as text (see [`mariadb`]).
+///
+/// If the statement can't be parsed it's returned unchanged, so that the error is reported when
+/// the table is added to the recipe.
+fn mariadb_uuid_columns_as_char(create_table: String) -> String {
+    let Ok(mut stmt) = nom_sql::parse_create_table(nom_sql::Dialect::MySQL, create_table.as_str())
+    else {
+        return create_table;
+    };
+    let Ok(body) = &mut stmt.body else {
+        return create_table;
+    };
+
+    let mut rewritten = false;
+    for field in &mut body.fields {
+        rewritten |= mariadb::uuid_column_as_char(field);
+    }
+
+    if rewritten {
+        stmt.display(nom_sql::Dialect::MySQL).to_string()
+    } else {
+        create_table
+    }
+}
+
 // Just another helper struct to make it streamable
 pub(crate) struct TableStream<'a> {
     query: mysql::QueryResult<'a, 'static, mysql::BinaryProtocol>,
@@ -807,3 +850,31 @@ impl Display for TableKind {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nom_sql::SqlType;
+
+    use super::*;
+
+    #[test]
+    fn mariadb_uuid_columns_rewritten_to_char() {
+        let rewritten = mariadb_uuid_columns_as_char(
+            "CREATE TABLE `t` (`id` int NOT NULL, `u` uuid DEFAULT NULL, PRIMARY KEY (`id`))"
+                .to_owned(),
+        );
+        let stmt = nom_sql::parse_create_table(nom_sql::Dialect::MySQL, rewritten).unwrap();
+        let fields = stmt.body.unwrap().fields;
+        assert_eq!(fields[0].sql_type, SqlType::Int(None));
+        assert_eq!(fields[1].sql_type, SqlType::Char(Some(36)));
+    }
+
+    #[test]
+    fn tables_without_uuid_columns_unchanged() {
+        let create_table = "CREATE TABLE `t` (`id` int NOT NULL, PRIMARY KEY (`id`))".to_owned();
+        assert_eq!(
+            mariadb_uuid_columns_as_char(create_table.clone()),
+            create_table
+        );
+    }
+}
@@ -3,13 +3,15 @@ use std::mem;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use database_utils::{DatabaseURL, UpstreamConfig};
+use database_utils::{DatabaseType, DatabaseURL, MySqlFlavor, UpstreamConfig};
 use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
 use failpoint_macros::set_failpoint;
 use metrics::{counter, histogram};
 use mysql::prelude::Queryable;
 use mysql::{OptsBuilder, PoolConstraints, PoolOpts, SslOpts};
-use nom_sql::{DialectDisplay, NonReplicatedRelation, NotReplicatedReason, Relation};
+use nom_sql::{
+    CreateTableStatement, DialectDisplay, NonReplicatedRelation, NotReplicatedReason, Relation,
+};
 use postgres_native_tls::MakeTlsConnector;
 use postgres_protocol::escape::escape_literal;
 use readyset_client::consistency::Timestamp;
@@ -22,11 +24,13 @@ use readyset_client::{ReadySetHandle, Table, TableOperation};
 use readyset_data::Dialect;
 use readyset_errors::{internal_err, set_failpoint_return_err, ReadySetError, ReadySetResult};
 use readyset_telemetry_reporter::{TelemetryBuilder, TelemetryEvent, TelemetrySender};
+use replication_offset::cdc::CdcPosition;
 use replication_offset::{ReplicationOffset, ReplicationOffsets};
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tracing::{debug, error, info, info_span, trace, warn, Instrument};
 use {mysql_async as mysql, tokio_postgres as pgsql};
 
+use crate::cdc_connector::{CdcConnector, CdcSource, TableSchema};
 use crate::db_util::{CreateSchema, DatabaseSchemas};
 use crate::mysql_connector::{MySqlBinlogConnector, MySqlReplicator};
 use crate::postgres_connector::{
@@ -40,6 +44,9 @@ use crate::{ControllerMessage, ReplicatorMessage};
 /// DDL changes from thrashing snapshotting
 const WAIT_BEFORE_RESNAPSHOT: Duration = Duration::from_secs(3);
 
+/// Time to wait before reconnecting to a change stream that was closed
+const WAIT_BEFORE_RECONNECT: Duration = Duration::from_secs(1);
+
 const RESNAPSHOT_SLOT: &str = "readyset_resnapshot";
 
 #[derive(Debug)]
@@ -57,6 +64,19 @@ pub(crate) enum ReplicationAction {
         schema: String,
         changes: Vec<Change>,
     },
+    /// The contents of the given table changed upstream in a way that isn't reflected in the
+    /// replication log, so it needs to be snapshotted again
+    ResnapshotTable {
+        table: Relation,
+        /// Tables that were replicated on their own until now but have become part of `table`,
+        /// and so need to be removed from ReadySet before it's snapshotted again
+        absorbed: Vec<Relation>,
+    },
+    /// The schema of a table changed in a stream of change events. The table needs to be created
+    /// again with the given statement, and replayed from the start of the stream.
+    ReplayTable {
+        statement: CreateTableStatement,
+    },
     LogPosition,
 }
 
@@ -83,6 +103,11 @@ pub(crate) trait Connector {
 /// Cleans up replication related assets on the upstream database as supplied by the
 /// UpstreamConfig.
 pub async fn cleanup(config: UpstreamConfig) -> ReadySetResult<()> {
+    if config.cdc_source.is_some() {
+        // Nothing is set up upstream when replicating from a change stream
+        return Ok(());
+    }
+
     if let DatabaseURL::PostgreSQL(options) = config
         .upstream_db_url
         .as_ref()
@@ -162,6 +187,52 @@ pub struct NoriaAdapter {
     table_filter: TableFilter,
     /// If the connector can partially resnapshot a database
     supports_resnapshot: bool,
+    /// A pool of connections to the upstream database, used to check that tables exist before
+    /// they're added to replication. `None` when replicating from a change stream, which has no
+    /// database to check against.
+    upstream_pool: Option<UpstreamPool>,
+}
+
+/// A pool of connections to the upstream database, for queries made outside of replication
+enum UpstreamPool {
+    MySql(mysql::Pool),
+    Postgres(Pool),
+}
+
+impl UpstreamPool {
+    /// Returns true if the given table exists in the upstream database
+    async fn table_exists(&self, table: &Relation) -> ReadySetResult<bool> {
+        let schema = table
+            .schema
+            .as_deref()
+            .ok_or_else(|| internal_err!("All tables should have a schema in the replicator"))?;
+        match self {
+            UpstreamPool::MySql(pool) => {
+                let exists: Option<u8> = pool
+                    .get_conn()
+                    .await?
+                    .exec_first(
+                        "SELECT 1 FROM information_schema.tables \
+                         WHERE table_schema = ? AND table_name = ?",
+                        (schema, table.name.as_str()),
+                    )
+                    .await?;
+                Ok(exists.is_some())
+            }
+            UpstreamPool::Postgres(pool) => {
+                let rows = pool
+                    .get()
+                    .await?
+                    .query(
+                        "SELECT 1 FROM information_schema.tables \
+                         WHERE table_schema = $1 AND table_name = $2",
+                        &[&schema, &table.name.as_str()],
+                    )
+                    .await?;
+                Ok(!rows.is_empty())
+            }
+        }
+    }
 }
 
 impl NoriaAdapter {
@@ -169,7 +240,7 @@ impl NoriaAdapter {
         noria: ReadySetHandle,
         mut config: UpstreamConfig,
         notification_channel: &UnboundedSender<ReplicatorMessage>,
-        controller_channel: &UnboundedReceiver<ControllerMessage>,
+        controller_channel: &mut UnboundedReceiver<ControllerMessage>,
         telemetry_sender: TelemetrySender,
         server_startup: bool,
         enable_statement_logging: bool,
@@ -178,6 +249,18 @@ impl NoriaAdapter {
         // replication-tables config parameter.
         let mut resnapshot = server_startup;
         let mut full_snapshot = false;
+
+        if let Some(source) = config.cdc_source.take() {
+            return NoriaAdapter::start_cdc(
+                CdcSource::parse(&source),
+                noria,
+                config,
+                notification_channel,
+                controller_channel,
+            )
+            .await;
+        }
+
         let url: DatabaseURL = config
             .upstream_db_url
             .take()
@@ -189,6 +272,24 @@ impl NoriaAdapter {
                 ))
             })?;
 
+        // The table filter is kept across restarts of the replicator, so that changes made to the
+        // set of replicated tables at runtime (via `ALTER READYSET`) survive a resnapshot
+        let mut table_filter = match &url {
+            DatabaseURL::MySQL(options) => TableFilter::try_new(
+                nom_sql::Dialect::MySQL,
+                config.replication_tables.take(),
+                config.replication_tables_ignore.take(),
+                options.db_name(),
+            )?,
+            DatabaseURL::PostgreSQL(_) => TableFilter::try_new(
+                nom_sql::Dialect::PostgreSQL,
+                config.replication_tables.take(),
+                config.replication_tables_ignore.take(),
+                None,
+            )?,
+        };
+        apply_replicated_table_changes(&noria, &mut table_filter).await?;
+
         while let Err(err) = match url.clone() {
             DatabaseURL::MySQL(options) => {
                 let noria = noria.clone();
@@ -199,6 +300,7 @@ impl NoriaAdapter {
                     config,
                     notification_channel,
                     controller_channel,
+                    &mut table_filter,
                     resnapshot,
                     &telemetry_sender,
                     enable_statement_logging,
@@ -240,6 +342,7 @@ impl NoriaAdapter {
                     config,
                     notification_channel,
                     controller_channel,
+                    &mut table_filter,
                     resnapshot,
                     full_snapshot,
                     &telemetry_sender,
@@ -275,6 +378,318 @@ impl NoriaAdapter {
         unreachable!("inner loop will never stop with an Ok status");
     }
 
+    /// Replicate from a stream of change events, restarting from the last applied position if
+    /// a table needs to be resnapshotted or the stream is closed.
+    ///
+    /// Change streams can't be snapshotted on demand, so tables that are added to replication,
+    /// dropped to be resnapshotted or whose schema changed are snapshotted by replaying the stream
+    /// from its start.
+    async fn start_cdc(
+        source: CdcSource,
+        noria: ReadySetHandle,
+        mut config: UpstreamConfig,
+        notification_channel: &UnboundedSender<ReplicatorMessage>,
+        controller_channel: &mut UnboundedReceiver<ControllerMessage>,
+    ) -> ReadySetResult<!> {
+        let dialect = match config.cdc_source_type {
+            DatabaseType::MySQL => nom_sql::Dialect::MySQL,
+            DatabaseType::PostgreSQL => nom_sql::Dialect::PostgreSQL,
+        };
+        let mut table_filter = TableFilter::try_new(
+            dialect,
+            config.replication_tables.take(),
+            config.replication_tables_ignore.take(),
+            None,
+        )?;
+        apply_replicated_table_changes(&noria, &mut table_filter).await?;
+        let mut resnapshot = false;
+
+        while let Err(err) = NoriaAdapter::start_inner_cdc(
+            &source,
+            noria.clone(),
+            dialect,
+            notification_channel,
+            controller_channel,
+            &mut table_filter,
+            resnapshot,
+        )
+        .await
+        {
+            match err {
+                ReadySetError::ResnapshotNeeded | ReadySetError::FullResnapshotNeeded => {
+                    tokio::time::sleep(WAIT_BEFORE_RESNAPSHOT).await;
+                    resnapshot = true;
+                }
+                // Named pipes and sockets are closed whenever the producer restarts, which then
+                // starts the stream over from the beginning
+                ReadySetError::UpstreamConnectionLost(_) | ReadySetError::IOError(_) => {
+                    warn!(error=%err, "Lost connection to change stream, reconnecting");
+                    tokio::time::sleep(WAIT_BEFORE_RECONNECT).await;
+                    resnapshot = false;
+                }
+                err => {
+                    warn!(error=%err, "Restarting adapter after error encountered");
+                    return Err(err);
+                }
+            }
+        }
+        unreachable!("inner loop will never stop with an Ok status");
+    }
+
+    /// Begin reading change events from `source`, from the minimum replication offset of any
+    /// table, catching each table up to the maximum offset before reporting that the snapshot is
+    /// done.
+    ///
+    /// If `resnapshot` is set, tables that are replicated but have no replication offset are first
+    /// snapshotted by replaying the stream up to that minimum offset.
+    async fn start_inner_cdc(
+        source: &CdcSource,
+        mut noria: ReadySetHandle,
+        dialect: nom_sql::Dialect,
+        notification_channel: &UnboundedSender<ReplicatorMessage>,
+        controller_channel: &mut UnboundedReceiver<ControllerMessage>,
+        table_filter: &mut TableFilter,
+        resnapshot: bool,
+    ) -> ReadySetResult<!> {
+        let replication_offsets = retry_with_exponential_backoff(
+            || async {
+                let mut noria = noria.clone();
+                noria.replication_offsets().await
+            },
+            5,
+            Duration::from_millis(250),
+        )
+        .await?;
+
+        let pos: CdcPosition = match replication_offsets.min_present_offset()? {
+            Some(pos) => pos.try_into()?,
+            None => CdcPosition::default(),
+        };
+
+        let snapshot = resnapshot && pos != CdcPosition::default();
+        let connector = Box::new(
+            CdcConnector::connect(
+                source,
+                if snapshot {
+                    CdcPosition::default()
+                } else {
+                    pos
+                },
+                dialect,
+                Self::cdc_table_schemas(&mut noria).await?,
+            )
+            .await?,
+        );
+
+        let mut adapter = NoriaAdapter {
+            noria: noria.clone(),
+            connector,
+            replication_offsets,
+            mutator_map: HashMap::new(),
+            warned_missing_tables: HashSet::new(),
+            table_filter: table_filter.clone(),
+            supports_resnapshot: false,
+            upstream_pool: None,
+            dialect: match dialect {
+                nom_sql::Dialect::MySQL => Dialect::DEFAULT_MYSQL,
+                nom_sql::Dialect::PostgreSQL => Dialect::DEFAULT_POSTGRESQL,
+            },
+        };
+
+        let mut current_pos: ReplicationOffset = pos.into();
+
+        if snapshot {
+            adapter.snapshot_cdc_tables(&current_pos).await?;
+            *table_filter = adapter.table_filter.clone();
+            let tables = Self::cdc_table_schemas(&mut adapter.noria).await?;
+            adapter.connector =
+                Box::new(CdcConnector::connect(source, pos, dialect, tables).await?);
+        }
+
+        match adapter.replication_offsets.max_offset()? {
+            Some(max) if max > &current_pos => {
+                info!(start = %current_pos, end = %max, "Catching up");
+                let max = max.clone();
+                adapter
+                    .main_loop(
+                        &mut current_pos,
+                        Some(max),
+                        notification_channel,
+                        controller_channel,
+                        table_filter,
+                    )
+                    .await?;
+            }
+            _ => {}
+        }
+
+        info!(position = %current_pos, "Replicating from change stream");
+
+        // Let Controller know that the initial snapshotting is complete. Ignores the error, which
+        // will not occur unless the Controller dropped the rx half of this channel.
+        let _ = notification_channel.send(ReplicatorMessage::SnapshotDone);
+
+        adapter
+            .main_loop(
+                &mut current_pos,
+                None,
+                notification_channel,
+                controller_channel,
+                table_filter,
+            )
+            .await?;
+
+        unreachable!("`main_loop` will never stop with an Ok status if `until = None`");
+    }
+
+    /// Snapshot the tables that are replicated but don't have a replication offset, because they
+    /// were just added to replication or dropped to be resnapshotted, by replaying the change
+    /// stream from its start up to `until` for just those tables. Debezium starts each stream with
+    /// a snapshot of its own, so this leaves them at the same position as all the other tables.
+    ///
+    /// Expects `self.connector` to be reading from the start of the stream.
+    async fn snapshot_cdc_tables(&mut self, until: &ReplicationOffset) -> ReadySetResult<()> {
+        info!(%until, "Snapshotting tables from the start of the change stream");
+        let mut snapshotted = HashSet::new();
+        let mut position = ReplicationOffset::from(CdcPosition::default());
+
+        // Tables that already exist but need to be snapshotted may hold some of their rows, either
+        // because their schema changed or because an earlier snapshot was interrupted. The
+        // connector won't create these tables, so empty them by creating them again.
+        let existing = self
+            .replication_offsets
+            .tables
+            .keys()
+            .filter(|table| self.needs_cdc_snapshot(table, &snapshotted))
+            .cloned()
+            .collect::<Vec<_>>();
+        for table in existing {
+            let Some(body) = self.noria.table(table.clone()).await?.schema().cloned() else {
+                continue;
+            };
+            self.noria
+                .extend_recipe(ChangeList::from_changes(
+                    vec![
+                        Change::Drop {
+                            name: table.clone(),
+                            if_exists: true,
+                        },
+                        Change::CreateTable {
+                            statement: CreateTableStatement {
+                                if_not_exists: false,
+                                table: table.clone(),
+                                body: Ok(body),
+                                options: Ok(vec![]),
+                            },
+                            pg_meta: None,
+                        },
+                    ],
+                    self.dialect,
+                ))
+                .await?;
+            snapshotted.insert(table);
+        }
+        self.clear_mutator_cache();
+
+        while position < *until {
+            let (actions, pos) = match self.connector.next_action(&position, Some(until)).await {
+                Ok(next_actions) => next_actions,
+                // Tables we aren't snapshotting already saw (and handled) this error the first
+                // time around
+                Err(ReadySetError::TableError { table, source }) => {
+                    if self.needs_cdc_snapshot(&table, &snapshotted) {
+                        snapshotted.remove(&table);
+                        self.deny_replication_for_table(table, source).await?;
+                    }
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            if pos > *until {
+                break;
+            }
+            position = pos;
+
+            for action in actions {
+                match action {
+                    ReplicationAction::DdlChange { schema, changes } => {
+                        let changes = changes
+                            .into_iter()
+                            .filter(|change| match change {
+                                Change::CreateTable { statement, .. } => {
+                                    statement.body.is_ok()
+                                        && self.needs_cdc_snapshot(&statement.table, &snapshotted)
+                                }
+                                _ => false,
+                            })
+                            .collect::<Vec<_>>();
+                        if changes.is_empty() {
+                            continue;
+                        }
+                        for change in &changes {
+                            if let Change::CreateTable { statement, .. } = change {
+                                snapshotted.insert(statement.table.clone());
+                            }
+                        }
+                        self.noria
+                            .extend_recipe(
+                                ChangeList::from_changes(changes, self.dialect)
+                                    .with_schema_search_path(vec![schema.into()]),
+                            )
+                            .await?;
+                        self.clear_mutator_cache();
+                    }
+                    ReplicationAction::TableAction {
+                        table,
+                        actions,
+                        txid,
+                    } if snapshotted.contains(&table) => {
+                        self.handle_table_actions(table, actions, txid, &position)
+                            .await?
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Leave every table we snapshotted at the position the rest of replication resumes from
+        for table in snapshotted {
+            if let Some(mutator) = self.mutator_for_table(&table).await? {
+                mutator.set_replication_offset(until.clone()).await?;
+                self.replication_offsets
+                    .tables
+                    .insert(table, Some(until.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The schema of each table that exists in ReadySet, so that a [`CdcConnector`] doesn't create
+    /// them again
+    async fn cdc_table_schemas(
+        noria: &mut ReadySetHandle,
+    ) -> ReadySetResult<HashMap<Relation, TableSchema>> {
+        let mut schemas = HashMap::new();
+        for table in noria.tables().await?.into_keys() {
+            if let Some(body) = noria.table(table.clone()).await?.schema() {
+                schemas.insert(table, TableSchema::from_body(body));
+            }
+        }
+        Ok(schemas)
+    }
+
+    /// Returns true if `table` is being snapshotted from the change stream by
+    /// [`Self::snapshot_cdc_tables`], or should be: it's replicated, but has no replication offset
+    fn needs_cdc_snapshot(&self, table: &Relation, snapshotted: &HashSet<Relation>) -> bool {
+        snapshotted.contains(table)
+            || (table
+                .schema
+                .as_deref()
+                .is_some_and(|schema| self.table_filter.should_be_processed(schema, &table.name))
+                && !matches!(self.replication_offsets.tables.get(table), Some(Some(_))))
+    }
+
     /// Finish the build and begin monitoring the binlog for changes
     /// If noria has no replication offset information, it will replicate the target database in its
     /// entirety to ReadySet before listening on the binlog
@@ -289,9 +704,10 @@ impl NoriaAdapter {
     async fn start_inner_mysql(
         mut mysql_options: mysql::Opts,
         mut noria: ReadySetHandle,
-        mut config: UpstreamConfig,
+        config: UpstreamConfig,
         notification_channel: &UnboundedSender<ReplicatorMessage>,
-        controller_channel: &UnboundedReceiver<ControllerMessage>,
+        controller_channel: &mut UnboundedReceiver<ControllerMessage>,
+        table_filter: &mut TableFilter,
         resnapshot: bool,
         telemetry_sender: &TelemetrySender,
         enable_statement_logging: bool,
@@ -319,13 +735,6 @@ impl NoriaAdapter {
         )
         .await?;
 
-        let table_filter = TableFilter::try_new(
-            nom_sql::Dialect::MySQL,
-            config.replication_tables.take(),
-            config.replication_tables_ignore.take(),
-            mysql_options.db_name(),
-        )?;
-
         let mut db_schemas = DatabaseSchemas::new();
 
         let pos = match (replication_offsets.max_offset()?, resnapshot) {
@@ -358,8 +767,11 @@ impl NoriaAdapter {
                     .flatten()
                     .unwrap_or_else(|| "unknown".to_owned());
 
-                let replicator = MySqlReplicator {
+                let flavor = MySqlFlavor::from_version(&db_version);
+
+                let mut replicator = MySqlReplicator {
                     pool,
+                    flavor,
                     table_filter: table_filter.clone(),
                 };
 
@@ -381,6 +793,9 @@ impl NoriaAdapter {
                     )
                     .instrument(span.clone())
                     .await;
+                // Tables that failed to snapshot are denied replication by the snapshot, which has
+                // to be kept along with the changes made by `ALTER READYSET`
+                *table_filter = replicator.table_filter.clone();
 
                 let status = if snapshot_result.is_err() {
                     SnapshotStatusTag::Failed.value()
@@ -424,7 +839,7 @@ impl NoriaAdapter {
                 let _ = telemetry_sender.send_event_with_payload(
                     TelemetryEvent::SnapshotComplete,
                     TelemetryBuilder::new()
-                        .db_backend("mysql")
+                        .db_backend(flavor.to_string())
                         .db_version(db_version)
                         .build(),
                 );
@@ -463,9 +878,10 @@ impl NoriaAdapter {
             replication_offsets,
             mutator_map: HashMap::new(),
             warned_missing_tables: HashSet::new(),
-            table_filter,
+            table_filter: table_filter.clone(),
             supports_resnapshot: true,
             dialect: Dialect::DEFAULT_MYSQL,
+            upstream_pool: Some(UpstreamPool::MySql(mysql::Pool::new(mysql_options))),
         };
 
         let mut current_pos: ReplicationOffset = pos.into();
@@ -487,6 +903,7 @@ impl NoriaAdapter {
                         Some(max),
                         notification_channel,
                         controller_channel,
+                        table_filter,
                     )
                     .await?;
             }
@@ -506,6 +923,7 @@ impl NoriaAdapter {
                 None,
                 notification_channel,
                 controller_channel,
+                table_filter,
             )
             .await?;
 
@@ -516,9 +934,10 @@ impl NoriaAdapter {
     async fn start_inner_postgres(
         pgsql_opts: pgsql::Config,
         mut noria: ReadySetHandle,
-        mut config: UpstreamConfig,
+        config: UpstreamConfig,
         notification_channel: &UnboundedSender<ReplicatorMessage>,
-        controller_channel: &UnboundedReceiver<ControllerMessage>,
+        controller_channel: &mut UnboundedReceiver<ControllerMessage>,
+        table_filter: &mut TableFilter,
         resnapshot: bool,
         mut full_resnapshot: bool,
         telemetry_sender: &TelemetrySender,
@@ -553,13 +972,6 @@ impl NoriaAdapter {
             .transpose()?;
         let snapshot_report_interval_secs = config.snapshot_report_interval_secs;
 
-        let table_filter = TableFilter::try_new(
-            nom_sql::Dialect::PostgreSQL,
-            config.replication_tables.take(),
-            config.replication_tables_ignore.take(),
-            None,
-        )?;
-
         let (mut client, connection) = pgsql_opts.connect(tls_connector.clone()).await?;
         let _connection_handle = tokio::spawn(connection);
 
@@ -665,9 +1077,13 @@ impl NoriaAdapter {
                 .and_then(|row| row.try_get::<_, String>(0))
                 .unwrap_or_else(|_| "unknown".to_owned());
 
-            let mut replicator =
-                PostgresReplicator::new(&mut client, pool, &mut noria, table_filter.clone())
-                    .await?;
+            let mut replicator = PostgresReplicator::new(
+                &mut client,
+                pool.clone(),
+                &mut noria,
+                table_filter.clone(),
+            )
+            .await?;
 
             let snapshot_result = replicator
                 .snapshot_to_noria(
@@ -684,6 +1100,9 @@ impl NoriaAdapter {
                     max_parallel_snapshot_tables,
                 )
                 .await;
+            // Tables that failed to snapshot are denied replication by the snapshot, which has to
+            // be kept along with the changes made by `ALTER READYSET`
+            *table_filter = replicator.table_filter.clone();
 
             let status = if snapshot_result.is_err() {
                 SnapshotStatusTag::Failed.value()
@@ -742,9 +1161,10 @@ impl NoriaAdapter {
             replication_offsets,
             mutator_map: HashMap::new(),
             warned_missing_tables: HashSet::new(),
-            table_filter,
+            table_filter: table_filter.clone(),
             supports_resnapshot: true,
             dialect: Dialect::DEFAULT_POSTGRESQL,
+            upstream_pool: Some(UpstreamPool::Postgres(pool)),
         };
 
         if min_pos != max_pos {
@@ -755,6 +1175,7 @@ impl NoriaAdapter {
                     Some(max_pos),
                     notification_channel,
                     controller_channel,
+                    table_filter,
                 )
                 .await?;
         }
@@ -766,7 +1187,13 @@ impl NoriaAdapter {
         info!("Streaming replication started");
 
         adapter
-            .main_loop(&mut min_pos, None, notification_channel, controller_channel)
+            .main_loop(
+                &mut min_pos,
+                None,
+                notification_channel,
+                controller_channel,
+                table_filter,
+            )
             .await?;
 
         unreachable!("`main_loop` will never stop with an Ok status if `until = None`");
@@ -982,6 +1409,48 @@ impl NoriaAdapter {
         let mut actionables: Vec<ReplicationAction> = Vec::new();
         for action in actions {
             match action {
+                ReplicationAction::ResnapshotTable { ref table, .. } => {
+                    match &self.replication_offsets.schema {
+                        Some(cur) if pos <= *cur => {
+                            if !catchup {
+                                warn!(%pos, %cur, "Skipping table resnapshot for earlier entry");
+                            }
+                        }
+                        _ => {
+                            if self.table_filter.should_be_processed(
+                                table.schema.as_deref().ok_or_else(|| {
+                                    internal_err!(
+                                        "All tables should have a schema in the replicator"
+                                    )
+                                })?,
+                                &table.name,
+                            ) {
+                                actionables.push(action);
+                            }
+                        }
+                    }
+                }
+                ReplicationAction::ReplayTable { ref statement } => {
+                    match self.replication_offsets.tables.get(&statement.table) {
+                        Some(Some(cur)) if pos <= *cur => {
+                            if !catchup {
+                                warn!(%pos, %cur, "Skipping table replay for earlier entry");
+                            }
+                        }
+                        _ => {
+                            if self.table_filter.should_be_processed(
+                                statement.table.schema.as_deref().ok_or_else(|| {
+                                    internal_err!(
+                                        "All tables should have a schema in the replicator"
+                                    )
+                                })?,
+                                &statement.table.name,
+                            ) {
+                                actionables.push(action);
+                            }
+                        }
+                    }
+                }
                 ReplicationAction::DdlChange { .. } | ReplicationAction::LogPosition => {
                     match &self.replication_offsets.schema {
                         Some(cur) if pos <= *cur => {
@@ -1051,6 +1520,29 @@ impl NoriaAdapter {
                     self.handle_table_actions(table, actions, txid, &pos)
                         .await?
                 }
+                ReplicationAction::ResnapshotTable { table, absorbed } => {
+                    if let Some(pos) = self.replication_offsets.max_offset()?.cloned() {
+                        // Forward all positions to the maximum position (the one prior to this
+                        // event) to avoid needless replay later
+                        self.handle_log_position(&pos).await?;
+                    }
+                    for absorbed_table in absorbed {
+                        self.remove_table_from_readyset(
+                            absorbed_table,
+                            NotReplicatedReason::Partitioned,
+                        )
+                        .await?;
+                    }
+                    self.resnapshot_table(table).await?
+                }
+                ReplicationAction::ReplayTable { statement } => {
+                    if let Some(pos) = self.replication_offsets.max_offset()?.cloned() {
+                        // Forward all positions to the maximum position (the one prior to this
+                        // event) to avoid needless replay later
+                        self.handle_log_position(&pos).await?;
+                    }
+                    self.replay_table(statement).await?
+                }
                 ReplicationAction::LogPosition => self.handle_log_position(&pos).await?,
             }
         }
@@ -1059,12 +1551,30 @@ impl NoriaAdapter {
 
     /// Loop over the actions. `until` may be passed to set a replication offset to stop
     /// replicating at.
+    ///
+    /// Once the loop exits, any changes made to the set of replicated tables are written back to
+    /// `table_filter`, so that they are kept if the replicator restarts.
     async fn main_loop(
         &mut self,
         position: &mut ReplicationOffset,
         until: Option<ReplicationOffset>,
         notification_channel: &UnboundedSender<ReplicatorMessage>,
-        _controller_channel: &UnboundedReceiver<ControllerMessage>,
+        controller_channel: &mut UnboundedReceiver<ControllerMessage>,
+        table_filter: &mut TableFilter,
+    ) -> ReadySetResult<()> {
+        let res = self
+            .main_loop_inner(position, until, notification_channel, controller_channel)
+            .await;
+        *table_filter = self.table_filter.clone();
+        res
+    }
+
+    async fn main_loop_inner(
+        &mut self,
+        position: &mut ReplicationOffset,
+        until: Option<ReplicationOffset>,
+        notification_channel: &UnboundedSender<ReplicatorMessage>,
+        controller_channel: &mut UnboundedReceiver<ControllerMessage>,
     ) -> ReadySetResult<()> {
         // Notify the controller that we've started replication if we've entered the main (not
         // catchup) replication loop.
@@ -1074,6 +1584,12 @@ impl NoriaAdapter {
         }
 
         loop {
+            // Requests from the controller are only checked in between replication actions, since
+            // `Connector::next_action` is not cancellation safe
+            while let Ok(message) = controller_channel.try_recv() {
+                self.handle_controller_message(message).await?;
+            }
+
             set_failpoint!(failpoints::UPSTREAM, |_| ReadySetResult::Err(
                 ReadySetError::ReplicationFailed(
                     "replication-upstream failpoint injected".to_string()
@@ -1124,6 +1640,128 @@ impl NoriaAdapter {
         }
     }
 
+    /// Drop the given table from ReadySet, and return [`ReadySetError::ResnapshotNeeded`] so that
+    /// the partial resnapshot that follows snapshots it again from scratch.
+    async fn resnapshot_table(&mut self, table: Relation) -> ReadySetResult<()> {
+        info!(table = %table.display_unquoted(), "Resnapshotting table");
+        self.replication_offsets.tables.remove(&table);
+        self.mutator_map.remove(&table);
+        let changelist = ChangeList::from_change(
+            Change::Drop {
+                name: table,
+                if_exists: true,
+            },
+            self.dialect,
+        );
+        self.noria.extend_recipe(changelist).await?;
+        Err(ReadySetError::ResnapshotNeeded)
+    }
+
+    /// Create the given table again, dropping its rows, and return
+    /// [`ReadySetError::ResnapshotNeeded`] so that [`Self::snapshot_cdc_tables`] replays it from
+    /// the start of the change stream.
+    async fn replay_table(&mut self, statement: CreateTableStatement) -> ReadySetResult<()> {
+        info!(
+            table = %statement.table.display_unquoted(),
+            "Schema of table changed, replaying table"
+        );
+        let table = statement.table.clone();
+        self.replication_offsets.tables.remove(&table);
+        self.mutator_map.remove(&table);
+        let changelist = ChangeList::from_changes(
+            vec![
+                Change::Drop {
+                    name: table,
+                    if_exists: true,
+                },
+                Change::CreateTable {
+                    statement,
+                    pg_meta: None,
+                },
+            ],
+            self.dialect,
+        );
+        self.noria.extend_recipe(changelist).await?;
+        Err(ReadySetError::ResnapshotNeeded)
+    }
+
+    /// Handle a request from the controller to change the set of tables we replicate.
+    ///
+    /// Requests that require tables to be snapshotted return [`ReadySetError::ResnapshotNeeded`],
+    /// which restarts the replicator with a partial resnapshot. A partial resnapshot only snapshots
+    /// the tables that don't have a replication offset, so replication of all other tables
+    /// continues from where it left off.
+    ///
+    /// Changes to the set of replicated tables are recorded by the controller, so that they're
+    /// applied again by [`apply_replicated_table_changes`] whenever the replicator starts.
+    async fn handle_controller_message(
+        &mut self,
+        message: ControllerMessage,
+    ) -> ReadySetResult<()> {
+        match message {
+            ControllerMessage::ResnapshotTable { table } => self.resnapshot_table(table).await,
+            ControllerMessage::AddTables { tables, done } => {
+                let res = self.add_tables(tables).await;
+                let resnapshot = res.is_ok();
+                let _ = done.send(res);
+                if resnapshot {
+                    Err(ReadySetError::ResnapshotNeeded)
+                } else {
+                    Ok(())
+                }
+            }
+            ControllerMessage::DropTables { tables, done } => {
+                let res = self.drop_tables(tables).await;
+                let _ = done.send(res.clone());
+                res
+            }
+        }
+    }
+
+    /// Allow replication of the given tables, which must all exist in the upstream database. The
+    /// tables are snapshotted by the partial resnapshot that follows.
+    async fn add_tables(&mut self, tables: Vec<Relation>) -> ReadySetResult<()> {
+        for table in &tables {
+            let exists = match &self.upstream_pool {
+                Some(pool) => pool.table_exists(table).await?,
+                None => true,
+            };
+            if !exists {
+                return Err(ReadySetError::TableNotFound {
+                    name: table.name.to_string(),
+                    schema: table.schema.as_ref().map(|s| s.to_string()),
+                });
+            }
+        }
+
+        self.noria
+            .record_replicated_tables(tables.clone(), true)
+            .await?;
+        for table in &tables {
+            let schema = table.schema.as_deref().ok_or_else(|| {
+                internal_err!("All tables should have a schema in the replicator")
+            })?;
+            self.table_filter.allow_replication(schema, &table.name);
+        }
+        info!(num_tables = tables.len(), "Adding tables to replication");
+        Ok(())
+    }
+
+    /// Stop replicating the given tables, and remove them from ReadySet
+    async fn drop_tables(&mut self, tables: Vec<Relation>) -> ReadySetResult<()> {
+        for table in tables.iter().cloned() {
+            let schema = table.schema.clone().ok_or_else(|| {
+                internal_err!("All tables should have a schema in the replicator")
+            })?;
+            let name = table.name.clone();
+            self.remove_table_from_readyset(table, NotReplicatedReason::Configuration)
+                .await?;
+            self.table_filter
+                .deny_replication(schema.as_str(), name.as_str());
+        }
+        self.noria.record_replicated_tables(tables, false).await
+    }
+
     /// When schema changes there is a risk the cached mutators will no longer be in sync
     /// and we need to drop them all
     fn clear_mutator_cache(&mut self) {
@@ -1147,8 +1785,12 @@ impl NoriaAdapter {
     }
 
     /// Remove the table referenced by the provided schema and table name from our base table and
-    /// dataflow state (if any).
-    async fn remove_table_from_readyset(&mut self, table: Relation) -> ReadySetResult<()> {
+    /// dataflow state (if any), and mark it as not replicated for the given `reason`.
+    async fn remove_table_from_readyset(
+        &mut self,
+        table: Relation,
+        reason: NotReplicatedReason,
+    ) -> ReadySetResult<()> {
         info!(
             table = %table.display(nom_sql::Dialect::PostgreSQL),
             "Removing table state from readyset"
@@ -1164,9 +1806,8 @@ impl NoriaAdapter {
                     if_exists: true,
                 },
                 Change::AddNonReplicatedRelation(NonReplicatedRelation {
-                    // assuming NonReplicatedRelation has fields `relation` and `reason`
                     name: table,
-                    reason: NotReplicatedReason::TableDropped,
+                    reason,
                 }),
             ],
             self.dialect,
@@ -1212,10 +1853,12 @@ impl NoriaAdapter {
         // successfully removing the table from readyset--that would lead to permanently
         // stale results.
         set_failpoint_return_err!("ignore-table-fail-dropping-table");
-        self.remove_table_from_readyset(table.clone()).await.map_err(|error| {
-            error!(%error, "failed to remove ignored table from readyset, will need to resnapshot it to continue");
-            ReadySetError::ResnapshotNeeded
-        })?;
+        self.remove_table_from_readyset(table.clone(), NotReplicatedReason::TableDropped)
+            .await
+            .map_err(|error| {
+                error!(%error, "failed to remove ignored table from readyset, will need to resnapshot it to continue");
+                ReadySetError::ResnapshotNeeded
+            })?;
 
         self.table_filter
             .deny_replication(schema.as_str(), name.as_str());
@@ -1224,6 +1867,36 @@ impl NoriaAdapter {
     }
 }
 
+/// Apply the changes made to the set of replicated tables at runtime with `ALTER READYSET`, which
+/// are recorded by the controller, on top of the tables the replicator is configured to replicate
+async fn apply_replicated_table_changes(
+    noria: &ReadySetHandle,
+    table_filter: &mut TableFilter,
+) -> ReadySetResult<()> {
+    let changes = retry_with_exponential_backoff(
+        || async {
+            let mut noria = noria.clone();
+            noria.replicated_table_changes().await
+        },
+        5,
+        Duration::from_millis(250),
+    )
+    .await?;
+
+    for (table, replicate) in changes {
+        let schema = table
+            .schema
+            .as_deref()
+            .ok_or_else(|| internal_err!("All tables should have a schema in the replicator"))?;
+        if replicate {
+            table_filter.allow_replication(schema, &table.name);
+        } else {
+            table_filter.deny_replication(schema, &table.name);
+        }
+    }
+    Ok(())
+}
+
 pub async fn pg_pool(
     config: pgsql::Config,
     pool_size: usize,
@@ -39,6 +39,11 @@ use crate::noria_adapter::{Connector, ReplicationAction};
 /// `CREATE` - To create a publication, the user must have the CREATE privilege in the database. To
 /// add tables to a publication, the user must have ownership rights on the table. To create a
 /// publication that publishes all tables automatically, the user must be a superuser.
+///
+/// The connector may also connect to a physical standby running PostgreSQL 16 or later, which
+/// supports logical decoding. In that case the replication slot is created on the standby, but
+/// since a standby is read-only, the publication and the DDL replication event triggers (see
+/// [`setup_ddl_replication`]) have to be created on the primary ahead of time.
 pub struct PostgresWalConnector {
     /// This is the underlying (regular) PostgreSQL client
     client: pgsql::Client,
@@ -66,6 +71,86 @@ pub struct PostgresWalConnector {
     /// Whether or not we just processed a table error and need to allow mismatched lsns for the
     /// next commit
     had_table_error: bool,
+    /// If the upstream database is a physical standby, a regular connection to it that we use to
+    /// notice when it gets promoted
+    standby: Option<StandbyMonitor>,
+}
+
+/// A regular (non-replication) connection to an upstream physical standby.
+///
+/// Logical replication slots survive the promotion of a standby, but we don't try to reason about
+/// what happens to the WAL stream across the resulting timeline switch; instead, we resnapshot
+/// from scratch as soon as we notice the standby has been promoted.
+struct StandbyMonitor {
+    client: pgsql::Client,
+    connection_handle: tokio::task::JoinHandle<Result<(), pgsql::Error>>,
+}
+
+impl StandbyMonitor {
+    async fn connect(pg_config: &pgsql::Config, tls: MakeTlsConnector) -> ReadySetResult<Self> {
+        let (client, connection) = pg_config
+            .connect(tls)
+            .await
+            .map_err(|e| ReadySetError::ReplicationFailed(format!("Failed to connect: {e}")))?;
+        let connection_handle = tokio::spawn(connection);
+        Ok(Self {
+            client,
+            connection_handle,
+        })
+    }
+
+    /// Returns `true` if the standby has been promoted since we connected to it
+    async fn promoted(&self) -> ReadySetResult<bool> {
+        let in_recovery: bool = self
+            .client
+            .query_one("SELECT pg_is_in_recovery()", &[])
+            .await?
+            .try_get(0)?;
+        Ok(!in_recovery)
+    }
+}
+
+impl Drop for StandbyMonitor {
+    fn drop(&mut self) {
+        self.connection_handle.abort();
+    }
+}
+
+/// The minimum version of PostgreSQL that supports logical decoding on a physical standby
+const MIN_STANDBY_VERSION: u32 = 160000;
+
+/// The minimum version of PostgreSQL that supports publishing changes to partitions as changes to
+/// their partition root, with `publish_via_partition_root`
+const MIN_PUBLISH_VIA_ROOT_VERSION: u32 = 130000;
+
+/// Checks that a physical standby running the given version of PostgreSQL (as reported by
+/// `server_version_num`) supports logical decoding
+fn check_standby_version(version: u32) -> ReadySetResult<()> {
+    if version < MIN_STANDBY_VERSION {
+        return Err(ReadySetError::ReplicationFailed(format!(
+            "The upstream database is a physical standby running PostgreSQL {version}, but \
+             replicating from a standby requires PostgreSQL 16 or later"
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that the publication with the given name can be used to replicate from a standby, given
+/// its `pubviaroot` setting, or [`None`] if the publication doesn't exist
+fn check_standby_publication_via_root(name: &str, via_root: Option<bool>) -> ReadySetResult<()> {
+    match via_root {
+        Some(true) => Ok(()),
+        Some(false) => Err(ReadySetError::ReplicationFailed(format!(
+            "Publication {name} does not publish via partition root. Since the upstream database \
+             is a standby, run `ALTER PUBLICATION {name} SET (publish_via_partition_root = true)` \
+             on the primary"
+        ))),
+        None => Err(ReadySetError::ReplicationFailed(format!(
+            "Publication {name} does not exist. Since the upstream database is a standby, run \
+             `CREATE PUBLICATION {name} FOR ALL TABLES WITH (publish_via_partition_root = true)` \
+             on the primary"
+        ))),
+    }
 }
 
 /// The decoded response to `IDENTIFY_SYSTEM`
@@ -113,6 +198,9 @@ impl PostgresWalConnector {
 
     /// Connects to postgres and if needed creates a new replication slot for itself with an
     /// exported snapshot.
+    ///
+    /// If the upstream database is a physical standby, DDL replication is not set up, since the
+    /// event triggers it relies on can only be created on the primary.
     #[allow(clippy::too_many_arguments)]
     pub(crate) async fn connect<S: AsRef<str>>(
         mut pg_config: pgsql::Config,
@@ -125,13 +213,11 @@ impl PostgresWalConnector {
         full_resnapshot: bool,
         controller: ReadySetHandle,
     ) -> ReadySetResult<Self> {
-        if !config.disable_setup_ddl_replication {
-            setup_ddl_replication(pg_config.clone(), tls_connector.clone()).await?;
-        }
+        let regular_config = pg_config.clone();
         pg_config.dbname(dbname.as_ref()).set_replication_database();
 
         let (client, connection) = pg_config
-            .connect(tls_connector)
+            .connect(tls_connector.clone())
             .await
             .map_err(|e| ReadySetError::ReplicationFailed(format!("Failed to connect: {e}")))?;
         let connection_handle = tokio::spawn(connection);
@@ -159,8 +245,24 @@ impl PostgresWalConnector {
             controller,
             status_update_interval,
             had_table_error: false,
+            standby: None,
         };
 
+        if connector.is_in_recovery().await? {
+            connector.check_standby_supported().await?;
+            if !config.disable_setup_ddl_replication {
+                warn!(
+                    "The upstream database is a physical standby, so DDL replication can't be set \
+                     up automatically. Schema changes will not be replicated unless \
+                     ddl_replication.sql has been run on the primary"
+                );
+            }
+            connector.standby =
+                Some(StandbyMonitor::connect(&regular_config, tls_connector).await?);
+        } else if !config.disable_setup_ddl_replication {
+            setup_ddl_replication(regular_config, tls_connector).await?;
+        }
+
         if full_resnapshot || next_position.is_none() {
             // If we don't have a consistent replication offset to start replicating from or if we
             // need to perform a full resnapshot, drop and recreate our replication slot.
@@ -169,6 +271,16 @@ impl PostgresWalConnector {
             connector
                 .create_publication_and_slot(repl_slot_name)
                 .await?;
+        } else if !connector
+            .publication_publishes_via_partition_root(PUBLICATION_NAME)
+            .await?
+        {
+            // Publications created by earlier versions of ReadySet publish changes to partitions
+            // under the name of the partition rather than its partition root, which we don't
+            // replicate anymore. Switching the publication over means replicating every partition
+            // root from scratch.
+            warn!("Publication does not publish via partition root, full resnapshot needed");
+            return Err(ReadySetError::FullResnapshotNeeded);
         }
 
         Ok(connector)
@@ -183,20 +295,12 @@ impl PostgresWalConnector {
             dbname = ?system.dbname
         );
 
-        match self.create_publication(PUBLICATION_NAME).await {
-            Ok(()) => {
-                // Created a new publication, everything is good
-            }
-            Err(err)
-                if err.to_string().contains("publication")
-                    && err.to_string().contains("already exists") =>
-            {
-                // This is an existing publication we are going to use
-            }
-            Err(err) if err.to_string().contains("permission denied") => {
-                error!("Insufficient permissions to create publication FOR ALL TABLES");
-            }
-            Err(err) => return Err(err),
+        if self.standby.is_some() {
+            // Publications can't be created on a standby, so it has to have been created on the
+            // primary
+            self.check_standby_publication(PUBLICATION_NAME).await?;
+        } else {
+            self.create_or_update_publication().await?;
         }
 
         // Drop the existing slot if any
@@ -217,6 +321,92 @@ impl PostgresWalConnector {
         Ok(())
     }
 
+    async fn create_or_update_publication(&mut self) -> ReadySetResult<()> {
+        let via_root = self.server_version().await? >= MIN_PUBLISH_VIA_ROOT_VERSION;
+        if !via_root {
+            warn!(
+                "The upstream database does not support publish_via_partition_root, which was \
+                 added in PostgreSQL 13. Changes to partitioned tables will not be replicated"
+            );
+        }
+
+        match self.create_publication(PUBLICATION_NAME, via_root).await {
+            Ok(()) => {
+                // Created a new publication, everything is good
+            }
+            Err(err)
+                if err.to_string().contains("publication")
+                    && err.to_string().contains("already exists") =>
+            {
+                // This is an existing publication we are going to use, but it might have been
+                // created before we replicated partitioned tables through their partition root
+                if via_root {
+                    self.set_publish_via_partition_root(PUBLICATION_NAME)
+                        .await?;
+                }
+            }
+            Err(err) if err.to_string().contains("permission denied") => {
+                error!("Insufficient permissions to create publication FOR ALL TABLES");
+            }
+            Err(err) => return Err(err),
+        }
+
+        Ok(())
+    }
+
+    /// Returns the version of the upstream database, as reported by `server_version_num`
+    async fn server_version(&mut self) -> ReadySetResult<u32> {
+        let [version] = self
+            .one_row_query::<1>("SELECT current_setting('server_version_num')")
+            .await?;
+        version.parse().map_err(|e| {
+            ReadySetError::Internal(format!("Unable to parse postgres version: {}", e))
+        })
+    }
+
+    /// Returns whether the upstream database is a physical standby that's still in recovery
+    async fn is_in_recovery(&mut self) -> ReadySetResult<bool> {
+        let [in_recovery] = self
+            .one_row_query::<1>("SELECT pg_is_in_recovery()")
+            .await?;
+        Ok(in_recovery == "t")
+    }
+
+    /// Checks that the upstream physical standby supports logical decoding, and warns about
+    /// configuration that makes it likely for our replication slot to be invalidated
+    async fn check_standby_supported(&mut self) -> ReadySetResult<()> {
+        check_standby_version(self.server_version().await?)?;
+        let [hot_standby_feedback] = self
+            .one_row_query::<1>("SELECT current_setting('hot_standby_feedback')")
+            .await?;
+
+        info!("The upstream database is a physical standby; replicating from the standby");
+        if hot_standby_feedback != "on" {
+            warn!(
+                "hot_standby_feedback is disabled on the upstream standby. Our replication slot \
+                 may be invalidated by recovery conflicts, which will require a full resnapshot"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the publication with the given name, which must have been created on the
+    /// primary, exists on the upstream standby and publishes via the partition root
+    async fn check_standby_publication(&mut self, name: &str) -> ReadySetResult<()> {
+        let query = format!(
+            "SELECT pubviaroot FROM pg_catalog.pg_publication WHERE pubname = {}",
+            escape_literal(name)
+        );
+        let rows = self.simple_query(&query).await?;
+        let via_root = rows.iter().find_map(|msg| match msg {
+            SimpleQueryMessage::Row(row) => Some(row.get(0) == Some("t")),
+            _ => None,
+        });
+
+        check_standby_publication_via_root(name, via_root)
+    }
+
     /// Waits and returns the next WAL event, while monitoring the connection
     /// handle for errors.
     async fn next_event(&mut self) -> ReadySetResult<WalEvent> {
@@ -271,15 +461,61 @@ impl PostgresWalConnector {
 
     /// Creates a new `PUBLICATION name FOR ALL TABLES`, to be able to receive WAL on that slot.
     /// The user must have superuser privileges for that to work.
-    async fn create_publication(&mut self, name: &str) -> ReadySetResult<()> {
-        let query = format!("CREATE PUBLICATION {} FOR ALL TABLES", name);
+    ///
+    /// If `via_root` is set, the publication is created with `publish_via_partition_root`, so that
+    /// changes to partitions of a partitioned table are published as changes to the partition
+    /// root. That option requires PostgreSQL 13 or later.
+    async fn create_publication(&mut self, name: &str, via_root: bool) -> ReadySetResult<()> {
+        let query = if via_root {
+            format!(
+                "CREATE PUBLICATION {} FOR ALL TABLES WITH (publish_via_partition_root = true)",
+                name
+            )
+        } else {
+            format!("CREATE PUBLICATION {} FOR ALL TABLES", name)
+        };
         self.simple_query(&query).await.map_err(|e| {
             ReadySetError::ReplicationFailed(format!("Failed to create publication: {e}"))
         })?;
         Ok(())
     }
 
-    /// Creates a new replication slot on the primary.
+    /// Makes an existing publication publish changes to partitions of a partitioned table as
+    /// changes to the partition root.
+    async fn set_publish_via_partition_root(&mut self, name: &str) -> ReadySetResult<()> {
+        let query = format!(
+            "ALTER PUBLICATION {} SET (publish_via_partition_root = true)",
+            name
+        );
+        self.simple_query(&query).await.map_err(|e| {
+            ReadySetError::ReplicationFailed(format!("Failed to alter publication: {e}"))
+        })?;
+        Ok(())
+    }
+
+    /// Returns whether the publication with the given name publishes changes to partitions as
+    /// changes to their partition root. Returns `true` if the publication doesn't exist, or if the
+    /// upstream database is too old to publish changes via the partition root at all.
+    async fn publication_publishes_via_partition_root(
+        &mut self,
+        name: &str,
+    ) -> ReadySetResult<bool> {
+        if self.server_version().await? < MIN_PUBLISH_VIA_ROOT_VERSION {
+            return Ok(true);
+        }
+
+        let query = format!(
+            "SELECT pubviaroot FROM pg_catalog.pg_publication WHERE pubname = {}",
+            escape_literal(name)
+        );
+        let rows = self.simple_query(&query).await?;
+        Ok(rows.iter().all(|msg| match msg {
+            SimpleQueryMessage::Row(row) => row.get(0) == Some("t"),
+            _ => true,
+        }))
+    }
+
+    /// Creates a new replication slot on the upstream database, which may be a physical standby.
     /// The command format for PostgreSQL is as follows:
     ///
     /// `CREATE_REPLICATION_SLOT slot_name [ TEMPORARY ] { PHYSICAL [ RESERVE_WAL ] | LOGICAL
@@ -364,6 +600,24 @@ impl PostgresWalConnector {
             return Err(ReadySetError::FullResnapshotNeeded);
         }
 
+        if self.standby.is_some() {
+            // Logical replication slots on a standby are additionally invalidated if the primary
+            // removes rows that the slot still needs
+            let [conflicting] = self
+                .one_row_query::<1>(&format!(
+                    "SELECT conflicting FROM pg_replication_slots WHERE slot_name = {}",
+                    escape_literal(slot),
+                ))
+                .await?;
+            if conflicting == "t" {
+                error!(
+                    "Our replication slot on the standby has been invalidated by a recovery \
+                     conflict, so a full resnapshot is necessary"
+                );
+                return Err(ReadySetError::FullResnapshotNeeded);
+            }
+        }
+
         let inner_client = self.client.inner();
         let wal_position = self.next_position.unwrap_or_default();
         let messages_support = if version >= 140000 {
@@ -589,6 +843,15 @@ impl Connector for PostgresWalConnector {
 
             self.send_standby_status_update(lsn).await?;
             self.time_last_position_reported = Instant::now();
+
+            if let Some(standby) = &self.standby {
+                if standby.promoted().await? {
+                    warn!(
+                        "The upstream standby has been promoted, so a full resnapshot is necessary"
+                    );
+                    return Err(ReadySetError::FullResnapshotNeeded);
+                }
+            }
         }
 
         let mut cur_table = Relation {
@@ -781,13 +1044,7 @@ impl Connector for PostgresWalConnector {
             match event {
                 WalEvent::DdlEvent { ddl_event, lsn } => {
                     if actions.is_empty() {
-                        return Ok((
-                            vec![ReplicationAction::DdlChange {
-                                schema: ddl_event.schema().to_string(),
-                                changes: vec![ddl_event.into_change()],
-                            }],
-                            cur_pos.with_lsn(lsn).into(),
-                        ));
+                        return Ok((vec![ddl_event.into_action()], cur_pos.with_lsn(lsn).into()));
                     } else {
                         self.peek = Some(Ok(WalEvent::DdlEvent { ddl_event, lsn }));
                         return Ok((
@@ -800,20 +1057,34 @@ impl Connector for PostgresWalConnector {
                         ));
                     }
                 }
-                WalEvent::WantsKeepaliveResponse { end } => {
-                    if !self.in_transaction && actions.is_empty() {
-                        // If the last event we applied to our base tables was a COMMIT and we have
-                        // no buffered actions, we can safely report the "end LSN" given to us in
-                        // the keepalive request as our current position.
-                        self.send_standby_status_update(end).await?;
-                    } else {
-                        // If we have buffered actions, we have to report the position of the *last*
-                        // event we applied, since we haven't yet applied the events associated with
-                        // `cur_pos`
-                        self.send_standby_status_update(last_pos.try_into()?)
-                            .await?;
+                WalEvent::Keepalive {
+                    end,
+                    wants_response,
+                } => {
+                    let caught_up = !self.in_transaction && actions.is_empty();
+                    if wants_response {
+                        if caught_up {
+                            // If the last event we applied to our base tables was a COMMIT and we
+                            // have no buffered actions, we can safely report the "end LSN" given to
+                            // us in the keepalive request as our current position.
+                            self.send_standby_status_update(end).await?;
+                        } else {
+                            // If we have buffered actions, we have to report the position of the
+                            // *last* event we applied, since we haven't yet applied the events
+                            // associated with `cur_pos`
+                            self.send_standby_status_update(last_pos.try_into()?)
+                                .await?;
+                        }
+                        self.time_last_position_reported = Instant::now();
+                    }
+
+                    // Every transaction that committed before `end` has been sent to us, so once
+                    // we've applied them all we're caught up to `end`, even if none of the
+                    // transactions since our last COMMIT touched any replicated tables
+                    let end_pos = PostgresPosition::wal_end(end);
+                    if caught_up && end_pos > cur_pos {
+                        return Ok((vec![ReplicationAction::LogPosition], end_pos.into()));
                     }
-                    self.time_last_position_reported = Instant::now();
                 }
                 WalEvent::Begin { final_lsn } => {
                     // BEGINs should only happen if we aren't already in a transaction
@@ -892,3 +1163,26 @@ impl Connector for PostgresWalConnector {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standby_version() {
+        check_standby_version(150004).unwrap_err();
+        check_standby_version(160000).unwrap();
+        check_standby_version(170002).unwrap();
+    }
+
+    #[test]
+    fn standby_publication() {
+        check_standby_publication_via_root("readyset", Some(true)).unwrap();
+
+        let err = check_standby_publication_via_root("readyset", Some(false)).unwrap_err();
+        assert!(err.to_string().contains("ALTER PUBLICATION readyset"));
+
+        let err = check_standby_publication_via_root("readyset", None).unwrap_err();
+        assert!(err.to_string().contains("CREATE PUBLICATION readyset"));
+    }
+}
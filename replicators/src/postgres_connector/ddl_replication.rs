@@ -15,6 +15,21 @@
 //!`setup_ddl_replication`] function when first starting up the postgresql
 //! replicator.
 //!
+//! # Setting up DDL replication manually
+//!
+//! Event triggers can only be created on a primary, so when replicating from a physical standby
+//! (or when `--disable-setup-ddl-replication` is passed) the replicator doesn't run
+//! `ddl_replication.sql` itself. In that case it has to be run on the primary as a separate step
+//! before starting ReadySet, for example with:
+//!
+//! ```text
+//! psql "$PRIMARY_DB_URL" -f replicators/src/postgres_connector/ddl_replication.sql
+//! ```
+//!
+//! The script is idempotent, so it's safe to run again when upgrading ReadySet. The event triggers
+//! write their messages into the primary's WAL, which the standby replays and decodes like any
+//! other WAL record.
+//!
 //! [pglogical]: https://github.com/2ndQuadrant/pglogical
 //! [event triggers]: https://www.postgresql.org/docs/current/event-triggers.html
 //!
@@ -35,6 +50,13 @@
 //!   to construct a full `ALTER TABLE` statement, `ALTER TABLE` events are replicated as a `CREATE
 //!   TABLE` statement - ReadySet will then know that a `CREATE TABLE` statement for a table that
 //!   already exists should be treated as an alter table.
+//! * Partitioned tables are replicated through their partition root, so a `CREATE TABLE` for a
+//!   partition only marks the partition as non-replicated. `ALTER TABLE ... ATTACH PARTITION` and
+//!   `ALTER TABLE ... DETACH PARTITION` change the rows of the partition root without writing any
+//!   WAL records for them, so they're replicated as a request to resnapshot the partition root.
+//!   These are detected by comparing the altered table's rows in `pg_inherits` before and after the
+//!   command, rather than by looking at the query text. Partitions that were replicated as
+//!   standalone tables before being attached are dropped.
 //!
 //! [dialect]: nom_sql::Dialect
 
@@ -52,6 +74,8 @@ use serde::{Deserialize, Deserializer};
 use tokio_postgres as pgsql;
 use tracing::info;
 
+use crate::noria_adapter::ReplicationAction;
+
 /// Setup everything in the database that's necessary for DDL replication.
 ///
 /// This makes a new connection to the database, since the main connection created for the
@@ -109,6 +133,12 @@ pub(crate) struct DdlEnumVariant {
     pub(crate) label: String,
 }
 
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub(crate) struct DdlPartition {
+    schema: String,
+    name: String,
+}
+
 macro_rules! make_parse_deserialize_with {
     ($name: ident -> $res: ty) => {
         make_parse_deserialize_with!($name -> $res, $name);
@@ -160,6 +190,16 @@ pub(crate) enum DdlEventData {
         #[serde(deserialize_with = "parse_alter_table_statement")]
         statement: Result<AlterTableStatement, String>,
     },
+    /// A partition was attached to the (possibly indirect) partition root with the given name
+    AttachPartition {
+        name: String,
+        /// All the partitions below the partition root, including the one that was just attached
+        partitions: Vec<DdlPartition>,
+    },
+    /// A partition was detached from the (possibly indirect) partition root with the given name
+    DetachPartition {
+        name: String,
+    },
     CreateView(#[serde(deserialize_with = "parse_create_view_statement")] CreateViewStatement),
     Drop(String),
     CreateType {
@@ -195,8 +235,46 @@ where
 }
 
 impl DdlEvent {
+    /// Convert this [`DdlEvent`] into the [`ReplicationAction`] that applies it to ReadySet.
+    ///
+    /// Attaching or detaching a partition requires the partition root to be resnapshotted, which
+    /// can't be expressed as a [`Change`] alone. An attached partition might have been replicated
+    /// as a table of its own before, so it's removed from ReadySet along with the resnapshot.
+    pub(crate) fn into_action(self) -> ReplicationAction {
+        match &self.data {
+            DdlEventData::AttachPartition { name, partitions } => {
+                ReplicationAction::ResnapshotTable {
+                    table: Relation {
+                        schema: Some(self.schema.clone().into()),
+                        name: name.clone().into(),
+                    },
+                    absorbed: partitions
+                        .iter()
+                        .map(|partition| Relation {
+                            schema: Some(partition.schema.clone().into()),
+                            name: partition.name.clone().into(),
+                        })
+                        .collect(),
+                }
+            }
+            DdlEventData::DetachPartition { name } => ReplicationAction::ResnapshotTable {
+                table: Relation {
+                    schema: Some(self.schema.clone().into()),
+                    name: name.clone().into(),
+                },
+                absorbed: vec![],
+            },
+            _ => ReplicationAction::DdlChange {
+                schema: self.schema.clone(),
+                changes: vec![self.into_change()],
+            },
+        }
+    }
+
     /// Convert this [`DdlEvent`] into a SQL DDL statement that can be sent to ReadySet directly
     /// (using the ReadySet-native SQL dialect, not the postgresql dialect!)
+    ///
+    /// For partition events, this only drops the partition root - see [`Self::into_action`].
     pub(crate) fn into_change(self) -> Change {
         match self.data {
             DdlEventData::CreateTable {
@@ -294,6 +372,15 @@ impl DdlEvent {
 
                 Change::AlterTable(stmt)
             }
+            DdlEventData::AttachPartition { name, .. } | DdlEventData::DetachPartition { name } => {
+                Change::Drop {
+                    name: Relation {
+                        schema: Some(self.schema.into()),
+                        name: name.into(),
+                    },
+                    if_exists: true,
+                }
+            }
             DdlEventData::CreateView(stmt) => Change::CreateView(stmt),
             DdlEventData::Drop(name) => Change::Drop {
                 name: name.into(),
@@ -648,9 +735,88 @@ mod tests {
             .unwrap();
 
         match ddl.data {
-            DdlEventData::AddNonReplicatedTable { name } => assert_eq!(name, "t1"),
+            DdlEventData::CreateTable { name, .. } => assert_eq!(name, "t1"),
+            data => panic!("Unexpected DDL event data: {data:?}"),
+        }
+
+        client
+            .simple_query("create table t1_p1 partition of t1 for values from (0) to (10)")
+            .await
+            .unwrap();
+
+        let ddl = get_last_ddl(&client, "create_partitioned_table")
+            .await
+            .unwrap();
+
+        match ddl.data {
+            DdlEventData::AddNonReplicatedTable { name } => assert_eq!(name, "t1_p1"),
             data => panic!("Unexpected DDL event data: {data:?}"),
         }
+
+        client.teardown().await;
+    }
+
+    #[parallel_group(GROUP)]
+    #[tokio::test]
+    async fn attach_detach_partition() {
+        let client = setup("attach_detach_partition").await;
+
+        client
+            .simple_query(
+                "create table t1 (key int, val int) partition by range (key);
+                 create table t1_p1 (key int, val int);",
+            )
+            .await
+            .unwrap();
+
+        let _ = get_last_ddl(&client, "attach_detach_partition").await;
+
+        client
+            .simple_query("alter table t1 attach partition t1_p1 for values from (0) to (10)")
+            .await
+            .unwrap();
+
+        let ddl = get_last_ddl(&client, "attach_detach_partition")
+            .await
+            .unwrap();
+        assert_eq!(ddl.schema, "public");
+        match ddl.data {
+            DdlEventData::AttachPartition {
+                ref name,
+                ref partitions,
+            } => {
+                assert_eq!(name, "t1");
+                assert_eq!(
+                    partitions,
+                    &[DdlPartition {
+                        schema: "public".into(),
+                        name: "t1_p1".into()
+                    }]
+                );
+            }
+            data => panic!("Unexpected DDL event data: {data:?}"),
+        }
+        assert!(matches!(
+            ddl.into_action(),
+            ReplicationAction::ResnapshotTable { table, absorbed }
+                if table == Relation { schema: Some("public".into()), name: "t1".into() }
+                    && absorbed == [Relation { schema: Some("public".into()), name: "t1_p1".into() }]
+        ));
+
+        client
+            .simple_query("alter table t1 detach partition t1_p1")
+            .await
+            .unwrap();
+
+        let ddl = get_last_ddl(&client, "attach_detach_partition")
+            .await
+            .unwrap();
+        match ddl.data {
+            DdlEventData::DetachPartition { name } => assert_eq!(name, "t1"),
+            data => panic!("Unexpected DDL event data: {data:?}"),
+        }
+
+        client.teardown().await;
     }
 
     #[parallel_group(GROUP)]
@@ -62,9 +62,12 @@ BEGIN
         SET LOCAL readyset.current_command_is_replica_identity TO false;
     END IF;
 
+    -- Partitioned tables are replicated through their partition root (the
+    -- publication is created with `publish_via_partition_root`), so the root
+    -- becomes a base table and its partitions are marked as non-replicated
     SELECT
-    CASE cls.relkind
-    WHEN 'r' THEN
+    CASE
+    WHEN NOT cls.relispartition THEN
         json_build_object(
             'schema', object.schema_name,
             'data', json_build_object('CreateTable', json_build_object(
@@ -110,7 +113,7 @@ BEGIN
                 )
             ))
         )
-    WHEN 'p' THEN
+    ELSE
          json_build_object(
             'schema', object.schema_name,
             'data', json_build_object('AddNonReplicatedTable', json_build_object(
@@ -133,12 +136,26 @@ BEGIN
 END $$;
 
 
+CREATE OR REPLACE FUNCTION readyset.pre_alter_table()
+RETURNS event_trigger
+LANGUAGE plpgsql
+AS $$
+BEGIN
+  -- As with `pre_alter_type`, we don't know which table is being altered in
+  -- `ddl_command_start`, so save all partitions of all tables to let
+  -- `replicate_alter_table` tell whether a partition was attached or detached
+  DROP TABLE IF EXISTS pg_inherits_original;
+  CREATE TEMP TABLE pg_inherits_original
+  AS SELECT inhrelid, inhparent FROM pg_catalog.pg_inherits;
+END $$;
+
 CREATE OR REPLACE FUNCTION readyset.replicate_alter_table()
 RETURNS event_trigger
 LANGUAGE plpgsql
 AS $$
 DECLARE
     alter_message text;
+    partition_change text;
     query text;
 BEGIN
     IF coalesce(
@@ -151,26 +168,96 @@ BEGIN
         )::boolean,
         false
     ) THEN
+        DROP TABLE IF EXISTS pg_inherits_original;
         RETURN;
     END IF;
 
     SELECT current_query() INTO query;
 
+    -- Find out whether this command attached or detached a partition of a
+    -- partitioned table, by comparing the partitions of the altered table with
+    -- the ones `pre_alter_table` saw before the command ran
     SELECT
-    json_build_object(
-        'schema', object.schema_name,
-        'data', json_build_object(
-            'AlterTable',
-            json_build_object(
-                'name', cls.relname,
-                'statement', query
+        CASE
+        WHEN EXISTS (
+            SELECT 1 FROM pg_catalog.pg_inherits inh
+            WHERE inh.inhparent = cls.oid
+            AND NOT EXISTS (
+                SELECT 1 FROM pg_inherits_original orig
+                WHERE orig.inhparent = inh.inhparent
+                AND orig.inhrelid = inh.inhrelid
             )
-        )
-    )
-    INTO alter_message
+        ) THEN 'AttachPartition'
+        WHEN EXISTS (
+            SELECT 1 FROM pg_inherits_original orig
+            WHERE orig.inhparent = cls.oid
+            AND NOT EXISTS (
+                SELECT 1 FROM pg_catalog.pg_inherits inh
+                WHERE inh.inhparent = orig.inhparent
+                AND inh.inhrelid = orig.inhrelid
+            )
+        ) THEN 'DetachPartition'
+        END
+    INTO partition_change
     FROM pg_event_trigger_ddl_commands() object
     JOIN pg_class cls ON object.objid = cls.oid
-    WHERE object.object_type in ('table', 'table column');
+    WHERE object.command_tag = 'ALTER TABLE'
+    AND object.object_type = 'table'
+    AND cls.relkind = 'p';
+
+    DROP TABLE IF EXISTS pg_inherits_original;
+
+    -- Attaching or detaching a partition changes the rows of the partition
+    -- root without any WAL records being written for them, so replicate these
+    -- separately to let ReadySet resnapshot the root. An attached partition
+    -- may have been replicated as a table of its own until now, so list all
+    -- partitions below the root to let ReadySet drop those
+    IF partition_change IS NOT NULL THEN
+        SELECT
+        json_build_object(
+            'schema', root_ns.nspname,
+            'data', json_build_object(
+                partition_change,
+                json_build_object(
+                    'name', root.relname,
+                    'partitions', (
+                        SELECT coalesce(json_agg(json_build_object(
+                            'schema', part_ns.nspname,
+                            'name', part.relname
+                        )), '[]'::json)
+                        FROM pg_partition_tree(root.oid) tree
+                        JOIN pg_class part ON part.oid = tree.relid
+                        JOIN pg_namespace part_ns ON part.relnamespace = part_ns.oid
+                        WHERE tree.level > 0
+                    )
+                )
+            )
+        )
+        INTO alter_message
+        FROM pg_event_trigger_ddl_commands() object
+        JOIN pg_class cls ON object.objid = cls.oid
+        JOIN pg_class root ON root.oid = pg_partition_root(cls.oid)
+        JOIN pg_namespace root_ns ON root.relnamespace = root_ns.oid
+        WHERE object.command_tag = 'ALTER TABLE'
+        AND object.object_type = 'table'
+        AND cls.relkind = 'p';
+    ELSE
+        SELECT
+        json_build_object(
+            'schema', object.schema_name,
+            'data', json_build_object(
+                'AlterTable',
+                json_build_object(
+                    'name', cls.relname,
+                    'statement', query
+                )
+            )
+        )
+        INTO alter_message
+        FROM pg_event_trigger_ddl_commands() object
+        JOIN pg_class cls ON object.objid = cls.oid
+        WHERE object.object_type in ('table', 'table column');
+    END IF;
 
     IF readyset.is_pre14() THEN
         UPDATE readyset.ddl_replication_log SET "ddl" = alter_message;
@@ -339,6 +426,12 @@ CREATE EVENT TRIGGER readyset_replicate_create_table
     WHEN TAG IN ('CREATE TABLE')
     EXECUTE PROCEDURE readyset.replicate_create_table();
 
+DROP EVENT TRIGGER IF EXISTS readyset_pre_alter_table;
+CREATE EVENT TRIGGER readyset_pre_alter_table
+    ON ddl_command_start
+    WHEN TAG IN ('ALTER TABLE')
+    EXECUTE PROCEDURE readyset.pre_alter_table();
+
 DROP EVENT TRIGGER IF EXISTS readyset_replicate_alter_table;
 CREATE EVENT TRIGGER readyset_replicate_alter_table
     ON ddl_command_end
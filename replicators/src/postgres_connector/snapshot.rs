@@ -72,12 +72,17 @@ struct TableEntry {
     schema: String,
     name: String,
     oid: u32,
+    /// Whether this is a partitioned table, whose rows are stored in its partitions
+    partitioned: bool,
+    /// Whether this table is a partition of a partitioned table
+    partition: bool,
 }
 
 #[derive(Debug, Clone)]
 struct TableDescription {
     oid: u32,
     name: Relation,
+    partitioned: bool,
     columns: Vec<ColumnEntry>,
     constraints: Vec<ConstraintEntry>,
 }
@@ -228,6 +233,8 @@ impl TryFrom<pgsql::Row> for TableEntry {
             schema: row.try_get(0)?,
             oid: row.try_get(1)?,
             name: row.try_get(2)?,
+            partitioned: row.try_get::<_, i8>(3)? as u8 == b'p',
+            partition: row.try_get(4)?,
         })
     }
 }
@@ -379,6 +386,7 @@ impl TableEntry {
                 schema: Some(self.schema.clone().into()),
                 name: self.name.clone().into(),
             },
+            partitioned: self.partitioned,
             columns,
             constraints,
         })
@@ -495,20 +503,35 @@ impl TableDescription {
                 // hasn't been analyzed yet, so we `greatest` it with `1` to make
                 // sure we always have a positive integer (to avoid panics when subtracting
                 // durations)
-                "SELECT greatest(c.reltuples::bigint, 1) AS approximate_nrows
-                 FROM pg_class c JOIN pg_namespace n ON c.relnamespace = n.oid
-                 WHERE c.relname = $1 AND n.nspname = $2",
-                &[&self.name.name.as_str(), &self.schema()?.as_str()],
+                // The rows of a partitioned table are all stored in its (leaf) partitions, so
+                // for those we sum up the estimates of each partition instead.
+                "SELECT greatest(sum(greatest(c.reltuples, 0))::bigint, 1) AS approximate_nrows
+                 FROM pg_class c
+                 WHERE c.oid = $1::oid
+                 OR c.oid IN (
+                     SELECT relid FROM pg_partition_tree($1::oid::regclass) WHERE isleaf
+                 )",
+                &[&self.oid],
             )
             .await?
             .try_get::<_, i64>("approximate_nrows")?;
 
-        // The most efficient way to copy an entire table is COPY BINARY
-        let query = format!(
-            "COPY \"{}\".\"{}\" TO stdout BINARY",
-            self.schema()?,
-            self.name.name
-        );
+        // The most efficient way to copy an entire table is COPY BINARY. Partitioned tables can't
+        // be copied from directly, so for those we copy the results of a query instead, which
+        // reads from all of the partitions.
+        let query = if self.partitioned {
+            format!(
+                "COPY (SELECT * FROM \"{}\".\"{}\") TO stdout BINARY",
+                self.schema()?,
+                self.name.name
+            )
+        } else {
+            format!(
+                "COPY \"{}\".\"{}\" TO stdout BINARY",
+                self.schema()?,
+                self.name.name
+            )
+        };
         let rows = transaction.copy_out(query.as_str()).await?;
 
         let type_map: Vec<_> = self.columns.iter().map(|c| c.pg_type.clone()).collect();
@@ -681,25 +704,29 @@ impl<'a> PostgresReplicator<'a> {
         let wal_position = PostgresPosition::commit_end(replication_slot.consistent_point).into();
         self.set_snapshot(&replication_slot.snapshot_name).await?;
 
-        let table_list = self.get_table_list(TableKind::RegularTable).await?;
+        let mut table_list = self.get_table_list(TableKind::RegularTable).await?;
+        table_list.extend(self.get_table_list(TableKind::PartitionedTable).await?);
         let view_list = self.get_table_list(TableKind::View).await?;
         let custom_types = self.get_custom_types().await?;
 
+        // Changes to partitions are published as changes to their partition root, so we replicate
+        // the partition root as a single base table and mark the partitions themselves as
+        // non-replicated
+        let (partitions, table_list) = table_list
+            .into_iter()
+            .partition::<Vec<_>, _>(|tbl| tbl.partition);
+        let partition_identifiers: HashSet<_> = partitions
+            .iter()
+            .map(|te| format!("{}.{}", te.schema, te.name))
+            .collect();
+
         let (table_list, mut non_replicated) =
             table_list.into_iter().partition::<Vec<_>, _>(|tbl| {
                 self.table_filter
                     .should_be_processed(tbl.schema.as_str(), tbl.name.as_str())
             });
 
-        // We don't support partitioned tables (only the partitions themselves) so mark those as
-        // non-replicated as well
-        let partitioned_tables = self.get_table_list(TableKind::PartitionedTable).await?;
-        let partitioned_identifiers: HashSet<_> = partitioned_tables
-            .iter()
-            .map(|te| format!("{}.{}", te.schema, te.name))
-            .collect();
-
-        non_replicated.extend(partitioned_tables);
+        non_replicated.extend(partitions);
 
         // We don't filter the view list by schemas since a view could be in schema 1 (that may not
         // be replicated), but refer to only tables in schema 2 that are all replicated. If we try
@@ -724,7 +751,7 @@ impl<'a> PostgresReplicator<'a> {
                     .map(|te| {
                         let te_identifier = format!("{}.{}", te.schema, te.name);
                         let not_replicated_reason: NotReplicatedReason =
-                            if partitioned_identifiers.contains(&te_identifier) {
+                            if partition_identifiers.contains(&te_identifier) {
                                 NotReplicatedReason::Partitioned
                             } else {
                                 NotReplicatedReason::Configuration
@@ -1000,7 +1027,7 @@ impl<'a> PostgresReplicator<'a> {
         // with replication when the column count on an insert doesnt match the column count
         // of the table.
         let query = r"
-        SELECT n.nspname, c.oid, c.relname, c.relkind
+        SELECT n.nspname, c.oid, c.relname, c.relkind, c.relispartition
         FROM pg_catalog.pg_class c
         LEFT JOIN pg_catalog.pg_namespace n
         ON n.oid = c.relnamespace
@@ -1113,18 +1140,41 @@ impl<'a> PostgresReplicator<'a> {
     ) -> ReadySetResult<()> {
         let tables_needing_replica_identity = get_transaction!(self)
             .query(
-                // Find all tables that are in the table list, and don't already have a primary key
+                // Find all tables that are in the table list (or are partitions of a partitioned
+                // table in the table list, since the replica identity of the partition is used for
+                // changes published via the partition root), and don't already have a primary key
                 // or a non-default replica identity set
                 "select n.nspname as schema, c.relname as name from pg_class c
                  join pg_namespace n
                  on n.oid = c.relnamespace
                  where c.oid not in (select indrelid from pg_index where indisprimary)
                  and c.relreplident = 'd'
-                 and c.oid = any ($1::oid[])",
+                 and c.relkind = 'r'
+                 and (c.oid = any ($1::oid[]) or pg_partition_root(c.oid) = any ($1::oid[]))",
                 &[&table_list.iter().map(|t| t.oid).collect::<Vec<_>>()],
             )
             .await?;
 
+        let in_recovery: bool = get_transaction!(self)
+            .query_one("SELECT pg_is_in_recovery()", &[])
+            .await?
+            .try_get(0)?;
+        if in_recovery {
+            // A standby is read-only, so the replica identity has to be set on the primary
+            for table_row in tables_needing_replica_identity {
+                let schema: String = table_row.get("schema");
+                let name: String = table_row.get("name");
+                warn!(
+                    %schema,
+                    %name,
+                    "Table has no primary key or replica identity, and the upstream database is \
+                     a standby. Run ALTER TABLE ... REPLICA IDENTITY FULL on the primary to \
+                     replicate updates and deletes to this table"
+                );
+            }
+            return Ok(());
+        }
+
         for table_row in tables_needing_replica_identity {
             let schema: String = table_row.get("schema");
             let name: String = table_row.get("name");
@@ -1155,6 +1205,7 @@ mod tests {
                 schema: Some("public".into()),
                 name: "ar_internal_metadata".into(),
             },
+            partitioned: false,
             columns: vec![
                 ColumnEntry {
                     attnum: 0,
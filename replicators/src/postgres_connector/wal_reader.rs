@@ -38,8 +38,9 @@ pub struct WalReader {
 
 #[derive(Debug)]
 pub(crate) enum WalEvent {
-    WantsKeepaliveResponse {
+    Keepalive {
         end: Lsn,
+        wants_response: bool,
     },
     Begin {
         final_lsn: CommitLsn,
@@ -102,7 +103,7 @@ impl WalEvent {
             | Self::UpdateByKey { lsn, .. }
             | Self::Truncate { lsn, .. }
             | Self::DdlEvent { lsn, .. } => Some(*lsn),
-            Self::Begin { .. } | Self::Commit { .. } | Self::WantsKeepaliveResponse { .. } => None,
+            Self::Begin { .. } | Self::Commit { .. } | Self::Keepalive { .. } => None,
         }
     }
 }
@@ -159,8 +160,11 @@ impl WalReader {
             };
 
             let (lsn, record) = match data {
-                WalData::Keepalive { reply: 1, end, .. } => {
-                    return Ok(WalEvent::WantsKeepaliveResponse { end });
+                WalData::Keepalive { reply, end, .. } => {
+                    return Ok(WalEvent::Keepalive {
+                        end,
+                        wants_response: reply == 1,
+                    });
                 }
                 WalData::XLogData { start, data, .. } => (start, data),
                 msg => {
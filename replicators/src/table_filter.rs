@@ -242,6 +242,25 @@ impl TableFilter {
         tables.insert(table);
     }
 
+    /// Start replicating the provided table, undoing any previous call to
+    /// [`Self::deny_replication`] for it
+    pub(crate) fn allow_replication(&mut self, schema: &str, table: &str) {
+        tracing::info!(%schema, %table, "allowing replication");
+        if let Some(tables) = self.replication_denied.get_mut(schema) {
+            tables.remove(table);
+        }
+
+        // If we're replicating all tables not explicitly denied, removing the table from the
+        // denied list is enough
+        if !self.explicitly_replicated.is_empty() {
+            let tables = self
+                .explicitly_replicated
+                .entry(schema.into())
+                .or_insert_with(ReplicateTableSpec::empty);
+            tables.insert(table);
+        }
+    }
+
     /// Check if a given table should be processed
     pub(crate) fn should_be_processed<Q1, Q2>(&self, schema: &Q1, table: &Q2) -> bool
     where
@@ -402,6 +421,44 @@ mod tests {
         assert!(!filter.should_be_processed("readyset", "t4"));
     }
 
+    #[test]
+    fn denied_then_allowed() {
+        let mut filter = TableFilter::try_new(
+            nom_sql::Dialect::MySQL,
+            Some("t1, readyset.t4".to_string().into()),
+            None,
+            Some("noria"),
+        )
+        .unwrap();
+        assert!(!filter.should_be_processed("noria", "t2"));
+        filter.allow_replication("noria", "t2");
+        assert!(filter.should_be_processed("noria", "t2"));
+
+        filter.deny_replication("readyset", "t4");
+        assert!(!filter.should_be_processed("readyset", "t4"));
+        filter.allow_replication("readyset", "t4");
+        assert!(filter.should_be_processed("readyset", "t4"));
+    }
+
+    #[test]
+    fn ignored_then_allowed() {
+        let mut filter = TableFilter::try_new(
+            nom_sql::Dialect::MySQL,
+            None,
+            Some("t1, readyset.*".to_string().into()),
+            Some("noria"),
+        )
+        .unwrap();
+        assert!(!filter.should_be_processed("noria", "t1"));
+        filter.allow_replication("noria", "t1");
+        assert!(filter.should_be_processed("noria", "t1"));
+
+        assert!(!filter.should_be_processed("readyset", "t4"));
+        filter.allow_replication("readyset", "t4");
+        assert!(filter.should_be_processed("readyset", "t4"));
+        assert!(!filter.should_be_processed("readyset", "t5"));
+    }
+
     #[test]
     fn regular_list_ignore() {
         let filter = TableFilter::try_new(
@@ -165,6 +165,7 @@ struct TestHandle {
     // connection spawns a background task we can only terminate by dropping the runtime
     replication_rt: Option<tokio::runtime::Runtime>,
     notification_channel: Option<TestChannel>,
+    controller_channel: Option<TestControllChannel>,
 }
 
 impl Drop for TestHandle {
@@ -333,6 +334,7 @@ impl TestHandle {
             authority,
             replication_rt: None,
             notification_channel: None,
+            controller_channel: None,
         };
 
         handle.start_repl(config, telemetry_sender, true).await?;
@@ -365,8 +367,9 @@ impl TestHandle {
 
         let url = self.url.clone().into();
         let (sender, receiver) = TestChannel::new();
-        let (controll_receiver, _controll_sender) = TestControllChannel::new();
+        let (controll_receiver, controll_sender) = TestControllChannel::new();
         self.notification_channel = Some(receiver);
+        self.controller_channel = Some(controll_sender);
         runtime.spawn(async move {
             if let Err(error) = NoriaAdapter::start(
                 controller,
@@ -448,6 +451,8 @@ impl TestHandle {
                                 .unwrap(),
                         ),
                         always: false,
+                        replicas: None,
+                        shards: None,
                     }),
                 ],
                 self.dialect,
@@ -488,6 +493,33 @@ impl TestHandle {
             .await
             .unwrap_err();
     }
+
+    /// Ask the replicator to add the given tables to (if `add` is true) or drop them from
+    /// replication, the way the controller does for `ALTER READYSET`, and wait for its reply
+    async fn change_replicated_tables(
+        &mut self,
+        tables: &[(&str, &str)],
+        add: bool,
+    ) -> ReadySetResult<()> {
+        let tables = tables
+            .iter()
+            .map(|(schema, name)| Relation {
+                schema: Some((*schema).into()),
+                name: (*name).into(),
+            })
+            .collect();
+        let (done, reply) = tokio::sync::oneshot::channel();
+        let message = if add {
+            ControllerMessage::AddTables { tables, done }
+        } else {
+            ControllerMessage::DropTables { tables, done }
+        };
+        let channel = &self.controller_channel.as_ref().unwrap().0;
+        if channel.send(message).is_err() {
+            internal!("Replicator is not running");
+        }
+        reply.await.unwrap()
+    }
 }
 
 /// Tests that we can have multiple ReadySet instances connected to the same postgres upstream
@@ -603,6 +635,14 @@ fn mysql_url() -> String {
     )
 }
 
+fn mariadb_url() -> String {
+    format!(
+        "mysql://root:noria@{}:{}/public",
+        env::var("MARIADB_HOST").unwrap_or_else(|_| "127.0.0.1".into()),
+        env::var("MARIADB_TCP_PORT").unwrap_or_else(|_| "3307".into()),
+    )
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[serial_test::serial]
 #[slow]
@@ -715,6 +755,24 @@ async fn mysql_replication_filter() {
     replication_filter_inner(&mysql_url()).await.unwrap();
 }
 
+#[tokio::test(flavor = "multi_thread")]
+#[serial_test::serial]
+#[slow]
+async fn pgsql_replicated_tables_at_runtime() {
+    replicated_tables_at_runtime_inner(&pgsql_url())
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial_test::serial]
+#[slow]
+async fn mysql_replicated_tables_at_runtime() {
+    replicated_tables_at_runtime_inner(&mysql_url())
+        .await
+        .unwrap();
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[serial_test::serial]
 #[slow]
@@ -1308,6 +1366,95 @@ async fn replication_filter_inner(url: &str) -> ReadySetResult<()> {
     Ok(())
 }
 
+async fn replicated_tables_at_runtime_inner(url: &str) -> ReadySetResult<()> {
+    readyset_tracing::init_test_logging();
+    let mut client = DbConnection::connect(url).await?;
+    client
+        .query(
+            "
+            DROP TABLE IF EXISTS t1 CASCADE; CREATE TABLE t1 (id int);
+            DROP TABLE IF EXISTS t2 CASCADE; CREATE TABLE t2 (id int);
+            INSERT INTO t1 VALUES (1),(2),(3);
+            INSERT INTO t2 VALUES (1),(2),(3);
+            ",
+        )
+        .await?;
+
+    let config = Config {
+        replication_tables: Some("public.t1".to_string().into()),
+        ..Default::default()
+    };
+    let (mut ctx, shutdown_tx) =
+        TestHandle::start_noria(url.to_string(), Some(config.clone())).await?;
+    ctx.notification_channel
+        .as_mut()
+        .unwrap()
+        .snapshot_completed()
+        .await
+        .unwrap();
+    ctx.assert_table_exists("public", "t1").await;
+    ctx.assert_table_missing("public", "t2").await;
+
+    // Tables that don't exist upstream can't be added
+    let err = ctx
+        .change_replicated_tables(&[("public", "t3")], true)
+        .await
+        .unwrap_err();
+    assert!(err.is_table_not_found(), "{err}");
+
+    ctx.change_replicated_tables(&[("public", "t2")], true)
+        .await?;
+    eventually! {
+        ctx.noria
+            .table(Relation {
+                schema: Some("public".into()),
+                name: "t2".into(),
+            })
+            .await
+            .is_ok()
+    }
+    ctx.noria
+        .extend_recipe(
+            ChangeList::from_str(
+                "CREATE VIEW public.t2_view AS SELECT * FROM public.t2;",
+                Dialect::DEFAULT_MYSQL,
+            )
+            .unwrap(),
+        )
+        .await
+        .unwrap();
+    ctx.check_results(
+        "t2_view",
+        "replicated_tables_at_runtime",
+        &[&[DfValue::Int(1)], &[DfValue::Int(2)], &[DfValue::Int(3)]],
+    )
+    .await?;
+
+    // Dropped tables are gone as soon as the replicator replies
+    ctx.change_replicated_tables(&[("public", "t1")], false)
+        .await?;
+    ctx.assert_table_missing("public", "t1").await;
+
+    // Both changes outlive the replicator, even though it's configured to only replicate t1
+    ctx.stop_repl().await;
+    ctx.start_repl(Some(config), TelemetrySender::new_no_op(), true)
+        .await?;
+    ctx.notification_channel
+        .as_mut()
+        .unwrap()
+        .snapshot_completed()
+        .await
+        .unwrap();
+    ctx.assert_table_missing("public", "t1").await;
+    ctx.assert_table_exists("public", "t2").await;
+
+    ctx.stop().await;
+    client.stop().await;
+    shutdown_tx.shutdown().await;
+
+    Ok(())
+}
+
 async fn replication_all_schemas_inner(url: &str) -> ReadySetResult<()> {
     readyset_tracing::init_test_logging();
     let mut client = DbConnection::connect(url).await?;
@@ -1614,6 +1761,109 @@ async fn mysql_enum_replication() {
     shutdown_tx.shutdown().await;
 }
 
+#[tokio::test(flavor = "multi_thread")]
+#[serial_test::serial]
+#[slow]
+async fn mariadb_uuid_replication() {
+    readyset_tracing::init_test_logging();
+    let url = &mariadb_url();
+    let mut client = DbConnection::connect(url).await.unwrap();
+    client
+        .query(
+            "
+            DROP TABLE IF EXISTS `uuid_test` CASCADE;
+            DROP VIEW IF EXISTS uuid_test_view;
+            CREATE TABLE `uuid_test` (
+                id int NOT NULL PRIMARY KEY,
+                u uuid
+            );
+            CREATE VIEW uuid_test_view AS SELECT * FROM `uuid_test` ORDER BY id ASC;
+            INSERT INTO uuid_test VALUES (0, '6ccd780c-baba-1026-9564-5b8c656024db')",
+        )
+        .await
+        .unwrap();
+
+    let (mut ctx, shutdown_tx) = TestHandle::start_noria(url.to_string(), None)
+        .await
+        .unwrap();
+    ctx.notification_channel
+        .as_mut()
+        .unwrap()
+        .snapshot_completed()
+        .await
+        .unwrap();
+
+    ctx.check_results(
+        "uuid_test_view",
+        "Snapshot",
+        &[&[
+            DfValue::Int(0),
+            DfValue::from("6ccd780c-baba-1026-9564-5b8c656024db"),
+        ]],
+    )
+    .await
+    .unwrap();
+
+    // Rows written after the snapshot come from the binlog, in MariaDB's binary `UUID` format
+    client
+        .query(
+            "
+            INSERT INTO uuid_test VALUES
+                (1, '018fd8f0-6d2e-7c3a-9b1e-4f2a11223344'),
+                (2, NULL);
+            UPDATE uuid_test SET u = '6ccd780c-baba-1026-9564-5b8c656024dc' WHERE id = 0",
+        )
+        .await
+        .unwrap();
+
+    ctx.check_results(
+        "uuid_test_view",
+        "Replication",
+        &[
+            &[
+                DfValue::Int(0),
+                DfValue::from("6ccd780c-baba-1026-9564-5b8c656024dc"),
+            ],
+            &[
+                DfValue::Int(1),
+                DfValue::from("018fd8f0-6d2e-7c3a-9b1e-4f2a11223344"),
+            ],
+            &[DfValue::Int(2), DfValue::None],
+        ],
+    )
+    .await
+    .unwrap();
+
+    // Tables created after the snapshot have their `UUID` columns replicated as text, too
+    client
+        .query(
+            "
+            DROP TABLE IF EXISTS `uuid_test2` CASCADE;
+            CREATE TABLE `uuid_test2` (
+                id int NOT NULL PRIMARY KEY,
+                u uuid
+            );
+            INSERT INTO uuid_test2 VALUES (0, '6ccd780c-baba-1026-9564-5b8c656024db')",
+        )
+        .await
+        .unwrap();
+
+    ctx.check_results(
+        "uuid_test2",
+        "Replicated table",
+        &[&[
+            DfValue::Int(0),
+            DfValue::from("6ccd780c-baba-1026-9564-5b8c656024db"),
+        ]],
+    )
+    .await
+    .unwrap();
+
+    client.stop().await;
+    ctx.stop().await;
+    shutdown_tx.shutdown().await;
+}
+
 #[tokio::test(flavor = "multi_thread")]
 #[serial_test::serial]
 //#[slow]
@@ -1822,6 +2072,8 @@ async fn postgresql_ddl_replicate_drop_view_internal(url: &str) {
             .unwrap(),
         ),
         always: false,
+        replicas: None,
+        shards: None,
     });
     ctx.noria
         .extend_recipe(ChangeList::from_change(
@@ -1903,6 +2155,8 @@ async fn postgresql_ddl_replicate_create_view_internal(url: &str) {
                     .unwrap()
                 ),
                 always: true,
+                replicas: None,
+                shards: None,
             }),
             Dialect::DEFAULT_POSTGRESQL
         ))
@@ -2842,7 +3096,7 @@ async fn pgsql_delete_from_table_without_pk() {
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn pgsql_dont_replicate_partitioned_table() {
+async fn pgsql_replicate_partitioned_table_via_root() {
     readyset_tracing::init_test_logging();
     let url = pgsql_url();
     let mut client = DbConnection::connect(&url).await.unwrap();
@@ -2852,6 +3106,8 @@ async fn pgsql_dont_replicate_partitioned_table() {
             "DROP TABLE IF EXISTS t CASCADE;
              DROP TABLE IF EXISTS t_true CASCADE;
              DROP TABLE IF EXISTS t_false CASCADE;
+             DROP TABLE IF EXISTS t2 CASCADE;
+             DROP TABLE IF EXISTS t2_p1 CASCADE;
 
              CREATE TABLE t (key bool not null, val int) PARTITION BY LIST (key);
              CREATE TABLE t_true PARTITION OF t FOR VALUES IN (true);
@@ -2876,62 +3132,107 @@ async fn pgsql_dont_replicate_partitioned_table() {
         .await
         .unwrap();
 
-    ctx.noria.table("t").await.unwrap_err();
-    let relation = Relation {
-        schema: Some("public".into()),
-        name: "t".into(),
-    };
+    ctx.assert_table_exists("public", "t").await;
 
-    assert!(ctx
-        .noria
-        .non_replicated_relations()
-        .await
-        .unwrap()
-        .contains(&NonReplicatedRelation::new(relation.clone())));
+    let non_replicated = ctx.noria.non_replicated_relations().await.unwrap();
+    for partition in ["t_true", "t_false"] {
+        assert!(
+            non_replicated.contains(&NonReplicatedRelation::new(Relation {
+                schema: Some("public".into()),
+                name: partition.into(),
+            }))
+        );
+    }
 
-    ctx.noria
-        .table(Relation {
-            schema: Some("public".into()),
-            name: "t_true".into(),
-        })
+    ctx.check_results(
+        "t",
+        "pgsql_replicate_partitioned_table_via_root",
+        &[
+            &[DfValue::from(false), DfValue::from(10)],
+            &[DfValue::from(false), DfValue::from(20)],
+            &[DfValue::from(true), DfValue::from(1)],
+            &[DfValue::from(true), DfValue::from(2)],
+        ],
+    )
+    .await
+    .unwrap();
+
+    // Writes to the partitions are replicated as writes to the partition root
+    client
+        .query("INSERT INTO t_true (key, val) VALUES (true, 3)")
         .await
         .unwrap();
-    ctx.noria
-        .table(Relation {
-            schema: Some("public".into()),
-            name: "t_false".into(),
-        })
+    client.query("DELETE FROM t WHERE val = 10").await.unwrap();
+
+    ctx.check_results(
+        "t",
+        "pgsql_replicate_partitioned_table_via_root",
+        &[
+            &[DfValue::from(false), DfValue::from(20)],
+            &[DfValue::from(true), DfValue::from(1)],
+            &[DfValue::from(true), DfValue::from(2)],
+            &[DfValue::from(true), DfValue::from(3)],
+        ],
+    )
+    .await
+    .unwrap();
+
+    // Detaching a partition removes its rows from the partition root, and the detached partition
+    // becomes a table of its own
+    client
+        .query("ALTER TABLE t DETACH PARTITION t_false")
         .await
         .unwrap();
 
     ctx.check_results(
-        "t_true",
-        "pgsql_dont_replicate_partitioned_table",
+        "t",
+        "pgsql_replicate_partitioned_table_via_root",
         &[
             &[DfValue::from(true), DfValue::from(1)],
             &[DfValue::from(true), DfValue::from(2)],
+            &[DfValue::from(true), DfValue::from(3)],
         ],
     )
     .await
     .unwrap();
     ctx.check_results(
         "t_false",
-        "pgsql_dont_replicate_partitioned_table",
+        "pgsql_replicate_partitioned_table_via_root",
+        &[&[DfValue::from(false), DfValue::from(20)]],
+    )
+    .await
+    .unwrap();
+
+    // Attaching it again adds its rows back to the partition root
+    client
+        .query("ALTER TABLE t ATTACH PARTITION t_false FOR VALUES IN (false)")
+        .await
+        .unwrap();
+
+    ctx.check_results(
+        "t",
+        "pgsql_replicate_partitioned_table_via_root",
         &[
-            &[DfValue::from(false), DfValue::from(10)],
             &[DfValue::from(false), DfValue::from(20)],
+            &[DfValue::from(true), DfValue::from(1)],
+            &[DfValue::from(true), DfValue::from(2)],
+            &[DfValue::from(true), DfValue::from(3)],
         ],
     )
     .await
     .unwrap();
 
+    // Partitioned tables created after snapshotting are replicated through their root as well
     client
-        .query("CREATE TABLE t2 (key int, val int) PARTITION BY RANGE (key)")
+        .query(
+            "CREATE TABLE t2 (key int, val int) PARTITION BY RANGE (key);
+             CREATE TABLE t2_p1 PARTITION OF t2 FOR VALUES FROM (0) TO (10);",
+        )
         .await
         .unwrap();
     let relation = Relation {
         schema: Some("public".into()),
-        name: "t2".into(),
+        name: "t2_p1".into(),
     };
     eventually! {
         ctx
@@ -2942,5 +3243,117 @@ async fn pgsql_dont_replicate_partitioned_table() {
             .contains(&NonReplicatedRelation::new(relation.clone()))
     }
 
+    client
+        .query("INSERT INTO t2 (key, val) VALUES (1, 1)")
+        .await
+        .unwrap();
+    ctx.check_results(
+        "t2",
+        "pgsql_replicate_partitioned_table_via_root",
+        &[&[DfValue::from(1), DfValue::from(1)]],
+    )
+    .await
+    .unwrap();
+
+    shutdown_tx.shutdown().await;
+}
+
+/// A Debezium change event for a row of `public.t (id int NOT NULL, name text)`
+fn cdc_event(op: &str, id: i32, name: &str) -> String {
+    let row_schema = |field: &str| {
+        serde_json::json!({
+            "type": "struct",
+            "optional": true,
+            "field": field,
+            "fields": [
+                { "type": "int32", "optional": false, "field": "id" },
+                { "type": "string", "optional": true, "field": "name" }
+            ]
+        })
+    };
+    let row = serde_json::json!({ "id": id, "name": name });
+    let (before, after) = match op {
+        "d" => (row, serde_json::Value::Null),
+        _ => (serde_json::Value::Null, row),
+    };
+    let mut line = serde_json::json!({
+        "schema": { "type": "struct", "fields": [row_schema("before"), row_schema("after")] },
+        "payload": {
+            "before": before,
+            "after": after,
+            "source": { "connector": "postgresql", "db": "app", "schema": "public", "table": "t" },
+            "op": op
+        }
+    })
+    .to_string();
+    line.push('\n');
+    line
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial_test::serial]
+async fn cdc_replication() {
+    use std::io::Write;
+
+    readyset_tracing::init_test_logging();
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(file, "{}{}", cdc_event("r", 1, "a"), cdc_event("r", 2, "b")).unwrap();
+    let path = file.path().to_string_lossy().into_owned();
+    let config = || Config {
+        cdc_source: Some(path.clone()),
+        ..Default::default()
+    };
+
+    // The URL is only used to pick the dialect; nothing connects to it
+    let (mut ctx, shutdown_tx) = TestHandle::start_noria(pgsql_url(), Some(config()))
+        .await
+        .unwrap();
+    ctx.notification_channel
+        .as_mut()
+        .unwrap()
+        .snapshot_completed()
+        .await
+        .unwrap();
+
+    ctx.check_results(
+        "t",
+        "cdc_replication",
+        &[
+            &[DfValue::from(1), DfValue::from("a")],
+            &[DfValue::from(2), DfValue::from("b")],
+        ],
+    )
+    .await
+    .unwrap();
+
+    // Events written to the file after we reached its end are picked up
+    write!(file, "{}", cdc_event("d", 1, "a")).unwrap();
+    ctx.check_results(
+        "t",
+        "cdc_replication",
+        &[&[DfValue::from(2), DfValue::from("b")]],
+    )
+    .await
+    .unwrap();
+
+    // Once restarted, the replicator resumes from where it left off, without applying any event
+    // twice
+    ctx.stop_repl().await;
+    write!(file, "{}", cdc_event("c", 3, "c")).unwrap();
+    ctx.start_repl(Some(config()), TelemetrySender::new_no_op(), false)
+        .await
+        .unwrap();
+    ctx.check_results(
+        "t",
+        "cdc_replication",
+        &[
+            &[DfValue::from(2), DfValue::from("b")],
+            &[DfValue::from(3), DfValue::from("c")],
+        ],
+    )
+    .await
+    .unwrap();
+
+    ctx.stop().await;
     shutdown_tx.shutdown().await;
 }
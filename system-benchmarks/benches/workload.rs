@@ -644,6 +644,7 @@ fn start_adapter(upstream_url: &str) -> anyhow::Result<()> {
                 ),
                 connection_handler: MySqlHandler {
                     enable_statement_logging: false,
+                    compression_threshold: None,
                 },
                 database_type: DatabaseType::MySQL,
                 parse_dialect: nom_sql::Dialect::MySQL,